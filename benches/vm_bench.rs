@@ -0,0 +1,120 @@
+//! バイトコードの VM と、SIR を直接評価するインタプリタの実行速度の比較。
+//!
+//! `cargo +nightly bench --bench vm_bench` で実行する。
+
+#![feature(test)]
+
+extern crate test;
+
+use std::collections::HashMap;
+
+use stelaro::stelaro_common::LocalDefId;
+use stelaro::stelaro_context::TyCtxt;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_session::{Input, Options};
+use stelaro::stelaro_sir_eval::SirEvaluator;
+use stelaro::stelaro_sir_typecheck::result::TypeckResults;
+use stelaro::stelaro_vm::bytecode::Program;
+use stelaro::stelaro_vm::{Vm, compile_program};
+use test::Bencher;
+
+/// 関数呼び出しが支配的なプログラム。
+const RECURSIVE: &str = r#"
+fn fib(n: i64): i64 {
+    if n < 2 {
+        return n;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+fn main(): i64 {
+    fib(20)
+}
+"#;
+
+/// ループと算術演算が支配的なプログラム。
+const LOOPING: &str = r#"
+fn main(): i64 {
+    let total: i64 = 0;
+    let i: i64 = 0;
+    while i < 300 {
+        let j: i64 = 0;
+        while j < 300 {
+            if (i * j) % 7 == 3 {
+                total = total + i - j;
+            } else {
+                total = total + 1;
+            }
+            j = j + 1;
+        }
+        i = i + 1;
+    }
+    total
+}
+"#;
+
+/// ソースコードを型検査とバイトコードへの変換まで行い、`f` に渡す。
+fn with_program(
+    source_code: &str,
+    f: impl for<'tcx> FnOnce(TyCtxt<'tcx>, &HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>, &Program),
+) {
+    let config = Config {
+        opts: Options::default(),
+        input: Input::Str {
+            name: "vm_bench".to_string(),
+            input: source_code.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    };
+
+    stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let mut bodies = passes::analysis(tcx, &typeck_results);
+            passes::optimize(tcx, &mut bodies);
+
+            let program = compile_program(tcx, &bodies);
+            f(tcx, &typeck_results, &program);
+        })
+    })
+}
+
+fn bench_vm(b: &mut Bencher, source_code: &str) {
+    with_program(source_code, |_, _, program| {
+        let mut vm = Vm::new(program);
+        b.iter(|| vm.run_main().unwrap().unwrap());
+    });
+}
+
+fn bench_sir_eval(b: &mut Bencher, source_code: &str) {
+    with_program(source_code, |tcx, typeck_results, _| {
+        let mut evaluator = SirEvaluator::new(tcx, typeck_results);
+        b.iter(|| evaluator.run_main().unwrap().unwrap());
+    });
+}
+
+#[bench]
+fn recursive_vm(b: &mut Bencher) {
+    bench_vm(b, RECURSIVE);
+}
+
+#[bench]
+fn recursive_sir_eval(b: &mut Bencher) {
+    bench_sir_eval(b, RECURSIVE);
+}
+
+#[bench]
+fn looping_vm(b: &mut Bencher) {
+    bench_vm(b, LOOPING);
+}
+
+#[bench]
+fn looping_sir_eval(b: &mut Bencher) {
+    bench_sir_eval(b, LOOPING);
+}
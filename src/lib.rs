@@ -1,41 +1,63 @@
-#![feature(array_windows)]
 #![feature(associated_type_defaults)]
 #![feature(box_patterns)]
-#![feature(debug_closure_helpers)]
 #![feature(never_type)]
 #![feature(min_specialization)]
 
 pub mod stelaro_ast;
 pub mod stelaro_ast_lowering;
+pub mod stelaro_borrowck;
 pub mod stelaro_codegen;
 pub mod stelaro_common;
 pub mod stelaro_context;
 pub mod stelaro_diagnostics;
+pub mod stelaro_error_codes;
+pub mod stelaro_incremental;
 pub mod stelaro_interface;
 pub mod stelaro_lexer;
+pub mod stelaro_lint;
+pub mod stelaro_metadata;
+pub mod stelaro_mir;
+pub mod stelaro_mir_build;
+pub mod stelaro_mir_transform;
 pub mod stelaro_parse;
 pub mod stelaro_resolve;
 pub mod stelaro_session;
 pub mod stelaro_sir;
+pub mod stelaro_sir_eval;
 pub mod stelaro_sir_typecheck;
+pub mod stelaro_std;
 pub mod stelaro_ty;
+pub mod stelaro_vm;
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use std::fs::OpenOptions;
 use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::process;
+use std::rc::Rc;
 
-use crate::stelaro_ast_lowering::lower_to_sir;
-use crate::stelaro_interface::passes::create_and_enter_global_ctxt;
+use clap::ValueEnum;
+
+use crate::stelaro_codegen::messages;
+use crate::stelaro_codegen::x86_64::{LinkError, link_executable};
+use crate::stelaro_common::{DUMMY_SPAN, FatalErrorMarker, SourceMap};
+use crate::stelaro_diagnostics::{DiagArgs, DiagCtxt, Lang};
+use crate::stelaro_interface::emit::emit_artifacts;
 use crate::stelaro_interface::{interface, passes};
-use crate::stelaro_resolve::{Resolver, ResolverArenas};
-use crate::stelaro_session::{Input, config};
+use crate::stelaro_session::config::{EmitKind, SteloType};
+use crate::stelaro_session::session::default_emitter;
+use crate::stelaro_session::{Input, Session, config};
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub(crate) struct Args {
-    input_file: PathBuf,
+    #[arg(required_unless_present = "explain")]
+    input_file: Option<PathBuf>,
+
+    /// エラーコード (e.g. `E0200`) の詳細な説明を表示する
+    #[arg(long, value_name = "CODE")]
+    explain: Option<String>,
 
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -45,13 +67,68 @@ pub(crate) struct Args {
 
     #[arg(long)]
     stelo_name: Option<String>,
+
+    /// 診断の出力形式
+    #[arg(long, value_enum, default_value_t)]
+    error_format: config::ErrorOutputType,
+
+    /// 診断メッセージの言語 (省略した場合は環境変数 `LANG` から決定する)
+    #[arg(long, value_enum)]
+    lang: Option<Lang>,
+
+    /// 診断の修正案のうち、自動的に適用できるものを入力ファイルに適用する
+    #[arg(long)]
+    fix: bool,
+
+    /// 指定したリントを報告しない
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+
+    /// 指定したリントを警告として報告する
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    warn: Vec<String>,
+
+    /// 指定したリントをエラーとして報告する (`-D warnings` で全ての警告をエラーにする)
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+
+    /// 出力する中間表現の種類 (`,` で区切って複数指定できる)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "sir")]
+    emit: Vec<config::EmitKind>,
+
+    /// 生成するステロの種類 (`lib` の場合はメタデータも出力する)
+    #[arg(long, value_enum, default_value_t)]
+    stelo_type: config::SteloType,
+
+    /// 読み込むステロの名前と、そのメタデータのパス (e.g. `--extern mathlib=mathlib.stmeta`)
+    #[arg(long = "extern", value_name = "NAME=PATH", value_parser = config::ExternEntry::parse)]
+    externs: Vec<config::ExternEntry>,
+
+    /// リリースビルドとしてコンパイルする (オーバーフローの検査を既定で無効にする)
+    #[arg(long)]
+    release: bool,
+
+    /// コード生成のオプション (e.g. `-C overflow-checks=no`, `-C incremental`)
+    #[arg(short = 'C', value_name = "OPT[=VALUE]", value_parser = config::CodegenFlag::parse)]
+    codegen: Vec<config::CodegenFlag>,
+
+    /// 不安定なオプション (e.g. `-Z statistics`)
+    #[arg(short = 'Z', value_enum, value_name = "FLAG")]
+    unstable: Vec<config::UnstableFlag>,
 }
 
 pub fn run() {
-    let args = Args::parse();
-    let opts = config::build_session_options(&args);
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if let Some(code) = &args.explain {
+        explain(code, config::select_lang(args.lang), args.error_format);
+        return;
+    }
 
-    let input = Input::File(args.input_file);
+    let opts = config::build_session_options(&args, &matches);
+
+    let input = Input::File(args.input_file.unwrap());
     let odir = args.output_dir;
     let ofile = args.output;
 
@@ -61,79 +138,191 @@ pub fn run() {
         output_dir: odir,
         output_file: ofile,
         file_loader: None,
+        emitter: None,
     };
 
-    stelaro_interface::run_compiler(config, |sess| {
-        let stelo = passes::parse(sess);
-
-        create_and_enter_global_ctxt(sess, |tcx| {
-            let arenas = &ResolverArenas::default();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        stelaro_interface::run_compiler(config, |sess| {
+            let emit = &sess.opts.emit;
+            // メタデータは `-o` で指定された出力の隣に書き出すため、`-o` の対象には数えない
+            let outputs = emit.iter().filter(|kind| **kind != EmitKind::Metadata).collect::<Vec<_>>();
+            if sess.paths.output_file.is_some() && outputs.len() > 1 {
+                let kinds = outputs.iter().map(|kind| emit_kind_name(**kind)).collect::<Vec<_>>();
+                let msg = sess.dcx().translate(
+                    stelaro_interface::messages::OUTPUT_FILE_WITH_MULTIPLE_EMIT,
+                    &[("kinds", &kinds.join(","))],
+                );
+                sess.dcx().emit_fatal(msg);
+            }
+            if sess.opts.stelo_type == SteloType::Lib && emit.contains(&EmitKind::Exe) {
+                let msg = sess.dcx().translate(stelaro_interface::messages::EMIT_EXE_FOR_LIB, &[]);
+                sess.dcx().emit_fatal(msg);
+            }
 
-            let mut resolver = Resolver::new(
-                tcx,
-                stelo.span.inner_span,
-                arenas,
+            emit_artifacts(
+                sess,
+                |kind, output| match kind {
+                    EmitKind::Exe => write_executable(sess, &output),
+                    _ => write_output(sess, kind, output.as_bytes()),
+                },
+                |metadata| write_output(sess, EmitKind::Metadata, &metadata),
             );
+        })
+    }));
 
-            resolver.resolve_stelo(&stelo);
-
-            let resolver = resolver.into_outputs().ast_lowering;
-            let sir_stelo = lower_to_sir(tcx, resolver, stelo);
-            let stelo = tcx.sir_arena.alloc(sir_stelo);
-            tcx.sir_stelo.replace(Some(stelo));
-
-            sess.dcx().abort_if_errors();
-
-            let output = format!("{:#?}", &tcx.sir_stelo.borrow().unwrap());
-
-            if let Some(file) = &sess.paths.output_file {
-                match OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(
-                        file
-                    )
-                {
-                    Ok(f) => {
-                        let mut writer = BufWriter::new(f);
-                        if let Err(e) = writer.write_all(output.as_bytes()) {
-                            eprintln!(
-                                "Error: ファイルに書き込めませんでした '{}': {}",
-                                file.display(),
-                                e
-                            );
-                            process::exit(1);
-                        }
-                    },
-                    Err(e) => {
-                        match e.kind() {
-                            io::ErrorKind::PermissionDenied => {
-                                eprintln!(
-                                    "Error: 書き込み権限がありません。ファイル '{}' を開けませんでした。",
-                                    file.display()
-                                );
-                            }
-                            io::ErrorKind::NotFound => {
-                                eprintln!(
-                                    "Error: ファイル '{}' の親ディレクトリが存在しません。",
-                                    file.display()
-                                );
-                            }
-                            _ => {
-                                eprintln!(
-                                    "Error: 書き込み用にファイル '{}' を開けませんでした: {}",
-                                    file.display(),
-                                    e
-                                );
-                            }
-                        }
-                        process::exit(1);
-                    }
+    // エラーによる中断は診断として報告済みであるため、終了コードのみを設定する
+    if let Err(payload) = result {
+        if payload.is::<FatalErrorMarker>() {
+            process::exit(1);
+        }
+        panic::resume_unwind(payload);
+    }
+}
+
+/// `--emit` で指定する名前。
+fn emit_kind_name(kind: EmitKind) -> String {
+    kind.to_possible_value().unwrap().get_name().to_string()
+}
+
+/// `-o` が指定されなかった場合の、出力ディレクトリの `ステロ名.拡張子` のパス。
+fn output_path(sess: &Session, kind: EmitKind) -> PathBuf {
+    let dir = sess.paths.output_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    let stelo_name = passes::get_stelo_name(sess);
+
+    match kind.extension() {
+        Some(ext) => dir.join(format!("{stelo_name}.{ext}")),
+        None => dir.join(stelo_name.as_str()),
+    }
+}
+
+/// アセンブリをリンクし、`-o` で指定されたファイル、
+/// または出力ディレクトリのステロ名のファイルに実行ファイルを書き込む。
+///
+/// `--extern` で読み込んだステロは、メタデータと同じディレクトリにある
+/// 同じ名前のアセンブリ (`.s`) をリンクする。
+fn write_executable(sess: &Session, asm: &str) {
+    let dcx = sess.dcx();
+    let path = sess
+        .paths
+        .output_file
+        .clone()
+        .unwrap_or_else(|| output_path(sess, EmitKind::Exe));
+
+    let mut externs = Vec::new();
+    for entry in &sess.opts.externs {
+        let asm_path = entry.path.with_extension(EmitKind::Asm.extension().unwrap());
+        if !asm_path.is_file() {
+            let args: DiagArgs<'_> = &[("name", &entry.name), ("path", &asm_path.display())];
+            let mut diag = dcx.struct_fatal(DUMMY_SPAN);
+            diag.set_message(dcx.translate(messages::LINK_EXTERN_ASM_NOT_FOUND, args));
+            diag.set_help(dcx.translate(messages::LINK_EXTERN_ASM_NOT_FOUND_HELP, &[]));
+            diag.emit();
+        }
+        externs.push(asm_path);
+    }
+
+    if let Err(e) = link_executable(asm, &externs, &path) {
+        let msg = match e {
+            LinkError::Io(error) => {
+                dcx.translate(messages::LINK_IO_ERROR, &[("error", &error)])
+            }
+            LinkError::ToolNotFound { tool } => {
+                dcx.translate(messages::LINK_TOOL_NOT_FOUND, &[("tool", &tool)])
+            }
+            LinkError::ToolFailed { tool, stderr } => dcx.translate(
+                messages::LINK_TOOL_FAILED,
+                &[("tool", &tool), ("stderr", &stderr.trim_end())],
+            ),
+        };
+        dcx.emit_fatal(msg);
+    }
+}
+
+/// `kind` の出力を `-o` で指定されたファイル、または出力ディレクトリの
+/// `ステロ名.拡張子` のファイルに書き込む。`-o -` の場合は標準出力に書き込む。
+///
+/// 他の出力とともに要求されたメタデータは、`-o` で指定されたファイルの拡張子を変えたパスに書き込む。
+fn write_output(sess: &Session, kind: EmitKind, output: &[u8]) {
+    let alongside = kind == EmitKind::Metadata && sess.opts.emit.len() > 1;
+    let file = match &sess.paths.output_file {
+        Some(file) if alongside && file.as_os_str() != "-" => {
+            file.with_extension(kind.extension().unwrap())
+        }
+        Some(_) if alongside => output_path(sess, kind),
+        Some(file) if file.as_os_str() == "-" => {
+            let _ = io::stdout().write_all(output);
+            return;
+        }
+        Some(file) => file.clone(),
+        None => output_path(sess, kind),
+    };
+    let file = &file;
+
+    match OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(
+            file
+        )
+    {
+        Ok(f) => {
+            let mut writer = BufWriter::new(f);
+            if let Err(e) = writer.write_all(output) {
+                eprintln!(
+                    "Error: ファイルに書き込めませんでした '{}': {}",
+                    file.display(),
+                    e
+                );
+                process::exit(1);
+            }
+        },
+        Err(e) => {
+            match e.kind() {
+                io::ErrorKind::PermissionDenied => {
+                    eprintln!(
+                        "Error: 書き込み権限がありません。ファイル '{}' を開けませんでした。",
+                        file.display()
+                    );
+                }
+                io::ErrorKind::NotFound => {
+                    eprintln!(
+                        "Error: ファイル '{}' の親ディレクトリが存在しません。",
+                        file.display()
+                    );
+                }
+                _ => {
+                    eprintln!(
+                        "Error: 書き込み用にファイル '{}' を開けませんでした: {}",
+                        file.display(),
+                        e
+                    );
                 }
-            } else {
-                println!("{output}");
             }
-        });
-    });
+            process::exit(1);
+        }
+    }
+}
+
+fn explain(code: &str, lang: Lang, error_format: config::ErrorOutputType) {
+    let info = stelaro_error_codes::parse_error_code(code)
+        .and_then(stelaro_error_codes::find_error_code);
+
+    match info {
+        Some(info) => {
+            print!("{}", info.explanation(lang));
+        }
+        None => {
+            // 入力ファイルを読み込む前であるため、ソースコードをもたない診断として報告する
+            let emitter = default_emitter(Rc::new(SourceMap::new()), error_format);
+            let dcx = DiagCtxt::new(emitter).with_lang(lang);
+            let dcx = dcx.handle();
+            let mut diag = dcx.struct_err(DUMMY_SPAN);
+            diag.set_message(
+                dcx.translate(stelaro_interface::messages::UNKNOWN_ERROR_CODE, &[("code", &code)]),
+            );
+            diag.emit();
+            process::exit(1);
+        }
+    }
 }
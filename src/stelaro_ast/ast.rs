@@ -8,6 +8,8 @@ use super::{
 
 #[derive(Debug)]
 pub struct Stelo {
+    /// `#![...]` の形式で書かれた、Stelo 全体に適用される属性
+    pub attrs: Vec<Attribute>,
     pub items: Vec<Box<Item>>,
     pub span: ModSpan,
     pub id: NodeId,
@@ -15,6 +17,7 @@ pub struct Stelo {
 
 #[derive(Debug, Clone)]
 pub struct Item {
+    pub attrs: Vec<Attribute>,
     pub kind: ItemKind,
     pub id: NodeId,
     // pub vis: Visibility,
@@ -22,10 +25,28 @@ pub struct Item {
     pub ident: Ident,
 }
 
+/// `#[name(arg, ...)]` または `#![name(arg, ...)]` の形式で書かれた属性
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub style: AttrStyle,
+    pub name: Ident,
+    pub args: Vec<Ident>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrStyle {
+    /// `#[...]`: 直後のアイテムに適用される
+    Outer,
+    /// `#![...]`: それを囲むアイテムに適用される
+    Inner,
+}
+
 #[derive(Debug, Clone)]
 pub enum ItemKind {
     Fn(Box<Function>),
     Mod(Ident, ModKind),
+    TyAlias(Box<TyAlias>),
     // Struct(Struct),
     // Enum(Enum),
     // Const(Const),
@@ -39,6 +60,14 @@ pub struct Function {
     pub body: Box<Block>,
 }
 
+/// `type Meters = i64;` のような型エイリアス
+#[derive(Debug, Clone)]
+pub struct TyAlias {
+    pub span: Span,
+    pub ident: Ident,
+    pub ty: Box<Ty>,
+}
+
 #[derive(Debug, Clone)]
 pub struct FnSig {
     pub decl: FnDecl,
@@ -51,7 +80,7 @@ pub struct FnDecl {
     pub output: FnRetTy,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FnRetTy {
     /// `Span` は返り値の型が入るべき場所のSpanを表す
     Default(Span),
@@ -180,9 +209,32 @@ pub enum ExprKind {
     /// `Span` は `=` の位置を表す
     Assign(Box<Expr>, Box<Expr>, Span),
     Path(Path),
+    /// クロージャ (e.g., `|x: i32| x + offset`, `move || x`)
+    Closure(Box<Closure>),
     // AssignOp(BinOp, Box<Expr>, Box<Expr>),
 }
 
+/// クロージャ式。
+///
+/// 引数の型と戻り値の型は省略でき、省略された型は推論される。
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub capture_clause: CaptureBy,
+    pub fn_decl: Box<FnDecl>,
+    pub body: Box<Expr>,
+    /// `|...|` と戻り値の型を含む、クロージャのヘッダーのスパン
+    pub fn_decl_span: Span,
+}
+
+/// クロージャが環境の変数をどのようにキャプチャするか。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureBy {
+    /// `move` キーワードによって、値をコピーしてキャプチャする
+    Value { move_kw: Span },
+    /// 変数への参照としてキャプチャする
+    Ref,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Path {
     pub span: Span,
@@ -256,6 +308,38 @@ pub enum BinOpKind {
     Gt,
 }
 
+impl BinOpKind {
+    pub fn as_str(&self) -> &'static str {
+        use BinOpKind::*;
+        match self {
+            Add => "+",
+            Sub => "-",
+            Mul => "*",
+            Div => "/",
+            Mod => "%",
+            And => "and",
+            Or => "or",
+            Eq => "==",
+            Lt => "<",
+            Le => "<=",
+            Ne => "!=",
+            Ge => ">=",
+            Gt => ">",
+        }
+    }
+
+    /// 比較演算子であるかどうか
+    pub fn is_comparison(&self) -> bool {
+        use BinOpKind::*;
+        matches!(self, Eq | Lt | Le | Ne | Ge | Gt)
+    }
+
+    /// 短絡評価される論理演算子であるかどうか
+    pub fn is_lazy(&self) -> bool {
+        matches!(self, BinOpKind::And | BinOpKind::Or)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum UnOp {
     ///  `!` 演算子: 論理反転
@@ -263,3 +347,12 @@ pub enum UnOp {
     ///  `-` 演算子 負の値
     Neg,
 }
+
+impl UnOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnOp::Not => "!",
+            UnOp::Neg => "-",
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! `--emit=tokens` と `--emit=ast` のための、トークン列と AST の木構造の出力。
+//!
+//! 各行は `行:列-行:列` の位置をもち、ノード ID のように
+//! 入力と無関係に変わる情報は含めない。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::{
+    AttrStyle, Attribute, Block, CaptureBy, Expr, ExprKind, FnRetTy, Item, ItemKind, LocalKind, ModKind,
+    Pat, PatKind, Path, Stelo, Stmt, StmtKind,
+};
+use crate::stelaro_ast::token::{Lit, LitKind, Token, TokenKind, TokenStream};
+use crate::stelaro_ast::ty::{Ty, TyKind};
+use crate::stelaro_common::{LineTable, Span};
+
+
+const INDENT: &str = "  ";
+
+/// トークン列を、一行に一つずつ `行:列 種類 文字列` の形式で出力する。
+pub fn write_tokens(tokens: &TokenStream, lines: &LineTable, w: &mut dyn Write) -> fmt::Result {
+    for token in tokens.iter() {
+        let loc = lines.lookup_loc(token.span.start);
+        let pos = format!("{}:{}", loc.line, loc.col);
+        let category = token_category(token);
+
+        match token.kind {
+            TokenKind::Eof => writeln!(w, "{pos:<8}{category}")?,
+            TokenKind::Literal(lit) => writeln!(w, "{pos:<8}{category:<10}{}", lit_to_string(lit))?,
+            kind => writeln!(w, "{pos:<8}{category:<10}{kind}")?,
+        }
+    }
+
+    Ok(())
+}
+
+fn token_category(token: &Token) -> &'static str {
+    match token.kind {
+        TokenKind::Ident(_) => "ident",
+        TokenKind::Literal(Lit { kind, .. }) => match kind {
+            LitKind::Bool(_) => "bool",
+            LitKind::Char => "char",
+            LitKind::Integer => "int",
+            LitKind::Float => "float",
+            LitKind::Str => "str",
+        },
+        TokenKind::Fn
+        | TokenKind::Mod
+        | TokenKind::Type
+        | TokenKind::Return
+        | TokenKind::Let
+        | TokenKind::If
+        | TokenKind::Else
+        | TokenKind::Break
+        | TokenKind::Continue
+        | TokenKind::For
+        | TokenKind::Loop
+        | TokenKind::While
+        | TokenKind::Move
+        | TokenKind::And
+        | TokenKind::Or => "keyword",
+        TokenKind::Eof => "eof",
+        _ => "punct",
+    }
+}
+
+/// リテラルをソースコード上の表記で表す。
+///
+/// 文字リテラルのシンボルはアンエスケープされた文字そのものであるため、引用符を補う。
+pub(crate) fn lit_to_string(lit: Lit) -> String {
+    match lit.kind {
+        LitKind::Char => format!("'{}'", lit.symbol.as_str().escape_default()),
+        _ => lit.symbol.as_str().to_string(),
+    }
+}
+
+/// AST を、ノードごとに一行の字下げされた木として出力する。
+pub fn write_ast_tree(stelo: &Stelo, lines: &LineTable, w: &mut dyn Write) -> fmt::Result {
+    let mut printer = TreePrinter { lines, w, depth: 0 };
+    printer.print_stelo(stelo)
+}
+
+struct TreePrinter<'a> {
+    lines: &'a LineTable,
+    w: &'a mut dyn Write,
+    depth: usize,
+}
+
+impl TreePrinter<'_> {
+    /// `label` と `span` の行を書き、`children` を一段深く字下げして書く。
+    fn node(
+        &mut self,
+        label: &str,
+        span: Span,
+        children: impl FnOnce(&mut Self) -> fmt::Result,
+    ) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.w.write_str(INDENT)?;
+        }
+        writeln!(self.w, "{label} @ {}", self.lines.span_to_range_string(span))?;
+
+        self.depth += 1;
+        let result = children(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn leaf(&mut self, label: &str, span: Span) -> fmt::Result {
+        self.node(label, span, |_| Ok(()))
+    }
+
+    fn print_stelo(&mut self, stelo: &Stelo) -> fmt::Result {
+        self.node("Stelo", stelo.span.inner_span, |this| {
+            for attr in &stelo.attrs {
+                this.print_attr(attr)?;
+            }
+            for item in &stelo.items {
+                this.print_item(item)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn print_attr(&mut self, attr: &Attribute) -> fmt::Result {
+        let style = match attr.style {
+            AttrStyle::Outer => "",
+            AttrStyle::Inner => "!",
+        };
+        let args = attr
+            .args
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.leaf(&format!("Attr #{style}[{}({args})]", attr.name), attr.span)
+    }
+
+    fn print_item(&mut self, item: &Item) -> fmt::Result {
+        match &item.kind {
+            ItemKind::Fn(function) => self.node(&format!("Fn {}", function.ident), item.span, |this| {
+                for attr in &item.attrs {
+                    this.print_attr(attr)?;
+                }
+                for param in &function.sig.decl.inputs {
+                    this.node("Param", param.span, |this| {
+                        this.print_pat(&param.pat)?;
+                        this.print_ty(&param.ty)
+                    })?;
+                }
+                match &function.sig.decl.output {
+                    FnRetTy::Default(span) => this.leaf("Ret ()", *span)?,
+                    FnRetTy::Ty(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty))?,
+                }
+                this.print_block("Body", &function.body)
+            }),
+            ItemKind::Mod(ident, ModKind::Inline(items, _)) => {
+                self.node(&format!("Mod {ident}"), item.span, |this| {
+                    for attr in &item.attrs {
+                        this.print_attr(attr)?;
+                    }
+                    for item in items {
+                        this.print_item(item)?;
+                    }
+                    Ok(())
+                })
+            }
+            ItemKind::TyAlias(ty_alias) => {
+                self.node(&format!("TyAlias {}", ty_alias.ident), item.span, |this| {
+                    for attr in &item.attrs {
+                        this.print_attr(attr)?;
+                    }
+                    this.print_ty(&ty_alias.ty)
+                })
+            }
+        }
+    }
+
+    fn print_block(&mut self, label: &str, block: &Block) -> fmt::Result {
+        self.node(label, block.span, |this| {
+            for stmt in &block.stmts {
+                this.print_stmt(stmt)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn print_stmt(&mut self, stmt: &Stmt) -> fmt::Result {
+        match &stmt.kind {
+            StmtKind::Let(local) => self.node("Let", stmt.span, |this| {
+                this.print_pat(&local.pat)?;
+                if let Some(ty) = &local.ty {
+                    this.print_ty(ty)?;
+                }
+                match &local.kind {
+                    LocalKind::Decl => Ok(()),
+                    LocalKind::Init(init) => this.print_expr(init),
+                }
+            }),
+            StmtKind::Expr(expr) => self.node("Expr", stmt.span, |this| this.print_expr(expr)),
+            StmtKind::Semi(expr) => self.node("Semi", stmt.span, |this| this.print_expr(expr)),
+            StmtKind::Loop(block) => {
+                self.node("Loop", stmt.span, |this| this.print_block("Block", block))
+            }
+            StmtKind::While(cond, block) => self.node("While", stmt.span, |this| {
+                this.print_expr(cond)?;
+                this.print_block("Block", block)
+            }),
+            StmtKind::Break(expr) => self.node("Break", stmt.span, |this| {
+                expr.as_ref().map_or(Ok(()), |expr| this.print_expr(expr))
+            }),
+            StmtKind::Continue => self.leaf("Continue", stmt.span),
+            StmtKind::Return(expr) => self.node("Return", stmt.span, |this| {
+                expr.as_ref().map_or(Ok(()), |expr| this.print_expr(expr))
+            }),
+        }
+    }
+
+    fn print_expr(&mut self, expr: &Expr) -> fmt::Result {
+        match &expr.kind {
+            ExprKind::Call(func, args) => self.node("Call", expr.span, |this| {
+                this.print_expr(func)?;
+                for arg in args {
+                    this.print_expr(arg)?;
+                }
+                Ok(())
+            }),
+            ExprKind::If(cond, then, els) => self.node("If", expr.span, |this| {
+                this.print_expr(cond)?;
+                this.print_block("Then", then)?;
+                match els {
+                    Some(els) => this.node("Else", els.span, |this| this.print_expr(els)),
+                    None => Ok(()),
+                }
+            }),
+            ExprKind::Block(block) => self.print_block("Block", block),
+            ExprKind::Binary(op, lhs, rhs) => {
+                self.node(&format!("Binary {}", op.node.as_str()), expr.span, |this| {
+                    this.print_expr(lhs)?;
+                    this.print_expr(rhs)
+                })
+            }
+            ExprKind::Unary(op, operand) => {
+                self.node(&format!("Unary {}", op.as_str()), expr.span, |this| {
+                    this.print_expr(operand)
+                })
+            }
+            ExprKind::Lit(lit) => self.leaf(&format!("Lit {}", lit_to_string(*lit)), expr.span),
+            ExprKind::Paren(inner) => self.node("Paren", expr.span, |this| this.print_expr(inner)),
+            ExprKind::Assign(lhs, rhs, _) => self.node("Assign", expr.span, |this| {
+                this.print_expr(lhs)?;
+                this.print_expr(rhs)
+            }),
+            ExprKind::Path(path) => self.leaf(&format!("Path {}", path_to_string(path)), expr.span),
+            ExprKind::Closure(closure) => {
+                let label = match closure.capture_clause {
+                    CaptureBy::Value { .. } => "Closure move",
+                    CaptureBy::Ref => "Closure",
+                };
+                self.node(label, expr.span, |this| {
+                    for param in &closure.fn_decl.inputs {
+                        this.node("Param", param.span, |this| {
+                            this.print_pat(&param.pat)?;
+                            this.print_ty(&param.ty)
+                        })?;
+                    }
+                    match &closure.fn_decl.output {
+                        FnRetTy::Default(span) => this.leaf("Ret _", *span)?,
+                        FnRetTy::Ty(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty))?,
+                    }
+                    this.print_expr(&closure.body)
+                })
+            }
+        }
+    }
+
+    fn print_pat(&mut self, pat: &Pat) -> fmt::Result {
+        match pat.kind {
+            PatKind::WildCard => self.leaf("Pat _", pat.span),
+            PatKind::Ident(ident) => self.leaf(&format!("Pat {ident}"), pat.span),
+        }
+    }
+
+    fn print_ty(&mut self, ty: &Ty) -> fmt::Result {
+        match &ty.kind {
+            TyKind::Path(path) => self.leaf(&format!("Ty {}", path_to_string(path)), ty.span),
+            TyKind::Infer => self.leaf("Ty _", ty.span),
+            TyKind::Unit => self.leaf("Ty ()", ty.span),
+            TyKind::Never => self.leaf("Ty !", ty.span),
+            TyKind::FnPtr(fn_ptr) => self.node("Ty fn", ty.span, |this| {
+                for input in &fn_ptr.inputs {
+                    this.print_ty(input)?;
+                }
+                match &fn_ptr.output {
+                    FnRetTy::Default(span) => this.leaf("Ret ()", *span),
+                    FnRetTy::Ty(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty)),
+                }
+            }),
+        }
+    }
+}
+
+pub(crate) fn path_to_string(path: &Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.name.as_str())
+        .collect::<Vec<_>>()
+        .join("::")
+}
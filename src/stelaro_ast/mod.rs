@@ -1,5 +1,8 @@
 pub mod ast;
+pub mod dump;
 pub mod node_id;
+pub mod pretty;
+pub mod stable_hash;
 pub mod token;
 pub mod ty;
 pub mod visit;
@@ -0,0 +1,286 @@
+//! `--emit=ast-pretty` のための、AST のソースコード形式での出力。
+//!
+//! 括弧は `ExprKind::Paren` として AST に残っているため、書かれた通りに出力される。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::{
+    AttrStyle, Attribute, Block, CaptureBy, Expr, ExprKind, FnRetTy, Item, ItemKind, LocalKind, ModKind,
+    Pat, PatKind, Stelo, Stmt, StmtKind,
+};
+use crate::stelaro_ast::dump::{lit_to_string, path_to_string};
+use crate::stelaro_ast::ty::{Ty, TyKind};
+
+
+const INDENT: &str = "    ";
+
+/// AST をソースコードの形式で出力する。
+pub fn write_ast_pretty(stelo: &Stelo, w: &mut dyn Write) -> fmt::Result {
+    let mut printer = PrettyPrinter { w, depth: 0 };
+
+    for attr in &stelo.attrs {
+        printer.print_attr(attr)?;
+    }
+    for (i, item) in stelo.items.iter().enumerate() {
+        if i != 0 || !stelo.attrs.is_empty() {
+            writeln!(printer.w)?;
+        }
+        printer.print_item(item)?;
+    }
+
+    Ok(())
+}
+
+struct PrettyPrinter<'a> {
+    w: &'a mut dyn Write,
+    depth: usize,
+}
+
+impl PrettyPrinter<'_> {
+    fn indent(&mut self) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.w.write_str(INDENT)?;
+        }
+        Ok(())
+    }
+
+    fn print_attr(&mut self, attr: &Attribute) -> fmt::Result {
+        self.indent()?;
+        let style = match attr.style {
+            AttrStyle::Outer => "",
+            AttrStyle::Inner => "!",
+        };
+        write!(self.w, "#{style}[{}", attr.name)?;
+        if !attr.args.is_empty() {
+            let args = attr
+                .args
+                .iter()
+                .map(|arg| arg.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(self.w, "({args})")?;
+        }
+        writeln!(self.w, "]")
+    }
+
+    fn print_item(&mut self, item: &Item) -> fmt::Result {
+        for attr in &item.attrs {
+            self.print_attr(attr)?;
+        }
+        self.indent()?;
+
+        match &item.kind {
+            ItemKind::Fn(function) => {
+                write!(self.w, "fn {}(", function.ident)?;
+                for (i, param) in function.sig.decl.inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_pat(&param.pat)?;
+                    write!(self.w, ": ")?;
+                    self.print_ty(&param.ty)?;
+                }
+                write!(self.w, ")")?;
+                if let FnRetTy::Ty(ty) = &function.sig.decl.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                write!(self.w, " ")?;
+                self.print_block(&function.body)?;
+                writeln!(self.w)
+            }
+            ItemKind::Mod(ident, ModKind::Inline(items, _)) => {
+                writeln!(self.w, "mod {ident} {{")?;
+                self.depth += 1;
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        writeln!(self.w)?;
+                    }
+                    self.print_item(item)?;
+                }
+                self.depth -= 1;
+                self.indent()?;
+                writeln!(self.w, "}}")
+            }
+            ItemKind::TyAlias(ty_alias) => {
+                write!(self.w, "type {} = ", ty_alias.ident)?;
+                self.print_ty(&ty_alias.ty)?;
+                writeln!(self.w, ";")
+            }
+        }
+    }
+
+    /// ブロックを `{` から `}` まで出力する。閉じ括弧の後の改行は呼び出し元が書く。
+    fn print_block(&mut self, block: &Block) -> fmt::Result {
+        if block.stmts.is_empty() {
+            return write!(self.w, "{{}}");
+        }
+
+        writeln!(self.w, "{{")?;
+        self.depth += 1;
+        for stmt in &block.stmts {
+            self.print_stmt(stmt)?;
+        }
+        self.depth -= 1;
+        self.indent()?;
+        write!(self.w, "}}")
+    }
+
+    fn print_stmt(&mut self, stmt: &Stmt) -> fmt::Result {
+        self.indent()?;
+
+        match &stmt.kind {
+            StmtKind::Let(local) => {
+                write!(self.w, "let ")?;
+                self.print_pat(&local.pat)?;
+                if let Some(ty) = &local.ty {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                if let LocalKind::Init(init) = &local.kind {
+                    write!(self.w, " = ")?;
+                    self.print_expr(init)?;
+                }
+                write!(self.w, ";")?;
+            }
+            StmtKind::Expr(expr) => self.print_expr(expr)?,
+            StmtKind::Semi(expr) => {
+                self.print_expr(expr)?;
+                write!(self.w, ";")?;
+            }
+            StmtKind::Loop(block) => {
+                write!(self.w, "loop ")?;
+                self.print_block(block)?;
+            }
+            StmtKind::While(cond, block) => {
+                write!(self.w, "while ")?;
+                self.print_expr(cond)?;
+                write!(self.w, " ")?;
+                self.print_block(block)?;
+            }
+            StmtKind::Break(expr) => {
+                write!(self.w, "break")?;
+                if let Some(expr) = expr {
+                    write!(self.w, " ")?;
+                    self.print_expr(expr)?;
+                }
+                write!(self.w, ";")?;
+            }
+            StmtKind::Continue => write!(self.w, "continue;")?,
+            StmtKind::Return(expr) => {
+                write!(self.w, "return")?;
+                if let Some(expr) = expr {
+                    write!(self.w, " ")?;
+                    self.print_expr(expr)?;
+                }
+                write!(self.w, ";")?;
+            }
+        }
+
+        writeln!(self.w)
+    }
+
+    fn print_expr(&mut self, expr: &Expr) -> fmt::Result {
+        match &expr.kind {
+            ExprKind::Call(func, args) => {
+                self.print_expr(func)?;
+                write!(self.w, "(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_expr(arg)?;
+                }
+                write!(self.w, ")")
+            }
+            ExprKind::If(cond, then, els) => {
+                write!(self.w, "if ")?;
+                self.print_expr(cond)?;
+                write!(self.w, " ")?;
+                self.print_block(then)?;
+                if let Some(els) = els {
+                    write!(self.w, " else ")?;
+                    self.print_expr(els)?;
+                }
+                Ok(())
+            }
+            ExprKind::Block(block) => self.print_block(block),
+            ExprKind::Binary(op, lhs, rhs) => {
+                self.print_expr(lhs)?;
+                write!(self.w, " {} ", op.node.as_str())?;
+                self.print_expr(rhs)
+            }
+            ExprKind::Unary(op, operand) => {
+                write!(self.w, "{}", op.as_str())?;
+                self.print_expr(operand)
+            }
+            ExprKind::Lit(lit) => write!(self.w, "{}", lit_to_string(*lit)),
+            ExprKind::Paren(inner) => {
+                write!(self.w, "(")?;
+                self.print_expr(inner)?;
+                write!(self.w, ")")
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                self.print_expr(lhs)?;
+                write!(self.w, " = ")?;
+                self.print_expr(rhs)
+            }
+            ExprKind::Path(path) => write!(self.w, "{}", path_to_string(path)),
+            ExprKind::Closure(closure) => {
+                if let CaptureBy::Value { .. } = closure.capture_clause {
+                    write!(self.w, "move ")?;
+                }
+                write!(self.w, "|")?;
+                for (i, param) in closure.fn_decl.inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_pat(&param.pat)?;
+                    // 省略された型は `_` として AST に残っている
+                    if !matches!(param.ty.kind, TyKind::Infer) {
+                        write!(self.w, ": ")?;
+                        self.print_ty(&param.ty)?;
+                    }
+                }
+                write!(self.w, "|")?;
+                if let FnRetTy::Ty(ty) = &closure.fn_decl.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                write!(self.w, " ")?;
+                self.print_expr(&closure.body)
+            }
+        }
+    }
+
+    fn print_pat(&mut self, pat: &Pat) -> fmt::Result {
+        match pat.kind {
+            PatKind::WildCard => write!(self.w, "_"),
+            PatKind::Ident(ident) => write!(self.w, "{ident}"),
+        }
+    }
+
+    fn print_ty(&mut self, ty: &Ty) -> fmt::Result {
+        match &ty.kind {
+            TyKind::Path(path) => write!(self.w, "{}", path_to_string(path)),
+            TyKind::Infer => write!(self.w, "_"),
+            TyKind::Unit => write!(self.w, "()"),
+            TyKind::Never => write!(self.w, "!"),
+            TyKind::FnPtr(fn_ptr) => {
+                write!(self.w, "fn(")?;
+                for (i, input) in fn_ptr.inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_ty(input)?;
+                }
+                write!(self.w, ")")?;
+                if let FnRetTy::Ty(ty) = &fn_ptr.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! インクリメンタルコンパイルのための、AST の安定したハッシュ。
+//!
+//! `NodeId` とスパンはセッションごと、あるいは無関係な編集によって変わるため含めない。
+//! したがって、同じ内容のアイテムはファイル内の位置が変わっても同じフィンガープリントになる。
+
+use std::mem;
+
+use crate::stelaro_ast::ast::{
+    AttrStyle, Attribute, BinOpKind, Block, CaptureBy, Expr, ExprKind, FnDecl, FnRetTy, FnSig, Function,
+    Item, ItemKind, Local, LocalKind, ModKind, Param, Pat, PatKind, Path, PathSegment, Stmt,
+    StmtKind, TyAlias, UnOp,
+};
+use crate::stelaro_ast::token::{Lit, LitKind};
+use crate::stelaro_ast::ty::{Ty, TyKind};
+use crate::stelaro_common::stable_hasher::HashStable;
+use crate::stelaro_common::{Fingerprint, StableHasher};
+
+
+/// アイテムの内容から、セッションをまたいで安定したフィンガープリントを計算する。
+pub fn item_fingerprint(item: &Item) -> Fingerprint {
+    let mut hasher = StableHasher::new();
+    item.hash_stable(&mut hasher);
+    hasher.finish()
+}
+
+impl HashStable for Item {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.attrs.hash_stable(hasher);
+        self.ident.hash_stable(hasher);
+        self.kind.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Attribute {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.style.hash_stable(hasher);
+        self.name.hash_stable(hasher);
+        self.args.hash_stable(hasher);
+    }
+}
+
+impl HashStable for AttrStyle {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+    }
+}
+
+impl HashStable for ItemKind {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+        match self {
+            ItemKind::Fn(function) => function.hash_stable(hasher),
+            ItemKind::Mod(ident, ModKind::Inline(items, _)) => {
+                ident.hash_stable(hasher);
+                items.hash_stable(hasher);
+            }
+            ItemKind::TyAlias(ty_alias) => ty_alias.hash_stable(hasher),
+        }
+    }
+}
+
+impl HashStable for TyAlias {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.ident.hash_stable(hasher);
+        self.ty.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Function {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.ident.hash_stable(hasher);
+        self.sig.hash_stable(hasher);
+        self.body.hash_stable(hasher);
+    }
+}
+
+impl HashStable for FnSig {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.decl.hash_stable(hasher);
+    }
+}
+
+impl HashStable for FnDecl {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.inputs.hash_stable(hasher);
+        self.output.hash_stable(hasher);
+    }
+}
+
+impl HashStable for FnRetTy {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+        match self {
+            FnRetTy::Default(_) => {}
+            FnRetTy::Ty(ty) => ty.hash_stable(hasher),
+        }
+    }
+}
+
+impl HashStable for Param {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.pat.hash_stable(hasher);
+        self.ty.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Block {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.stmts.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Stmt {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.kind.hash_stable(hasher);
+    }
+}
+
+impl HashStable for StmtKind {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+        match self {
+            StmtKind::Let(local) => local.hash_stable(hasher),
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => expr.hash_stable(hasher),
+            StmtKind::Loop(block) => block.hash_stable(hasher),
+            StmtKind::While(cond, block) => {
+                cond.hash_stable(hasher);
+                block.hash_stable(hasher);
+            }
+            StmtKind::Break(expr) | StmtKind::Return(expr) => expr.hash_stable(hasher),
+            StmtKind::Continue => {}
+        }
+    }
+}
+
+impl HashStable for Local {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.pat.hash_stable(hasher);
+        self.ty.hash_stable(hasher);
+        match &self.kind {
+            LocalKind::Decl => 0u8.hash_stable(hasher),
+            LocalKind::Init(init) => {
+                1u8.hash_stable(hasher);
+                init.hash_stable(hasher);
+            }
+        }
+    }
+}
+
+impl HashStable for Pat {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(&self.kind).hash_stable(hasher);
+        match &self.kind {
+            PatKind::WildCard => {}
+            PatKind::Ident(ident) => ident.hash_stable(hasher),
+        }
+    }
+}
+
+impl HashStable for Expr {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.kind.hash_stable(hasher);
+    }
+}
+
+impl HashStable for ExprKind {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+        match self {
+            ExprKind::Call(func, args) => {
+                func.hash_stable(hasher);
+                args.hash_stable(hasher);
+            }
+            ExprKind::If(cond, then, els) => {
+                cond.hash_stable(hasher);
+                then.hash_stable(hasher);
+                els.hash_stable(hasher);
+            }
+            ExprKind::Block(block) => block.hash_stable(hasher),
+            ExprKind::Binary(op, lhs, rhs) => {
+                op.node.hash_stable(hasher);
+                lhs.hash_stable(hasher);
+                rhs.hash_stable(hasher);
+            }
+            ExprKind::Unary(op, operand) => {
+                op.hash_stable(hasher);
+                operand.hash_stable(hasher);
+            }
+            ExprKind::Lit(lit) => lit.hash_stable(hasher),
+            ExprKind::Paren(expr) => expr.hash_stable(hasher),
+            ExprKind::Assign(lhs, rhs, _) => {
+                lhs.hash_stable(hasher);
+                rhs.hash_stable(hasher);
+            }
+            ExprKind::Path(path) => path.hash_stable(hasher),
+            ExprKind::Closure(closure) => {
+                matches!(closure.capture_clause, CaptureBy::Value { .. }).hash_stable(hasher);
+                closure.fn_decl.hash_stable(hasher);
+                closure.body.hash_stable(hasher);
+            }
+        }
+    }
+}
+
+impl HashStable for BinOpKind {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+    }
+}
+
+impl HashStable for UnOp {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(self).hash_stable(hasher);
+    }
+}
+
+impl HashStable for Lit {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(&self.kind).hash_stable(hasher);
+        if let LitKind::Bool(b) = self.kind {
+            b.hash_stable(hasher);
+        }
+        self.symbol.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Path {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.segments.hash_stable(hasher);
+    }
+}
+
+impl HashStable for PathSegment {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.ident.hash_stable(hasher);
+    }
+}
+
+impl HashStable for Ty {
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        mem::discriminant(&self.kind).hash_stable(hasher);
+        match &self.kind {
+            TyKind::Path(path) => path.hash_stable(hasher),
+            TyKind::FnPtr(fn_ptr) => {
+                fn_ptr.inputs.hash_stable(hasher);
+                fn_ptr.output.hash_stable(hasher);
+            }
+            TyKind::Infer | TyKind::Unit | TyKind::Never => {}
+        }
+    }
+}
@@ -25,6 +25,12 @@ pub enum TokenKind {
     LBrace,
     /// `}`
     RBrace,
+    /// `[`
+    LBracket,
+    /// `]`
+    RBracket,
+    /// `#`
+    Pound,
     /// `,`
     Comma,
     /// `.`
@@ -61,6 +67,8 @@ pub enum TokenKind {
     Less,
     /// `<=`
     LessEqual,
+    /// `|`
+    Pipe,
     /// `and`
     And,
     /// `or`
@@ -76,6 +84,8 @@ pub enum TokenKind {
     Fn, // fn
     /// モジュールの宣言
     Mod, // mod
+    /// 型エイリアスの宣言
+    Type, // type
     /// return文
     Return, // return
     /// let文
@@ -93,6 +103,8 @@ pub enum TokenKind {
     Loop, // loop
     /// while文
     While, // while
+    /// 値によるキャプチャを行うクロージャ
+    Move, // move
 
     Eof,
 }
@@ -237,6 +249,9 @@ impl fmt::Display for TokenKind {
             TokenKind::RParen => wrt!(f, ")"),
             TokenKind::LBrace => wrt!(f, "{"),
             TokenKind::RBrace => wrt!(f, "}"),
+            TokenKind::LBracket => wrt!(f, "["),
+            TokenKind::RBracket => wrt!(f, "]"),
+            TokenKind::Pound => wrt!(f, "#"),
             TokenKind::Comma => wrt!(f, ","),
             TokenKind::Dot => wrt!(f, "."),
             TokenKind::Plus => wrt!(f, "+"),
@@ -255,12 +270,14 @@ impl fmt::Display for TokenKind {
             TokenKind::GreaterEqual => wrt!(f, ">="),
             TokenKind::Less => wrt!(f, "<"),
             TokenKind::LessEqual => wrt!(f, "<="),
+            TokenKind::Pipe => wrt!(f, "|"),
             TokenKind::And => wrt!(f, "and"),
             TokenKind::Or => wrt!(f, "or"),
             TokenKind::Ident(symbol) => wrt!(f, symbol.as_str()),
             TokenKind::Literal(lit) => wrt!(f, lit.symbol.as_str()),
             TokenKind::Fn => wrt!(f, "fn"),
             TokenKind::Mod => wrt!(f, "mod"),
+            TokenKind::Type => wrt!(f, "type"),
             TokenKind::Return => wrt!(f, "return"),
             TokenKind::Let => wrt!(f, "let"),
             TokenKind::If => wrt!(f, "if"),
@@ -270,6 +287,7 @@ impl fmt::Display for TokenKind {
             TokenKind::For => wrt!(f, "for"),
             TokenKind::While => wrt!(f, "while"),
             TokenKind::Loop => wrt!(f, "loop"),
+            TokenKind::Move => wrt!(f, "move"),
             TokenKind::Eof => wrt!(f, "入力の終端"),
         }
     }
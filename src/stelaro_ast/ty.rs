@@ -1,7 +1,7 @@
 use crate::stelaro_ast::NodeId;
 use crate::stelaro_common::Span;
 
-use super::ast::Path;
+use super::ast::{FnRetTy, Path};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Ty {
@@ -19,7 +19,19 @@ pub enum TyKind {
     // NOTE: タプルの実装後、これを削除し
     // 空の Tuple が Unit を表すように変更する
     Unit,
+
+    /// 決して値をもたない `!` 型
+    Never,
+
+    /// `fn(i32): i32` のような関数ポインタ型
+    FnPtr(FnPtrTy),
     // Tuple,
     // Ref,
     // Array,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FnPtrTy {
+    pub inputs: Vec<Ty>,
+    pub output: FnRetTy,
+}
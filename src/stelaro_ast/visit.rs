@@ -36,6 +36,10 @@ pub trait Visitor<'ast> {
         walk_item(self, item)
     }
 
+    fn visit_attribute(&mut self, _attr: &'ast Attribute) -> Self::Result {
+        Self::Result::output()
+    }
+
     fn visit_fn(&mut self, f: &'ast Function) -> Self::Result {
         walk_fn(self, f)
     }
@@ -97,7 +101,8 @@ pub fn walk_stelo<'ast, V>(
 where
     V: Visitor<'ast> + ?Sized,
 {
-    let Stelo { items, .. } = stelo;
+    let Stelo { attrs, items, .. } = stelo;
+    walk_list!(visitor, visit_attribute, attrs);
     walk_list!(visitor, visit_item, items);
     V::Result::output()
 }
@@ -110,8 +115,9 @@ pub fn walk_item<'ast, V>(
 where
     V: Visitor<'ast> + ?Sized,
 {
-    let Item { kind, ident, .. } = item;
+    let Item { attrs, kind, ident, .. } = item;
 
+    walk_list!(visitor, visit_attribute, attrs);
     try_visit!(visitor.visit_ident(ident));
 
     match kind {
@@ -121,6 +127,7 @@ where
                 ModKind::Inline(items, ..) => walk_list!(visitor, visit_item, items),
             }
         },
+        super::ast::ItemKind::TyAlias(ty_alias) => try_visit!(visitor.visit_ty(&ty_alias.ty)),
     }
     V::Result::output()
 }
@@ -239,6 +246,11 @@ where
         TyKind::Path(path) => try_visit!(visitor.visit_path(path)),
         TyKind::Infer => {},
         TyKind::Unit => {},
+        TyKind::Never => {},
+        TyKind::FnPtr(fn_ptr) => {
+            walk_list!(visitor, visit_ty, &fn_ptr.inputs);
+            try_visit!(visitor.visit_fn_ret_ty(&fn_ptr.output));
+        }
     }
 
     V::Result::output()
@@ -349,6 +361,11 @@ where
         ExprKind::Path(path) => {
             try_visit!(visitor.visit_path(path));
         },
+        ExprKind::Closure(closure) => {
+            let Closure { fn_decl, body, .. } = &**closure;
+            try_visit!(visitor.visit_fn_decl(fn_decl));
+            try_visit!(visitor.visit_expr(body));
+        },
     }
 
     V::Result::output()
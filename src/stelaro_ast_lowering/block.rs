@@ -153,6 +153,20 @@ impl<'a, 'sir> LoweringContext<'a, 'sir> {
         result
     }
 
+    /// ループの文脈を持ち込まずに、クロージャの本体を lowering する。
+    pub(crate) fn with_closure_scope<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let was_in_loop_condition = self.is_in_loop_condition;
+        self.is_in_loop_condition = false;
+
+        let old_scope = self.loop_scope.take();
+        let result = f(self);
+        self.loop_scope = old_scope;
+
+        self.is_in_loop_condition = was_in_loop_condition;
+
+        result
+    }
+
     fn with_loop_condition_scope<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
         let was_in_loop_condition = self.is_in_loop_condition;
         self.is_in_loop_condition = true;
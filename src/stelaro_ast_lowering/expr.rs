@@ -1,4 +1,4 @@
-use crate::stelaro_ast::{ast, token};
+use crate::stelaro_ast::{NodeId, ast, token};
 use crate::stelaro_ast_lowering::LoweringContext;
 use crate::stelaro_common::{Span, Spanned, ensure_sufficient_stack, lit_utils::report_lit_error};
 use crate::stelaro_sir::sir::{self, LitKind};
@@ -59,6 +59,7 @@ impl<'sir> LoweringContext<'_, 'sir> {
                     sir::ExprKind::Assign(lhs, rhs, *span)
                 }
                 ExprKind::Path(path) => sir::ExprKind::Path(self.lower_path(e.id, path)),
+                ExprKind::Closure(closure) => self.lower_expr_closure(e.id, closure),
                 ExprKind::Paren(_) => unreachable!(),
             };
 
@@ -107,6 +108,28 @@ impl<'sir> LoweringContext<'_, 'sir> {
         }
     }
 
+    fn lower_expr_closure(
+        &mut self,
+        closure_id: NodeId,
+        closure: &ast::Closure,
+    ) -> sir::ExprKind<'sir> {
+        let def_id = self.local_def_id(closure_id);
+
+        // `break` と `continue` は、クロージャの外側のループを対象にできない
+        let body = self.with_closure_scope(|this| {
+            this.lower_fn_body(&closure.fn_decl, |this| this.lower_expr_mut(&closure.body))
+        });
+        let fn_decl = self.lower_fn_decl(&closure.fn_decl, closure_id, closure.fn_decl_span);
+
+        sir::ExprKind::Closure(self.arena.alloc(sir::Closure {
+            def_id,
+            capture_clause: closure.capture_clause,
+            fn_decl,
+            body,
+            fn_decl_span: closure.fn_decl_span,
+        }))
+    }
+
     pub fn expr(&mut self, span: Span, kind: sir::ExprKind<'sir>) -> sir::Expr<'sir> {
         let sir_id = self.next_id();
         sir::Expr { sir_id, kind, span }
@@ -140,6 +140,9 @@ impl<'sir> LoweringContext<'_, 'sir> {
                     sir::ItemKind::Mod(*ident, self.lower_mod(items, mod_span))
                 }
             },
+            ItemKind::TyAlias(box ast::TyAlias { ident, ty, .. }) => {
+                sir::ItemKind::TyAlias(*ident, self.lower_ty(ty))
+            }
         }
     }
 
@@ -167,7 +170,7 @@ impl<'sir> LoweringContext<'_, 'sir> {
         self.record_body(parameters, result)
     }
 
-    fn lower_fn_decl(
+    pub fn lower_fn_decl(
         &mut self,
         decl: &ast::FnDecl,
         _fn_node_id: NodeId,
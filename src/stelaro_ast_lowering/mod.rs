@@ -15,6 +15,7 @@ use crate::stelaro_ast::{
 use crate::stelaro_ast_lowering::index::index_sir;
 use crate::stelaro_common::{Arena, Idx, IndexVec, LocalDefId, STELO_DEF_ID, SortedMap, Span};
 use crate::stelaro_context::TyCtxt;
+use crate::stelaro_context::query::Providers;
 use crate::stelaro_sir::{
     def::Res,
     sir,
@@ -107,11 +108,16 @@ fn index_stelo<'a>(
     }
 }
 
-pub fn lower_to_sir(
-    tcx: TyCtxt<'_>,
-    mut resolver: ResolverAstLowering,
-    stelo: ast::Stelo,
-) -> sir::Stelo<'_> {
+pub fn provide(providers: &mut Providers) {
+    providers.sir_stelo = lower_to_sir;
+}
+
+/// 名前解決を終えた AST を取り出し、SIR に変換する。
+fn lower_to_sir(tcx: TyCtxt<'_>, (): ()) -> &sir::Stelo<'_> {
+    let (mut resolver, stelo) = tcx
+        .resolver_for_lowering
+        .take()
+        .expect("bug: 名前解決の結果が登録されていない");
     let ast_index = index_stelo(&resolver.node_id_to_def_id, &stelo);
     let mut owners = IndexVec::from_fn_n(
         |_| sir::MaybeOwner::Phantom,
@@ -135,7 +141,7 @@ pub fn lower_to_sir(
         drop(stelo);
     });
 
-    sir::Stelo { owners }
+    tcx.sir_arena.alloc(sir::Stelo { owners })
 }
 
 impl<'a, 'sir> LoweringContext<'a, 'sir> {
@@ -306,6 +312,17 @@ impl<'a, 'sir> LoweringContext<'a, 'sir> {
             }
             TyKind::Infer => sir::TyKind::Infer,
             TyKind::Unit => sir::TyKind::Unit,
+            TyKind::Never => sir::TyKind::Never,
+            TyKind::FnPtr(fn_ptr) => {
+                let inputs = self
+                    .arena
+                    .alloc_from_iter(fn_ptr.inputs.iter().map(|ty| self.lower_ty_direct(ty)));
+                let output = match &fn_ptr.output {
+                    ast::FnRetTy::Ty(ty) => sir::FnRetTy::Return(self.lower_ty(ty)),
+                    ast::FnRetTy::Default(span) => sir::FnRetTy::DefaultReturn(*span),
+                };
+                sir::TyKind::FnPtr(self.arena.alloc(sir::FnDecl { inputs, output }))
+            }
         };
 
         sir::Ty {
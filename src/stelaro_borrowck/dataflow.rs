@@ -0,0 +1,89 @@
+//! 各地点で初期化されていない可能性のあるローカル変数を求めるデータフロー解析。
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_mir::{
+    BasicBlock, Body, Local, START_BLOCK, Statement, StatementKind, Terminator, TerminatorKind,
+};
+
+
+/// ローカル変数の集合。`true` の要素が集合に含まれる。
+pub(crate) type LocalSet = IndexVec<Local, bool>;
+
+/// 初期化されていない可能性のあるローカル変数 (maybe-uninitialized) の解析結果。
+///
+/// ある経路で初期化されていなければ、その変数は初期化されていない可能性がある。
+/// そのため合流点では、各先行ブロックの集合の和をとる。
+pub(crate) struct MaybeUninitializedLocals {
+    /// 各基本ブロックの入口における集合。
+    entry_sets: IndexVec<BasicBlock, LocalSet>,
+}
+
+impl MaybeUninitializedLocals {
+    /// 不動点に達するまで、各基本ブロックの入口における集合を計算する。
+    pub(crate) fn compute(body: &Body<'_>) -> Self {
+        let num_locals = body.local_decls.len();
+        let bottom = LocalSet::from_elem_n(false, num_locals);
+        let mut entry_sets = IndexVec::from_elem_n(bottom, body.basic_blocks.len());
+
+        // 入口では、引数以外のすべてのローカル変数が初期化されていない
+        let entry_set = &mut entry_sets[START_BLOCK];
+        entry_set[Local::ZERO] = true;
+        for local in body.vars_and_temps_iter() {
+            entry_set[local] = true;
+        }
+
+        // 効果が伝播しないブロックも一度は処理するため、すべてのブロックから始める
+        let mut worklist = body.basic_blocks.indices().rev().collect::<Vec<_>>();
+        let mut state = LocalSet::new();
+
+        while let Some(bb) = worklist.pop() {
+            state.clone_from(&entry_sets[bb]);
+
+            let data = &body.basic_blocks[bb];
+            for statement in &data.statements {
+                Self::apply_statement_effect(&mut state, statement);
+            }
+            Self::apply_terminator_effect(&mut state, data.terminator());
+
+            for succ in data.terminator().successors() {
+                if join(&mut entry_sets[succ], &state) {
+                    worklist.push(succ);
+                }
+            }
+        }
+
+        MaybeUninitializedLocals { entry_sets }
+    }
+
+    pub(crate) fn entry_set(&self, bb: BasicBlock) -> &LocalSet {
+        &self.entry_sets[bb]
+    }
+
+    pub(crate) fn apply_statement_effect(state: &mut LocalSet, statement: &Statement<'_>) {
+        match &statement.kind {
+            StatementKind::Assign(assign) => state[assign.0.local] = false,
+            // ループの中で宣言された変数は、反復のたびに初期化されていない状態に戻る
+            StatementKind::StorageLive(local) => state[*local] = true,
+        }
+    }
+
+    pub(crate) fn apply_terminator_effect(state: &mut LocalSet, terminator: &Terminator<'_>) {
+        if let TerminatorKind::Call { destination, .. } = &terminator.kind {
+            state[destination.local] = false;
+        }
+    }
+}
+
+/// `set` に `other` を合わせ、`set` が変化したかどうかを返す。
+fn join(set: &mut LocalSet, other: &LocalSet) -> bool {
+    let mut changed = false;
+
+    for (elem, &other) in set.iter_mut().zip(other.iter()) {
+        if other && !*elem {
+            *elem = true;
+            changed = true;
+        }
+    }
+
+    changed
+}
@@ -0,0 +1,202 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsBorrowck;
+
+impl<'dcx> DiagsBorrowck {
+    pub fn use_of_possibly_uninitialized(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: &str,
+        use_span: Span,
+        decl_span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(use_span);
+        diag.set_code(ErrorCode::UseOfPossiblyUninitialized.into());
+        diag.set_message(
+            dcx.translate(messages::USE_OF_POSSIBLY_UNINITIALIZED, &[("name", &name)]),
+        );
+        diag.set_label(
+            use_span,
+            dcx.translate(messages::USE_OF_POSSIBLY_UNINITIALIZED_LABEL, &[("name", &name)]),
+        );
+        diag.set_label(
+            decl_span,
+            dcx.translate(
+                messages::USE_OF_POSSIBLY_UNINITIALIZED_DECLARED_LABEL,
+                &[("name", &name)],
+            ),
+        );
+        diag.set_help(
+            dcx.translate(messages::USE_OF_POSSIBLY_UNINITIALIZED_HELP, &[("name", &name)]),
+        );
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    UseOfPossiblyUninitialized = 600,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::stelaro_borrowck::{diagnostics::ErrorCode, mir_borrowck};
+    use crate::stelaro_common::create_default_session_globals_then;
+    use crate::stelaro_common::source_map::SourceMap;
+    use crate::stelaro_diagnostics::DiagCtxt;
+    use crate::stelaro_diagnostics::emitter::SilentEmitter;
+    use crate::stelaro_interface::passes;
+    use crate::stelaro_mir_build::build_mir;
+    use crate::stelaro_session::session::CompilerPaths;
+    use crate::stelaro_session::{Input, Options, ParseSess, Session};
+
+    fn create_test_session(src: &str) -> Session {
+        let source_map = Rc::new(SourceMap::new());
+        let emitter = SilentEmitter::new();
+        let dcx = DiagCtxt::new(Box::new(emitter));
+
+        Session {
+            psess: ParseSess::with_dcx(dcx, source_map),
+            opts: Options::default(),
+            paths: CompilerPaths {
+                input: Input::Str {
+                    name: "borrowck_test".to_string(),
+                    input: src.to_string(),
+                },
+                output_dir: None,
+                output_file: None,
+                temps_dir: None,
+            },
+        }
+    }
+
+    fn get_sess_after_borrowck(src: &str) -> Session {
+        create_default_session_globals_then(|| {
+            let sess = create_test_session(src);
+            let stelo = passes::parse(&sess);
+
+            passes::create_and_enter_global_ctxt(&sess, |tcx| {
+                passes::lower_to_sir(tcx, stelo);
+                let typeck_results = passes::typeck(tcx);
+                for body in build_mir(tcx, &typeck_results) {
+                    mir_borrowck(tcx, &body);
+                }
+            });
+
+            sess
+        })
+    }
+
+    fn has_uninitialized_error(src: &str) -> bool {
+        get_sess_after_borrowck(src)
+            .dcx()
+            .has_err_code(ErrorCode::UseOfPossiblyUninitialized.into())
+    }
+
+    #[test]
+    fn test_initialized_on_every_path() {
+        let sess = get_sess_after_borrowck(
+            r#"
+    fn main(c: bool) {
+        let x: i32;
+        if c {
+            x = 1;
+        } else {
+            x = 2;
+        }
+        let y = x;
+
+        let z: i32;
+        if c {
+            z = 1;
+        } else {
+            return;
+        }
+        let w = z;
+
+        let v: i32;
+        loop {
+            if c {
+                v = 3;
+                break;
+            }
+        }
+        let u = v;
+    }
+    "#,
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_use_of_uninitialized() {
+        assert!(has_uninitialized_error(
+            r#"
+    fn main() {
+        let x: i32;
+        let y = x + 1;
+    }
+    "#
+        ));
+    }
+
+    #[test]
+    fn test_use_of_possibly_uninitialized_in_branch() {
+        assert!(has_uninitialized_error(
+            r#"
+    fn main(c: bool) {
+        let x: i32;
+        if c {
+            x = 1;
+        }
+        let y = x;
+    }
+    "#
+        ));
+    }
+
+    #[test]
+    fn test_use_of_possibly_uninitialized_after_loop() {
+        assert!(has_uninitialized_error(
+            r#"
+    fn main(c: bool) {
+        let x: i32;
+        while c {
+            x = 1;
+        }
+        let y = x;
+    }
+    "#
+        ));
+    }
+
+    #[test]
+    fn test_declaration_in_loop_is_uninitialized_on_each_iteration() {
+        assert!(has_uninitialized_error(
+            r#"
+    fn main(c: bool) {
+        loop {
+            let x: i32;
+            if c {
+                let y = x;
+                break;
+            }
+            x = 1;
+        }
+    }
+    "#
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+//! MIR の検査で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    USE_OF_POSSIBLY_UNINITIALIZED {
+        ja: "初期化されていない可能性のある `{name}` の使用",
+        en: "use of possibly-uninitialized `{name}`",
+    }
+    USE_OF_POSSIBLY_UNINITIALIZED_LABEL {
+        ja: "`{name}` はここで使用されていますが、初期化されていない可能性があります",
+        en: "`{name}` used here but it is possibly-uninitialized",
+    }
+    USE_OF_POSSIBLY_UNINITIALIZED_DECLARED_LABEL {
+        ja: "`{name}` はここで宣言されていますが、初期値が与えられていません",
+        en: "`{name}` declared here but left uninitialized",
+    }
+    USE_OF_POSSIBLY_UNINITIALIZED_HELP {
+        ja: "使用する前に、すべての経路で `{name}` に値を代入してください",
+        en: "assign a value to `{name}` on every path before it is used",
+    }
+}
@@ -0,0 +1,179 @@
+//! MIR に対する検査。
+//!
+//! rustc の `rustc_borrowck` に基づいて設計されています。
+//! 現在は、ユーザーが宣言した変数が初期化される前に使用されていないかを検査する。
+
+mod dataflow;
+mod diagnostics;
+pub mod messages;
+
+use std::collections::HashMap;
+
+use crate::stelaro_common::Span;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    Body, Local, Operand, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+    VarDebugInfo,
+};
+use crate::stelaro_sir::{
+    Res,
+    sir::{self, ExprKind, Node, PatKind},
+};
+
+use dataflow::{LocalSet, MaybeUninitializedLocals};
+use diagnostics::DiagsBorrowck;
+
+
+/// 関数本体の MIR を検査し、エラーを報告する。
+pub fn mir_borrowck<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) {
+    let uninits = MaybeUninitializedLocals::compute(body);
+    let mut checker = InitChecker {
+        tcx,
+        body,
+        uninit_uses: HashMap::new(),
+    };
+
+    for (bb, data) in body.basic_blocks.iter_enumerated() {
+        let mut state = uninits.entry_set(bb).clone();
+
+        for statement in &data.statements {
+            checker.check_statement(&state, statement);
+            MaybeUninitializedLocals::apply_statement_effect(&mut state, statement);
+        }
+        checker.check_terminator(&state, data.terminator());
+    }
+
+    checker.report();
+}
+
+struct InitChecker<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    /// 初期化されていない可能性のある変数と、その最も前にある使用の位置。
+    /// 同じ変数についてのエラーは一度だけ報告する。
+    uninit_uses: HashMap<Local, Span>,
+}
+
+impl<'a, 'tcx> InitChecker<'a, 'tcx> {
+    fn check_statement(&mut self, state: &LocalSet, statement: &Statement<'tcx>) {
+        let span = statement.source_info.span;
+
+        match &statement.kind {
+            StatementKind::Assign(assign) => match &assign.1 {
+                Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => {
+                    self.check_operand(state, operand, span);
+                }
+                Rvalue::BinaryOp(_, operands) => {
+                    self.check_operand(state, &operands.0, span);
+                    self.check_operand(state, &operands.1, span);
+                }
+            },
+            StatementKind::StorageLive(_) => {}
+        }
+    }
+
+    fn check_terminator(&mut self, state: &LocalSet, terminator: &Terminator<'tcx>) {
+        let span = terminator.source_info.span;
+
+        match &terminator.kind {
+            TerminatorKind::SwitchInt { discr, .. } | TerminatorKind::Assert { cond: discr, .. } => {
+                self.check_operand(state, discr, span);
+            }
+            TerminatorKind::Call { func, args, .. } => {
+                self.check_operand(state, func, span);
+                for arg in args {
+                    self.check_operand(state, arg, span);
+                }
+            }
+            TerminatorKind::Goto { .. }
+            | TerminatorKind::Return
+            | TerminatorKind::Unreachable => {}
+        }
+    }
+
+    /// `operand` が初期化されていない可能性のある変数を読む場合、その使用を記録する。
+    ///
+    /// `span` は `operand` を含む文や終端命令の位置。
+    fn check_operand(&mut self, state: &LocalSet, operand: &Operand<'tcx>, span: Span) {
+        let Operand::Copy(place) = operand else {
+            return;
+        };
+        let local = place.local;
+
+        if !state[local] {
+            return;
+        }
+
+        // 一時変数は構築の時点で必ず初期化されるため、ユーザーが宣言した変数のみが対象となる
+        let Some(var) = self.user_var(local) else {
+            return;
+        };
+
+        let use_span = self.find_use_span(var, span).unwrap_or(span);
+        self.uninit_uses
+            .entry(local)
+            .and_modify(|span| {
+                if use_span.start < span.start {
+                    *span = use_span;
+                }
+            })
+            .or_insert(use_span);
+    }
+
+    /// 基本ブロックの順序はソースコード上の順序と一致しないため、
+    /// 記録した使用を位置の順に報告する。
+    fn report(&mut self) {
+        let mut uses = std::mem::take(&mut self.uninit_uses).into_iter().collect::<Vec<_>>();
+        uses.sort_by_key(|(_, span)| (span.start, span.end));
+
+        for (local, use_span) in uses {
+            let var = self.user_var(local).unwrap();
+            DiagsBorrowck::use_of_possibly_uninitialized(
+                self.tcx.dcx(),
+                var.name.as_str(),
+                use_span,
+                var.span,
+            )
+            .emit();
+        }
+    }
+
+    fn user_var(&self, local: Local) -> Option<&'a VarDebugInfo> {
+        self.body
+            .var_debug_info
+            .iter()
+            .find(|var| var.local == local)
+    }
+
+    /// MIR の文や終端命令は式全体の位置しかもたないため、
+    /// SIR から `span` の中で `var` を参照している式を探し、より正確な位置を求める。
+    fn find_use_span(&self, var: &VarDebugInfo, span: Span) -> Option<Span> {
+        // クロージャの本体の MIR では、SIR のノードはクロージャを含む関数がもつ
+        let owner = self.tcx.local_def_id_to_sir_id(self.body.def_id).owner;
+        let nodes = &self.tcx.sir_owner_nodes(owner).nodes;
+
+        let var_id = nodes.iter().find_map(|node| match node.node {
+            Node::Pat(sir::Pat {
+                kind: PatKind::Binding(var_id, ident),
+                ..
+            }) if ident.span == var.span => Some(*var_id),
+            _ => None,
+        })?;
+
+        nodes
+            .iter()
+            .filter_map(|node| match node.node {
+                Node::Expr(sir::Expr {
+                    kind:
+                        ExprKind::Path(sir::Path {
+                            res: Res::Local(id),
+                            ..
+                        }),
+                    span: expr_span,
+                    ..
+                }) if *id == var_id && span.contains(expr_span) => Some(*expr_span),
+                _ => None,
+            })
+            .min_by_key(|expr_span| expr_span.start)
+    }
+}
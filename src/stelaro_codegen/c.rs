@@ -0,0 +1,733 @@
+//! `--emit=c` のための C99 のソースコードの生成。
+//!
+//! 最適化済みの MIR を、一つの C99 のファイルに変換する。
+//! 基本ブロックはラベルに、終端命令は `goto` に対応し、ループもこの形で表される。
+//! 生成したファイルは `cc out.c -lm` のように、任意の C コンパイラでビルドできる。
+//!
+//! 文字列 `str` は、バイト列へのポインタと長さの組 `stelo_str` で表す。
+//! リテラルは C の文字列リテラルとして静的領域に置き、連結した文字列はヒープに確保して解放しない。
+//!
+//! 関数ポインタは、シグネチャに関係なく共通の型 `stelo_fn` で保持し、
+//! 呼び出す際にシグネチャに対応する関数ポインタの型にキャストする。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::{DefId, IndexVec};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BasicBlockData, BinOp, Body, ConstOperand, ConstValue, Local, Operand,
+    PANIC_EXIT_CODE, RETURN_PLACE, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+use crate::stelaro_session::config::EmitKind;
+use crate::stelaro_std::{Intrinsic, intrinsic};
+use crate::stelaro_ty::{
+    Ty, TyKind,
+    ty::{FloatTy, IntTy, UintTy},
+};
+
+use super::{find_read_locals, foreign_callees, is_entry_fn, mangle, write_fn_cached};
+
+
+const INDENT: &str = "    ";
+
+/// 生成するファイルの先頭に置く宣言。
+const PRELUDE: &str = r#"/* Generated by stelaro. */
+#include <math.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct {
+    const char *ptr;
+    uintptr_t len;
+} stelo_str;
+
+typedef void (*stelo_fn)(void);
+
+static inline bool stelo_str_eq(stelo_str a, stelo_str b) {
+    return a.len == b.len && memcmp(a.ptr, b.ptr, a.len) == 0;
+}
+
+static inline stelo_str stelo_str_concat(stelo_str a, stelo_str b) {
+    char *ptr = malloc(a.len + b.len + 1);
+    if (ptr == NULL) abort();
+    memcpy(ptr, a.ptr, a.len);
+    memcpy(ptr + a.len, b.ptr, b.len);
+    return (stelo_str){ ptr, a.len + b.len };
+}
+
+#if defined(__GNUC__) || defined(__clang__)
+#define STELO_UNREACHABLE() __builtin_unreachable()
+#else
+#define STELO_UNREACHABLE() abort()
+#endif
+"#;
+
+/// パニックのメッセージを出力してプロセスを終了するマクロの定義。
+fn write_panic_macro(w: &mut dyn Write) -> fmt::Result {
+    writeln!(
+        w,
+        "#define STELO_PANIC(msg) (fflush(stdout), fputs(msg \"\\n\", stderr), exit({PANIC_EXIT_CODE}))"
+    )
+}
+
+/// すべての関数本体を、一つの C のソースコードに変換する。
+///
+/// ステロのルートに `main` 関数がある場合は、それを呼び出す C の `main` 関数も生成する。
+pub fn codegen_c<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>]) -> String {
+    let mut out = String::new();
+    write_c(tcx, bodies, &mut out).unwrap();
+    out
+}
+
+fn write_c<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>], w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{PRELUDE}")?;
+    write_panic_macro(w)?;
+    writeln!(w)?;
+
+    // 関数は定義の順序に関係なく呼び出せるため、先にすべてのプロトタイプを宣言する
+    for body in bodies {
+        FnCodegen::new(tcx, body).write_signature(w)?;
+        writeln!(w, ";")?;
+    }
+    // 外部ステロの関数は、リンクの際に解決される。標準ステロの関数はここで定義する
+    for def_id in foreign_callees(bodies) {
+        match intrinsic(tcx, def_id) {
+            Some(intrinsic) => write_intrinsic(tcx, def_id, intrinsic, w)?,
+            None => write_foreign_prototype(tcx, def_id, w)?,
+        }
+    }
+
+    for body in bodies {
+        writeln!(w)?;
+        write_fn_cached(tcx, EmitKind::C, body, w, |w| FnCodegen::new(tcx, body).write_fn(w))?;
+    }
+
+    if let Some(main) = bodies.iter().find(|body| is_entry_fn(tcx, body)) {
+        writeln!(w)?;
+        write_entry_fn(tcx, main, w)?;
+    }
+
+    Ok(())
+}
+
+/// C の `main` 関数を生成する。
+/// ステロの `main` 関数が整数を返す場合は、その値を終了コードとする。
+fn write_entry_fn<'tcx>(tcx: TyCtxt<'tcx>, main: &Body<'tcx>, w: &mut dyn Write) -> fmt::Result {
+    let name = mangle(tcx, main.def_id.to_def_id());
+
+    writeln!(w, "int main(void) {{")?;
+    if main.return_ty().is_integral() {
+        writeln!(w, "{INDENT}return (int){name}();")?;
+    } else {
+        writeln!(w, "{INDENT}{name}();")?;
+        writeln!(w, "{INDENT}return 0;")?;
+    }
+    writeln!(w, "}}")
+}
+
+/// 外部ステロの関数のプロトタイプを、メタデータのシグネチャから生成する。
+fn write_foreign_prototype(tcx: TyCtxt<'_>, def_id: DefId, w: &mut dyn Write) -> fmt::Result {
+    let fn_sig = tcx.fn_sig(def_id);
+    let return_ty = c_type(fn_sig.output).unwrap_or("void");
+    let params = c_param_list(fn_sig.inputs.iter().filter_map(|&ty| c_type(ty)).collect());
+    writeln!(w, "{return_ty} {}({params});", mangle(tcx, def_id))
+}
+
+/// プロトタイプの仮引数の型の並び。仮引数が無い場合は `void` とする。
+fn c_param_list(params: Vec<&str>) -> String {
+    if params.is_empty() { "void".to_string() } else { params.join(", ") }
+}
+
+/// 標準ステロの関数を、C の標準ライブラリを使う `static` 関数として定義する。
+fn write_intrinsic(
+    tcx: TyCtxt<'_>,
+    def_id: DefId,
+    intrinsic: Intrinsic,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    let fn_sig = tcx.fn_sig(def_id);
+    let param = c_type(fn_sig.inputs[0]).unwrap();
+    let return_ty = c_type(fn_sig.output).unwrap_or("void");
+    writeln!(w, "static {return_ty} {}({param} x) {{", mangle(tcx, def_id))?;
+
+    match intrinsic {
+        Intrinsic::Len => writeln!(w, "{INDENT}return x.len;")?,
+        Intrinsic::Print | Intrinsic::Println | Intrinsic::Eprint | Intrinsic::Eprintln => {
+            let stream = match intrinsic {
+                Intrinsic::Print | Intrinsic::Println => "stdout",
+                _ => "stderr",
+            };
+            writeln!(w, "{INDENT}fwrite(x.ptr, 1, x.len, {stream});")?;
+            if matches!(intrinsic, Intrinsic::Println | Intrinsic::Eprintln) {
+                writeln!(w, "{INDENT}fputc('\\n', {stream});")?;
+            }
+        }
+        Intrinsic::PrintInt(_) => writeln!(w, "{INDENT}printf(\"%lld\", (long long)x);")?,
+        Intrinsic::PrintUint(_) => {
+            writeln!(w, "{INDENT}printf(\"%llu\", (unsigned long long)x);")?
+        }
+        Intrinsic::PrintFloat(_) => writeln!(w, "{INDENT}printf(\"%.6f\", (double)x);")?,
+        Intrinsic::PrintBool => writeln!(w, "{INDENT}fputs(x ? \"true\" : \"false\", stdout);")?,
+        Intrinsic::PrintChar => {
+            // 文字は Unicode のスカラー値であるため、UTF-8 に符号化して出力する
+            writeln!(w, "{INDENT}char buf[4];")?;
+            writeln!(w, "{INDENT}int len;")?;
+            writeln!(w, "{INDENT}if (x < 0x80) {{")?;
+            writeln!(w, "{INDENT}{INDENT}buf[0] = (char)x;")?;
+            writeln!(w, "{INDENT}{INDENT}len = 1;")?;
+            writeln!(w, "{INDENT}}} else if (x < 0x800) {{")?;
+            writeln!(w, "{INDENT}{INDENT}buf[0] = (char)(0xc0 | (x >> 6));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[1] = (char)(0x80 | (x & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}len = 2;")?;
+            writeln!(w, "{INDENT}}} else if (x < 0x10000) {{")?;
+            writeln!(w, "{INDENT}{INDENT}buf[0] = (char)(0xe0 | (x >> 12));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[1] = (char)(0x80 | ((x >> 6) & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[2] = (char)(0x80 | (x & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}len = 3;")?;
+            writeln!(w, "{INDENT}}} else {{")?;
+            writeln!(w, "{INDENT}{INDENT}buf[0] = (char)(0xf0 | (x >> 18));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[1] = (char)(0x80 | ((x >> 12) & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[2] = (char)(0x80 | ((x >> 6) & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}buf[3] = (char)(0x80 | (x & 0x3f));")?;
+            writeln!(w, "{INDENT}{INDENT}len = 4;")?;
+            writeln!(w, "{INDENT}}}")?;
+            writeln!(w, "{INDENT}fwrite(buf, 1, len, stdout);")?;
+        }
+        Intrinsic::FormatInt(_) => write_format("%lld", "(long long)x", w)?,
+        Intrinsic::FormatUint(_) => write_format("%llu", "(unsigned long long)x", w)?,
+        Intrinsic::FormatFloat(_) => write_format("%.6f", "(double)x", w)?,
+        Intrinsic::FormatBool => writeln!(
+            w,
+            "{INDENT}return x ? (stelo_str){{ \"true\", 4 }} : (stelo_str){{ \"false\", 5 }};"
+        )?,
+        // `exit` は標準出力を書き出してから終了する
+        Intrinsic::Exit => writeln!(w, "{INDENT}exit((int)x);")?,
+        Intrinsic::Assert => unreachable!("bug: `assert` の呼び出しは MIR の構築の際に `Assert` に置き換えられる"),
+    }
+
+    writeln!(w, "}}")
+}
+
+/// `printf` と同じ書式で `arg` を書式化し、確保した文字列として返す本体を出力する。
+/// 連結した文字列と同様に、確保した領域は解放しない。
+fn write_format(format: &str, arg: &str, w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{INDENT}int len = snprintf(NULL, 0, \"{format}\", {arg});")?;
+    writeln!(w, "{INDENT}char *ptr = malloc((size_t)len + 1);")?;
+    writeln!(w, "{INDENT}if (ptr == NULL) abort();")?;
+    writeln!(w, "{INDENT}snprintf(ptr, (size_t)len + 1, \"{format}\", {arg});")?;
+    writeln!(w, "{INDENT}return (stelo_str){{ ptr, (uintptr_t)len }};")
+}
+
+/// 型に対応する C の型。値をもたない型の場合は `None` を返す。
+fn c_type(ty: Ty<'_>) -> Option<&'static str> {
+    let name = match *ty.kind() {
+        TyKind::Bool => "bool",
+        TyKind::Char => "uint32_t",
+        TyKind::Str => "stelo_str",
+        TyKind::Int(int_ty) => match int_ty {
+            IntTy::Isize => "intptr_t",
+            IntTy::I8 => "int8_t",
+            IntTy::I16 => "int16_t",
+            IntTy::I32 => "int32_t",
+            IntTy::I64 => "int64_t",
+            // C99 には 128 ビット整数が無いため、GCC と Clang の拡張を使う
+            IntTy::I128 => "__int128",
+        },
+        TyKind::Uint(uint_ty) => match uint_ty {
+            UintTy::Usize => "uintptr_t",
+            UintTy::U8 => "uint8_t",
+            UintTy::U16 => "uint16_t",
+            UintTy::U32 => "uint32_t",
+            UintTy::U64 => "uint64_t",
+            UintTy::U128 => "unsigned __int128",
+        },
+        TyKind::Float(FloatTy::F32) => "float",
+        TyKind::Float(FloatTy::F64) => "double",
+        TyKind::FnPtr(_) => "stelo_fn",
+        TyKind::FnDef(_) | TyKind::Closure(..) | TyKind::Unit | TyKind::Never => return None,
+        TyKind::Tuple([]) => return None,
+        TyKind::Tuple(_) | TyKind::Infer(_) | TyKind::Error(_) => {
+            unreachable!("bug: C に変換できない型: {ty:?}")
+        }
+    };
+
+    Some(name)
+}
+
+/// 整数型の演算を、オーバーフローしても未定義動作とならない符号なし整数で行うための型。
+///
+/// `int` より狭い型は `int` に昇格されて符号付きの演算になるため、少なくとも 32 ビットの型を使う。
+fn wrapping_type(ty: Ty<'_>) -> Option<&'static str> {
+    let bit_width = match *ty.kind() {
+        TyKind::Int(IntTy::Isize) | TyKind::Uint(UintTy::Usize) => return Some("uintptr_t"),
+        TyKind::Int(int_ty) => int_ty.bit_width(),
+        TyKind::Uint(uint_ty) => uint_ty.bit_width(),
+        _ => return None,
+    };
+
+    Some(match bit_width {
+        ..=32 => "uint32_t",
+        64 => "uint64_t",
+        _ => "unsigned __int128",
+    })
+}
+
+struct FnCodegen<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    /// 読まれる可能性のあるローカル変数。
+    /// 読まれない変数を宣言すると C コンパイラが警告するため、これらの変数のみを宣言する。
+    read_locals: IndexVec<Local, bool>,
+}
+
+impl<'a, 'tcx> FnCodegen<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>) -> Self {
+        FnCodegen {
+            tcx,
+            body,
+            read_locals: find_read_locals(body),
+        }
+    }
+
+    /// 値を格納する C の変数が宣言されるかどうか。
+    fn is_declared(&self, local: Local) -> bool {
+        self.read_locals[local] && c_type(self.local_ty(local)).is_some()
+    }
+
+    fn local_ty(&self, local: Local) -> Ty<'tcx> {
+        self.body.local_decls[local].ty
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        match operand {
+            Operand::Copy(place) => self.local_ty(place.local),
+            Operand::Constant(constant) => constant.ty,
+        }
+    }
+
+    fn write_signature(&self, w: &mut dyn Write) -> fmt::Result {
+        let return_ty = c_type(self.body.return_ty()).unwrap_or("void");
+        write!(w, "{return_ty} {}(", mangle(self.tcx, self.body.def_id.to_def_id()))?;
+
+        let params = self
+            .body
+            .args_iter()
+            .filter_map(|arg| c_type(self.local_ty(arg)).map(|ty| (arg, ty)))
+            .collect::<Vec<_>>();
+        if params.is_empty() {
+            write!(w, "void")?;
+        }
+        for (i, (arg, ty)) in params.into_iter().enumerate() {
+            if i != 0 {
+                write!(w, ", ")?;
+            }
+            write!(w, "{ty} {arg:?}")?;
+        }
+
+        write!(w, ")")
+    }
+
+    fn write_fn(&self, w: &mut dyn Write) -> fmt::Result {
+        self.write_signature(w)?;
+        writeln!(w, " {{")?;
+
+        let return_and_locals = std::iter::once(RETURN_PLACE).chain(self.body.vars_and_temps_iter());
+        let locals = return_and_locals
+            .filter(|&local| self.is_declared(local))
+            .collect::<Vec<_>>();
+        // 読まれない引数も、C コンパイラが警告しないように明示的に捨てる
+        let unread_args = self
+            .body
+            .args_iter()
+            .filter(|&arg| !self.read_locals[arg] && c_type(self.local_ty(arg)).is_some())
+            .collect::<Vec<_>>();
+
+        for &local in &locals {
+            writeln!(w, "{INDENT}{} {local:?};", c_type(self.local_ty(local)).unwrap())?;
+        }
+        for &arg in &unread_args {
+            writeln!(w, "{INDENT}(void){arg:?};")?;
+        }
+        if !locals.is_empty() || !unread_args.is_empty() {
+            writeln!(w)?;
+        }
+
+        let mut is_jump_target = IndexVec::<BasicBlock, bool>::from_elem_n(false, self.body.basic_blocks.len());
+        for data in self.body.basic_blocks.iter() {
+            for succ in data.terminator().successors() {
+                is_jump_target[succ] = true;
+            }
+        }
+
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            self.write_basic_block(bb, data, is_jump_target[bb], w)?;
+        }
+
+        writeln!(w, "}}")
+    }
+
+    fn write_basic_block(
+        &self,
+        bb: BasicBlock,
+        data: &BasicBlockData<'tcx>,
+        is_jump_target: bool,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        if is_jump_target {
+            writeln!(w, "{bb:?}:")?;
+        }
+
+        for statement in &data.statements {
+            self.write_statement(statement, w)?;
+        }
+
+        self.write_terminator(data.terminator(), w)
+    }
+
+    fn write_statement(&self, statement: &Statement<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match &statement.kind {
+            StatementKind::Assign(box (place, rvalue)) => {
+                // 右辺値は副作用をもたないため、格納先の変数が無ければ何も出力しない
+                if !self.is_declared(place.local) {
+                    return Ok(());
+                }
+
+                write!(w, "{INDENT}{:?} = ", place.local)?;
+                self.write_rvalue(rvalue, w)?;
+                writeln!(w, ";")
+            }
+            // C の変数は関数の先頭で宣言するため、何も出力しない
+            StatementKind::StorageLive(_) => Ok(()),
+        }
+    }
+
+    fn write_terminator(&self, terminator: &Terminator<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match &terminator.kind {
+            TerminatorKind::Goto { target } => writeln!(w, "{INDENT}goto {target:?};"),
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let discr_ty = self.operand_ty(discr);
+
+                if discr_ty.is_bool() && let [(0, else_)] = targets.values[..] {
+                    write!(w, "{INDENT}if (")?;
+                    self.write_operand(discr, w)?;
+                    writeln!(w, ") goto {:?};", targets.otherwise)?;
+                    return writeln!(w, "{INDENT}goto {else_:?};");
+                }
+
+                write!(w, "{INDENT}switch (")?;
+                self.write_operand(discr, w)?;
+                writeln!(w, ") {{")?;
+                for &(value, target) in &targets.values {
+                    write!(w, "{INDENT}case ")?;
+                    let const_ = if discr_ty.is_bool() {
+                        ConstValue::Bool(value != 0)
+                    } else {
+                        ConstValue::Int(value)
+                    };
+                    write_const(discr_ty, const_, w)?;
+                    writeln!(w, ": goto {target:?};")?;
+                }
+                writeln!(w, "{INDENT}default: goto {:?};", targets.otherwise)?;
+                writeln!(w, "{INDENT}}}")
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+            } => {
+                write!(w, "{INDENT}")?;
+                if self.is_declared(destination.local) {
+                    write!(w, "{:?} = ", destination.local)?;
+                }
+                match *self.operand_ty(func).kind() {
+                    TyKind::FnDef(def_id) => write!(w, "{}(", mangle(self.tcx, def_id))?,
+                    TyKind::FnPtr(sig_tys) => {
+                        let return_ty = c_type(sig_tys.output()).unwrap_or("void");
+                        let params = sig_tys.inputs().iter().filter_map(|&ty| c_type(ty));
+                        let params = c_param_list(params.collect());
+                        write!(w, "(({return_ty} (*)({params}))")?;
+                        self.write_operand(func, w)?;
+                        write!(w, ")(")?;
+                    }
+                    _ => unreachable!("bug: 関数ではない値の呼び出し: {func:?}"),
+                }
+
+                let args = args
+                    .iter()
+                    .filter(|arg| c_type(self.operand_ty(arg)).is_some())
+                    .collect::<Vec<_>>();
+                for (i, arg) in args.into_iter().enumerate() {
+                    if i != 0 {
+                        write!(w, ", ")?;
+                    }
+                    self.write_operand(arg, w)?;
+                }
+                writeln!(w, ");")?;
+
+                writeln!(w, "{INDENT}goto {target:?};")
+            }
+            TerminatorKind::Assert {
+                cond,
+                expected,
+                msg,
+                target,
+            } => {
+                let location = self
+                    .tcx
+                    .sess
+                    .source_map()
+                    .span_to_location_string(terminator.source_info.span);
+
+                write!(w, "{INDENT}if ({}", if *expected { "!" } else { "" })?;
+                self.write_operand(cond, w)?;
+                write!(w, ") STELO_PANIC(")?;
+                write_c_string(&msg.panic_message(self.tcx.sess.opts.lang, &location), w)?;
+                writeln!(w, ");")?;
+                writeln!(w, "{INDENT}goto {target:?};")
+            }
+            TerminatorKind::Return => {
+                if c_type(self.body.return_ty()).is_some() {
+                    writeln!(w, "{INDENT}return {RETURN_PLACE:?};")
+                } else {
+                    writeln!(w, "{INDENT}return;")
+                }
+            }
+            TerminatorKind::Unreachable => writeln!(w, "{INDENT}STELO_UNREACHABLE();"),
+        }
+    }
+
+    fn write_rvalue(&self, rvalue: &Rvalue<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match rvalue {
+            Rvalue::Use(operand) => self.write_operand(operand, w),
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => self.write_binary_op(*op, lhs, rhs, w),
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = self.operand_ty(operand);
+
+                match (op, wrapping_type(ty)) {
+                    (UnOp::Not, _) if ty.is_bool() => write!(w, "!")?,
+                    (UnOp::Not, Some(_)) => write!(w, "({})~", c_type(ty).unwrap())?,
+                    (UnOp::Neg, Some(wrapping_ty)) => {
+                        write!(w, "({})(0 - ({wrapping_ty})", c_type(ty).unwrap())?;
+                        self.write_operand(operand, w)?;
+                        return write!(w, ")");
+                    }
+                    (UnOp::Neg, None) => write!(w, "-")?,
+                    (UnOp::Not, None) => unreachable!("bug: `!` を適用できない型: {ty:?}"),
+                }
+                self.write_operand(operand, w)
+            }
+        }
+    }
+
+    fn write_binary_op(
+        &self,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        let ty = self.operand_ty(lhs);
+
+        if ty.is_str() {
+            let func = match op {
+                BinOp::Add => "stelo_str_concat",
+                BinOp::Eq => "stelo_str_eq",
+                BinOp::Ne => "!stelo_str_eq",
+                _ => unreachable!("bug: 文字列に適用できない演算子: {op:?}"),
+            };
+            write!(w, "{func}(")?;
+            self.write_operand(lhs, w)?;
+            write!(w, ", ")?;
+            self.write_operand(rhs, w)?;
+            return write!(w, ")");
+        }
+
+        let symbol = match op {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Eq => "==",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Ne => "!=",
+            BinOp::Ge => ">=",
+            BinOp::Gt => ">",
+            BinOp::AddOverflows | BinOp::SubOverflows | BinOp::MulOverflows => {
+                return self.write_overflow_check(op, lhs, rhs, w);
+            }
+            BinOp::DivOverflows => {
+                if !ty.is_signed() {
+                    return write!(w, "false");
+                }
+
+                let TyKind::Int(int_ty) = *ty.kind() else {
+                    unreachable!("bug: 整数ではない型の除算の検査: {ty:?}");
+                };
+                let bit_width = int_ty.bit_width();
+                write!(w, "(")?;
+                self.write_operand(lhs, w)?;
+                write!(w, " == ")?;
+                write_const(ty, ConstValue::Int(1 << (bit_width - 1)), w)?;
+                write!(w, " && ")?;
+                self.write_operand(rhs, w)?;
+                write!(w, " == ")?;
+                write_const(ty, ConstValue::Int(u128::MAX >> (128 - bit_width)), w)?;
+                return write!(w, ")");
+            }
+        };
+
+        if op.is_comparison() {
+            self.write_operand(lhs, w)?;
+            write!(w, " {symbol} ")?;
+            return self.write_operand(rhs, w);
+        }
+
+        match *ty.kind() {
+            // 浮動小数点数の剰余は `%` では計算できない
+            TyKind::Float(float_ty) if op == BinOp::Rem => {
+                let fmod = match float_ty {
+                    FloatTy::F32 => "fmodf",
+                    FloatTy::F64 => "fmod",
+                };
+                write!(w, "{fmod}(")?;
+                self.write_operand(lhs, w)?;
+                write!(w, ", ")?;
+                self.write_operand(rhs, w)?;
+                write!(w, ")")
+            }
+            TyKind::Float(_) => {
+                self.write_operand(lhs, w)?;
+                write!(w, " {symbol} ")?;
+                self.write_operand(rhs, w)
+            }
+            // 符号付き整数のオーバーフローは C では未定義動作のため、符号なし整数で計算して 2 の補数で折り返す
+            _ if matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul) => {
+                let wrapping_ty = wrapping_type(ty).unwrap();
+                write!(w, "({})(({wrapping_ty})", c_type(ty).unwrap())?;
+                self.write_operand(lhs, w)?;
+                write!(w, " {symbol} ({wrapping_ty})")?;
+                self.write_operand(rhs, w)?;
+                write!(w, ")")
+            }
+            _ => {
+                write!(w, "({})(", c_type(ty).unwrap())?;
+                self.write_operand(lhs, w)?;
+                write!(w, " {symbol} ")?;
+                self.write_operand(rhs, w)?;
+                write!(w, ")")
+            }
+        }
+    }
+
+    /// 加算、減算、乗算のオーバーフローを、GCC と Clang の組み込み関数で検査する。
+    /// 計算結果は C99 の複合リテラルに捨てる。
+    fn write_overflow_check(
+        &self,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        let builtin = match op {
+            BinOp::AddOverflows => "__builtin_add_overflow",
+            BinOp::SubOverflows => "__builtin_sub_overflow",
+            _ => "__builtin_mul_overflow",
+        };
+
+        write!(w, "{builtin}(")?;
+        self.write_operand(lhs, w)?;
+        write!(w, ", ")?;
+        self.write_operand(rhs, w)?;
+        write!(w, ", &({}){{0}})", c_type(self.operand_ty(lhs)).unwrap())
+    }
+
+    fn write_operand(&self, operand: &Operand<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match operand {
+            Operand::Copy(place) => write!(w, "{:?}", place.local),
+            Operand::Constant(box ConstOperand {
+                const_: ConstValue::FnPtr(def_id),
+                ..
+            }) => write!(w, "((stelo_fn){})", mangle(self.tcx, *def_id)),
+            Operand::Constant(box ConstOperand { ty, const_, .. }) => write_const(*ty, *const_, w),
+        }
+    }
+}
+
+fn write_const(ty: Ty<'_>, const_: ConstValue, w: &mut dyn Write) -> fmt::Result {
+    match (const_, *ty.kind()) {
+        (ConstValue::Int(bits), TyKind::Int(int_ty)) => {
+            let shift = 128 - int_ty.bit_width();
+            let value = ((bits << shift) as i128) >> shift;
+            let c_ty = c_type(ty).unwrap();
+
+            if value >= 0 {
+                write_int_literal(c_ty, value as u128, w)
+            } else {
+                // 最小値の絶対値は同じ型で表せないため、`-(x - 1) - 1` の形にする
+                write!(w, "(-")?;
+                write_int_literal(c_ty, (-(value + 1)) as u128, w)?;
+                write!(w, " - 1)")
+            }
+        }
+        (ConstValue::Int(bits), TyKind::Uint(_)) => write_int_literal(c_type(ty).unwrap(), bits, w),
+        (ConstValue::Float(symbol), TyKind::Float(float_ty)) => {
+            let text = symbol.as_str();
+            let suffix = match float_ty {
+                FloatTy::F32 => "f",
+                FloatTy::F64 => "",
+            };
+            // `1e-7` のように小数点を含まない表記は、そのままでは整数として扱われる
+            let point = if text.contains(['.', 'e']) { "" } else { ".0" };
+
+            if text.starts_with('-') {
+                write!(w, "({text}{point}{suffix})")
+            } else {
+                write!(w, "{text}{point}{suffix}")
+            }
+        }
+        (ConstValue::Bool(b), _) => write!(w, "{b}"),
+        (ConstValue::Char(c), _) => write!(w, "UINT32_C({})", c as u32),
+        (ConstValue::Str(symbol), _) => {
+            let s = symbol.as_str();
+            write!(w, "((stelo_str){{ ")?;
+            write_c_string(s, w)?;
+            write!(w, ", {} }})", s.len())
+        }
+        (const_, _) => unreachable!("bug: C に変換できない定数: {const_:?}: {ty:?}"),
+    }
+}
+
+/// 文字列を C の文字列リテラルとして出力する。
+fn write_c_string(s: &str, w: &mut dyn Write) -> fmt::Result {
+    write!(w, "\"")?;
+    for byte in s.bytes() {
+        match byte {
+            b'"' | b'\\' => write!(w, "\\{}", byte as char)?,
+            0x20..=0x7e => write!(w, "{}", byte as char)?,
+            // 8 進数のエスケープは 3 桁までしか続かないため、後続の文字と混ざらない
+            _ => write!(w, "\\{byte:03o}")?,
+        }
+    }
+    write!(w, "\"")
+}
+
+/// 非負の整数リテラルを `c_ty` 型の値として出力する。
+fn write_int_literal(c_ty: &str, value: u128, w: &mut dyn Write) -> fmt::Result {
+    if let Ok(value) = u64::try_from(value) {
+        write!(w, "(({c_ty})UINT64_C({value}))")
+    } else {
+        // 64 ビットを超えるリテラルは書けないため、上位と下位に分けて組み立てる
+        let (high, low) = ((value >> 64) as u64, value as u64);
+        write!(
+            w,
+            "(({c_ty})(((unsigned __int128)UINT64_C({high}) << 64) | UINT64_C({low})))"
+        )
+    }
+}
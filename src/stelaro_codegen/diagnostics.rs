@@ -0,0 +1,207 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsCodegen;
+
+impl<'dcx> DiagsCodegen {
+    pub fn unsupported_wasm_type(dcx: DiagCtxtHandle<'dcx>, ty: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::UnsupportedWasmType.into());
+        diag.set_message(dcx.translate(messages::UNSUPPORTED_WASM_TYPE, &[("ty", &ty)]));
+        diag.set_label(span, dcx.translate(messages::UNSUPPORTED_WASM_TYPE_LABEL, &[("ty", &ty)]));
+        diag.set_help(dcx.translate(messages::UNSUPPORTED_WASM_TYPE_HELP, &[]));
+
+        diag
+    }
+
+    pub fn unsupported_asm_type(dcx: DiagCtxtHandle<'dcx>, ty: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::UnsupportedAsmType.into());
+        diag.set_message(dcx.translate(messages::UNSUPPORTED_ASM_TYPE, &[("ty", &ty)]));
+        diag.set_label(span, dcx.translate(messages::UNSUPPORTED_ASM_TYPE_LABEL, &[("ty", &ty)]));
+        diag.set_help(dcx.translate(messages::UNSUPPORTED_ASM_TYPE_HELP, &[]));
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    UnsupportedWasmType = 700,
+    UnsupportedAsmType = 701,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::stelaro_codegen::{diagnostics::ErrorCode, wasm::codegen_wat, x86_64::codegen_asm};
+    use crate::stelaro_common::create_default_session_globals_then;
+    use crate::stelaro_common::source_map::SourceMap;
+    use crate::stelaro_diagnostics::DiagCtxt;
+    use crate::stelaro_diagnostics::emitter::SilentEmitter;
+    use crate::stelaro_interface::passes;
+    use crate::stelaro_session::session::CompilerPaths;
+    use crate::stelaro_session::{Input, Options, ParseSess, Session};
+
+    fn create_test_session(src: &str) -> Session {
+        let source_map = Rc::new(SourceMap::new());
+        let emitter = SilentEmitter::new();
+        let dcx = DiagCtxt::new(Box::new(emitter));
+
+        Session {
+            psess: ParseSess::with_dcx(dcx, source_map),
+            opts: Options::default(),
+            paths: CompilerPaths {
+                input: Input::Str {
+                    name: "codegen_test".to_string(),
+                    input: src.to_string(),
+                },
+                output_dir: None,
+                output_file: None,
+                temps_dir: None,
+            },
+        }
+    }
+
+    fn get_sess_after_codegen_wat(src: &str) -> Session {
+        create_default_session_globals_then(|| {
+            let sess = create_test_session(src);
+            let stelo = passes::parse(&sess);
+
+            passes::create_and_enter_global_ctxt(&sess, |tcx| {
+                passes::lower_to_sir(tcx, stelo);
+                let typeck_results = passes::typeck(tcx);
+                let mut bodies = passes::analysis(tcx, &typeck_results);
+                passes::optimize(tcx, &mut bodies);
+                codegen_wat(tcx, &bodies);
+            });
+
+            sess
+        })
+    }
+
+    fn get_sess_after_codegen_asm(src: &str) -> Session {
+        create_default_session_globals_then(|| {
+            let sess = create_test_session(src);
+            let stelo = passes::parse(&sess);
+
+            passes::create_and_enter_global_ctxt(&sess, |tcx| {
+                passes::lower_to_sir(tcx, stelo);
+                let typeck_results = passes::typeck(tcx);
+                let mut bodies = passes::analysis(tcx, &typeck_results);
+                passes::optimize(tcx, &mut bodies);
+                codegen_asm(tcx, &bodies);
+            });
+
+            sess
+        })
+    }
+
+    fn has_unsupported_type_error(src: &str) -> bool {
+        get_sess_after_codegen_wat(src)
+            .dcx()
+            .has_err_code(ErrorCode::UnsupportedWasmType.into())
+    }
+
+    #[test]
+    fn test_supported_types() {
+        let sess = get_sess_after_codegen_wat(
+            r#"
+    fn f(a: i8, b: u16, c: i64, d: usize, e: f32, g: f64, h: bool, i: char): u64 {
+        0
+    }
+    "#,
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_unsupported_param_type() {
+        assert!(has_unsupported_type_error(
+            r#"
+    fn square(x: i128): i64 {
+        0
+    }
+    "#,
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_return_type() {
+        assert!(has_unsupported_type_error(
+            r#"
+    fn big(): u128 {
+        1
+    }
+    "#,
+        ));
+    }
+
+    #[test]
+    fn test_unread_local_is_not_checked() {
+        let sess = get_sess_after_codegen_wat(
+            r#"
+    fn greet() {
+        let _s = "hello";
+    }
+    "#,
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_read_local_is_checked() {
+        assert!(has_unsupported_type_error(
+            r#"
+    fn f(c: bool): i32 {
+        let x: i128 = 5;
+        if c {
+            x = 6;
+        }
+        if x > 5 {
+            1
+        } else {
+            0
+        }
+    }
+    "#,
+        ));
+    }
+
+    #[test]
+    fn test_asm_supported_types() {
+        let sess = get_sess_after_codegen_asm(
+            r#"
+    fn f(a: i8, b: u16, c: i64, d: usize, h: bool, i: char): u64 {
+        0
+    }
+    "#,
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_asm_unsupported_float_type() {
+        let sess = get_sess_after_codegen_asm(
+            r#"
+    fn half(x: f64): f64 {
+        x / 2.0
+    }
+    "#,
+        );
+
+        assert!(sess.dcx().has_err_code(ErrorCode::UnsupportedAsmType.into()));
+    }
+}
@@ -0,0 +1,51 @@
+//! コード生成で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    UNSUPPORTED_WASM_TYPE {
+        ja: "型 `{ty}` は WebAssembly バックエンドでは扱えません",
+        en: "type `{ty}` is not supported by the WebAssembly backend",
+    }
+    UNSUPPORTED_WASM_TYPE_LABEL {
+        ja: "この値の型は `{ty}` です",
+        en: "this value has type `{ty}`",
+    }
+    UNSUPPORTED_WASM_TYPE_HELP {
+        ja: "WebAssembly バックエンドは、64 ビット以下の整数型、浮動小数点数型、`bool`、`char` のみを扱えます。`--emit=c` を使用してください",
+        en: "the WebAssembly backend only supports integers up to 64 bits, floats, `bool` and `char`; consider using `--emit=c`",
+    }
+    UNSUPPORTED_ASM_TYPE {
+        ja: "型 `{ty}` は x86-64 バックエンドでは扱えません",
+        en: "type `{ty}` is not supported by the x86-64 backend",
+    }
+    UNSUPPORTED_ASM_TYPE_LABEL {
+        ja: "この値の型は `{ty}` です",
+        en: "this value has type `{ty}`",
+    }
+    UNSUPPORTED_ASM_TYPE_HELP {
+        ja: "x86-64 バックエンドは、64 ビット以下の整数型、`bool`、`char` のみを扱えます。`--emit=c` を使用してください",
+        en: "the x86-64 backend only supports integers up to 64 bits, `bool` and `char`; consider using `--emit=c`",
+    }
+    LINK_TOOL_NOT_FOUND {
+        ja: "実行ファイルを生成するための `{tool}` が見つかりませんでした",
+        en: "could not find `{tool}`, which is needed to produce an executable",
+    }
+    LINK_TOOL_FAILED {
+        ja: "`{tool}` が失敗しました:\n{stderr}",
+        en: "`{tool}` failed:\n{stderr}",
+    }
+    LINK_IO_ERROR {
+        ja: "実行ファイルの生成中にエラーが発生しました: {error}",
+        en: "an error occurred while producing the executable: {error}",
+    }
+    LINK_EXTERN_ASM_NOT_FOUND {
+        ja: "ステロ `{name}` のアセンブリ `{path}` が見つかりませんでした",
+        en: "could not find the assembly `{path}` of the stelo `{name}`",
+    }
+    LINK_EXTERN_ASM_NOT_FOUND_HELP {
+        ja: "ライブラリを `--stelo-type=lib --emit=asm` でコンパイルし、メタデータと同じディレクトリにアセンブリを出力してください",
+        en: "compile the library with `--stelo-type=lib --emit=asm` so that its assembly is written next to its metadata",
+    }
+}
@@ -1 +1,252 @@
+//! 最適化済みの MIR からのコード生成。
+//!
+//! バックエンドごとにモジュールを分け、それぞれが `--emit` の一つの種類に対応する。
 
+pub mod c;
+mod diagnostics;
+pub mod messages;
+pub mod wasm;
+pub mod x86_64;
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_common::{DefId, IndexVec, Span, Symbol};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    Body, ConstValue, Local, Operand, RETURN_PLACE, Rvalue, StatementKind, TerminatorKind,
+};
+use crate::stelaro_session::config::{EmitKind, SteloType};
+use crate::stelaro_ty::{Ty, TyKind};
+
+
+/// 実行可能なステロのルートにある、引数をとらない `main` 関数かどうか。
+fn is_entry_fn(tcx: TyCtxt<'_>, body: &Body<'_>) -> bool {
+    tcx.sess.opts.stelo_type == SteloType::Bin
+        && tcx.def_path_str(body.def_id.to_def_id()) == "main"
+        && body.arg_count == 0
+}
+
+/// 関数本体の文と `Call` の被演算子を、現れる順にたどる。
+fn for_each_value_operand<'a, 'tcx>(body: &'a Body<'tcx>, mut f: impl FnMut(&'a Operand<'tcx>)) {
+    for data in body.basic_blocks.iter() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(box (_, rvalue)) = &statement.kind {
+                match rvalue {
+                    Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => f(operand),
+                    Rvalue::BinaryOp(_, box (lhs, rhs)) => {
+                        f(lhs);
+                        f(rhs);
+                    }
+                }
+            }
+        }
+
+        if let TerminatorKind::Call { func, args, .. } = &data.terminator().kind {
+            f(func);
+            args.iter().for_each(&mut f);
+        }
+    }
+}
+
+/// 関数本体から呼び出している、または関数ポインタとして参照している外部ステロの関数を、
+/// 最初に現れた順に返す。
+///
+/// 外部ステロの関数の本体は生成しないため、バックエンドはこれらを宣言として出力する。
+fn foreign_callees(bodies: &[Body<'_>]) -> Vec<DefId> {
+    let mut callees = Vec::new();
+    for body in bodies {
+        for_each_value_operand(body, |operand| {
+            let Operand::Constant(constant) = operand else {
+                return;
+            };
+            let def_id = match (constant.const_, *constant.ty.kind()) {
+                (ConstValue::FnPtr(def_id), _) | (_, TyKind::FnDef(def_id)) => def_id,
+                _ => return,
+            };
+            if !def_id.is_local() && !callees.contains(&def_id) {
+                callees.push(def_id);
+            }
+        });
+    }
+    callees
+}
+
+/// 関数本体が関数ポインタとして参照している関数を、最初に現れた順に返す。
+fn fn_ptr_consts(bodies: &[Body<'_>]) -> Vec<DefId> {
+    let mut def_ids = Vec::new();
+    for body in bodies {
+        for_each_value_operand(body, |operand| {
+            if let Operand::Constant(constant) = operand
+                && let ConstValue::FnPtr(def_id) = constant.const_
+                && !def_ids.contains(&def_id)
+            {
+                def_ids.push(def_id);
+            }
+        });
+    }
+    def_ids
+}
+
+/// 関数本体が呼び出す関数のシグネチャに含まれる型と、その呼び出しの位置。
+/// 関数ポインタを通した呼び出しでは、関数ポインタの型のシグネチャを使う。
+///
+/// 定数の引数は変数を経由しないため、バックエンドが扱える型かどうかはシグネチャで調べる。
+fn callee_sig_tys<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Vec<(Ty<'tcx>, Span)> {
+    let mut tys = Vec::new();
+    for data in body.basic_blocks.iter() {
+        let terminator = data.terminator();
+        let TerminatorKind::Call { func, .. } = &terminator.kind else {
+            continue;
+        };
+        let fn_sig = match *operand_ty(body, func).kind() {
+            TyKind::FnDef(def_id) => tcx.fn_sig(def_id),
+            TyKind::FnPtr(sig_tys) => sig_tys.to_fn_sig(),
+            _ => continue,
+        };
+        for &ty in fn_sig.inputs.iter().chain([&fn_sig.output]) {
+            tys.push((ty, terminator.source_info.span));
+        }
+    }
+    tys
+}
+
+fn operand_ty<'tcx>(body: &Body<'tcx>, operand: &Operand<'tcx>) -> Ty<'tcx> {
+    match operand {
+        Operand::Copy(place) => body.local_decls[place.local].ty,
+        Operand::Constant(constant) => constant.ty,
+    }
+}
+
+/// 関数本体に現れる文字列のリテラルを、重複を除いて最初に現れた順に返す。
+fn str_literals(body: &Body<'_>) -> Vec<Symbol> {
+    let mut literals = Vec::new();
+    for_each_value_operand(body, |operand| {
+        if let Operand::Constant(constant) = operand
+            && let ConstValue::Str(symbol) = constant.const_
+            && !literals.contains(&symbol)
+        {
+            literals.push(symbol);
+        }
+    });
+    literals
+}
+
+/// 文字列の比較か連結を含むかどうか。
+fn has_str_op(body: &Body<'_>) -> bool {
+    body.basic_blocks.iter().flat_map(|data| &data.statements).any(|statement| {
+        let StatementKind::Assign(box (_, Rvalue::BinaryOp(_, box (lhs, _)))) = &statement.kind
+        else {
+            return false;
+        };
+        let ty = match lhs {
+            Operand::Copy(place) => body.local_decls[place.local].ty,
+            Operand::Constant(constant) => constant.ty,
+        };
+        ty.is_str()
+    })
+}
+
+/// 関数のコードを出力する。
+///
+/// インクリメンタルコンパイルでは、前回のコンパイルから変更されていない関数の出力を再利用し、
+/// それ以外は `generate` で生成した出力を次のコンパイルのために記録する。
+fn write_fn_cached<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    kind: EmitKind,
+    body: &Body<'tcx>,
+    w: &mut dyn Write,
+    generate: impl FnOnce(&mut dyn Write) -> fmt::Result,
+) -> fmt::Result {
+    let def_id = body.def_id;
+    let reused = tcx
+        .incremental
+        .borrow_mut()
+        .as_mut()
+        .map(|cache| cache.reuse_codegen(tcx, def_id, kind));
+
+    match reused {
+        None => generate(w),
+        Some(Some(output)) => w.write_str(&output),
+        Some(None) => {
+            let mut output = String::new();
+            generate(&mut output)?;
+            w.write_str(&output)?;
+
+            if let Some(cache) = tcx.incremental.borrow_mut().as_mut() {
+                cache.record_codegen(tcx, def_id, kind, output);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 定義のパスから、C や WebAssembly、アセンブリの識別子として使える一意な名前を作る。
+///
+/// パスの各要素を長さとともに連結するため、異なるパスが同じ名前になることはない。
+/// 識別子に使えない文字は `_u{コードポイント}_` に、`_` は `__` に置き換える。
+///
+/// ライブラリのステロの定義は、他のステロとリンクしても衝突しないよう、ステロの名前をパスの先頭に加える。
+/// ライブラリを読み込んだステロでは、外部ステロの定義に対して同じ名前を作る。
+fn mangle(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let is_lib = !def_id.is_local() || tcx.sess.opts.stelo_type == SteloType::Lib;
+    let stelo = is_lib.then(|| tcx.stelo_name(def_id.stelo).to_string());
+    let def_path = tcx.def_path(def_id);
+    let path = def_path.data.iter().map(|component| component.to_string());
+
+    let mut mangled = String::from("stelo_");
+    for component in stelo.into_iter().chain(path) {
+        let mut encoded = String::new();
+        for c in component.chars() {
+            match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' => encoded.push(c),
+                '_' => encoded.push_str("__"),
+                _ => write!(encoded, "_u{:x}_", c as u32).unwrap(),
+            }
+        }
+        write!(mangled, "{}{encoded}", encoded.len()).unwrap();
+    }
+    mangled
+}
+
+/// 読まれる可能性のあるローカル変数。戻り値は `Return` によって暗黙に読まれる。
+fn find_read_locals(body: &Body<'_>) -> IndexVec<Local, bool> {
+    let mut read = IndexVec::from_elem_n(false, body.local_decls.len());
+    let visit_operand = |operand: &Operand<'_>, read: &mut IndexVec<Local, bool>| {
+        if let Operand::Copy(place) = operand {
+            read[place.local] = true;
+        }
+    };
+
+    for data in body.basic_blocks.iter() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(box (_, rvalue)) = &statement.kind {
+                match rvalue {
+                    Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => {
+                        visit_operand(operand, &mut read);
+                    }
+                    Rvalue::BinaryOp(_, box (lhs, rhs)) => {
+                        visit_operand(lhs, &mut read);
+                        visit_operand(rhs, &mut read);
+                    }
+                }
+            }
+        }
+
+        match &data.terminator().kind {
+            TerminatorKind::SwitchInt { discr, .. } | TerminatorKind::Assert { cond: discr, .. } => {
+                visit_operand(discr, &mut read);
+            }
+            TerminatorKind::Call { func, args, .. } => {
+                visit_operand(func, &mut read);
+                for arg in args {
+                    visit_operand(arg, &mut read);
+                }
+            }
+            // 戻り値は `Return` で読まれる。`Return` に到達しない本体では戻り値は読まれない
+            TerminatorKind::Return => read[RETURN_PLACE] = true,
+            TerminatorKind::Goto { .. } | TerminatorKind::Unreachable => {}
+        }
+    }
+
+    read
+}
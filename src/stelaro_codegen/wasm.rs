@@ -0,0 +1,1081 @@
+//! `--emit=wat` のための WebAssembly のテキスト形式 (WAT) の生成。
+//!
+//! MIR の制御フローグラフは任意の `goto` を含むため、そのままでは WebAssembly の
+//! 構造化された制御 (`block`, `loop`, `if`) に対応しない。
+//! Ramsey による "Beyond Relooper" の方法で、支配木に沿って構造化された制御に変換する。
+//!
+//! - ループの先頭のブロック (後ろ向きの辺の移動先) は `loop` で囲み、
+//!   後ろ向きの辺 (`continue` やループの末尾) は `loop` への `br` とする
+//! - 複数の前向きの辺が合流するブロックは、その直前で終わる `block` を移動元の周りに置き、
+//!   合流する辺 (`break` や `if` の後) は `block` への `br` とする
+//! - それ以外の前向きの辺の移動先は、移動元の中にそのまま置く
+//!
+//! ステロのソースコードは構造化されているため、MIR の制御フローグラフは常に可約であり、
+//! この方法で変換できる。
+//!
+//! WebAssembly には標準エラー出力が無いため、パニックはメッセージを出力せず、
+//! `unreachable` によるトラップとする。
+//!
+//! 文字列 `str` は、線形メモリ上のアドレスを下位 32 ビットに、バイト数を上位 32 ビットに
+//! 詰めた `i64` で表す。リテラルはデータセグメントに置き、内容のハッシュを名前とする
+//! グローバル変数から参照する。関数の出力はリテラルの配置に依存しないため、
+//! インクリメンタルコンパイルで再利用できる。連結した文字列はメモリの末尾から確保し、解放しない。
+//! 文字列を扱うモジュールは、ホストが `std` の関数で文字列を読めるよう、メモリを `memory` としてエクスポートする。
+//! `format_i32` のように文字列を返す `std` の関数のため、確保する位置も `stelo_heap` としてエクスポートする。
+//! ホストは `stelo_heap` の位置に文字列を書き込み (足りなければメモリを拡張し)、書き込んだバイト数だけ進める。
+//!
+//! 関数ポインタは、関数テーブルの位置を表す `i32` とし、`call_indirect` で呼び出す。
+//! 文字列のリテラルと同様に、関数の位置は関数の名前から作った名前のグローバル変数から参照し、
+//! 呼び出しのシグネチャはその値型から作った名前の型で指定する。
+
+use std::fmt::{self, Write};
+use std::hash::Hash;
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::{DefId, Hash64, IndexVec, StableHasher, Symbol};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BinOp, Body, ConstOperand, ConstValue, Local, Operand, RETURN_PLACE, Rvalue,
+    START_BLOCK, StatementKind, TerminatorKind,
+    traversal::{Dominators, predecessors},
+};
+use crate::stelaro_session::config::{EmitKind, SteloType};
+use crate::stelaro_std::{Intrinsic, intrinsic};
+use crate::stelaro_ty::{
+    FnSigTys, Ty, TyKind,
+    ty::{FloatTy, IntTy, UintTy},
+};
+
+use super::diagnostics::DiagsCodegen;
+use super::{
+    callee_sig_tys, find_read_locals, fn_ptr_consts, foreign_callees, has_str_op, is_entry_fn,
+    mangle, operand_ty, str_literals, write_fn_cached,
+};
+
+
+const INDENT: &str = "  ";
+
+/// すべての関数本体を、一つの WebAssembly モジュールに変換する。
+///
+/// ステロのルートに `main` 関数がある場合は、`main` という名前でエクスポートする。
+/// ライブラリのステロでは、すべての関数をシンボル名でエクスポートする。
+/// WebAssembly で表せない型を使う関数はエラーを報告し、モジュールに含めない。
+pub fn codegen_wat<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>]) -> String {
+    let mut out = String::new();
+    write_wat(tcx, bodies, &mut out).unwrap();
+    out
+}
+
+fn write_wat<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>], w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "(module")?;
+
+    // インポートは関数の定義よりも前に置く必要がある
+    let foreign_callees = foreign_callees(bodies);
+    for &def_id in &foreign_callees {
+        write_import(tcx, def_id, w)?;
+    }
+
+    let uses_str = bodies.iter().any(|body| {
+        body.local_decls.iter().any(|decl| decl.ty.is_str())
+            || callee_sig_tys(tcx, body).iter().any(|(ty, _)| ty.is_str())
+    });
+    if uses_str {
+        write_str_data(bodies, w)?;
+    }
+    write_fn_table(tcx, bodies, w)?;
+
+    for body in bodies {
+        // 再利用できる出力は型を検査済みであるため、`check_types` は生成する場合にのみ呼ぶ
+        write_fn_cached(tcx, EmitKind::Wat, body, w, |w| {
+            if check_types(tcx, body) {
+                FnCodegen::new(tcx, body).write_fn(w)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if bodies.iter().any(has_str_op) {
+        write_str_helpers(w)?;
+    }
+    for &def_id in &foreign_callees {
+        if intrinsic(tcx, def_id) == Some(Intrinsic::Len) {
+            write_len(tcx, def_id, w)?;
+        }
+    }
+
+    if let Some(main) = bodies.iter().find(|body| is_entry_fn(tcx, body)) {
+        let name = mangle(tcx, main.def_id.to_def_id());
+        writeln!(w, "{INDENT}(export \"main\" (func ${name}))")?;
+    }
+
+    writeln!(w, ")")
+}
+
+/// 文字列のリテラルを参照するグローバル変数の名前。
+fn str_global(symbol: Symbol) -> String {
+    let mut hasher = StableHasher::new();
+    symbol.as_str().hash(&mut hasher);
+    let hash: Hash64 = hasher.finish();
+    format!("$stelo_str_{:016x}", hash.as_u64())
+}
+
+/// 線形メモリと、文字列のリテラルのデータセグメントとグローバル変数、
+/// および連結した文字列やホストが返す文字列を確保する位置を表すグローバル変数 `$stelo_heap` を出力する。
+fn write_str_data(bodies: &[Body<'_>], w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{INDENT}(memory (export \"memory\") 1)")?;
+
+    let mut literals = Vec::new();
+    for body in bodies {
+        for symbol in str_literals(body) {
+            if !literals.contains(&symbol) {
+                literals.push(symbol);
+            }
+        }
+    }
+
+    let mut offset = 0u64;
+    for symbol in literals {
+        let bytes = symbol.as_str().as_bytes();
+        if !bytes.is_empty() {
+            // テキスト形式の字句を単純に保つため、英数字以外のバイトはすべてエスケープする
+            write!(w, "{INDENT}(data (i32.const {offset}) \"")?;
+            for &byte in bytes {
+                if byte.is_ascii_alphanumeric() {
+                    write!(w, "{}", byte as char)?;
+                } else {
+                    write!(w, "\\{byte:02x}")?;
+                }
+            }
+            writeln!(w, "\")")?;
+        }
+
+        let packed = offset | (bytes.len() as u64) << 32;
+        writeln!(w, "{INDENT}(global {} i64 (i64.const {packed}))", str_global(symbol))?;
+        offset += bytes.len() as u64;
+    }
+
+    writeln!(w, "{INDENT}(global $stelo_heap (mut i32) (i32.const {offset}))")?;
+    writeln!(w, "{INDENT}(export \"stelo_heap\" (global $stelo_heap))")
+}
+
+/// 関数ポインタを通した呼び出しのシグネチャを表す型の名前。
+/// シグネチャに WebAssembly で表せない型を含む場合は `Err` を返す。
+fn sig_type_name(sig_tys: FnSigTys<'_>) -> Result<String, ()> {
+    let mut name = String::from("$stelo_sig");
+    for &ty in sig_tys.inputs() {
+        if let Some(vt) = wasm_type(ty)? {
+            write!(name, "_{vt}").unwrap();
+        }
+    }
+    name.push_str("_to");
+    if let Some(vt) = wasm_type(sig_tys.output())? {
+        write!(name, "_{vt}").unwrap();
+    }
+    Ok(name)
+}
+
+/// 関数テーブルでの関数の位置を保持するグローバル変数の名前。
+fn fn_ptr_global(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    format!("${}_ptr", mangle(tcx, def_id))
+}
+
+/// 関数ポインタとして参照される関数の関数テーブルと、その位置を保持するグローバル変数、
+/// および関数ポインタを通した呼び出しのシグネチャの型を出力する。
+fn write_fn_table<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>], w: &mut dyn Write) -> fmt::Result {
+    let mut sig_types = Vec::new();
+    for body in bodies {
+        for data in body.basic_blocks.iter() {
+            if let TerminatorKind::Call { func, .. } = &data.terminator().kind
+                && let TyKind::FnPtr(sig_tys) = *operand_ty(body, func).kind()
+                && let Ok(name) = sig_type_name(sig_tys)
+                && !sig_types.iter().any(|(other, _)| *other == name)
+            {
+                sig_types.push((name, sig_tys));
+            }
+        }
+    }
+    for (name, sig_tys) in sig_types {
+        write!(w, "{INDENT}(type {name} (func")?;
+        for &ty in sig_tys.inputs() {
+            if let Some(vt) = value_type(ty) {
+                write!(w, " (param {vt})")?;
+            }
+        }
+        if let Some(vt) = value_type(sig_tys.output()) {
+            write!(w, " (result {vt})")?;
+        }
+        writeln!(w, "))")?;
+    }
+
+    let funcs = fn_ptr_consts(bodies);
+    if funcs.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "{INDENT}(table {} funcref)", funcs.len())?;
+    write!(w, "{INDENT}(elem (i32.const 0) func")?;
+    for &def_id in &funcs {
+        write!(w, " ${}", mangle(tcx, def_id))?;
+    }
+    writeln!(w, ")")?;
+    for (index, &def_id) in funcs.iter().enumerate() {
+        writeln!(w, "{INDENT}(global {} i32 (i32.const {index}))", fn_ptr_global(tcx, def_id))?;
+    }
+    Ok(())
+}
+
+/// 文字列の比較と連結を行う関数 `$stelo_str_eq` と `$stelo_str_concat` を出力する。
+fn write_str_helpers(w: &mut dyn Write) -> fmt::Result {
+    const EQ: &str = "\
+(func $stelo_str_eq (param $a i64) (param $b i64) (result i32)
+  (local $len i32)
+  (local $i i32)
+  local.get $a
+  i64.const 32
+  i64.shr_u
+  local.get $b
+  i64.const 32
+  i64.shr_u
+  i64.ne
+  if
+    i32.const 0
+    return
+  end
+  local.get $a
+  i64.const 32
+  i64.shr_u
+  i32.wrap_i64
+  local.set $len
+  block $done
+    loop $loop
+      local.get $i
+      local.get $len
+      i32.ge_u
+      if
+        br $done
+      end
+      local.get $a
+      i32.wrap_i64
+      local.get $i
+      i32.add
+      i32.load8_u
+      local.get $b
+      i32.wrap_i64
+      local.get $i
+      i32.add
+      i32.load8_u
+      i32.ne
+      if
+        i32.const 0
+        return
+      end
+      local.get $i
+      i32.const 1
+      i32.add
+      local.set $i
+      br $loop
+    end
+  end
+  i32.const 1
+)";
+
+    // 確保する領域がメモリの末尾を超える場合は、64 KiB のページ単位でメモリを拡張する
+    const CONCAT: &str = "\
+(func $stelo_str_concat (param $a i64) (param $b i64) (result i64)
+  (local $ptr i32)
+  (local $a_len i32)
+  (local $len i32)
+  global.get $stelo_heap
+  local.set $ptr
+  local.get $a
+  i64.const 32
+  i64.shr_u
+  i32.wrap_i64
+  local.set $a_len
+  local.get $b
+  i64.const 32
+  i64.shr_u
+  i32.wrap_i64
+  local.get $a_len
+  i32.add
+  local.set $len
+  local.get $ptr
+  local.get $len
+  i32.add
+  global.set $stelo_heap
+  global.get $stelo_heap
+  memory.size
+  i32.const 16
+  i32.shl
+  i32.gt_u
+  if
+    global.get $stelo_heap
+    memory.size
+    i32.const 16
+    i32.shl
+    i32.sub
+    i32.const 65535
+    i32.add
+    i32.const 16
+    i32.shr_u
+    memory.grow
+    i32.const -1
+    i32.eq
+    if
+      unreachable
+    end
+  end
+  local.get $ptr
+  local.get $a
+  i32.wrap_i64
+  local.get $a_len
+  memory.copy
+  local.get $ptr
+  local.get $a_len
+  i32.add
+  local.get $b
+  i32.wrap_i64
+  local.get $len
+  local.get $a_len
+  i32.sub
+  memory.copy
+  local.get $ptr
+  i64.extend_i32_u
+  local.get $len
+  i64.extend_i32_u
+  i64.const 32
+  i64.shl
+  i64.or
+)";
+
+    for func in [EQ, CONCAT] {
+        for line in func.lines() {
+            writeln!(w, "{INDENT}{line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// 標準ステロの `len` を、文字列の上位 32 ビットを取り出す関数として定義する。
+fn write_len(tcx: TyCtxt<'_>, def_id: DefId, w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{INDENT}(func ${} (param $s i64) (result i32)", mangle(tcx, def_id))?;
+    for instr in ["local.get $s", "i64.const 32", "i64.shr_u", "i32.wrap_i64"] {
+        writeln!(w, "{INDENT}{INDENT}{instr}")?;
+    }
+    writeln!(w, "{INDENT})")
+}
+
+/// 外部ステロの関数を、ステロの名前をモジュール名としてインポートする。
+/// 標準ステロの関数はホストが実装するものとし、`std` モジュールから `print_i32` のような
+/// `std` での名前でインポートする。
+///
+/// シグネチャに表せない型を含む関数は、それを呼び出す関数が `check_types` で報告されるため、
+/// インポートしない。`len` はホストに頼らずモジュールの中で定義する。
+fn write_import(tcx: TyCtxt<'_>, def_id: DefId, w: &mut dyn Write) -> fmt::Result {
+    if intrinsic(tcx, def_id) == Some(Intrinsic::Len) {
+        return Ok(());
+    }
+
+    let fn_sig = tcx.fn_sig(def_id);
+    let Ok(params) = fn_sig.inputs.iter().map(|&ty| wasm_type(ty)).collect::<Result<Vec<_>, _>>()
+    else {
+        return Ok(());
+    };
+    let Ok(result) = wasm_type(fn_sig.output) else {
+        return Ok(());
+    };
+
+    let name = mangle(tcx, def_id);
+    let field = intrinsic(tcx, def_id).map_or_else(|| name.clone(), Intrinsic::name);
+    write!(w, "{INDENT}(import \"{}\" \"{field}\" (func ${name}", tcx.stelo_name(def_id.stelo))?;
+    for ty in params.into_iter().flatten() {
+        write!(w, " (param {ty})")?;
+    }
+    if let Some(ty) = result {
+        write!(w, " (result {ty})")?;
+    }
+    writeln!(w, "))")
+}
+
+/// 関数のシグネチャと、読まれる可能性のあるローカル変数の型が WebAssembly で表せるかを調べる。
+/// 表せない型があれば、型ごとに最初の変数についてエラーを報告する。
+/// 呼び出す関数のシグネチャに表せない型があれば、その呼び出しについてエラーを報告する。
+///
+/// 読まれない変数への代入は出力されないため、その型は問わない。
+fn check_types<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> bool {
+    let read_locals = find_read_locals(body);
+    let mut reported = Vec::new();
+
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        let is_signature = local.as_usize() <= body.arg_count;
+        if !is_signature && !read_locals[local] {
+            continue;
+        }
+
+        if wasm_type(decl.ty).is_err() && !reported.contains(&decl.ty) {
+            DiagsCodegen::unsupported_wasm_type(tcx.dcx(), &tcx.ty_string(decl.ty), decl.span)
+                .emit();
+            reported.push(decl.ty);
+        }
+    }
+
+    for (ty, span) in callee_sig_tys(tcx, body) {
+        if wasm_type(ty).is_err() && !reported.contains(&ty) {
+            DiagsCodegen::unsupported_wasm_type(tcx.dcx(), &tcx.ty_string(ty), span).emit();
+            reported.push(ty);
+        }
+    }
+
+    reported.is_empty()
+}
+
+/// 型に対応する WebAssembly の値型。値をもたない型の場合は `None` を返す。
+///
+/// 32 ビット以下の整数型、`bool`、`char` は `i32` で表す。
+/// `isize` と `usize` は、wasm32 のポインタの幅に合わせて `i32` とする。
+/// 文字列はアドレスとバイト数を詰めた `i64` で、関数ポインタは関数テーブルの位置の `i32` で表す。
+/// 128 ビットの整数は一つの値型に収まらないため、まだ扱えない。
+fn wasm_type(ty: Ty<'_>) -> Result<Option<&'static str>, ()> {
+    let name = match *ty.kind() {
+        TyKind::Bool | TyKind::Char | TyKind::FnPtr(_) => "i32",
+        TyKind::Int(IntTy::I64) | TyKind::Uint(UintTy::U64) | TyKind::Str => "i64",
+        TyKind::Int(IntTy::I128) | TyKind::Uint(UintTy::U128) => return Err(()),
+        TyKind::Int(_) | TyKind::Uint(_) => "i32",
+        TyKind::Float(FloatTy::F32) => "f32",
+        TyKind::Float(FloatTy::F64) => "f64",
+        TyKind::FnDef(_) | TyKind::Closure(..) | TyKind::Unit | TyKind::Never | TyKind::Tuple([]) => {
+            return Ok(None);
+        }
+        TyKind::Tuple(_) | TyKind::Infer(_) | TyKind::Error(_) => {
+            unreachable!("bug: WebAssembly に変換できない型: {ty:?}")
+        }
+    };
+
+    Ok(Some(name))
+}
+
+/// `check_types` を通過した関数本体の中の型に対応する値型。
+fn value_type(ty: Ty<'_>) -> Option<&'static str> {
+    wasm_type(ty).unwrap()
+}
+
+struct FnCodegen<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    dominators: Dominators,
+    /// 読まれる可能性のあるローカル変数。読まれない変数への代入は出力しない。
+    read_locals: IndexVec<Local, bool>,
+    /// 後ろ向きの辺の移動先となるブロック。
+    is_loop_header: IndexVec<BasicBlock, bool>,
+    /// 複数の前向きの辺の移動先となるブロック。
+    is_merge_node: IndexVec<BasicBlock, bool>,
+    /// 支配木における子。
+    dominated_children: IndexVec<BasicBlock, Vec<BasicBlock>>,
+    depth: usize,
+}
+
+impl<'a, 'tcx> FnCodegen<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>) -> Self {
+        let dominators = Dominators::compute(body);
+        let num_blocks = body.basic_blocks.len();
+
+        let mut is_loop_header = IndexVec::from_elem_n(false, num_blocks);
+        let mut is_merge_node = IndexVec::from_elem_n(false, num_blocks);
+        let mut dominated_children = IndexVec::from_elem_n(Vec::new(), num_blocks);
+
+        for (bb, preds) in predecessors(body).iter_enumerated() {
+            let Some(rpo_index) = dominators.rpo_index(bb) else {
+                continue;
+            };
+
+            let reachable_preds = preds
+                .iter()
+                .filter_map(|&pred| dominators.rpo_index(pred))
+                .collect::<Vec<_>>();
+            is_loop_header[bb] = reachable_preds.iter().any(|&pred| pred >= rpo_index);
+            is_merge_node[bb] = reachable_preds.iter().filter(|&&pred| pred < rpo_index).count() > 1;
+
+            if let Some(idom) = dominators.immediate_dominator(bb) {
+                dominated_children[idom].push(bb);
+            }
+        }
+
+        FnCodegen {
+            tcx,
+            body,
+            dominators,
+            read_locals: find_read_locals(body),
+            is_loop_header,
+            is_merge_node,
+            dominated_children,
+            depth: 2,
+        }
+    }
+
+    fn local_ty(&self, local: Local) -> Ty<'tcx> {
+        self.body.local_decls[local].ty
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        match operand {
+            Operand::Copy(place) => self.local_ty(place.local),
+            Operand::Constant(constant) => constant.ty,
+        }
+    }
+
+    /// 値を格納する WebAssembly のローカル変数が宣言されるかどうか。
+    fn is_declared(&self, local: Local) -> bool {
+        self.read_locals[local] && value_type(self.local_ty(local)).is_some()
+    }
+
+    fn rpo_index(&self, bb: BasicBlock) -> usize {
+        self.dominators.rpo_index(bb).unwrap()
+    }
+
+    /// 現在の深さで一つの命令を出力する。
+    fn instr(&self, w: &mut dyn Write, instr: fmt::Arguments<'_>) -> fmt::Result {
+        for _ in 0..self.depth {
+            write!(w, "{INDENT}")?;
+        }
+        writeln!(w, "{instr}")
+    }
+
+    fn write_fn(&mut self, w: &mut dyn Write) -> fmt::Result {
+        let name = mangle(self.tcx, self.body.def_id.to_def_id());
+        write!(w, "{INDENT}(func ${name}")?;
+        // ライブラリのステロの関数は、他のモジュールからインポートできるようにエクスポートする
+        if self.tcx.sess.opts.stelo_type == SteloType::Lib {
+            write!(w, " (export \"{name}\")")?;
+        }
+        for arg in self.body.args_iter() {
+            if let Some(ty) = value_type(self.local_ty(arg)) {
+                write!(w, " (param ${arg:?} {ty})")?;
+            }
+        }
+        let result = value_type(self.body.return_ty());
+        if let Some(ty) = result {
+            write!(w, " (result {ty})")?;
+        }
+        writeln!(w)?;
+
+        let return_and_locals = std::iter::once(RETURN_PLACE).chain(self.body.vars_and_temps_iter());
+        for local in return_and_locals.filter(|&local| self.is_declared(local)) {
+            let ty = value_type(self.local_ty(local)).unwrap();
+            self.instr(w, format_args!("(local ${local:?} {ty})"))?;
+        }
+
+        let ends_with_structure = self.do_tree(START_BLOCK, w)?;
+
+        // 構造化された制御の直後は、すべての経路が分岐していても検証上は到達可能と見なされるため、
+        // 値を返さずに関数の末尾に達することがないことを明示する
+        if result.is_some() && ends_with_structure {
+            self.instr(w, format_args!("unreachable"))?;
+        }
+
+        writeln!(w, "{INDENT})")
+    }
+
+    /// `bb` と、支配木において `bb` が支配するブロックを出力する。
+    ///
+    /// 出力の最後が `end` で終わる場合は `true` を返す。
+    fn do_tree(&mut self, bb: BasicBlock, w: &mut dyn Write) -> Result<bool, fmt::Error> {
+        // 合流するブロックは、逆後順で後ろのものほど外側の `block` の後に置く
+        let mut merge_children = self.dominated_children[bb]
+            .iter()
+            .copied()
+            .filter(|&child| self.is_merge_node[child])
+            .collect::<Vec<_>>();
+        merge_children.sort_by_key(|&child| std::cmp::Reverse(self.rpo_index(child)));
+
+        if self.is_loop_header[bb] {
+            self.instr(w, format_args!("loop $loop_{bb:?}"))?;
+            self.depth += 1;
+            self.node_within(bb, &merge_children, w)?;
+            self.depth -= 1;
+            self.instr(w, format_args!("end"))?;
+            Ok(true)
+        } else {
+            self.node_within(bb, &merge_children, w)
+        }
+    }
+
+    fn node_within(
+        &mut self,
+        bb: BasicBlock,
+        merge_children: &[BasicBlock],
+        w: &mut dyn Write,
+    ) -> Result<bool, fmt::Error> {
+        let Some((&outermost, rest)) = merge_children.split_first() else {
+            return self.write_basic_block(bb, w);
+        };
+
+        self.instr(w, format_args!("block ${outermost:?}"))?;
+        self.depth += 1;
+        self.node_within(bb, rest, w)?;
+        self.depth -= 1;
+        self.instr(w, format_args!("end"))?;
+
+        self.do_tree(outermost, w)
+    }
+
+    /// `source` から `target` へ制御を移す。
+    fn do_branch(
+        &mut self,
+        source: BasicBlock,
+        target: BasicBlock,
+        w: &mut dyn Write,
+    ) -> Result<bool, fmt::Error> {
+        if self.rpo_index(target) <= self.rpo_index(source) {
+            self.instr(w, format_args!("br $loop_{target:?}"))?;
+            Ok(false)
+        } else if self.is_merge_node[target] {
+            self.instr(w, format_args!("br ${target:?}"))?;
+            Ok(false)
+        } else {
+            self.do_tree(target, w)
+        }
+    }
+
+    fn write_basic_block(&mut self, bb: BasicBlock, w: &mut dyn Write) -> Result<bool, fmt::Error> {
+        let body = self.body;
+        let data = &body.basic_blocks[bb];
+
+        for statement in &data.statements {
+            match &statement.kind {
+                StatementKind::Assign(box (place, rvalue)) => {
+                    // 右辺値は副作用をもたないため、格納先の変数が無ければ何も出力しない
+                    if !self.is_declared(place.local) {
+                        continue;
+                    }
+                    self.write_rvalue(rvalue, w)?;
+                    self.instr(w, format_args!("local.set ${:?}", place.local))?;
+                }
+                StatementKind::StorageLive(_) => {}
+            }
+        }
+
+        match &data.terminator().kind {
+            TerminatorKind::Goto { target } => self.do_branch(bb, *target, w),
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let discr_ty = self.operand_ty(discr);
+                let vt = value_type(discr_ty).unwrap();
+
+                if discr_ty.is_bool()
+                    && let [(0, else_)] = targets.values[..]
+                {
+                    self.write_operand(discr, w)?;
+                    self.instr(w, format_args!("if"))?;
+                    self.depth += 1;
+                    self.do_branch(bb, targets.otherwise, w)?;
+                    self.depth -= 1;
+                    self.instr(w, format_args!("else"))?;
+                    self.depth += 1;
+                    self.do_branch(bb, else_, w)?;
+                    self.depth -= 1;
+                    self.instr(w, format_args!("end"))?;
+                    return Ok(true);
+                }
+
+                // `if` を入れ子にして、一致する値を順に調べる
+                for &(value, target) in &targets.values {
+                    self.write_operand(discr, w)?;
+                    self.write_const(discr_ty, ConstValue::Int(value), w)?;
+                    self.instr(w, format_args!("{vt}.eq"))?;
+                    self.instr(w, format_args!("if"))?;
+                    self.depth += 1;
+                    self.do_branch(bb, target, w)?;
+                    self.depth -= 1;
+                    self.instr(w, format_args!("else"))?;
+                    self.depth += 1;
+                }
+
+                self.do_branch(bb, targets.otherwise, w)?;
+
+                for _ in &targets.values {
+                    self.depth -= 1;
+                    self.instr(w, format_args!("end"))?;
+                }
+                Ok(!targets.values.is_empty())
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+            } => {
+                for arg in args {
+                    if value_type(self.operand_ty(arg)).is_some() {
+                        self.write_operand(arg, w)?;
+                    }
+                }
+                match *self.operand_ty(func).kind() {
+                    TyKind::FnDef(def_id) => {
+                        self.instr(w, format_args!("call ${}", mangle(self.tcx, def_id)))?;
+                    }
+                    TyKind::FnPtr(sig_tys) => {
+                        // 関数テーブルの位置は、引数の後に積む
+                        self.write_operand(func, w)?;
+                        let sig_type = sig_type_name(sig_tys).unwrap();
+                        self.instr(w, format_args!("call_indirect (type {sig_type})"))?;
+                    }
+                    _ => unreachable!("bug: 関数ではない値の呼び出し: {func:?}"),
+                }
+
+                if self.is_declared(destination.local) {
+                    self.instr(w, format_args!("local.set ${:?}", destination.local))?;
+                } else if value_type(self.local_ty(destination.local)).is_some() {
+                    self.instr(w, format_args!("drop"))?;
+                }
+
+                self.do_branch(bb, *target, w)
+            }
+            TerminatorKind::Assert {
+                cond, expected, target, ..
+            } => {
+                self.write_operand(cond, w)?;
+                if *expected {
+                    self.instr(w, format_args!("i32.eqz"))?;
+                }
+                self.instr(w, format_args!("if"))?;
+                self.depth += 1;
+                self.instr(w, format_args!("unreachable"))?;
+                self.depth -= 1;
+                self.instr(w, format_args!("end"))?;
+
+                self.do_branch(bb, *target, w)
+            }
+            TerminatorKind::Return => {
+                if value_type(self.body.return_ty()).is_some() {
+                    self.instr(w, format_args!("local.get ${RETURN_PLACE:?}"))?;
+                }
+                self.instr(w, format_args!("return"))?;
+                Ok(false)
+            }
+            TerminatorKind::Unreachable => {
+                self.instr(w, format_args!("unreachable"))?;
+                Ok(false)
+            }
+        }
+    }
+
+    fn write_rvalue(&self, rvalue: &Rvalue<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match rvalue {
+            Rvalue::Use(operand) => self.write_operand(operand, w),
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => self.write_binary_op(*op, lhs, rhs, w),
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = self.operand_ty(operand);
+                let vt = value_type(ty).unwrap();
+
+                match op {
+                    UnOp::Not if ty.is_bool() => {
+                        self.write_operand(operand, w)?;
+                        self.instr(w, format_args!("i32.eqz"))
+                    }
+                    UnOp::Not => {
+                        self.write_operand(operand, w)?;
+                        self.instr(w, format_args!("{vt}.const -1"))?;
+                        self.instr(w, format_args!("{vt}.xor"))?;
+                        self.wrap_narrow_int(ty, w)
+                    }
+                    UnOp::Neg if ty.is_integral() => {
+                        self.instr(w, format_args!("{vt}.const 0"))?;
+                        self.write_operand(operand, w)?;
+                        self.instr(w, format_args!("{vt}.sub"))?;
+                        self.wrap_narrow_int(ty, w)
+                    }
+                    UnOp::Neg => {
+                        self.write_operand(operand, w)?;
+                        self.instr(w, format_args!("{vt}.neg"))
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_binary_op(
+        &self,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        let ty = self.operand_ty(lhs);
+
+        if ty.is_str() {
+            self.write_operand(lhs, w)?;
+            self.write_operand(rhs, w)?;
+            return match op {
+                BinOp::Add => self.instr(w, format_args!("call $stelo_str_concat")),
+                BinOp::Eq => self.instr(w, format_args!("call $stelo_str_eq")),
+                BinOp::Ne => {
+                    self.instr(w, format_args!("call $stelo_str_eq"))?;
+                    self.instr(w, format_args!("i32.eqz"))
+                }
+                _ => unreachable!("bug: 文字列に適用できない演算子: {op:?}"),
+            };
+        }
+
+        let vt = value_type(ty).unwrap();
+        let is_float = matches!(ty.kind(), TyKind::Float(_));
+        // 整数の除算と大小比較は、符号の有無によって命令が異なる
+        let sign = if is_float {
+            ""
+        } else if ty.is_signed() {
+            "_s"
+        } else {
+            "_u"
+        };
+
+        // WebAssembly には浮動小数点数の剰余の命令が無いため、`a - b * trunc(a / b)` で計算する
+        if is_float && op == BinOp::Rem {
+            self.write_operand(lhs, w)?;
+            self.write_operand(rhs, w)?;
+            self.write_operand(lhs, w)?;
+            self.write_operand(rhs, w)?;
+            self.instr(w, format_args!("{vt}.div"))?;
+            self.instr(w, format_args!("{vt}.trunc"))?;
+            self.instr(w, format_args!("{vt}.mul"))?;
+            return self.instr(w, format_args!("{vt}.sub"));
+        }
+
+        if op.is_overflow_check() {
+            return self.write_overflow_check(op, lhs, rhs, w);
+        }
+
+        let name = match op {
+            BinOp::Add => "add",
+            BinOp::Sub => "sub",
+            BinOp::Mul => "mul",
+            BinOp::Div if is_float => "div",
+            BinOp::Div => &format!("div{sign}"),
+            BinOp::Rem => &format!("rem{sign}"),
+            BinOp::Eq => "eq",
+            BinOp::Ne => "ne",
+            BinOp::Lt => &format!("lt{sign}"),
+            BinOp::Le => &format!("le{sign}"),
+            BinOp::Gt => &format!("gt{sign}"),
+            BinOp::Ge => &format!("ge{sign}"),
+            BinOp::AddOverflows | BinOp::SubOverflows | BinOp::MulOverflows | BinOp::DivOverflows => {
+                unreachable!()
+            }
+        };
+
+        self.write_operand(lhs, w)?;
+        self.write_operand(rhs, w)?;
+        self.instr(w, format_args!("{vt}.{name}"))?;
+
+        if op.is_comparison() {
+            Ok(())
+        } else {
+            self.wrap_narrow_int(ty, w)
+        }
+    }
+
+    /// 整数の演算がオーバーフローするかどうかを、`i32` の真偽値として計算する。
+    ///
+    /// オペランドは副作用の無い `local.get` か定数であるため、必要なら何度でも読み直す。
+    fn write_overflow_check(
+        &self,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        let ty = self.operand_ty(lhs);
+        let vt = value_type(ty).unwrap();
+        let signed = ty.is_signed();
+        let bits = match *ty.kind() {
+            TyKind::Int(IntTy::I8) | TyKind::Uint(UintTy::U8) => 8,
+            TyKind::Int(IntTy::I16) | TyKind::Uint(UintTy::U16) => 16,
+            _ if vt == "i64" => 64,
+            _ => 32,
+        };
+        let min = i64::MIN >> (64 - bits);
+
+        let name = match op {
+            BinOp::AddOverflows => "add",
+            BinOp::SubOverflows => "sub",
+            BinOp::MulOverflows => "mul",
+            BinOp::DivOverflows if signed => {
+                // `MIN / -1` のみがオーバーフローする
+                self.write_operand(lhs, w)?;
+                self.instr(w, format_args!("{vt}.const {min}"))?;
+                self.instr(w, format_args!("{vt}.eq"))?;
+                self.write_operand(rhs, w)?;
+                self.instr(w, format_args!("{vt}.const -1"))?;
+                self.instr(w, format_args!("{vt}.eq"))?;
+                return self.instr(w, format_args!("i32.and"));
+            }
+            BinOp::DivOverflows => return self.instr(w, format_args!("i32.const 0")),
+            _ => unreachable!("bug: オーバーフローの検査ではない演算: {op:?}"),
+        };
+
+        let write_op = |w: &mut dyn Write| -> fmt::Result {
+            self.write_operand(lhs, w)?;
+            self.write_operand(rhs, w)?;
+            self.instr(w, format_args!("{vt}.{name}"))
+        };
+
+        match bits {
+            // 32 ビット未満の整数の演算は `i32` で正確に計算できるため、折り返した結果と比べる
+            8 | 16 => {
+                write_op(w)?;
+                write_op(w)?;
+                self.wrap_narrow_int(ty, w)?;
+                self.instr(w, format_args!("i32.ne"))
+            }
+            // 32 ビットの整数の演算は `i64` で正確に計算できるため、結果が範囲に収まるかを調べる
+            32 => {
+                let extend = if signed { "i64.extend_i32_s" } else { "i64.extend_i32_u" };
+                let write_wide_op = |w: &mut dyn Write| -> fmt::Result {
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("{extend}"))?;
+                    self.write_operand(rhs, w)?;
+                    self.instr(w, format_args!("{extend}"))?;
+                    self.instr(w, format_args!("i64.{name}"))
+                };
+
+                write_wide_op(w)?;
+                if signed {
+                    write_wide_op(w)?;
+                    self.instr(w, format_args!("i32.wrap_i64"))?;
+                    self.instr(w, format_args!("i64.extend_i32_s"))?;
+                    self.instr(w, format_args!("i64.ne"))
+                } else {
+                    self.instr(w, format_args!("i64.const 32"))?;
+                    self.instr(w, format_args!("i64.shr_u"))?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.ne"))
+                }
+            }
+            _ => match op {
+                // 符号なしの加算は、結果が左辺より小さくなる場合にオーバーフローする
+                BinOp::AddOverflows if !signed => {
+                    write_op(w)?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.lt_u"))
+                }
+                BinOp::SubOverflows if !signed => {
+                    self.write_operand(lhs, w)?;
+                    self.write_operand(rhs, w)?;
+                    self.instr(w, format_args!("i64.lt_u"))
+                }
+                // 符号付きの加算は、両辺と結果の符号が異なる場合にオーバーフローする。
+                // 減算は、両辺の符号が異なり、左辺と結果の符号が異なる場合にオーバーフローする
+                BinOp::AddOverflows | BinOp::SubOverflows => {
+                    if op == BinOp::AddOverflows {
+                        self.write_operand(lhs, w)?;
+                        write_op(w)?;
+                        self.instr(w, format_args!("i64.xor"))?;
+                        self.write_operand(rhs, w)?;
+                    } else {
+                        self.write_operand(lhs, w)?;
+                        self.write_operand(rhs, w)?;
+                        self.instr(w, format_args!("i64.xor"))?;
+                        self.write_operand(lhs, w)?;
+                    }
+                    write_op(w)?;
+                    self.instr(w, format_args!("i64.xor"))?;
+                    self.instr(w, format_args!("i64.and"))?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.lt_s"))
+                }
+                // 乗算は、折り返した結果を左辺で割って右辺に戻らない場合にオーバーフローする。
+                // ゼロ除算と `MIN / -1` のトラップを避けるため、左辺が 0 (と -1) の場合は
+                // 1 で割ったうえで、その結果を使わない
+                _ if signed => {
+                    self.write_operand(rhs, w)?;
+                    self.instr(w, format_args!("i64.const {min}"))?;
+                    self.instr(w, format_args!("i64.eq"))?;
+                    write_op(w)?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 1"))?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const -1"))?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.instr(w, format_args!("i32.and"))?;
+                    self.instr(w, format_args!("select"))?;
+                    self.instr(w, format_args!("i64.div_s"))?;
+                    self.write_operand(rhs, w)?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    // 左辺が -1 の場合は、右辺が `MIN` の場合のみオーバーフローする
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const -1"))?;
+                    self.instr(w, format_args!("i64.eq"))?;
+                    self.instr(w, format_args!("select"))?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.instr(w, format_args!("i32.and"))
+                }
+                _ => {
+                    write_op(w)?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 1"))?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.instr(w, format_args!("select"))?;
+                    self.instr(w, format_args!("i64.div_u"))?;
+                    self.write_operand(rhs, w)?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.write_operand(lhs, w)?;
+                    self.instr(w, format_args!("i64.const 0"))?;
+                    self.instr(w, format_args!("i64.ne"))?;
+                    self.instr(w, format_args!("i32.and"))
+                }
+            },
+        }
+    }
+
+    /// `i32` で表した 32 ビット未満の整数を、その型の範囲に折り返す。
+    fn wrap_narrow_int(&self, ty: Ty<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match *ty.kind() {
+            TyKind::Int(IntTy::I8) => self.instr(w, format_args!("i32.extend8_s")),
+            TyKind::Int(IntTy::I16) => self.instr(w, format_args!("i32.extend16_s")),
+            TyKind::Uint(UintTy::U8) => {
+                self.instr(w, format_args!("i32.const 255"))?;
+                self.instr(w, format_args!("i32.and"))
+            }
+            TyKind::Uint(UintTy::U16) => {
+                self.instr(w, format_args!("i32.const 65535"))?;
+                self.instr(w, format_args!("i32.and"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn write_operand(&self, operand: &Operand<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match operand {
+            Operand::Copy(place) => self.instr(w, format_args!("local.get ${:?}", place.local)),
+            Operand::Constant(box ConstOperand { ty, const_, .. }) => {
+                self.write_const(*ty, *const_, w)
+            }
+        }
+    }
+
+    fn write_const(&self, ty: Ty<'tcx>, const_: ConstValue, w: &mut dyn Write) -> fmt::Result {
+        let vt = value_type(ty).unwrap();
+
+        match (const_, *ty.kind()) {
+            (ConstValue::Int(bits), TyKind::Int(int_ty)) => {
+                let shift = 128 - int_ty.bit_width();
+                let value = ((bits << shift) as i128) >> shift;
+                self.instr(w, format_args!("{vt}.const {value}"))
+            }
+            (ConstValue::Int(bits), _) => self.instr(w, format_args!("{vt}.const {bits}")),
+            (ConstValue::Float(symbol), _) => self.instr(w, format_args!("{vt}.const {symbol}")),
+            (ConstValue::Bool(b), _) => self.instr(w, format_args!("i32.const {}", b as u32)),
+            (ConstValue::Char(c), _) => self.instr(w, format_args!("i32.const {}", c as u32)),
+            (ConstValue::Str(symbol), _) => {
+                self.instr(w, format_args!("global.get {}", str_global(symbol)))
+            }
+            (ConstValue::FnPtr(def_id), _) => {
+                self.instr(w, format_args!("global.get {}", fn_ptr_global(self.tcx, def_id)))
+            }
+            (const_, _) => unreachable!("bug: WebAssembly に変換できない定数: {const_:?}: {ty:?}"),
+        }
+    }
+}
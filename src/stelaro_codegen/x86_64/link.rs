@@ -0,0 +1,82 @@
+//! システムの `as` と `ld` による、生成したアセンブリの実行ファイルへの変換。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+
+/// 実行ファイルの生成に失敗した原因。
+#[derive(Debug)]
+pub enum LinkError {
+    /// 中間ファイルを書き込めなかった。
+    Io(io::Error),
+    /// アセンブラまたはリンカが見つからなかった。
+    ToolNotFound { tool: &'static str },
+    /// アセンブラまたはリンカが失敗した。`stderr` はそのツールの標準エラー出力。
+    ToolFailed { tool: &'static str, stderr: String },
+}
+
+impl From<io::Error> for LinkError {
+    fn from(e: io::Error) -> Self {
+        LinkError::Io(e)
+    }
+}
+
+/// アセンブリをアセンブルしてリンクし、`output` に実行ファイルを書き込む。
+/// `externs` は `--extern` で読み込んだライブラリのステロのアセンブリのパスで、ともにリンクする。
+///
+/// 中間ファイルは一時ディレクトリに置き、終了時に削除する。
+pub fn link_executable(asm: &str, externs: &[PathBuf], output: &Path) -> Result<(), LinkError> {
+    let temps_dir = TempDir::new()?;
+    let asm_path = temps_dir.path.join("out.s");
+    fs::write(&asm_path, asm)?;
+
+    let sources = std::iter::once(&asm_path).chain(externs);
+    let mut objects = Vec::new();
+    for (i, source) in sources.enumerate() {
+        let obj_path = temps_dir.path.join(format!("out{i}.o"));
+        run_tool(Command::new("as").arg("-o").arg(&obj_path).arg(source), "as")?;
+        objects.push(obj_path);
+    }
+    run_tool(Command::new("ld").arg("-o").arg(output).args(&objects), "ld")
+}
+
+fn run_tool(command: &mut Command, tool: &'static str) -> Result<(), LinkError> {
+    let output = command.output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => LinkError::ToolNotFound { tool },
+        _ => LinkError::Io(e),
+    })?;
+
+    if !output.status.success() {
+        return Err(LinkError::ToolFailed {
+            tool,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+/// 破棄されると中身ごと削除される一時ディレクトリ。
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new() -> io::Result<Self> {
+        // 同時に実行される他のコンパイラと衝突しないよう、プロセス ID と連番を名前に含める
+        static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let path = std::env::temp_dir().join(format!("stelaro-link-{}-{n}", process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(TempDir { path })
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
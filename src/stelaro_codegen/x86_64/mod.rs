@@ -0,0 +1,1239 @@
+//! `--emit=asm` のための x86-64 (System V ABI) のアセンブリの生成。
+//!
+//! 最適化済みの MIR の各文と終端命令に対して命令を選択し、GNU アセンブラの
+//! Intel 記法のテキストを出力する。ローカル変数は `regalloc` で
+//! レジスタかスタック上の退避領域に割り当てる。
+//!
+//! 整数は型の幅に関係なく 64 ビットのレジスタに、符号付き整数は符号拡張、
+//! 符号なし整数はゼロ拡張した形で保持する。64 ビットより狭い型の演算の結果は、
+//! 型の幅で折り返すために拡張し直す。
+//!
+//! ステロのルートに `main` 関数がある場合は、それを呼び出して戻り値を終了コードとする
+//! `_start` も出力する。`--emit=exe` では、これをシステムの `as` と `ld` で実行ファイルにする。
+//! ライブラリのステロの関数は `.globl` とし、`--extern` で読み込んだステロとともにリンクする。
+//!
+//! 文字列 `str` は、アドレスを下位 32 ビットに、バイト数を上位 32 ビットに詰めた一つのレジスタで保持する。
+//! `ld` が静的にリンクする実行ファイルでは、リテラルの `.rodata` も `brk` で確保するヒープも
+//! 32 ビットのアドレスに収まる。連結した文字列や書式化した文字列はヒープに確保し、解放しない。
+//! 関数ポインタは関数のアドレスとして保持し、レジスタを通して間接的に呼び出す。
+//!
+//! パニックはメッセージを標準エラー出力に書き、終了コード 101 でプロセスを終了する。
+//! C のライブラリに依存しないよう、どちらもシステムコールで直接行う。
+
+mod link;
+mod regalloc;
+
+pub use link::{LinkError, link_executable};
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::{DefId, IndexVec, Symbol};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BinOp, Body, ConstOperand, ConstValue, Local, Operand, PANIC_EXIT_CODE,
+    RETURN_PLACE, Rvalue, StatementKind, TerminatorKind,
+};
+use crate::stelaro_session::config::{EmitKind, SteloType};
+use crate::stelaro_std::{Intrinsic, intrinsic};
+use crate::stelaro_ty::{
+    Ty, TyKind,
+    ty::{IntTy, UintTy},
+};
+
+use super::diagnostics::DiagsCodegen;
+use super::{
+    callee_sig_tys, find_read_locals, foreign_callees, has_str_op, is_entry_fn, mangle,
+    str_literals, write_fn_cached,
+};
+use regalloc::{Allocation, Loc};
+
+
+const INDENT: &str = "    ";
+
+/// 整数の引数を渡すレジスタ。7 番目以降の引数はスタックで渡す。
+const ARG_REGS: [Reg; 6] = [Reg::Rdi, Reg::Rsi, Reg::Rdx, Reg::Rcx, Reg::R8, Reg::R9];
+
+/// 変数の割り当てと引数の受け渡しに使う汎用レジスタ。
+/// 作業用の `rax` と、フレームの `rbp`, `rsp` は含まない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Reg {
+    Rbx,
+    Rcx,
+    Rdx,
+    Rsi,
+    Rdi,
+    R8,
+    R9,
+    R10,
+    R11,
+    R12,
+    R13,
+    R14,
+    R15,
+}
+
+impl Reg {
+    fn name(self) -> &'static str {
+        match self {
+            Reg::Rbx => "rbx",
+            Reg::Rcx => "rcx",
+            Reg::Rdx => "rdx",
+            Reg::Rsi => "rsi",
+            Reg::Rdi => "rdi",
+            Reg::R8 => "r8",
+            Reg::R9 => "r9",
+            Reg::R10 => "r10",
+            Reg::R11 => "r11",
+            Reg::R12 => "r12",
+            Reg::R13 => "r13",
+            Reg::R14 => "r14",
+            Reg::R15 => "r15",
+        }
+    }
+}
+
+/// 整数を保持するレジスタの値の解釈。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IntRepr {
+    bits: u32,
+    signed: bool,
+}
+
+/// すべての関数本体を、一つのアセンブリのファイルに変換する。
+///
+/// x86-64 で扱えない型を使う関数はエラーを報告し、ファイルに含めない。
+pub fn codegen_asm<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>]) -> String {
+    let mut out = String::new();
+    write_asm(tcx, bodies, &mut out).unwrap();
+    out
+}
+
+fn write_asm<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>], w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{INDENT}.intel_syntax noprefix")?;
+    writeln!(w, "{INDENT}.text")?;
+
+    for body in bodies {
+        // 再利用できる出力は型を検査済みであるため、`check_types` は生成する場合にのみ呼ぶ
+        write_fn_cached(tcx, EmitKind::Asm, body, w, |w| {
+            if check_types(tcx, body) {
+                writeln!(w)?;
+                FnCodegen::new(tcx, body).write_fn(w)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(main) = bodies.iter().find(|body| is_entry_fn(tcx, body)) {
+        writeln!(w)?;
+        write_start(tcx, main, w)?;
+    }
+
+    if bodies.iter().any(has_assert) {
+        writeln!(w)?;
+        write_panic(w)?;
+    }
+
+    if bodies.iter().any(has_str_op) {
+        writeln!(w)?;
+        write_str_helpers(w)?;
+    }
+
+    write_intrinsics(tcx, bodies, w)?;
+
+    // スタックを実行可能にしないことをリンカに伝える
+    writeln!(w)?;
+    writeln!(w, "{INDENT}.section .note.GNU-stack,\"\",@progbits")
+}
+
+/// `main` 関数を呼び出し、その戻り値を終了コードとしてプロセスを終了する `_start` を出力する。
+fn write_start<'tcx>(tcx: TyCtxt<'tcx>, main: &Body<'tcx>, w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "{INDENT}.globl _start")?;
+    writeln!(w, "_start:")?;
+    // プロセスの開始時点で、スタックは 16 バイト境界に揃っている
+    writeln!(w, "{INDENT}xor ebp, ebp")?;
+    writeln!(w, "{INDENT}call {}", mangle(tcx, main.def_id.to_def_id()))?;
+    if let Ok(Some(_)) = int_repr(main.return_ty()) {
+        writeln!(w, "{INDENT}mov edi, eax")?;
+    } else {
+        writeln!(w, "{INDENT}xor edi, edi")?;
+    }
+    // exit システムコール
+    writeln!(w, "{INDENT}mov eax, 60")?;
+    writeln!(w, "{INDENT}syscall")
+}
+
+/// `rsi` と `rdx` で渡されたメッセージを標準エラー出力に書き、プロセスを終了する `stelo_panic` を出力する。
+fn write_panic(w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "stelo_panic:")?;
+    // write システムコール
+    writeln!(w, "{INDENT}mov edi, 2")?;
+    writeln!(w, "{INDENT}mov eax, 1")?;
+    writeln!(w, "{INDENT}syscall")?;
+    // exit システムコール
+    writeln!(w, "{INDENT}mov edi, {PANIC_EXIT_CODE}")?;
+    writeln!(w, "{INDENT}mov eax, 60")?;
+    writeln!(w, "{INDENT}syscall")
+}
+
+/// スタックで渡された二つの文字列を比較する `stelo_str_eq` と、連結する `stelo_str_concat` を出力する。
+///
+/// 左辺を `[rsp + 8]`、右辺を `[rsp + 16]` に積んで呼び出し、結果は `rax` に返す。
+/// 二項演算の途中で呼び出すため、`rax` 以外のレジスタはすべて保存する。
+fn write_str_helpers(w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "stelo_str_eq:")?;
+    writeln!(w, "{INDENT}push rcx")?;
+    writeln!(w, "{INDENT}push rsi")?;
+    writeln!(w, "{INDENT}push rdi")?;
+    writeln!(w, "{INDENT}mov rsi, qword ptr [rsp + 32]")?;
+    writeln!(w, "{INDENT}mov rdi, qword ptr [rsp + 40]")?;
+    writeln!(w, "{INDENT}xor eax, eax")?;
+    // 長さが異なれば等しくない
+    writeln!(w, "{INDENT}mov rcx, rsi")?;
+    writeln!(w, "{INDENT}xor rcx, rdi")?;
+    writeln!(w, "{INDENT}shr rcx, 32")?;
+    writeln!(w, "{INDENT}jnz .Lstelo_str_eq_done")?;
+    // 長さが 0 の場合、`repe cmpsb` はフラグを変えず、`shr` による ZF が残る
+    writeln!(w, "{INDENT}mov rcx, rsi")?;
+    writeln!(w, "{INDENT}shr rcx, 32")?;
+    writeln!(w, "{INDENT}mov esi, esi")?;
+    writeln!(w, "{INDENT}mov edi, edi")?;
+    writeln!(w, "{INDENT}repe cmpsb")?;
+    writeln!(w, "{INDENT}sete al")?;
+    writeln!(w, ".Lstelo_str_eq_done:")?;
+    writeln!(w, "{INDENT}pop rdi")?;
+    writeln!(w, "{INDENT}pop rsi")?;
+    writeln!(w, "{INDENT}pop rcx")?;
+    writeln!(w, "{INDENT}ret")?;
+
+    // 他のオブジェクトファイルの `stelo_str_concat` と領域が重ならないよう、
+    // 確保のたびに現在のプログラムブレークを問い合わせる
+    writeln!(w, "stelo_str_concat:")?;
+    let saved = [Reg::Rcx, Reg::Rdx, Reg::Rsi, Reg::Rdi, Reg::R11];
+    for reg in saved {
+        writeln!(w, "{INDENT}push {}", reg.name())?;
+    }
+    let lhs = 8 * (saved.len() + 1);
+    let rhs = lhs + 8;
+    // brk システムコール。`brk(0)` は現在のプログラムブレークを返す
+    writeln!(w, "{INDENT}xor edi, edi")?;
+    writeln!(w, "{INDENT}mov eax, 12")?;
+    writeln!(w, "{INDENT}syscall")?;
+    writeln!(w, "{INDENT}mov rdx, rax")?;
+    writeln!(w, "{INDENT}mov rdi, qword ptr [rsp + {lhs}]")?;
+    writeln!(w, "{INDENT}shr rdi, 32")?;
+    writeln!(w, "{INDENT}mov rsi, qword ptr [rsp + {rhs}]")?;
+    writeln!(w, "{INDENT}shr rsi, 32")?;
+    writeln!(w, "{INDENT}add rdi, rsi")?;
+    writeln!(w, "{INDENT}add rdi, rdx")?;
+    writeln!(w, "{INDENT}mov eax, 12")?;
+    writeln!(w, "{INDENT}syscall")?;
+    // 拡張できなかった場合、`brk` は元のプログラムブレークを返す
+    writeln!(w, "{INDENT}cmp rax, rdi")?;
+    writeln!(w, "{INDENT}jb .Lstelo_str_concat_fail")?;
+    writeln!(w, "{INDENT}mov rdi, rdx")?;
+    for offset in [lhs, rhs] {
+        writeln!(w, "{INDENT}mov rsi, qword ptr [rsp + {offset}]")?;
+        writeln!(w, "{INDENT}mov rcx, rsi")?;
+        writeln!(w, "{INDENT}shr rcx, 32")?;
+        writeln!(w, "{INDENT}mov esi, esi")?;
+        writeln!(w, "{INDENT}rep movsb")?;
+    }
+    writeln!(w, "{INDENT}mov rax, rdi")?;
+    writeln!(w, "{INDENT}sub rax, rdx")?;
+    writeln!(w, "{INDENT}shl rax, 32")?;
+    writeln!(w, "{INDENT}or rax, rdx")?;
+    for reg in saved.iter().rev() {
+        writeln!(w, "{INDENT}pop {}", reg.name())?;
+    }
+    writeln!(w, "{INDENT}ret")?;
+    writeln!(w, ".Lstelo_str_concat_fail:")?;
+    writeln!(w, "{INDENT}ud2")
+}
+
+/// 呼び出している標準ステロの関数を、システムコールで直接出力するルーチンとして出力する。
+///
+/// 整数は 10 進数に変換するルーチン `stelo_print_i64`, `stelo_print_u64` と
+/// `stelo_format_i64`, `stelo_format_u64` を共有する。
+/// 浮動小数点数を扱う関数は、呼び出し元が `check_types` で報告されるため出力しない。
+fn write_intrinsics<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>], w: &mut dyn Write) -> fmt::Result {
+    let mut print_int = false;
+    let mut format_int = false;
+
+    for def_id in foreign_callees(bodies) {
+        let Some(intrinsic) = intrinsic(tcx, def_id) else {
+            continue;
+        };
+        let name = mangle(tcx, def_id);
+
+        match intrinsic {
+            Intrinsic::Len => {
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}mov rax, rdi")?;
+                writeln!(w, "{INDENT}shr rax, 32")?;
+                writeln!(w, "{INDENT}ret")?;
+            }
+            Intrinsic::Print | Intrinsic::Println | Intrinsic::Eprint | Intrinsic::Eprintln => {
+                let fd = match intrinsic {
+                    Intrinsic::Print | Intrinsic::Println => 1,
+                    _ => 2,
+                };
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}mov rdx, rdi")?;
+                writeln!(w, "{INDENT}shr rdx, 32")?;
+                writeln!(w, "{INDENT}mov esi, edi")?;
+                write_write_syscall(fd, w)?;
+                if matches!(intrinsic, Intrinsic::Println | Intrinsic::Eprintln) {
+                    writeln!(w, "{INDENT}lea rsi, [rip + .L{name}_newline]")?;
+                    writeln!(w, "{INDENT}mov edx, 1")?;
+                    write_write_syscall(fd, w)?;
+                    writeln!(w, "{INDENT}ret")?;
+                    writeln!(w, "{INDENT}.section .rodata")?;
+                    writeln!(w, ".L{name}_newline:")?;
+                    writeln!(w, "{INDENT}.ascii \"\\n\"")?;
+                    writeln!(w, "{INDENT}.text")?;
+                } else {
+                    writeln!(w, "{INDENT}ret")?;
+                }
+            }
+            Intrinsic::PrintInt(_) | Intrinsic::PrintUint(_) => {
+                // 引数は 64 ビットに拡張されて渡される
+                let signed = matches!(intrinsic, Intrinsic::PrintInt(_));
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}jmp stelo_print_{}64", if signed { "i" } else { "u" })?;
+                print_int = true;
+            }
+            Intrinsic::FormatInt(_) | Intrinsic::FormatUint(_) => {
+                let signed = matches!(intrinsic, Intrinsic::FormatInt(_));
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}jmp stelo_format_{}64", if signed { "i" } else { "u" })?;
+                format_int = true;
+            }
+            // リテラルと同じく、アドレスとバイト数を詰めた値を返す
+            Intrinsic::FormatBool => {
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}lea rax, [rip + .L{name}_true]")?;
+                writeln!(w, "{INDENT}mov edx, 4")?;
+                writeln!(w, "{INDENT}test rdi, rdi")?;
+                writeln!(w, "{INDENT}jnz .L{name}_done")?;
+                writeln!(w, "{INDENT}lea rax, [rip + .L{name}_false]")?;
+                writeln!(w, "{INDENT}mov edx, 5")?;
+                writeln!(w, ".L{name}_done:")?;
+                writeln!(w, "{INDENT}shl rdx, 32")?;
+                writeln!(w, "{INDENT}or rax, rdx")?;
+                writeln!(w, "{INDENT}ret")?;
+                writeln!(w, "{INDENT}.section .rodata")?;
+                writeln!(w, ".L{name}_true:")?;
+                writeln!(w, "{INDENT}.ascii \"true\"")?;
+                writeln!(w, ".L{name}_false:")?;
+                writeln!(w, "{INDENT}.ascii \"false\"")?;
+                writeln!(w, "{INDENT}.text")?;
+            }
+            Intrinsic::PrintBool => {
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                writeln!(w, "{INDENT}lea rsi, [rip + .L{name}_true]")?;
+                writeln!(w, "{INDENT}mov edx, 4")?;
+                writeln!(w, "{INDENT}test rdi, rdi")?;
+                writeln!(w, "{INDENT}jnz .L{name}_write")?;
+                writeln!(w, "{INDENT}lea rsi, [rip + .L{name}_false]")?;
+                writeln!(w, "{INDENT}mov edx, 5")?;
+                writeln!(w, ".L{name}_write:")?;
+                write_stdout_syscall(w)?;
+                writeln!(w, "{INDENT}ret")?;
+                writeln!(w, "{INDENT}.section .rodata")?;
+                writeln!(w, ".L{name}_true:")?;
+                writeln!(w, "{INDENT}.ascii \"true\"")?;
+                writeln!(w, ".L{name}_false:")?;
+                writeln!(w, "{INDENT}.ascii \"false\"")?;
+                writeln!(w, "{INDENT}.text")?;
+            }
+            Intrinsic::PrintChar => {
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                write_print_char(&name, w)?;
+            }
+            Intrinsic::Exit => {
+                writeln!(w)?;
+                writeln!(w, "{name}:")?;
+                // exit システムコール。出力はバッファリングしていないため、書き出すものは無い
+                writeln!(w, "{INDENT}mov eax, 60")?;
+                writeln!(w, "{INDENT}syscall")?;
+            }
+            Intrinsic::PrintFloat(_) | Intrinsic::FormatFloat(_) => {}
+            Intrinsic::Assert => {
+                unreachable!("bug: `assert` の呼び出しは MIR の構築の際に `Assert` に置き換えられる")
+            }
+        }
+    }
+
+    if print_int {
+        writeln!(w)?;
+        write_int_routines("print", w)?;
+    }
+    if format_int {
+        writeln!(w)?;
+        write_int_routines("format", w)?;
+    }
+    Ok(())
+}
+
+/// `rsi` と `rdx` で渡されたバイト列を標準出力に書く。
+fn write_stdout_syscall(w: &mut dyn Write) -> fmt::Result {
+    write_write_syscall(1, w)
+}
+
+/// `rsi` と `rdx` で渡されたバイト列を、ファイル記述子 `fd` に書く。
+fn write_write_syscall(fd: u32, w: &mut dyn Write) -> fmt::Result {
+    // write システムコール
+    writeln!(w, "{INDENT}mov edi, {fd}")?;
+    writeln!(w, "{INDENT}mov eax, 1")?;
+    writeln!(w, "{INDENT}syscall")
+}
+
+/// バイト列を `.ascii` 指令として出力する。
+fn write_ascii(bytes: &[u8], w: &mut dyn Write) -> fmt::Result {
+    write!(w, "{INDENT}.ascii \"")?;
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(w, "\\{}", byte as char)?,
+            0x20..=0x7e => write!(w, "{}", byte as char)?,
+            _ => write!(w, "\\{byte:03o}")?,
+        }
+    }
+    writeln!(w, "\"")
+}
+
+/// `rdi` の整数を 10 進数に変換するルーチン `stelo_{kind}_i64` と `stelo_{kind}_u64` を出力する。
+/// `print` は標準出力に書き、`format` はヒープに確保した文字列を `rax` に返す。
+///
+/// 下の桁から順に、スタック上の領域の末尾から先頭に向かって書く。
+fn write_int_routines(kind: &str, w: &mut dyn Write) -> fmt::Result {
+    writeln!(w, "stelo_{kind}_i64:")?;
+    writeln!(w, "{INDENT}xor esi, esi")?;
+    writeln!(w, "{INDENT}test rdi, rdi")?;
+    writeln!(w, "{INDENT}jns .Lstelo_{kind}_digits")?;
+    // 最小値の符号を反転しても最小値のままだが、符号なし整数としては正しい絶対値になる
+    writeln!(w, "{INDENT}neg rdi")?;
+    writeln!(w, "{INDENT}mov esi, 1")?;
+    writeln!(w, "{INDENT}jmp .Lstelo_{kind}_digits")?;
+    writeln!(w, "stelo_{kind}_u64:")?;
+    writeln!(w, "{INDENT}xor esi, esi")?;
+    writeln!(w, ".Lstelo_{kind}_digits:")?;
+    // 最大 20 桁と符号を書く領域
+    writeln!(w, "{INDENT}push rbp")?;
+    writeln!(w, "{INDENT}mov rbp, rsp")?;
+    writeln!(w, "{INDENT}sub rsp, 32")?;
+    writeln!(w, "{INDENT}mov rcx, rbp")?;
+    writeln!(w, "{INDENT}mov rax, rdi")?;
+    writeln!(w, "{INDENT}mov r8d, 10")?;
+    writeln!(w, ".Lstelo_{kind}_digits_loop:")?;
+    writeln!(w, "{INDENT}xor edx, edx")?;
+    writeln!(w, "{INDENT}div r8")?;
+    writeln!(w, "{INDENT}add dl, 48")?;
+    writeln!(w, "{INDENT}dec rcx")?;
+    writeln!(w, "{INDENT}mov byte ptr [rcx], dl")?;
+    writeln!(w, "{INDENT}test rax, rax")?;
+    writeln!(w, "{INDENT}jnz .Lstelo_{kind}_digits_loop")?;
+    writeln!(w, "{INDENT}test esi, esi")?;
+    writeln!(w, "{INDENT}jz .Lstelo_{kind}_digits_write")?;
+    writeln!(w, "{INDENT}dec rcx")?;
+    writeln!(w, "{INDENT}mov byte ptr [rcx], 45")?;
+    writeln!(w, ".Lstelo_{kind}_digits_write:")?;
+    writeln!(w, "{INDENT}mov rsi, rcx")?;
+    writeln!(w, "{INDENT}mov rdx, rbp")?;
+    writeln!(w, "{INDENT}sub rdx, rcx")?;
+    if kind == "print" {
+        write_stdout_syscall(w)?;
+    } else {
+        write_copy_to_heap(kind, w)?;
+    }
+    writeln!(w, "{INDENT}mov rsp, rbp")?;
+    writeln!(w, "{INDENT}pop rbp")?;
+    writeln!(w, "{INDENT}ret")?;
+    if kind != "print" {
+        writeln!(w, ".Lstelo_{kind}_fail:")?;
+        writeln!(w, "{INDENT}ud2")?;
+    }
+    Ok(())
+}
+
+/// `rsi` と `rdx` で渡されたバイト列を `brk` で確保した領域に写し、その文字列を `rax` に返す。
+///
+/// `stelo_str_concat` と同様に、確保のたびに現在のプログラムブレークを問い合わせる。
+fn write_copy_to_heap(kind: &str, w: &mut dyn Write) -> fmt::Result {
+    // brk システムコール。システムコールは `rsi` と `rdx` を保存する
+    writeln!(w, "{INDENT}xor edi, edi")?;
+    writeln!(w, "{INDENT}mov eax, 12")?;
+    writeln!(w, "{INDENT}syscall")?;
+    writeln!(w, "{INDENT}mov r8, rax")?;
+    writeln!(w, "{INDENT}lea rdi, [rax + rdx]")?;
+    writeln!(w, "{INDENT}mov eax, 12")?;
+    writeln!(w, "{INDENT}syscall")?;
+    // 拡張できなかった場合、`brk` は元のプログラムブレークを返す
+    writeln!(w, "{INDENT}cmp rax, rdi")?;
+    writeln!(w, "{INDENT}jb .Lstelo_{kind}_fail")?;
+    writeln!(w, "{INDENT}mov rdi, r8")?;
+    writeln!(w, "{INDENT}mov rcx, rdx")?;
+    writeln!(w, "{INDENT}rep movsb")?;
+    writeln!(w, "{INDENT}mov rax, rdx")?;
+    writeln!(w, "{INDENT}shl rax, 32")?;
+    writeln!(w, "{INDENT}or rax, r8")
+}
+
+/// `edi` の文字を UTF-8 に符号化し、標準出力に書くルーチンの本体を出力する。
+fn write_print_char(name: &str, w: &mut dyn Write) -> fmt::Result {
+    // 符号化したバイト列を書く領域
+    writeln!(w, "{INDENT}sub rsp, 8")?;
+
+    // 各長さの符号化の、先頭のバイトの接頭辞と、それより小さい文字の上限
+    let encodings: [(u32, u32); 4] = [(0, 0x80), (0xc0, 0x800), (0xe0, 0x10000), (0xf0, 0)];
+    for (i, &(prefix, limit)) in encodings.iter().enumerate() {
+        let len = i + 1;
+        writeln!(w, ".L{name}_{len}:")?;
+        if limit != 0 {
+            writeln!(w, "{INDENT}cmp edi, {limit:#x}")?;
+            writeln!(w, "{INDENT}jae .L{name}_{}", len + 1)?;
+        }
+
+        for byte in 0..len {
+            let shift = 6 * (len - 1 - byte);
+            writeln!(w, "{INDENT}mov eax, edi")?;
+            if shift != 0 {
+                writeln!(w, "{INDENT}shr eax, {shift}")?;
+            }
+            if byte == 0 {
+                if prefix != 0 {
+                    writeln!(w, "{INDENT}or eax, {prefix:#x}")?;
+                }
+            } else {
+                writeln!(w, "{INDENT}and eax, 0x3f")?;
+                writeln!(w, "{INDENT}or eax, 0x80")?;
+            }
+            writeln!(w, "{INDENT}mov byte ptr [rsp + {byte}], al")?;
+        }
+        writeln!(w, "{INDENT}mov edx, {len}")?;
+        if len != encodings.len() {
+            writeln!(w, "{INDENT}jmp .L{name}_write")?;
+        }
+    }
+
+    writeln!(w, ".L{name}_write:")?;
+    writeln!(w, "{INDENT}mov rsi, rsp")?;
+    write_stdout_syscall(w)?;
+    writeln!(w, "{INDENT}add rsp, 8")?;
+    writeln!(w, "{INDENT}ret")
+}
+
+fn has_assert(body: &Body<'_>) -> bool {
+    body.basic_blocks
+        .iter()
+        .any(|data| matches!(data.terminator().kind, TerminatorKind::Assert { .. }))
+}
+
+
+/// 関数のシグネチャと、読まれる可能性のあるローカル変数の型が x86-64 で扱えるかを調べる。
+/// 扱えない型があれば、型ごとに最初の変数についてエラーを報告する。
+/// 呼び出す関数のシグネチャに扱えない型があれば、その呼び出しについてエラーを報告する。
+///
+/// 読まれない変数への代入は出力されないため、その型は問わない。
+fn check_types<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> bool {
+    let read_locals = find_read_locals(body);
+    let mut reported = Vec::new();
+
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        let is_signature = local.as_usize() <= body.arg_count;
+        if !is_signature && !read_locals[local] {
+            continue;
+        }
+
+        if int_repr(decl.ty).is_err() && !reported.contains(&decl.ty) {
+            DiagsCodegen::unsupported_asm_type(tcx.dcx(), &tcx.ty_string(decl.ty), decl.span)
+                .emit();
+            reported.push(decl.ty);
+        }
+    }
+
+    for (ty, span) in callee_sig_tys(tcx, body) {
+        if int_repr(ty).is_err() && !reported.contains(&ty) {
+            DiagsCodegen::unsupported_asm_type(tcx.dcx(), &tcx.ty_string(ty), span).emit();
+            reported.push(ty);
+        }
+    }
+
+    reported.is_empty()
+}
+
+/// 型の値を保持するレジスタの解釈。値をもたない型の場合は `None` を返す。
+///
+/// `bool` は 0 か 1 の、`char` はコードポイントの符号なし整数として扱う。
+/// 文字列はアドレスとバイト数を詰めた 64 ビットの値として、関数ポインタはアドレスとして扱う。
+/// 浮動小数点数と 128 ビットの整数はまだ扱えない。
+fn int_repr(ty: Ty<'_>) -> Result<Option<IntRepr>, ()> {
+    let repr = match *ty.kind() {
+        TyKind::Bool => IntRepr { bits: 8, signed: false },
+        TyKind::Char => IntRepr { bits: 32, signed: false },
+        TyKind::Int(IntTy::I128) | TyKind::Uint(UintTy::U128) => return Err(()),
+        TyKind::Int(IntTy::Isize) => IntRepr { bits: 64, signed: true },
+        TyKind::Uint(UintTy::Usize) => IntRepr { bits: 64, signed: false },
+        TyKind::Int(int_ty) => IntRepr { bits: int_ty.bit_width(), signed: true },
+        TyKind::Uint(uint_ty) => IntRepr { bits: uint_ty.bit_width(), signed: false },
+        TyKind::Str | TyKind::FnPtr(_) => IntRepr { bits: 64, signed: false },
+        TyKind::Float(_) => return Err(()),
+        TyKind::FnDef(_) | TyKind::Closure(..) | TyKind::Unit | TyKind::Never | TyKind::Tuple([]) => {
+            return Ok(None);
+        }
+        TyKind::Tuple(_) | TyKind::Infer(_) | TyKind::Error(_) => {
+            unreachable!("bug: x86-64 に変換できない型: {ty:?}")
+        }
+    };
+
+    Ok(Some(repr))
+}
+
+/// `check_types` を通過した関数本体の中の型に対応する、レジスタの値の解釈。
+fn value_repr(ty: Ty<'_>) -> Option<IntRepr> {
+    int_repr(ty).unwrap()
+}
+
+/// 命令の被演算子。
+#[derive(Debug, Clone, Copy)]
+enum Src {
+    Loc(Loc),
+    Imm(i64),
+    /// 関数の文字列のリテラルの番号。アドレスはリンクの際に決まるため、`movabs` で読み込む。
+    Str(usize),
+    /// 関数のアドレス。文字列のリテラルと同様に `movabs` で読み込む。
+    Fn(DefId),
+}
+
+/// 呼び出す関数。
+enum Callee {
+    /// シンボルの名前で直接呼び出す。
+    Direct(String),
+    /// 関数ポインタの値を `rax` に読み込んで呼び出す。
+    Indirect(Src),
+}
+
+struct FnCodegen<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    name: String,
+    alloc: Allocation,
+    /// 関数の文字列のリテラル。関数の後の `.rodata` に置く。
+    str_literals: Vec<Symbol>,
+}
+
+impl<'a, 'tcx> FnCodegen<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>) -> Self {
+        // 読まれない変数には場所を割り当てず、その変数への代入も出力しない
+        let read_locals = find_read_locals(body);
+        let has_value = body
+            .local_decls
+            .iter_enumerated()
+            .map(|(local, decl)| {
+                let is_signature = local.as_usize() <= body.arg_count;
+                (is_signature || read_locals[local]) && value_repr(decl.ty).is_some()
+            })
+            .collect::<IndexVec<Local, _>>();
+
+        FnCodegen {
+            tcx,
+            body,
+            name: mangle(tcx, body.def_id.to_def_id()),
+            alloc: regalloc::allocate(body, &has_value),
+            str_literals: str_literals(body),
+        }
+    }
+
+    fn local_repr(&self, local: Local) -> Option<IntRepr> {
+        value_repr(self.body.local_decls[local].ty)
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        match operand {
+            Operand::Copy(place) => self.body.local_decls[place.local].ty,
+            Operand::Constant(constant) => constant.ty,
+        }
+    }
+
+    /// 値をもつ引数。ABI では、値をもたない引数は渡されない。
+    fn value_args(&self) -> Vec<Local> {
+        self.body
+            .args_iter()
+            .filter(|&arg| self.local_repr(arg).is_some())
+            .collect()
+    }
+
+    fn label(&self, bb: BasicBlock) -> String {
+        format!(".L{}_{bb:?}", self.name)
+    }
+
+    /// 場所をアセンブリの被演算子として書く。
+    fn loc_str(&self, loc: Loc) -> String {
+        match loc {
+            Loc::Reg(reg) => reg.name().to_string(),
+            Loc::Stack(slot) => {
+                // 退避領域は、保存したレジスタの下に置く
+                let offset = 8 * (self.alloc.callee_saved.len() as u32 + slot + 1);
+                format!("qword ptr [rbp - {offset}]")
+            }
+        }
+    }
+
+    fn src_str(&self, src: Src) -> String {
+        match src {
+            Src::Loc(loc) => self.loc_str(loc),
+            Src::Imm(value) => value.to_string(),
+            Src::Str(index) => {
+                let len = self.str_literals[index].as_str().len() as u64;
+                format!("offset {} + {}", self.str_label(index), len << 32)
+            }
+            Src::Fn(def_id) => format!("offset {}", mangle(self.tcx, def_id)),
+        }
+    }
+
+    fn str_label(&self, index: usize) -> String {
+        format!(".L{}_str_{index}", self.name)
+    }
+
+    fn operand_src(&self, operand: &Operand<'tcx>) -> Option<Src> {
+        match operand {
+            Operand::Copy(place) => self.alloc.locs[place.local].map(Src::Loc),
+            Operand::Constant(box ConstOperand {
+                const_: ConstValue::Str(symbol),
+                ..
+            }) => {
+                let index = self.str_literals.iter().position(|s| s == symbol).unwrap();
+                Some(Src::Str(index))
+            }
+            Operand::Constant(box ConstOperand {
+                const_: ConstValue::FnPtr(def_id),
+                ..
+            }) => Some(Src::Fn(*def_id)),
+            Operand::Constant(box ConstOperand { ty, const_, .. }) => {
+                let repr = value_repr(*ty)?;
+                Some(Src::Imm(const_imm(repr, *const_)))
+            }
+        }
+    }
+
+    fn write_fn(&self, w: &mut dyn Write) -> fmt::Result {
+        // ライブラリのステロの関数は、他のステロのオブジェクトファイルからリンクできるようにする
+        if self.tcx.sess.opts.stelo_type == SteloType::Lib {
+            writeln!(w, "{INDENT}.globl {}", self.name)?;
+        }
+        writeln!(w, "{}:", self.name)?;
+        self.write_prologue(w)?;
+
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            writeln!(w, "{}:", self.label(bb))?;
+
+            for statement in &data.statements {
+                if let StatementKind::Assign(box (place, rvalue)) = &statement.kind
+                    && let Some(dst) = self.alloc.locs[place.local]
+                {
+                    self.write_rvalue(dst, rvalue, w)?;
+                }
+            }
+
+            self.write_terminator(bb, &data.terminator().kind, w)?;
+        }
+
+        self.write_panic_stubs(w)?;
+        self.write_str_literals(w)
+    }
+
+    fn write_str_literals(&self, w: &mut dyn Write) -> fmt::Result {
+        if self.str_literals.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(w, "{INDENT}.section .rodata")?;
+        for (index, symbol) in self.str_literals.iter().enumerate() {
+            writeln!(w, "{}:", self.str_label(index))?;
+            write_ascii(symbol.as_str().as_bytes(), w)?;
+        }
+        writeln!(w, "{INDENT}.text")
+    }
+
+    fn panic_label(&self, bb: BasicBlock) -> String {
+        format!(".L{}_panic_{bb:?}", self.name)
+    }
+
+    /// 各 `Assert` が失敗した場合に、メッセージを渡して `stelo_panic` へ移る命令と、
+    /// そのメッセージを出力する。
+    fn write_panic_stubs(&self, w: &mut dyn Write) -> fmt::Result {
+        let mut messages = Vec::new();
+
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            let terminator = data.terminator();
+            let TerminatorKind::Assert { msg, .. } = &terminator.kind else {
+                continue;
+            };
+
+            let location = self
+                .tcx
+                .sess
+                .source_map()
+                .span_to_location_string(terminator.source_info.span);
+            let message = format!("{}\n", msg.panic_message(self.tcx.sess.opts.lang, &location));
+            let message_label = format!(".L{}_msg_{bb:?}", self.name);
+
+            writeln!(w, "{}:", self.panic_label(bb))?;
+            writeln!(w, "{INDENT}lea rsi, [rip + {message_label}]")?;
+            writeln!(w, "{INDENT}mov edx, {}", message.len())?;
+            writeln!(w, "{INDENT}jmp stelo_panic")?;
+            messages.push((message_label, message));
+        }
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(w, "{INDENT}.section .rodata")?;
+        for (label, message) in messages {
+            writeln!(w, "{label}:")?;
+            write_ascii(message.as_bytes(), w)?;
+        }
+        writeln!(w, "{INDENT}.text")
+    }
+
+    /// フレームを作り、呼び出し先で保存されるレジスタを保存して、引数を割り当てた場所に移す。
+    ///
+    /// フレームは上から順に、保存した `rbp`、保存したレジスタ、退避領域からなる。
+    fn write_prologue(&self, w: &mut dyn Write) -> fmt::Result {
+        writeln!(w, "{INDENT}push rbp")?;
+        writeln!(w, "{INDENT}mov rbp, rsp")?;
+        for reg in &self.alloc.callee_saved {
+            writeln!(w, "{INDENT}push {}", reg.name())?;
+        }
+
+        // 関数を呼び出す時点で `rsp` が 16 バイト境界に揃うようにする
+        let saved = self.alloc.callee_saved.len() as u32;
+        let spill_size = 8 * (self.alloc.spill_slots + (saved + self.alloc.spill_slots) % 2);
+        if spill_size != 0 {
+            writeln!(w, "{INDENT}sub rsp, {spill_size}")?;
+        }
+
+        // 引数のレジスタが他の引数の移動先になりうるため、一度スタックを経由する
+        let args = self.value_args();
+        let reg_args = args.iter().zip(ARG_REGS).collect::<Vec<_>>();
+        for &(_, reg) in &reg_args {
+            writeln!(w, "{INDENT}push {}", reg.name())?;
+        }
+        for &(&arg, _) in reg_args.iter().rev() {
+            match self.alloc.locs[arg] {
+                Some(loc) => writeln!(w, "{INDENT}pop {}", self.loc_str(loc))?,
+                None => writeln!(w, "{INDENT}add rsp, 8")?,
+            }
+        }
+
+        // 7 番目以降の引数は、戻りアドレスと保存した `rbp` の上にある
+        for (i, &arg) in args.iter().enumerate().skip(ARG_REGS.len()) {
+            if let Some(loc) = self.alloc.locs[arg] {
+                let offset = 16 + 8 * (i - ARG_REGS.len());
+                writeln!(w, "{INDENT}mov rax, qword ptr [rbp + {offset}]")?;
+                self.write_store(loc, w)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_epilogue(&self, w: &mut dyn Write) -> fmt::Result {
+        let saved = self.alloc.callee_saved.len();
+        if saved != 0 {
+            writeln!(w, "{INDENT}lea rsp, [rbp - {}]", 8 * saved)?;
+            for reg in self.alloc.callee_saved.iter().rev() {
+                writeln!(w, "{INDENT}pop {}", reg.name())?;
+            }
+        } else {
+            writeln!(w, "{INDENT}mov rsp, rbp")?;
+        }
+        writeln!(w, "{INDENT}pop rbp")?;
+        writeln!(w, "{INDENT}ret")
+    }
+
+    /// `rax` に値を読み込む。
+    fn write_load(&self, src: Src, w: &mut dyn Write) -> fmt::Result {
+        let mnemonic = if let Src::Str(_) | Src::Fn(_) = src { "movabs" } else { "mov" };
+        writeln!(w, "{INDENT}{mnemonic} rax, {}", self.src_str(src))
+    }
+
+    /// `rax` の値を格納する。
+    fn write_store(&self, dst: Loc, w: &mut dyn Write) -> fmt::Result {
+        writeln!(w, "{INDENT}mov {}, rax", self.loc_str(dst))
+    }
+
+    /// `rax` と被演算子の二項演算の命令を書く。
+    /// 32 ビットに収まらない即値は、一度 `rcx` に読み込む。
+    fn write_alu(&self, op: &str, src: Src, w: &mut dyn Write) -> fmt::Result {
+        match src {
+            Src::Imm(value) if i32::try_from(value).is_err() => {
+                writeln!(w, "{INDENT}mov rcx, {value}")?;
+                writeln!(w, "{INDENT}{op} rax, rcx")
+            }
+            Src::Imm(value) if op == "imul" => writeln!(w, "{INDENT}imul rax, rax, {value}"),
+            _ => writeln!(w, "{INDENT}{op} rax, {}", self.src_str(src)),
+        }
+    }
+
+    fn write_rvalue(&self, dst: Loc, rvalue: &Rvalue<'tcx>, w: &mut dyn Write) -> fmt::Result {
+        match rvalue {
+            Rvalue::Use(operand) => {
+                let Some(src) = self.operand_src(operand) else {
+                    return Ok(());
+                };
+                self.write_move(dst, src, w)
+            }
+            Rvalue::BinaryOp(op, box (lhs, rhs)) if self.operand_ty(lhs).is_str() => {
+                let helper = match op {
+                    BinOp::Add => "stelo_str_concat",
+                    BinOp::Eq | BinOp::Ne => "stelo_str_eq",
+                    _ => unreachable!("bug: 文字列に適用できない演算子: {op:?}"),
+                };
+                self.write_push(self.operand_src(rhs).unwrap(), w)?;
+                self.write_push(self.operand_src(lhs).unwrap(), w)?;
+                writeln!(w, "{INDENT}call {helper}")?;
+                writeln!(w, "{INDENT}add rsp, 16")?;
+                if *op == BinOp::Ne {
+                    writeln!(w, "{INDENT}xor eax, 1")?;
+                }
+                self.write_store(dst, w)
+            }
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+                let repr = value_repr(self.operand_ty(lhs)).unwrap();
+                let lhs = self.operand_src(lhs).unwrap();
+                let rhs = self.operand_src(rhs).unwrap();
+                self.write_binary_op(*op, repr, lhs, rhs, w)?;
+                self.write_store(dst, w)
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = self.operand_ty(operand);
+                let repr = value_repr(ty).unwrap();
+                self.write_load(self.operand_src(operand).unwrap(), w)?;
+                match op {
+                    UnOp::Not if ty.is_bool() => writeln!(w, "{INDENT}xor rax, 1")?,
+                    UnOp::Not => {
+                        writeln!(w, "{INDENT}not rax")?;
+                        write_wrap(repr, w)?;
+                    }
+                    UnOp::Neg => {
+                        writeln!(w, "{INDENT}neg rax")?;
+                        write_wrap(repr, w)?;
+                    }
+                }
+                self.write_store(dst, w)
+            }
+        }
+    }
+
+    /// 値を移す。レジスタと即値はそのまま移し、メモリ同士は `rax` を経由する。
+    fn write_move(&self, dst: Loc, src: Src, w: &mut dyn Write) -> fmt::Result {
+        match (dst, src) {
+            (dst, Src::Loc(src)) if dst == src => Ok(()),
+            (Loc::Reg(reg), Src::Str(_) | Src::Fn(_)) => {
+                writeln!(w, "{INDENT}movabs {}, {}", reg.name(), self.src_str(src))
+            }
+            (_, Src::Str(_) | Src::Fn(_)) => {
+                self.write_load(src, w)?;
+                self.write_store(dst, w)
+            }
+            (Loc::Reg(_), _) | (_, Src::Loc(Loc::Reg(_))) => {
+                writeln!(w, "{INDENT}mov {}, {}", self.loc_str(dst), self.src_str(src))
+            }
+            (Loc::Stack(_), Src::Imm(value)) if i32::try_from(value).is_ok() => {
+                writeln!(w, "{INDENT}mov {}, {value}", self.loc_str(dst))
+            }
+            _ => {
+                self.write_load(src, w)?;
+                self.write_store(dst, w)
+            }
+        }
+    }
+
+    /// 二項演算の結果を `rax` に求める。
+    fn write_binary_op(
+        &self,
+        op: BinOp,
+        repr: IntRepr,
+        lhs: Src,
+        rhs: Src,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        self.write_load(lhs, w)?;
+
+        match op {
+            BinOp::Add | BinOp::Sub | BinOp::Mul => {
+                let mnemonic = match op {
+                    BinOp::Add => "add",
+                    BinOp::Sub => "sub",
+                    _ => "imul",
+                };
+                self.write_alu(mnemonic, rhs, w)?;
+                write_wrap(repr, w)
+            }
+            BinOp::Div | BinOp::Rem => {
+                // 除数は `rcx` に置き、被除数を `rdx:rax` に拡張する
+                writeln!(w, "{INDENT}mov rcx, {}", self.src_str(rhs))?;
+                if repr.signed {
+                    writeln!(w, "{INDENT}cqo")?;
+                    writeln!(w, "{INDENT}idiv rcx")?;
+                } else {
+                    writeln!(w, "{INDENT}xor edx, edx")?;
+                    writeln!(w, "{INDENT}div rcx")?;
+                }
+                if op == BinOp::Rem {
+                    writeln!(w, "{INDENT}mov rax, rdx")?;
+                }
+                // 64 ビットより狭い型では、`MIN / -1` が型の幅を超える
+                write_wrap(repr, w)
+            }
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                self.write_alu("cmp", rhs, w)?;
+                writeln!(w, "{INDENT}set{} al", condition_code(op, repr.signed))?;
+                writeln!(w, "{INDENT}movzx eax, al")
+            }
+            BinOp::AddOverflows | BinOp::SubOverflows | BinOp::MulOverflows => {
+                self.write_overflow_check(op, repr, rhs, w)?;
+                writeln!(w, "{INDENT}movzx eax, al")
+            }
+            // `MIN / -1` のみがオーバーフローする
+            BinOp::DivOverflows if repr.signed => {
+                let min = i64::MIN >> (64 - repr.bits);
+                self.write_alu("cmp", Src::Imm(min), w)?;
+                writeln!(w, "{INDENT}sete al")?;
+                writeln!(w, "{INDENT}mov rcx, {}", self.src_str(rhs))?;
+                writeln!(w, "{INDENT}cmp rcx, -1")?;
+                writeln!(w, "{INDENT}sete cl")?;
+                writeln!(w, "{INDENT}and al, cl")?;
+                writeln!(w, "{INDENT}movzx eax, al")
+            }
+            BinOp::DivOverflows => writeln!(w, "{INDENT}xor eax, eax"),
+        }
+    }
+
+    /// `rax` の左辺との加算、減算、乗算がオーバーフローするかどうかを `al` に求める。
+    fn write_overflow_check(
+        &self,
+        op: BinOp,
+        repr: IntRepr,
+        rhs: Src,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        // 64 ビットより狭い型の演算は 64 ビットで正確に計算できるため、折り返した結果と比べる
+        if repr.bits < 64 {
+            let mnemonic = match op {
+                BinOp::AddOverflows => "add",
+                BinOp::SubOverflows => "sub",
+                _ => "imul",
+            };
+            self.write_alu(mnemonic, rhs, w)?;
+            writeln!(w, "{INDENT}mov rdx, rax")?;
+            write_wrap(repr, w)?;
+            writeln!(w, "{INDENT}cmp rax, rdx")?;
+            return writeln!(w, "{INDENT}setne al");
+        }
+
+        match (op, repr.signed) {
+            (BinOp::AddOverflows, signed) => {
+                self.write_alu("add", rhs, w)?;
+                writeln!(w, "{INDENT}set{} al", if signed { "o" } else { "c" })
+            }
+            (BinOp::SubOverflows, signed) => {
+                self.write_alu("sub", rhs, w)?;
+                writeln!(w, "{INDENT}set{} al", if signed { "o" } else { "c" })
+            }
+            (_, true) => {
+                self.write_alu("imul", rhs, w)?;
+                writeln!(w, "{INDENT}seto al")
+            }
+            // 符号なしの乗算は、`rdx:rax` への積の上位が 0 でない場合にオーバーフローする
+            (_, false) => {
+                writeln!(w, "{INDENT}mov rcx, {}", self.src_str(rhs))?;
+                writeln!(w, "{INDENT}mul rcx")?;
+                writeln!(w, "{INDENT}setc al")
+            }
+        }
+    }
+
+    fn write_terminator(
+        &self,
+        bb: BasicBlock,
+        kind: &TerminatorKind<'tcx>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        match kind {
+            TerminatorKind::Goto { target } => self.write_goto(bb, *target, w),
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let repr = value_repr(self.operand_ty(discr)).unwrap();
+                self.write_load(self.operand_src(discr).unwrap(), w)?;
+
+                for &(value, target) in &targets.values {
+                    if value == 0 {
+                        writeln!(w, "{INDENT}test rax, rax")?;
+                    } else {
+                        let value = const_imm(repr, ConstValue::Int(value));
+                        self.write_alu("cmp", Src::Imm(value), w)?;
+                    }
+                    writeln!(w, "{INDENT}je {}", self.label(target))?;
+                }
+                self.write_goto(bb, targets.otherwise, w)
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+            } => {
+                let callee = match *self.operand_ty(func).kind() {
+                    TyKind::FnDef(def_id) => Callee::Direct(mangle(self.tcx, def_id)),
+                    TyKind::FnPtr(_) => Callee::Indirect(self.operand_src(func).unwrap()),
+                    _ => unreachable!("bug: 関数ではない値の呼び出し: {func:?}"),
+                };
+
+                let args = args
+                    .iter()
+                    .filter(|arg| value_repr(self.operand_ty(arg)).is_some())
+                    .map(|arg| self.operand_src(arg).unwrap())
+                    .collect::<Vec<_>>();
+                self.write_call(&callee, &args, w)?;
+
+                if let Some(dst) = self.alloc.locs[destination.local] {
+                    self.write_store(dst, w)?;
+                }
+                self.write_goto(bb, *target, w)
+            }
+            TerminatorKind::Assert {
+                cond, expected, target, ..
+            } => {
+                self.write_load(self.operand_src(cond).unwrap(), w)?;
+                writeln!(w, "{INDENT}test rax, rax")?;
+                let jump = if *expected { "je" } else { "jne" };
+                writeln!(w, "{INDENT}{jump} {}", self.panic_label(bb))?;
+                self.write_goto(bb, *target, w)
+            }
+            TerminatorKind::Return => {
+                if let Some(loc) = self.alloc.locs[RETURN_PLACE] {
+                    self.write_load(Src::Loc(loc), w)?;
+                }
+                self.write_epilogue(w)
+            }
+            TerminatorKind::Unreachable => writeln!(w, "{INDENT}ud2"),
+        }
+    }
+
+    /// 引数を ABI に従って渡し、関数を呼び出す。戻り値は `rax` に残る。
+    fn write_call(&self, callee: &Callee, args: &[Src], w: &mut dyn Write) -> fmt::Result {
+        let stack_args = args.len().saturating_sub(ARG_REGS.len());
+        // 呼び出しの時点で `rsp` が 16 バイト境界に揃うようにする
+        let padding = stack_args % 2;
+        if padding != 0 {
+            writeln!(w, "{INDENT}sub rsp, 8")?;
+        }
+
+        // スタックで渡す引数は、右から順に積む。
+        // レジスタで渡す引数も、引数のレジスタに割り当てた変数を上書きしないよう、一度スタックに積む
+        for &arg in args.iter().rev() {
+            self.write_push(arg, w)?;
+        }
+        // 関数ポインタも引数のレジスタに割り当てられうるため、引数と同様にスタックを経由する
+        if let Callee::Indirect(src) = *callee {
+            self.write_push(src, w)?;
+            writeln!(w, "{INDENT}pop rax")?;
+        }
+        for reg in ARG_REGS.iter().take(args.len()) {
+            writeln!(w, "{INDENT}pop {}", reg.name())?;
+        }
+
+        match callee {
+            Callee::Direct(name) => writeln!(w, "{INDENT}call {name}")?,
+            Callee::Indirect(_) => writeln!(w, "{INDENT}call rax")?,
+        }
+
+        let cleanup = 8 * (stack_args + padding);
+        if cleanup != 0 {
+            writeln!(w, "{INDENT}add rsp, {cleanup}")?;
+        }
+        Ok(())
+    }
+
+    fn write_push(&self, src: Src, w: &mut dyn Write) -> fmt::Result {
+        match src {
+            Src::Imm(value) if i32::try_from(value).is_err() => {
+                writeln!(w, "{INDENT}mov rax, {value}")?;
+                writeln!(w, "{INDENT}push rax")
+            }
+            Src::Str(_) | Src::Fn(_) => {
+                self.write_load(src, w)?;
+                writeln!(w, "{INDENT}push rax")
+            }
+            _ => writeln!(w, "{INDENT}push {}", self.src_str(src)),
+        }
+    }
+
+    /// `from` の末尾から `target` へ移る。直後のブロックへ移る場合は命令を出力しない。
+    fn write_goto(&self, from: BasicBlock, target: BasicBlock, w: &mut dyn Write) -> fmt::Result {
+        if target == from + 1 {
+            return Ok(());
+        }
+        writeln!(w, "{INDENT}jmp {}", self.label(target))
+    }
+}
+
+/// 64 ビットより狭い型の演算結果を、型の幅で折り返して拡張し直す。
+fn write_wrap(repr: IntRepr, w: &mut dyn Write) -> fmt::Result {
+    let instr = match (repr.bits, repr.signed) {
+        (8, true) => "movsx rax, al",
+        (16, true) => "movsx rax, ax",
+        (32, true) => "movsxd rax, eax",
+        (8, false) => "movzx eax, al",
+        (16, false) => "movzx eax, ax",
+        // 32 ビットのレジスタへの書き込みは、上位 32 ビットをゼロにする
+        (32, false) => "mov eax, eax",
+        _ => return Ok(()),
+    };
+    writeln!(w, "{INDENT}{instr}")
+}
+
+/// 比較演算に対応する条件コード。符号なし整数は `b` (below) と `a` (above) で比較する。
+fn condition_code(op: BinOp, signed: bool) -> &'static str {
+    match (op, signed) {
+        (BinOp::Eq, _) => "e",
+        (BinOp::Ne, _) => "ne",
+        (BinOp::Lt, true) => "l",
+        (BinOp::Le, true) => "le",
+        (BinOp::Gt, true) => "g",
+        (BinOp::Ge, true) => "ge",
+        (BinOp::Lt, false) => "b",
+        (BinOp::Le, false) => "be",
+        (BinOp::Gt, false) => "a",
+        (BinOp::Ge, false) => "ae",
+        _ => unreachable!("bug: 比較ではない演算子: {op:?}"),
+    }
+}
+
+/// 定数を、レジスタに保持する 64 ビットの値にする。
+fn const_imm(repr: IntRepr, const_: ConstValue) -> i64 {
+    match const_ {
+        ConstValue::Int(bits) => {
+            let shift = 128 - repr.bits;
+            if repr.signed {
+                (((bits << shift) as i128) >> shift) as i64
+            } else {
+                ((bits << shift) >> shift) as i64
+            }
+        }
+        ConstValue::Bool(b) => b as i64,
+        ConstValue::Char(c) => c as i64,
+        ConstValue::Float(_) | ConstValue::Str(_) | ConstValue::ZeroSized | ConstValue::FnPtr(_) => {
+            unreachable!("bug: 整数ではない定数: {const_:?}")
+        }
+    }
+}
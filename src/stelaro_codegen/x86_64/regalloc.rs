@@ -0,0 +1,267 @@
+//! ローカル変数に対する線形走査 (linear scan) レジスタ割り当て。
+//!
+//! Poletto と Sarkar による "Linear Scan Register Allocation" に基づいて設計されています。
+//! 基本ブロックを番号の順に並べて各文と終端命令に位置を振り、
+//! 各ローカル変数の生存区間を、その変数が生存するすべての位置を含む一つの区間で近似する。
+//!
+//! 関数呼び出しをまたいで生存する変数には、呼び出し先で保存されるレジスタのみを割り当てる。
+//! 割り当てられるレジスタが無い場合は、区間の終わりが最も遠い変数をスタックに退避する。
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_mir::{
+    BasicBlock, Body, Local, Operand, RETURN_PLACE, Rvalue, StatementKind, TerminatorKind,
+};
+
+use super::Reg;
+
+
+/// 呼び出し先で保存されるレジスタ。使用する場合は関数の入口で保存する。
+const CALLEE_SAVED: [Reg; 5] = [Reg::Rbx, Reg::R12, Reg::R13, Reg::R14, Reg::R15];
+
+/// 関数呼び出しをまたがない変数に割り当てるレジスタ。呼び出し元で保存されるものを優先する。
+/// `rax`, `rcx`, `rdx` は命令の選択で作業用に使うため、割り当てない。
+const ALLOCATABLE: [Reg; 11] = [
+    Reg::Rdi, Reg::Rsi, Reg::R8, Reg::R9, Reg::R10, Reg::R11,
+    Reg::Rbx, Reg::R12, Reg::R13, Reg::R14, Reg::R15,
+];
+
+/// ローカル変数の値を格納する場所。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Loc {
+    Reg(Reg),
+    /// スタック上の退避領域の番号。
+    Stack(u32),
+}
+
+/// レジスタ割り当ての結果。
+pub(super) struct Allocation {
+    /// 値をもつローカル変数の場所。値をもたない変数、または一度も使われない変数は `None`。
+    pub locs: IndexVec<Local, Option<Loc>>,
+    /// 使用した、呼び出し先で保存されるレジスタ。
+    pub callee_saved: Vec<Reg>,
+    /// スタック上の退避領域の数。
+    pub spill_slots: u32,
+}
+
+/// ローカル変数の生存区間。両端の位置を含む。
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    local: Local,
+    start: u32,
+    end: u32,
+    /// 区間の内部に関数呼び出しがあるかどうか。
+    crosses_call: bool,
+}
+
+/// `has_value` が `true` のローカル変数に、レジスタかスタック上の場所を割り当てる。
+pub(super) fn allocate(body: &Body<'_>, has_value: &IndexVec<Local, bool>) -> Allocation {
+    let mut intervals = live_intervals(body, has_value);
+    intervals.sort_by_key(|interval| (interval.start, interval.local));
+
+    let mut locs = IndexVec::from_elem_n(None, body.local_decls.len());
+    let mut callee_saved = Vec::new();
+    let mut spill_slots = 0;
+    let mut active: Vec<(Interval, Reg)> = Vec::new();
+
+    let mut spill = |locs: &mut IndexVec<Local, Option<Loc>>, local: Local| {
+        locs[local] = Some(Loc::Stack(spill_slots));
+        spill_slots += 1;
+    };
+
+    for interval in intervals {
+        // 同じ位置では、値を読んでから書き込むため、この位置で終わる区間のレジスタは再利用できる
+        active.retain(|(other, _)| other.end > interval.start);
+
+        let candidates: &[Reg] = if interval.crosses_call {
+            &CALLEE_SAVED
+        } else {
+            &ALLOCATABLE
+        };
+
+        let free = candidates
+            .iter()
+            .copied()
+            .find(|reg| active.iter().all(|(_, used)| used != reg));
+
+        let reg = match free {
+            Some(reg) => reg,
+            None => {
+                // 候補のレジスタを使っている区間のうち、最も遠くまで生存するもの
+                let (victim_index, &(victim, reg)) = active
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, reg))| candidates.contains(reg))
+                    .max_by_key(|(_, (other, _))| other.end)
+                    .unwrap();
+
+                if victim.end <= interval.end {
+                    spill(&mut locs, interval.local);
+                    continue;
+                }
+
+                spill(&mut locs, victim.local);
+                active.swap_remove(victim_index);
+                reg
+            }
+        };
+
+        if CALLEE_SAVED.contains(&reg) && !callee_saved.contains(&reg) {
+            callee_saved.push(reg);
+        }
+        locs[interval.local] = Some(Loc::Reg(reg));
+        active.push((interval, reg));
+    }
+
+    callee_saved.sort();
+    Allocation {
+        locs,
+        callee_saved,
+        spill_slots,
+    }
+}
+
+/// 各ローカル変数の生存区間を求める。
+///
+/// 位置 `0` は関数の入口で、引数はここで定義される。
+/// それ以降、基本ブロックの番号の順に、各文と終端命令に一つずつ位置を振る。
+fn live_intervals(body: &Body<'_>, has_value: &IndexVec<Local, bool>) -> Vec<Interval> {
+    let num_locals = body.local_decls.len();
+    let num_blocks = body.basic_blocks.len();
+
+    let mut block_start = IndexVec::<BasicBlock, u32>::from_elem_n(0, num_blocks);
+    let mut block_end = IndexVec::<BasicBlock, u32>::from_elem_n(0, num_blocks);
+    // 各基本ブロックの中で、定義より前に読まれる変数と、定義される変数
+    let mut uses = IndexVec::from_elem_n(IndexVec::from_elem_n(false, num_locals), num_blocks);
+    let mut defs = IndexVec::from_elem_n(IndexVec::from_elem_n(false, num_locals), num_blocks);
+
+    let mut ranges = IndexVec::<Local, Option<(u32, u32)>>::from_elem_n(None, num_locals);
+    let extend = |ranges: &mut IndexVec<Local, Option<(u32, u32)>>, local: Local, pos: u32| {
+        if has_value[local] {
+            let range = ranges[local].get_or_insert((pos, pos));
+            range.0 = range.0.min(pos);
+            range.1 = range.1.max(pos);
+        }
+    };
+
+    for arg in body.args_iter() {
+        extend(&mut ranges, arg, 0);
+    }
+
+    let mut call_positions = Vec::new();
+    let mut pos = 0;
+    for (bb, data) in body.basic_blocks.iter_enumerated() {
+        block_start[bb] = pos + 1;
+
+        let mut visit = |local: Local, is_def: bool, pos: u32, ranges: &mut _| {
+            if is_def {
+                defs[bb][local] = true;
+            } else if !defs[bb][local] {
+                uses[bb][local] = true;
+            }
+            extend(ranges, local, pos);
+        };
+
+        for statement in &data.statements {
+            pos += 1;
+            if let StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                for operand in rvalue_operands(rvalue) {
+                    if let Operand::Copy(place) = operand {
+                        visit(place.local, false, pos, &mut ranges);
+                    }
+                }
+                visit(place.local, true, pos, &mut ranges);
+            }
+        }
+
+        pos += 1;
+        match &data.terminator().kind {
+            TerminatorKind::SwitchInt { discr, .. } | TerminatorKind::Assert { cond: discr, .. } => {
+                if let Operand::Copy(place) = discr {
+                    visit(place.local, false, pos, &mut ranges);
+                }
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } => {
+                for arg in std::iter::once(func).chain(args) {
+                    if let Operand::Copy(place) = arg {
+                        visit(place.local, false, pos, &mut ranges);
+                    }
+                }
+                visit(destination.local, true, pos, &mut ranges);
+                call_positions.push(pos);
+            }
+            TerminatorKind::Return => visit(RETURN_PLACE, false, pos, &mut ranges),
+            TerminatorKind::Goto { .. } | TerminatorKind::Unreachable => {}
+        }
+        block_end[bb] = pos;
+    }
+
+    // 基本ブロックの出口で生存する変数を、不動点に達するまで後ろ向きに求める
+    let mut live_in = IndexVec::from_elem_n(IndexVec::from_elem_n(false, num_locals), num_blocks);
+    let mut live_out = live_in.clone();
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for bb in body.basic_blocks.indices().rev() {
+            let mut out = IndexVec::<Local, bool>::from_elem_n(false, num_locals);
+            for succ in body.basic_blocks[bb].terminator().successors() {
+                for (local, &live) in live_in[succ].iter_enumerated() {
+                    out[local] |= live;
+                }
+            }
+
+            let mut in_ = out.clone();
+            for local in in_.indices() {
+                in_[local] = uses[bb][local] || (out[local] && !defs[bb][local]);
+            }
+
+            if in_ != live_in[bb] || out != live_out[bb] {
+                live_in[bb] = in_;
+                live_out[bb] = out;
+                changed = true;
+            }
+        }
+    }
+
+    for bb in body.basic_blocks.indices() {
+        for local in live_in[bb].indices() {
+            if live_in[bb][local] {
+                extend(&mut ranges, local, block_start[bb]);
+            }
+            if live_out[bb][local] {
+                extend(&mut ranges, local, block_end[bb]);
+            }
+        }
+    }
+
+    ranges
+        .iter_enumerated()
+        .filter_map(|(local, range)| {
+            let (start, end) = (*range)?;
+            // 使われない引数には場所を割り当てない
+            if end == 0 {
+                return None;
+            }
+
+            Some(Interval {
+                local,
+                start,
+                end,
+                crosses_call: call_positions.iter().any(|&pos| start < pos && pos < end),
+            })
+        })
+        .collect()
+}
+
+/// 右辺値が読む被演算子。
+fn rvalue_operands<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Vec<&'a Operand<'tcx>> {
+    match rvalue {
+        Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => vec![operand],
+        Rvalue::BinaryOp(_, box (lhs, rhs)) => vec![lhs, rhs],
+    }
+}
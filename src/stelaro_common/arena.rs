@@ -82,6 +82,11 @@ impl Arena {
         self.inner.alloc_slice_copy(slice)
     }
 
+    #[inline]
+    pub fn alloc_str(&self, s: &str) -> &str {
+        self.inner.alloc_str(s)
+    }
+
     pub fn capacity(&self) -> usize {
         self.inner.chunk_capacity()
     }
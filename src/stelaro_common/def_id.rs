@@ -1,5 +1,6 @@
 //! rustc の `rustc_span/def_id.rs` に基づいて設計されています。
 
+use super::stable_hasher::HashStable;
 use super::{Hash64, Idx, StableHasher, Symbol, fingerprint::Fingerprint};
 use std::{fmt, hash::Hash};
 
@@ -109,6 +110,13 @@ impl Default for DefPathHash {
     }
 }
 
+impl HashStable for DefPathHash {
+    #[inline]
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        self.0.hash_stable(hasher);
+    }
+}
+
 stelaro_macros::newtype_index! {
     /// ステロ内の定義を一意に識別するインデックス。
     #[orderable]
@@ -0,0 +1,156 @@
+//! インクリメンタルコンパイルのキャッシュとステロのメタデータのための、バイナリ形式の読み書き。
+//!
+//! 整数はリトルエンディアンで、文字列とバイト列は長さを前に置いて書く。
+//! 読み込みは壊れたファイルや古い形式のファイルでも失敗するだけで、パニックしない。
+
+use super::Fingerprint;
+
+
+#[derive(Default)]
+pub struct Encoder {
+    data: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn emit_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn emit_bool(&mut self, value: bool) {
+        self.emit_u8(value as u8);
+    }
+
+    pub fn emit_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn emit_usize(&mut self, value: usize) {
+        self.data.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+    pub fn emit_fingerprint(&mut self, fingerprint: Fingerprint) {
+        self.data.extend_from_slice(&fingerprint.to_le_bytes());
+    }
+
+    pub fn emit_bytes(&mut self, bytes: &[u8]) {
+        self.emit_usize(bytes.len());
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn emit_str(&mut self, s: &str) {
+        self.emit_bytes(s.as_bytes());
+    }
+}
+
+pub struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Decoder { data }
+    }
+
+    /// すべてのデータを読み終えたかどうか
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (bytes, rest) = self.data.split_first_chunk::<N>()?;
+        self.data = rest;
+        Some(*bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Option<u8> {
+        let [value] = self.read_array()?;
+        Some(value)
+    }
+
+    pub fn read_bool(&mut self) -> Option<bool> {
+        match self.read_u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Option<u32> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_usize(&mut self) -> Option<usize> {
+        self.read_array().map(u64::from_le_bytes)?.try_into().ok()
+    }
+
+    pub fn read_fingerprint(&mut self) -> Option<Fingerprint> {
+        self.read_array().map(Fingerprint::from_le_bytes)
+    }
+
+    pub fn read_bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.read_usize()?;
+        let (bytes, rest) = self.data.split_at_checked(len)?;
+        self.data = rest;
+        Some(bytes)
+    }
+
+    pub fn read_str(&mut self) -> Option<&'a str> {
+        str::from_utf8(self.read_bytes()?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let fingerprint = Fingerprint::new(0x0123_4567_89ab_cdef_u64, 42_u64);
+
+        let mut encoder = Encoder::new();
+        encoder.emit_u8(7);
+        encoder.emit_bool(true);
+        encoder.emit_u32(0xdead_beef);
+        encoder.emit_usize(usize::MAX >> 1);
+        encoder.emit_fingerprint(fingerprint);
+        encoder.emit_str("ステロ");
+        encoder.emit_bytes(&[]);
+        let data = encoder.finish();
+
+        let mut decoder = Decoder::new(&data);
+        assert_eq!(decoder.read_u8(), Some(7));
+        assert_eq!(decoder.read_bool(), Some(true));
+        assert_eq!(decoder.read_u32(), Some(0xdead_beef));
+        assert_eq!(decoder.read_usize(), Some(usize::MAX >> 1));
+        assert_eq!(decoder.read_fingerprint(), Some(fingerprint));
+        assert_eq!(decoder.read_str(), Some("ステロ"));
+        assert_eq!(decoder.read_bytes(), Some(&[][..]));
+        assert!(decoder.is_empty());
+    }
+
+    /// 途中で切れたデータや不正な値は、パニックせずに `None` になる
+    #[test]
+    fn test_malformed_data() {
+        let mut encoder = Encoder::new();
+        encoder.emit_str("incremental");
+        let data = encoder.finish();
+
+        assert_eq!(Decoder::new(&data[..data.len() - 1]).read_str(), None);
+        assert_eq!(Decoder::new(&data[..4]).read_usize(), None);
+        assert_eq!(Decoder::new(&[2]).read_bool(), None);
+        assert_eq!(Decoder::new(&[]).read_u8(), None);
+
+        // 長さが残りのデータより長い
+        let mut encoder = Encoder::new();
+        encoder.emit_usize(usize::MAX);
+        assert_eq!(Decoder::new(&encoder.finish()).read_bytes(), None);
+    }
+}
@@ -43,11 +43,13 @@ impl LitKind {
                     LitKind::Float(symbol)
                 },
                 token::LitKind::Str => {
+                    // トークンのシンボルは前後の `"` を含む
                     let str = symbol.as_str();
+                    let str = &str[1..str.len() - 1];
                     if str.contains('\\') {
                         LitKind::Str(unescape_str(str))
                     } else {
-                        LitKind::Str(symbol)
+                        LitKind::Str(Symbol::intern(str))
                     }
                 },
             }
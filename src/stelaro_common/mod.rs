@@ -1,6 +1,7 @@
 pub mod arena;
 pub mod def_id;
 pub mod delayed_map;
+pub mod encoder;
 pub mod fatal_error;
 pub mod fingerprint;
 pub mod hashes;
@@ -36,7 +37,9 @@ pub use index_vec::IndexVec;
 pub use map::IndexMap;
 pub use slice::IndexSlice;
 pub use sorted_map::SortedMap;
-pub use source_map::{FileLoader, RealFileLoader, SourceMap, SourceMapInputs};
+pub use source_map::{
+    FileLoader, InMemoryFileLoader, LineTable, RealFileLoader, SourceMap, SourceMapInputs,
+};
 pub use span::{DUMMY_SPAN, Span, Spanned};
 // impl_hash_stable_trivial は stelaro_common 外部に公開されるべきではない
 pub use stable_hasher::{FromStableHash, StableHasher, StableHasherHash};
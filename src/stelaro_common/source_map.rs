@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io;
 use std::rc::Rc;
 use std::{
@@ -46,6 +47,13 @@ impl SourceMap {
         }
     }
 
+    /// `span` の開始位置を `ファイル名:行:列` の形式で表す。
+    pub fn span_to_location_string(&self, span: Span) -> String {
+        let file = self.file.borrow();
+        let loc = file.lookup_loc(span.start);
+        format!("{}:{}:{}", file.name.display(), loc.line, loc.col)
+    }
+
     pub fn truncate_span_to_item_header(&self, span: Span) -> Span {
         self.span_until_char(span, '{')
     }
@@ -106,6 +114,63 @@ impl FileLoader for RealFileLoader {
     }
 }
 
+/// パスと文字列の対応からファイルを読み込む `FileLoader`
+///
+/// ディスクに触れずに仮想的なファイルの集まりをコンパイルするために使う。
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryFileLoader {
+    files: HashMap<PathBuf, String>,
+}
+
+impl InMemoryFileLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `path` のファイルの内容を `src` とする。
+    /// 既に同じパスのファイルがあった場合は置き換え、以前の内容を返す。
+    pub fn insert(&mut self, path: impl Into<PathBuf>, src: impl Into<String>) -> Option<String> {
+        self.files.insert(path.into(), src.into())
+    }
+}
+
+impl<P: Into<PathBuf>, S: Into<String>> FromIterator<(P, S)> for InMemoryFileLoader {
+    fn from_iter<I: IntoIterator<Item = (P, S)>>(iter: I) -> Self {
+        InMemoryFileLoader {
+            files: iter
+                .into_iter()
+                .map(|(path, src)| (path.into(), src.into()))
+                .collect(),
+        }
+    }
+}
+
+impl FileLoader for InMemoryFileLoader {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("ファイル `{}` が見つかりません", path.display()),
+            )
+        })
+    }
+}
+
+/// ソースファイル上の位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Loc {
+    /// 1 始まりの行番号
+    pub line: usize,
+    /// 1 始まりの、文字単位の列番号
+    pub col: usize,
+    /// ファイル先頭からのバイト単位の位置
+    pub byte: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct SourceFile {
     pub name: PathBuf,
@@ -116,6 +181,25 @@ pub struct SourceFile {
 impl SourceFile {
     const MAX_FILE_SIZE: u32 = u32::MAX - 1;
 
+    /// 文字単位の位置 `pos` (`Span` の単位) に対応する `Loc` を得る。
+    /// ファイル終端を超える位置はファイル終端として扱う。
+    pub fn lookup_loc(&self, pos: u32) -> Loc {
+        let mut loc = Loc { line: 1, col: 1, byte: 0 };
+
+        for c in self.src.chars().take(pos as usize) {
+            loc.byte += c.len_utf8();
+
+            if c == '\n' {
+                loc.line += 1;
+                loc.col = 1;
+            } else {
+                loc.col += 1;
+            }
+        }
+
+        loc
+    }
+
     pub fn new(name: PathBuf, src: String) -> Self {
         let file_id = SourceFileId::from_file_name(&name);
 
@@ -127,6 +211,62 @@ impl SourceFile {
     }
 }
 
+/// 多数の位置を続けて `Loc` に変換するための、各行の開始位置の表。
+///
+/// `SourceFile::lookup_loc` はファイルの先頭から文字を数えるため、
+/// 中間表現の出力のように全てのノードの位置を求める場合はこちらを使う。
+pub struct LineTable {
+    src: Rc<String>,
+    /// 各行の開始位置の、文字単位とバイト単位の組
+    line_starts: Vec<(u32, usize)>,
+}
+
+impl LineTable {
+    pub fn new(file: &SourceFile) -> Self {
+        let mut line_starts = vec![(0, 0)];
+
+        for (pos, (byte, c)) in file.src.char_indices().enumerate() {
+            if c == '\n' {
+                line_starts.push((pos as u32 + 1, byte + 1));
+            }
+        }
+
+        LineTable {
+            src: Rc::clone(&file.src),
+            line_starts,
+        }
+    }
+
+    /// 文字単位の位置 `pos` に対応する `Loc` を得る。
+    /// ファイル終端を超える位置はファイル終端として扱う。
+    pub fn lookup_loc(&self, pos: u32) -> Loc {
+        let line = self.line_starts.partition_point(|&(start, _)| start <= pos) - 1;
+        let (line_start, mut byte) = self.line_starts[line];
+        let mut col = 1;
+
+        for c in self.src[byte..].chars().take((pos - line_start) as usize) {
+            byte += c.len_utf8();
+            col += 1;
+        }
+
+        Loc { line: line + 1, col, byte }
+    }
+
+    /// `span` が指すソースコードの文字列を得る。
+    pub fn snippet(&self, span: Span) -> &str {
+        let start = self.lookup_loc(span.start).byte;
+        let end = self.lookup_loc(span.end).byte.max(start);
+        &self.src[start..end]
+    }
+
+    /// `span` を `行:列-行:列` の形式で表す。
+    pub fn span_to_range_string(&self, span: Span) -> String {
+        let start = self.lookup_loc(span.start);
+        let end = self.lookup_loc(span.end);
+        format!("{}:{}-{}:{}", start.line, start.col, end.line, end.col)
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SourceFileId(pub Hash128);
 
@@ -151,3 +291,37 @@ pub fn get_source_map() -> Option<Rc<SourceMap>> {
 pub struct SourceMapInputs {
     pub file_loader: Box<dyn FileLoader + Send + Sync>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_table_matches_lookup_loc() {
+        let file = SourceFile::new("test.stelo".into(), "fn 主() {\n    1\n}\n".to_string());
+        let lines = LineTable::new(&file);
+
+        for pos in 0..=file.src.chars().count() as u32 + 2 {
+            assert_eq!(lines.lookup_loc(pos), file.lookup_loc(pos), "{pos}");
+        }
+
+        assert_eq!(lines.snippet((3..4).into()), "主");
+        assert_eq!(lines.span_to_range_string((9..16).into()), "2:1-3:2");
+    }
+
+    #[test]
+    fn test_in_memory_file_loader() {
+        let mut loader = InMemoryFileLoader::from_iter([("src/main.stelo", "fn main() {}")]);
+        assert_eq!(loader.insert("src/lib.stelo", "mod a {}"), None);
+
+        let source_map = SourceMap::with_inputs(SourceMapInputs {
+            file_loader: Box::new(loader),
+        });
+        let file = source_map.load_file(Path::new("src/main.stelo")).unwrap();
+        assert_eq!(file.src.as_str(), "fn main() {}");
+        assert_eq!(file.name, Path::new("src/main.stelo"));
+
+        let err = source_map.load_file(Path::new("src/missing.stelo")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
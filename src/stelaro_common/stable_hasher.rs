@@ -325,6 +325,13 @@ where
     }
 }
 
+impl<T: HashStable + ?Sized> HashStable for Box<T> {
+    #[inline]
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        (**self).hash_stable(hasher);
+    }
+}
+
 impl<T> HashStable for ::std::mem::Discriminant<T> {
     #[inline]
     fn hash_stable(&self, hasher: &mut StableHasher) {
@@ -5,7 +5,8 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use super::{SESSION_GLOBALS, span::Span};
+use super::stable_hasher::HashStable;
+use super::{Arena, SESSION_GLOBALS, StableHasher, span::Span};
 
 #[derive(Debug, Clone, Copy, Eq, PartialOrd, Ord)]
 pub struct Ident {
@@ -36,6 +37,14 @@ impl Hash for Ident {
     }
 }
 
+impl HashStable for Ident {
+    #[inline]
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        // `PartialEq` と同様に、スパンは含めない
+        self.name.hash_stable(hasher);
+    }
+}
+
 impl fmt::Display for Ident {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.name.as_str())
@@ -75,6 +84,14 @@ impl Symbol {
     }
 }
 
+impl HashStable for Symbol {
+    #[inline]
+    fn hash_stable(&self, hasher: &mut StableHasher) {
+        // シンボルのインデックスはセッションごとに異なるため、文字列の内容をハッシュする
+        self.as_str().hash_stable(hasher);
+    }
+}
+
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
@@ -84,6 +101,8 @@ impl fmt::Display for Symbol {
 pub struct Interner(RefCell<InternerInner>);
 
 struct InternerInner {
+    /// インターンされた文字列の実体。呼び出し元の文字列はすぐに解放されうるため、複製して保持する
+    arena: Arena,
     strings: HashMap<&'static str, u32>,
     symbols: Vec<&'static str>,
     next_idx: u32,
@@ -114,10 +133,10 @@ impl InternerInner {
 
         let idx = self.next_idx;
 
-        // SAFETY: Internerが生きている間しかこの参照にアクセスできない
+        // SAFETY: 文字列は `arena` が所有し、Internerが生きている間しかこの参照にアクセスできない
         // また、&'static str は外部へ持ち込まれない
         // ライフタイムを'static に拡張
-        let string: &'static str = unsafe { &*(string as *const str) };
+        let string: &'static str = unsafe { &*(self.arena.alloc_str(string) as *const str) };
 
         self.strings.insert(string, idx);
         self.next_idx += 1;
@@ -131,6 +150,7 @@ impl InternerInner {
 impl Default for Interner {
     fn default() -> Self {
         let mut inner = InternerInner {
+            arena: Arena::new(),
             strings: HashMap::with_capacity(1024),
             symbols: Vec::with_capacity(1024),
             next_idx: 0,
@@ -212,8 +232,22 @@ define_keywords_and_symbols! {
     STELARO_OUT => "stelaro_out",
     MAIN => "main",
     LET => "let",
+    ISIZE => "isize",
+    I8 => "i8",
+    I16 => "i16",
     I32 => "i32",
     I64 => "i64",
+    I128 => "i128",
+    USIZE => "usize",
+    U8 => "u8",
+    U16 => "u16",
+    U32 => "u32",
+    U64 => "u64",
+    U128 => "u128",
+    F32 => "f32",
+    F64 => "f64",
     BOOL => "bool",
     CHAR => "char",
+    STR => "str",
+    STD => "std",
 }
@@ -1,6 +1,36 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use crate::stelaro_common::TypedArena;
-use crate::stelaro_ty::{Ty, TyKind, ty::{FloatTy, IntTy, UintTy}};
+use crate::stelaro_ty::{
+    Ty, TyKind,
+    ty::{FloatTy, IntTy, UintTy, WithCachedTypeInfo},
+    visit::FlagComputation,
+};
+
 
+/// 型のインターナー。
+/// 同一の `TyKind` に対して、常に同一の参照をもつ `Ty` を返す。
+pub struct CtxtInterners<'tcx> {
+    arena: &'tcx TypedArena<'tcx, WithCachedTypeInfo<TyKind<'tcx>>>,
+    types: RefCell<HashMap<TyKind<'tcx>, Ty<'tcx>>>,
+}
+
+impl<'tcx> CtxtInterners<'tcx> {
+    pub fn new(arena: &'tcx TypedArena<'tcx, WithCachedTypeInfo<TyKind<'tcx>>>) -> Self {
+        CtxtInterners {
+            arena,
+            types: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn intern_ty(&self, kind: TyKind<'tcx>) -> Ty<'tcx> {
+        *self.types.borrow_mut().entry(kind).or_insert_with(|| {
+            let flags = FlagComputation::for_kind(&kind).flags;
+            Ty(self.arena.alloc(WithCachedTypeInfo { internee: kind, flags }))
+        })
+    }
+}
 
 pub struct CommonTypes<'tcx> {
     pub unit: Ty<'tcx>,
@@ -25,10 +55,8 @@ pub struct CommonTypes<'tcx> {
 }
 
 impl<'tcx> CommonTypes<'tcx> {
-    pub fn new(
-        interner: &'tcx TypedArena<'tcx, TyKind<'tcx>>,
-    ) -> CommonTypes<'tcx> {
-        let mk = |ty| Ty(interner.alloc(ty));
+    pub fn new(interners: &CtxtInterners<'tcx>) -> CommonTypes<'tcx> {
+        let mk = |ty| interners.intern_ty(ty);
 
         use TyKind::*;
         use IntTy::*;
@@ -0,0 +1,46 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsQuery;
+
+impl<'dcx> DiagsQuery {
+    pub fn cycle(dcx: DiagCtxtHandle<'dcx>, query: &str, cycle: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::Cycle.into());
+        diag.set_message(dcx.translate(messages::QUERY_CYCLE, &[("query", &query)]));
+        diag.set_label(span, dcx.translate(messages::QUERY_CYCLE_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::QUERY_CYCLE_HELP, &[("cycle", &cycle)]));
+
+        diag
+    }
+
+    /// 型エイリアスの定義が、直接または他の型エイリアスを通して自身を参照している。
+    /// クエリの循環と同じエラーコードで報告する。
+    pub fn ty_alias_cycle(
+        dcx: DiagCtxtHandle<'dcx>,
+        alias: &str,
+        cycle: &str,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::Cycle.into());
+        diag.set_message(dcx.translate(messages::TY_ALIAS_CYCLE, &[("alias", &alias)]));
+        diag.set_label(span, dcx.translate(messages::TY_ALIAS_CYCLE_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::QUERY_CYCLE_HELP, &[("cycle", &cycle)]));
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    Cycle = 901,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
@@ -0,0 +1,66 @@
+//! クエリシステムで報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    QUERY_CYCLE {
+        ja: "{query}が循環しています",
+        en: "cycle detected when {query}",
+    }
+
+    QUERY_CYCLE_LABEL {
+        ja: "この計算には、それ自身の結果が必要です",
+        en: "this computation requires its own result",
+    }
+
+    QUERY_CYCLE_HELP {
+        ja: "循環: {cycle}",
+        en: "cycle: {cycle}",
+    }
+
+    TY_ALIAS_CYCLE {
+        ja: "型エイリアス `{alias}` が自身を参照しています",
+        en: "type alias `{alias}` refers to itself",
+    }
+
+    TY_ALIAS_CYCLE_LABEL {
+        ja: "この型エイリアスを展開するには、それ自身を展開する必要があります",
+        en: "expanding this type alias requires expanding it again",
+    }
+
+    QUERY_SOURCE_SPAN {
+        ja: "`{name}` の位置の取得",
+        en: "getting the span of `{name}`",
+    }
+
+    QUERY_LOCAL_DEF_KIND {
+        ja: "`{name}` の定義の種類の取得",
+        en: "getting the definition kind of `{name}`",
+    }
+
+    QUERY_SIR_STELO {
+        ja: "SIR への変換",
+        en: "lowering the AST to SIR",
+    }
+
+    QUERY_OPT_SIR_OWNER_NODES {
+        ja: "`{name}` の SIR の取得",
+        en: "getting the SIR of `{name}`",
+    }
+
+    QUERY_FN_SIG {
+        ja: "`{name}` のシグネチャの計算",
+        en: "computing the signature of `{name}`",
+    }
+
+    QUERY_TYPE_OF {
+        ja: "型エイリアス `{name}` の展開",
+        en: "expanding type alias `{name}`",
+    }
+
+    QUERY_TYPECK {
+        ja: "`{name}` の型検査",
+        en: "type-checking `{name}`",
+    }
+}
@@ -1,21 +1,29 @@
 pub mod context;
+mod diagnostics;
+pub mod messages;
+pub mod query;
 pub mod sir_map;
 
+use std::cell::RefCell;
 use std::ops::Deref;
-use std::{cell::RefCell, collections::HashMap};
 
+use crate::stelaro_ast::ast;
 use crate::stelaro_common::{
-    Arena, DefId, IndexVec, LocalDefId, STELO_DEF_ID, Span, StableSteloId, Symbol, TypedArena,
+    Arena, DefId, DefPathHash, LOCAL_STELO, LocalDefId, STELO_DEF_ID, Span,
+    StableSteloId, SteloNum, Symbol, TypedArena,
 };
-use crate::stelaro_context::context::CommonTypes;
+use crate::stelaro_context::context::{CommonTypes, CtxtInterners};
+use crate::stelaro_context::query::{Providers, QuerySystem};
 use crate::stelaro_diagnostics::DiagCtxtHandle;
+use crate::stelaro_incremental::IncrementalCache;
+use crate::stelaro_lint::levels::LintLevelMap;
+use crate::stelaro_metadata::CStore;
 use crate::stelaro_session::Session;
 use crate::stelaro_sir::{
     def::DefKind,
-    definitions::{self, Definitions},
-    sir,
+    definitions::{self, DefPath, Definitions},
 };
-use crate::stelaro_ty::{Ty, TyKind};
+use crate::stelaro_ty::{FnSig, FnSigTys, ResolverAstLowering, Ty, TyKind, ty::WithCachedTypeInfo};
 
 #[derive(Clone, Copy)]
 pub struct TyCtxt<'tcx> {
@@ -27,25 +35,30 @@ pub struct GlobalCtxt<'tcx> {
     pub sir_arena: &'tcx Arena,
     pub sess: &'tcx Session,
 
+    /// コンパイル中のステロの名前
+    pub stelo_name: Symbol,
+
+    pub interners: CtxtInterners<'tcx>,
+
     pub types: CommonTypes<'tcx>,
 
     /// DefId から実際の定義へのマップ
     pub definitions: RefCell<Definitions>,
 
-    /// 同一のTyKind<'ctx>に対して同一の参照を保持させるためのインターナー
-    pub types_interner: RefCell<HashMap<TyKind<'tcx>, Ty<'tcx>>>,
+    /// 名前解決を終えた AST と、その結果。`sir_stelo` クエリが SIR に変換する際に取り出す
+    pub resolver_for_lowering: RefCell<Option<(ResolverAstLowering, ast::Stelo)>>,
 
-    /// 定義がもつ `Span` への参照
-    pub source_span: RefCell<IndexVec<LocalDefId, Span>>,
+    /// AST に対するリントの実行時に記録した、MIR に対するリントのレベル
+    pub lint_levels: RefCell<LintLevelMap<LocalDefId>>,
 
-    /// 定義がもつ `DefKind` への参照
-    pub def_kind_table: RefCell<IndexVec<LocalDefId, DefKind>>,
+    /// クエリの関数と、計算済みの結果
+    pub queries: QuerySystem<'tcx>,
 
-    /// AST Lowering 後の Stelo
-    pub sir_stelo: RefCell<Option<&'tcx sir::Stelo<'tcx>>>,
-    // std, core 実装時など、複数のStelo解析の際に使われる
-    // /// インターンされた [StableSteloId] のマップ
-    // pub stable_stelo_ids: IndexMap<StableSteloId, SteloNum, BuildHasherDefault<Unhasher>>,
+    /// インクリメンタルコンパイルのキャッシュ。インクリメンタルコンパイルを行わない場合は `None`
+    pub incremental: RefCell<Option<IncrementalCache>>,
+
+    /// `--extern` で読み込んだステロのメタデータ
+    pub cstore: CStore,
 }
 
 impl<'tcx> Deref for TyCtxt<'tcx> {
@@ -73,30 +86,36 @@ impl<'tcx> TyCtxt<'tcx> {
     }
 
     pub fn create_local_stelo_def_id(self, stelo_span: Span) -> LocalDefId {
-        let key_span = self.source_span.borrow_mut().push(stelo_span);
-        let key_def_kind = self.def_kind_table.borrow_mut().push(DefKind::Mod);
-
-        assert_eq!(key_span, STELO_DEF_ID);
-        assert_eq!(key_def_kind, STELO_DEF_ID);
+        self.feed_local_def(STELO_DEF_ID, stelo_span, DefKind::Mod);
         STELO_DEF_ID
     }
 
-    pub fn local_def_kind(&self, local_def_id: LocalDefId) -> DefKind {
-        // LocalDefId が生成されるとき、同時に DefKind は必ず登録される
-        self.def_kind_table
-            .borrow()
-            .get(local_def_id)
-            .copied()
-            .expect("bug: LocalDefId とともに DefKind が登録されていない")
+    /// `TyKind` をインターンし、対応する `Ty` を返す。
+    #[inline]
+    pub fn mk_ty(self, kind: TyKind<'tcx>) -> Ty<'tcx> {
+        self.interners.intern_ty(kind)
+    }
+
+    /// シグネチャ `sig` をもつ関数ポインタ型。
+    pub fn mk_fn_ptr(self, sig: &FnSig<'tcx>) -> Ty<'tcx> {
+        let inputs_and_output = self
+            .arena
+            .alloc_from_iter(sig.inputs.iter().copied().chain([sig.output]));
+        self.mk_ty(TyKind::FnPtr(FnSigTys { inputs_and_output }))
+    }
+
+    pub fn mk_closure(self, def_id: DefId, sig: &FnSig<'tcx>) -> Ty<'tcx> {
+        let inputs_and_output = self
+            .arena
+            .alloc_from_iter(sig.inputs.iter().copied().chain([sig.output]));
+        self.mk_ty(TyKind::Closure(def_id, FnSigTys { inputs_and_output }))
     }
 
     pub fn def_kind(&self, def_id: DefId) -> DefKind {
         if let Some(local_def_id) = def_id.as_local() {
             self.local_def_kind(local_def_id)
         } else {
-            // 外部ステロに対する読み込みはまだ実装されていない
-            // FIXME: 外部ステロに対しても def_kind を返せるようにする
-            unimplemented!()
+            self.cstore.get(def_id.stelo).def_kind(def_id.index)
         }
     }
 
@@ -104,9 +123,40 @@ impl<'tcx> TyCtxt<'tcx> {
         if let Some(id) = id.as_local() {
             self.definitions.borrow().def_key(id)
         } else {
-            // 外部ステロに対する読み込みはまだ実装されていない
-            // FIXME: 外部ステロに対しても def_kind を返せるようにする
-            unimplemented!()
+            self.cstore.get(id.stelo).def_key(id.index)
+        }
+    }
+
+    /// ステロのルートから定義へのパス。ルート自身は含まない。
+    pub fn def_path(self, id: DefId) -> DefPath {
+        DefPath::make(id.stelo, id.index, |index| self.def_key(DefId { index, ..id }))
+    }
+
+    pub fn def_path_hash(self, id: DefId) -> DefPathHash {
+        if let Some(id) = id.as_local() {
+            self.definitions.borrow().def_path_hash(id)
+        } else {
+            self.cstore.get(id.stelo).def_path_hash(id.index)
+        }
+    }
+
+    /// `DefPathHash` に対応する、現在のステロまたは読み込んだステロの定義を返す。
+    pub fn def_path_hash_to_def_id(self, hash: DefPathHash) -> Option<DefId> {
+        if let Some(id) = self.definitions.borrow().local_def_path_hash_to_def_id(hash) {
+            return Some(id.to_def_id());
+        }
+
+        let stelo = self.cstore.stelo_by_stable_id(hash.stable_stelo_id())?;
+        let index = self.cstore.get(stelo).def_path_hash_to_index(hash)?;
+        Some(DefId::new(stelo, index))
+    }
+
+    /// ステロの名前。読み込んだステロの場合は、`--extern` の名前ではなくステロ自身の名前を返す
+    pub fn stelo_name(self, stelo: SteloNum) -> Symbol {
+        if stelo == LOCAL_STELO {
+            self.stelo_name
+        } else {
+            self.cstore.get(stelo).name
         }
     }
 
@@ -136,21 +186,29 @@ impl<'tcx> TyCtxt<'tcx> {
 
     pub fn create_global_ctxt(
         sess: &'tcx Session,
-        stable_stelo_id: StableSteloId,
+        stelo_name: Symbol,
+        cstore: CStore,
         arena: &'tcx Arena,
         sir_arena: &'tcx Arena,
-        types_arena: &'tcx TypedArena<'tcx, TyKind<'tcx>>
+        types_arena: &'tcx TypedArena<'tcx, WithCachedTypeInfo<TyKind<'tcx>>>,
+        providers: Providers,
     ) -> GlobalCtxt<'tcx> {
+        let interners = CtxtInterners::new(types_arena);
+        let types = CommonTypes::new(&interners);
+
         GlobalCtxt {
             arena,
             sir_arena,
             sess,
-            definitions: RefCell::new(Definitions::new(stable_stelo_id)),
-            types_interner: RefCell::new(HashMap::new()),
-            source_span: RefCell::new(IndexVec::new()),
-            def_kind_table: RefCell::new(IndexVec::new()),
-            sir_stelo: RefCell::new(None),
-            types: CommonTypes::new(types_arena),
+            stelo_name,
+            definitions: RefCell::new(Definitions::new(StableSteloId::new(stelo_name))),
+            resolver_for_lowering: RefCell::new(None),
+            lint_levels: RefCell::new(LintLevelMap::default()),
+            queries: QuerySystem::new(providers),
+            incremental: RefCell::new(None),
+            cstore,
+            interners,
+            types,
         }
     }
 }
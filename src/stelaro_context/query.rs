@@ -0,0 +1,515 @@
+//! 要求駆動のクエリシステム。
+//!
+//! クエリは `tcx.typeck(def_id)` のように呼び出され、初めて要求されたときに
+//! [`Providers`] に登録された関数で計算し、その結果を記録する。同じキーで再び要求された場合は、
+//! 記録した結果を返す。計算中のクエリを再び要求した場合は循環として報告し、代わりの値を返す。
+//!
+//! 新しいクエリを追加する場合は、`define_queries!` に宣言を加え、
+//! 計算する関数を定義したモジュールの `provide` で [`Providers`] に登録する。
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::mem;
+
+use crate::stelaro_common::{DUMMY_SPAN, DefId, IndexVec, LocalDefId, Span};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_context::diagnostics::DiagsQuery;
+use crate::stelaro_context::messages;
+use crate::stelaro_diagnostics::{DiagMessage, ErrorEmitted};
+use crate::stelaro_sir::def::DefKind;
+use crate::stelaro_sir::sir::{self, MaybeOwner, OwnerNodes};
+use crate::stelaro_sir_typecheck::result::TypeckResults;
+use crate::stelaro_ty::{FnSig, Ty, TyKind};
+
+
+/// クエリのキー
+pub trait QueryKey: Copy + Eq + Hash {
+    /// 診断で計算の対象を示すための定義。特定の定義に対するクエリでなければ `None`
+    fn def_id(self) -> Option<DefId>;
+}
+
+impl QueryKey for () {
+    fn def_id(self) -> Option<DefId> {
+        None
+    }
+}
+
+impl QueryKey for DefId {
+    fn def_id(self) -> Option<DefId> {
+        Some(self)
+    }
+}
+
+impl QueryKey for LocalDefId {
+    fn def_id(self) -> Option<DefId> {
+        Some(self.to_def_id())
+    }
+}
+
+/// クエリの結果
+pub trait QueryValue<'tcx, K>: Clone {
+    /// 循環が検出された場合に、クエリの結果の代わりに使う値を返す。
+    fn from_cycle_error(tcx: TyCtxt<'tcx>, key: K, guar: ErrorEmitted) -> Self;
+}
+
+impl<'tcx> QueryValue<'tcx, ()> for &'tcx sir::Stelo<'tcx> {
+    fn from_cycle_error(tcx: TyCtxt<'tcx>, _key: (), _guar: ErrorEmitted) -> Self {
+        // どの定義もオーナーとして持たない SIR。エラーが報告されているため、
+        // `lower_to_sir` の後でコンパイルは中断される
+        let owners = IndexVec::from_fn_n(
+            |_| MaybeOwner::Phantom,
+            tcx.definitions.borrow().def_index_count(),
+        );
+        tcx.sir_arena.alloc(sir::Stelo { owners })
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, LocalDefId> for Span {
+    fn from_cycle_error(_tcx: TyCtxt<'tcx>, _key: LocalDefId, _guar: ErrorEmitted) -> Self {
+        DUMMY_SPAN
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, LocalDefId> for DefKind {
+    fn from_cycle_error(_tcx: TyCtxt<'tcx>, key: LocalDefId, _guar: ErrorEmitted) -> Self {
+        // 結果は定義の作成時に与えられ、計算されることはないため、循環しない
+        unreachable!("bug: `local_def_kind({key:?})` が循環しました")
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, LocalDefId> for Option<&'tcx OwnerNodes<'tcx>> {
+    fn from_cycle_error(_tcx: TyCtxt<'tcx>, _key: LocalDefId, _guar: ErrorEmitted) -> Self {
+        None
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, DefId> for FnSig<'tcx> {
+    fn from_cycle_error(tcx: TyCtxt<'tcx>, _key: DefId, guar: ErrorEmitted) -> Self {
+        FnSig {
+            inputs: Vec::new(),
+            output: tcx.mk_ty(TyKind::Error(guar)),
+        }
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, DefId> for Ty<'tcx> {
+    fn from_cycle_error(tcx: TyCtxt<'tcx>, _key: DefId, guar: ErrorEmitted) -> Self {
+        tcx.mk_ty(TyKind::Error(guar))
+    }
+}
+
+impl<'tcx> QueryValue<'tcx, LocalDefId> for &'tcx TypeckResults<'tcx> {
+    fn from_cycle_error(tcx: TyCtxt<'tcx>, key: LocalDefId, _guar: ErrorEmitted) -> Self {
+        let mut results = TypeckResults::new(key);
+        results.tainted_by_errors = true;
+        tcx.arena.alloc(results)
+    }
+}
+
+enum QueryState<V> {
+    /// 計算中
+    Started,
+    Done(V),
+}
+
+/// 一つのクエリの計算済みの結果
+pub struct QueryCache<K, V> {
+    map: RefCell<HashMap<K, QueryState<V>>>,
+}
+
+impl<K, V> Default for QueryCache<K, V> {
+    fn default() -> Self {
+        QueryCache { map: RefCell::new(HashMap::new()) }
+    }
+}
+
+/// 計算中のクエリ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueryFrame {
+    name: &'static str,
+    def_id: Option<DefId>,
+}
+
+macro_rules! define_queries {
+    ($(
+        $(#[$attr:meta])*
+        fn $name:ident($K:ty) -> $V:ty, desc: $desc:path;
+    )*) => {
+        /// 各クエリを計算する関数
+        #[derive(Clone, Copy)]
+        pub struct Providers {
+            $(pub $name: for<'tcx> fn(TyCtxt<'tcx>, $K) -> $V,)*
+        }
+
+        impl Default for Providers {
+            /// どのクエリの関数も登録されていない状態
+            fn default() -> Self {
+                Providers {
+                    $($name: |_, _| panic!(concat!(
+                        "bug: クエリ `", stringify!($name), "` を計算する関数が登録されていません"
+                    )),)*
+                }
+            }
+        }
+
+        #[derive(Default)]
+        struct QueryCaches<'tcx> {
+            $($name: QueryCache<$K, $V>,)*
+        }
+
+        /// クエリの説明のメッセージを返す
+        fn query_description(name: &str) -> &'static DiagMessage {
+            match name {
+                $(stringify!($name) => $desc,)*
+                _ => unreachable!(),
+            }
+        }
+
+        impl<'tcx> TyCtxt<'tcx> {
+            $(
+                $(#[$attr])*
+                #[inline]
+                pub fn $name(self, key: $K) -> $V {
+                    let queries = &self.queries;
+                    execute_query(
+                        self,
+                        &queries.caches.$name,
+                        stringify!($name),
+                        key,
+                        queries.providers.$name,
+                    )
+                }
+            )*
+        }
+    };
+}
+
+define_queries! {
+    /// 定義の `Span`。定義の作成時に [`TyCtxt::feed_local_def`] で与えられる。
+    fn source_span(LocalDefId) -> Span, desc: messages::QUERY_SOURCE_SPAN;
+
+    /// 定義の種類。定義の作成時に [`TyCtxt::feed_local_def`] で与えられる。
+    fn local_def_kind(LocalDefId) -> DefKind, desc: messages::QUERY_LOCAL_DEF_KIND;
+
+    /// AST から変換された SIR。
+    fn sir_stelo(()) -> &'tcx sir::Stelo<'tcx>, desc: messages::QUERY_SIR_STELO;
+
+    /// 定義がオーナーであれば、そのオーナーが持つ SIR のノード。
+    fn opt_sir_owner_nodes(LocalDefId) -> Option<&'tcx OwnerNodes<'tcx>>,
+        desc: messages::QUERY_OPT_SIR_OWNER_NODES;
+
+    /// 関数のシグネチャ。
+    fn fn_sig(DefId) -> FnSig<'tcx>, desc: messages::QUERY_FN_SIG;
+
+    /// 型エイリアスを展開した型。循環する型エイリアスはクエリの循環として報告される。
+    fn type_of(DefId) -> Ty<'tcx>, desc: messages::QUERY_TYPE_OF;
+
+    /// 関数本体の型検査の結果。
+    fn typeck(LocalDefId) -> &'tcx TypeckResults<'tcx>, desc: messages::QUERY_TYPECK;
+}
+
+/// クエリの関数と、計算済みの結果
+pub struct QuerySystem<'tcx> {
+    pub providers: Providers,
+    caches: QueryCaches<'tcx>,
+
+    /// 計算中のクエリのスタック。循環を報告するために使う
+    stack: RefCell<Vec<QueryFrame>>,
+}
+
+impl<'tcx> QuerySystem<'tcx> {
+    pub fn new(providers: Providers) -> Self {
+        QuerySystem {
+            providers,
+            caches: QueryCaches::default(),
+            stack: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// 計算せずに定まる結果を与えるクエリ (`source_span`, `local_def_kind`) の関数を登録する。
+/// 結果が与えられていないキーで要求された場合はパニックする。
+pub fn provide(providers: &mut Providers) {
+    providers.source_span = |_, def_id| {
+        panic!("bug: {def_id:?} の `Span` が定義の作成時に与えられていない")
+    };
+    providers.local_def_kind = |_, def_id| {
+        panic!("bug: {def_id:?} の `DefKind` が定義の作成時に与えられていない")
+    };
+}
+
+impl<'tcx> TyCtxt<'tcx> {
+    /// 作成した定義の `Span` と `DefKind` を、`source_span` と `local_def_kind` の結果として与える。
+    pub fn feed_local_def(self, def_id: LocalDefId, span: Span, def_kind: DefKind) {
+        let caches = &self.queries.caches;
+        feed_query(&caches.source_span, def_id, span);
+        feed_query(&caches.local_def_kind, def_id, def_kind);
+    }
+}
+
+fn feed_query<K: QueryKey, V>(cache: &QueryCache<K, V>, key: K, value: V) {
+    let previous = cache.map.borrow_mut().insert(key, QueryState::Done(value));
+    assert!(previous.is_none(), "bug: クエリの結果が既に与えられている");
+}
+
+/// 計算中のクエリの記録。
+///
+/// クエリの関数がパニックによって巻き戻された場合にも、計算中の状態とスタックのフレームを取り除く。
+/// 巻き戻しは `FatalErrorMarker` として捕捉され、その後も同じ `TyCtxt` が使われることがあるため、
+/// 残しておくと同じクエリの次の要求が循環として誤って報告される。
+struct QueryJobGuard<'a, 'tcx, K: QueryKey, V> {
+    tcx: TyCtxt<'tcx>,
+    cache: &'a QueryCache<K, V>,
+    key: K,
+}
+
+impl<'a, 'tcx, K: QueryKey, V> QueryJobGuard<'a, 'tcx, K, V> {
+    fn start(tcx: TyCtxt<'tcx>, cache: &'a QueryCache<K, V>, key: K, frame: QueryFrame) -> Self {
+        tcx.queries.stack.borrow_mut().push(frame);
+        QueryJobGuard { tcx, cache, key }
+    }
+
+    /// 計算の結果を記録する。
+    fn complete(self, value: V) {
+        let QueryJobGuard { tcx, cache, key } = self;
+        mem::forget(self);
+
+        tcx.queries.stack.borrow_mut().pop();
+        cache.map.borrow_mut().insert(key, QueryState::Done(value));
+    }
+}
+
+impl<K: QueryKey, V> Drop for QueryJobGuard<'_, '_, K, V> {
+    fn drop(&mut self) {
+        self.tcx.queries.stack.borrow_mut().pop();
+        self.cache.map.borrow_mut().remove(&self.key);
+    }
+}
+
+fn execute_query<'tcx, K: QueryKey, V: QueryValue<'tcx, K>>(
+    tcx: TyCtxt<'tcx>,
+    cache: &QueryCache<K, V>,
+    name: &'static str,
+    key: K,
+    provider: fn(TyCtxt<'tcx>, K) -> V,
+) -> V {
+    let frame = QueryFrame { name, def_id: key.def_id() };
+
+    let started = match cache.map.borrow_mut().entry(key) {
+        Entry::Occupied(entry) => match entry.get() {
+            QueryState::Done(value) => return value.clone(),
+            QueryState::Started => true,
+        },
+        Entry::Vacant(entry) => {
+            entry.insert(QueryState::Started);
+            false
+        }
+    };
+
+    // 計算中のクエリが、その計算の途中で再び要求された
+    if started {
+        let guar = report_cycle(tcx, frame);
+        return V::from_cycle_error(tcx, key, guar);
+    }
+
+    let guard = QueryJobGuard::start(tcx, cache, key, frame);
+    let value = provider(tcx, key);
+    guard.complete(value.clone());
+    value
+}
+
+/// `frame` から始まる循環を報告する。
+fn report_cycle(tcx: TyCtxt<'_>, frame: QueryFrame) -> ErrorEmitted {
+    // 診断を構築する間に他のクエリが要求されることがあるため、スタックは複製しておく
+    let stack = tcx.queries.stack.borrow().clone();
+    let start = stack.iter().position(|f| *f == frame).unwrap();
+
+    let describe = |frame: &QueryFrame| {
+        let name = frame.def_id.map(|def_id| tcx.def_path_str(def_id)).unwrap_or_default();
+        tcx.dcx().translate(query_description(frame.name), &[("name", &name)])
+    };
+    let span = frame
+        .def_id
+        .and_then(DefId::as_local)
+        .map_or(DUMMY_SPAN, |def_id| tcx.source_span(def_id));
+
+    // 型エイリアスだけからなる循環は、展開の過程ではなく型エイリアスの名前で示す
+    let aliases = stack[start..]
+        .iter()
+        .chain([&frame])
+        .map(|f| f.def_id.filter(|_| f.name == "type_of"))
+        .collect::<Option<Vec<_>>>();
+    if let Some(aliases) = aliases {
+        let cycle = aliases
+            .iter()
+            .map(|&def_id| format!("`{}`", tcx.def_path_str(def_id)))
+            .collect::<Vec<_>>()
+            .join(" → ");
+        let alias = tcx.def_path_str(frame.def_id.unwrap());
+        return DiagsQuery::ty_alias_cycle(tcx.dcx(), &alias, &cycle, span).emit();
+    }
+
+    let cycle = stack[start..]
+        .iter()
+        .chain([&frame])
+        .map(describe)
+        .collect::<Vec<_>>()
+        .join(" → ");
+
+    DiagsQuery::cycle(tcx.dcx(), &describe(&frame), &cycle, span).emit()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    use crate::stelaro_common::source_map::SourceMap;
+    use crate::stelaro_common::{
+        Arena, FatalError, LocalDefId, Symbol, TypedArena, create_default_session_globals_then,
+    };
+    use crate::stelaro_context::TyCtxt;
+    use crate::stelaro_context::query::Providers;
+    use crate::stelaro_diagnostics::DiagCtxt;
+    use crate::stelaro_diagnostics::emitter::CollectingEmitter;
+    use crate::stelaro_interface::passes;
+    use crate::stelaro_metadata::CStore;
+    use crate::stelaro_session::session::CompilerPaths;
+    use crate::stelaro_session::{Input, Options, ParseSess, Session};
+    use crate::stelaro_sir::sir::{self, ItemKind, MaybeOwner, OwnerNode};
+    use crate::stelaro_ty::FnSig;
+
+    thread_local! {
+        static FN_SIG_CALLS: Cell<usize> = const { Cell::new(0) };
+        static FN_SIG_FAILS: Cell<bool> = const { Cell::new(true) };
+    }
+
+    /// `providers` を使ってソースコードを SIR に変換し、最初の関数を `f` に渡す
+    fn with_first_fn(
+        src: &str,
+        providers: Providers,
+        emitter: &CollectingEmitter,
+        f: impl for<'tcx> FnOnce(TyCtxt<'tcx>, LocalDefId),
+    ) {
+        create_default_session_globals_then(|| {
+            let dcx = DiagCtxt::new(Box::new(emitter.clone()));
+            let sess = Session {
+                psess: ParseSess::with_dcx(dcx, Rc::new(SourceMap::new())),
+                opts: Options::default(),
+                paths: CompilerPaths {
+                    input: Input::Str {
+                        name: "query_test".to_string(),
+                        input: src.to_string(),
+                    },
+                    output_dir: None,
+                    output_file: None,
+                    temps_dir: None,
+                },
+            };
+            let stelo = passes::parse(&sess);
+
+            let arena = Arena::new();
+            let sir_arena = Arena::new();
+            let types_arena = TypedArena::new();
+            let gcx = TyCtxt::create_global_ctxt(
+                &sess,
+                Symbol::intern("query_test"),
+                CStore::default(),
+                &arena,
+                &sir_arena,
+                &types_arena,
+                providers,
+            );
+            let tcx = TyCtxt::new(&gcx);
+
+            passes::lower_to_sir(tcx, stelo);
+            let def_id = tcx
+                .sir_stelo(())
+                .owners
+                .iter_enumerated()
+                .find_map(|(def_id, owner)| match owner {
+                    MaybeOwner::Owner(info) => match info.nodes.node() {
+                        OwnerNode::Item(sir::Item { kind: ItemKind::Fn { .. }, .. }) => Some(def_id),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .unwrap();
+
+            f(tcx, def_id);
+        });
+    }
+
+    #[test]
+    fn test_query_is_memoized() {
+        let mut providers = passes::default_providers();
+        providers.fn_sig = |tcx, _| {
+            FN_SIG_CALLS.with(|calls| calls.set(calls.get() + 1));
+            FnSig { inputs: Vec::new(), output: tcx.types.unit }
+        };
+
+        let emitter = CollectingEmitter::new();
+        with_first_fn("fn f() {}", providers, &emitter, |tcx, def_id| {
+            let results = tcx.typeck(def_id);
+            assert!(std::ptr::eq(results, tcx.typeck(def_id)));
+
+            assert_eq!(tcx.fn_sig(def_id.to_def_id()).output, tcx.types.unit);
+            assert_eq!(FN_SIG_CALLS.with(Cell::get), 1);
+        });
+        assert!(emitter.take_diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let mut providers = passes::default_providers();
+        // 型検査はシグネチャを必要とするため、シグネチャが型検査を必要とすると循環する
+        providers.fn_sig = |tcx, def_id| {
+            tcx.typeck(def_id.expect_local());
+            FnSig { inputs: Vec::new(), output: tcx.types.unit }
+        };
+
+        let emitter = CollectingEmitter::new();
+        with_first_fn("fn f() {}", providers, &emitter, |tcx, def_id| {
+            // 循環の内側の要求には代わりの値が返され、外側の計算は完了する
+            let results = tcx.typeck(def_id);
+            assert!(std::ptr::eq(results, tcx.typeck(def_id)));
+            assert!(tcx.dcx().has_errors().is_some());
+        });
+
+        let diags = emitter.take_diagnostics();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].code, Some(901));
+        assert_eq!(diags[0].msg, ["`f` の型検査が循環しています"]);
+        assert_eq!(
+            diags[0].help,
+            ["循環: `f` の型検査 → `f` のシグネチャの計算 → `f` の型検査"]
+        );
+    }
+
+    #[test]
+    fn test_unwound_query_is_not_reported_as_cycle() {
+        let mut providers = passes::default_providers();
+        // 最初の要求だけ、致命的なエラーによって巻き戻される
+        providers.fn_sig = |tcx, _| {
+            if FN_SIG_FAILS.with(|fails| fails.replace(false)) {
+                FatalError.raise();
+            }
+            FnSig { inputs: Vec::new(), output: tcx.types.unit }
+        };
+
+        let emitter = CollectingEmitter::new();
+        with_first_fn("fn f() {}", providers, &emitter, |tcx, def_id| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| tcx.typeck(def_id)));
+            assert!(result.is_err());
+            assert!(tcx.queries.stack.borrow().is_empty());
+
+            // 巻き戻された計算は記録されず、再び要求すると計算し直される
+            let results = tcx.typeck(def_id);
+            assert!(!results.tainted_by_errors);
+        });
+        assert!(emitter.take_diagnostics().is_empty());
+    }
+}
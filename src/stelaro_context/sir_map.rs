@@ -2,7 +2,7 @@ use crate::stelaro_common::{DefId, DefPathHash, LocalDefId};
 use crate::stelaro_context::TyCtxt;
 use crate::stelaro_sir::{
     DefKey, DefPath,
-    sir::{Body, BodyId, Item, ItemId, Mod, Node, OwnerNode, OwnerNodes},
+    sir::{Body, BodyId, Closure, Expr, ExprKind, Item, ItemId, Mod, Node, OwnerNode, OwnerNodes},
     sir_id::{ItemLocalId, OwnerId, STELO_OWNER_ID, SirId},
     visit::SirTyCtxt,
 };
@@ -40,6 +40,17 @@ impl<'tcx> TyCtxt<'tcx> {
         self.sir_node(self.local_def_id_to_sir_id(id))
     }
 
+    /// クロージャの定義に対応する、クロージャ式の `sir::Closure` を取得します。
+    pub fn expect_sir_closure(self, def_id: LocalDefId) -> &'tcx Closure<'tcx> {
+        match self.sir_node_by_def_id(def_id) {
+            Node::Expr(Expr {
+                kind: ExprKind::Closure(closure),
+                ..
+            }) => closure,
+            node => panic!("bug: {def_id:?} はクロージャではない: {node:?}"),
+        }
+    }
+
     /// この `sir_id` を持つノードの親HIRノードの `SirId` を返します。
     /// `sir_id == CRATE_HIR_ID` の場合に限り、同じ `sir_id` を返します。
     ///
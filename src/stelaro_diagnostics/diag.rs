@@ -1,11 +1,11 @@
-use super::{DiagCtxt, emitter::DynEmitter};
-use crate::stelaro_common::{FatalError, Hash128, Span, StableHasher};
+use super::{DiagCtxt, emitter::DynEmitter, messages, translation::{DiagArgs, DiagMessage, Lang}};
+use crate::stelaro_common::{DUMMY_SPAN, FatalError, Hash128, Span, StableHasher};
+use crate::stelaro_error_codes::{ErrorCodeDisplay, find_error_code};
 
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::process;
 
 /// 診断メッセージの出力保証を表すトレイト
 pub trait EmissionGuarantee: Sized {
@@ -50,7 +50,31 @@ impl ErrorEmitted {
     }
 }
 
+/// 診断の出力方法を調整するフラグ
+#[derive(Debug, Clone, Copy)]
+pub struct DiagCtxtFlags {
+    /// `false` のとき、警告は出力されない (`-A warnings`)
+    pub can_emit_warnings: bool,
+
+    /// `true` のとき、警告はエラーとして出力される (`-D warnings`)
+    pub warnings_as_errors: bool,
+}
+
+impl Default for DiagCtxtFlags {
+    fn default() -> Self {
+        DiagCtxtFlags {
+            can_emit_warnings: true,
+            warnings_as_errors: false,
+        }
+    }
+}
+
 pub struct DiagCtxtInner {
+    pub flags: DiagCtxtFlags,
+
+    /// 診断メッセージを出力する言語
+    pub lang: Lang,
+
     /// 発行されたエラーを保持する
     pub errors: Vec<ErrorEmitted>,
 
@@ -66,22 +90,39 @@ pub struct DiagCtxtInner {
     /// 重複が排除され、実際に表示された警告の個数を表す。
     pub emitted_warn_count: usize,
 
+    /// 出力された診断がもつ、自動的に適用できる修正案 (`--fix` で使用する)
+    pub machine_applicable_suggestions: Vec<Suggestion>,
+
     pub emitter: Box<DynEmitter>,
 }
 
 impl DiagCtxtInner {
     pub fn new(emitter: Box<DynEmitter>) -> Self {
         Self {
+            flags: DiagCtxtFlags::default(),
+            lang: Lang::default(),
             errors: Vec::new(),
             emitted_diagnostics: HashSet::new(),
             emitted_diagnostic_codes: HashSet::new(),
             emitted_err_count: 0,
             emitted_warn_count: 0,
+            machine_applicable_suggestions: Vec::new(),
             emitter,
         }
     }
 
-    pub fn emit_diagnostic(&mut self, diag: DiagInner) -> Option<ErrorEmitted> {
+    pub fn emit_diagnostic(&mut self, mut diag: DiagInner) -> Option<ErrorEmitted> {
+        if diag.level == Level::Warning {
+            if !self.flags.can_emit_warnings {
+                return None;
+            }
+
+            if self.flags.warnings_as_errors {
+                diag.level = Level::Error;
+                diag.help.push(messages::WARNINGS_AS_ERRORS_HELP.format(self.lang, &[]));
+            }
+        }
+
         if let Some(code) = diag.code {
             self.emitted_diagnostic_codes.insert(code);
         }
@@ -102,6 +143,13 @@ impl DiagCtxtInner {
                 self.emitted_warn_count += 1;
             }
 
+            self.machine_applicable_suggestions.extend(
+                diag.suggestions
+                    .iter()
+                    .filter(|s| s.applicability == Applicability::MachineApplicable)
+                    .cloned(),
+            );
+
             self.emitter.emit_diagnostic(diag);
         }
 
@@ -157,9 +205,12 @@ impl<'a> DiagCtxtHandle<'a> {
         Diag::new(self, span, Level::FatalError)
     }
 
+    /// 位置をもたない致命的なエラー (e.g. 入力ファイルを読み込めない) を報告し、コンパイルを中断する
     pub fn emit_fatal(self, msg: String) -> ! {
-        eprintln!("\x1b[31mError:\x1b[0m {msg}");
-        process::exit(1)
+        let mut diag = DiagInner::new(Level::FatalError, DUMMY_SPAN);
+        diag.msg.push(msg);
+        self.emit_diagnostic(diag);
+        FatalError.raise()
     }
 
     fn emit_diagnostic(&self, diag: DiagInner) -> Option<ErrorEmitted> {
@@ -170,6 +221,20 @@ impl<'a> DiagCtxtHandle<'a> {
         self.inner.borrow().has_errors()
     }
 
+    pub fn lang(&self) -> Lang {
+        self.inner.borrow().lang
+    }
+
+    /// メッセージを現在の言語で文字列にする
+    pub fn translate(&self, msg: &DiagMessage, args: DiagArgs<'_>) -> String {
+        msg.format(self.lang(), args)
+    }
+
+    /// これまでに出力された、自動的に適用できる修正案を取り出す
+    pub fn take_machine_applicable_suggestions(&self) -> Vec<Suggestion> {
+        std::mem::take(&mut self.inner.borrow_mut().machine_applicable_suggestions)
+    }
+
     pub fn has_err_code(self, code: i32) -> bool {
         self.inner.borrow().emitted_diagnostic_codes.contains(&code)
     }
@@ -214,7 +279,13 @@ impl<'a, G: EmissionGuarantee> Diag<'a, G> {
         guar.unwrap()
     }
 
+    /// `code` は `stelaro_error_codes` に登録されたものでなければならない
     pub fn set_code(&mut self, code: i32) {
+        debug_assert!(
+            find_error_code(code).is_some(),
+            "{} is not a registered error code",
+            ErrorCodeDisplay(code),
+        );
         self.diag.as_deref_mut().unwrap().code = Some(code);
     }
 
@@ -230,11 +301,66 @@ impl<'a, G: EmissionGuarantee> Diag<'a, G> {
         self.diag.as_deref_mut().unwrap().help.push(msg);
     }
 
+    /// `span` を `replacement` で置き換える修正案を追加する。
+    /// `replacement` が空の場合は削除、`span` が空の場合は挿入を表す。
+    pub fn set_suggestion(
+        &mut self,
+        span: Span,
+        msg: String,
+        replacement: String,
+        applicability: Applicability,
+    ) {
+        self.diag.as_deref_mut().unwrap().suggestions.push(Suggestion {
+            span,
+            msg,
+            replacement,
+            applicability,
+        });
+    }
+
     pub fn emit(self) -> G::EmitResult {
         G::emit_producing_guarantee(self)
     }
 }
 
+/// 修正案をどの程度信頼して、自動的に適用できるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// 修正案は意図通りであり、`--fix` によって自動的に適用できる
+    MachineApplicable,
+
+    /// 修正案は意図と異なる可能性があるため、利用者が確認する必要がある
+    MaybeIncorrect,
+
+    /// 修正案は利用者が書き換えるべきプレースホルダを含む (e.g. `name`)
+    HasPlaceholders,
+
+    /// 修正案の信頼性が不明
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+            Applicability::HasPlaceholders => "has-placeholders",
+            Applicability::Unspecified => "unspecified",
+        }
+    }
+}
+
+/// ソースコードに対する構造化された修正案
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    /// 置き換えられる範囲
+    pub span: Span,
+    /// 修正案の説明
+    pub msg: String,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
 #[derive(Debug)]
 pub struct DiagInner {
     pub code: Option<i32>,
@@ -242,6 +368,7 @@ pub struct DiagInner {
     pub msg: Vec<String>,
     pub label: Vec<(Span, String)>,
     pub help: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
     pub span: Span,
 }
 
@@ -252,6 +379,7 @@ impl DiagInner {
             msg: Vec::new(),
             label: Vec::new(),
             help: Vec::with_capacity(0),
+            suggestions: Vec::new(),
             span,
             code: None,
         }
@@ -275,6 +403,7 @@ impl DiagInner {
         &Span,
         &Vec<(Span, String)>,
         &Vec<String>,
+        &Vec<Suggestion>,
     ) {
         (
             &self.level,
@@ -283,6 +412,7 @@ impl DiagInner {
             &self.span,
             &self.label,
             &self.help,
+            &self.suggestions,
         )
     }
 }
@@ -1,7 +1,9 @@
 use super::diag::{DiagInner, Level};
 use crate::stelaro_common::SourceMap;
+use crate::stelaro_error_codes::ErrorCodeDisplay;
 
 use ariadne::{Label, Report, Source};
+use std::cell::RefCell;
 use std::rc::Rc;
 
 pub type DynEmitter = dyn Emitter;
@@ -28,6 +30,17 @@ impl AriadneEmitter {
 
 impl Emitter for AriadneEmitter {
     fn emit_diagnostic(&mut self, diag: DiagInner) {
+        // 位置をもたない診断 (`DiagCtxtHandle::emit_fatal`) は、ソースコードを示さずに出力する
+        if diag.span.is_dummy() && diag.label.is_empty() {
+            let prefix = match diag.level {
+                Level::FatalError | Level::Error => "\x1b[31mError:\x1b[0m",
+                Level::Warning => "\x1b[33mWarning:\x1b[0m",
+                Level::Help => "\x1b[36mHelp:\x1b[0m",
+            };
+            eprintln!("{prefix} {}", diag.msg.join("\n"));
+            return;
+        }
+
         // TODO: 複数ファイル対応時には、Spanに対して入力ソース、ファイル名を得られるように変更する
         let file = &self.source_map
             .as_ref()
@@ -50,7 +63,7 @@ impl Emitter for AriadneEmitter {
         }
 
         if let Some(code) = diag.code {
-            report = report.with_code(code)
+            report = report.with_code(ErrorCodeDisplay(code))
         }
 
         if !diag.label.is_empty() {
@@ -67,6 +80,16 @@ impl Emitter for AriadneEmitter {
             }
         }
 
+        for suggestion in diag.suggestions {
+            if suggestion.replacement.is_empty() {
+                report = report.with_help(suggestion.msg);
+            } else {
+                report = report.with_help(
+                    format!("{}: `{}`", suggestion.msg, suggestion.replacement),
+                );
+            }
+        }
+
 
         report.finish()
             .print((
@@ -92,6 +115,35 @@ fn level_to_ariadne_kind(level: Level) -> ariadne::ReportKind<'static> {
     }
 }
 
+/// 出力された診断を表示せずに蓄積する。コンパイラを組み込んで、診断を値として受け取る場合に用いる
+///
+/// 複製は同じ蓄積先を共有するため、複製の一方を `DiagCtxt` に渡し、他方から診断を取り出す。
+#[derive(Default, Clone)]
+pub struct CollectingEmitter {
+    diags: Rc<RefCell<Vec<DiagInner>>>,
+}
+
+impl CollectingEmitter {
+    pub fn new() -> Self {
+        CollectingEmitter::default()
+    }
+
+    /// これまでに蓄積された診断を、出力された順に取り出す
+    pub fn take_diagnostics(&self) -> Vec<DiagInner> {
+        std::mem::take(&mut self.diags.borrow_mut())
+    }
+}
+
+impl Emitter for CollectingEmitter {
+    fn source_map(&self) -> Option<&SourceMap> {
+        None
+    }
+
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        self.diags.borrow_mut().push(diag);
+    }
+}
+
 /// テストなどに用いられ、診断が表示されるべきでない場合に用いる
 #[derive(Default)]
 pub struct SilentEmitter;
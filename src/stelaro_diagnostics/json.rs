@@ -0,0 +1,281 @@
+use super::diag::{DiagInner, Level};
+use super::emitter::Emitter;
+use crate::stelaro_common::{SourceMap, Span, source_map::SourceFile};
+use crate::stelaro_error_codes::ErrorCodeDisplay;
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// 診断を一行につき一つの JSON オブジェクトとして出力する。
+/// エディタや CI など、機械的に診断を読み取る用途に用いる。
+///
+/// ```json
+/// {"level":"error","code":"E0200","messages":["..."],"span":{...},"labels":[{...,"message":"..."}],"help":["..."],
+///  "suggestions":[{...,"message":"...","replacement":"...","applicability":"machine-applicable"}]}
+/// ```
+pub struct JsonEmitter {
+    dst: Box<dyn Write>,
+    source_map: Option<Rc<SourceMap>>,
+}
+
+impl JsonEmitter {
+    pub fn stderr(source_map: Rc<SourceMap>) -> Self {
+        JsonEmitter::new(Box::new(io::stderr()), source_map)
+    }
+
+    pub fn new(dst: Box<dyn Write>, source_map: Rc<SourceMap>) -> Self {
+        JsonEmitter {
+            dst,
+            source_map: Some(source_map),
+        }
+    }
+
+    fn diag_to_json(&self, diag: &DiagInner) -> String {
+        // TODO: 複数ファイル対応時には、Spanに対して入力ソース、ファイル名を得られるように変更する
+        let file = self.source_map
+            .as_ref()
+            .unwrap()
+            .file
+            .borrow();
+
+        let mut json = String::new();
+        json.push('{');
+
+        let _ = write!(json, "\"level\":{}", json_str(level_to_str(diag.level)));
+
+        json.push_str(",\"code\":");
+        match diag.code {
+            Some(code) => json.push_str(&json_str(&ErrorCodeDisplay(code).to_string())),
+            None => json.push_str("null"),
+        }
+
+        json.push_str(",\"messages\":");
+        json_str_array(&mut json, &diag.msg);
+
+        // 位置をもたない診断 (`DiagCtxtHandle::emit_fatal`) の `span` は `null` とする
+        if diag.span.is_dummy() && diag.label.is_empty() {
+            json.push_str(",\"span\":null");
+        } else {
+            json.push_str(",\"span\":{");
+            span_to_json(&mut json, &file, diag.span);
+            json.push('}');
+        }
+
+        json.push_str(",\"labels\":[");
+        for (i, (span, msg)) in diag.label.iter().enumerate() {
+            if i != 0 {
+                json.push(',');
+            }
+            json.push('{');
+            span_to_json(&mut json, &file, *span);
+            let _ = write!(json, ",\"message\":{}", json_str(msg));
+            json.push('}');
+        }
+        json.push(']');
+
+        json.push_str(",\"help\":");
+        json_str_array(&mut json, &diag.help);
+
+        json.push_str(",\"suggestions\":[");
+        for (i, suggestion) in diag.suggestions.iter().enumerate() {
+            if i != 0 {
+                json.push(',');
+            }
+            json.push('{');
+            span_to_json(&mut json, &file, suggestion.span);
+            let _ = write!(
+                json,
+                ",\"message\":{},\"replacement\":{},\"applicability\":{}",
+                json_str(&suggestion.msg),
+                json_str(&suggestion.replacement),
+                json_str(suggestion.applicability.as_str()),
+            );
+            json.push('}');
+        }
+        json.push(']');
+
+        json.push('}');
+        json
+    }
+}
+
+impl Emitter for JsonEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        let json = self.diag_to_json(&diag);
+
+        if let Err(e) = writeln!(self.dst, "{json}") {
+            panic!("診断を JSON として出力できませんでした: {e}");
+        }
+    }
+
+    fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
+}
+
+fn level_to_str(level: Level) -> &'static str {
+    match level {
+        Level::FatalError => "fatal",
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Help => "help",
+    }
+}
+
+/// `Span` を、ファイル名、行番号、列番号、バイト単位の範囲を表すフィールドとして書き込む
+fn span_to_json(json: &mut String, file: &SourceFile, span: Span) {
+    let start = file.lookup_loc(span.start);
+    let end = file.lookup_loc(span.end);
+
+    let _ = write!(
+        json,
+        "\"file\":{},\"line_start\":{},\"column_start\":{},\"line_end\":{},\"column_end\":{},\"byte_start\":{},\"byte_end\":{}",
+        json_str(&file.name.to_string_lossy()),
+        start.line,
+        start.col,
+        end.line,
+        end.col,
+        start.byte,
+        end.byte,
+    );
+}
+
+fn json_str_array(json: &mut String, strs: &[String]) {
+    json.push('[');
+    for (i, s) in strs.iter().enumerate() {
+        if i != 0 {
+            json.push(',');
+        }
+        json.push_str(&json_str(s));
+    }
+    json.push(']');
+}
+
+/// 文字列をエスケープし、JSON の文字列リテラルにする
+fn json_str(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    use crate::stelaro_common::{SourceMap, Span, create_default_session_globals_then};
+    use crate::stelaro_diagnostics::{Applicability, DiagCtxt, json::JsonEmitter};
+
+    /// 出力先をテストから読み取れるようにするためのバッファ
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn emit_to_json(src: &str, f: impl FnOnce(&DiagCtxt)) -> String {
+        create_default_session_globals_then(|| {
+            let source_map = Rc::new(SourceMap::new());
+            source_map.new_source_file("main.stelo".into(), src.to_string());
+
+            let buf = SharedBuffer::default();
+            let emitter = JsonEmitter::new(Box::new(buf.clone()), source_map);
+            let dcx = DiagCtxt::new(Box::new(emitter));
+            f(&dcx);
+
+            String::from_utf8(buf.0.borrow().clone()).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_json_emitter() {
+        let src = "fn main() {\n    let あ = \"x\";\n}";
+        let output = emit_to_json(src, |dcx| {
+            // `あ` の位置 (文字単位)
+            let span: Span = (20..21).into();
+            let mut diag = dcx.handle().struct_err(span);
+            diag.set_code(300);
+            diag.set_message("\"あ\" は\n未使用".to_string());
+            diag.set_label(span, "ここ".to_string());
+            diag.set_help("help".to_string());
+            diag.set_suggestion(
+                span,
+                "変更".to_string(),
+                "a".to_string(),
+                Applicability::MachineApplicable,
+            );
+            diag.emit();
+        });
+
+        let span = r#"{"file":"main.stelo","line_start":2,"column_start":9,"line_end":2,"column_end":10,"byte_start":20,"byte_end":23"#;
+        assert_eq!(
+            output,
+            format!(
+                concat!(
+                    r#"{{"level":"error","code":"E0300","messages":["\"あ\" は\n未使用"],"#,
+                    r#""span":{span}}},"labels":[{span},"message":"ここ"}}],"help":["help"],"#,
+                    r#""suggestions":[{span},"message":"変更","replacement":"a","applicability":"machine-applicable"}}]}}"#,
+                    "\n",
+                ),
+                span = span,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_json_emitter_one_line_per_diagnostic() {
+        let output = emit_to_json("fn main() {}", |dcx| {
+            dcx.handle().struct_warn((3..7).into()).emit();
+            dcx.handle().struct_err((0..2).into()).emit();
+        });
+
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"level":"warning","code":null,"#));
+        assert!(lines[1].starts_with(r#"{"level":"error","code":null,"#));
+    }
+
+    #[test]
+    fn test_json_emitter_fatal_without_span() {
+        let output = emit_to_json("fn main() {}", |dcx| {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                dcx.handle().emit_fatal("ファイルが見つかりません".to_string())
+            }));
+            assert!(result.is_err());
+        });
+
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"level":"fatal","code":null,"messages":["ファイルが見つかりません"],"#,
+                r#""span":null,"labels":[],"help":[],"suggestions":[]}"#,
+                "\n",
+            ),
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! 診断の出力時に付け加えられるメッセージのカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    WARNINGS_AS_ERRORS_HELP {
+        ja: "`-D warnings` によって警告はエラーとして扱われます",
+        en: "`-D warnings` turns warnings into errors",
+    }
+}
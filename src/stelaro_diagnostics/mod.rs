@@ -1,8 +1,15 @@
 pub mod diag;
 pub mod emitter;
+pub mod json;
+pub mod messages;
+pub mod translation;
 
-pub use diag::{Diag, DiagCtxtHandle, ErrorEmitted};
-pub use emitter::{AriadneEmitter, SilentEmitter};
+pub use diag::{
+    Applicability, Diag, DiagCtxtFlags, DiagCtxtHandle, DiagInner, ErrorEmitted, Level, Suggestion,
+};
+pub use emitter::{AriadneEmitter, CollectingEmitter, SilentEmitter};
+pub use json::JsonEmitter;
+pub use translation::{DiagArgs, DiagMessage, Lang};
 
 use diag::DiagCtxtInner;
 use emitter::DynEmitter;
@@ -19,6 +26,16 @@ impl DiagCtxt {
         }
     }
 
+    pub fn with_flags(self, flags: DiagCtxtFlags) -> DiagCtxt {
+        self.inner.borrow_mut().flags = flags;
+        self
+    }
+
+    pub fn with_lang(self, lang: Lang) -> DiagCtxt {
+        self.inner.borrow_mut().lang = lang;
+        self
+    }
+
     pub fn handle(&self) -> DiagCtxtHandle<'_> {
         DiagCtxtHandle { dcx: self }
     }
@@ -0,0 +1,206 @@
+use std::fmt::Display;
+
+
+/// 診断メッセージを出力する言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum Lang {
+    /// 日本語
+    #[default]
+    Ja,
+
+    /// 英語
+    En,
+}
+
+impl Lang {
+    /// ロケール (e.g. `ja_JP.UTF-8`, `en_US`) から言語を決定する
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let lang = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default();
+
+        match lang {
+            "ja" => Some(Lang::Ja),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// 環境変数 `LANG` から言語を決定する
+    pub fn from_env() -> Option<Lang> {
+        std::env::var("LANG")
+            .ok()
+            .and_then(|locale| Lang::from_locale(&locale))
+    }
+}
+
+/// メッセージカタログの一項目。言語ごとの文面をもつ。
+///
+/// 文面には `{name}` の形式でプレースホルダを書くことができ、
+/// [`DiagMessage::format`] に渡された引数で置き換えられる。
+#[derive(Debug)]
+pub struct DiagMessage {
+    pub id: &'static str,
+    pub ja: &'static str,
+    pub en: &'static str,
+}
+
+pub type DiagArgs<'a> = &'a [(&'a str, &'a dyn Display)];
+
+impl DiagMessage {
+    pub fn get(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.ja,
+            Lang::En => self.en,
+        }
+    }
+
+    /// 指定された言語の文面を選び、プレースホルダを `args` で置き換える。
+    /// 対応する引数がないプレースホルダはそのまま残される。
+    pub fn format(&self, lang: Lang, args: DiagArgs<'_>) -> String {
+        let template = self.get(lang);
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(open) = rest.find('{') {
+            output.push_str(&rest[..open]);
+            rest = &rest[open..];
+
+            let replaced = placeholder(rest).and_then(|name| {
+                args.iter()
+                    .find(|(arg, _)| *arg == name)
+                    .map(|(_, value)| (name, value))
+            });
+
+            match replaced {
+                Some((name, value)) => {
+                    output.push_str(&value.to_string());
+                    rest = &rest[name.len() + 2..];
+                }
+                None => {
+                    output.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    /// 文面に含まれるプレースホルダの名前を列挙する
+    pub fn placeholders(&self, lang: Lang) -> Vec<&'static str> {
+        let template = self.get(lang);
+
+        template
+            .match_indices('{')
+            .filter_map(|(i, _)| placeholder(&template[i..]))
+            .collect()
+    }
+}
+
+/// `{name}` で始まる文字列から `name` を取り出す
+fn placeholder(s: &str) -> Option<&str> {
+    let end = s.find('}')?;
+    let name = &s[1..end];
+
+    let is_ident = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    is_ident.then_some(name)
+}
+
+/// メッセージカタログを定義する。
+/// 各項目は `&'static DiagMessage` として参照でき、全ての項目は `MESSAGES` にまとめられる。
+///
+/// ```ignore
+/// declare_messages! {
+///     UNEXPECTED_CHARACTER {
+///         ja: "予期しない文字",
+///         en: "unexpected character",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_messages {
+    ($($(#[$attr:meta])* $NAME:ident { ja: $ja:expr, en: $en:expr $(,)? })*) => {
+        $(
+            $(#[$attr])*
+            pub static $NAME: &$crate::stelaro_diagnostics::DiagMessage =
+                &$crate::stelaro_diagnostics::DiagMessage {
+                    id: stringify!($NAME),
+                    ja: $ja,
+                    en: $en,
+                };
+        )*
+
+        /// このカタログに含まれる全てのメッセージ
+        #[allow(dead_code)]
+        pub static MESSAGES: &[&$crate::stelaro_diagnostics::DiagMessage] = &[$($NAME),*];
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{DiagMessage, Lang};
+    use crate::{
+        stelaro_borrowck, stelaro_codegen, stelaro_context, stelaro_diagnostics, stelaro_incremental, stelaro_interface, stelaro_lexer, stelaro_lint, stelaro_metadata, stelaro_mir, stelaro_mir_transform, stelaro_parse, stelaro_resolve, stelaro_sir_typecheck, stelaro_vm,
+    };
+
+    const MSG: &DiagMessage = &DiagMessage {
+        id: "TEST",
+        ja: "`{name}` は {count} 回定義されています: `{...}`",
+        en: "`{name}` is defined {count} times: `{...}`",
+    };
+
+    #[test]
+    fn test_format() {
+        let args: &[(&str, &dyn std::fmt::Display)] = &[("name", &"f"), ("count", &2)];
+
+        assert_eq!(MSG.format(Lang::Ja, args), "`f` は 2 回定義されています: `{...}`");
+        assert_eq!(MSG.format(Lang::En, args), "`f` is defined 2 times: `{...}`");
+        assert_eq!(MSG.format(Lang::En, &[]), MSG.en);
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Lang::from_locale("ja_JP.UTF-8"), Some(Lang::Ja));
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("en"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C.UTF-8"), None);
+        assert_eq!(Lang::from_locale(""), None);
+    }
+
+    /// 全ての言語で、同じプレースホルダが使われていることを確認する
+    #[test]
+    fn test_catalogs_have_same_placeholders() {
+        let catalogs = [
+            stelaro_lexer::messages::MESSAGES,
+            stelaro_parse::messages::MESSAGES,
+            stelaro_resolve::messages::MESSAGES,
+            stelaro_sir_typecheck::messages::MESSAGES,
+            stelaro_borrowck::messages::MESSAGES,
+            stelaro_codegen::messages::MESSAGES,
+            stelaro_interface::messages::MESSAGES,
+            stelaro_incremental::messages::MESSAGES,
+            stelaro_context::messages::MESSAGES,
+            stelaro_metadata::messages::MESSAGES,
+            stelaro_vm::messages::MESSAGES,
+            stelaro_lint::messages::MESSAGES,
+            stelaro_mir::messages::MESSAGES,
+            stelaro_mir_transform::messages::MESSAGES,
+            stelaro_diagnostics::messages::MESSAGES,
+        ];
+
+        for msg in catalogs.iter().flat_map(|catalog| catalog.iter()) {
+            let ja = msg.placeholders(Lang::Ja).into_iter().collect::<BTreeSet<_>>();
+            let en = msg.placeholders(Lang::En).into_iter().collect::<BTreeSet<_>>();
+
+            assert_eq!(ja, en, "{}", msg.id);
+            assert!(!msg.en.is_empty() && !msg.ja.is_empty(), "{}", msg.id);
+        }
+    }
+}
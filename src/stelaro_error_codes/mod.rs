@@ -0,0 +1,372 @@
+//! 全てのエラーコードと、その詳細な説明を管理する。
+//!
+//! 各説明は `error_codes/{ja,en}/E0xxx.md` に記述され、
+//! 問題の説明、誤ったコード例、修正したコード例を含む。
+//! 新しいエラーコードを追加した場合は、ここにも登録する必要がある。
+
+use std::fmt;
+
+use crate::stelaro_diagnostics::Lang;
+
+
+/// エラーコードと、その詳細な説明
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorCodeInfo {
+    pub code: i32,
+    /// 日本語による説明
+    pub ja: &'static str,
+    /// 英語による説明
+    pub en: &'static str,
+}
+
+impl ErrorCodeInfo {
+    pub fn explanation(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.ja,
+            Lang::En => self.en,
+        }
+    }
+}
+
+macro_rules! register_error_codes {
+    ($($code:literal => $name:literal,)*) => {
+        /// 登録されている全てのエラーコード
+        pub static ERROR_CODES: &[ErrorCodeInfo] = &[
+            $(
+                ErrorCodeInfo {
+                    code: $code,
+                    ja: include_str!(concat!("error_codes/ja/", $name, ".md")),
+                    en: include_str!(concat!("error_codes/en/", $name, ".md")),
+                },
+            )*
+        ];
+    };
+}
+
+register_error_codes! {
+    // stelaro_lexer
+    100 => "E0100",
+    101 => "E0101",
+    102 => "E0102",
+    103 => "E0103",
+    104 => "E0104",
+    105 => "E0105",
+    106 => "E0106",
+    107 => "E0107",
+
+    // stelaro_parse
+    200 => "E0200",
+    201 => "E0201",
+    202 => "E0202",
+    203 => "E0203",
+    204 => "E0204",
+    205 => "E0205",
+    206 => "E0206",
+    207 => "E0207",
+    208 => "E0208",
+    209 => "E0209",
+    210 => "E0210",
+    211 => "E0211",
+    212 => "E0212",
+    213 => "E0213",
+    214 => "E0214",
+    215 => "E0215",
+
+    // stelaro_resolve
+    300 => "E0300",
+    301 => "E0301",
+    302 => "E0302",
+
+    // stelaro_sir_typecheck
+    400 => "E0400",
+    401 => "E0401",
+    402 => "E0402",
+    403 => "E0403",
+    404 => "E0404",
+    405 => "E0405",
+    406 => "E0406",
+    407 => "E0407",
+    408 => "E0408",
+    409 => "E0409",
+    410 => "E0410",
+    411 => "E0411",
+
+    // stelaro_lint
+    500 => "E0500",
+    501 => "E0501",
+
+    // stelaro_borrowck
+    600 => "E0600",
+
+    // stelaro_codegen
+    700 => "E0700",
+    701 => "E0701",
+
+    // stelaro_vm
+    800 => "E0800",
+
+    // stelaro_common
+    900 => "E0900",
+
+    // stelaro_context
+    901 => "E0901",
+
+    // stelaro_metadata
+    1000 => "E1000",
+    1001 => "E1001",
+    1002 => "E1002",
+    1003 => "E1003",
+
+    // stelaro_incremental
+    1100 => "E1100",
+}
+
+/// エラーコードを `E0200` の形式で表示する
+pub struct ErrorCodeDisplay(pub i32);
+
+impl fmt::Display for ErrorCodeDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+pub fn find_error_code(code: i32) -> Option<&'static ErrorCodeInfo> {
+    ERROR_CODES.iter().find(|info| info.code == code)
+}
+
+/// `E0200`, `E200`, `200` のいずれかの形式で書かれたエラーコードを読む
+pub fn parse_error_code(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use clap::ValueEnum;
+
+    use super::{ERROR_CODES, ErrorCodeDisplay, ErrorCodeInfo, find_error_code, parse_error_code};
+    use crate::stelaro_interface::{self, CompileOutcome, Config};
+    use crate::stelaro_session::config::{EmitKind, ExternEntry, SteloType};
+    use crate::stelaro_session::{Input, Options};
+
+    /// 説明のコード例から `--extern` で読み込まれるステロのメタデータのファイル名と、そのソースコード。
+    /// ステロの名前はファイル名の拡張子を除いたものになる
+    const EXTERN_STELOS: &[(&str, &str)] = &[(
+        "mathlib.stmeta",
+        "fn square(x: i64): i64 {\n    x * x\n}\n",
+    )];
+
+    /// 説明のコード例から参照される、その他のファイル
+    const EXAMPLE_FILES: &[(&str, &[u8])] = &[
+        ("not_a_directory", b""),
+        ("not_metadata.stmeta", b"not metadata"),
+    ];
+
+    /// `src` 以下のファイルから、`enum ErrorCode` に定義されたコードを集める
+    fn collect_declared_codes(dir: &Path, codes: &mut BTreeSet<(i32, String)>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                collect_declared_codes(&path, codes);
+                continue;
+            }
+
+            if path.extension().is_none_or(|ext| ext != "rs") {
+                continue;
+            }
+
+            let src = fs::read_to_string(&path).unwrap();
+            let Some((_, rest)) = src.split_once("enum ErrorCode {") else {
+                continue;
+            };
+            let (body, _) = rest.split_once('}').unwrap();
+
+            for variant in body.split(',') {
+                let Some((name, code)) = variant.split_once('=') else {
+                    continue;
+                };
+                let code = code.trim().parse().unwrap();
+                codes.insert((code, format!("{}::{}", path.display(), name.trim())));
+            }
+        }
+    }
+
+    /// 説明に含まれる `stelo` のコード例を、誤ったコード例、修正したコード例の順に取り出す
+    fn examples(explanation: &str) -> Vec<&str> {
+        explanation
+            .split("```stelo\n")
+            .skip(1)
+            .map(|block| block.split_once("```").unwrap().0)
+            .collect()
+    }
+
+    fn compile_stelo(name: &str, source_code: &str, opts: Options) -> CompileOutcome {
+        stelaro_interface::compile(Config {
+            opts: Options { stelo_name: Some(name.to_string()), ..opts },
+            input: Input::Str { name: name.to_string(), input: source_code.to_string() },
+            output_dir: None,
+            output_file: None,
+            file_loader: None,
+            emitter: None,
+        })
+    }
+
+    /// コード例のオプションに書かれたパスを解決するディレクトリ。
+    /// `EXTERN_STELOS` のメタデータと `EXAMPLE_FILES` を書き出しておく
+    fn example_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join("stelaro_error_code_tests");
+        fs::create_dir_all(&dir).unwrap();
+
+        for (file_name, source_code) in EXTERN_STELOS {
+            let name = Path::new(file_name).file_stem().unwrap().to_str().unwrap();
+            let opts = Options {
+                stelo_type: SteloType::Lib,
+                emit: vec![EmitKind::Metadata],
+                ..Options::default()
+            };
+            let outcome = compile_stelo(name, source_code, opts);
+            assert!(outcome.succeeded);
+            fs::write(dir.join(file_name), outcome.metadata.unwrap()).unwrap();
+        }
+
+        for (file_name, data) in EXAMPLE_FILES {
+            fs::write(dir.join(file_name), data).unwrap();
+        }
+
+        dir
+    }
+
+    /// コード例の先頭の `// compiled with `...`` に書かれたオプション
+    fn example_flags(example: &str) -> Option<&str> {
+        example
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("// compiled with `"))
+            .and_then(|line| line.strip_suffix('`'))
+    }
+
+    /// コード例のオプションを読む。書かれていない場合は、MIR の検査までを行う。
+    /// パスは `dir` からの相対パスとして解決する。
+    fn example_options(flags: Option<&str>, dir: &Path) -> Options {
+        let mut opts = Options { emit: vec![EmitKind::Mir], ..Options::default() };
+
+        let Some(flags) = flags else {
+            return opts;
+        };
+
+        let mut flags = flags.split_whitespace();
+        while let Some(flag) = flags.next() {
+            if let Some(kinds) = flag.strip_prefix("--emit=") {
+                opts.emit = kinds
+                    .split(',')
+                    .map(|kind| EmitKind::from_str(kind, false).unwrap())
+                    .collect();
+            } else if flag == "--extern" {
+                let entry = ExternEntry::parse(flags.next().unwrap()).unwrap();
+                let path = dir.join(&entry.path);
+                opts.externs.push(ExternEntry { path, ..entry });
+            } else if flag == "-C"
+                && let Some(incremental_dir) = flags.next().and_then(|opt| opt.strip_prefix("incremental="))
+            {
+                opts.cg_opts.incremental = true;
+                opts.cg_opts.incremental_dir = Some(dir.join(incremental_dir));
+            } else {
+                panic!("コード例のオプション `{flag}` は解釈できません");
+            }
+        }
+
+        opts
+    }
+
+    fn emitted_codes(outcome: &CompileOutcome) -> Vec<i32> {
+        outcome.diagnostics.iter().filter_map(|diag| diag.code).collect()
+    }
+
+    /// コード例が期待通りに報告しない場合、その理由を返す
+    fn check_examples(info: &ErrorCodeInfo, dir: &Path) -> Option<String> {
+        let code = ErrorCodeDisplay(info.code);
+        let [erroneous, fixed] = examples(info.en)[..] else {
+            return Some(format!("{code}: コード例は誤ったものと修正したものの二つである必要があります"));
+        };
+
+        let opts = example_options(example_flags(erroneous), dir);
+        let outcome = compile_stelo("example", erroneous, opts);
+        if !emitted_codes(&outcome).contains(&info.code) {
+            return Some(format!(
+                "{code}: 誤ったコード例が {code} を報告しません: {:?}",
+                outcome.diagnostics,
+            ));
+        }
+
+        // 修正したコード例にオプションが書かれていない場合は、誤ったコード例と同じオプションでコンパイルする
+        let opts = example_options(example_flags(fixed).or(example_flags(erroneous)), dir);
+        let outcome = compile_stelo("example", fixed, opts);
+        if emitted_codes(&outcome).contains(&info.code) {
+            return Some(format!(
+                "{code}: 修正したコード例が {code} を報告します: {:?}",
+                outcome.diagnostics,
+            ));
+        }
+
+        None
+    }
+
+    #[test]
+    fn test_all_declared_codes_are_registered() {
+        let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut codes = BTreeSet::new();
+        collect_declared_codes(&src_dir, &mut codes);
+
+        assert!(!codes.is_empty());
+
+        let unregistered = codes
+            .iter()
+            .filter(|(code, _)| find_error_code(*code).is_none())
+            .collect::<Vec<_>>();
+        assert!(unregistered.is_empty(), "登録されていないエラーコード: {unregistered:?}");
+    }
+
+    /// 全ての説明の誤ったコード例が実際にそのコードを報告し、
+    /// 修正したコード例が報告しないことを確認する
+    #[test]
+    fn test_explanation_examples_emit_their_code() {
+        let dir = example_dir();
+        let failures = ERROR_CODES
+            .iter()
+            .filter_map(|info| check_examples(info, &dir))
+            .collect::<Vec<_>>();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_explanations_have_examples() {
+        for info in ERROR_CODES {
+            let code = ErrorCodeDisplay(info.code);
+            assert_eq!(info.ja.matches("```stelo").count(), 2, "{code} (ja)");
+            assert_eq!(info.en.matches("```stelo").count(), 2, "{code} (en)");
+            assert_eq!(examples(info.ja).len(), 2, "{code} (ja)");
+        }
+    }
+
+    #[test]
+    fn test_parse_error_code() {
+        assert_eq!(parse_error_code("E0200"), Some(200));
+        assert_eq!(parse_error_code("E200"), Some(200));
+        assert_eq!(parse_error_code("200"), Some(200));
+        assert_eq!(parse_error_code("e0100"), Some(100));
+        assert_eq!(parse_error_code("E"), None);
+        assert_eq!(parse_error_code("E02x0"), None);
+        assert_eq!(ErrorCodeDisplay(200).to_string(), "E0200");
+    }
+}
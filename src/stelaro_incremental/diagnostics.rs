@@ -0,0 +1,35 @@
+use std::io;
+use std::path::Path;
+
+use crate::stelaro_common::DUMMY_SPAN;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsIncremental;
+
+impl<'dcx> DiagsIncremental {
+    /// キャッシュを保存できなくても生成物には影響しないため、警告として報告する
+    pub fn save_failed(dcx: DiagCtxtHandle<'dcx>, path: &Path, error: &io::Error) -> Diag<'dcx, ()> {
+        let mut diag = dcx.struct_warn(DUMMY_SPAN);
+        diag.set_code(ErrorCode::SaveFailed.into());
+        diag.set_message(dcx.translate(
+            messages::INCREMENTAL_SAVE_FAILED,
+            &[("path", &path.display()), ("error", error)],
+        ));
+        diag.set_help(dcx.translate(messages::INCREMENTAL_SAVE_FAILED_HELP, &[]));
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    SaveFailed = 1100,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
@@ -0,0 +1,47 @@
+//! インクリメンタルコンパイルで報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    INCREMENTAL_SAVE_FAILED {
+        ja: "インクリメンタルコンパイルのキャッシュを `{path}` に保存できませんでした: {error}",
+        en: "failed to save the incremental compilation cache to `{path}`: {error}",
+    }
+    INCREMENTAL_SAVE_FAILED_HELP {
+        ja: "`-C incremental=DIR` で、書き込めるディレクトリを指定してください",
+        en: "specify a writable directory with `-C incremental=DIR`",
+    }
+    INCREMENTAL_REPORT_TITLE {
+        ja: "インクリメンタルコンパイル:",
+        en: "incremental compilation:",
+    }
+    INCREMENTAL_REPORT_ITEMS {
+        ja: "関数",
+        en: "functions",
+    }
+    INCREMENTAL_REPORT_AST_UNCHANGED {
+        ja: "AST が変更されていない関数",
+        en: "unchanged ASTs",
+    }
+    INCREMENTAL_REPORT_SIR_UNCHANGED {
+        ja: "SIR が変更されていない関数",
+        en: "unchanged SIRs",
+    }
+    INCREMENTAL_REPORT_TYPECK_REUSED {
+        ja: "再利用された型検査の結果",
+        en: "reused type check results",
+    }
+    INCREMENTAL_REPORT_TYPECK_CHECKED {
+        ja: "型検査された関数",
+        en: "type checked functions",
+    }
+    INCREMENTAL_REPORT_CODEGEN_REUSED {
+        ja: "再利用されたコード生成の結果",
+        en: "reused codegen results",
+    }
+    INCREMENTAL_REPORT_CODEGEN_GENERATED {
+        ja: "コードが生成された関数",
+        en: "codegened functions",
+    }
+}
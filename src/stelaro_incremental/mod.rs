@@ -0,0 +1,551 @@
+//! インクリメンタルコンパイル。
+//!
+//! 関数ごとに AST と SIR のフィンガープリントを計算し、型検査とコード生成の結果とともに
+//! キャッシュファイルに保存する。次のコンパイルでは、関数自身と、それが参照している関数の
+//! シグネチャが変わっていなければ、保存された結果を再利用する。
+//!
+//! 関数は `DefPathHash` で識別するため、他の関数を追加や削除しても影響を受けない。
+//! キャッシュはエラーなくコンパイルを終えた場合にのみ保存する。
+
+mod diagnostics;
+pub mod messages;
+mod typeck;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::stelaro_ast::NodeId;
+use crate::stelaro_ast::ast::{self, ItemKind, ModKind};
+use crate::stelaro_ast::stable_hash::item_fingerprint;
+use crate::stelaro_common::encoder::{Decoder, Encoder};
+use crate::stelaro_common::stable_hasher::HashStable;
+use crate::stelaro_common::{
+    DefPathHash, Fingerprint, LineTable, LocalDefId, StableHasher, Symbol,
+};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_diagnostics::Lang;
+use crate::stelaro_incremental::diagnostics::DiagsIncremental;
+use crate::stelaro_session::Session;
+use crate::stelaro_session::config::{EmitKind, Input, SteloType};
+use crate::stelaro_sir::sir::MaybeOwner;
+use crate::stelaro_sir::stable_hash::{FnFingerprints, fn_fingerprints};
+use crate::stelaro_sir_typecheck::result::TypeckResults;
+
+
+/// キャッシュファイルの先頭に置く識別子
+const MAGIC: &[u8; 4] = b"STIC";
+
+/// キャッシュファイルの形式の版。形式を変更したら増やす
+const FORMAT_VERSION: u32 = 3;
+
+/// 関数ごとに出力を保存する `--emit` の種類。
+/// バイトコードはプログラム全体をまとめて生成するため、関数ごとには保存しない
+const CODEGEN_KINDS: [EmitKind; 3] = [EmitKind::C, EmitKind::Wat, EmitKind::Asm];
+
+/// 前回のコンパイルの結果を再利用した数。`-Z incremental-info` で表示する
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IncrementalStats {
+    /// 関数の数。
+    pub items: usize,
+    /// AST が前回から変わっていない関数の数。
+    pub ast_unchanged: usize,
+    /// SIR が前回から変わっていない関数の数。
+    pub sir_unchanged: usize,
+    /// 型検査の結果を再利用した関数の数。
+    pub typeck_reused: usize,
+    /// 型検査を行った関数の数。
+    pub typeck_checked: usize,
+    /// コード生成の結果を再利用した関数の数。`--emit` の種類ごとに数える。
+    pub codegen_reused: usize,
+    /// コードを生成した関数の数。`--emit` の種類ごとに数える。
+    pub codegen_generated: usize,
+}
+
+impl IncrementalStats {
+    /// `-Z incremental-info` で表示するための文字列を返す。
+    pub fn to_report(&self, lang: Lang) -> String {
+        let rows = [
+            (messages::INCREMENTAL_REPORT_ITEMS, self.items),
+            (messages::INCREMENTAL_REPORT_AST_UNCHANGED, self.ast_unchanged),
+            (messages::INCREMENTAL_REPORT_SIR_UNCHANGED, self.sir_unchanged),
+            (messages::INCREMENTAL_REPORT_TYPECK_REUSED, self.typeck_reused),
+            (messages::INCREMENTAL_REPORT_TYPECK_CHECKED, self.typeck_checked),
+            (messages::INCREMENTAL_REPORT_CODEGEN_REUSED, self.codegen_reused),
+            (messages::INCREMENTAL_REPORT_CODEGEN_GENERATED, self.codegen_generated),
+        ];
+
+        let mut report = format!("{}\n", messages::INCREMENTAL_REPORT_TITLE.format(lang, &[]));
+        for (name, count) in rows {
+            report.push_str(&format!("    {}: {count}\n", name.format(lang, &[])));
+        }
+        report
+    }
+}
+
+/// 関数ごとに保存する情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ItemRecord {
+    ast: Fingerprint,
+    sir: Fingerprint,
+    signature: Fingerprint,
+
+    /// 型検査の結果が依存する情報のフィンガープリント。
+    /// 関数の SIR と、参照している関数のシグネチャから計算する
+    typeck_key: Fingerprint,
+
+    /// コード生成の結果が依存する情報のフィンガープリント。
+    /// `typeck_key` に加えて、パニックのメッセージに含まれる関数の位置と字面から計算する
+    codegen_key: Fingerprint,
+
+    /// `typeck::encode_typeck_results` で変換した型検査の結果
+    typeck: Option<Vec<u8>>,
+
+    codegen: BTreeMap<EmitKind, String>,
+}
+
+/// 前回のコンパイルで保存された結果と、現在のコンパイルで記録している結果
+pub struct IncrementalCache {
+    path: PathBuf,
+    previous: HashMap<DefPathHash, ItemRecord>,
+    current: HashMap<DefPathHash, ItemRecord>,
+
+    /// `record_items` で関数を記録したかどうか。
+    /// SIR まで進まなかったコンパイルでは、前回のキャッシュを上書きしない
+    recorded: bool,
+
+    stats: IncrementalStats,
+}
+
+impl IncrementalCache {
+    /// インクリメンタルコンパイルが有効であれば、前回のコンパイルのキャッシュを読み込む。
+    ///
+    /// キャッシュが存在しない場合や、異なる版のコンパイラやオプションで保存された場合は、
+    /// 空のキャッシュから始める。
+    pub fn load(sess: &Session, stelo_name: Symbol) -> Option<IncrementalCache> {
+        let dir = sess.incremental_dir()?;
+        let path = dir.join(cache_file_name(sess, stelo_name));
+
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|data| decode_cache(&data, &header(sess)))
+            .unwrap_or_default();
+
+        Some(IncrementalCache {
+            path,
+            previous,
+            current: HashMap::new(),
+            recorded: false,
+            stats: IncrementalStats::default(),
+        })
+    }
+
+    /// SIR に変換したすべての関数のフィンガープリントを計算し、記録する。
+    /// `ast` は `ast_fingerprints` で SIR に変換する前に計算したもの。
+    pub fn record_items(&mut self, tcx: TyCtxt<'_>, ast: &HashMap<DefPathHash, Fingerprint>) {
+        let lines = LineTable::new(&tcx.sess.source_map().file.borrow());
+        let file_name = tcx.sess.source_map().file.borrow().name.clone();
+
+        let mut items = Vec::new();
+        for (def_id, owner) in tcx.sir_stelo(()).owners.iter_enumerated() {
+            if let MaybeOwner::Owner(_) = owner
+                && let Some(fingerprints) = fn_fingerprints(tcx, def_id)
+            {
+                items.push((def_id, tcx.sir_def_path_hash(def_id), fingerprints));
+            }
+        }
+
+        let signatures = items
+            .iter()
+            .map(|(_, hash, fingerprints)| (*hash, fingerprints.signature))
+            .collect::<HashMap<_, _>>();
+
+        for (def_id, hash, FnFingerprints { item, signature, deps }) in items {
+            let mut hasher = StableHasher::new();
+            item.hash_stable(&mut hasher);
+            for dep in &deps {
+                dep.hash_stable(&mut hasher);
+                // 外部ステロの関数のシグネチャは、読み込んだメタデータから得る
+                let signature = signatures.get(dep).copied().or_else(|| {
+                    let def_id = tcx.def_path_hash_to_def_id(*dep).filter(|id| !id.is_local())?;
+                    tcx.cstore.get(def_id.stelo).fn_sig_fingerprint(def_id.index)
+                });
+                signature.hash_stable(&mut hasher);
+            }
+            let typeck_key: Fingerprint = hasher.finish();
+
+            let span = tcx.source_span(def_id);
+            let mut hasher = StableHasher::new();
+            typeck_key.hash_stable(&mut hasher);
+            file_name.hash_stable(&mut hasher);
+            lines.span_to_range_string(span).hash_stable(&mut hasher);
+            lines.snippet(span).hash_stable(&mut hasher);
+            let codegen_key: Fingerprint = hasher.finish();
+
+            let record = ItemRecord {
+                ast: ast.get(&hash).copied().unwrap_or(Fingerprint::ZERO),
+                sir: item,
+                signature,
+                typeck_key,
+                codegen_key,
+                typeck: None,
+                codegen: BTreeMap::new(),
+            };
+
+            self.stats.items += 1;
+            if let Some(previous) = self.previous.get(&hash) {
+                self.stats.ast_unchanged += (previous.ast == record.ast) as usize;
+                self.stats.sir_unchanged += (previous.sir == record.sir) as usize;
+            }
+            self.current.insert(hash, record);
+        }
+
+        self.recorded = true;
+    }
+
+    /// これまでに前回の結果を再利用した数
+    pub fn stats(&self) -> IncrementalStats {
+        self.stats
+    }
+
+    fn records(&self, hash: DefPathHash) -> Option<(&ItemRecord, &ItemRecord)> {
+        Some((self.previous.get(&hash)?, self.current.get(&hash)?))
+    }
+
+    /// 型検査の結果を再利用できれば、それを復元して返す。
+    pub fn reuse_typeck<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        def_id: LocalDefId,
+    ) -> Option<TypeckResults<'tcx>> {
+        let hash = tcx.sir_def_path_hash(def_id);
+
+        let reused = self
+            .records(hash)
+            .filter(|(previous, current)| previous.typeck_key == current.typeck_key)
+            .and_then(|(previous, _)| {
+                let data = previous.typeck.as_ref()?;
+                Some((data.clone(), typeck::decode_typeck_results(tcx, def_id, data)?))
+            });
+
+        match reused {
+            Some((data, results)) => {
+                self.stats.typeck_reused += 1;
+                self.current.get_mut(&hash).unwrap().typeck = Some(data);
+                Some(results)
+            }
+            None => {
+                self.stats.typeck_checked += 1;
+                None
+            }
+        }
+    }
+
+    /// 型検査した関数の結果を記録する。
+    pub fn record_typeck(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        results: &HashMap<LocalDefId, &TypeckResults<'_>>,
+    ) {
+        for (&def_id, results) in results {
+            let Some(record) = self.current.get_mut(&tcx.sir_def_path_hash(def_id)) else {
+                continue;
+            };
+            if record.typeck.is_none() {
+                record.typeck = typeck::encode_typeck_results(tcx, results);
+            }
+        }
+    }
+
+    /// コード生成の結果を再利用できれば、それを返す。
+    pub fn reuse_codegen(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        def_id: LocalDefId,
+        kind: EmitKind,
+    ) -> Option<String> {
+        let hash = tcx.sir_def_path_hash(def_id);
+
+        let reused = self
+            .records(hash)
+            .filter(|(previous, current)| previous.codegen_key == current.codegen_key)
+            .and_then(|(previous, _)| previous.codegen.get(&kind).cloned());
+
+        match reused {
+            Some(output) => {
+                self.stats.codegen_reused += 1;
+                self.record_output(hash, kind, output.clone());
+                Some(output)
+            }
+            None => {
+                self.stats.codegen_generated += 1;
+                None
+            }
+        }
+    }
+
+    /// 生成した関数のコードを記録する。
+    pub fn record_codegen(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        def_id: LocalDefId,
+        kind: EmitKind,
+        output: String,
+    ) {
+        self.record_output(tcx.sir_def_path_hash(def_id), kind, output);
+    }
+
+    fn record_output(&mut self, hash: DefPathHash, kind: EmitKind, output: String) {
+        if CODEGEN_KINDS.contains(&kind)
+            && let Some(record) = self.current.get_mut(&hash)
+        {
+            record.codegen.insert(kind, output);
+        }
+    }
+
+    /// 記録した結果をキャッシュファイルに保存し、再利用の統計を返す。
+    ///
+    /// エラーが報告されている場合や、SIR まで進まなかった場合は保存しない。
+    /// 現在のコンパイルで行わなかった段階の結果は、依存する情報が変わっていなければ前回のものを引き継ぐ。
+    pub fn save(mut self, tcx: TyCtxt<'_>) -> IncrementalStats {
+        if !self.recorded || tcx.dcx().has_errors().is_some() {
+            return self.stats;
+        }
+
+        for (hash, current) in &mut self.current {
+            let Some(previous) = self.previous.remove(hash) else {
+                continue;
+            };
+
+            if current.typeck.is_none() && previous.typeck_key == current.typeck_key {
+                current.typeck = previous.typeck;
+            }
+            if previous.codegen_key == current.codegen_key {
+                for (kind, output) in previous.codegen {
+                    current.codegen.entry(kind).or_insert(output);
+                }
+            }
+        }
+
+        let data = encode_cache(&self.current, &header(tcx.sess));
+        if let Err(error) = write_atomically(&self.path, &data) {
+            DiagsIncremental::save_failed(tcx.dcx(), &self.path, &error).emit();
+        }
+
+        self.stats
+    }
+}
+
+/// AST のすべての関数のフィンガープリントを、その定義の `DefPathHash` ごとに計算する。
+///
+/// SIR への変換は AST を消費するため、変換の前に呼び出す。
+pub fn ast_fingerprints(
+    tcx: TyCtxt<'_>,
+    stelo: &ast::Stelo,
+    node_id_to_def_id: &HashMap<NodeId, LocalDefId>,
+) -> HashMap<DefPathHash, Fingerprint> {
+    fn visit_items(
+        tcx: TyCtxt<'_>,
+        items: &[Box<ast::Item>],
+        node_id_to_def_id: &HashMap<NodeId, LocalDefId>,
+        fingerprints: &mut HashMap<DefPathHash, Fingerprint>,
+    ) {
+        for item in items {
+            match &item.kind {
+                ItemKind::Fn(_) => {
+                    if let Some(&def_id) = node_id_to_def_id.get(&item.id) {
+                        fingerprints.insert(tcx.sir_def_path_hash(def_id), item_fingerprint(item));
+                    }
+                }
+                ItemKind::Mod(_, ModKind::Inline(items, _)) => {
+                    visit_items(tcx, items, node_id_to_def_id, fingerprints);
+                }
+                // 型エイリアスは、それを使う関数のフィンガープリントに展開した型として含まれる
+                ItemKind::TyAlias(_) => {}
+            }
+        }
+    }
+
+    let mut fingerprints = HashMap::new();
+    visit_items(tcx, &stelo.items, node_id_to_def_id, &mut fingerprints);
+    fingerprints
+}
+
+/// 入力ごとに異なるキャッシュファイルを使うよう、ステロの名前と入力のハッシュから名前を作る。
+fn cache_file_name(sess: &Session, stelo_name: Symbol) -> String {
+    let mut hasher = StableHasher::new();
+    match &sess.paths.input {
+        Input::File(path) => {
+            0u8.hash_stable(&mut hasher);
+            path.hash_stable(&mut hasher);
+        }
+        Input::Str { name, .. } => {
+            1u8.hash_stable(&mut hasher);
+            name.hash_stable(&mut hasher);
+        }
+    }
+    let hash: Fingerprint = hasher.finish();
+
+    format!("{stelo_name}-{:016x}.bin", hash.to_smaller_hash().as_u64())
+}
+
+/// 結果に影響するコンパイラの版とオプション。異なる場合は前回のキャッシュを使わない
+fn header(sess: &Session) -> Vec<u8> {
+    let mut e = Encoder::new();
+    for &b in MAGIC {
+        e.emit_u8(b);
+    }
+    e.emit_u32(FORMAT_VERSION);
+    e.emit_str(env!("CARGO_PKG_VERSION"));
+    e.emit_bool(sess.overflow_checks());
+    // ライブラリのステロでは、シンボル名やエクスポートなどコード生成の結果が変わる
+    e.emit_bool(sess.opts.stelo_type == SteloType::Lib);
+    e.finish()
+}
+
+fn encode_cache(records: &HashMap<DefPathHash, ItemRecord>, header: &[u8]) -> Vec<u8> {
+    let mut e = Encoder::new();
+    e.emit_bytes(header);
+
+    // 同じ内容であれば同じファイルになるよう、`DefPathHash` の順に並べる
+    let mut records = records.iter().collect::<Vec<_>>();
+    records.sort_unstable_by_key(|&(hash, _)| hash);
+
+    e.emit_usize(records.len());
+    for (hash, record) in records {
+        e.emit_fingerprint(hash.0);
+        e.emit_fingerprint(record.ast);
+        e.emit_fingerprint(record.sir);
+        e.emit_fingerprint(record.signature);
+        e.emit_fingerprint(record.typeck_key);
+        e.emit_fingerprint(record.codegen_key);
+
+        e.emit_bool(record.typeck.is_some());
+        if let Some(typeck) = &record.typeck {
+            e.emit_bytes(typeck);
+        }
+
+        e.emit_usize(record.codegen.len());
+        for (kind, output) in &record.codegen {
+            let index = CODEGEN_KINDS.iter().position(|k| k == kind).unwrap();
+            e.emit_u8(index as u8);
+            e.emit_str(output);
+        }
+    }
+
+    e.finish()
+}
+
+fn decode_cache(data: &[u8], header: &[u8]) -> Option<HashMap<DefPathHash, ItemRecord>> {
+    let mut d = Decoder::new(data);
+    if d.read_bytes()? != header {
+        return None;
+    }
+
+    let len = d.read_usize()?;
+    let mut records = HashMap::new();
+    for _ in 0..len {
+        let hash = DefPathHash(d.read_fingerprint()?);
+        let ast = d.read_fingerprint()?;
+        let sir = d.read_fingerprint()?;
+        let signature = d.read_fingerprint()?;
+        let typeck_key = d.read_fingerprint()?;
+        let codegen_key = d.read_fingerprint()?;
+
+        let typeck = match d.read_bool()? {
+            true => Some(d.read_bytes()?.to_vec()),
+            false => None,
+        };
+
+        let mut codegen = BTreeMap::new();
+        for _ in 0..d.read_usize()? {
+            let kind = *CODEGEN_KINDS.get(d.read_u8()? as usize)?;
+            codegen.insert(kind, d.read_str()?.to_string());
+        }
+
+        records.insert(hash, ItemRecord {
+            ast,
+            sir,
+            signature,
+            typeck_key,
+            codegen_key,
+            typeck,
+            codegen,
+        });
+    }
+
+    d.is_empty().then_some(records)
+}
+
+/// 書き込みの途中で中断されても壊れたファイルが残らないよう、一時ファイルに書いてから置き換える。
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(seed: u64) -> ItemRecord {
+        let fingerprint = |n: u64| Fingerprint::new(seed, n);
+        ItemRecord {
+            ast: fingerprint(1),
+            sir: fingerprint(2),
+            signature: fingerprint(3),
+            typeck_key: fingerprint(4),
+            codegen_key: fingerprint(5),
+            typeck: Some(vec![0, 1, 2]),
+            codegen: BTreeMap::from([
+                (EmitKind::C, "int32_t f(void);".to_string()),
+                (EmitKind::Asm, "f:\n    ret\n".to_string()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let records = HashMap::from([
+            (DefPathHash(Fingerprint::new(1_u64, 1_u64)), record(10)),
+            (DefPathHash(Fingerprint::new(1_u64, 2_u64)), ItemRecord {
+                typeck: None,
+                codegen: BTreeMap::new(),
+                ..record(20)
+            }),
+        ]);
+
+        let data = encode_cache(&records, b"header");
+        assert_eq!(decode_cache(&data, b"header"), Some(records));
+    }
+
+    /// 異なる版のコンパイラやオプションで保存されたキャッシュは使わない
+    #[test]
+    fn test_cache_with_different_header() {
+        let records = HashMap::from([(DefPathHash(Fingerprint::new(1_u64, 1_u64)), record(10))]);
+
+        let data = encode_cache(&records, b"header");
+        assert_eq!(decode_cache(&data, b"other"), None);
+        assert_eq!(decode_cache(&data[..data.len() - 1], b"header"), None);
+    }
+
+    #[test]
+    fn test_stats_report() {
+        let stats = IncrementalStats {
+            items: 3,
+            typeck_reused: 2,
+            typeck_checked: 1,
+            ..IncrementalStats::default()
+        };
+
+        let report = stats.to_report(Lang::En);
+        assert!(report.starts_with("incremental compilation:\n"), "{report}");
+        assert!(report.contains("    reused type check results: 2\n"), "{report}");
+        assert!(report.contains("    type checked functions: 1\n"), "{report}");
+    }
+}
@@ -0,0 +1,127 @@
+//! 型検査の結果の保存と復元。
+//!
+//! 型の書き出しには `stelaro_ty::codec` を使う。推論変数やエラーを含む結果は保存しない。
+
+use crate::stelaro_common::encoder::{Decoder, Encoder};
+use crate::stelaro_common::{DefPathHash, LocalDefId};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::sir_id::{ItemLocalId, OwnerId, SirId};
+use crate::stelaro_sir_typecheck::result::{CaptureKind, CapturedVar, TypeckResults};
+use crate::stelaro_ty::codec::{decode_fn_sig, decode_ty, encode_fn_sig, encode_ty};
+
+
+/// 型検査の結果をバイト列に変換する。保存できない型を含む場合は `None` を返す。
+pub fn encode_typeck_results(tcx: TyCtxt<'_>, results: &TypeckResults<'_>) -> Option<Vec<u8>> {
+    if results.tainted_by_errors {
+        return None;
+    }
+
+    let mut e = Encoder::new();
+
+    e.emit_bool(results.fn_sig.is_some());
+    if let Some(fn_sig) = &results.fn_sig {
+        encode_fn_sig(tcx, &mut e, fn_sig)?;
+    }
+
+    // 出力が実行ごとに変わらないよう、ノードの順に並べる
+    let mut node_types = results.node_types().collect::<Vec<_>>();
+    node_types.sort_unstable_by_key(|&(id, _)| id);
+
+    e.emit_usize(node_types.len());
+    for (id, ty) in node_types {
+        e.emit_u32(id.as_u32());
+        encode_ty(tcx, &mut e, ty)?;
+    }
+
+    let mut coercions = results.fn_ptr_coercions().collect::<Vec<_>>();
+    coercions.sort_unstable_by_key(|&(id, _)| id);
+
+    e.emit_usize(coercions.len());
+    for (id, ty) in coercions {
+        e.emit_u32(id.as_u32());
+        encode_ty(tcx, &mut e, ty)?;
+    }
+
+    // 型エイリアスは外部ステロの定義であることもあるため、`DefPathHash` で表す
+    let mut aliases = results.ty_aliases().collect::<Vec<_>>();
+    aliases.sort_unstable_by_key(|&(id, _)| id);
+
+    e.emit_usize(aliases.len());
+    for (id, alias) in aliases {
+        e.emit_u32(id.as_u32());
+        e.emit_fingerprint(tcx.def_path_hash(alias).0);
+    }
+
+    // クロージャの `LocalDefId` はセッション固有のため、`DefPathHash` で表す
+    let mut captures = results.all_closure_captures().collect::<Vec<_>>();
+    captures.sort_unstable_by_key(|&(closure, _)| closure.local_def_index);
+
+    e.emit_usize(captures.len());
+    for (closure, vars) in captures {
+        e.emit_fingerprint(tcx.def_path_hash(closure.to_def_id()).0);
+        e.emit_usize(vars.len());
+        for var in vars {
+            e.emit_u32(var.var_id.local_id.as_u32());
+            e.emit_bool(var.kind == CaptureKind::ByValue);
+        }
+    }
+
+    Some(e.finish())
+}
+
+/// `encode_typeck_results` で変換したバイト列から、`owner_id` の型検査の結果を復元する。
+/// 参照している関数が存在しなくなった場合などは `None` を返す。
+pub fn decode_typeck_results<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    owner_id: LocalDefId,
+    data: &[u8],
+) -> Option<TypeckResults<'tcx>> {
+    let mut d = Decoder::new(data);
+    let mut results = TypeckResults::new(owner_id);
+
+    if d.read_bool()? {
+        results.fn_sig = Some(decode_fn_sig(tcx, &mut d)?);
+    }
+
+    let len = d.read_usize()?;
+    for _ in 0..len {
+        let id = ItemLocalId::from_u32(d.read_u32()?);
+        let ty = decode_ty(tcx, &mut d)?;
+        results.record_type(id, ty);
+    }
+
+    let len = d.read_usize()?;
+    for _ in 0..len {
+        let id = ItemLocalId::from_u32(d.read_u32()?);
+        let ty = decode_ty(tcx, &mut d)?;
+        results.record_fn_ptr_coercion(id, ty);
+    }
+
+    let len = d.read_usize()?;
+    for _ in 0..len {
+        let id = ItemLocalId::from_u32(d.read_u32()?);
+        let alias = tcx.def_path_hash_to_def_id(DefPathHash(d.read_fingerprint()?))?;
+        results.record_ty_alias(id, alias);
+    }
+
+    let len = d.read_usize()?;
+    for _ in 0..len {
+        let hash = DefPathHash(d.read_fingerprint()?);
+        let closure = tcx.def_path_hash_to_def_id(hash)?.as_local()?;
+        let vars = (0..d.read_usize()?)
+            .map(|_| {
+                let local_id = ItemLocalId::from_u32(d.read_u32()?);
+                let kind = if d.read_bool()? {
+                    CaptureKind::ByValue
+                } else {
+                    CaptureKind::ByRef
+                };
+                let var_id = SirId { owner: OwnerId { def_id: owner_id }, local_id };
+                Some(CapturedVar { var_id, kind })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        results.record_closure_captures(closure, vars);
+    }
+
+    d.is_empty().then_some(results)
+}
@@ -0,0 +1,97 @@
+//! ツールにコンパイラを組み込むための入口。
+//!
+//! `run_compiler` はエラーをパニックで伝え、`stelaro::run` はプロセスを終了させる。
+//! `compile` はそれらを捕捉し、診断と生成物をすべて `CompileOutcome` として返す。
+
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use crate::stelaro_common::FatalErrorMarker;
+use crate::stelaro_common::source_map::{SourceFile, get_source_map};
+use crate::stelaro_diagnostics::{CollectingEmitter, DiagInner};
+use crate::stelaro_interface::emit::emit_artifacts;
+use crate::stelaro_interface::{Config, messages, run_compiler};
+use crate::stelaro_session::config::EmitKind;
+
+
+/// `compile` の結果
+pub struct CompileOutcome {
+    /// 報告された診断。出力された順に並ぶ
+    pub diagnostics: Vec<DiagInner>,
+
+    /// `opts.emit` で要求された中間表現のテキスト。
+    /// コンパイルが途中で中断された場合は、それまでに生成されたものだけを含む
+    pub artifacts: BTreeMap<EmitKind, String>,
+
+    /// `EmitKind::Metadata` が要求された場合の、ステロのメタデータ。
+    /// `--extern` で読み込めるよう、そのままファイルに書き出す
+    pub metadata: Option<Vec<u8>>,
+
+    /// エラーが報告されずにコンパイルが終わった
+    pub succeeded: bool,
+
+    /// 読み込まれた入力ファイル。診断のスパンを行と列に変換するために使う。
+    /// 入力を読み込む前に中断された場合は `None`
+    pub source: Option<Rc<SourceFile>>,
+}
+
+/// 診断を標準エラー出力に出力せず、プロセスも終了させずにコンパイルする。
+///
+/// `config.emitter` は無視され、診断はすべて `CompileOutcome::diagnostics` に集められる。
+/// 入力ファイルを書き換える `opts.fix` と、標準エラー出力に書く `-Z statistics` と
+/// `-Z incremental-info` は無効になる。
+/// 実行ファイルの生成 (`EmitKind::Exe`) はファイルへの書き出しを伴うため、エラーとして報告する。
+///
+/// 内部コンパイラエラーなど、診断の報告以外の理由によるパニックは捕捉しない。
+pub fn compile(mut config: Config) -> CompileOutcome {
+    let emitter = CollectingEmitter::new();
+    config.emitter = Some(Box::new(emitter.clone()));
+    config.opts.fix = false;
+    config.opts.unstable_opts.statistics = false;
+    config.opts.unstable_opts.incremental_info = false;
+
+    let mut artifacts = BTreeMap::new();
+    let mut metadata = None;
+    let mut source_map = None;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run_compiler(config, |sess| {
+            // 入力ファイルは構文解析の際に読み込まれるため、中断された場合に備えて
+            // ソースマップを保持しておき、コンパイルの後で取り出す
+            source_map = get_source_map();
+
+            if sess.opts.emit.contains(&EmitKind::Exe) {
+                let msg = sess.dcx().translate(messages::EMIT_EXE_IN_EMBEDDED_COMPILE, &[]);
+                sess.dcx().emit_fatal(msg);
+            }
+
+            emit_artifacts(
+                sess,
+                |kind, output| {
+                    artifacts.insert(kind, output);
+                },
+                |data| metadata = Some(data),
+            );
+        })
+    }));
+
+    let succeeded = match result {
+        Ok(()) => true,
+        Err(payload) if payload.is::<FatalErrorMarker>() => false,
+        Err(payload) => panic::resume_unwind(payload),
+    };
+
+    // 入力を読み込む前に中断された場合、ソースマップには名前のない空のファイルが残る
+    let source = source_map
+        .map(|source_map| Rc::clone(&source_map.file.borrow()))
+        .filter(|file| !file.name.as_os_str().is_empty());
+
+    CompileOutcome {
+        diagnostics: emitter.take_diagnostics(),
+        artifacts,
+        metadata,
+        succeeded,
+        source,
+    }
+}
@@ -0,0 +1,132 @@
+//! `--emit` で要求された中間表現の生成。
+//!
+//! コマンドラインからの実行と、組み込み用の `compile` の両方から使われる。
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::stelaro_ast::token::TokenStream;
+use crate::stelaro_common::LineTable;
+use crate::stelaro_common::source_map::get_source_map;
+use crate::stelaro_diagnostics::{DiagCtxt, SilentEmitter};
+use crate::stelaro_interface::passes::{self, create_and_enter_global_ctxt};
+use crate::stelaro_lexer::Lexer;
+use crate::stelaro_session::config::EmitKind;
+use crate::stelaro_session::{ParseSess, Session};
+use crate::{
+    stelaro_ast, stelaro_codegen, stelaro_metadata, stelaro_mir, stelaro_sir, stelaro_sir_typecheck,
+    stelaro_vm,
+};
+
+
+/// `sess.opts.emit` で要求された中間表現を段階の順に生成し、生成するたびに `f` に渡す。
+///
+/// `EmitKind::Exe` にはリンクする前のアセンブリを渡す。
+/// `EmitKind::Metadata` はバイナリ形式であるため、`f` ではなく `metadata` に渡す。
+/// 要求された中で最も後の段階の出力を生成した時点でコンパイルを終える。
+/// エラーが報告された場合は、その段階でコンパイルを中断する。
+pub fn emit_artifacts(
+    sess: &Session,
+    mut f: impl FnMut(EmitKind, String),
+    mut metadata: impl FnMut(Vec<u8>),
+) {
+    let emit = &sess.opts.emit;
+    // `emit` は段階の順に並んでいるため、最後の要素より後の段階は実行しない
+    let Some(&last) = emit.last() else {
+        return;
+    };
+    let wants = |kind| emit.contains(&kind);
+
+    let stelo = passes::parse(sess);
+    let lines = LineTable::new(&sess.source_map().file.borrow());
+
+    if wants(EmitKind::Tokens) {
+        let tokens = relex(sess);
+        f(EmitKind::Tokens, to_text(|w| stelaro_ast::dump::write_tokens(&tokens, &lines, w)));
+    }
+    if wants(EmitKind::Ast) {
+        f(EmitKind::Ast, to_text(|w| stelaro_ast::dump::write_ast_tree(&stelo, &lines, w)));
+    }
+    if wants(EmitKind::AstPretty) {
+        f(EmitKind::AstPretty, to_text(|w| stelaro_ast::pretty::write_ast_pretty(&stelo, w)));
+    }
+    if last <= EmitKind::AstPretty {
+        return;
+    }
+
+    create_and_enter_global_ctxt(sess, |tcx| {
+        passes::lower_to_sir(tcx, stelo);
+
+        if wants(EmitKind::Sir) {
+            f(EmitKind::Sir, to_text(|w| stelaro_sir::dump::write_sir_tree(tcx, &lines, w)));
+        }
+        if wants(EmitKind::SirPretty) {
+            f(EmitKind::SirPretty, to_text(|w| stelaro_sir::pretty::write_sir_pretty(tcx, w)));
+        }
+        if last <= EmitKind::SirPretty {
+            return;
+        }
+
+        let typeck_results = passes::typeck(tcx);
+
+        if wants(EmitKind::Types) {
+            f(
+                EmitKind::Types,
+                to_text(|w| stelaro_sir_typecheck::dump::write_types(tcx, &typeck_results, &lines, w)),
+            );
+        }
+        if wants(EmitKind::Metadata) {
+            metadata(stelaro_metadata::encode_metadata(tcx));
+        }
+        if last <= EmitKind::Metadata {
+            return;
+        }
+
+        let mut bodies = passes::analysis(tcx, &typeck_results);
+        passes::optimize(tcx, &mut bodies);
+
+        // `Asm` と `Exe` を両方要求された場合も、アセンブリは一度だけ生成する
+        let mut asm = None;
+
+        for &kind in emit.iter().filter(|kind| **kind > EmitKind::Metadata) {
+            let output = match kind {
+                EmitKind::Mir => {
+                    to_text(|w| stelaro_mir::pretty::write_mir_pretty(tcx, &bodies, w))
+                }
+                EmitKind::C => stelaro_codegen::c::codegen_c(tcx, &bodies),
+                EmitKind::Wat => stelaro_codegen::wasm::codegen_wat(tcx, &bodies),
+                EmitKind::Bytecode => stelaro_vm::compile_program(tcx, &bodies).to_string(),
+                EmitKind::Asm | EmitKind::Exe => asm
+                    .get_or_insert_with(|| stelaro_codegen::x86_64::codegen_asm(tcx, &bodies))
+                    .clone(),
+                _ => unreachable!(),
+            };
+            sess.dcx().abort_if_errors();
+
+            f(kind, output);
+        }
+    });
+}
+
+fn to_text(write: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result) -> String {
+    let mut output = String::new();
+    write(&mut output).unwrap();
+    output
+}
+
+/// 構文解析器はトークン列を消費するため、`--emit=tokens` のために入力を字句解析し直す。
+///
+/// 字句解析のエラーは構文解析の際に報告済みであるため、ここでは報告しない。
+fn relex(sess: &Session) -> TokenStream {
+    let src = Rc::clone(&sess.source_map().file.borrow().src);
+    let dcx = DiagCtxt::new(Box::new(SilentEmitter::new()));
+    let psess = ParseSess::with_dcx(dcx, get_source_map().unwrap());
+
+    match Lexer::new(&psess, &src).lex() {
+        Ok(tokens) => tokens,
+        Err(_) => {
+            sess.dcx().abort_if_errors();
+            unreachable!("bug: 字句解析のエラーが報告されていません")
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs;
+
+use super::messages;
+use crate::stelaro_diagnostics::Suggestion;
+use crate::stelaro_session::{Session, config::Input};
+
+
+/// `--fix` が指定された場合に、出力された診断がもつ自動的に適用できる修正案を
+/// 入力ファイルに書き戻す。
+pub fn apply_machine_applicable_suggestions(sess: &Session) {
+    let suggestions = sess.dcx().take_machine_applicable_suggestions();
+
+    // 文字列から与えられた入力は書き戻す先が存在しない
+    let Input::File(path) = &sess.paths.input else {
+        return;
+    };
+
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let src = sess.source_map().file.borrow().src.clone();
+    let (fixed, applied) = apply_suggestions(&src, &suggestions);
+
+    if applied == 0 {
+        return;
+    }
+
+    let dcx = sess.dcx();
+    if let Err(error) = fs::write(path, fixed) {
+        let msg = dcx.translate(
+            messages::FIX_WRITE_FAILED,
+            &[("path", &path.display()), ("error", &error)],
+        );
+        dcx.emit_fatal(msg);
+    }
+
+    eprintln!(
+        "{}",
+        dcx.translate(messages::FIXES_APPLIED, &[("applied", &applied), ("path", &path.display())]),
+    );
+}
+
+/// 修正案をソースコードに適用し、修正後のソースコードと適用した修正案の数を返す。
+///
+/// `Span` は文字単位の位置であることに注意する。
+/// 他の修正案と範囲が重なる修正案は適用しない。
+pub fn apply_suggestions(src: &str, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut suggestions = suggestions.iter().collect::<Vec<_>>();
+    suggestions.sort_by_key(|s| (s.span.start, s.span.end));
+    suggestions.dedup();
+
+    // 文字単位の位置からバイト単位の位置への対応
+    let byte_pos = |char_pos: u32| {
+        src.char_indices()
+            .nth(char_pos as usize)
+            .map_or(src.len(), |(i, _)| i)
+    };
+
+    let mut fixed = String::with_capacity(src.len());
+    let mut applied = 0;
+    // 直前に適用した修正案の終端 (文字単位、バイト単位)
+    let mut last_end = None;
+    let mut copied = 0;
+
+    for suggestion in suggestions {
+        let span = suggestion.span;
+
+        if let Some(end) = last_end
+            && span.start < end
+        {
+            continue;
+        }
+
+        let (start, end) = (byte_pos(span.start), byte_pos(span.end));
+        fixed.push_str(&src[copied..start]);
+        fixed.push_str(&suggestion.replacement);
+
+        copied = end;
+        last_end = Some(span.end);
+        applied += 1;
+    }
+
+    fixed.push_str(&src[copied..]);
+
+    (fixed, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stelaro_common::Span;
+    use crate::stelaro_diagnostics::{Applicability, Suggestion};
+
+    use super::apply_suggestions;
+
+    fn suggestion(span: Span, replacement: &str) -> Suggestion {
+        Suggestion {
+            span,
+            msg: String::new(),
+            replacement: replacement.to_string(),
+            applicability: Applicability::MachineApplicable,
+        }
+    }
+
+    #[test]
+    fn test_apply_suggestions() {
+        let src = "fn f { let x = 1 }";
+        let (fixed, applied) = apply_suggestions(
+            src,
+            &[
+                // 順序に依存しない
+                suggestion((16..16).into(), ";"),
+                suggestion((4..4).into(), "()"),
+                suggestion((11..12).into(), "_x"),
+            ],
+        );
+
+        assert_eq!(fixed, "fn f() { let _x = 1; }");
+        assert_eq!(applied, 3);
+    }
+
+    #[test]
+    fn test_apply_suggestions_with_multibyte_chars() {
+        let src = "let あ = 0";
+        let (fixed, _) = apply_suggestions(src, &[suggestion((4..5).into(), "_あ")]);
+
+        assert_eq!(fixed, "let _あ = 0");
+    }
+
+    #[test]
+    fn test_overlapping_suggestions_are_skipped() {
+        let src = "abcdef";
+        let (fixed, applied) = apply_suggestions(
+            src,
+            &[
+                suggestion((1..4).into(), "X"),
+                suggestion((2..3).into(), "Y"),
+                suggestion((4..4).into(), "Z"),
+            ],
+        );
+
+        assert_eq!(fixed, "aXZef");
+        assert_eq!(applied, 2);
+    }
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use crate::{
     stelaro_common::FileLoader,
+    stelaro_diagnostics::emitter::DynEmitter,
     stelaro_session::config::{self, Input},
 };
 
@@ -13,5 +14,10 @@ pub struct Config {
     pub output_dir: Option<PathBuf>,
     pub output_file: Option<PathBuf>,
 
+    /// 入力ファイルの読み込みに使う `FileLoader`。
+    /// 省略した場合はディスクから読み込む (`RealFileLoader`)
     pub file_loader: Option<Box<dyn FileLoader + Send + Sync>>,
+
+    /// 診断の出力先。省略した場合は `opts.error_format` に従って標準エラー出力に出力する
+    pub emitter: Option<Box<DynEmitter>>,
 }
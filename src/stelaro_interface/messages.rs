@@ -0,0 +1,36 @@
+//! コンパイラの駆動で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    OUTPUT_FILE_WITH_MULTIPLE_EMIT {
+        ja: "`-o` は `--emit` に出力の種類を一つだけ指定した場合にのみ使用できます (`{kinds}` が指定されました)",
+        en: "`-o` can only be used when `--emit` specifies a single output kind (got `{kinds}`)",
+    }
+
+    EMIT_EXE_IN_EMBEDDED_COMPILE {
+        ja: "`compile` では実行ファイルを生成できません。`--emit=asm` を指定してアセンブリを取得してください",
+        en: "`compile` cannot produce an executable; request `--emit=asm` to obtain the assembly instead",
+    }
+
+    EMIT_EXE_FOR_LIB {
+        ja: "ライブラリのステロからは実行ファイルを生成できません",
+        en: "cannot produce an executable from a library stelo",
+    }
+
+    FIX_WRITE_FAILED {
+        ja: "修正をファイル '{path}' に書き込めませんでした: {error}",
+        en: "could not write fixes to '{path}': {error}",
+    }
+
+    FIXES_APPLIED {
+        ja: "{applied} 件の修正を '{path}' に適用しました",
+        en: "applied {applied} fix(es) to '{path}'",
+    }
+
+    UNKNOWN_ERROR_CODE {
+        ja: "`{code}` は登録されたエラーコードではありません",
+        en: "`{code}` is not a registered error code",
+    }
+}
@@ -1,6 +1,11 @@
+pub mod compile;
+pub mod emit;
+pub mod fix;
 pub mod interface;
+pub mod messages;
 pub mod passes;
 
+pub use compile::{CompileOutcome, compile};
 pub use interface::Config;
 
 use crate::stelaro_common::{RealFileLoader, SourceMapInputs, create_session_globals_then};
@@ -8,7 +13,7 @@ use crate::stelaro_session::Session;
 use crate::stelaro_session::session::{CompilerPaths, build_session};
 
 pub fn run_compiler<R>(config: Config, f: impl FnOnce(&Session) -> R) -> R {
-    let file_loader = Box::new(RealFileLoader);
+    let file_loader = config.file_loader.unwrap_or_else(|| Box::new(RealFileLoader));
 
     create_session_globals_then(Some(SourceMapInputs { file_loader }), || {
         let sess = build_session(
@@ -19,6 +24,7 @@ pub fn run_compiler<R>(config: Config, f: impl FnOnce(&Session) -> R) -> R {
                 output_file: config.output_file,
                 temps_dir: dirs::cache_dir(),
             },
+            config.emitter,
         );
 
         // `f` からの脱出パスは2つある。
@@ -26,6 +32,11 @@ pub fn run_compiler<R>(config: Config, f: impl FnOnce(&Session) -> R) -> R {
         // - パニック。例： `abort_if_errors` や致命的なエラーによって引き起こされる場合。
         let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&sess)));
 
+        // エラーによって中断された場合でも、それまでに得られた修正案を適用する
+        if sess.opts.fix {
+            fix::apply_machine_applicable_suggestions(&sess);
+        }
+
         // エラー診断が出力された場合、この関数の戻り値は `R` であり
         // `Result<R, E>` ではないため、直接エラーを返すことはできない。
         // しかし、エラーの存在を呼び出し元に伝える必要がある。さもないと、
@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+
 use crate::stelaro_ast::ast;
-use crate::stelaro_common::{Arena, StableSteloId, Symbol, TypedArena, sym};
+use crate::stelaro_common::{Arena, LocalDefId, Symbol, TypedArena, sym};
 use crate::stelaro_context::TyCtxt;
+use crate::stelaro_context::query::{self, Providers};
+use crate::stelaro_incremental::{self, IncrementalCache};
+use crate::stelaro_metadata::CStore;
+use crate::stelaro_mir::Body;
+use crate::stelaro_mir_transform::{self, OptimizationStats};
 use crate::stelaro_parse::{new_parser_from_file, new_parser_from_source_str};
+use crate::stelaro_resolve::{Resolver, ResolverArenas};
 use crate::stelaro_session::{Session, config::Input};
+use crate::stelaro_sir_typecheck::{self, result::TypeckResults};
+use crate::{stelaro_ast_lowering, stelaro_borrowck, stelaro_lint, stelaro_mir_build, stelaro_sir};
 
 pub fn parse(sess: &Session) -> ast::Stelo {
     let parser = match &sess.paths.input {
@@ -18,28 +28,157 @@ pub fn parse(sess: &Session) -> ast::Stelo {
     }
 }
 
+/// 名前解決とリントを行い、`sir_stelo` クエリで SIR に変換する。
+///
+/// エラーが報告された場合は、ここでコンパイルを中断する。
+pub fn lower_to_sir(tcx: TyCtxt<'_>, stelo: ast::Stelo) {
+    let sess = tcx.sess;
+    let arenas = &ResolverArenas::default();
+
+    let mut resolver = Resolver::new(
+        tcx,
+        stelo.span.inner_span,
+        arenas,
+    );
+
+    resolver.resolve_stelo(&stelo);
+
+    let resolver = resolver.into_outputs().ast_lowering;
+
+    // SIR への変換は AST を消費するため、AST のフィンガープリントは先に計算しておく
+    let ast_fingerprints = tcx.incremental.borrow().is_some().then(|| {
+        stelaro_incremental::ast_fingerprints(tcx, &stelo, &resolver.node_id_to_def_id)
+    });
+
+    if sess.dcx().has_errors().is_none() {
+        let lint_levels = stelaro_lint::check_ast_stelo(
+            sess.dcx(),
+            &sess.opts.lint_opts,
+            &stelo,
+            &resolver.res_map,
+        );
+        tcx.lint_levels.replace(
+            lint_levels.map_ids(|node_id| resolver.node_id_to_def_id.get(&node_id).copied()),
+        );
+    }
+
+    tcx.resolver_for_lowering.replace(Some((resolver, stelo)));
+    tcx.sir_stelo(());
+
+    sess.dcx().abort_if_errors();
+
+    if let Some(ast_fingerprints) = ast_fingerprints
+        && let Some(cache) = tcx.incremental.borrow_mut().as_mut()
+    {
+        cache.record_items(tcx, &ast_fingerprints);
+    }
+}
+
+/// すべての関数本体を `typeck` クエリで型検査する。
+///
+/// エラーが報告された場合は、ここでコンパイルを中断する。
+pub fn typeck<'tcx>(tcx: TyCtxt<'tcx>) -> HashMap<LocalDefId, &'tcx TypeckResults<'tcx>> {
+    let results = stelaro_sir_typecheck::check_stelo(tcx);
+    tcx.dcx().abort_if_errors();
+
+    if let Some(cache) = tcx.incremental.borrow_mut().as_mut() {
+        cache.record_typeck(tcx, &results);
+    }
+    results
+}
+
+/// MIR を構築し、借用検査と、常にパニックする演算のリントを行う。
+///
+/// エラーが報告された場合は、ここでコンパイルを中断する。
+pub fn analysis<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>,
+) -> Vec<Body<'tcx>> {
+    let bodies = stelaro_mir_build::build_mir(tcx, typeck_results);
+
+    for body in &bodies {
+        stelaro_borrowck::mir_borrowck(tcx, body);
+    }
+
+    // 定数の伝播は初期化の検査を通過した MIR を前提とするため、エラーがない場合のみ行う
+    if tcx.dcx().has_errors().is_none() {
+        for body in &bodies {
+            stelaro_mir_transform::check_known_panics(tcx, body);
+        }
+    }
+
+    tcx.dcx().abort_if_errors();
+    bodies
+}
+
+/// すべての関数本体に MIR の最適化を適用する。
+///
+/// `-Z statistics` が指定された場合は、最適化の統計を表示する。
+pub fn optimize(tcx: TyCtxt<'_>, bodies: &mut [Body<'_>]) {
+    let mut stats = OptimizationStats::default();
+
+    for body in bodies {
+        stats += stelaro_mir_transform::run_optimization_passes(body);
+    }
+
+    if tcx.sess.opts.unstable_opts.statistics {
+        eprint!("{}", stats.to_report(tcx.dcx().lang()));
+    }
+}
+
 pub fn create_and_enter_global_ctxt<T, F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> T>(
     sess: &Session,
     f: F,
 ) -> T {
     let stelo_name = get_stelo_name(sess);
 
+    let cstore = CStore::load(sess, stelo_name);
+
     let arena = Arena::new();
     let sir_arena = Arena::new();
     let types_arena = TypedArena::new();
-    let stable_stelo_id = StableSteloId::new(stelo_name);
 
     let gcx = TyCtxt::create_global_ctxt(
         sess,
-        stable_stelo_id,
+        stelo_name,
+        cstore,
         &arena,
         &sir_arena,
         &types_arena,
+        default_providers(),
     );
 
+    gcx.incremental.replace(IncrementalCache::load(sess, stelo_name));
+
     let tcx = TyCtxt::new(&gcx);
 
-    f(tcx)
+    let result = f(tcx);
+    save_incremental(tcx);
+    result
+}
+
+/// コンパイラの各モジュールが定義するクエリの関数を登録する。
+pub fn default_providers() -> Providers {
+    let mut providers = Providers::default();
+    query::provide(&mut providers);
+    stelaro_ast_lowering::provide(&mut providers);
+    stelaro_sir::provide(&mut providers);
+    stelaro_sir_typecheck::provide(&mut providers);
+    providers
+}
+
+/// インクリメンタルコンパイルのキャッシュを保存する。
+///
+/// `-Z incremental-info` が指定された場合は、前回の結果を再利用した数を表示する。
+fn save_incremental(tcx: TyCtxt<'_>) {
+    let Some(cache) = tcx.incremental.take() else {
+        return;
+    };
+
+    let stats = cache.save(tcx);
+    if tcx.sess.opts.unstable_opts.incremental_info {
+        eprint!("{}", stats.to_report(tcx.dcx().lang()));
+    }
 }
 
 pub fn get_stelo_name(sess: &Session) -> Symbol {
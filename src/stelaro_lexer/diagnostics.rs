@@ -1,6 +1,8 @@
 use crate::stelaro_common::Span;
 use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
 
+use super::messages;
+
 pub struct DiagsLexer;
 
 impl<'dcx> DiagsLexer {
@@ -11,8 +13,11 @@ impl<'dcx> DiagsLexer {
     ) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedCharacter.into());
-        diag.set_message("予期しない文字".to_string());
-        diag.set_label(span, format!("不正な文字`{}`が入力されました", unexpected));
+        diag.set_message(dcx.translate(messages::UNEXPECTED_CHARACTER, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(messages::UNEXPECTED_CHARACTER_LABEL, &[("ch", &unexpected)]),
+        );
 
         diag
     }
@@ -20,8 +25,8 @@ impl<'dcx> DiagsLexer {
     pub fn invalid_float_format(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::InvalidFloatFormat.into());
-        diag.set_message("無効な浮動小数点数の表記".to_string());
-        diag.set_label(span, "二個目の`.`が見つかりました".to_string());
+        diag.set_message(dcx.translate(messages::INVALID_FLOAT_FORMAT, &[]));
+        diag.set_label(span, dcx.translate(messages::INVALID_FLOAT_FORMAT_LABEL, &[]));
 
         diag
     }
@@ -29,8 +34,8 @@ impl<'dcx> DiagsLexer {
     pub fn missing_fractional_part(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::MissingFractionalPart.into());
-        diag.set_message("小数部の欠落".to_string());
-        diag.set_label(span, "小数部が必要です".to_string());
+        diag.set_message(dcx.translate(messages::MISSING_FRACTIONAL_PART, &[]));
+        diag.set_label(span, dcx.translate(messages::MISSING_FRACTIONAL_PART_LABEL, &[]));
 
         diag
     }
@@ -42,10 +47,10 @@ impl<'dcx> DiagsLexer {
     ) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::InvalidEscapeSequence.into());
-        diag.set_message("無効なエスケープシーケンス".to_string());
+        diag.set_message(dcx.translate(messages::INVALID_ESCAPE_SEQUENCE, &[]));
         diag.set_label(
             span,
-            format!("`{}`は無効なエスケープシーケンス文字です", invalid_ch),
+            dcx.translate(messages::INVALID_ESCAPE_SEQUENCE_LABEL, &[("ch", &invalid_ch)]),
         );
 
         diag
@@ -54,8 +59,8 @@ impl<'dcx> DiagsLexer {
     pub fn unterminated_string_literal(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnterminatedStringLiteral.into());
-        diag.set_message("閉じられていない文字列リテラル".to_string());
-        diag.set_label(span, "文字列が閉じられていません".to_string());
+        diag.set_message(dcx.translate(messages::UNTERMINATED_STRING_LITERAL, &[]));
+        diag.set_label(span, dcx.translate(messages::UNTERMINATED_STRING_LITERAL_LABEL, &[]));
 
         diag
     }
@@ -64,8 +69,8 @@ impl<'dcx> DiagsLexer {
     pub fn unexpected_quote(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedQuote.into());
-        diag.set_message("予期しない`'`".to_string());
-        diag.set_label(span, "不正な`'`が見つかりました".to_string());
+        diag.set_message(dcx.translate(messages::UNEXPECTED_QUOTE, &[]));
+        diag.set_label(span, dcx.translate(messages::UNEXPECTED_QUOTE_LABEL, &[]));
 
         diag
     }
@@ -73,8 +78,8 @@ impl<'dcx> DiagsLexer {
     pub fn unterminated_char_literal(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnterminatedCharLiteral.into());
-        diag.set_message("閉じられていない文字リテラル".to_string());
-        diag.set_label(span, "文字リテラルが閉じられていません".to_string());
+        diag.set_message(dcx.translate(messages::UNTERMINATED_CHAR_LITERAL, &[]));
+        diag.set_label(span, dcx.translate(messages::UNTERMINATED_CHAR_LITERAL_LABEL, &[]));
 
         diag
     }
@@ -85,9 +90,12 @@ impl<'dcx> DiagsLexer {
     ) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::MultipleCharactersInCharLiteral.into());
-        diag.set_message("無効な複数文字の文字リテラル".to_string());
-        diag.set_label(span, "文字リテラルに複数の文字が含まれています".to_string());
-        diag.set_help("一文字にするか、文字列に変更してください".to_string());
+        diag.set_message(dcx.translate(messages::MULTIPLE_CHARACTERS_IN_CHAR_LITERAL, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(messages::MULTIPLE_CHARACTERS_IN_CHAR_LITERAL_LABEL, &[]),
+        );
+        diag.set_help(dcx.translate(messages::MULTIPLE_CHARACTERS_IN_CHAR_LITERAL_HELP, &[]));
 
         diag
     }
@@ -141,7 +149,7 @@ mod tests {
 
     #[test]
     fn test_unexpected_character() {
-        let (sess, is_err) = get_sess_after_src_lex("let # = 0;");
+        let (sess, is_err) = get_sess_after_src_lex("let $ = 0;");
 
         assert!(is_err);
         assert!(
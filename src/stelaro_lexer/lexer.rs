@@ -9,7 +9,12 @@ use super::diagnostics::DiagsLexer;
 pub struct Lexer<'src, 'sess> {
     src: &'src str,
     cursor: Cursor<'src>,
+    /// 文字単位の位置 (`Span` の単位)
     pos: usize,
+    /// `pos` に対応するバイト単位の位置
+    byte_pos: usize,
+    /// 読んでいるトークンの先頭のバイト単位の位置
+    token_start_byte: usize,
     psess: &'sess ParseSess,
 }
 
@@ -19,6 +24,8 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
             src,
             cursor: Cursor::new(src),
             pos: 0,
+            byte_pos: 0,
+            token_start_byte: 0,
             psess,
         }
     }
@@ -49,6 +56,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
 
         // 読み始めるトークンの最初の位置を保持する
         let pos = self.pos;
+        self.token_start_byte = self.byte_pos;
 
         let token_kind = match self.first() {
             '(' => {
@@ -67,6 +75,18 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
                 self.bump();
                 TokenKind::RBrace
             }
+            '[' => {
+                self.bump();
+                TokenKind::LBracket
+            }
+            ']' => {
+                self.bump();
+                TokenKind::RBracket
+            }
+            '#' => {
+                self.bump();
+                TokenKind::Pound
+            }
             ',' => {
                 self.bump();
                 TokenKind::Comma
@@ -97,6 +117,10 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
                 self.bump();
                 TokenKind::Percent
             }
+            '|' => {
+                self.bump();
+                TokenKind::Pipe
+            }
             ';' => {
                 self.bump();
                 TokenKind::Semicolon
@@ -155,7 +179,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
                 let lit_kind = self.lex_number(pos)?;
                 TokenKind::Literal(Lit {
                     kind: lit_kind,
-                    symbol: Symbol::intern(&self.src[pos..self.pos]),
+                    symbol: Symbol::intern(self.token_str()),
                 })
             }
             '"' => {
@@ -166,7 +190,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
 
                 TokenKind::Literal(Lit {
                     kind: LitKind::Str,
-                    symbol: Symbol::intern(&self.src[pos..self.pos]),
+                    symbol: Symbol::intern(self.token_str()),
                 })
             }
             '\'' => {
@@ -180,7 +204,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
             c if c.is_alphabetic() || c == '_' => {
                 self.bump();
                 // キーワード、Identifier、boolean値を解析する
-                self.lex_word(pos)?
+                self.lex_word()?
             }
             EOF_CHAR => TokenKind::Eof,
             c => {
@@ -213,7 +237,14 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
 
     fn bump(&mut self) -> Option<char> {
         self.pos += 1;
-        self.cursor.bump()
+        let c = self.cursor.bump()?;
+        self.byte_pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// 読んでいるトークンの先頭から現在の位置までのソースコード
+    fn token_str(&self) -> &'src str {
+        &self.src[self.token_start_byte..self.byte_pos]
     }
 
     fn skip_whitespace_and_comment(&mut self) {
@@ -332,7 +363,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
         let symbol = match self.first() {
             '\\' => {
                 self.lex_escape_sequence()?;
-                Symbol::intern(&self.src[pos..self.pos])
+                Symbol::intern(&self.token_str()[1..])
             }
             '\n' => {
                 self.bump();
@@ -344,7 +375,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
             }
             _ => {
                 self.bump();
-                Symbol::intern(&self.src[pos..self.pos])
+                Symbol::intern(&self.token_str()[1..])
             }
         };
 
@@ -388,13 +419,13 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
         Ok(symbol)
     }
 
-    fn lex_word(&mut self, pos: usize) -> Result<TokenKind, ErrorEmitted> {
+    fn lex_word(&mut self) -> Result<TokenKind, ErrorEmitted> {
         // アンダースコア、数字がここに来ることはない
         while matches!(self.first(), c if c.is_alphabetic() || c == '_' || c.is_numeric()) {
             self.bump();
         }
 
-        let keyword_or_ident = &self.src[pos..self.pos];
+        let keyword_or_ident = self.token_str();
 
         Ok(match self.as_keyword(keyword_or_ident) {
             Some(keyword) => keyword,
@@ -402,12 +433,12 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
                 if keyword_or_ident == "true" {
                     TokenKind::Literal(Lit {
                         kind: LitKind::Bool(true),
-                        symbol: Symbol::intern(&self.src[pos..self.pos]),
+                        symbol: Symbol::intern(self.token_str()),
                     })
                 } else if keyword_or_ident == "false" {
                     TokenKind::Literal(Lit {
                         kind: LitKind::Bool(false),
-                        symbol: Symbol::intern(&self.src[pos..self.pos]),
+                        symbol: Symbol::intern(self.token_str()),
                     })
                 } else {
                     TokenKind::Ident(Symbol::intern(keyword_or_ident))
@@ -420,6 +451,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
         match string {
             "fn" => Some(TokenKind::Fn),
             "mod" => Some(TokenKind::Mod),
+            "type" => Some(TokenKind::Type),
             "break" => Some(TokenKind::Break),
             "continue" => Some(TokenKind::Continue),
             "return" => Some(TokenKind::Return),
@@ -431,6 +463,7 @@ impl<'src, 'sess> Lexer<'src, 'sess> {
             "for" => Some(TokenKind::For),
             "loop" => Some(TokenKind::Loop),
             "while" => Some(TokenKind::While),
+            "move" => Some(TokenKind::Move),
             _ => None,
         }
     }
@@ -0,0 +1,82 @@
+//! 字句解析で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    UNEXPECTED_CHARACTER {
+        ja: "予期しない文字",
+        en: "unexpected character",
+    }
+    UNEXPECTED_CHARACTER_LABEL {
+        ja: "不正な文字`{ch}`が入力されました",
+        en: "invalid character `{ch}` found",
+    }
+
+    INVALID_FLOAT_FORMAT {
+        ja: "無効な浮動小数点数の表記",
+        en: "invalid floating-point literal",
+    }
+    INVALID_FLOAT_FORMAT_LABEL {
+        ja: "二個目の`.`が見つかりました",
+        en: "found a second `.`",
+    }
+
+    MISSING_FRACTIONAL_PART {
+        ja: "小数部の欠落",
+        en: "missing fractional part",
+    }
+    MISSING_FRACTIONAL_PART_LABEL {
+        ja: "小数部が必要です",
+        en: "a fractional part is required",
+    }
+
+    INVALID_ESCAPE_SEQUENCE {
+        ja: "無効なエスケープシーケンス",
+        en: "invalid escape sequence",
+    }
+    INVALID_ESCAPE_SEQUENCE_LABEL {
+        ja: "`{ch}`は無効なエスケープシーケンス文字です",
+        en: "`{ch}` is not a valid escape character",
+    }
+
+    UNTERMINATED_STRING_LITERAL {
+        ja: "閉じられていない文字列リテラル",
+        en: "unterminated string literal",
+    }
+    UNTERMINATED_STRING_LITERAL_LABEL {
+        ja: "文字列が閉じられていません",
+        en: "the string is not closed",
+    }
+
+    UNEXPECTED_QUOTE {
+        ja: "予期しない`'`",
+        en: "unexpected `'`",
+    }
+    UNEXPECTED_QUOTE_LABEL {
+        ja: "不正な`'`が見つかりました",
+        en: "found an invalid `'`",
+    }
+
+    UNTERMINATED_CHAR_LITERAL {
+        ja: "閉じられていない文字リテラル",
+        en: "unterminated character literal",
+    }
+    UNTERMINATED_CHAR_LITERAL_LABEL {
+        ja: "文字リテラルが閉じられていません",
+        en: "the character literal is not closed",
+    }
+
+    MULTIPLE_CHARACTERS_IN_CHAR_LITERAL {
+        ja: "無効な複数文字の文字リテラル",
+        en: "character literal may only contain one character",
+    }
+    MULTIPLE_CHARACTERS_IN_CHAR_LITERAL_LABEL {
+        ja: "文字リテラルに複数の文字が含まれています",
+        en: "the character literal contains more than one character",
+    }
+    MULTIPLE_CHARACTERS_IN_CHAR_LITERAL_HELP {
+        ja: "一文字にするか、文字列に変更してください",
+        en: "use a single character, or change it to a string literal",
+    }
+}
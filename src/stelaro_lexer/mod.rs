@@ -1,5 +1,6 @@
 mod cursor;
 mod diagnostics;
 pub mod lexer;
+pub mod messages;
 
 pub use lexer::Lexer;
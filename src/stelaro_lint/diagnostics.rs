@@ -0,0 +1,308 @@
+use crate::stelaro_common::{Ident, Span};
+use crate::stelaro_diagnostics::{Applicability, Diag, DiagCtxtHandle, DiagMessage};
+use crate::stelaro_session::lint::{
+    LintLevel,
+    builtin::{NON_SNAKE_CASE, UNKNOWN_LINTS, UNUSED_VARIABLES},
+};
+
+use super::levels::{LevelAndSource, struct_lint};
+use super::messages;
+
+pub struct DiagsLint;
+
+impl<'dcx> DiagsLint {
+    pub fn unknown_attribute(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: Ident,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(name.span);
+        diag.set_code(ErrorCode::UnknownAttribute.into());
+        diag.set_message(dcx.translate(messages::UNKNOWN_ATTRIBUTE, &[("name", &name)]));
+        diag.set_label(name.span, dcx.translate(messages::UNKNOWN_ATTRIBUTE_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::UNKNOWN_ATTRIBUTE_HELP, &[]));
+
+        diag
+    }
+
+    pub fn malformed_lint_attribute(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LintLevel,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::MalformedLintAttribute.into());
+        diag.set_message(dcx.translate(messages::MALFORMED_LINT_ATTRIBUTE, &[("level", &level)]));
+        diag.set_label(span, dcx.translate(messages::MALFORMED_LINT_ATTRIBUTE_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::MALFORMED_LINT_ATTRIBUTE_HELP, &[("level", &level)]));
+
+        diag
+    }
+
+    /// `flag_level` はコマンドライン引数で指定された場合、そのレベルを表す
+    pub fn unknown_lint(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LevelAndSource,
+        name: &str,
+        flag_level: Option<LintLevel>,
+        span: Span,
+    ) -> Option<Diag<'dcx, ()>> {
+        let mut diag = struct_lint(dcx, UNKNOWN_LINTS, level, span)?;
+
+        match flag_level {
+            Some(flag_level) => {
+                diag.set_message(dcx.translate(
+                    messages::UNKNOWN_LINT_FLAG,
+                    &[("flag", &flag_level.as_flag()), ("name", &name)],
+                ));
+            }
+            None => {
+                diag.set_message(dcx.translate(messages::UNKNOWN_LINT, &[("name", &name)]));
+                diag.set_label(span, dcx.translate(messages::UNKNOWN_LINT_LABEL, &[]));
+            }
+        }
+
+        Some(diag)
+    }
+
+    /// `descr` は名前の種類 (e.g. [`messages::DESCR_FN`]) を表す
+    pub fn non_snake_case(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LevelAndSource,
+        descr: &DiagMessage,
+        ident: Ident,
+        snake_case: &str,
+    ) -> Option<Diag<'dcx, ()>> {
+        let mut diag = struct_lint(dcx, NON_SNAKE_CASE, level, ident.span)?;
+        let descr = dcx.translate(descr, &[]);
+        diag.set_message(dcx.translate(
+            messages::NON_SNAKE_CASE,
+            &[("descr", &descr), ("name", &ident)],
+        ));
+        diag.set_label(ident.span, dcx.translate(messages::NON_SNAKE_CASE_LABEL, &[]));
+        // 名前を参照している箇所は変更されないため、自動的に適用することはできない
+        diag.set_suggestion(
+            ident.span,
+            dcx.translate(messages::NON_SNAKE_CASE_SUGGESTION, &[]),
+            snake_case.to_string(),
+            Applicability::MaybeIncorrect,
+        );
+
+        Some(diag)
+    }
+
+    pub fn unused_variable(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LevelAndSource,
+        ident: Ident,
+    ) -> Option<Diag<'dcx, ()>> {
+        let mut diag = struct_lint(dcx, UNUSED_VARIABLES, level, ident.span)?;
+        diag.set_message(dcx.translate(messages::UNUSED_VARIABLE, &[("name", &ident)]));
+        diag.set_label(ident.span, dcx.translate(messages::UNUSED_VARIABLE_LABEL, &[]));
+        diag.set_suggestion(
+            ident.span,
+            dcx.translate(messages::UNUSED_VARIABLE_SUGGESTION, &[]),
+            format!("_{}", ident),
+            Applicability::MachineApplicable,
+        );
+
+        Some(diag)
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    UnknownAttribute = 500,
+    MalformedLintAttribute = 501,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use crate::stelaro_common::create_default_session_globals_then;
+    use crate::stelaro_common::source_map::SourceMap;
+    use crate::stelaro_diagnostics::{CollectingEmitter, DiagCtxt, DiagCtxtFlags, Lang};
+    use crate::stelaro_diagnostics::emitter::SilentEmitter;
+    use crate::stelaro_lint::{check_ast_stelo, diagnostics::ErrorCode};
+    use crate::stelaro_parse::new_parser_from_source_str;
+    use crate::stelaro_session::{ParseSess, lint::LintLevel};
+
+    fn create_test_context(flags: DiagCtxtFlags) -> ParseSess {
+        let source_map = Rc::new(SourceMap::new());
+        let emitter = SilentEmitter::new();
+        let dcx = DiagCtxt::new(Box::new(emitter)).with_flags(flags);
+        ParseSess::with_dcx(dcx, source_map)
+    }
+
+    /// 名前解決の結果を与えずにリントを実行するため、全ての変数は未使用として扱われる
+    fn get_sess_after_lint(
+        src: &str,
+        lint_opts: &[(String, LintLevel)],
+        flags: DiagCtxtFlags,
+    ) -> ParseSess {
+        create_default_session_globals_then(|| {
+            let psess = create_test_context(flags);
+            let stelo = new_parser_from_source_str(&psess, "lint_test".into(), src.to_string())
+                .unwrap()
+                .parse_stelo()
+                .unwrap();
+            check_ast_stelo(psess.dcx(), lint_opts, &stelo, &HashMap::new());
+            psess
+        })
+    }
+
+    #[test]
+    fn test_unknown_attribute() {
+        let sess = get_sess_after_lint(
+            "#[inline] fn f() {}",
+            &[],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_err_code(ErrorCode::UnknownAttribute.into()));
+    }
+
+    #[test]
+    fn test_malformed_lint_attribute() {
+        let sess = get_sess_after_lint(
+            "#[allow] fn f() {}",
+            &[],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_err_code(ErrorCode::MalformedLintAttribute.into()));
+    }
+
+    #[test]
+    fn test_lint_is_warning_by_default() {
+        let sess = get_sess_after_lint(
+            "fn FooBar() { let x = 1; }",
+            &[],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_deny_lint_by_attribute() {
+        let sess = get_sess_after_lint(
+            r#"
+    mod m {
+        #[deny(non_snake_case)]
+        fn FooBar() {}
+    }
+"#.trim(),
+            &[],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_errors().is_some());
+    }
+
+    #[test]
+    fn test_allow_overrides_command_line_deny() {
+        let sess = get_sess_after_lint(
+            r#"
+    #![allow(unused_variables)]
+    fn f() {
+        let x = 1;
+    }
+"#.trim(),
+            &[("unused-variables".to_string(), LintLevel::Deny)],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_later_command_line_flag_wins() {
+        let src = "fn f() { let x = 1; }";
+        let deny_then_allow = [
+            ("unused-variables".to_string(), LintLevel::Deny),
+            ("unused-variables".to_string(), LintLevel::Allow),
+        ];
+        let sess = get_sess_after_lint(src, &deny_then_allow, DiagCtxtFlags::default());
+        assert!(sess.dcx().has_errors().is_none());
+
+        let allow_then_deny = [
+            ("unused-variables".to_string(), LintLevel::Allow),
+            ("unused-variables".to_string(), LintLevel::Deny),
+        ];
+        let sess = get_sess_after_lint(src, &allow_then_deny, DiagCtxtFlags::default());
+        assert!(sess.dcx().has_errors().is_some());
+    }
+
+    #[test]
+    fn test_deny_warnings_by_attribute() {
+        let sess = get_sess_after_lint(
+            r#"
+    #[deny(warnings)]
+    fn f() {
+        let x = 1;
+    }
+"#.trim(),
+            &[],
+            DiagCtxtFlags::default(),
+        );
+
+        assert!(sess.dcx().has_errors().is_some());
+    }
+
+    #[test]
+    fn test_deny_warnings_by_flags() {
+        let sess = get_sess_after_lint(
+            "fn f() { let x = 1; }",
+            &[],
+            DiagCtxtFlags {
+                can_emit_warnings: true,
+                warnings_as_errors: true,
+            },
+        );
+
+        assert!(sess.dcx().has_errors().is_some());
+    }
+
+    #[test]
+    fn test_underscore_prefixed_variable_is_not_linted() {
+        let sess = get_sess_after_lint(
+            "fn f(_x: i32) { let _y = 1; }",
+            &[],
+            DiagCtxtFlags {
+                can_emit_warnings: true,
+                warnings_as_errors: true,
+            },
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_lint_message_follows_lang() {
+        let emitter = CollectingEmitter::new();
+        create_default_session_globals_then(|| {
+            let dcx = DiagCtxt::new(Box::new(emitter.clone()))
+                .with_flags(DiagCtxtFlags { can_emit_warnings: true, warnings_as_errors: true })
+                .with_lang(Lang::En);
+            let psess = ParseSess::with_dcx(dcx, Rc::new(SourceMap::new()));
+            let src = "fn f() { let x = 1; }".to_string();
+            let stelo = new_parser_from_source_str(&psess, "lint_test".into(), src)
+                .unwrap()
+                .parse_stelo()
+                .unwrap();
+            check_ast_stelo(psess.dcx(), &[], &stelo, &HashMap::new());
+        });
+
+        let diags = emitter.take_diagnostics();
+        assert_eq!(diags[0].msg, ["unused variable: `x`"]);
+        assert!(diags[0].help.iter().any(|help| help == "`-D warnings` turns warnings into errors"));
+    }
+}
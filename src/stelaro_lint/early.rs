@@ -0,0 +1,151 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::stelaro_ast::{
+    NodeId,
+    ast::*,
+    visit::{self, Visitor},
+};
+use crate::stelaro_common::Ident;
+use crate::stelaro_diagnostics::{DiagCtxtHandle, DiagMessage};
+use crate::stelaro_session::lint::builtin::{NON_SNAKE_CASE, UNUSED_VARIABLES};
+use crate::stelaro_sir::def::Res;
+
+use super::{
+    diagnostics::DiagsLint,
+    levels::{LintLevelMap, LintLevelsBuilder},
+    messages,
+};
+
+
+/// 名前解決後の AST を走査し、リントを報告する
+pub struct EarlyLintVisitor<'dcx> {
+    dcx: DiagCtxtHandle<'dcx>,
+    builder: LintLevelsBuilder<'dcx>,
+
+    /// パス式によって参照されたローカル変数 (パターンの `NodeId`)
+    used_locals: HashSet<NodeId>,
+
+    /// ステロのルートと関数ごとに記録した、MIR に対するリントのレベル
+    lint_levels: LintLevelMap<NodeId>,
+}
+
+impl<'dcx> EarlyLintVisitor<'dcx> {
+    pub fn new(
+        dcx: DiagCtxtHandle<'dcx>,
+        builder: LintLevelsBuilder<'dcx>,
+        res_map: &HashMap<NodeId, Res<NodeId>>,
+    ) -> Self {
+        // パターン自身もローカル変数として解決されるため、それを除外する
+        let used_locals = res_map
+            .iter()
+            .filter_map(|(&node_id, res)| match *res {
+                Res::Local(binding) if binding != node_id => Some(binding),
+                _ => None,
+            })
+            .collect();
+
+        EarlyLintVisitor {
+            dcx,
+            builder,
+            used_locals,
+            lint_levels: LintLevelMap::default(),
+        }
+    }
+
+    pub fn into_lint_levels(self) -> LintLevelMap<NodeId> {
+        self.lint_levels
+    }
+
+    fn check_snake_case(&self, descr: &DiagMessage, ident: Ident) {
+        let name = ident.name.as_str();
+
+        if !name.chars().any(char::is_uppercase) {
+            return;
+        }
+
+        if let Some(diag) = DiagsLint::non_snake_case(
+            self.dcx,
+            self.builder.lint_level(NON_SNAKE_CASE),
+            descr,
+            ident,
+            &to_snake_case(name),
+        ) {
+            diag.emit();
+        }
+    }
+
+    fn check_unused_variable(&self, pat: &Pat, ident: Ident) {
+        if ident.name.as_str().starts_with('_')
+            || self.used_locals.contains(&pat.id)
+        {
+            return;
+        }
+
+        if let Some(diag) = DiagsLint::unused_variable(
+            self.dcx,
+            self.builder.lint_level(UNUSED_VARIABLES),
+            ident,
+        ) {
+            diag.emit();
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for EarlyLintVisitor<'_> {
+    fn visit_stelo(&mut self, stelo: &'ast Stelo) {
+        self.builder.push(&stelo.attrs);
+        self.lint_levels.record(stelo.id, &self.builder);
+        visit::walk_stelo(self, stelo);
+        self.builder.pop();
+    }
+
+    fn visit_item(&mut self, item: &'ast Item) {
+        self.builder.push(&item.attrs);
+
+        // 型エイリアスの名前は `Meters` のようなキャメルケースで書くため、検査しない
+        let descr = match item.kind {
+            ItemKind::Fn(..) => Some(messages::DESCR_FN),
+            ItemKind::Mod(..) => Some(messages::DESCR_MOD),
+            ItemKind::TyAlias(..) => None,
+        };
+        if let Some(descr) = descr {
+            self.check_snake_case(descr, item.ident);
+        }
+        if let ItemKind::Fn(..) = item.kind {
+            self.lint_levels.record(item.id, &self.builder);
+        }
+
+        visit::walk_item(self, item);
+        self.builder.pop();
+    }
+
+    fn visit_pat(&mut self, pat: &'ast Pat) {
+        if let PatKind::Ident(ident) = pat.kind {
+            self.check_snake_case(messages::DESCR_VARIABLE, ident);
+            self.check_unused_variable(pat, ident);
+        }
+
+        visit::walk_pat(self, pat);
+    }
+}
+
+/// `FooBar`, `fooBar` を `foo_bar` に変換する
+fn to_snake_case(name: &str) -> String {
+    let mut snake_case = String::with_capacity(name.len() + 4);
+    let mut prev_is_lower_or_digit = false;
+
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if prev_is_lower_or_digit {
+                snake_case.push('_');
+            }
+            snake_case.extend(c.to_lowercase());
+            prev_is_lower_or_digit = false;
+        } else {
+            snake_case.push(c);
+            prev_is_lower_or_digit = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+
+    snake_case
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::stelaro_ast::ast::Attribute;
+use crate::stelaro_common::{DUMMY_SPAN, LocalDefId, Span};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle, diag::Level};
+use crate::stelaro_session::lint::{
+    Lint, LintLevel,
+    builtin::{MIR_LINTS, UNKNOWN_LINTS, WARNINGS},
+    find_lint,
+};
+
+use super::diagnostics::DiagsLint;
+use super::messages;
+
+
+/// リントレベルがどこで指定されたか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevelSource {
+    /// リントのデフォルトのレベル
+    Default,
+
+    /// コマンドライン引数による指定
+    CommandLine,
+
+    /// `#[allow(...)]` などの属性による指定。
+    /// `warnings` による指定の場合、`via_warnings` が `true` になる
+    Attribute { span: Span, via_warnings: bool },
+}
+
+pub type LevelAndSource = (LintLevel, LintLevelSource);
+
+/// 属性のスコープごとに、リントのレベルを決定する
+pub struct LintLevelsBuilder<'dcx> {
+    dcx: DiagCtxtHandle<'dcx>,
+
+    /// コマンドライン引数で指定されたリントのレベル
+    cmdline: HashMap<&'static str, LintLevel>,
+
+    /// 外側のアイテムから順に積まれた、属性によるリントのレベル
+    stack: Vec<Vec<(&'static Lint, LintLevel, Span)>>,
+}
+
+impl<'dcx> LintLevelsBuilder<'dcx> {
+    pub fn new(dcx: DiagCtxtHandle<'dcx>, lint_opts: &[(String, LintLevel)]) -> Self {
+        let mut builder = LintLevelsBuilder {
+            dcx,
+            cmdline: HashMap::new(),
+            stack: Vec::new(),
+        };
+
+        for (name, level) in lint_opts {
+            match find_lint(name) {
+                Some(lint) => {
+                    builder.cmdline.insert(lint.name, *level);
+                }
+                None => {
+                    if let Some(diag) = DiagsLint::unknown_lint(
+                        dcx,
+                        builder.lint_level(UNKNOWN_LINTS),
+                        name,
+                        Some(*level),
+                        DUMMY_SPAN,
+                    ) {
+                        diag.emit();
+                    }
+                }
+            }
+        }
+
+        builder
+    }
+
+    /// アイテムに付与された属性を読み、新しいスコープを作る
+    pub fn push(&mut self, attrs: &[Attribute]) {
+        let mut specs = vec![];
+
+        for attr in attrs {
+            let Some(level) = LintLevel::from_attr_name(attr.name.name.as_str()) else {
+                DiagsLint::unknown_attribute(self.dcx, attr.name).emit();
+                continue;
+            };
+
+            if attr.args.is_empty() {
+                DiagsLint::malformed_lint_attribute(self.dcx, level, attr.span).emit();
+                continue;
+            }
+
+            for arg in &attr.args {
+                match find_lint(arg.name.as_str()) {
+                    Some(lint) => specs.push((lint, level, attr.span)),
+                    None => {
+                        if let Some(diag) = DiagsLint::unknown_lint(
+                            self.dcx,
+                            self.lint_level(UNKNOWN_LINTS),
+                            arg.name.as_str(),
+                            None,
+                            arg.span,
+                        ) {
+                            diag.emit();
+                        }
+                    }
+                }
+            }
+        }
+
+        self.stack.push(specs);
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// 最も内側の属性による指定を探す
+    fn attr_level(&self, lint: &'static Lint) -> Option<(LintLevel, Span)> {
+        self.stack
+            .iter()
+            .rev()
+            .flat_map(|specs| specs.iter().rev())
+            .find(|(l, ..)| *l == lint)
+            .map(|&(_, level, span)| (level, span))
+    }
+
+    /// 現在のスコープにおける、リントのレベルを決定する。
+    ///
+    /// 属性による指定、コマンドライン引数による指定、デフォルトのレベルの順に優先される。
+    /// 警告となるリントは、属性 `#[deny(warnings)]` などによってさらにレベルが変更される。
+    pub fn lint_level(&self, lint: &'static Lint) -> LevelAndSource {
+        let (level, src) = if let Some((level, span)) = self.attr_level(lint) {
+            (level, LintLevelSource::Attribute { span, via_warnings: false })
+        } else if let Some(&level) = self.cmdline.get(lint.name) {
+            (level, LintLevelSource::CommandLine)
+        } else {
+            (lint.default_level, LintLevelSource::Default)
+        };
+
+        // `-D warnings`, `-A warnings` は DiagCtxt によって処理されるため、
+        // ここでは属性による指定のみを考慮する
+        if level == LintLevel::Warn
+            && lint != WARNINGS
+            && let Some((level, span)) = self.attr_level(WARNINGS)
+        {
+            return (level, LintLevelSource::Attribute { span, via_warnings: true });
+        }
+
+        (level, src)
+    }
+}
+
+/// アイテムごとに記録した、MIR に対するリント ([`MIR_LINTS`]) のレベル。
+///
+/// AST に対するリントの実行時に `NodeId` をキーとして記録し、
+/// 名前解決の結果を用いて `LocalDefId` をキーとするマップに変換する。
+#[derive(Debug)]
+pub struct LintLevelMap<Id> {
+    levels: HashMap<Id, HashMap<&'static str, LevelAndSource>>,
+}
+
+impl<Id> Default for LintLevelMap<Id> {
+    fn default() -> Self {
+        LintLevelMap { levels: HashMap::new() }
+    }
+}
+
+impl<Id: Eq + Hash> LintLevelMap<Id> {
+    /// `builder` の現在のスコープにおけるレベルを、`id` のレベルとして記録する
+    pub fn record(&mut self, id: Id, builder: &LintLevelsBuilder<'_>) {
+        let levels = MIR_LINTS
+            .iter()
+            .map(|&lint| (lint.name, builder.lint_level(lint)))
+            .collect();
+        self.levels.insert(id, levels);
+    }
+
+    pub fn map_ids<U: Eq + Hash>(self, mut f: impl FnMut(Id) -> Option<U>) -> LintLevelMap<U> {
+        let levels = self
+            .levels
+            .into_iter()
+            .filter_map(|(id, levels)| Some((f(id)?, levels)))
+            .collect();
+        LintLevelMap { levels }
+    }
+}
+
+impl LintLevelMap<LocalDefId> {
+    /// `def_id` におけるリントのレベル。
+    ///
+    /// クロージャなど、レベルが記録されていない定義では、記録のある親の定義のレベルを用いる。
+    pub fn get(&self, tcx: TyCtxt<'_>, lint: &'static Lint, def_id: LocalDefId) -> LevelAndSource {
+        let mut current = Some(def_id);
+        while let Some(def_id) = current {
+            if let Some(&level) = self.levels.get(&def_id).and_then(|levels| levels.get(lint.name)) {
+                return level;
+            }
+            current = tcx.opt_local_parent(def_id);
+        }
+
+        (lint.default_level, LintLevelSource::Default)
+    }
+}
+
+/// レベルに応じてリントの診断を構築する。`allow` の場合は何も報告しない。
+pub fn struct_lint<'dcx>(
+    dcx: DiagCtxtHandle<'dcx>,
+    lint: &'static Lint,
+    (level, src): LevelAndSource,
+    span: Span,
+) -> Option<Diag<'dcx, ()>> {
+    let diag_level = match level {
+        LintLevel::Allow => return None,
+        LintLevel::Warn => Level::Warning,
+        LintLevel::Deny => Level::Error,
+    };
+
+    let mut diag = Diag::new(dcx, span, diag_level);
+    let name = lint.name_lower();
+
+    match src {
+        LintLevelSource::Default => {
+            diag.set_help(dcx.translate(
+                messages::LEVEL_DEFAULT_HELP,
+                &[("level", &level), ("name", &name)],
+            ));
+        }
+        LintLevelSource::CommandLine => {
+            diag.set_help(dcx.translate(
+                messages::LEVEL_COMMAND_LINE_HELP,
+                &[("flag", &level.as_flag()), ("level", &level), ("name", &name)],
+            ));
+        }
+        LintLevelSource::Attribute { span, via_warnings } => {
+            let msg = if via_warnings {
+                messages::LEVEL_VIA_WARNINGS_LABEL
+            } else {
+                messages::LEVEL_ATTRIBUTE_LABEL
+            };
+            diag.set_label(span, dcx.translate(msg, &[("level", &level), ("name", &name)]));
+        }
+    }
+
+    Some(diag)
+}
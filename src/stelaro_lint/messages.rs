@@ -0,0 +1,100 @@
+//! リントで報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    LEVEL_DEFAULT_HELP {
+        ja: "`#[{level}({name})]` はデフォルトで有効です",
+        en: "`#[{level}({name})]` on by default",
+    }
+    LEVEL_COMMAND_LINE_HELP {
+        ja: "コマンドライン引数 `{flag} {name}` によって `#[{level}({name})]` が有効になっています",
+        en: "`#[{level}({name})]` implied by `{flag} {name}`",
+    }
+    LEVEL_VIA_WARNINGS_LABEL {
+        ja: "`#[{level}(warnings)]` によって `#[{level}({name})]` が有効になっています",
+        en: "`#[{level}({name})]` implied by `#[{level}(warnings)]`",
+    }
+    LEVEL_ATTRIBUTE_LABEL {
+        ja: "`#[{level}({name})]` はここで指定されています",
+        en: "the lint level `#[{level}({name})]` is defined here",
+    }
+
+    UNKNOWN_ATTRIBUTE {
+        ja: "不明な属性: `{name}`",
+        en: "cannot find attribute `{name}`",
+    }
+    UNKNOWN_ATTRIBUTE_LABEL {
+        ja: "この属性は存在しません",
+        en: "this attribute does not exist",
+    }
+    UNKNOWN_ATTRIBUTE_HELP {
+        ja: "使用できる属性は `allow`, `warn`, `deny` です",
+        en: "the available attributes are `allow`, `warn` and `deny`",
+    }
+
+    MALFORMED_LINT_ATTRIBUTE {
+        ja: "`{level}` 属性の形式が正しくありません",
+        en: "malformed `{level}` attribute",
+    }
+    MALFORMED_LINT_ATTRIBUTE_LABEL {
+        ja: "リント名が指定されていません",
+        en: "no lint name is given",
+    }
+    MALFORMED_LINT_ATTRIBUTE_HELP {
+        ja: "`#[{level}(lint_name)]` の形式で記述してください",
+        en: "write it in the form `#[{level}(lint_name)]`",
+    }
+
+    UNKNOWN_LINT_FLAG {
+        ja: "コマンドライン引数 `{flag} {name}` で不明なリントが指定されました",
+        en: "unknown lint `{name}` given by command line argument `{flag} {name}`",
+    }
+    UNKNOWN_LINT {
+        ja: "不明なリント: `{name}`",
+        en: "unknown lint: `{name}`",
+    }
+    UNKNOWN_LINT_LABEL {
+        ja: "このリントは存在しません",
+        en: "this lint does not exist",
+    }
+
+    NON_SNAKE_CASE {
+        ja: "{descr} `{name}` は snake_case で命名するべきです",
+        en: "{descr} `{name}` should have a snake case name",
+    }
+    NON_SNAKE_CASE_LABEL {
+        ja: "snake_case ではない名前",
+        en: "not a snake case name",
+    }
+    NON_SNAKE_CASE_SUGGESTION {
+        ja: "snake_case に変更してください",
+        en: "convert the identifier to snake case",
+    }
+    DESCR_FN {
+        ja: "関数",
+        en: "function",
+    }
+    DESCR_MOD {
+        ja: "モジュール",
+        en: "module",
+    }
+    DESCR_VARIABLE {
+        ja: "変数",
+        en: "variable",
+    }
+
+    UNUSED_VARIABLE {
+        ja: "未使用の変数: `{name}`",
+        en: "unused variable: `{name}`",
+    }
+    UNUSED_VARIABLE_LABEL {
+        ja: "この変数は使用されていません",
+        en: "this variable is never used",
+    }
+    UNUSED_VARIABLE_SUGGESTION {
+        ja: "意図的に使用しない場合は、`_` から始まる名前にしてください",
+        en: "if this is intentional, prefix it with an underscore",
+    }
+}
@@ -0,0 +1,33 @@
+mod diagnostics;
+mod early;
+pub mod levels;
+pub mod messages;
+
+use std::collections::HashMap;
+
+use crate::stelaro_ast::{NodeId, ast::Stelo, visit::Visitor};
+use crate::stelaro_diagnostics::DiagCtxtHandle;
+use crate::stelaro_session::lint::LintLevel;
+use crate::stelaro_sir::def::Res;
+
+use early::EarlyLintVisitor;
+use levels::{LintLevelMap, LintLevelsBuilder};
+
+
+/// 名前解決の結果を用いて、AST に対するリントを実行する。
+///
+/// 各リントのレベルは `lint_opts` (コマンドライン引数) と、
+/// `#[allow(...)]`, `#[warn(...)]`, `#[deny(...)]` 属性によって決定される。
+/// MIR に対するリントのために、ステロのルートと関数ごとのレベルを記録して返す。
+pub fn check_ast_stelo(
+    dcx: DiagCtxtHandle<'_>,
+    lint_opts: &[(String, LintLevel)],
+    stelo: &Stelo,
+    res_map: &HashMap<NodeId, Res<NodeId>>,
+) -> LintLevelMap<NodeId> {
+    let builder = LintLevelsBuilder::new(dcx, lint_opts);
+    let mut visitor = EarlyLintVisitor::new(dcx, builder, res_map);
+
+    visitor.visit_stelo(stelo);
+    visitor.into_lint_levels()
+}
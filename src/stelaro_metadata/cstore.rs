@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::stelaro_common::encoder::Decoder;
+use crate::stelaro_common::stable_hasher::HashStable;
+use crate::stelaro_common::{
+    DefId, DefIndex, DefPathHash, Fingerprint, IndexVec, LOCAL_STELO, StableHasher, StableSteloId,
+    SteloNum, Symbol,
+};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_session::Session;
+use crate::stelaro_std::{STD_STELO_NAME, std_metadata};
+use crate::stelaro_sir::def::DefKind;
+use crate::stelaro_sir::definitions::DefKey;
+use crate::stelaro_ty::codec::{decode_fn_sig, decode_ty};
+use crate::stelaro_ty::{FnSig, Ty, TyKind};
+
+use super::diagnostics::DiagsMetadata;
+use super::{DefEntry, decode_metadata};
+
+
+/// `--extern` で読み込んだ一つのステロのメタデータ
+pub struct SteloMetadata {
+    /// `--extern` で指定された、ソースコードからステロを参照する名前
+    pub extern_name: Symbol,
+
+    /// ステロ自身の名前。シンボル名やパスの表示に使う
+    pub name: Symbol,
+
+    pub stable_stelo_id: StableSteloId,
+
+    /// 読み込んだメタデータのパス
+    pub path: PathBuf,
+
+    defs: IndexVec<DefIndex, DefEntry>,
+    def_path_hash_to_index: HashMap<DefPathHash, DefIndex>,
+}
+
+impl SteloMetadata {
+    pub fn def_key(&self, index: DefIndex) -> DefKey {
+        self.defs[index].key
+    }
+
+    pub fn def_path_hash(&self, index: DefIndex) -> DefPathHash {
+        self.defs[index].def_path_hash
+    }
+
+    pub fn def_kind(&self, index: DefIndex) -> DefKind {
+        self.defs[index].kind
+    }
+
+    pub fn def_path_hash_to_index(&self, hash: DefPathHash) -> Option<DefIndex> {
+        self.def_path_hash_to_index.get(&hash).copied()
+    }
+
+    /// `parent` の直下にある定義を、定義された順に返す。
+    pub fn children(&self, parent: DefIndex) -> impl Iterator<Item = DefIndex> {
+        self.defs
+            .iter_enumerated()
+            .filter(move |(_, def)| def.key.parent == Some(parent))
+            .map(|(index, _)| index)
+    }
+
+    /// 関数のシグネチャのフィンガープリント。
+    /// インクリメンタルコンパイルで、外部ステロの関数のシグネチャの変更を検出するために使う
+    pub fn fn_sig_fingerprint(&self, index: DefIndex) -> Option<Fingerprint> {
+        let fn_sig = self.defs[index].fn_sig.as_ref()?;
+        let mut hasher = StableHasher::new();
+        fn_sig.hash_stable(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+/// `--extern` で読み込んだすべてのステロ。
+///
+/// `SteloNum` はローカルのステロが 0 で、読み込んだステロには 1 から割り当てる。
+/// 標準ステロ `std` を読み込む場合は、`std` が 1 となり、`--extern` のステロがその後に続く。
+#[derive(Default)]
+pub struct CStore {
+    metas: Vec<SteloMetadata>,
+
+    /// 標準ステロの `SteloNum`。`-Z no-std` が指定された場合は `None`
+    std: Option<SteloNum>,
+}
+
+impl CStore {
+    /// 標準ステロと、`sess.opts.externs` で指定されたメタデータを読み込む。
+    ///
+    /// 読み込めなかった場合や、同じステロを複数回読み込んだ場合はエラーを報告し、コンパイルを中断する。
+    /// `local_stelo_name` はコンパイル中のステロの名前で、読み込んだステロと衝突しないことを確かめる。
+    pub fn load(sess: &Session, local_stelo_name: Symbol) -> CStore {
+        let dcx = sess.dcx();
+        let mut cstore = CStore::default();
+        let local_stable_stelo_id = StableSteloId::new(local_stelo_name);
+
+        if !sess.opts.unstable_opts.no_std {
+            let path = PathBuf::from(format!("<{STD_STELO_NAME}>"));
+            cstore.std =
+                cstore.add(sess, STD_STELO_NAME, &path, std_metadata(), local_stable_stelo_id);
+        }
+
+        for entry in &sess.opts.externs {
+            let data = match fs::read(&entry.path) {
+                Ok(data) => data,
+                Err(error) => {
+                    DiagsMetadata::read_failed(dcx, &entry.name, &entry.path, &error).emit();
+                    continue;
+                }
+            };
+            cstore.add(sess, &entry.name, &entry.path, &data, local_stable_stelo_id);
+        }
+
+        dcx.abort_if_errors();
+        cstore
+    }
+
+    /// メタデータ `data` を `extern_name` という名前で読み込む。
+    /// エラーを報告した場合は `None` を返す。
+    fn add(
+        &mut self,
+        sess: &Session,
+        extern_name: &str,
+        path: &Path,
+        data: &[u8],
+        local_stable_stelo_id: StableSteloId,
+    ) -> Option<SteloNum> {
+        let dcx = sess.dcx();
+        if self.metas.iter().any(|meta| meta.extern_name.as_str() == extern_name) {
+            DiagsMetadata::duplicate_extern(dcx, extern_name, path).emit();
+            return None;
+        }
+
+        let Some((name, defs)) = decode_metadata(data) else {
+            DiagsMetadata::invalid(dcx, extern_name, path).emit();
+            return None;
+        };
+
+        // `DefPathHash` はステロの名前から計算されるため、同じ名前のステロの定義は区別できない
+        let stable_stelo_id = StableSteloId::new(name);
+        if stable_stelo_id == local_stable_stelo_id
+            || self.metas.iter().any(|meta| meta.stable_stelo_id == stable_stelo_id)
+        {
+            DiagsMetadata::duplicate_stelo(dcx, name.as_str(), extern_name, path).emit();
+            return None;
+        }
+
+        let def_path_hash_to_index = defs
+            .iter_enumerated()
+            .map(|(index, def)| (def.def_path_hash, index))
+            .collect();
+
+        self.metas.push(SteloMetadata {
+            extern_name: Symbol::intern(extern_name),
+            name,
+            stable_stelo_id,
+            path: path.to_path_buf(),
+            defs,
+            def_path_hash_to_index,
+        });
+        Some(SteloNum::from_usize(self.metas.len()))
+    }
+
+    /// 標準ステロの `SteloNum`。
+    pub fn std_stelo(&self) -> Option<SteloNum> {
+        self.std
+    }
+
+    /// 読み込んだステロのメタデータ。
+    pub fn get(&self, stelo: SteloNum) -> &SteloMetadata {
+        assert_ne!(stelo, LOCAL_STELO, "bug: ローカルのステロのメタデータは存在しない");
+        &self.metas[stelo.index() - 1]
+    }
+
+    /// 読み込んだすべてのステロと、その `SteloNum`。
+    pub fn iter(&self) -> impl Iterator<Item = (SteloNum, &SteloMetadata)> {
+        self.metas
+            .iter()
+            .enumerate()
+            .map(|(i, meta)| (SteloNum::from_usize(i + 1), meta))
+    }
+
+    /// `StableSteloId` に対応する、読み込んだステロ。
+    pub fn stelo_by_stable_id(&self, stable_stelo_id: StableSteloId) -> Option<SteloNum> {
+        self.iter()
+            .find(|(_, meta)| meta.stable_stelo_id == stable_stelo_id)
+            .map(|(stelo, _)| stelo)
+    }
+}
+
+/// 外部ステロの関数のシグネチャを読む。`fn_sig` クエリの実装から呼ばれる。
+///
+/// 参照している定義が読み込まれていないなど、シグネチャを読めない場合はエラーを報告する。
+pub fn foreign_fn_sig<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> FnSig<'tcx> {
+    let meta = tcx.cstore.get(def_id.stelo);
+    let fn_sig = meta.defs[def_id.index]
+        .fn_sig
+        .as_ref()
+        .expect("bug: 関数ではない定義のシグネチャが要求された");
+
+    let mut d = Decoder::new(fn_sig);
+    match decode_fn_sig(tcx, &mut d).filter(|_| d.is_empty()) {
+        Some(fn_sig) => fn_sig,
+        None => {
+            let guar = DiagsMetadata::invalid(tcx.dcx(), meta.extern_name.as_str(), &meta.path).emit();
+            FnSig {
+                inputs: Vec::new(),
+                output: tcx.mk_ty(TyKind::Error(guar)),
+            }
+        }
+    }
+}
+
+/// 外部ステロの型エイリアスを展開した型を読む。`type_of` クエリの実装から呼ばれる。
+///
+/// 型を読めない場合はエラーを報告する。
+pub fn foreign_type_of<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> Ty<'tcx> {
+    let meta = tcx.cstore.get(def_id.stelo);
+    let ty = meta.defs[def_id.index]
+        .ty
+        .as_ref()
+        .expect("bug: 型エイリアスではない定義の型が要求された");
+
+    let mut d = Decoder::new(ty);
+    match decode_ty(tcx, &mut d).filter(|_| d.is_empty()) {
+        Some(ty) => ty,
+        None => {
+            let guar = DiagsMetadata::invalid(tcx.dcx(), meta.extern_name.as_str(), &meta.path).emit();
+            tcx.mk_ty(TyKind::Error(guar))
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::io;
+use std::path::Path;
+
+use crate::stelaro_common::DUMMY_SPAN;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+/// `--extern` で指定されたファイルに関する診断。
+/// コマンドライン引数にはソースコード上の位置がないため、メッセージに引数そのものを含める
+pub struct DiagsMetadata;
+
+impl<'dcx> DiagsMetadata {
+    pub fn read_failed(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: &str,
+        path: &Path,
+        error: &io::Error,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(DUMMY_SPAN);
+        diag.set_code(ErrorCode::ReadFailed.into());
+        diag.set_message(dcx.translate(
+            messages::METADATA_READ_FAILED,
+            &[("name", &name), ("path", &path.display()), ("error", error)],
+        ));
+
+        diag
+    }
+
+    pub fn invalid(dcx: DiagCtxtHandle<'dcx>, name: &str, path: &Path) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(DUMMY_SPAN);
+        diag.set_code(ErrorCode::InvalidMetadata.into());
+        diag.set_message(dcx.translate(
+            messages::METADATA_INVALID,
+            &[("name", &name), ("path", &path.display())],
+        ));
+        diag.set_help(dcx.translate(messages::METADATA_INVALID_HELP, &[("name", &name)]));
+
+        diag
+    }
+
+    pub fn duplicate_extern(dcx: DiagCtxtHandle<'dcx>, name: &str, path: &Path) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(DUMMY_SPAN);
+        diag.set_code(ErrorCode::DuplicateExtern.into());
+        diag.set_message(dcx.translate(
+            messages::DUPLICATE_EXTERN,
+            &[("name", &name), ("path", &path.display())],
+        ));
+
+        diag
+    }
+
+    pub fn duplicate_stelo(
+        dcx: DiagCtxtHandle<'dcx>,
+        stelo: &str,
+        name: &str,
+        path: &Path,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(DUMMY_SPAN);
+        diag.set_code(ErrorCode::DuplicateStelo.into());
+        diag.set_message(dcx.translate(
+            messages::DUPLICATE_STELO,
+            &[("stelo", &stelo), ("name", &name), ("path", &path.display())],
+        ));
+        diag.set_help(dcx.translate(messages::DUPLICATE_STELO_HELP, &[]));
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    ReadFailed = 1000,
+    InvalidMetadata = 1001,
+    DuplicateExtern = 1002,
+    DuplicateStelo = 1003,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
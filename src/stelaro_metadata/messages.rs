@@ -0,0 +1,31 @@
+//! ステロのメタデータの読み込みで報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    METADATA_READ_FAILED {
+        ja: "`--extern {name}={path}` で指定されたメタデータを読み込めませんでした: {error}",
+        en: "failed to read the metadata given by `--extern {name}={path}`: {error}",
+    }
+    METADATA_INVALID {
+        ja: "`--extern {name}={path}` で指定されたファイルはステロのメタデータではないか、異なる版のコンパイラで生成されました",
+        en: "the file given by `--extern {name}={path}` is not stelo metadata, or was produced by a different version of the compiler",
+    }
+    METADATA_INVALID_HELP {
+        ja: "ステロ `{name}` を `--stelo-type=lib` でコンパイルし直してください",
+        en: "recompile stelo `{name}` with `--stelo-type=lib`",
+    }
+    DUPLICATE_EXTERN {
+        ja: "`--extern {name}` が複数回指定されました: `--extern {name}={path}`",
+        en: "`--extern {name}` was specified more than once: `--extern {name}={path}`",
+    }
+    DUPLICATE_STELO {
+        ja: "ステロ `{stelo}` が複数回読み込まれました: `--extern {name}={path}`",
+        en: "stelo `{stelo}` was loaded more than once: `--extern {name}={path}`",
+    }
+    DUPLICATE_STELO_HELP {
+        ja: "同じ名前のステロの定義は区別できません。どちらかのステロの名前を `--stelo-name` で変更してください",
+        en: "definitions of stelos with the same name cannot be distinguished; rename one of them with `--stelo-name`",
+    }
+}
@@ -0,0 +1,197 @@
+//! ステロのメタデータ。
+//!
+//! `--stelo-type=lib` でコンパイルしたステロは、すべての定義の `DefKey` と `DefPathHash`、
+//! `DefKind` の表と、関数のシグネチャ、型エイリアスの型をメタデータとして書き出す。
+//! `--extern 名前=パス` で指定されたメタデータは `CStore` に読み込まれ、
+//! そのステロの定義は `SteloNum` が 1 以上の `DefId` で参照される。
+//!
+//! 関数の本体は含まないため、外部ステロの関数の呼び出しはリンクの際に解決される。
+
+mod cstore;
+mod diagnostics;
+pub mod messages;
+
+pub use cstore::{CStore, SteloMetadata, foreign_fn_sig, foreign_type_of};
+
+use crate::stelaro_common::encoder::{Decoder, Encoder};
+use crate::stelaro_common::{DefIndex, DefPathHash, IndexVec, LocalDefId, StableSteloId, Symbol};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::def::DefKind;
+use crate::stelaro_sir::definitions::{DefKey, DefPathData, DisambiguatedDefPathData};
+use crate::stelaro_ty::codec::{encode_fn_sig, encode_ty};
+
+
+/// メタデータのファイルの先頭に置く識別子
+const MAGIC: &[u8; 4] = b"STMD";
+
+/// メタデータの形式の版。形式を変更したら増やす
+const FORMAT_VERSION: u32 = 3;
+
+const TAG_STELO_ROOT: u8 = 0;
+const TAG_TYPE_NS: u8 = 1;
+const TAG_VALUE_NS: u8 = 2;
+const TAG_CLOSURE: u8 = 3;
+
+const DEF_KINDS: [DefKind; 4] = [DefKind::Mod, DefKind::Fn, DefKind::Closure, DefKind::TyAlias];
+
+/// メタデータに記録する一つの定義
+#[derive(Debug, Clone)]
+struct DefEntry {
+    key: DefKey,
+    def_path_hash: DefPathHash,
+    kind: DefKind,
+
+    /// `stelaro_ty::codec::encode_fn_sig` で書き出した関数のシグネチャ。
+    /// 型は `TyCtxt` にインターンする必要があるため、`fn_sig` クエリで要求されたときに読む
+    fn_sig: Option<Vec<u8>>,
+
+    /// `stelaro_ty::codec::encode_ty` で書き出した、型エイリアスを展開した型
+    ty: Option<Vec<u8>>,
+}
+
+/// 型検査を終えたステロのメタデータをバイト列に変換する。
+pub fn encode_metadata(tcx: TyCtxt<'_>) -> Vec<u8> {
+    let defs = tcx
+        .definitions
+        .borrow()
+        .def_path_table()
+        .enumerated_keys_and_path_hashes()
+        .map(|(index, key, def_path_hash)| (index, *key, def_path_hash))
+        .collect::<Vec<_>>();
+
+    let mut e = Encoder::new();
+    for &b in MAGIC {
+        e.emit_u8(b);
+    }
+    e.emit_u32(FORMAT_VERSION);
+    e.emit_str(env!("CARGO_PKG_VERSION"));
+    e.emit_str(tcx.stelo_name.as_str());
+
+    e.emit_usize(defs.len());
+    for (index, key, def_path_hash) in defs {
+        let def_id = LocalDefId::new(index);
+        let kind = tcx.local_def_kind(def_id);
+
+        e.emit_bool(key.parent.is_some());
+        if let Some(parent) = key.parent {
+            e.emit_u32(parent.as_u32());
+        }
+        match key.disambiguated_data.data {
+            DefPathData::SteloRoot => e.emit_u8(TAG_STELO_ROOT),
+            DefPathData::TypeNs(name) => {
+                e.emit_u8(TAG_TYPE_NS);
+                e.emit_bool(name.is_some());
+                if let Some(name) = name {
+                    e.emit_str(name.as_str());
+                }
+            }
+            DefPathData::ValueNs(name) => {
+                e.emit_u8(TAG_VALUE_NS);
+                e.emit_str(name.as_str());
+            }
+            DefPathData::Closure => e.emit_u8(TAG_CLOSURE),
+        }
+        e.emit_u32(key.disambiguated_data.disambiguator);
+        e.emit_fingerprint(def_path_hash.0);
+        e.emit_u8(DEF_KINDS.iter().position(|k| *k == kind).unwrap() as u8);
+
+        let fn_sig = (kind == DefKind::Fn).then(|| {
+            let mut sig = Encoder::new();
+            encode_fn_sig(tcx, &mut sig, &tcx.fn_sig(def_id.to_def_id()))
+                .expect("bug: 型検査を終えたシグネチャに保存できない型が含まれている");
+            sig.finish()
+        });
+        e.emit_bool(fn_sig.is_some());
+        if let Some(fn_sig) = &fn_sig {
+            e.emit_bytes(fn_sig);
+        }
+
+        let ty = (kind == DefKind::TyAlias).then(|| {
+            let mut ty = Encoder::new();
+            encode_ty(tcx, &mut ty, tcx.type_of(def_id.to_def_id()))
+                .expect("bug: 型検査を終えた型エイリアスに保存できない型が含まれている");
+            ty.finish()
+        });
+        e.emit_bool(ty.is_some());
+        if let Some(ty) = &ty {
+            e.emit_bytes(ty);
+        }
+    }
+
+    e.finish()
+}
+
+/// `encode_metadata` で変換したバイト列から、ステロの名前と定義の表を読む。
+/// 壊れたデータや、異なる版のコンパイラで生成されたデータに対しては `None` を返す。
+fn decode_metadata(data: &[u8]) -> Option<(Symbol, IndexVec<DefIndex, DefEntry>)> {
+    let mut d = Decoder::new(data);
+    for &b in MAGIC {
+        if d.read_u8()? != b {
+            return None;
+        }
+    }
+    if d.read_u32()? != FORMAT_VERSION || d.read_str()? != env!("CARGO_PKG_VERSION") {
+        return None;
+    }
+
+    let name = Symbol::intern(d.read_str()?);
+    let stable_stelo_id = StableSteloId::new(name);
+
+    let len = d.read_usize()?;
+    let mut defs = IndexVec::new();
+    for i in 0..len {
+        let parent = match d.read_bool()? {
+            // 親は子よりも先に定義される
+            true => Some(DefIndex::from_u32(d.read_u32()?)).filter(|p| p.index() < i),
+            false => None,
+        };
+        let data = match d.read_u8()? {
+            TAG_STELO_ROOT => DefPathData::SteloRoot,
+            TAG_TYPE_NS => {
+                let name = match d.read_bool()? {
+                    true => Some(Symbol::intern(d.read_str()?)),
+                    false => None,
+                };
+                DefPathData::TypeNs(name)
+            }
+            TAG_VALUE_NS => DefPathData::ValueNs(Symbol::intern(d.read_str()?)),
+            TAG_CLOSURE => DefPathData::Closure,
+            _ => return None,
+        };
+        let disambiguator = d.read_u32()?;
+        let def_path_hash = DefPathHash(d.read_fingerprint()?);
+        let kind = *DEF_KINDS.get(d.read_u8()? as usize)?;
+        let fn_sig = match d.read_bool()? {
+            true => Some(d.read_bytes()?.to_vec()),
+            false => None,
+        };
+        let ty = match d.read_bool()? {
+            true => Some(d.read_bytes()?.to_vec()),
+            false => None,
+        };
+
+        // ルートだけが親をもたない
+        if (parent.is_none()) != (i == 0) || (data == DefPathData::SteloRoot) != (i == 0) {
+            return None;
+        }
+        if def_path_hash.stable_stelo_id() != stable_stelo_id
+            || (kind == DefKind::Fn) != fn_sig.is_some()
+            || (kind == DefKind::TyAlias) != ty.is_some()
+        {
+            return None;
+        }
+
+        defs.push(DefEntry {
+            key: DefKey {
+                parent,
+                disambiguated_data: DisambiguatedDefPathData { data, disambiguator },
+            },
+            def_path_hash,
+            kind,
+            fn_sig,
+            ty,
+        });
+    }
+
+    d.is_empty().then_some((name, defs))
+}
@@ -0,0 +1,50 @@
+//! 実行時のパニックのメッセージカタログ
+//!
+//! MIR の `Assert` には原因の種類のみが記録され、文面は各バックエンドが
+//! コードを生成する際に、セッションの言語で選ぶ。
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    PANICKED_AT {
+        ja: "{location} でパニックしました: {description}",
+        en: "panicked at {location}: {description}",
+    }
+    OVERFLOW_ADD {
+        ja: "加算がオーバーフローしました",
+        en: "attempt to add with overflow",
+    }
+    OVERFLOW_SUB {
+        ja: "減算がオーバーフローしました",
+        en: "attempt to subtract with overflow",
+    }
+    OVERFLOW_MUL {
+        ja: "乗算がオーバーフローしました",
+        en: "attempt to multiply with overflow",
+    }
+    OVERFLOW_DIV {
+        ja: "除算がオーバーフローしました",
+        en: "attempt to divide with overflow",
+    }
+    OVERFLOW_REM {
+        ja: "剰余の計算がオーバーフローしました",
+        en: "attempt to calculate the remainder with overflow",
+    }
+    OVERFLOW_NEG {
+        ja: "符号の反転がオーバーフローしました",
+        en: "attempt to negate with overflow",
+    }
+    DIVISION_BY_ZERO {
+        ja: "ゼロで除算しようとしました",
+        en: "attempt to divide by zero",
+    }
+    REMAINDER_BY_ZERO {
+        ja: "ゼロで割った余りを求めようとしました",
+        en: "attempt to calculate the remainder with a divisor of zero",
+    }
+    ASSERTION_FAILED {
+        ja: "アサーションに失敗しました",
+        en: "assertion failed",
+    }
+}
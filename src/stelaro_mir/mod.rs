@@ -0,0 +1,407 @@
+//! 制御フローグラフに基づく中間表現 (MIR)。
+//!
+//! rustc の `rustc_middle/mir` に基づいて設計されています。
+//! SIR の入れ子になったブロックやループ、`if` 式を、基本ブロックと
+//! それらを結ぶ終端命令 (terminator) に平坦化したもので、
+//! データフロー解析やコード生成はこの表現の上で行われる。
+
+pub mod messages;
+pub mod pretty;
+pub mod simplify;
+pub mod traversal;
+
+use crate::stelaro_ast::ast::{BinOpKind, UnOp};
+use crate::stelaro_common::{DefId, IndexVec, LocalDefId, Span, Symbol};
+use crate::stelaro_diagnostics::{DiagMessage, Lang};
+use crate::stelaro_ty::Ty;
+
+
+stelaro_macros::newtype_index! {
+    /// 関数本体の中のローカル変数 (戻り値、引数、ユーザー変数、一時変数)。
+    #[orderable]
+    #[debug_format = "_{}"]
+    pub struct Local {}
+}
+
+/// 戻り値を格納するローカル変数。常に `_0` である。
+pub const RETURN_PLACE: Local = Local::ZERO;
+
+stelaro_macros::newtype_index! {
+    /// 関数本体の中の基本ブロック。
+    #[orderable]
+    #[debug_format = "bb{}"]
+    pub struct BasicBlock {}
+}
+
+/// 関数の入口となる基本ブロック。
+pub const START_BLOCK: BasicBlock = BasicBlock::ZERO;
+
+/// 一つの関数本体の MIR。
+#[derive(Debug, Clone)]
+pub struct Body<'tcx> {
+    /// この本体をもつ関数。
+    pub def_id: LocalDefId,
+
+    pub basic_blocks: IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+
+    /// ローカル変数の宣言。
+    /// 先頭は戻り値 (`_0`) で、その後に `arg_count` 個の引数が続く。
+    pub local_decls: IndexVec<Local, LocalDecl<'tcx>>,
+
+    /// ユーザーが宣言した変数と、それを格納するローカル変数の対応。
+    pub var_debug_info: Vec<VarDebugInfo>,
+
+    /// 引数の数。
+    pub arg_count: usize,
+
+    pub span: Span,
+}
+
+impl<'tcx> Body<'tcx> {
+    /// 戻り値の型。
+    pub fn return_ty(&self) -> Ty<'tcx> {
+        self.local_decls[RETURN_PLACE].ty
+    }
+
+    /// 引数のローカル変数 (`_1` から `_{arg_count}` まで)。
+    pub fn args_iter(&self) -> impl ExactSizeIterator<Item = Local> {
+        (1..self.arg_count + 1).map(Local::from_usize)
+    }
+
+    /// 戻り値と引数を除いた、ユーザー変数と一時変数。
+    pub fn vars_and_temps_iter(&self) -> impl ExactSizeIterator<Item = Local> {
+        (self.arg_count + 1..self.local_decls.len()).map(Local::from_usize)
+    }
+}
+
+/// ローカル変数の宣言。
+#[derive(Debug, Clone)]
+pub struct LocalDecl<'tcx> {
+    pub ty: Ty<'tcx>,
+    /// 変数の宣言、または一時変数を生成した式の位置。
+    pub span: Span,
+}
+
+/// ユーザーが宣言した変数の情報。
+#[derive(Debug, Clone)]
+pub struct VarDebugInfo {
+    pub name: Symbol,
+    /// 変数が宣言された位置。
+    pub span: Span,
+    pub local: Local,
+}
+
+/// 基本ブロック。
+/// 文の列と、制御を次のブロックに移す一つの終端命令からなる。
+#[derive(Debug, Clone)]
+pub struct BasicBlockData<'tcx> {
+    pub statements: Vec<Statement<'tcx>>,
+
+    /// 構築中は `None` になりうるが、構築が終わった時点では必ず存在する。
+    pub terminator: Option<Terminator<'tcx>>,
+}
+
+impl<'tcx> BasicBlockData<'tcx> {
+    pub fn new(terminator: Option<Terminator<'tcx>>) -> Self {
+        Self {
+            statements: Vec::new(),
+            terminator,
+        }
+    }
+
+    pub fn terminator(&self) -> &Terminator<'tcx> {
+        self.terminator
+            .as_ref()
+            .expect("bug: 終端命令が設定されていない基本ブロック")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceInfo {
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement<'tcx> {
+    pub source_info: SourceInfo,
+    pub kind: StatementKind<'tcx>,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind<'tcx> {
+    /// `place = rvalue`
+    Assign(Box<(Place, Rvalue<'tcx>)>),
+
+    /// ローカル変数の領域が使用可能になったことを表す。
+    /// この時点で変数は初期化されていない。
+    StorageLive(Local),
+}
+
+/// 値を格納できる場所。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Place {
+    pub local: Local,
+}
+
+impl From<Local> for Place {
+    fn from(local: Local) -> Self {
+        Place { local }
+    }
+}
+
+/// 演算の被演算子となる値。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand<'tcx> {
+    /// 場所に格納された値をコピーする。
+    Copy(Place),
+
+    /// 定数。
+    Constant(Box<ConstOperand<'tcx>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstOperand<'tcx> {
+    pub span: Span,
+    pub ty: Ty<'tcx>,
+    pub const_: ConstValue,
+}
+
+/// 定数の値。値の解釈は `ConstOperand::ty` による。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    /// 整数。符号付き整数の場合は 2 の補数表現。
+    Int(u128),
+    /// 浮動小数点数。リテラルの表記をそのまま保持する。
+    Float(Symbol),
+    Bool(bool),
+    Char(char),
+    Str(Symbol),
+    /// `()` や関数アイテムのように、値をもたない型の値。
+    ZeroSized,
+    /// 関数ポインタに型強制された関数アイテム。関数のアドレスを表す。
+    FnPtr(DefId),
+}
+
+/// 代入の右辺となる値の計算。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rvalue<'tcx> {
+    Use(Operand<'tcx>),
+    BinaryOp(BinOp, Box<(Operand<'tcx>, Operand<'tcx>)>),
+    UnaryOp(UnOp, Operand<'tcx>),
+}
+
+/// 二項演算。
+///
+/// `and`, `or` は短絡評価されるため、分岐として表される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Lt,
+    Le,
+    Ne,
+    Ge,
+    Gt,
+
+    /// `lhs + rhs` が被演算子の整数型の範囲を超える場合に `true` となる。
+    AddOverflows,
+    /// `lhs - rhs` が被演算子の整数型の範囲を超える場合に `true` となる。
+    SubOverflows,
+    /// `lhs * rhs` が被演算子の整数型の範囲を超える場合に `true` となる。
+    MulOverflows,
+    /// `lhs / rhs` (および `lhs % rhs`) が `MIN / -1` である場合に `true` となる。
+    /// 符号なし整数型では常に `false` となる。
+    DivOverflows,
+}
+
+impl BinOp {
+    /// 短絡評価される演算子の場合は `None` を返す。
+    pub fn from_ast(op: BinOpKind) -> Option<BinOp> {
+        let op = match op {
+            BinOpKind::Add => BinOp::Add,
+            BinOpKind::Sub => BinOp::Sub,
+            BinOpKind::Mul => BinOp::Mul,
+            BinOpKind::Div => BinOp::Div,
+            BinOpKind::Mod => BinOp::Rem,
+            BinOpKind::Eq => BinOp::Eq,
+            BinOpKind::Lt => BinOp::Lt,
+            BinOpKind::Le => BinOp::Le,
+            BinOpKind::Ne => BinOp::Ne,
+            BinOpKind::Ge => BinOp::Ge,
+            BinOpKind::Gt => BinOp::Gt,
+            BinOpKind::And | BinOpKind::Or => return None,
+        };
+
+        Some(op)
+    }
+
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq | BinOp::Lt | BinOp::Le | BinOp::Ne | BinOp::Ge | BinOp::Gt
+        )
+    }
+
+    /// 整数のオーバーフローを検査する演算かどうか。結果は `bool` となる。
+    pub fn is_overflow_check(self) -> bool {
+        matches!(
+            self,
+            BinOp::AddOverflows | BinOp::SubOverflows | BinOp::MulOverflows | BinOp::DivOverflows
+        )
+    }
+
+    /// この演算のオーバーフローを検査する演算。
+    pub fn overflow_check(self) -> Option<BinOp> {
+        match self {
+            BinOp::Add => Some(BinOp::AddOverflows),
+            BinOp::Sub => Some(BinOp::SubOverflows),
+            BinOp::Mul => Some(BinOp::MulOverflows),
+            BinOp::Div | BinOp::Rem => Some(BinOp::DivOverflows),
+            _ => None,
+        }
+    }
+}
+
+/// `Assert` が失敗したときのパニックの原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssertKind {
+    /// 算術演算 (`Add`, `Sub`, `Mul`, `Div`, `Rem` のいずれか) のオーバーフロー。
+    Overflow(BinOp),
+    /// 符号の反転のオーバーフロー。
+    OverflowNeg,
+    DivisionByZero,
+    RemainderByZero,
+    /// `std::assert` の引数が偽であった。
+    Assertion,
+}
+
+impl AssertKind {
+    /// パニックの原因の説明。
+    pub fn message(self) -> &'static DiagMessage {
+        match self {
+            AssertKind::Overflow(BinOp::Add) => messages::OVERFLOW_ADD,
+            AssertKind::Overflow(BinOp::Sub) => messages::OVERFLOW_SUB,
+            AssertKind::Overflow(BinOp::Mul) => messages::OVERFLOW_MUL,
+            AssertKind::Overflow(BinOp::Div) => messages::OVERFLOW_DIV,
+            AssertKind::Overflow(BinOp::Rem) => messages::OVERFLOW_REM,
+            AssertKind::Overflow(op) => unreachable!("bug: オーバーフローしない演算子: {op:?}"),
+            AssertKind::OverflowNeg => messages::OVERFLOW_NEG,
+            AssertKind::DivisionByZero => messages::DIVISION_BY_ZERO,
+            AssertKind::RemainderByZero => messages::REMAINDER_BY_ZERO,
+            AssertKind::Assertion => messages::ASSERTION_FAILED,
+        }
+    }
+
+    /// `lang` によるパニックの原因の説明。
+    pub fn description(self, lang: Lang) -> &'static str {
+        self.message().get(lang)
+    }
+
+    /// 実行時に出力するパニックのメッセージ。`location` はソースコード上の位置。
+    pub fn panic_message(self, lang: Lang, location: &str) -> String {
+        messages::PANICKED_AT.format(
+            lang,
+            &[("location", &location), ("description", &self.description(lang))],
+        )
+    }
+}
+
+/// パニックしたプログラムの終了コード。
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+#[derive(Debug, Clone)]
+pub struct Terminator<'tcx> {
+    pub source_info: SourceInfo,
+    pub kind: TerminatorKind<'tcx>,
+}
+
+impl<'tcx> Terminator<'tcx> {
+    pub fn successors(&self) -> impl Iterator<Item = BasicBlock> + '_ {
+        self.kind.successors()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TerminatorKind<'tcx> {
+    /// 無条件に別のブロックへ移る。
+    Goto { target: BasicBlock },
+
+    /// 整数値 (または `bool`) の値によって分岐する。
+    SwitchInt {
+        discr: Operand<'tcx>,
+        targets: SwitchTargets,
+    },
+
+    /// 関数を呼び出し、結果を `destination` に格納して `target` へ移る。
+    Call {
+        func: Operand<'tcx>,
+        args: Vec<Operand<'tcx>>,
+        destination: Place,
+        target: BasicBlock,
+    },
+
+    /// `cond` が `expected` と等しければ `target` へ移り、そうでなければパニックする。
+    Assert {
+        cond: Operand<'tcx>,
+        expected: bool,
+        msg: AssertKind,
+        target: BasicBlock,
+    },
+
+    /// `_0` に格納された値を返して関数から戻る。
+    Return,
+
+    /// 到達しないことが分かっている。
+    Unreachable,
+}
+
+impl<'tcx> TerminatorKind<'tcx> {
+    pub fn successors(&self) -> impl Iterator<Item = BasicBlock> + '_ {
+        let (targets, otherwise): (&[(u128, BasicBlock)], Option<BasicBlock>) = match self {
+            TerminatorKind::Goto { target }
+            | TerminatorKind::Call { target, .. }
+            | TerminatorKind::Assert { target, .. } => (&[], Some(*target)),
+            TerminatorKind::SwitchInt { targets, .. } => (&targets.values, Some(targets.otherwise)),
+            TerminatorKind::Return | TerminatorKind::Unreachable => (&[], None),
+        };
+
+        targets.iter().map(|&(_, bb)| bb).chain(otherwise)
+    }
+
+    pub fn successors_mut(&mut self) -> impl Iterator<Item = &mut BasicBlock> {
+        let (targets, otherwise): (&mut [(u128, BasicBlock)], Option<&mut BasicBlock>) =
+            match self {
+                TerminatorKind::Goto { target }
+                | TerminatorKind::Call { target, .. }
+                | TerminatorKind::Assert { target, .. } => (&mut [], Some(target)),
+                TerminatorKind::SwitchInt { targets, .. } => {
+                    (&mut targets.values, Some(&mut targets.otherwise))
+                }
+                TerminatorKind::Return | TerminatorKind::Unreachable => (&mut [], None),
+            };
+
+        targets.iter_mut().map(|(_, bb)| bb).chain(otherwise)
+    }
+}
+
+/// `SwitchInt` の分岐先。
+/// 値が `values` のいずれとも一致しない場合は `otherwise` へ移る。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchTargets {
+    pub values: Vec<(u128, BasicBlock)>,
+    pub otherwise: BasicBlock,
+}
+
+impl SwitchTargets {
+    /// `bool` の値による分岐。
+    pub fn static_if(then: BasicBlock, else_: BasicBlock) -> Self {
+        SwitchTargets {
+            values: vec![(0, else_)],
+            otherwise: then,
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! `--emit=mir` のための MIR の出力。
+//!
+//! rustc の `-Z dump-mir` の形式に倣う。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BasicBlockData, Body, ConstOperand, ConstValue, Operand, RETURN_PLACE, Rvalue,
+    Statement, StatementKind, Terminator, TerminatorKind,
+};
+use crate::stelaro_ty::TyKind;
+
+
+const INDENT: &str = "    ";
+
+/// すべての関数本体の MIR を出力する。
+pub fn write_mir_pretty<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    bodies: &[Body<'tcx>],
+    w: &mut dyn Write,
+) -> fmt::Result {
+    for (i, body) in bodies.iter().enumerate() {
+        if i != 0 {
+            writeln!(w)?;
+        }
+        write_mir_fn(tcx, body, w)?;
+    }
+
+    Ok(())
+}
+
+/// 一つの関数本体の MIR を出力する。
+pub fn write_mir_fn<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>, w: &mut dyn Write) -> fmt::Result {
+    write!(w, "fn {}(", tcx.def_path_str(body.def_id.to_def_id()))?;
+    for (i, arg) in body.args_iter().enumerate() {
+        if i != 0 {
+            write!(w, ", ")?;
+        }
+        write!(w, "{arg:?}: {}", tcx.ty_string(body.local_decls[arg].ty))?;
+    }
+    writeln!(w, ") -> {} {{", tcx.ty_string(body.return_ty()))?;
+
+    for info in &body.var_debug_info {
+        writeln!(w, "{INDENT}debug {} => {:?};", info.name, info.local)?;
+    }
+
+    let return_and_locals = std::iter::once(RETURN_PLACE).chain(body.vars_and_temps_iter());
+    for local in return_and_locals {
+        writeln!(w, "{INDENT}let {local:?}: {};", tcx.ty_string(body.local_decls[local].ty))?;
+    }
+
+    for (bb, data) in body.basic_blocks.iter_enumerated() {
+        writeln!(w)?;
+        write_basic_block(tcx, bb, data, w)?;
+    }
+
+    writeln!(w, "}}")
+}
+
+fn write_basic_block<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    bb: BasicBlock,
+    data: &BasicBlockData<'tcx>,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    writeln!(w, "{INDENT}{bb:?}: {{")?;
+
+    for statement in &data.statements {
+        write!(w, "{INDENT}{INDENT}")?;
+        write_statement(tcx, statement, w)?;
+        writeln!(w, ";")?;
+    }
+
+    write!(w, "{INDENT}{INDENT}")?;
+    write_terminator(tcx, data.terminator(), w)?;
+    writeln!(w, ";")?;
+
+    writeln!(w, "{INDENT}}}")
+}
+
+fn write_statement<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    statement: &Statement<'tcx>,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    match &statement.kind {
+        StatementKind::Assign(box (place, rvalue)) => {
+            write!(w, "{:?} = ", place.local)?;
+            write_rvalue(tcx, rvalue, w)
+        }
+        StatementKind::StorageLive(local) => write!(w, "StorageLive({local:?})"),
+    }
+}
+
+fn write_terminator<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    terminator: &Terminator<'tcx>,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    match &terminator.kind {
+        TerminatorKind::Goto { target } => write!(w, "goto -> {target:?}"),
+        TerminatorKind::SwitchInt { discr, targets } => {
+            write!(w, "switchInt(")?;
+            write_operand(tcx, discr, w)?;
+            write!(w, ") -> [")?;
+            for (value, target) in &targets.values {
+                write!(w, "{value}: {target:?}, ")?;
+            }
+            write!(w, "otherwise: {:?}]", targets.otherwise)
+        }
+        TerminatorKind::Call {
+            func,
+            args,
+            destination,
+            target,
+        } => {
+            write!(w, "{:?} = ", destination.local)?;
+            match func {
+                // 呼び出す関数が定数の場合は、`const` を付けずに表す
+                Operand::Constant(constant) => write_const(tcx, constant, w)?,
+                Operand::Copy(_) => write_operand(tcx, func, w)?,
+            }
+            write!(w, "(")?;
+            for (i, arg) in args.iter().enumerate() {
+                if i != 0 {
+                    write!(w, ", ")?;
+                }
+                write_operand(tcx, arg, w)?;
+            }
+            write!(w, ") -> {target:?}")
+        }
+        TerminatorKind::Assert {
+            cond,
+            expected,
+            msg,
+            target,
+        } => {
+            write!(w, "assert({}", if *expected { "" } else { "!" })?;
+            write_operand(tcx, cond, w)?;
+            write!(w, ", {:?}) -> {target:?}", msg.description(tcx.sess.opts.lang))
+        }
+        TerminatorKind::Return => write!(w, "return"),
+        TerminatorKind::Unreachable => write!(w, "unreachable"),
+    }
+}
+
+fn write_rvalue<'tcx>(tcx: TyCtxt<'tcx>, rvalue: &Rvalue<'tcx>, w: &mut dyn Write) -> fmt::Result {
+    match rvalue {
+        Rvalue::Use(operand) => write_operand(tcx, operand, w),
+        Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+            write!(w, "{op:?}(")?;
+            write_operand(tcx, lhs, w)?;
+            write!(w, ", ")?;
+            write_operand(tcx, rhs, w)?;
+            write!(w, ")")
+        }
+        Rvalue::UnaryOp(op, operand) => {
+            let name = match op {
+                UnOp::Not => "Not",
+                UnOp::Neg => "Neg",
+            };
+            write!(w, "{name}(")?;
+            write_operand(tcx, operand, w)?;
+            write!(w, ")")
+        }
+    }
+}
+
+fn write_operand<'tcx>(tcx: TyCtxt<'tcx>, operand: &Operand<'tcx>, w: &mut dyn Write) -> fmt::Result {
+    match operand {
+        Operand::Copy(place) => write!(w, "copy {:?}", place.local),
+        Operand::Constant(constant) => {
+            write!(w, "const ")?;
+            write_const(tcx, constant, w)
+        }
+    }
+}
+
+fn write_const<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    constant: &ConstOperand<'tcx>,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    match (constant.const_, *constant.ty.kind()) {
+        (ConstValue::Int(bits), TyKind::Int(int_ty)) => {
+            // 符号拡張して、負の値を正しく表示する
+            let shift = 128 - int_ty.bit_width();
+            let value = ((bits << shift) as i128) >> shift;
+            write!(w, "{value}_{}", int_ty.name_str())
+        }
+        (ConstValue::Int(bits), TyKind::Uint(uint_ty)) => {
+            write!(w, "{bits}_{}", uint_ty.name_str())
+        }
+        (ConstValue::Float(symbol), TyKind::Float(float_ty)) => {
+            write!(w, "{symbol}_{}", float_ty.name_str())
+        }
+        (ConstValue::Bool(b), _) => write!(w, "{b}"),
+        (ConstValue::Char(c), _) => write!(w, "{c:?}"),
+        (ConstValue::Str(s), _) => write!(w, "{:?}", s.as_str()),
+        (ConstValue::ZeroSized, TyKind::FnDef(def_id) | TyKind::Closure(def_id, _)) => {
+            write!(w, "{}", tcx.def_path_str(def_id))
+        }
+        (ConstValue::ZeroSized, TyKind::Unit) => write!(w, "()"),
+        (ConstValue::FnPtr(def_id), _) => {
+            write!(w, "{} as {}", tcx.def_path_str(def_id), tcx.ty_string(constant.ty))
+        }
+        (const_, _) => write!(w, "{const_:?}: {}", tcx.ty_string(constant.ty)),
+    }
+}
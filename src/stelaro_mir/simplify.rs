@@ -0,0 +1,48 @@
+//! MIR の単純化。
+
+use crate::stelaro_common::{Idx, IndexVec};
+use crate::stelaro_mir::{BasicBlock, Body, START_BLOCK};
+
+
+/// 入口のブロックから到達できない基本ブロックを取り除き、残ったブロックの番号を詰める。
+///
+/// 構築中の MIR では、`return` や `break` の後の文のために到達しないブロックが作られる。
+/// それらは終端命令をもたない可能性があるため、他の処理の前に取り除く必要がある。
+pub fn remove_dead_blocks(body: &mut Body<'_>) {
+    let num_blocks = body.basic_blocks.len();
+    let mut reachable = vec![false; num_blocks];
+    let mut worklist = vec![START_BLOCK];
+
+    while let Some(bb) = worklist.pop() {
+        if std::mem::replace(&mut reachable[bb.index()], true) {
+            continue;
+        }
+        worklist.extend(body.basic_blocks[bb].terminator().successors());
+    }
+
+    if reachable.iter().all(|&r| r) {
+        return;
+    }
+
+    let mut replacements = IndexVec::<BasicBlock, Option<BasicBlock>>::from_elem_n(None, num_blocks);
+    let mut next = BasicBlock::ZERO;
+    for (bb, replacement) in replacements.iter_enumerated_mut() {
+        if reachable[bb.index()] {
+            *replacement = Some(next);
+            next.increment_by(1);
+        }
+    }
+
+    let blocks = std::mem::take(&mut body.basic_blocks);
+    body.basic_blocks = blocks
+        .into_iter_enumerated()
+        .filter(|(bb, _)| reachable[bb.index()])
+        .map(|(_, data)| data)
+        .collect();
+
+    for data in body.basic_blocks.iter_mut() {
+        for target in data.terminator.as_mut().unwrap().kind.successors_mut() {
+            *target = replacements[*target].unwrap();
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! 制御フローグラフの走査と、支配関係の計算。
+//!
+//! rustc の `rustc_middle::mir::traversal` と
+//! `rustc_data_structures::graph::dominators` に基づいて設計されています。
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_mir::{BasicBlock, Body, START_BLOCK};
+
+
+/// 入口のブロックから到達できる基本ブロックを、逆後順 (reverse postorder) に並べる。
+///
+/// 逆後順では、後ろ向きの辺 (ループの先頭へ戻る辺) を除いて、
+/// 辺の移動元は常に移動先より前に現れる。
+pub fn reverse_postorder(body: &Body<'_>) -> Vec<BasicBlock> {
+    let mut visited = IndexVec::<BasicBlock, bool>::from_elem_n(false, body.basic_blocks.len());
+    let mut postorder = Vec::new();
+    // 訪問中のブロックと、次に調べる後続のブロックの位置
+    let mut stack = vec![(START_BLOCK, 0)];
+    visited[START_BLOCK] = true;
+
+    while let Some((bb, next)) = stack.last_mut() {
+        let bb = *bb;
+        let succ = body.basic_blocks[bb].terminator().successors().nth(*next);
+        *next += 1;
+
+        match succ {
+            Some(succ) if !visited[succ] => {
+                visited[succ] = true;
+                stack.push((succ, 0));
+            }
+            Some(_) => {}
+            None => {
+                postorder.push(bb);
+                stack.pop();
+            }
+        }
+    }
+
+    postorder.reverse();
+    postorder
+}
+
+/// 各基本ブロックへ移る辺の移動元。同じブロックから複数の辺がある場合は、その数だけ含む。
+pub fn predecessors(body: &Body<'_>) -> IndexVec<BasicBlock, Vec<BasicBlock>> {
+    let mut preds = IndexVec::from_elem_n(Vec::new(), body.basic_blocks.len());
+    for (bb, data) in body.basic_blocks.iter_enumerated() {
+        for succ in data.terminator().successors() {
+            preds[succ].push(bb);
+        }
+    }
+    preds
+}
+
+/// 入口のブロックから到達できる基本ブロックの支配関係。
+///
+/// 入口からブロック `b` へのすべての経路がブロック `a` を通るとき、`a` は `b` を支配する。
+/// Cooper, Harvey, Kennedy による "A Simple, Fast Dominance Algorithm" で計算する。
+pub struct Dominators {
+    /// 逆後順での位置。到達できないブロックは `None`。
+    rpo_index: IndexVec<BasicBlock, Option<usize>>,
+    /// 直接の支配ブロック。入口のブロックと到達できないブロックは `None`。
+    immediate: IndexVec<BasicBlock, Option<BasicBlock>>,
+}
+
+impl Dominators {
+    pub fn compute(body: &Body<'_>) -> Self {
+        let rpo = reverse_postorder(body);
+        let preds = predecessors(body);
+
+        let mut rpo_index = IndexVec::from_elem_n(None, body.basic_blocks.len());
+        for (i, &bb) in rpo.iter().enumerate() {
+            rpo_index[bb] = Some(i);
+        }
+
+        // 計算中は、入口のブロックの支配ブロックを入口自身とする
+        let mut immediate = IndexVec::<BasicBlock, Option<BasicBlock>>::from_elem_n(
+            None,
+            body.basic_blocks.len(),
+        );
+        immediate[START_BLOCK] = Some(START_BLOCK);
+
+        let intersect = |immediate: &IndexVec<BasicBlock, Option<BasicBlock>>,
+                         mut a: BasicBlock,
+                         mut b: BasicBlock| {
+            while a != b {
+                while rpo_index[a] > rpo_index[b] {
+                    a = immediate[a].unwrap();
+                }
+                while rpo_index[b] > rpo_index[a] {
+                    b = immediate[b].unwrap();
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for &bb in &rpo[1..] {
+                let new_idom = preds[bb]
+                    .iter()
+                    .filter(|&&pred| immediate[pred].is_some())
+                    .copied()
+                    .reduce(|a, b| intersect(&immediate, a, b));
+
+                if new_idom.is_some() && immediate[bb] != new_idom {
+                    immediate[bb] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        immediate[START_BLOCK] = None;
+        Dominators {
+            rpo_index,
+            immediate,
+        }
+    }
+
+    /// 逆後順での位置。到達できないブロックは `None` を返す。
+    pub fn rpo_index(&self, bb: BasicBlock) -> Option<usize> {
+        self.rpo_index[bb]
+    }
+
+    /// 直接の支配ブロック。入口のブロックと到達できないブロックは `None` を返す。
+    pub fn immediate_dominator(&self, bb: BasicBlock) -> Option<BasicBlock> {
+        self.immediate[bb]
+    }
+
+    /// `a` が `b` を支配するかどうか。すべてのブロックは自身を支配する。
+    pub fn dominates(&self, a: BasicBlock, mut b: BasicBlock) -> bool {
+        loop {
+            if a == b {
+                return true;
+            }
+            match self.immediate[b] {
+                Some(idom) => b = idom,
+                None => return false,
+            }
+        }
+    }
+}
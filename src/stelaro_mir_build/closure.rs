@@ -0,0 +1,117 @@
+//! クロージャの MIR の構築。
+//!
+//! クロージャは定義された関数の外へ持ち出せないため、その値は大きさをもたず、
+//! 呼び出しのたびに本体を呼び出しの位置に展開する。参照によってキャプチャされた変数は
+//! 外側の変数をそのまま読み書きし、値によってキャプチャされた変数は、クロージャの生成時に
+//! 複製したローカル変数を読み書きする。
+
+use crate::stelaro_common::{LocalDefId, Span};
+use crate::stelaro_mir::{BasicBlock, Local, Operand, Place, Rvalue, TerminatorKind};
+use crate::stelaro_sir::sir::{self, PatKind};
+use crate::stelaro_sir::sir_id::SirId;
+use crate::stelaro_sir_typecheck::result::CaptureKind;
+use crate::stelaro_ty::TyKind;
+
+use super::{BlockAnd, BlockAndExtension, Builder, ReturnScope, unpack};
+
+
+impl<'a, 'tcx> Builder<'a, 'tcx> {
+    /// クロージャの生成時に、値によってキャプチャする変数を複製する。
+    pub(super) fn capture_by_value(
+        &mut self,
+        block: BasicBlock,
+        span: Span,
+        closure: &'tcx sir::Closure<'tcx>,
+    ) {
+        for capture in self.typeck_results.closure_captures(closure.def_id) {
+            if capture.kind != CaptureKind::ByValue {
+                continue;
+            }
+
+            let env = self.closure_env(closure.def_id, capture.var_id, span);
+            let value = Operand::Copy(self.var_local(capture.var_id).into());
+            self.push_assign(block, span, env.into(), Rvalue::Use(value));
+        }
+    }
+
+    /// クロージャの呼び出しを、本体を展開して構築する。
+    ///
+    /// 本体の中の `return` は、展開した本体の後に移る。
+    pub(super) fn closure_call_into_dest(
+        &mut self,
+        destination: Place,
+        mut block: BasicBlock,
+        expr: &'tcx sir::Expr<'tcx>,
+        callee: &'tcx sir::Expr<'tcx>,
+        args: &'tcx [sir::Expr<'tcx>],
+    ) -> BlockAnd<()> {
+        let TyKind::Closure(def_id, sig_tys) = *self.node_ty(callee.sir_id).kind() else {
+            panic!("bug: クロージャではない値の呼び出しを展開しようとした: {callee:?}");
+        };
+        let closure_id = def_id.expect_local();
+        let closure = self.tcx.expect_sir_closure(closure_id);
+        let body = self.tcx.sir_body(closure.body);
+
+        // 呼び出される値は大きさをもたないが、副作用のために評価する
+        let mut exprs = vec![callee];
+        exprs.extend(args);
+        let mut args = unpack!(block = self.as_operands(block, &exprs));
+        args.remove(0);
+
+        for (param, arg) in body.params.iter().zip(args) {
+            let ty = self.node_ty(param.pat.sir_id);
+            let local = self.new_temp(ty, param.span);
+            if let PatKind::Binding(var_id, ident) = param.pat.kind {
+                self.declare_var(var_id, ident, local);
+            }
+            self.push_assign(block, param.span, local.into(), Rvalue::Use(arg));
+        }
+
+        // 本体の中では、値によってキャプチャされた変数はその複製を指す
+        let mut shadowed = Vec::new();
+        for capture in self.typeck_results.closure_captures(closure_id) {
+            if capture.kind == CaptureKind::ByValue {
+                let env = self.closure_env(closure_id, capture.var_id, expr.span);
+                shadowed.push((capture.var_id, self.var_indices.insert(capture.var_id, env)));
+            }
+        }
+
+        let return_place = self.new_temp(sig_tys.output(), expr.span).into();
+        let exit_block = self.new_block();
+        self.return_scopes.push(ReturnScope {
+            return_place,
+            exit_block,
+        });
+
+        unpack!(block = self.expr_into_dest(return_place, block, body.value));
+        if self.node_ty(body.value.sir_id).is_never() {
+            self.terminate(block, body.value.span, TerminatorKind::Unreachable);
+        } else {
+            self.goto(block, body.value.span, exit_block);
+        }
+
+        self.return_scopes.pop();
+        for (var_id, local) in shadowed {
+            match local {
+                Some(local) => self.var_indices.insert(var_id, local),
+                None => self.var_indices.remove(&var_id),
+            };
+        }
+
+        let value = Operand::Copy(return_place);
+        self.push_assign(exit_block, expr.span, destination, Rvalue::Use(value));
+        exit_block.unit()
+    }
+
+    /// 値によってキャプチャされた変数の複製を格納するローカル変数。
+    /// 同じクロージャの生成と呼び出しは、同じローカル変数を共有する。
+    fn closure_env(&mut self, closure_id: LocalDefId, var_id: SirId, span: Span) -> Local {
+        if let Some(&local) = self.closure_envs.get(&(closure_id, var_id)) {
+            return local;
+        }
+
+        let local = self.new_temp(self.node_ty(var_id), span);
+        self.closure_envs.insert((closure_id, var_id), local);
+        local
+    }
+}
@@ -0,0 +1,428 @@
+use crate::stelaro_ast::ast::{BinOpKind, UnOp};
+use crate::stelaro_common::{Span, ensure_sufficient_stack};
+use crate::stelaro_mir::{
+    AssertKind, BasicBlock, BinOp, ConstOperand, ConstValue, Local, Operand, Place, Rvalue,
+    SwitchTargets, TerminatorKind,
+};
+use crate::stelaro_sir::{
+    DefKind, Res,
+    sir::{self, ExprKind, LitKind},
+};
+use crate::stelaro_std::{Intrinsic, intrinsic};
+use crate::stelaro_ty::{Ty, TyKind};
+
+use super::{BlockAnd, BlockAndExtension, Builder, unpack};
+
+
+impl<'a, 'tcx> Builder<'a, 'tcx> {
+    /// 式を評価し、その値を `destination` に格納する。
+    pub(super) fn expr_into_dest(
+        &mut self,
+        destination: Place,
+        mut block: BasicBlock,
+        expr: &'tcx sir::Expr<'tcx>,
+    ) -> BlockAnd<()> {
+        ensure_sufficient_stack(|| match expr.kind {
+            ExprKind::Block(ast_block) => self.ast_block_into_dest(destination, block, ast_block),
+            ExprKind::If(cond, then, else_opt) => {
+                let cond = unpack!(block = self.as_operand(block, cond));
+                let then_block = self.new_block();
+                let else_block = self.new_block();
+                self.terminate(
+                    block,
+                    expr.span,
+                    TerminatorKind::SwitchInt {
+                        discr: cond,
+                        targets: SwitchTargets::static_if(then_block, else_block),
+                    },
+                );
+
+                let then_end = unpack!(self.expr_into_dest(destination, then_block, then));
+                let else_end = match else_opt {
+                    Some(else_expr) => unpack!(self.expr_into_dest(destination, else_block, else_expr)),
+                    None => {
+                        self.push_assign_unit(else_block, expr.span, destination);
+                        else_block
+                    }
+                };
+
+                let join_block = self.new_block();
+                self.goto(then_end, expr.span, join_block);
+                self.goto(else_end, expr.span, join_block);
+                join_block.unit()
+            }
+            ExprKind::Binary(op, lhs, rhs) if op.node.is_lazy() => {
+                // `a and b` は `a` が偽であれば `b` を評価せずに偽となる
+                let lhs = unpack!(block = self.as_operand(block, lhs));
+                let rhs_block = self.new_block();
+                let short_circuit = self.new_block();
+                let (targets, short_value) = match op.node {
+                    BinOpKind::And => (SwitchTargets::static_if(rhs_block, short_circuit), false),
+                    _ => (SwitchTargets::static_if(short_circuit, rhs_block), true),
+                };
+                self.terminate(
+                    block,
+                    expr.span,
+                    TerminatorKind::SwitchInt {
+                        discr: lhs,
+                        targets,
+                    },
+                );
+
+                let short_value = self.bool_constant(expr.span, short_value);
+                self.push_assign(short_circuit, expr.span, destination, Rvalue::Use(short_value));
+                let rhs_end = unpack!(self.expr_into_dest(destination, rhs_block, rhs));
+
+                let join_block = self.new_block();
+                self.goto(short_circuit, expr.span, join_block);
+                self.goto(rhs_end, expr.span, join_block);
+                join_block.unit()
+            }
+            ExprKind::Call(callee, args) if self.node_ty(callee.sir_id).is_closure() => {
+                self.closure_call_into_dest(destination, block, expr, callee, args)
+            }
+            ExprKind::Call(callee, args) => {
+                let func = unpack!(block = self.as_operand(block, callee));
+                let args = args.iter().collect::<Vec<_>>();
+                let mut args = unpack!(block = self.as_operands(block, &args));
+                let success = self.new_block();
+
+                // `std::assert` の呼び出しは、オーバーフローの検査と同じ `Assert` で表す
+                if let TyKind::FnDef(def_id) = *self.node_ty(callee.sir_id).kind()
+                    && intrinsic(self.tcx, def_id) == Some(Intrinsic::Assert)
+                {
+                    self.terminate(
+                        block,
+                        expr.span,
+                        TerminatorKind::Assert {
+                            cond: args.pop().unwrap(),
+                            expected: true,
+                            msg: AssertKind::Assertion,
+                            target: success,
+                        },
+                    );
+                    self.push_assign_unit(success, expr.span, destination);
+                    return success.unit();
+                }
+
+                // `!` を返す関数は戻らないため、戻り値を捨てて呼び出しの後を到達不能とする
+                let diverges = self.node_ty(expr.sir_id).is_never();
+                let destination = if diverges {
+                    self.new_temp(self.tcx.types.never, expr.span).into()
+                } else {
+                    destination
+                };
+                self.terminate(
+                    block,
+                    expr.span,
+                    TerminatorKind::Call {
+                        func,
+                        args,
+                        destination,
+                        target: success,
+                    },
+                );
+                if diverges {
+                    self.terminate(success, expr.span, TerminatorKind::Unreachable);
+                    return self.new_block().unit();
+                }
+                success.unit()
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                unpack!(block = self.assign_expr(block, lhs, rhs));
+                self.push_assign_unit(block, expr.span, destination);
+                block.unit()
+            }
+            ExprKind::Lit(_)
+            | ExprKind::Path(_)
+            | ExprKind::Binary(..)
+            | ExprKind::Unary(..)
+            | ExprKind::Closure(_) => {
+                let rvalue = unpack!(block = self.as_rvalue(block, expr));
+                self.push_assign(block, expr.span, destination, rvalue);
+                block.unit()
+            }
+            ExprKind::Err(_) => panic!("bug: 型検査に失敗した本体から MIR を構築しようとした"),
+        })
+    }
+
+    /// 代入式 `lhs = rhs` を評価する。
+    pub(super) fn assign_expr(
+        &mut self,
+        block: BasicBlock,
+        lhs: &'tcx sir::Expr<'tcx>,
+        rhs: &'tcx sir::Expr<'tcx>,
+    ) -> BlockAnd<()> {
+        let ExprKind::Path(sir::Path {
+            res: Res::Local(var_id),
+            ..
+        }) = lhs.kind
+        else {
+            panic!("bug: 代入の左辺がローカル変数ではない: {lhs:?}");
+        };
+
+        let place = self.var_local(var_id).into();
+        self.expr_into_dest(place, block, rhs)
+    }
+
+    /// 式を評価し、値を計算する右辺値を返す。
+    fn as_rvalue(
+        &mut self,
+        mut block: BasicBlock,
+        expr: &'tcx sir::Expr<'tcx>,
+    ) -> BlockAnd<Rvalue<'tcx>> {
+        match expr.kind {
+            ExprKind::Binary(op, lhs_expr, rhs) => {
+                let op = BinOp::from_ast(op.node)
+                    .expect("bug: 短絡評価される演算子は分岐として構築される");
+                let mut operands = unpack!(block = self.as_operands(block, &[lhs_expr, rhs]));
+                let rhs = operands.pop().unwrap();
+                let lhs = operands.pop().unwrap();
+
+                let ty = self.node_ty(lhs_expr.sir_id);
+                if ty.is_integral() {
+                    unpack!(block = self.check_binary_op(block, expr.span, op, ty, &lhs, &rhs));
+                }
+                block.and(Rvalue::BinaryOp(op, Box::new((lhs, rhs))))
+            }
+            ExprKind::Unary(op, operand_expr) => {
+                let ty = self.node_ty(operand_expr.sir_id);
+
+                // `-2147483648_i32` のような負のリテラルは、`MIN` を反転する演算ではなく
+                // 一つの定数として構築する。リテラル自体は型の範囲を超えうるため、否定すると常にパニックする
+                if op == UnOp::Neg
+                    && let ExprKind::Lit(lit) = operand_expr.kind
+                    && let LitKind::Int(n) = lit.node
+                    && let TyKind::Int(int_ty) = *ty.kind()
+                {
+                    let mask = u128::MAX >> (128 - int_ty.bit_width());
+                    let bits = n.wrapping_neg() & mask;
+                    return block.and(Rvalue::Use(self.int_constant(expr.span, ty, bits)));
+                }
+
+                let operand = unpack!(block = self.as_operand(block, operand_expr));
+
+                // `-MIN` は型の範囲を超える
+                if op == UnOp::Neg
+                    && let TyKind::Int(int_ty) = *ty.kind()
+                    && self.tcx.sess.overflow_checks()
+                {
+                    let min = self.int_constant(expr.span, ty, 1 << (int_ty.bit_width() - 1));
+                    let is_min = Rvalue::BinaryOp(BinOp::Eq, Box::new((operand.clone(), min)));
+                    block = self.assert_not(block, expr.span, is_min, AssertKind::OverflowNeg);
+                }
+                block.and(Rvalue::UnaryOp(op, operand))
+            }
+            _ => {
+                let operand = unpack!(block = self.as_operand(block, expr));
+                block.and(Rvalue::Use(operand))
+            }
+        }
+    }
+
+    /// 整数の算術演算の前に、演算がパニックすべき場合を検査する。
+    ///
+    /// ゼロによる除算と `MIN / -1` は常に検査し、加算、減算、乗算のオーバーフローは
+    /// `-C overflow-checks` が有効な場合にのみ検査する。検査しない場合、結果は型の幅で折り返される。
+    fn check_binary_op(
+        &mut self,
+        mut block: BasicBlock,
+        span: Span,
+        op: BinOp,
+        ty: Ty<'tcx>,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+    ) -> BlockAnd<()> {
+        match op {
+            BinOp::Div | BinOp::Rem => {
+                let msg = if op == BinOp::Div {
+                    AssertKind::DivisionByZero
+                } else {
+                    AssertKind::RemainderByZero
+                };
+                let zero = self.int_constant(span, ty, 0);
+                let is_zero = Rvalue::BinaryOp(BinOp::Eq, Box::new((rhs.clone(), zero)));
+                block = self.assert_not(block, span, is_zero, msg);
+
+                if !ty.is_signed() {
+                    return block.unit();
+                }
+            }
+            BinOp::Add | BinOp::Sub | BinOp::Mul if self.tcx.sess.overflow_checks() => {}
+            _ => return block.unit(),
+        }
+
+        let check = op.overflow_check().unwrap();
+        let overflows = Rvalue::BinaryOp(check, Box::new((lhs.clone(), rhs.clone())));
+        self.assert_not(block, span, overflows, AssertKind::Overflow(op)).unit()
+    }
+
+    /// `cond` を計算し、それが `true` であればパニックする `Assert` で終端する。
+    /// 検査に成功した場合に移るブロックを返す。
+    fn assert_not(
+        &mut self,
+        block: BasicBlock,
+        span: Span,
+        cond: Rvalue<'tcx>,
+        msg: AssertKind,
+    ) -> BasicBlock {
+        let temp = self.new_temp(self.tcx.types.bool, span);
+        self.push_assign(block, span, temp.into(), cond);
+
+        let success = self.new_block();
+        self.terminate(
+            block,
+            span,
+            TerminatorKind::Assert {
+                cond: Operand::Copy(temp.into()),
+                expected: false,
+                msg,
+                target: success,
+            },
+        );
+        success
+    }
+
+    /// 式を評価し、被演算子として使える値を返す。
+    fn as_operand(
+        &mut self,
+        mut block: BasicBlock,
+        expr: &'tcx sir::Expr<'tcx>,
+    ) -> BlockAnd<Operand<'tcx>> {
+        // 関数ポインタへ型強制される式は関数アイテム、キャプチャを行わないクロージャ、
+        // またはローカル変数のパスであり、副作用をもたない。その値は、型が表す関数のアドレスとなる
+        if let Some(ty) = self.typeck_results.fn_ptr_coercion(expr.sir_id.local_id) {
+            let (TyKind::FnDef(def_id) | TyKind::Closure(def_id, _)) =
+                *self.node_ty(expr.sir_id).kind()
+            else {
+                panic!("bug: 関数アイテムではない式が関数ポインタへ型強制された: {expr:?}");
+            };
+            return block.and(Operand::Constant(Box::new(ConstOperand {
+                span: expr.span,
+                ty,
+                const_: ConstValue::FnPtr(def_id),
+            })));
+        }
+
+        match expr.kind {
+            ExprKind::Lit(lit) => block.and(self.lit_to_operand(expr, lit)),
+            ExprKind::Path(sir::Path {
+                res: Res::Local(var_id),
+                ..
+            }) => block.and(Operand::Copy(self.var_local(var_id).into())),
+            ExprKind::Path(sir::Path {
+                res: Res::Def(DefKind::Fn, _),
+                ..
+            }) => block.and(Operand::Constant(Box::new(ConstOperand {
+                span: expr.span,
+                ty: self.node_ty(expr.sir_id),
+                const_: ConstValue::ZeroSized,
+            }))),
+            ExprKind::Closure(closure) => {
+                self.capture_by_value(block, expr.span, closure);
+                block.and(Operand::Constant(Box::new(ConstOperand {
+                    span: expr.span,
+                    ty: self.node_ty(expr.sir_id),
+                    const_: ConstValue::ZeroSized,
+                })))
+            }
+            _ => {
+                let temp = unpack!(block = self.as_temp(block, expr));
+                block.and(Operand::Copy(temp.into()))
+            }
+        }
+    }
+
+    /// 複数の式を左から順に評価し、被演算子の列を返す。
+    ///
+    /// 後に評価される式が変数に代入する可能性がある場合、先に読んだ変数の値が
+    /// 変わらないように、その値を一時変数に複製しておく。
+    pub(super) fn as_operands(
+        &mut self,
+        mut block: BasicBlock,
+        exprs: &[&'tcx sir::Expr<'tcx>],
+    ) -> BlockAnd<Vec<Operand<'tcx>>> {
+        let mut operands = Vec::with_capacity(exprs.len());
+
+        for (i, expr) in exprs.iter().enumerate() {
+            let is_local = matches!(
+                expr.kind,
+                ExprKind::Path(sir::Path {
+                    res: Res::Local(_),
+                    ..
+                })
+            );
+            let operand = if is_local && exprs[i + 1..].iter().any(|expr| self.may_assign(expr)) {
+                let temp = unpack!(block = self.as_temp(block, expr));
+                Operand::Copy(temp.into())
+            } else {
+                unpack!(block = self.as_operand(block, expr))
+            };
+            operands.push(operand);
+        }
+
+        block.and(operands)
+    }
+
+    /// 式を評価し、その値を格納した新しい一時変数を返す。
+    pub(super) fn as_temp(
+        &mut self,
+        mut block: BasicBlock,
+        expr: &'tcx sir::Expr<'tcx>,
+    ) -> BlockAnd<Local> {
+        let temp = self.new_temp(self.expr_ty(expr), expr.span);
+        unpack!(block = self.expr_into_dest(temp.into(), block, expr));
+        block.and(temp)
+    }
+
+    fn lit_to_operand(&self, expr: &'tcx sir::Expr<'tcx>, lit: &sir::Lit) -> Operand<'tcx> {
+        let const_ = match lit.node {
+            LitKind::Str(symbol) => ConstValue::Str(symbol),
+            LitKind::Char(c) => ConstValue::Char(c),
+            LitKind::Int(n) => ConstValue::Int(n),
+            LitKind::Float(symbol) => ConstValue::Float(symbol),
+            LitKind::Bool(b) => ConstValue::Bool(b),
+            LitKind::Err(_) => panic!("bug: 不正なリテラルから MIR を構築しようとした"),
+        };
+
+        Operand::Constant(Box::new(ConstOperand {
+            span: expr.span,
+            ty: self.node_ty(expr.sir_id),
+            const_,
+        }))
+    }
+
+    /// 整数型 `ty` の定数。`bits` は型の幅の 2 の補数表現。
+    fn int_constant(&self, span: Span, ty: Ty<'tcx>, bits: u128) -> Operand<'tcx> {
+        Operand::Constant(Box::new(ConstOperand {
+            span,
+            ty,
+            const_: ConstValue::Int(bits),
+        }))
+    }
+
+    /// 式の評価がローカル変数への代入を含む可能性があるかどうか。
+    ///
+    /// クロージャの呼び出しは、参照によってキャプチャした変数に代入する可能性がある。
+    fn may_assign(&self, expr: &sir::Expr<'tcx>) -> bool {
+        match expr.kind {
+            ExprKind::Block(_) | ExprKind::If(..) | ExprKind::Assign(..) => true,
+            ExprKind::Binary(_, lhs, rhs) => self.may_assign(lhs) || self.may_assign(rhs),
+            ExprKind::Unary(_, operand) => self.may_assign(operand),
+            ExprKind::Call(callee, args) => {
+                self.node_ty(callee.sir_id).is_closure()
+                    || self.may_assign(callee)
+                    || args.iter().any(|arg| self.may_assign(arg))
+            }
+            ExprKind::Lit(_) | ExprKind::Path(_) | ExprKind::Closure(_) | ExprKind::Err(_) => false,
+        }
+    }
+
+    fn bool_constant(&self, span: Span, b: bool) -> Operand<'tcx> {
+        Operand::Constant(Box::new(ConstOperand {
+            span,
+            ty: self.tcx.types.bool,
+            const_: ConstValue::Bool(b),
+        }))
+    }
+}
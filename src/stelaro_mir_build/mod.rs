@@ -0,0 +1,288 @@
+//! 型検査済みの SIR から MIR を構築する。
+//!
+//! rustc の `rustc_mir_build` に基づいて設計されています。
+//! rustc とは異なり THIR を経由せず、SIR と `TypeckResults` から直接構築する。
+
+mod closure;
+mod expr;
+mod stmt;
+
+use std::collections::HashMap;
+
+use crate::stelaro_common::{Ident, IndexVec, LocalDefId, Span};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BasicBlockData, Body, ConstOperand, ConstValue, Local, LocalDecl, Operand, Place,
+    RETURN_PLACE, Rvalue, SourceInfo, Statement, StatementKind, Terminator, TerminatorKind,
+    VarDebugInfo, simplify,
+};
+use crate::stelaro_sir::sir::{self, ItemKind, MaybeOwner, OwnerNode, PatKind};
+use crate::stelaro_sir::sir_id::SirId;
+use crate::stelaro_sir_typecheck::result::TypeckResults;
+use crate::stelaro_ty::{FnSig, FnSigTys, Ty, TyKind};
+
+
+/// ステロ内のすべての関数の MIR を、定義の順に構築する。
+///
+/// クロージャは呼び出しの位置に展開されるため、通常は独立した本体をもたない。
+/// 関数ポインタへ型強制されたクロージャに限り、それを含む関数の後に本体を構築する。
+/// 型検査でエラーが発生した関数は対象としない。
+pub fn build_mir<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>,
+) -> Vec<Body<'tcx>> {
+    let mut bodies = Vec::new();
+
+    for (def_id, owner) in tcx.sir_stelo(()).owners.iter_enumerated() {
+        let MaybeOwner::Owner(info) = owner else {
+            continue;
+        };
+
+        let OwnerNode::Item(sir::Item {
+            kind: ItemKind::Fn { body, .. },
+            span,
+            ..
+        }) = info.nodes.node()
+        else {
+            continue;
+        };
+
+        let results = typeck_results[&def_id];
+        if results.tainted_by_errors {
+            continue;
+        }
+
+        let fn_sig = results
+            .fn_sig
+            .clone()
+            .expect("bug: 関数のシグネチャが記録されていない");
+        let builder = Builder::new(tcx, def_id, results);
+        bodies.push(builder.build(fn_sig, tcx.sir_body(*body), *span));
+
+        for (closure_id, sig_tys) in coerced_closures(results) {
+            let closure = tcx.expect_sir_closure(closure_id);
+            let body = tcx.sir_body(closure.body);
+            let span = Span::from(closure.fn_decl_span.start..body.value.span.end);
+            let builder = Builder::new(tcx, closure_id, results);
+            bodies.push(builder.build(sig_tys.to_fn_sig(), body, span));
+        }
+    }
+
+    bodies
+}
+
+/// 関数ポインタへ型強制されたクロージャと、そのシグネチャを定義の順に返す。
+fn coerced_closures<'tcx>(results: &TypeckResults<'tcx>) -> Vec<(LocalDefId, FnSigTys<'tcx>)> {
+    let mut closures = results
+        .fn_ptr_coercions()
+        .filter_map(|(id, _)| match *results.node_type(id)?.kind() {
+            TyKind::Closure(def_id, sig_tys) => Some((def_id.expect_local(), sig_tys)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    closures.sort_unstable_by_key(|&(def_id, _)| def_id.local_def_index);
+    closures.dedup_by_key(|&mut (def_id, _)| def_id);
+    closures
+}
+
+/// 基本ブロックと値の組。
+/// MIR を構築する関数は、処理を続けるべきブロックをこの形で返す。
+#[must_use = "処理を続けるブロックを使用しなければならない"]
+struct BlockAnd<T>(BasicBlock, T);
+
+trait BlockAndExtension {
+    fn and<T>(self, v: T) -> BlockAnd<T>;
+    fn unit(self) -> BlockAnd<()>;
+}
+
+impl BlockAndExtension for BasicBlock {
+    fn and<T>(self, v: T) -> BlockAnd<T> {
+        BlockAnd(self, v)
+    }
+
+    fn unit(self) -> BlockAnd<()> {
+        BlockAnd(self, ())
+    }
+}
+
+/// `BlockAnd<T>` からブロックを取り出して変数に代入し、値を返す。
+/// 値が `()` の場合は、ブロックそのものを返す。
+macro_rules! unpack {
+    ($x:ident = $c:expr) => {{
+        let BlockAnd(b, v) = $c;
+        $x = b;
+        v
+    }};
+
+    ($c:expr) => {{
+        let BlockAnd(b, ()) = $c;
+        b
+    }};
+}
+use unpack;
+
+/// `break` と `continue` の移動先。
+struct LoopScope {
+    /// ループ文の `SirId`。`Destination::target_id` と対応する。
+    loop_id: SirId,
+    continue_block: BasicBlock,
+    break_block: BasicBlock,
+}
+
+/// 呼び出しの位置に展開したクロージャの本体における、`return` の移動先。
+struct ReturnScope {
+    /// クロージャの戻り値を格納する一時変数。
+    return_place: Place,
+    exit_block: BasicBlock,
+}
+
+struct Builder<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    def_id: LocalDefId,
+    typeck_results: &'a TypeckResults<'tcx>,
+
+    basic_blocks: IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+    local_decls: IndexVec<Local, LocalDecl<'tcx>>,
+    var_debug_info: Vec<VarDebugInfo>,
+
+    /// 束縛パターンの `SirId` から、その変数を格納するローカル変数へのマップ。
+    var_indices: HashMap<SirId, Local>,
+
+    loop_scopes: Vec<LoopScope>,
+
+    /// 展開中のクロージャの `return` の移動先。空であれば、`return` は関数から戻る。
+    return_scopes: Vec<ReturnScope>,
+
+    /// 値によってキャプチャされた変数の複製を格納するローカル変数。
+    /// クロージャと、キャプチャされた変数の `SirId` の組をキーとする。
+    closure_envs: HashMap<(LocalDefId, SirId), Local>,
+}
+
+impl<'a, 'tcx> Builder<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, def_id: LocalDefId, typeck_results: &'a TypeckResults<'tcx>) -> Self {
+        Self {
+            tcx,
+            def_id,
+            typeck_results,
+            basic_blocks: IndexVec::new(),
+            local_decls: IndexVec::new(),
+            var_debug_info: Vec::new(),
+            var_indices: HashMap::new(),
+            loop_scopes: Vec::new(),
+            return_scopes: Vec::new(),
+            closure_envs: HashMap::new(),
+        }
+    }
+
+    fn build(mut self, fn_sig: FnSig<'tcx>, body: &'tcx sir::Body<'tcx>, span: Span) -> Body<'tcx> {
+        self.local_decls.push(LocalDecl {
+            ty: fn_sig.output,
+            span,
+        });
+
+        for param in body.params {
+            let ty = self.node_ty(param.pat.sir_id);
+            let local = self.local_decls.push(LocalDecl {
+                ty,
+                span: param.span,
+            });
+
+            if let PatKind::Binding(var_id, ident) = param.pat.kind {
+                self.declare_var(var_id, ident, local);
+            }
+        }
+
+        let mut block = self.basic_blocks.push(BasicBlockData::new(None));
+        unpack!(block = self.expr_into_dest(RETURN_PLACE.into(), block, body.value));
+
+        // 本体が発散する場合、本体の末尾には到達しない
+        let kind = if self.node_ty(body.value.sir_id).is_never() {
+            TerminatorKind::Unreachable
+        } else {
+            TerminatorKind::Return
+        };
+        self.terminate(block, body.value.span, kind);
+
+        let mut mir = Body {
+            def_id: self.def_id,
+            basic_blocks: self.basic_blocks,
+            local_decls: self.local_decls,
+            var_debug_info: self.var_debug_info,
+            arg_count: body.params.len(),
+            span,
+        };
+        simplify::remove_dead_blocks(&mut mir);
+
+        mir
+    }
+
+    fn node_ty(&self, sir_id: SirId) -> Ty<'tcx> {
+        self.typeck_results
+            .node_type(sir_id.local_id)
+            .unwrap_or_else(|| panic!("bug: {sir_id:?} の型が記録されていない"))
+    }
+
+    /// 式の値の型。関数ポインタへ型強制される式の場合は、強制後の型を返す。
+    fn expr_ty(&self, expr: &sir::Expr<'tcx>) -> Ty<'tcx> {
+        self.typeck_results
+            .fn_ptr_coercion(expr.sir_id.local_id)
+            .unwrap_or_else(|| self.node_ty(expr.sir_id))
+    }
+
+    fn new_block(&mut self) -> BasicBlock {
+        self.basic_blocks.push(BasicBlockData::new(None))
+    }
+
+    fn new_temp(&mut self, ty: Ty<'tcx>, span: Span) -> Local {
+        self.local_decls.push(LocalDecl { ty, span })
+    }
+
+    fn declare_var(&mut self, var_id: SirId, ident: Ident, local: Local) {
+        self.var_indices.insert(var_id, local);
+        self.var_debug_info.push(VarDebugInfo {
+            name: ident.name,
+            span: ident.span,
+            local,
+        });
+    }
+
+    fn var_local(&self, var_id: SirId) -> Local {
+        *self
+            .var_indices
+            .get(&var_id)
+            .unwrap_or_else(|| panic!("bug: 変数 {var_id:?} が宣言されていない"))
+    }
+
+    fn push(&mut self, block: BasicBlock, span: Span, kind: StatementKind<'tcx>) {
+        self.basic_blocks[block].statements.push(Statement {
+            source_info: SourceInfo { span },
+            kind,
+        });
+    }
+
+    fn push_assign(&mut self, block: BasicBlock, span: Span, place: Place, rvalue: Rvalue<'tcx>) {
+        self.push(block, span, StatementKind::Assign(Box::new((place, rvalue))));
+    }
+
+    fn push_assign_unit(&mut self, block: BasicBlock, span: Span, place: Place) {
+        let unit = Operand::Constant(Box::new(ConstOperand {
+            span,
+            ty: self.tcx.types.unit,
+            const_: ConstValue::ZeroSized,
+        }));
+        self.push_assign(block, span, place, Rvalue::Use(unit));
+    }
+
+    fn terminate(&mut self, block: BasicBlock, span: Span, kind: TerminatorKind<'tcx>) {
+        let data = &mut self.basic_blocks[block];
+        assert!(data.terminator.is_none(), "bug: {block:?} は既に終端している");
+        data.terminator = Some(Terminator {
+            source_info: SourceInfo { span },
+            kind,
+        });
+    }
+
+    fn goto(&mut self, from: BasicBlock, span: Span, target: BasicBlock) {
+        self.terminate(from, span, TerminatorKind::Goto { target });
+    }
+}
@@ -0,0 +1,150 @@
+use crate::stelaro_mir::{
+    BasicBlock, LocalDecl, Place, RETURN_PLACE, StatementKind, TerminatorKind,
+};
+use crate::stelaro_sir::sir::{self, ExprKind, PatKind, StmtKind};
+use crate::stelaro_sir::sir_id::SirId;
+
+use super::{BlockAnd, BlockAndExtension, Builder, LoopScope, unpack};
+
+
+impl<'a, 'tcx> Builder<'a, 'tcx> {
+    /// ブロックを評価し、その値を `destination` に格納する。
+    pub(super) fn ast_block_into_dest(
+        &mut self,
+        destination: Place,
+        mut block: BasicBlock,
+        ast_block: &'tcx sir::Block<'tcx>,
+    ) -> BlockAnd<()> {
+        for stmt in ast_block.stmts {
+            unpack!(block = self.stmt(block, stmt));
+        }
+
+        match ast_block.expr {
+            Some(tail) => unpack!(block = self.expr_into_dest(destination, block, tail)),
+            // 発散するブロックは値をもたない
+            None if self.node_ty(ast_block.sir_id).is_never() => {}
+            None => self.push_assign_unit(block, ast_block.span, destination),
+        }
+
+        block.unit()
+    }
+
+    fn stmt(&mut self, mut block: BasicBlock, stmt: &'tcx sir::Stmt<'tcx>) -> BlockAnd<()> {
+        match stmt.kind {
+            StmtKind::Let(local) => {
+                match local.pat.kind {
+                    PatKind::Binding(var_id, ident) => {
+                        let ty = self.node_ty(local.pat.sir_id);
+                        let var = self.local_decls.push(LocalDecl {
+                            ty,
+                            span: local.pat.span,
+                        });
+                        self.declare_var(var_id, ident, var);
+                        self.push(block, stmt.span, StatementKind::StorageLive(var));
+
+                        if let Some(init) = local.init {
+                            unpack!(block = self.expr_into_dest(var.into(), block, init));
+                        }
+                    }
+                    // `let _ = init;` は初期化式を評価して値を捨てる
+                    PatKind::WildCard => {
+                        if let Some(init) = local.init {
+                            unpack!(block = self.as_temp(block, init));
+                        }
+                    }
+                }
+                block.unit()
+            }
+            // アイテムは自身のオーナーで構築される
+            StmtKind::Item(_) => block.unit(),
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => self.stmt_expr(block, expr),
+            StmtKind::Break(destination, value) => {
+                if let Some(value) = value {
+                    unpack!(block = self.as_temp(block, value));
+                }
+
+                let loop_id = destination
+                    .target_id
+                    .expect("bug: ループの外の `break` から MIR を構築しようとした");
+                let break_block = self.loop_scope(loop_id).break_block;
+                self.goto(block, stmt.span, break_block);
+
+                // 後続の文は到達しないブロックに置かれる
+                self.new_block().unit()
+            }
+            StmtKind::Continue(destination) => {
+                let loop_id = destination
+                    .target_id
+                    .expect("bug: ループの外の `continue` から MIR を構築しようとした");
+                let continue_block = self.loop_scope(loop_id).continue_block;
+                self.goto(block, stmt.span, continue_block);
+
+                self.new_block().unit()
+            }
+            StmtKind::Return(value) => {
+                // 展開したクロージャの本体の中では、`return` は本体の後に移る
+                let scope = self
+                    .return_scopes
+                    .last()
+                    .map(|scope| (scope.return_place, scope.exit_block));
+                let return_place = scope.map_or(RETURN_PLACE.into(), |(place, _)| place);
+
+                match value {
+                    Some(value) => {
+                        unpack!(block = self.expr_into_dest(return_place, block, value));
+                    }
+                    None => self.push_assign_unit(block, stmt.span, return_place),
+                }
+                match scope {
+                    Some((_, exit_block)) => self.goto(block, stmt.span, exit_block),
+                    None => self.terminate(block, stmt.span, TerminatorKind::Return),
+                }
+
+                self.new_block().unit()
+            }
+            StmtKind::Loop(body, _, _) => {
+                let loop_block = self.new_block();
+                let break_block = self.new_block();
+                self.goto(block, stmt.span, loop_block);
+
+                self.loop_scopes.push(LoopScope {
+                    loop_id: stmt.sir_id,
+                    continue_block: loop_block,
+                    break_block,
+                });
+
+                let mut body_block = loop_block;
+                for stmt in body.stmts {
+                    unpack!(body_block = self.stmt(body_block, stmt));
+                }
+                if let Some(tail) = body.expr {
+                    unpack!(body_block = self.stmt_expr(body_block, tail));
+                }
+                self.goto(body_block, body.span, loop_block);
+
+                self.loop_scopes.pop();
+
+                break_block.unit()
+            }
+        }
+    }
+
+    /// 値を使用しない式を評価する。
+    fn stmt_expr(&mut self, mut block: BasicBlock, expr: &'tcx sir::Expr<'tcx>) -> BlockAnd<()> {
+        match expr.kind {
+            ExprKind::Assign(lhs, rhs, _) => self.assign_expr(block, lhs, rhs),
+            _ => {
+                unpack!(block = self.as_temp(block, expr));
+                block.unit()
+            }
+        }
+    }
+
+    fn loop_scope(&self, loop_id: SirId) -> &LoopScope {
+        self.loop_scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.loop_id == loop_id)
+            .unwrap_or_else(|| panic!("bug: ループ {loop_id:?} のスコープが見つからない"))
+    }
+}
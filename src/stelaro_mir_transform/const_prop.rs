@@ -0,0 +1,339 @@
+//! 定数の伝播と畳み込み。
+
+use std::collections::HashMap;
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::{IndexVec, Symbol};
+use crate::stelaro_mir::{
+    BinOp, Body, ConstOperand, ConstValue, Local, Operand, Rvalue, StatementKind, TerminatorKind,
+};
+use crate::stelaro_ty::{Ty, TyKind, ty::FloatTy};
+
+use super::OptimizationStats;
+
+
+/// 一度だけ代入される変数に定数が代入される場合、その変数の読み出しを定数で置き換え、
+/// 被演算子がすべて定数となった演算を畳み込む。
+///
+/// 初期化の検査を通過した MIR では、変数の読み出しは必ずその変数への代入の後に行われる。
+/// そのため、代入が一度しかない変数の値は、どの読み出しの時点でもその代入の値である。
+/// 引数は関数の入口で暗黙に代入されるため対象としない。
+pub(super) fn propagate_constants<'tcx>(body: &mut Body<'tcx>, stats: &mut OptimizationStats) {
+    let single_assignments = find_single_assignments(body);
+    let mut consts: HashMap<Local, ConstOperand<'tcx>> = HashMap::new();
+
+    // 定数が分かった変数が、別の変数の定数を決めることがあるため、変化がなくなるまで繰り返す
+    loop {
+        let mut changed = false;
+
+        for data in body.basic_blocks.iter_mut() {
+            for statement in &mut data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &mut statement.kind else {
+                    continue;
+                };
+
+                changed |= propagate_into_rvalue(rvalue, &consts, stats);
+                if let Some(folded) = fold_rvalue(rvalue, body.local_decls[place.local].ty) {
+                    *rvalue = Rvalue::Use(Operand::Constant(Box::new(folded)));
+                    stats.folded_rvalues += 1;
+                    changed = true;
+                }
+
+                if single_assignments[place.local]
+                    && !consts.contains_key(&place.local)
+                    && let Rvalue::Use(Operand::Constant(constant)) = rvalue
+                {
+                    consts.insert(place.local, (**constant).clone());
+                    changed = true;
+                }
+            }
+
+            let terminator = data.terminator.as_mut().unwrap();
+            match &mut terminator.kind {
+                TerminatorKind::SwitchInt { discr, .. }
+                | TerminatorKind::Assert { cond: discr, .. } => {
+                    changed |= propagate_into_operand(discr, &consts, stats);
+                }
+                TerminatorKind::Call { func, args, .. } => {
+                    changed |= propagate_into_operand(func, &consts, stats);
+                    for arg in args {
+                        changed |= propagate_into_operand(arg, &consts, stats);
+                    }
+                }
+                TerminatorKind::Goto { .. }
+                | TerminatorKind::Return
+                | TerminatorKind::Unreachable => {}
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// 関数本体でちょうど一度だけ代入される、引数以外のローカル変数。
+fn find_single_assignments(body: &Body<'_>) -> IndexVec<Local, bool> {
+    let mut counts = IndexVec::<Local, usize>::from_elem_n(0, body.local_decls.len());
+
+    for data in body.basic_blocks.iter() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(box (place, _)) = &statement.kind {
+                counts[place.local] += 1;
+            }
+        }
+        if let TerminatorKind::Call { destination, .. } = &data.terminator().kind {
+            counts[destination.local] += 1;
+        }
+    }
+
+    for arg in body.args_iter() {
+        counts[arg] = 0;
+    }
+
+    let mut single = IndexVec::from_elem_n(false, counts.len());
+    for (local, &count) in counts.iter_enumerated() {
+        single[local] = count == 1;
+    }
+    single
+}
+
+fn propagate_into_rvalue<'tcx>(
+    rvalue: &mut Rvalue<'tcx>,
+    consts: &HashMap<Local, ConstOperand<'tcx>>,
+    stats: &mut OptimizationStats,
+) -> bool {
+    match rvalue {
+        Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => {
+            propagate_into_operand(operand, consts, stats)
+        }
+        Rvalue::BinaryOp(_, box (lhs, rhs)) => {
+            let lhs = propagate_into_operand(lhs, consts, stats);
+            let rhs = propagate_into_operand(rhs, consts, stats);
+            lhs || rhs
+        }
+    }
+}
+
+fn propagate_into_operand<'tcx>(
+    operand: &mut Operand<'tcx>,
+    consts: &HashMap<Local, ConstOperand<'tcx>>,
+    stats: &mut OptimizationStats,
+) -> bool {
+    let Operand::Copy(place) = operand else {
+        return false;
+    };
+    let Some(constant) = consts.get(&place.local) else {
+        return false;
+    };
+
+    *operand = Operand::Constant(Box::new(constant.clone()));
+    stats.propagated_operands += 1;
+    true
+}
+
+/// 被演算子がすべて定数である演算を評価する。`ty` は演算の結果の型。
+///
+/// オーバーフローやゼロ除算のように、実行時の振る舞いに任せるべき演算は畳み込まない。
+fn fold_rvalue<'tcx>(rvalue: &Rvalue<'tcx>, ty: Ty<'tcx>) -> Option<ConstOperand<'tcx>> {
+    let (span, const_) = match rvalue {
+        Rvalue::BinaryOp(op, box (Operand::Constant(lhs), Operand::Constant(rhs))) => {
+            (lhs.span.merge(&rhs.span), eval_binary(*op, lhs, rhs)?)
+        }
+        Rvalue::UnaryOp(op, Operand::Constant(operand)) => {
+            (operand.span, eval_unary(*op, operand)?)
+        }
+        // `MIN / -1` 以外の除算はオーバーフローしないため、除数が -1 でなければ被除数によらない
+        Rvalue::BinaryOp(BinOp::DivOverflows, box (_, Operand::Constant(rhs)))
+            if !is_minus_one(rhs)? =>
+        {
+            (rhs.span, ConstValue::Bool(false))
+        }
+        Rvalue::Use(_) | Rvalue::BinaryOp(..) | Rvalue::UnaryOp(..) => return None,
+    };
+
+    Some(ConstOperand { span, ty, const_ })
+}
+
+/// 整数型の値を、符号の有無とビット幅とともに表す。
+#[derive(Clone, Copy)]
+struct IntValue {
+    signed: bool,
+    bit_width: u32,
+}
+
+impl IntValue {
+    fn of(ty: Ty<'_>) -> Option<IntValue> {
+        match *ty.kind() {
+            TyKind::Int(int_ty) => Some(IntValue {
+                signed: true,
+                bit_width: int_ty.bit_width(),
+            }),
+            TyKind::Uint(uint_ty) => Some(IntValue {
+                signed: false,
+                bit_width: uint_ty.bit_width(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// ビット列を、型に応じて符号拡張した値として読む。
+    fn to_i128(self, bits: u128) -> i128 {
+        let shift = 128 - self.bit_width;
+        if self.signed {
+            ((bits << shift) as i128) >> shift
+        } else {
+            ((bits << shift) >> shift) as i128
+        }
+    }
+
+    /// 値が型の範囲に収まる場合、そのビット列を返す。
+    fn to_bits(self, value: i128) -> Option<u128> {
+        let (min, max) = if self.signed {
+            let max = i128::MAX >> (128 - self.bit_width);
+            (-max - 1, max)
+        } else {
+            // `u128` の上半分は、呼び出し側で除外している
+            (0, (u128::MAX >> (128 - self.bit_width)).min(i128::MAX as u128) as i128)
+        };
+
+        if value < min || value > max {
+            return None;
+        }
+
+        let mask = u128::MAX >> (128 - self.bit_width);
+        Some(value as u128 & mask)
+    }
+}
+
+fn eval_binary(op: BinOp, lhs: &ConstOperand<'_>, rhs: &ConstOperand<'_>) -> Option<ConstValue> {
+    match (lhs.const_, rhs.const_) {
+        (ConstValue::Int(a), ConstValue::Int(b)) => {
+            let int = IntValue::of(lhs.ty)?;
+
+            // `u128` の上半分は `i128` で表せないため畳み込まない
+            if !int.signed && int.bit_width == 128 && (a > i128::MAX as u128 || b > i128::MAX as u128)
+            {
+                return None;
+            }
+
+            let (a, b) = (int.to_i128(a), int.to_i128(b));
+            if op.is_overflow_check() {
+                return Some(ConstValue::Bool(overflows(op, int, a, b)));
+            }
+
+            let value = match op {
+                BinOp::Add => a.checked_add(b)?,
+                BinOp::Sub => a.checked_sub(b)?,
+                BinOp::Mul => a.checked_mul(b)?,
+                BinOp::Div => a.checked_div(b)?,
+                BinOp::Rem => a.checked_rem(b)?,
+                _ => return Some(ConstValue::Bool(compare(op, a, b))),
+            };
+            int.to_bits(value).map(ConstValue::Int)
+        }
+        (ConstValue::Float(a), ConstValue::Float(b)) => {
+            let TyKind::Float(float_ty) = *lhs.ty.kind() else {
+                return None;
+            };
+
+            // `f32` の演算は `f32` のまま行い、`f64` で計算した結果を丸めることによる誤差を避ける
+            macro_rules! eval_float {
+                ($float:ty) => {{
+                    let a = a.as_str().parse::<$float>().ok()?;
+                    let b = b.as_str().parse::<$float>().ok()?;
+                    let value = match op {
+                        BinOp::Add => a + b,
+                        BinOp::Sub => a - b,
+                        BinOp::Mul => a * b,
+                        BinOp::Div => a / b,
+                        BinOp::Rem => a % b,
+                        _ => return Some(ConstValue::Bool(compare(op, a, b))),
+                    };
+                    float_constant(value.is_finite(), format!("{value:?}"))
+                }};
+            }
+
+            match float_ty {
+                FloatTy::F32 => eval_float!(f32),
+                FloatTy::F64 => eval_float!(f64),
+            }
+        }
+        (ConstValue::Bool(a), ConstValue::Bool(b)) if op.is_comparison() => {
+            Some(ConstValue::Bool(compare(op, a, b)))
+        }
+        (ConstValue::Char(a), ConstValue::Char(b)) if op.is_comparison() => {
+            Some(ConstValue::Bool(compare(op, a, b)))
+        }
+        (ConstValue::Str(a), ConstValue::Str(b)) => match op {
+            BinOp::Add => Some(ConstValue::Str(Symbol::intern(&format!("{a}{b}")))),
+            _ => Some(ConstValue::Bool(compare(op, a.as_str(), b.as_str()))),
+        },
+        _ => None,
+    }
+}
+
+/// 整数の定数が -1 であるかどうか。符号なし整数は -1 にならない。
+fn is_minus_one(constant: &ConstOperand<'_>) -> Option<bool> {
+    let ConstValue::Int(bits) = constant.const_ else {
+        return None;
+    };
+    let int = IntValue::of(constant.ty)?;
+    Some(int.signed && int.to_i128(bits) == -1)
+}
+
+/// オーバーフローを検査する演算を評価する。
+fn overflows(op: BinOp, int: IntValue, a: i128, b: i128) -> bool {
+    let value = match op {
+        BinOp::AddOverflows => a.checked_add(b),
+        BinOp::SubOverflows => a.checked_sub(b),
+        BinOp::MulOverflows => a.checked_mul(b),
+        // 符号なし整数の `b` は負にならないため、`MIN / -1` は符号付き整数でのみ起こる
+        BinOp::DivOverflows => {
+            return b == -1 && a.checked_neg().and_then(|value| int.to_bits(value)).is_none();
+        }
+        _ => unreachable!("bug: オーバーフローを検査する演算ではない: {op:?}"),
+    };
+    value.and_then(|value| int.to_bits(value)).is_none()
+}
+
+fn compare<T: PartialOrd>(op: BinOp, a: T, b: T) -> bool {
+    match op {
+        BinOp::Eq => a == b,
+        BinOp::Ne => a != b,
+        BinOp::Lt => a < b,
+        BinOp::Le => a <= b,
+        BinOp::Gt => a > b,
+        BinOp::Ge => a >= b,
+        _ => unreachable!("bug: 比較演算子ではない: {op:?}"),
+    }
+}
+
+fn eval_unary(op: UnOp, operand: &ConstOperand<'_>) -> Option<ConstValue> {
+    match (op, operand.const_) {
+        (UnOp::Not, ConstValue::Bool(b)) => Some(ConstValue::Bool(!b)),
+        (UnOp::Not, ConstValue::Int(bits)) => {
+            let int = IntValue::of(operand.ty)?;
+            let mask = u128::MAX >> (128 - int.bit_width);
+            Some(ConstValue::Int(!bits & mask))
+        }
+        (UnOp::Neg, ConstValue::Int(bits)) => {
+            let int = IntValue::of(operand.ty)?;
+            let value = int.to_i128(bits).checked_neg()?;
+            int.to_bits(value).map(ConstValue::Int)
+        }
+        // 符号の反転は丸めを伴わないため、`f32` であっても `f64` で計算してよい
+        (UnOp::Neg, ConstValue::Float(symbol)) => {
+            let value = -symbol.as_str().parse::<f64>().ok()?;
+            float_constant(value.is_finite(), format!("{value:?}"))
+        }
+        _ => None,
+    }
+}
+
+/// 浮動小数点数の計算結果を定数にする。
+/// 有限でない値はリテラルとして表せないため、畳み込まない。
+fn float_constant(is_finite: bool, text: String) -> Option<ConstValue> {
+    // インターナーは文字列を所有しないため、計算した文字列はリークさせて生存させる
+    is_finite.then(|| ConstValue::Float(Symbol::intern(text.leak())))
+}
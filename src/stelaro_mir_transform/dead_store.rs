@@ -0,0 +1,88 @@
+//! 読まれることのない変数への代入の除去。
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_mir::{
+    Body, Local, Operand, RETURN_PLACE, Rvalue, StatementKind, TerminatorKind,
+};
+
+use super::OptimizationStats;
+
+
+/// 関数本体のどこからも読まれない変数への代入と、その変数の `StorageLive` を取り除く。
+///
+/// 右辺値の計算は副作用を持たないため、取り除いても振る舞いは変わらない。
+/// 関数の呼び出しは副作用を持ちうるため、その結果が読まれなくても残す。
+/// 戻り値は `Return` によって暗黙に読まれるため対象としない。
+pub(super) fn remove_dead_stores(body: &mut Body<'_>, stats: &mut OptimizationStats) {
+    // 取り除いた代入が他の変数を読んでいた場合、その変数も読まれなくなることがある
+    loop {
+        let used = find_used_locals(body);
+        let mut removed = 0;
+
+        for data in body.basic_blocks.iter_mut() {
+            data.statements.retain(|statement| {
+                let local = match &statement.kind {
+                    StatementKind::Assign(box (place, _)) => place.local,
+                    StatementKind::StorageLive(local) => *local,
+                };
+
+                let is_dead = local != RETURN_PLACE && !used[local];
+                removed += is_dead as usize;
+                !is_dead
+            });
+        }
+
+        stats.removed_statements += removed;
+        if removed == 0 {
+            break;
+        }
+    }
+}
+
+/// 読まれる可能性のあるローカル変数。
+fn find_used_locals(body: &Body<'_>) -> IndexVec<Local, bool> {
+    let mut used = IndexVec::from_elem_n(false, body.local_decls.len());
+    let visit_operand = |used: &mut IndexVec<Local, bool>, operand: &Operand<'_>| {
+        if let Operand::Copy(place) = operand {
+            used[place.local] = true;
+        }
+    };
+
+    for data in body.basic_blocks.iter() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(box (_, rvalue)) = &statement.kind {
+                match rvalue {
+                    Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) => {
+                        visit_operand(&mut used, operand);
+                    }
+                    Rvalue::BinaryOp(_, box (lhs, rhs)) => {
+                        visit_operand(&mut used, lhs);
+                        visit_operand(&mut used, rhs);
+                    }
+                }
+            }
+        }
+
+        match &data.terminator().kind {
+            TerminatorKind::SwitchInt { discr, .. } | TerminatorKind::Assert { cond: discr, .. } => {
+                visit_operand(&mut used, discr);
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } => {
+                visit_operand(&mut used, func);
+                for arg in args {
+                    visit_operand(&mut used, arg);
+                }
+                // 呼び出しの結果を格納する変数は、呼び出しとともに残す
+                used[destination.local] = true;
+            }
+            TerminatorKind::Goto { .. } | TerminatorKind::Return | TerminatorKind::Unreachable => {}
+        }
+    }
+
+    used
+}
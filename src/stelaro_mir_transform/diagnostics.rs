@@ -0,0 +1,37 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+use crate::stelaro_lint::levels::{LevelAndSource, struct_lint};
+use crate::stelaro_mir::AssertKind;
+use crate::stelaro_session::lint::builtin::{ARITHMETIC_OVERFLOW, UNCONDITIONAL_PANIC};
+
+use super::messages;
+
+pub struct DiagsMirTransform;
+
+impl<'dcx> DiagsMirTransform {
+    pub fn arithmetic_overflow(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LevelAndSource,
+        kind: AssertKind,
+        span: Span,
+    ) -> Option<Diag<'dcx, ()>> {
+        let mut diag = struct_lint(dcx, ARITHMETIC_OVERFLOW, level, span)?;
+        diag.set_message(dcx.translate(messages::ARITHMETIC_OVERFLOW, &[]));
+        diag.set_label(span, dcx.translate(kind.message(), &[]));
+
+        Some(diag)
+    }
+
+    pub fn unconditional_panic(
+        dcx: DiagCtxtHandle<'dcx>,
+        level: LevelAndSource,
+        kind: AssertKind,
+        span: Span,
+    ) -> Option<Diag<'dcx, ()>> {
+        let mut diag = struct_lint(dcx, UNCONDITIONAL_PANIC, level, span)?;
+        diag.set_message(dcx.translate(messages::UNCONDITIONAL_PANIC, &[]));
+        diag.set_label(span, dcx.translate(kind.message(), &[]));
+
+        Some(diag)
+    }
+}
@@ -0,0 +1,70 @@
+//! 常にパニックする演算のリント。
+//!
+//! rustc の `KnownPanicsLint` に基づいて設計されています。
+
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{AssertKind, BinOp, Body, ConstValue, Operand, TerminatorKind, simplify};
+use crate::stelaro_session::lint::builtin::{ARITHMETIC_OVERFLOW, UNCONDITIONAL_PANIC};
+
+use super::{OptimizationStats, const_prop, diagnostics::DiagsMirTransform, simplify_cfg};
+
+
+/// 定数の伝播によって常に失敗することが分かる `Assert` を、
+/// `arithmetic_overflow` または `unconditional_panic` のリントとして報告する。
+///
+/// 最適化と同じ伝播を本体の複製に適用するため、`body` 自身は変更しない。
+/// 到達しない分岐の中の `Assert` は、先に取り除かれるため報告されない。
+/// `std::assert` による `Assertion` はユーザーが意図したものとして扱い、報告しない。
+pub fn check_known_panics(tcx: TyCtxt<'_>, body: &Body<'_>) {
+    let mut body = body.clone();
+    let mut stats = OptimizationStats::default();
+
+    const_prop::propagate_constants(&mut body, &mut stats);
+    simplify_cfg::simplify_const_branches(&mut body, &mut stats);
+    simplify::remove_dead_blocks(&mut body);
+
+    let lint_levels = tcx.lint_levels.borrow();
+
+    for data in body.basic_blocks.iter() {
+        let terminator = data.terminator();
+        let TerminatorKind::Assert {
+            cond: Operand::Constant(constant),
+            expected,
+            msg,
+            ..
+        } = &terminator.kind
+        else {
+            continue;
+        };
+
+        if constant.const_ == ConstValue::Bool(*expected) {
+            continue;
+        }
+
+        let span = terminator.source_info.span;
+        let diag = match *msg {
+            // `i32::MIN / -1` は、rustc と同様にゼロ除算と同じ扱いで報告する
+            AssertKind::Overflow(BinOp::Div | BinOp::Rem)
+            | AssertKind::DivisionByZero
+            | AssertKind::RemainderByZero => DiagsMirTransform::unconditional_panic(
+                tcx.dcx(),
+                lint_levels.get(tcx, UNCONDITIONAL_PANIC, body.def_id),
+                *msg,
+                span,
+            ),
+            AssertKind::Overflow(_) | AssertKind::OverflowNeg => {
+                DiagsMirTransform::arithmetic_overflow(
+                    tcx.dcx(),
+                    lint_levels.get(tcx, ARITHMETIC_OVERFLOW, body.def_id),
+                    *msg,
+                    span,
+                )
+            }
+            AssertKind::Assertion => None,
+        };
+
+        if let Some(diag) = diag {
+            diag.emit();
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! MIR に対するリントで報告される診断と、最適化の統計のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    ARITHMETIC_OVERFLOW {
+        ja: "この算術演算は常にオーバーフローします",
+        en: "this arithmetic operation will overflow",
+    }
+    UNCONDITIONAL_PANIC {
+        ja: "この演算は実行時に常にパニックします",
+        en: "this operation will panic at runtime",
+    }
+    OPTIMIZATION_REPORT_TITLE {
+        ja: "MIR の最適化:",
+        en: "MIR optimizations:",
+    }
+    OPTIMIZATION_REPORT_FOLDED_RVALUES {
+        ja: "畳み込まれた演算",
+        en: "folded rvalues",
+    }
+    OPTIMIZATION_REPORT_PROPAGATED_OPERANDS {
+        ja: "伝播された定数",
+        en: "propagated constants",
+    }
+    OPTIMIZATION_REPORT_SIMPLIFIED_BRANCHES {
+        ja: "単純化された分岐",
+        en: "simplified branches",
+    }
+    OPTIMIZATION_REPORT_REMOVED_STATEMENTS {
+        ja: "削除された文",
+        en: "removed statements",
+    }
+    OPTIMIZATION_REPORT_REMOVED_BLOCKS {
+        ja: "削除された基本ブロック",
+        en: "removed basic blocks",
+    }
+}
@@ -0,0 +1,88 @@
+//! MIR の最適化パス。
+//!
+//! rustc の `rustc_mir_transform` に基づいて設計されています。
+//! 最適化はすべての検査が終わった後、バックエンドに渡す前に行う。
+
+mod const_prop;
+mod dead_store;
+mod diagnostics;
+mod known_panics;
+pub mod messages;
+mod simplify_cfg;
+
+use std::ops::AddAssign;
+
+use crate::stelaro_diagnostics::Lang;
+use crate::stelaro_mir::{Body, simplify};
+
+pub use known_panics::check_known_panics;
+
+
+/// 最適化によって畳み込まれたり、取り除かれたりしたノードの数。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizationStats {
+    /// 定数に畳み込まれた演算の数。
+    pub folded_rvalues: usize,
+    /// 定数で置き換えられた変数の読み出しの数。
+    pub propagated_operands: usize,
+    /// 条件が定数であるため無条件の移動に置き換えられた分岐の数。
+    pub simplified_branches: usize,
+    /// 取り除かれた文の数。
+    pub removed_statements: usize,
+    /// 取り除かれた基本ブロックの数。
+    pub removed_blocks: usize,
+}
+
+impl AddAssign for OptimizationStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.folded_rvalues += rhs.folded_rvalues;
+        self.propagated_operands += rhs.propagated_operands;
+        self.simplified_branches += rhs.simplified_branches;
+        self.removed_statements += rhs.removed_statements;
+        self.removed_blocks += rhs.removed_blocks;
+    }
+}
+
+impl OptimizationStats {
+    /// `-Z statistics` で表示するための文字列を返す。
+    pub fn to_report(&self, lang: Lang) -> String {
+        let rows = [
+            (messages::OPTIMIZATION_REPORT_FOLDED_RVALUES, self.folded_rvalues),
+            (messages::OPTIMIZATION_REPORT_PROPAGATED_OPERANDS, self.propagated_operands),
+            (messages::OPTIMIZATION_REPORT_SIMPLIFIED_BRANCHES, self.simplified_branches),
+            (messages::OPTIMIZATION_REPORT_REMOVED_STATEMENTS, self.removed_statements),
+            (messages::OPTIMIZATION_REPORT_REMOVED_BLOCKS, self.removed_blocks),
+        ];
+
+        let mut report = format!("{}\n", messages::OPTIMIZATION_REPORT_TITLE.format(lang, &[]));
+        for (name, count) in rows {
+            report.push_str(&format!("    {}: {count}\n", name.format(lang, &[])));
+        }
+        report
+    }
+}
+
+/// 関数本体に最適化パスを順に適用する。
+///
+/// 1. 一度だけ代入される変数の定数を伝播し、定数どうしの演算を畳み込む
+/// 2. 条件が定数である分岐を、無条件の移動に置き換える
+/// 3. 到達しなくなったブロックを取り除く
+/// 4. 読まれることのない変数への代入を取り除く
+/// 5. 一本道になったブロックを結合する
+///
+/// 発散する文の後に続くコードは、MIR の構築時に到達しないブロックに置かれるため、
+/// 3 と同じ処理で取り除かれる。
+pub fn run_optimization_passes(body: &mut Body<'_>) -> OptimizationStats {
+    let mut stats = OptimizationStats::default();
+    let num_blocks = body.basic_blocks.len();
+
+    const_prop::propagate_constants(body, &mut stats);
+    simplify_cfg::simplify_const_branches(body, &mut stats);
+    simplify::remove_dead_blocks(body);
+    dead_store::remove_dead_stores(body, &mut stats);
+    simplify_cfg::merge_blocks(body);
+    simplify::remove_dead_blocks(body);
+
+    stats.removed_blocks = num_blocks - body.basic_blocks.len();
+    stats
+}
@@ -0,0 +1,89 @@
+//! 制御フローグラフの単純化。
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_mir::{
+    BasicBlock, Body, ConstValue, Operand, START_BLOCK, TerminatorKind,
+};
+
+use super::OptimizationStats;
+
+
+/// 条件が定数である `SwitchInt` と、常に成功する `Assert` を、無条件の `Goto` に置き換える。
+///
+/// 選ばれなかった分岐先は到達しなくなるため、その後で取り除かれる。
+/// 常に失敗する `Assert` は、実行時にパニックさせるためにそのまま残す。
+pub(super) fn simplify_const_branches(body: &mut Body<'_>, stats: &mut OptimizationStats) {
+    for data in body.basic_blocks.iter_mut() {
+        let terminator = data.terminator.as_mut().unwrap();
+
+        if let TerminatorKind::Assert {
+            cond: Operand::Constant(constant),
+            expected,
+            target,
+            ..
+        } = &terminator.kind
+        {
+            if constant.const_ == ConstValue::Bool(*expected) {
+                terminator.kind = TerminatorKind::Goto { target: *target };
+                stats.simplified_branches += 1;
+            }
+            continue;
+        }
+
+        let TerminatorKind::SwitchInt {
+            discr: Operand::Constant(constant),
+            targets,
+        } = &terminator.kind
+        else {
+            continue;
+        };
+
+        let value = match constant.const_ {
+            ConstValue::Bool(b) => b as u128,
+            ConstValue::Int(bits) => bits,
+            _ => continue,
+        };
+
+        let target = targets
+            .values
+            .iter()
+            .find(|&&(v, _)| v == value)
+            .map_or(targets.otherwise, |&(_, target)| target);
+
+        terminator.kind = TerminatorKind::Goto { target };
+        stats.simplified_branches += 1;
+    }
+}
+
+/// `Goto` で移る先のブロックが他のどこからも移られない場合、そのブロックを移動元に結合する。
+///
+/// 結合されたブロックは到達しなくなるため、その後で取り除かれる。
+pub(super) fn merge_blocks(body: &mut Body<'_>) {
+    let mut predecessor_counts = IndexVec::<BasicBlock, usize>::from_elem_n(0, body.basic_blocks.len());
+    for data in body.basic_blocks.iter() {
+        for succ in data.terminator().successors() {
+            predecessor_counts[succ] += 1;
+        }
+    }
+
+    for bb in body.basic_blocks.indices() {
+        // 結合によって `bb` の終端命令が変わるため、移動先が無くなるまで繰り返す
+        while let TerminatorKind::Goto { target } = body.basic_blocks[bb].terminator().kind {
+            if target == bb || target == START_BLOCK || predecessor_counts[target] != 1 {
+                break;
+            }
+
+            let target_data = std::mem::take(&mut body.basic_blocks[target].statements);
+            let target_terminator = body.basic_blocks[target].terminator.clone();
+
+            // 結合されたブロックは、どこにも移らない空のブロックとして残す
+            body.basic_blocks[target].terminator.as_mut().unwrap().kind =
+                TerminatorKind::Unreachable;
+            predecessor_counts[target] = 0;
+
+            let data = &mut body.basic_blocks[bb];
+            data.statements.extend(target_data);
+            data.terminator = target_terminator;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::stelaro_ast::{ast::*, token::TokenKind};
+
+use super::{diagnostics::DiagsParser, parser::Parser, PResult};
+
+
+impl Parser<'_> {
+    /// Stelo の先頭に書かれた `#![...]` を可能な限り解析する
+    pub fn parse_inner_attributes(&mut self) -> PResult<Vec<Attribute>> {
+        let mut attrs = vec![];
+
+        while self.token.kind == TokenKind::Pound
+            && self.look_ahead(0).is_some_and(|t| t.kind == TokenKind::Bang)
+        {
+            attrs.push(self.parse_attribute()?);
+        }
+
+        Ok(attrs)
+    }
+
+    /// アイテムの直前に書かれた `#[...]` を可能な限り解析する
+    pub fn parse_outer_attributes(&mut self) -> PResult<Vec<Attribute>> {
+        let mut attrs = vec![];
+
+        while self.token.kind == TokenKind::Pound {
+            let attr = self.parse_attribute()?;
+
+            if attr.style == AttrStyle::Inner {
+                Err(
+                    DiagsParser::inner_attribute_not_permitted(
+                        self.dcx(),
+                        attr.span,
+                    ).emit()
+                )?
+            }
+
+            attrs.push(attr);
+        }
+
+        Ok(attrs)
+    }
+
+    /// `#[name]`, `#[name(arg, ...)]` 及び `!` を伴う内部属性を解析する
+    fn parse_attribute(&mut self) -> PResult<Attribute> {
+        let start = self.token.span;
+        self.eat(TokenKind::Pound, start)?;
+
+        let style = if self.token.kind == TokenKind::Bang {
+            self.bump();
+            AttrStyle::Inner
+        } else {
+            AttrStyle::Outer
+        };
+
+        self.eat(TokenKind::LBracket, self.token.span)?;
+
+        let name = self.parse_ident()?;
+
+        let mut args = vec![];
+
+        if self.token.kind == TokenKind::LParen {
+            self.bump();
+
+            // #[allow()] 及び #[allow(a, b,)] を許可
+            while self.token.kind != TokenKind::RParen {
+                args.push(self.parse_ident()?);
+
+                match self.token.kind {
+                    TokenKind::Comma => self.bump(),
+                    TokenKind::RParen => {},
+                    _ => {
+                        Err(
+                            DiagsParser::unexpected_token_with_expected_any(
+                                self.dcx(),
+                                self.token.kind,
+                                &[TokenKind::Comma, TokenKind::RParen],
+                                self.token.span,
+                            ).emit()
+                        )?
+                    }
+                }
+            }
+
+            self.bump();
+        }
+
+        self.eat(TokenKind::RBracket, self.token.span)?;
+
+        Ok(Attribute {
+            style,
+            name,
+            args,
+            span: start.merge(&self.prev_token.span),
+        })
+    }
+}
@@ -1,6 +1,8 @@
 use crate::stelaro_ast::token::{Lit, Token, TokenKind};
 use crate::stelaro_common::{Ident, Span};
-use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle, ErrorEmitted};
+use crate::stelaro_diagnostics::{Applicability, Diag, DiagCtxtHandle, ErrorEmitted};
+
+use super::messages;
 
 pub struct DiagsParser;
 
@@ -12,10 +14,10 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedToken.into());
-        diag.set_message(format!("予期しないトークン: `{}`", unexpected));
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN, &[("token", &unexpected)]));
         diag.set_label(
             span,
-            format!("不正なトークン`{}`が入力されました", unexpected),
+            dcx.translate(messages::UNEXPECTED_TOKEN_LABEL, &[("token", &unexpected)]),
         );
 
         diag
@@ -29,12 +31,12 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedToken.into());
-        diag.set_message(format!("予期しないトークン: `{}`", unexpected));
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN, &[("token", &unexpected)]));
         diag.set_label(
             span,
-            format!(
-                "`{}`を期待していましたが、`{}` は無効な入力です",
-                expected, unexpected
+            dcx.translate(
+                messages::UNEXPECTED_TOKEN_EXPECTED_LABEL,
+                &[("expected", &expected), ("token", &unexpected)],
             ),
         );
 
@@ -49,36 +51,23 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedToken.into());
-        diag.set_message(format!("予期しないトークン: `{}`", unexpected));
-
-        match expected.len() {
-            0 => {
-                diag.set_label(span, format!("`{}` は無効な入力です", unexpected));
-            }
-            1 => {
-                diag.set_label(
-                    span,
-                    format!(
-                        "`{}` を期待していましたが、`{}` は無効な入力です",
-                        expected[0], unexpected
-                    ),
-                );
-            }
-            _ => {
-                let expected_list = expected
-                    .iter()
-                    .map(|t| format!("`{}`", t))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                diag.set_label(
-                    span,
-                    format!(
-                        "{} のいずれかを期待していましたが、`{}` は無効な入力です",
-                        expected_list, unexpected
-                    ),
-                );
-            }
-        }
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN, &[("token", &unexpected)]));
+
+        let label = match expected {
+            [] => dcx.translate(
+                messages::UNEXPECTED_TOKEN_INVALID_LABEL,
+                &[("token", &unexpected)],
+            ),
+            [expected] => dcx.translate(
+                messages::UNEXPECTED_TOKEN_EXPECTED_LABEL,
+                &[("expected", expected), ("token", &unexpected)],
+            ),
+            _ => dcx.translate(
+                messages::UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL,
+                &[("expected", &expected_list(expected)), ("token", &unexpected)],
+            ),
+        };
+        diag.set_label(span, label);
 
         diag
     }
@@ -90,9 +79,9 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(op1.merge(&op2));
         diag.set_code(ErrorCode::ChainedComparison.into());
-        diag.set_message("連鎖した比較演算子".to_string());
-        diag.set_label(op1, "この比較は無効です".to_string());
-        diag.set_label(op2, "比較演算子が連鎖しています".to_string());
+        diag.set_message(dcx.translate(messages::CHAINED_COMPARISON, &[]));
+        diag.set_label(op1, dcx.translate(messages::CHAINED_COMPARISON_FIRST_LABEL, &[]));
+        diag.set_label(op2, dcx.translate(messages::CHAINED_COMPARISON_SECOND_LABEL, &[]));
 
         diag
     }
@@ -104,9 +93,12 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::ExpectExpression.into());
-        diag.set_message("不正な式".to_string());
-        diag.set_label(span, format!("`{}`は式ではありません", unexpected));
-        diag.set_help("これを削除するか、間に値を追加してください".to_string());
+        diag.set_message(dcx.translate(messages::EXPECT_EXPRESSION, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(messages::EXPECT_EXPRESSION_LABEL, &[("token", &unexpected)]),
+        );
+        diag.set_help(dcx.translate(messages::EXPECT_EXPRESSION_HELP, &[]));
 
         diag
     }
@@ -114,12 +106,9 @@ impl<'dcx> DiagsParser {
     pub fn prefix_increment(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::PrefixIncrement.into());
-        diag.set_message("前置インクリメント".to_string());
-        diag.set_label(
-            span,
-            "steraloは前置インクリメント演算子をもちません".to_string(),
-        );
-        diag.set_help("`lhs += 1;`を使用してください".to_string());
+        diag.set_message(dcx.translate(messages::PREFIX_INCREMENT, &[]));
+        diag.set_label(span, dcx.translate(messages::PREFIX_INCREMENT_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::PREFIX_INCREMENT_HELP, &[]));
 
         diag
     }
@@ -130,12 +119,17 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedClosingDelimiter.into());
-        diag.set_message("余分な閉じ括弧".to_string());
+        diag.set_message(dcx.translate(messages::UNEXPECTED_CLOSING_DELIMITER, &[]));
         diag.set_label(
             span,
-            "式の解析中に余分な閉じ括弧が見つかりました".to_string(),
+            dcx.translate(messages::UNEXPECTED_CLOSING_DELIMITER_LABEL, &[]),
+        );
+        diag.set_suggestion(
+            span,
+            dcx.translate(messages::UNEXPECTED_CLOSING_DELIMITER_SUGGESTION, &[]),
+            String::new(),
+            Applicability::MaybeIncorrect,
         );
-        diag.set_help("これを削除してください".to_string());
 
         diag
     }
@@ -146,8 +140,11 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedTokenForIdentifier.into());
-        diag.set_message("無効な識別子".to_string());
-        diag.set_label(span, "識別子でない予期しないトークンがあります".to_string());
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN_FOR_IDENTIFIER, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(messages::UNEXPECTED_TOKEN_FOR_IDENTIFIER_LABEL, &[]),
+        );
 
         diag
     }
@@ -158,8 +155,8 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedTokenForType.into());
-        diag.set_message("無効な型".to_string());
-        diag.set_label(span, "これは型ではありません".to_string());
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN_FOR_TYPE, &[]));
+        diag.set_label(span, dcx.translate(messages::UNEXPECTED_TOKEN_FOR_TYPE_LABEL, &[]));
 
         diag
     }
@@ -171,26 +168,39 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedNumericLiteralForIdentifier.into());
-        diag.set_message("識別子はリテラルから始めることができない".to_string());
+        diag.set_message(
+            dcx.translate(messages::UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER, &[]),
+        );
         diag.set_label(
             span,
-            format!(
-                "識別子を`{}`から始めることはできません",
-                unexpected_literal.symbol.as_str()
+            dcx.translate(
+                messages::UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER_LABEL,
+                &[("literal", &unexpected_literal.symbol.as_str())],
             ),
         );
-        diag.set_help("識別子に数値リテラルを使うことはできません".to_string());
+        diag.set_help(
+            dcx.translate(messages::UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER_HELP, &[]),
+        );
 
         diag
     }
 
-    // 式文解析時にセミコロンがない場合使用される
-    pub fn missing_semicolon(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx, ErrorEmitted> {
-        let mut diag = dcx.struct_err(span);
+    /// 式文解析時にセミコロンがない場合使用される。
+    /// `prev_span` はセミコロンが必要な文の、最後のトークンの位置を表す
+    pub fn missing_semicolon(
+        dcx: DiagCtxtHandle<'dcx>,
+        prev_span: Span,
+    ) -> Diag<'dcx, ErrorEmitted> {
+        let mut diag = dcx.struct_err(prev_span);
         diag.set_code(ErrorCode::MissingSemicolon.into());
-        diag.set_message("セミコロンがありません".to_string());
-        diag.set_label(span, "この文の末尾にセミコロンが必要です".to_string());
-        diag.set_help("文の末尾に `;` を追加してください".to_string());
+        diag.set_message(dcx.translate(messages::MISSING_SEMICOLON, &[]));
+        diag.set_label(prev_span, dcx.translate(messages::MISSING_SEMICOLON_LABEL, &[]));
+        diag.set_suggestion(
+            (prev_span.end..prev_span.end).into(),
+            dcx.translate(messages::MISSING_SEMICOLON_SUGGESTION, &[]),
+            ";".to_string(),
+            Applicability::MachineApplicable,
+        );
 
         diag
     }
@@ -201,8 +211,8 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::MissingFunctionBody.into());
-        diag.set_message("関数のボディがありません".to_string());
-        diag.set_label(span, "関数のボディ`{...}`がありません".to_string());
+        diag.set_message(dcx.translate(messages::MISSING_FUNCTION_BODY, &[]));
+        diag.set_label(span, dcx.translate(messages::MISSING_FUNCTION_BODY_LABEL, &[]));
 
         diag
     }
@@ -213,12 +223,18 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::CannotUseUnderscoreAsIdentifier.into());
-        diag.set_message("無効な識別子: `_`".to_string());
+        diag.set_message(dcx.translate(messages::CANNOT_USE_UNDERSCORE_AS_IDENTIFIER, &[]));
         diag.set_label(
             span,
-            "`_` を識別子として使用することはできません".to_string(),
+            dcx.translate(messages::CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_LABEL, &[]),
+        );
+        diag.set_help(dcx.translate(messages::CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_HELP, &[]));
+        diag.set_suggestion(
+            span,
+            dcx.translate(messages::CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_SUGGESTION, &[]),
+            "name".to_string(),
+            Applicability::HasPlaceholders,
         );
-        diag.set_help("`_` は値の破棄や未使用変数の表現としてのみ利用可能です".to_string());
 
         diag
     }
@@ -230,20 +246,16 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UnexpectedTokenForItem.into());
-        diag.set_message(format!("予期しないトークン: `{}`", unexpected));
+        diag.set_message(dcx.translate(messages::UNEXPECTED_TOKEN, &[("token", &unexpected)]));
 
         // Itemが最初にとりうるトークンが増えたとき、ここに追加する
-        let expected_list = [TokenKind::Fn, TokenKind::Mod]
-            .iter()
-            .map(|t| format!("`{}`", t))
-            .collect::<Vec<_>>()
-            .join(", ");
+        let expected = expected_list(&[TokenKind::Fn, TokenKind::Mod, TokenKind::Pound]);
 
         diag.set_label(
             span,
-            format!(
-                "{} のいずれかを期待していましたが、`{}` は無効な入力です",
-                expected_list, unexpected
+            dcx.translate(
+                messages::UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL,
+                &[("expected", &expected), ("token", &unexpected)],
             ),
         );
 
@@ -257,19 +269,16 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(unexpected.span);
         diag.set_code(ErrorCode::UnclosedDelimiter.into());
-        diag.set_message("閉じられていない括弧".to_string());
+        diag.set_message(dcx.translate(messages::UNCLOSED_DELIMITER, &[]));
 
         diag.set_label(
             unexpected.span,
-            format!(
-                "閉じ括弧を期待していましたが、`{}` は無効な入力です",
-                unexpected
-            ),
+            dcx.translate(messages::UNCLOSED_DELIMITER_LABEL, &[("token", &unexpected)]),
         );
 
         diag.set_label(
             opening_delim_span,
-            "これに対応する括弧が見つかりません".to_string(),
+            dcx.translate(messages::UNCLOSED_DELIMITER_OPENING_LABEL, &[]),
         );
 
         diag
@@ -282,22 +291,73 @@ impl<'dcx> DiagsParser {
     ) -> Diag<'dcx, ErrorEmitted> {
         let mut diag = dcx.struct_err(name.span);
         diag.set_code(ErrorCode::MissingFunctionParentheses.into());
-        diag.set_message("宣言された関数の開き括弧がありません".to_string());
+        diag.set_message(dcx.translate(messages::MISSING_FUNCTION_PARENTHESES, &[]));
 
         diag.set_label(
             (name.span.end..lbrace.start).into(),
-            format!(
-                "関数 `{}` の引数リスト `(` が存在しません",
-                name,
+            dcx.translate(messages::MISSING_FUNCTION_PARENTHESES_LABEL, &[("name", &name)]),
+        );
+
+        diag.set_help(dcx.translate(messages::MISSING_FUNCTION_PARENTHESES_HELP, &[]));
+        diag.set_suggestion(
+            (name.span.end..name.span.end).into(),
+            dcx.translate(messages::MISSING_FUNCTION_PARENTHESES_SUGGESTION, &[]),
+            "()".to_string(),
+            Applicability::MachineApplicable,
+        );
+
+        diag
+    }
+
+    pub fn expected_item_after_attributes(
+        dcx: DiagCtxtHandle<'dcx>,
+        attr_span: Span,
+        unexpected: Token,
+    ) -> Diag<'dcx, ErrorEmitted> {
+        let mut diag = dcx.struct_err(attr_span);
+        diag.set_code(ErrorCode::ExpectedItemAfterAttributes.into());
+        diag.set_message(dcx.translate(messages::EXPECTED_ITEM_AFTER_ATTRIBUTES, &[]));
+        diag.set_label(
+            attr_span,
+            dcx.translate(messages::EXPECTED_ITEM_AFTER_ATTRIBUTES_LABEL, &[]),
+        );
+        diag.set_label(
+            unexpected.span,
+            dcx.translate(
+                messages::EXPECTED_ITEM_AFTER_ATTRIBUTES_FOUND_LABEL,
+                &[("token", &unexpected)],
             ),
         );
 
-        diag.set_help("たとえ引数が空であっても括弧を記述してください".to_string());
+        diag
+    }
+
+    pub fn inner_attribute_not_permitted(
+        dcx: DiagCtxtHandle<'dcx>,
+        span: Span,
+    ) -> Diag<'dcx, ErrorEmitted> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::InnerAttributeNotPermitted.into());
+        diag.set_message(dcx.translate(messages::INNER_ATTRIBUTE_NOT_PERMITTED, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(messages::INNER_ATTRIBUTE_NOT_PERMITTED_LABEL, &[]),
+        );
+        diag.set_help(dcx.translate(messages::INNER_ATTRIBUTE_NOT_PERMITTED_HELP, &[]));
 
         diag
     }
 }
 
+/// 期待されるトークンの一覧を `` `fn`, `mod` `` の形式にする
+pub fn expected_list(expected: &[TokenKind]) -> String {
+    expected
+        .iter()
+        .map(|t| format!("`{}`", t))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[repr(i32)]
 enum ErrorCode {
     UnexpectedToken = 200,
@@ -314,6 +374,8 @@ enum ErrorCode {
     UnexpectedTokenForItem = 211,
     UnclosedDelimiter = 212,
     MissingFunctionParentheses = 213,
+    ExpectedItemAfterAttributes = 214,
+    InnerAttributeNotPermitted = 215,
 }
 
 impl From<ErrorCode> for i32 {
@@ -560,4 +622,43 @@ mod tests {
         assert!(is_err);
         assert!(sess.dcx().has_err_code(ErrorCode::MissingFunctionParentheses.into()));
     }
+
+    #[test]
+    fn test_expected_item_after_attributes() {
+        let (sess, is_err) = get_sess_after_stelo_parse(
+            r#"
+    fn main() {
+        return;
+    }
+
+    #[allow(unused_variables)]
+"#.trim()
+        );
+
+        assert!(is_err);
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::ExpectedItemAfterAttributes.into())
+        );
+    }
+
+    #[test]
+    fn test_inner_attribute_not_permitted() {
+        let (sess, is_err) = get_sess_after_stelo_parse(
+            r#"
+    fn main() {
+        return;
+    }
+
+    #![allow(non_snake_case)]
+    fn f() {}
+"#.trim()
+        );
+
+        assert!(is_err);
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::InnerAttributeNotPermitted.into())
+        );
+    }
 }
@@ -1,10 +1,16 @@
 use crate::stelaro_ast::{
     ast::*,
     token::{Token, TokenKind},
+    ty::{Ty, TyKind},
 };
 use crate::stelaro_common::Span;
 
-use super::{PResult, diagnostics::DiagsParser, parser::Parser};
+use super::{
+    PResult,
+    diagnostics::{DiagsParser, expected_list},
+    messages,
+    parser::Parser,
+};
 
 /// 中置演算子 (AssocOp) の定義
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -230,6 +236,8 @@ impl Parser<'_> {
             | TokenKind::LParen
             | TokenKind::If     // If式
             | TokenKind::LBrace // ブロック式 {}
+            | TokenKind::Pipe   // クロージャ |x| x
+            | TokenKind::Move   // クロージャ move |x| x
         )
     }
 
@@ -281,6 +289,7 @@ impl Parser<'_> {
             TokenKind::RParen | TokenKind::RBrace => {
                 Err(DiagsParser::unexpected_closing_delimiter(self.dcx(), self.token.span).emit())?
             }
+            TokenKind::Pipe | TokenKind::Move => self.parse_expr_closure(),
             _ if !self.can_start_expr() => {
                 Err(DiagsParser::expect_expression(self.dcx(), self.token, self.token.span).emit())
             }
@@ -346,6 +355,108 @@ impl Parser<'_> {
         }
     }
 
+    /// `move |x: i32, y|: i32 { x + y }` のようなクロージャを解析する
+    ///
+    /// 本体は可能な限り長く解析されるため、`|x| x + 1` の本体は `x + 1` になる。
+    /// 戻り値の型を書く場合、本体はブロックでなければならない。
+    fn parse_expr_closure(&mut self) -> PResult<Expr> {
+        let start = self.token.span;
+
+        let capture_clause = if self.token.kind == TokenKind::Move {
+            self.bump();
+            CaptureBy::Value {
+                move_kw: self.prev_token.span,
+            }
+        } else {
+            CaptureBy::Ref
+        };
+
+        self.eat(TokenKind::Pipe, self.token.span)?;
+
+        let mut inputs = Vec::new();
+
+        // |x, y,| のような末尾のカンマを許可する
+        while self.token.kind != TokenKind::Pipe {
+            inputs.push(self.parse_closure_param()?);
+
+            match self.token.kind {
+                TokenKind::Comma => self.bump(),
+                TokenKind::Pipe => {}
+                _ => {
+                    let mut diag = DiagsParser::unexpected_token(
+                        self.dcx(),
+                        self.token.kind,
+                        self.token.span,
+                    );
+
+                    let expected = expected_list(&[TokenKind::Comma, TokenKind::Pipe]);
+                    diag.set_label(
+                        self.token.span,
+                        self.dcx().translate(
+                            messages::UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL,
+                            &[("expected", &expected), ("token", &self.token.kind)],
+                        ),
+                    );
+                    Err(diag.emit())?
+                }
+            }
+        }
+
+        self.bump();
+
+        let output = self.parse_ret_ty()?;
+        let fn_decl_span = start.merge(&self.prev_token.span);
+
+        let body = match output {
+            FnRetTy::Default(_) => self.parse_expr_(PrecedenceLimit::None)?,
+            FnRetTy::Ty(_) => {
+                if self.token.kind != TokenKind::LBrace {
+                    Err(DiagsParser::unexpected_token_with_expected(
+                        self.dcx(),
+                        self.token.kind,
+                        TokenKind::LBrace,
+                        self.token.span,
+                    )
+                    .emit())?
+                }
+                self.parse_expr_bottom()?
+            }
+        };
+
+        let span = start.merge(&body.span);
+        let closure = Closure {
+            capture_clause,
+            fn_decl: Box::new(FnDecl { inputs, output }),
+            body: Box::new(body),
+            fn_decl_span,
+        };
+
+        Ok(self.mk_expr(span, ExprKind::Closure(Box::new(closure))))
+    }
+
+    /// クロージャの引数を解析する。型を省略した場合は `_` として扱う。
+    fn parse_closure_param(&mut self) -> PResult<Param> {
+        let pat = self.parse_pat_before_ty()?;
+
+        let ty = if self.token.kind == TokenKind::Colon {
+            self.bump();
+            self.parse_ty()?
+        } else {
+            Ty {
+                id: self.next_node_id(),
+                kind: TyKind::Infer,
+                span: pat.span,
+            }
+        };
+
+        Ok(Param {
+            id: self.next_node_id(),
+            span: pat.span.merge(&ty.span),
+            ty: Box::new(ty),
+            pat,
+        })
+    }
+
     fn parse_expr_fn_call(&mut self, start: Span, f: Expr) -> PResult<Expr> {
         let seq = self.parse_delim_comma_seq(TokenKind::LParen, TokenKind::RParen)?;
 
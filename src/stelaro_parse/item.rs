@@ -1,17 +1,45 @@
 use crate::stelaro_ast::{ast::*, token::TokenKind};
 use crate::stelaro_common::Ident;
 
-use super::{diagnostics::DiagsParser, parser::Parser, PResult};
+use super::{
+    PResult,
+    diagnostics::{DiagsParser, expected_list},
+    messages,
+    parser::Parser,
+};
 
 
 impl<'sess> Parser<'sess> {
     pub fn parse_item(&mut self) -> PResult<Option<Item>> {
+        let attrs = self.parse_outer_attributes()?;
+        let attr_span = attrs.first()
+            .map(|first| first.span.merge(&attrs.last().unwrap().span));
+
+        let item = self.parse_item_without_attrs(attrs)?;
+
+        if item.is_none()
+            && let Some(attr_span) = attr_span
+        {
+            Err(
+                DiagsParser::expected_item_after_attributes(
+                    self.dcx(),
+                    attr_span,
+                    self.token,
+                ).emit()
+            )?
+        }
+
+        Ok(item)
+    }
+
+    fn parse_item_without_attrs(&mut self, attrs: Vec<Attribute>) -> PResult<Option<Item>> {
         match self.token.kind {
             TokenKind::Fn => {
                 let start = self.token.span;
                 let (ident, f) = self.parse_fn()?;
                 Ok(Some(
                     Item {
+                        attrs,
                         kind: ItemKind::Fn(Box::new(f)),
                         id: self.next_node_id(),
                         span: start.merge(&self.prev_token.span),
@@ -25,6 +53,7 @@ impl<'sess> Parser<'sess> {
 
                 Ok(Some(
                     Item {
+                        attrs,
                         kind: ItemKind::Mod(
                             ident,
                             ModKind::Inline(
@@ -38,6 +67,20 @@ impl<'sess> Parser<'sess> {
                     }
                 ))
             },
+            TokenKind::Type => {
+                let start = self.token.span;
+                let ty_alias = self.parse_ty_alias()?;
+
+                Ok(Some(
+                    Item {
+                        attrs,
+                        ident: ty_alias.ident,
+                        kind: ItemKind::TyAlias(Box::new(ty_alias)),
+                        id: self.next_node_id(),
+                        span: start.merge(&self.prev_token.span),
+                    }
+                ))
+            },
             _ => {
                 Ok(None)
             }
@@ -48,7 +91,9 @@ impl<'sess> Parser<'sess> {
     pub fn can_start_item(&self) -> bool {
         matches!(self.token.kind,
             TokenKind::Fn |
-            TokenKind::Mod
+            TokenKind::Mod |
+            TokenKind::Type |
+            TokenKind::Pound
         )
     }
 
@@ -103,21 +148,37 @@ impl<'sess> Parser<'sess> {
         ))
     }
 
-    fn parse_fn_sig(&mut self) -> PResult<FnSig> {
-        let start = self.prev_token.span;
-        let params = self.parse_fn_params()?;
+    /// `type Meters = i64;` を読む。
+    fn parse_ty_alias(&mut self) -> PResult<TyAlias> {
+        let start = self.token.span;
+        self.eat(TokenKind::Type, start)?;
 
-        let ret_ty = if self.token.kind == TokenKind::Colon {
-            self.bump();
+        let ident = self.parse_ident()?;
 
-            let ty = self.parse_ty()?;
+        if ident.is_underscore() {
+            Err(
+                DiagsParser::cannot_use_underscore_as_identifier(
+                    self.dcx(),
+                    ident.span,
+                ).emit()
+            )?
+        }
 
-            FnRetTy::Ty(Box::new(ty))
-        } else {
-            // 返り値が入るべき場所を指す Span を生成する
-            let start = self.prev_token.span.end;
-            FnRetTy::Default((start..start).into())
-        };
+        self.eat(TokenKind::Equal, self.token.span)?;
+        let ty = self.parse_ty()?;
+        self.eat(TokenKind::Semicolon, self.token.span)?;
+
+        Ok(TyAlias {
+            span: start.merge(&self.prev_token.span),
+            ident,
+            ty: Box::new(ty),
+        })
+    }
+
+    fn parse_fn_sig(&mut self) -> PResult<FnSig> {
+        let start = self.prev_token.span;
+        let params = self.parse_fn_params()?;
+        let ret_ty = self.parse_ret_ty()?;
 
         Ok(
             FnSig {
@@ -130,6 +191,21 @@ impl<'sess> Parser<'sess> {
         )
     }
 
+    /// `: i32` のような戻り値の型を読む。省略されている場合は `FnRetTy::Default` を返す。
+    pub(super) fn parse_ret_ty(&mut self) -> PResult<FnRetTy> {
+        if self.token.kind == TokenKind::Colon {
+            self.bump();
+
+            let ty = self.parse_ty()?;
+
+            Ok(FnRetTy::Ty(Box::new(ty)))
+        } else {
+            // 返り値が入るべき場所を指す Span を生成する
+            let start = self.prev_token.span.end;
+            Ok(FnRetTy::Default((start..start).into()))
+        }
+    }
+
     fn parse_fn_params(&mut self) -> PResult<Vec<Param>> {
         if self.token.kind != TokenKind::LParen {
             Err(
@@ -180,11 +256,12 @@ impl<'sess> Parser<'sess> {
                             self.token.span,
                         );
 
+                        let expected = expected_list(&[TokenKind::Comma, TokenKind::RParen]);
                         diag.set_label(
                             self.token.span,
-                            format!(
-                                "`,`または`)`を期待しましたが、`{}`が見つかりました",
-                                self.token.kind
+                            self.dcx().translate(
+                                messages::UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL,
+                                &[("expected", &expected), ("token", &self.token.kind)],
                             ),
                         );
                         Err(diag.emit())?
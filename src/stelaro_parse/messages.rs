@@ -0,0 +1,210 @@
+//! 構文解析で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    UNEXPECTED_TOKEN {
+        ja: "予期しないトークン: `{token}`",
+        en: "unexpected token: `{token}`",
+    }
+    UNEXPECTED_TOKEN_LABEL {
+        ja: "不正なトークン`{token}`が入力されました",
+        en: "invalid token `{token}` found",
+    }
+    UNEXPECTED_TOKEN_INVALID_LABEL {
+        ja: "`{token}` は無効な入力です",
+        en: "`{token}` is not valid here",
+    }
+    UNEXPECTED_TOKEN_EXPECTED_LABEL {
+        ja: "`{expected}` を期待していましたが、`{token}` は無効な入力です",
+        en: "expected `{expected}`, found `{token}`",
+    }
+    /// `expected` は `` `fn`, `mod` `` のような、期待されるトークンの一覧
+    UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL {
+        ja: "{expected} のいずれかを期待していましたが、`{token}` は無効な入力です",
+        en: "expected one of {expected}, found `{token}`",
+    }
+
+    CHAINED_COMPARISON {
+        ja: "連鎖した比較演算子",
+        en: "comparison operators cannot be chained",
+    }
+    CHAINED_COMPARISON_FIRST_LABEL {
+        ja: "この比較は無効です",
+        en: "this comparison is invalid",
+    }
+    CHAINED_COMPARISON_SECOND_LABEL {
+        ja: "比較演算子が連鎖しています",
+        en: "the comparison operators are chained here",
+    }
+
+    EXPECT_EXPRESSION {
+        ja: "不正な式",
+        en: "expected expression",
+    }
+    EXPECT_EXPRESSION_LABEL {
+        ja: "`{token}`は式ではありません",
+        en: "`{token}` is not an expression",
+    }
+    EXPECT_EXPRESSION_HELP {
+        ja: "これを削除するか、間に値を追加してください",
+        en: "remove this, or add a value in between",
+    }
+
+    PREFIX_INCREMENT {
+        ja: "前置インクリメント",
+        en: "prefix increment",
+    }
+    PREFIX_INCREMENT_LABEL {
+        ja: "steraloは前置インクリメント演算子をもちません",
+        en: "stelaro has no prefix increment operator",
+    }
+    PREFIX_INCREMENT_HELP {
+        ja: "`lhs += 1;`を使用してください",
+        en: "use `lhs += 1;` instead",
+    }
+
+    UNEXPECTED_CLOSING_DELIMITER {
+        ja: "余分な閉じ括弧",
+        en: "unexpected closing delimiter",
+    }
+    UNEXPECTED_CLOSING_DELIMITER_LABEL {
+        ja: "式の解析中に余分な閉じ括弧が見つかりました",
+        en: "found an extra closing delimiter while parsing an expression",
+    }
+    UNEXPECTED_CLOSING_DELIMITER_SUGGESTION {
+        ja: "これを削除してください",
+        en: "remove this",
+    }
+
+    UNEXPECTED_TOKEN_FOR_IDENTIFIER {
+        ja: "無効な識別子",
+        en: "expected identifier",
+    }
+    UNEXPECTED_TOKEN_FOR_IDENTIFIER_LABEL {
+        ja: "識別子でない予期しないトークンがあります",
+        en: "this token is not an identifier",
+    }
+
+    UNEXPECTED_TOKEN_FOR_TYPE {
+        ja: "無効な型",
+        en: "expected type",
+    }
+    UNEXPECTED_TOKEN_FOR_TYPE_LABEL {
+        ja: "これは型ではありません",
+        en: "this is not a type",
+    }
+    TYPE_EXPECTED_HERE_LABEL {
+        ja: "ここに型を記述してください",
+        en: "write a type here",
+    }
+
+    UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER {
+        ja: "識別子はリテラルから始めることができない",
+        en: "identifiers cannot start with a literal",
+    }
+    UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER_LABEL {
+        ja: "識別子を`{literal}`から始めることはできません",
+        en: "an identifier cannot start with `{literal}`",
+    }
+    UNEXPECTED_NUMERIC_LITERAL_FOR_IDENTIFIER_HELP {
+        ja: "識別子に数値リテラルを使うことはできません",
+        en: "numeric literals cannot be used in identifiers",
+    }
+
+    MISSING_SEMICOLON {
+        ja: "セミコロンがありません",
+        en: "missing semicolon",
+    }
+    MISSING_SEMICOLON_LABEL {
+        ja: "この文の末尾にセミコロンが必要です",
+        en: "this statement must end with a semicolon",
+    }
+    MISSING_SEMICOLON_SUGGESTION {
+        ja: "文の末尾にセミコロンを追加してください",
+        en: "add a semicolon at the end of the statement",
+    }
+
+    MISSING_FUNCTION_BODY {
+        ja: "関数のボディがありません",
+        en: "missing function body",
+    }
+    MISSING_FUNCTION_BODY_LABEL {
+        ja: "関数のボディ`{...}`がありません",
+        en: "the function body `{...}` is missing",
+    }
+
+    CANNOT_USE_UNDERSCORE_AS_IDENTIFIER {
+        ja: "無効な識別子: `_`",
+        en: "invalid identifier: `_`",
+    }
+    CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_LABEL {
+        ja: "`_` を識別子として使用することはできません",
+        en: "`_` cannot be used as an identifier",
+    }
+    CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_HELP {
+        ja: "`_` は値の破棄や未使用変数の表現としてのみ利用可能です",
+        en: "`_` can only be used to discard a value or to mark a variable as unused",
+    }
+    CANNOT_USE_UNDERSCORE_AS_IDENTIFIER_SUGGESTION {
+        ja: "名前を付けてください",
+        en: "give it a name",
+    }
+
+    UNCLOSED_DELIMITER {
+        ja: "閉じられていない括弧",
+        en: "unclosed delimiter",
+    }
+    UNCLOSED_DELIMITER_LABEL {
+        ja: "閉じ括弧を期待していましたが、`{token}` は無効な入力です",
+        en: "expected a closing delimiter, found `{token}`",
+    }
+    UNCLOSED_DELIMITER_OPENING_LABEL {
+        ja: "これに対応する括弧が見つかりません",
+        en: "this delimiter is never closed",
+    }
+
+    MISSING_FUNCTION_PARENTHESES {
+        ja: "宣言された関数の開き括弧がありません",
+        en: "missing parentheses in function declaration",
+    }
+    MISSING_FUNCTION_PARENTHESES_LABEL {
+        ja: "関数 `{name}` の引数リスト `(` が存在しません",
+        en: "the parameter list `(` of function `{name}` is missing",
+    }
+    MISSING_FUNCTION_PARENTHESES_HELP {
+        ja: "たとえ引数が空であっても括弧を記述してください",
+        en: "parentheses are required even if there are no parameters",
+    }
+    MISSING_FUNCTION_PARENTHESES_SUGGESTION {
+        ja: "引数リストを追加してください",
+        en: "add a parameter list",
+    }
+
+    EXPECTED_ITEM_AFTER_ATTRIBUTES {
+        ja: "属性の後にアイテムがありません",
+        en: "expected item after attributes",
+    }
+    EXPECTED_ITEM_AFTER_ATTRIBUTES_LABEL {
+        ja: "この属性を適用するアイテムがありません",
+        en: "there is no item for this attribute to apply to",
+    }
+    EXPECTED_ITEM_AFTER_ATTRIBUTES_FOUND_LABEL {
+        ja: "アイテムを期待していましたが、`{token}` が見つかりました",
+        en: "expected an item, found `{token}`",
+    }
+
+    INNER_ATTRIBUTE_NOT_PERMITTED {
+        ja: "この位置に内部属性を書くことはできません",
+        en: "an inner attribute is not permitted in this context",
+    }
+    INNER_ATTRIBUTE_NOT_PERMITTED_LABEL {
+        ja: "`#![...]` はファイルの先頭にのみ書くことができます",
+        en: "`#![...]` can only appear at the beginning of the file",
+    }
+    INNER_ATTRIBUTE_NOT_PERMITTED_HELP {
+        ja: "直後のアイテムに適用するには `#[...]` を使用してください",
+        en: "use `#[...]` to apply an attribute to the following item",
+    }
+}
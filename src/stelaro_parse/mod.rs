@@ -1,6 +1,8 @@
+mod attr;
 mod diagnostics;
 mod expr;
 mod item;
+pub mod messages;
 pub mod parser;
 mod pat;
 mod path;
@@ -80,6 +80,8 @@ impl<'sess> Parser<'sess> {
     pub fn parse_stelo(&mut self) -> PResult<Stelo> {
         let start = self.token.span;
 
+        let attrs = self.parse_inner_attributes()?;
+
         let mut items = vec![];
         loop {
             if self.token.kind == TokenKind::Eof {
@@ -98,6 +100,7 @@ impl<'sess> Parser<'sess> {
         }
 
         Ok(Stelo {
+            attrs,
             items,
             span: ModSpan {
                 inner_span: start.merge(&self.prev_token.span),
@@ -187,7 +190,7 @@ impl<'sess> Parser<'sess> {
                             Err(
                                 DiagsParser::missing_semicolon(
                                     self.dcx(),
-                                    self.token.span,
+                                    self.prev_token.span,
                                 ).emit()
                             )?
                         }
@@ -21,7 +21,9 @@ impl Parser<'_> {
             },
             TokenKind::Continue => {
                 self.eat(TokenKind::Continue, self.token.span)?;
-                Ok(Some(self.mk_stmt(self.prev_token.span, StmtKind::Continue)))
+                let start = self.prev_token.span;
+                self.eat(TokenKind::Semicolon, self.token.span)?;
+                Ok(Some(self.mk_stmt(start.merge(&self.prev_token.span), StmtKind::Continue)))
             }
             TokenKind::Return => {
                 self.parse_stmt_return().map(Some)
@@ -87,11 +89,10 @@ impl Parser<'_> {
             self.eat(TokenKind::Equal, self.token.span)?;
             let expr = self.parse_expr()?;
             if !matches!(self.token.kind, TokenKind::Semicolon) {
-                let start = self.prev_token.span.start;
                 Err(
                     DiagsParser::missing_semicolon(
                         self.dcx(),
-                        (start+1..start+1).into(),
+                        self.prev_token.span,
                     ).emit()
                 )?
             } else {
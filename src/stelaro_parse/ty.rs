@@ -1,9 +1,14 @@
 use crate::stelaro_ast::{
     token::TokenKind,
-    ty::{Ty, TyKind},
+    ty::{FnPtrTy, Ty, TyKind},
 };
 
-use super::{PResult, diagnostics::DiagsParser, parser::Parser};
+use super::{
+    PResult,
+    diagnostics::{DiagsParser, expected_list},
+    messages,
+    parser::Parser,
+};
 
 impl Parser<'_> {
     pub fn parse_ty(&mut self) -> PResult<Ty> {
@@ -27,6 +32,16 @@ impl Parser<'_> {
 
                 TyKind::Unit
             }
+            TokenKind::Bang => {
+                self.bump();
+
+                TyKind::Never
+            }
+            TokenKind::Fn => {
+                self.bump();
+
+                TyKind::FnPtr(self.parse_fn_ptr_ty()?)
+            }
             _ => {
                 let mut diag = DiagsParser::unexpected_token_for_type(
                     self.dcx(),
@@ -35,7 +50,7 @@ impl Parser<'_> {
 
                 diag.set_label(
                     self.prev_token.span.between(&self.token.span),
-                    "ここに型を記述してください".to_string(),
+                    self.dcx().translate(messages::TYPE_EXPECTED_HERE_LABEL, &[]),
                 );
 
                 Err(diag.emit())?
@@ -48,4 +63,44 @@ impl Parser<'_> {
             span: start.merge(&self.prev_token.span),
         })
     }
+
+    /// `fn` に続く `(i32, bool): i32` を読む
+    fn parse_fn_ptr_ty(&mut self) -> PResult<FnPtrTy> {
+        self.eat(TokenKind::LParen, self.token.span)?;
+
+        let mut inputs = Vec::new();
+
+        // fn(i32, bool,) のような末尾のカンマを許可する
+        while self.token.kind != TokenKind::RParen {
+            inputs.push(self.parse_ty()?);
+
+            match self.token.kind {
+                TokenKind::Comma => self.bump(),
+                TokenKind::RParen => {}
+                _ => {
+                    let mut diag = DiagsParser::unexpected_token(
+                        self.dcx(),
+                        self.token.kind,
+                        self.token.span,
+                    );
+
+                    let expected = expected_list(&[TokenKind::Comma, TokenKind::RParen]);
+                    diag.set_label(
+                        self.token.span,
+                        self.dcx().translate(
+                            messages::UNEXPECTED_TOKEN_EXPECTED_ANY_LABEL,
+                            &[("expected", &expected), ("token", &self.token.kind)],
+                        ),
+                    );
+                    Err(diag.emit())?
+                }
+            }
+        }
+
+        self.bump();
+
+        let output = self.parse_ret_ty()?;
+
+        Ok(FnPtrTy { inputs, output })
+    }
 }
@@ -3,7 +3,7 @@ use std::mem;
 use crate::stelaro_ast::{
     NodeId,
     ast::*,
-    visit::{Visitor, walk_item},
+    visit::{Visitor, walk_expr, walk_item},
 };
 use crate::stelaro_common::{LocalDefId, STELO_DEF_ID, Span, Symbol};
 use crate::stelaro_sir::def::DefKind;
@@ -55,6 +55,7 @@ impl<'a, 'ra, 'tcx> Visitor<'a> for DefCollector<'a, 'ra, 'tcx> {
         let def_kind = match &item.kind {
             ItemKind::Fn(..) => DefKind::Fn,
             ItemKind::Mod(..) => DefKind::Mod,
+            ItemKind::TyAlias(..) => DefKind::TyAlias,
         };
 
         let def_id = self.create_def(item.id, Some(item.ident.name), def_kind, item.span);
@@ -63,4 +64,13 @@ impl<'a, 'ra, 'tcx> Visitor<'a> for DefCollector<'a, 'ra, 'tcx> {
             walk_item(this, item);
         });
     }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        let parent_def = match expr.kind {
+            ExprKind::Closure(_) => self.create_def(expr.id, None, DefKind::Closure, expr.span),
+            _ => self.parent_def,
+        };
+
+        self.with_parent(parent_def, |this| walk_expr(this, expr));
+    }
 }
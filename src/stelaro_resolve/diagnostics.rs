@@ -6,7 +6,7 @@ use crate::stelaro_sir::def::{
     PerNS, Res,
 };
 
-use super::{Module, ModuleKind, NameBinding, Resolver};
+use super::{Module, ModuleKind, NameBinding, Resolver, messages};
 
 impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
     pub fn dcx(&self) -> DiagCtxtHandle<'tcx> {
@@ -27,9 +27,10 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
             return self.report_conflict(parent, ident, ns, old_binding, new_binding);
         }
 
+        let lang = self.dcx().lang();
         let container_dscr = match parent.kind {
-            ModuleKind::Def(kind, _, _) => kind.descr_ja(parent.def_id()),
-            ModuleKind::Block => "ブロック",
+            ModuleKind::Def(kind, _, _) => kind.descr_in(parent.def_id(), lang).to_string(),
+            ModuleKind::Block => self.dcx().translate(messages::DESCR_BLOCK, &[]),
         };
 
         let (name, span) = (
@@ -47,16 +48,17 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
         let name_str = name.as_str();
 
         let old_kind_dscr = match (ns, old_binding.module()) {
-            (ValueNS, _) => "値",
-            (TypeNS, Some(module)) if module.is_normal() => "モジュール",
-            (TypeNS, _) => "型",
+            (ValueNS, _) => messages::DESCR_VALUE,
+            (TypeNS, Some(module)) if module.is_normal() => messages::DESCR_MODULE,
+            (TypeNS, _) => messages::DESCR_TYPE,
         };
+        let old_kind_dscr = self.dcx().translate(old_kind_dscr, &[]);
 
         let mut diag = DiagsResolver::name_defined_multiple_time(
             self.dcx(),
             name_str,
-            ns.descr_ja(),
-            container_dscr,
+            ns.descr_in(lang),
+            &container_dscr,
             span,
         );
 
@@ -65,9 +67,9 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
                 .sess
                 .source_map()
                 .truncate_span_to_item_header(old_binding.span),
-            format!(
-                "既に{}名前空間内に `{}` はここで定義されています",
-                old_kind_dscr, name_str,
+            self.dcx().translate(
+                messages::NAME_DEFINED_MULTIPLE_TIME_PREVIOUS_LABEL,
+                &[("kind", &old_kind_dscr), ("name", &name_str)],
             ),
         );
 
@@ -88,6 +90,8 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
         ident: Ident,
     ) -> String {
         let is_last = failed_segment_idx == path.len() - 1;
+        let dcx = self.dcx();
+        let lang = dcx.lang();
         let ns = if is_last {
             opt_ns.unwrap_or(TypeNS)
         } else {
@@ -97,7 +101,10 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
             let parent = path[failed_segment_idx - 1].ident.name;
             let parent = format!("`{parent}`");
 
-            let mut msg = format!("`{ident}` は {parent} の中で見つかりませんでした");
+            let mut msg = dcx.translate(
+                messages::NOT_FOUND_IN_PARENT,
+                &[("ident", &ident), ("parent", &parent)],
+            );
             let ns_to_try = if ns == ValueNS { TypeNS } else { ValueNS };
             let binding = if let Some(module) = module {
                 self.resolve_ident_in_module(
@@ -135,21 +142,23 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
                 .ok()
             };
             if let Some(binding) = binding {
-                let mut found = |what| {
-                    msg = format!(
-                        "{}を期待していましたが、{} `{}` が {} で見つかりました",
-                        ns.descr_ja(),
-                        what,
-                        ident,
-                        parent
+                let mut found = |what: &str| {
+                    msg = dcx.translate(
+                        messages::EXPECTED_NS_FOUND_IN_PARENT,
+                        &[
+                            ("expected", &ns.descr_in(lang)),
+                            ("found", &what),
+                            ("ident", &ident),
+                            ("parent", &parent),
+                        ],
                     )
                 };
                 if binding.module().is_some() {
-                    found("モジュール")
+                    found(&dcx.translate(messages::DESCR_MODULE, &[]))
                 } else {
                     match binding.res() {
-                        Res::Def(kind, id) => found(kind.descr_ja(id)),
-                        _ => found(ns_to_try.descr_ja()),
+                        Res::Def(kind, id) => found(kind.descr_in(id, lang)),
+                        _ => found(ns_to_try.descr_in(lang)),
                     }
                 }
             };
@@ -163,10 +172,10 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
             false,
             ignore_binding
         ) {
-            let descr = binding.res().descr_ja();
-            format!("{descr} `{ident}` はモジュール名ではありません")
+            let descr = binding.res().descr_in(lang);
+            dcx.translate(messages::NOT_A_MODULE_NAME, &[("descr", &descr), ("ident", &ident)])
         } else {
-            format!("`{ident}` を解決することができませんでした")
+            dcx.translate(messages::FAILED_TO_RESOLVE, &[("ident", &ident)])
         }
     }
 }
@@ -194,12 +203,12 @@ impl<'dcx, 'ra> DiagsResolver {
     ) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::NameDefinedMultipleTime.into());
-        diag.set_message(format!("名前 `{name}` の重複した定義"));
+        diag.set_message(dcx.translate(messages::NAME_DEFINED_MULTIPLE_TIME, &[("name", &name)]));
         diag.set_label(
             span,
-            format!(
-                "`{name}` は重複して定義されています\n
-            `{name}` はこの{container}の{ns}名前空間で一回だけ定義することができます"
+            dcx.translate(
+                messages::NAME_DEFINED_MULTIPLE_TIME_LABEL,
+                &[("name", &name), ("container", &container), ("ns", &ns)],
             ),
         );
 
@@ -214,10 +223,15 @@ impl<'dcx, 'ra> DiagsResolver {
         let name = ident.name.as_str();
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::DuplicateIdentifierInParameterList.into());
-        diag.set_message(format!("パラメーター `{name}` の重複した定義"));
+        diag.set_message(
+            dcx.translate(messages::DUPLICATE_IDENTIFIER_IN_PARAMETER_LIST, &[("name", &name)]),
+        );
         diag.set_label(
             span,
-            format!("`{name}` は引数リストの中で重複して定義されています"),
+            dcx.translate(
+                messages::DUPLICATE_IDENTIFIER_IN_PARAMETER_LIST_LABEL,
+                &[("name", &name)],
+            ),
         );
 
         diag
@@ -232,15 +246,16 @@ impl<'dcx, 'ra> DiagsResolver {
     ) -> Diag<'dcx> {
         let mut diag = dcx.struct_err(span);
         diag.set_code(ErrorCode::UndefinedIdentifier.into());
-        let descr = module.and_then(|m| m.res()).map(|m| m.descr_ja());
+        let descr = module.and_then(|m| m.res()).map(|m| m.descr_in(dcx.lang()));
+        let name = segment_name.as_str();
 
         if let Some(descr) = descr {
-            diag.set_message(format!(
-                "定義されていない{descr} `{}`",
-                segment_name.as_str()
+            diag.set_message(dcx.translate(
+                messages::UNDEFINED_IDENTIFIER_IN,
+                &[("descr", &descr), ("name", &name)],
             ));
         } else {
-            diag.set_message(format!("定義されていない `{}`", segment_name.as_str()));
+            diag.set_message(dcx.translate(messages::UNDEFINED_IDENTIFIER, &[("name", &name)]));
         }
 
         diag.set_label(span, msg);
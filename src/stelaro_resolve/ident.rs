@@ -3,6 +3,7 @@ use crate::stelaro_resolve::{
     BindingKey, Determinacy, Finalize, LexicalScopeBinding, Module, ModuleKind, NameBinding,
     PathResult, Resolver, Segment,
     late::{Scope, ScopeKind},
+    messages,
 };
 use crate::stelaro_sir::def::{
     Namespace::{self, TypeNS, ValueNS},
@@ -166,7 +167,29 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
             current_module = module_to_search.parent;
         }
 
-        // ステロのルートまで遡っても見つからなかった
+        // ステロのルートまで遡っても見つからなかった場合は、標準ステロのプレリュードを探す。
+        // ローカルの定義はプレリュードの同じ名前の定義を隠す
+        if let Some(prelude) = self.prelude {
+            let result = self.resolve_ident_in_module(
+                &prelude,
+                ident,
+                ns,
+                parent_module,
+                finalize,
+                ignore_binding,
+            );
+            if let Ok(binding) = result {
+                return Ok(binding);
+            }
+        }
+
+        // `--extern` で読み込んだステロを探す
+        if ns == TypeNS
+            && let Some(&binding) = self.extern_prelude.get(&ident.name)
+        {
+            return Ok(binding);
+        }
+
         Err(Determinacy::Determined)
     }
 
@@ -274,9 +297,9 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
                             is_error_from_last_segment: is_last,
                             segment_name: ident.name,
                             module,
-                            label: format!(
-                                "`{ident}` は{}で、モジュールではありません",
-                                res.descr_ja()
+                            label: self.dcx().translate(
+                                messages::NOT_A_MODULE,
+                                &[("ident", ident), ("descr", &res.descr_in(self.dcx().lang()))],
                             ),
                         };
                     }
@@ -238,7 +238,7 @@ impl<'a, 'ast, 'ra: 'ast, 'tcx> LateResolutionVisitor<'a, 'ast, 'ra, 'tcx> {
         let def_kind = self.r.local_def_kind(item.id);
 
         match &item.kind {
-            ItemKind::Fn(_) => {
+            ItemKind::Fn(_) | ItemKind::TyAlias(_) => {
                 self.with_param_scope(ScopeKind::Item(def_kind), |this| {
                 visit::walk_item(this, item)
                 })
@@ -302,6 +302,15 @@ impl<'a, 'ast, 'ra: 'ast, 'tcx> LateResolutionVisitor<'a, 'ast, 'ra, 'tcx> {
 
                 visit::walk_expr(self, expr);
             }
+            // クロージャの引数は本体からのみ見える。外側の変数はそのまま参照でき、
+            // 型検査でキャプチャとして扱われる
+            ExprKind::Closure(closure) => {
+                self.with_scope(ValueNS, ScopeKind::NoRestriction, |this| {
+                    this.resolve_fn_params(&closure.fn_decl.inputs);
+                    this.visit_fn_ret_ty(&closure.fn_decl.output);
+                    this.visit_expr(&closure.body);
+                });
+            }
             _ => visit::walk_expr(self, expr),
         }
     }
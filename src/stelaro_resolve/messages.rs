@@ -0,0 +1,76 @@
+//! 名前解決で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    NAME_DEFINED_MULTIPLE_TIME {
+        ja: "名前 `{name}` の重複した定義",
+        en: "the name `{name}` is defined multiple times",
+    }
+    /// `container` は名前を含むもの (e.g. モジュール)、`ns` は名前空間を表す
+    NAME_DEFINED_MULTIPLE_TIME_LABEL {
+        ja: "`{name}` は重複して定義されています\n`{name}` はこの{container}の{ns}名前空間で一回だけ定義することができます",
+        en: "`{name}` redefined here\n`{name}` must be defined only once in the {ns} namespace of this {container}",
+    }
+    NAME_DEFINED_MULTIPLE_TIME_PREVIOUS_LABEL {
+        ja: "既に{kind}名前空間内に `{name}` はここで定義されています",
+        en: "previous definition of the {kind} `{name}` here",
+    }
+
+    DUPLICATE_IDENTIFIER_IN_PARAMETER_LIST {
+        ja: "パラメーター `{name}` の重複した定義",
+        en: "identifier `{name}` is bound more than once in this parameter list",
+    }
+    DUPLICATE_IDENTIFIER_IN_PARAMETER_LIST_LABEL {
+        ja: "`{name}` は引数リストの中で重複して定義されています",
+        en: "`{name}` is used as a parameter more than once",
+    }
+
+    UNDEFINED_IDENTIFIER {
+        ja: "定義されていない `{name}`",
+        en: "cannot find `{name}`",
+    }
+    UNDEFINED_IDENTIFIER_IN {
+        ja: "定義されていない{descr} `{name}`",
+        en: "cannot find {descr} `{name}`",
+    }
+
+    NOT_FOUND_IN_PARENT {
+        ja: "`{ident}` は {parent} の中で見つかりませんでした",
+        en: "could not find `{ident}` in {parent}",
+    }
+    EXPECTED_NS_FOUND_IN_PARENT {
+        ja: "{expected}を期待していましたが、{found} `{ident}` が {parent} で見つかりました",
+        en: "expected {expected}, found {found} `{ident}` in {parent}",
+    }
+    NOT_A_MODULE_NAME {
+        ja: "{descr} `{ident}` はモジュール名ではありません",
+        en: "{descr} `{ident}` is not a module name",
+    }
+    NOT_A_MODULE {
+        ja: "`{ident}` は{descr}で、モジュールではありません",
+        en: "`{ident}` is a {descr}, not a module",
+    }
+    FAILED_TO_RESOLVE {
+        ja: "`{ident}` を解決することができませんでした",
+        en: "failed to resolve `{ident}`",
+    }
+
+    DESCR_BLOCK {
+        ja: "ブロック",
+        en: "block",
+    }
+    DESCR_MODULE {
+        ja: "モジュール",
+        en: "module",
+    }
+    DESCR_VALUE {
+        ja: "値",
+        en: "value",
+    }
+    DESCR_TYPE {
+        ja: "型",
+        en: "type",
+    }
+}
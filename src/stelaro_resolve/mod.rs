@@ -2,6 +2,7 @@ mod def_collector;
 mod diagnostics;
 mod ident;
 mod late;
+pub mod messages;
 mod module_graph_builder;
 
 use std::cell::RefCell;
@@ -402,6 +403,14 @@ pub struct Resolver<'ra, 'tcx> {
 
     main_def: Option<MainDefinition>,
 
+    /// `--extern` で読み込んだステロのルートモジュールへの束縛。
+    /// ステロのルートまで遡っても見つからない名前は、ここから探す
+    extern_prelude: HashMap<Symbol, NameBinding<'ra>>,
+
+    /// 標準ステロのルートモジュール。ステロのルートまで遡っても見つからない名前は、
+    /// `extern_prelude` よりも先にここから探す。`-Z no-std` が指定された場合は `None`
+    prelude: Option<Module<'ra>>,
+
     /// 既に重複して定義されている名前に対して、診断がさらに重複しないようにする
     name_already_seen: HashMap<Symbol, Span>,
 }
@@ -457,12 +466,7 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
         assert!(!self.node_id_to_def_id.contains_key(&node_id),);
 
         let def_id = self.tcx.create_def(parent, name, def_kind);
-
-        let _id1 = self.tcx.source_span.borrow_mut().push(span);
-        let _id2 = self.tcx.def_kind_table.borrow_mut().push(def_kind);
-
-        debug_assert_eq!(_id1, _id2);
-        debug_assert_eq!(def_id, _id1);
+        self.tcx.feed_local_def(def_id, span, def_kind);
 
         self.node_id_to_def_id.insert(node_id, def_id);
         assert_eq!(self.def_id_to_node_id.push(node_id), def_id);
@@ -471,6 +475,7 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
     }
 
     pub fn resolve_stelo(&mut self, stelo: &Stelo) {
+        self.build_extern_prelude();
         self.build_module_graph(stelo, self.graph_root);
 
         if self.dcx().has_errors().is_some() {
@@ -547,6 +552,8 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
             node_id_to_def_id,
             def_id_to_node_id,
             main_def: None,
+            extern_prelude: HashMap::new(),
+            prelude: None,
             name_already_seen: HashMap::new(),
         }
     }
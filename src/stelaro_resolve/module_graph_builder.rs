@@ -6,8 +6,8 @@ use super::{
 };
 
 use crate::stelaro_ast::{NodeId, ast::*, visit};
-use crate::stelaro_common::{Ident, Span};
-use crate::stelaro_sir::def::{Namespace, Res};
+use crate::stelaro_common::{DUMMY_SPAN, DefId, Ident, STELO_ROOT_INDEX, Span};
+use crate::stelaro_sir::def::{DefKind, Namespace, Res};
 
 impl<'ra> ToNameBinding<'ra> for (Module<'ra>, /*ty::Visibility<Id>,*/ Span) {
     fn to_name_binding(self, arenas: &'ra ResolverArenas<'ra>) -> NameBinding<'ra> {
@@ -71,6 +71,65 @@ impl<'ra, 'tcx> Resolver<'ra, 'tcx> {
         }
     }
 
+    /// `--extern` で読み込んだステロのモジュールを、メタデータの定義の表から作る。
+    /// 標準ステロのモジュールはプレリュードとしても使う。
+    pub fn build_extern_prelude(&mut self) {
+        let tcx = self.tcx;
+
+        for (stelo, meta) in tcx.cstore.iter() {
+            let root = DefId::new(stelo, STELO_ROOT_INDEX);
+            let module = self.new_module(
+                None,
+                ModuleKind::Def(DefKind::Mod, root, Some(meta.extern_name)),
+                DUMMY_SPAN,
+            );
+            self.build_extern_module(module);
+            if tcx.cstore.std_stelo() == Some(stelo) {
+                self.prelude = Some(module);
+            }
+
+            let binding = (module, DUMMY_SPAN).to_name_binding(self.arenas);
+            self.extern_prelude.insert(meta.extern_name, binding);
+        }
+    }
+
+    /// 外部ステロのモジュールの直下にある定義を、モジュールに束縛する。
+    fn build_extern_module(&mut self, module: Module<'ra>) {
+        let def_id = module.def_id();
+        let meta = self.tcx.cstore.get(def_id.stelo);
+
+        for index in meta.children(def_id.index) {
+            let child = DefId::new(def_id.stelo, index);
+            let name = meta
+                .def_key(index)
+                .get_opt_name()
+                .expect("bug: ステロのルート以外の定義は名前をもつ");
+            let ident = Ident::new(name, DUMMY_SPAN);
+
+            match meta.def_kind(index) {
+                DefKind::Fn => {
+                    let res = Res::Def(DefKind::Fn, child);
+                    self.define(module, ident, Namespace::ValueNS, (res, DUMMY_SPAN));
+                }
+                DefKind::Mod => {
+                    let child_module = self.new_module(
+                        Some(module),
+                        ModuleKind::Def(DefKind::Mod, child, Some(name)),
+                        DUMMY_SPAN,
+                    );
+                    self.define(module, ident, Namespace::TypeNS, (child_module, DUMMY_SPAN));
+                    self.build_extern_module(child_module);
+                }
+                DefKind::TyAlias => {
+                    let res = Res::Def(DefKind::TyAlias, child);
+                    self.define(module, ident, Namespace::TypeNS, (res, DUMMY_SPAN));
+                }
+                // クロージャは関数の子であり、モジュールの直下には現れない
+                DefKind::Closure => {}
+            }
+        }
+    }
+
     pub fn build_module_graph(
         &mut self,
         stelo: &Stelo,
@@ -108,6 +167,7 @@ impl<'r, 'ra, 'tcx> ModuleGraphBuilder<'r, 'ra, 'tcx> {
             id,
             span,
             ident,
+            ..
         } = item;
         let local_def_id = self.r.node_id_to_def_id.get(id).unwrap();
         let def_id = local_def_id.to_def_id();
@@ -130,6 +190,10 @@ impl<'r, 'ra, 'tcx> ModuleGraphBuilder<'r, 'ra, 'tcx> {
                     .define(parent, *ident, Namespace::TypeNS, (module, /*vis,*/ *span));
                 self.parent_module = module;
             }
+            ItemKind::TyAlias(..) => {
+                self.r
+                    .define(parent, *ident, Namespace::TypeNS, (res, /* vis,*/ *span));
+            }
         }
     }
 
@@ -1,24 +1,352 @@
 use std::path::PathBuf;
 
+use clap::ArgMatches;
+
 use crate::Args;
+use crate::stelaro_diagnostics::Lang;
+use crate::stelaro_session::lint::LintLevel;
 
 pub struct Options {
     pub stelo_name: Option<String>,
     pub working_dir: PathBuf, 
-    // pub error_format: ErrorOutputType,
+    pub error_format: ErrorOutputType,
+
+    /// 診断メッセージの言語
+    pub lang: Lang,
+
+    /// 自動的に適用できる修正案を、入力ファイルに適用する
+    pub fix: bool,
+
+    /// コマンドライン引数で指定されたリントとそのレベル。
+    /// 同じリントが複数回指定された場合、後の指定が優先される。
+    pub lint_opts: Vec<(String, LintLevel)>,
+
+    /// 出力する中間表現の種類。コンパイルは最も後の段階のものを出力した時点で終わる
+    pub emit: Vec<EmitKind>,
+
+    /// 生成するステロの種類
+    pub stelo_type: SteloType,
+
+    /// `--extern` で指定された、読み込むステロの名前とメタデータのパス
+    pub externs: Vec<ExternEntry>,
+
+    /// リリースビルドとしてコンパイルする
+    pub release: bool,
+
+    /// `-C` で指定されたコード生成のオプション
+    pub cg_opts: CodegenOptions,
+
+    /// `-Z` で指定された不安定なオプション
+    pub unstable_opts: UnstableOptions,
     // pub target_triple: TargetTuple,
 }
 
-pub(crate) fn build_session_options(args: &Args) -> Options {
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            stelo_name: None,
+            working_dir: std::env::current_dir().unwrap_or_default(),
+            error_format: ErrorOutputType::default(),
+            lang: Lang::default(),
+            fix: false,
+            lint_opts: Vec::new(),
+            emit: vec![EmitKind::default()],
+            stelo_type: SteloType::default(),
+            externs: Vec::new(),
+            release: false,
+            cg_opts: CodegenOptions::default(),
+            unstable_opts: UnstableOptions::default(),
+        }
+    }
+}
+
+pub(crate) fn build_session_options(args: &Args, matches: &ArgMatches) -> Options {
     let stelo_name = args.stelo_name.clone();
 
     let working_dir = std::env::current_dir().unwrap_or_else(|e| {
         panic!("Current directory is invalid: {e}");
     });
 
+    let lint_opts = lint_opts_in_order(args, matches);
+
+    // 出力は段階の順に行うため、指定の順序によらず段階の順に並べる
+    let mut emit = args.emit.clone();
+    // ライブラリは他のステロから読み込めるよう、常にメタデータを出力する
+    if args.stelo_type == SteloType::Lib {
+        emit.push(EmitKind::Metadata);
+    }
+    emit.sort();
+    emit.dedup();
+
     Options {
         stelo_name,
         working_dir,
+        error_format: args.error_format,
+        lang: select_lang(args.lang),
+        fix: args.fix,
+        lint_opts,
+        emit,
+        stelo_type: args.stelo_type,
+        externs: args.externs.clone(),
+        release: args.release,
+        cg_opts: CodegenOptions::from_flags(&args.codegen),
+        unstable_opts: UnstableOptions::from_flags(&args.unstable),
+    }
+}
+
+/// `-A`, `-W`, `-D` で指定されたリントを、コマンドライン引数に現れた順に並べる。
+/// 同じリントに対して複数のレベルが指定された場合、後の指定が優先される。
+fn lint_opts_in_order(args: &Args, matches: &ArgMatches) -> Vec<(String, LintLevel)> {
+    let flags = [
+        ("allow", &args.allow, LintLevel::Allow),
+        ("warn", &args.warn, LintLevel::Warn),
+        ("deny", &args.deny, LintLevel::Deny),
+    ];
+
+    let mut lint_opts = flags
+        .into_iter()
+        .flat_map(|(id, names, level)| {
+            let indices = matches.indices_of(id).into_iter().flatten();
+            indices.zip(names).map(move |(index, name)| (index, name.clone(), level))
+        })
+        .collect::<Vec<_>>();
+    lint_opts.sort_by_key(|&(index, ..)| index);
+
+    lint_opts.into_iter().map(|(_, name, level)| (name, level)).collect()
+}
+
+/// `--lang` が指定されなかった場合、環境変数 `LANG` から言語を決定する
+pub(crate) fn select_lang(lang: Option<Lang>) -> Lang {
+    lang.or_else(Lang::from_env).unwrap_or_default()
+}
+
+/// 診断の出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorOutputType {
+    /// `ariadne` による人間が読むための出力
+    #[default]
+    #[value(name = "human")]
+    HumanReadable,
+
+    /// 一行につき一つの診断を JSON オブジェクトとして出力する
+    Json,
+}
+
+/// `--emit` で出力する中間表現の種類
+///
+/// コンパイラの段階の順に並んでおり、順序の比較で段階の前後を判定できる。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum EmitKind {
+    /// 一行に一つずつ、位置と種類を添えたトークン列
+    Tokens,
+
+    /// 位置を添えた AST の木構造
+    Ast,
+
+    /// ソースコードの形式で書き直した AST
+    AstPretty,
+
+    /// 位置と名前解決の結果を添えた SIR の木構造
+    #[default]
+    Sir,
+
+    /// ソースコードに近い形式で書き直した、名前解決の結果 (定義の完全なパスと変数の束縛) をもつ SIR
+    SirPretty,
+
+    /// 各関数のシグネチャと、式とパターンの型
+    Types,
+
+    /// 他のステロから `--extern` で読み込むためのバイナリ形式のメタデータ
+    Metadata,
+
+    /// 制御フローグラフに基づく中間表現 (MIR)
+    Mir,
+
+    /// C99 のソースコード
+    C,
+
+    /// WebAssembly のテキスト形式
+    Wat,
+
+    /// スタック型のバイトコードの逆アセンブル結果
+    Bytecode,
+
+    /// x86-64 のアセンブリ (GNU アセンブラの Intel 記法)
+    Asm,
+
+    /// システムの `as` と `ld` でアセンブリからリンクした実行ファイル
+    Exe,
+}
+
+impl EmitKind {
+    /// `-o` が指定されなかった場合に、ステロ名に付ける出力ファイルの拡張子。
+    /// 実行ファイルには拡張子を付けない。
+    pub fn extension(self) -> Option<&'static str> {
+        let ext = match self {
+            EmitKind::Tokens => "tokens",
+            EmitKind::Ast => "ast",
+            EmitKind::AstPretty => "ast.stelo",
+            EmitKind::Sir => "sir",
+            EmitKind::SirPretty => "sir.stelo",
+            EmitKind::Types => "types",
+            EmitKind::Metadata => "stmeta",
+            EmitKind::Mir => "mir",
+            EmitKind::C => "c",
+            EmitKind::Wat => "wat",
+            EmitKind::Bytecode => "bytecode",
+            EmitKind::Asm => "s",
+            EmitKind::Exe => return None,
+        };
+        Some(ext)
+    }
+}
+
+/// `--stelo-type` で指定する、生成するステロの種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SteloType {
+    /// `main` 関数から実行を始める実行可能なステロ
+    #[default]
+    Bin,
+
+    /// 他のステロから `--extern` で読み込むためのステロ
+    Lib,
+}
+
+/// `--extern 名前=パス` で指定された、読み込むステロ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternEntry {
+    /// ステロを参照する際の名前
+    pub name: String,
+
+    /// `--stelo-type=lib` で出力されたメタデータのパス
+    pub path: PathBuf,
+}
+
+impl ExternEntry {
+    /// `名前=パス` の形式の引数を解釈する。
+    pub(crate) fn parse(s: &str) -> Result<ExternEntry, String> {
+        let Some((name, path)) = s.split_once('=') else {
+            return Err(format!("`{s}` は `名前=パス` の形式ではありません"));
+        };
+
+        let is_ident = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_ident {
+            return Err(format!("`{name}` はステロの名前として使えません"));
+        }
+
+        Ok(ExternEntry { name: name.to_string(), path: PathBuf::from(path) })
+    }
+}
+
+/// `-C` で指定するコード生成のオプション
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenFlag {
+    /// 整数の算術演算のオーバーフローを実行時に検査する (`-C overflow-checks[=yes|no]`)
+    OverflowChecks(bool),
+
+    /// 前回のコンパイルの結果を保存し、変更されていない関数の結果を再利用する (`-C incremental[=DIR]`)
+    Incremental(Option<PathBuf>),
+}
+
+impl CodegenFlag {
+    /// `名前[=値]` の形式のオプションを解釈する。
+    pub(crate) fn parse(s: &str) -> Result<CodegenFlag, String> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (s, None),
+        };
+
+        match name {
+            "overflow-checks" => parse_bool(value).map(CodegenFlag::OverflowChecks),
+            "incremental" => Ok(CodegenFlag::Incremental(value.map(PathBuf::from))),
+            _ => Err(format!("不明なコード生成のオプションです: `{name}`")),
+        }
+    }
+}
+
+/// 真偽値のオプションの値を解釈する。値を省略した場合は `true` とする。
+fn parse_bool(value: Option<&str>) -> Result<bool, String> {
+    match value {
+        None | Some("y" | "yes" | "on" | "true") => Ok(true),
+        Some("n" | "no" | "off" | "false") => Ok(false),
+        Some(value) => Err(format!(
+            "`{value}` は真偽値ではありません (`yes` または `no` を指定してください)"
+        )),
+    }
+}
+
+/// `-C` で指定されたコード生成のオプションの集まり
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+    /// オーバーフローの検査の有無。省略した場合は、リリースビルドでなければ検査する
+    pub overflow_checks: Option<bool>,
+
+    /// インクリメンタルコンパイルを行う
+    pub incremental: bool,
+
+    /// インクリメンタルコンパイルのキャッシュを保存するディレクトリ。
+    /// 省略した場合は、一時ファイルのディレクトリ (`CompilerPaths::temps_dir`) に保存する
+    pub incremental_dir: Option<PathBuf>,
+}
+
+impl CodegenOptions {
+    fn from_flags(flags: &[CodegenFlag]) -> Self {
+        let mut opts = CodegenOptions::default();
+
+        for flag in flags {
+            match flag {
+                CodegenFlag::OverflowChecks(b) => opts.overflow_checks = Some(*b),
+                CodegenFlag::Incremental(dir) => {
+                    opts.incremental = true;
+                    opts.incremental_dir = dir.clone();
+                }
+            }
+        }
+
+        opts
+    }
+}
+
+/// `-Z` で指定する不安定なオプション
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum UnstableFlag {
+    /// コンパイラの各段階の統計を表示する
+    Statistics,
+
+    /// インクリメンタルコンパイルで前回の結果を再利用した数を表示する
+    IncrementalInfo,
+
+    /// 標準ステロ `std` を読み込まない
+    NoStd,
+}
+
+/// `-Z` で指定された不安定なオプションの集まり
+#[derive(Debug, Clone, Default)]
+pub struct UnstableOptions {
+    /// MIR の最適化などの統計を標準エラー出力に表示する
+    pub statistics: bool,
+
+    /// インクリメンタルコンパイルで前回の結果を再利用した数を標準エラー出力に表示する
+    pub incremental_info: bool,
+
+    /// 標準ステロ `std` を読み込まず、プレリュードも使わない。`std` 自身のコンパイルに使う
+    pub no_std: bool,
+}
+
+impl UnstableOptions {
+    fn from_flags(flags: &[UnstableFlag]) -> Self {
+        let mut opts = UnstableOptions::default();
+
+        for flag in flags {
+            match flag {
+                UnstableFlag::Statistics => opts.statistics = true,
+                UnstableFlag::IncrementalInfo => opts.incremental_info = true,
+                UnstableFlag::NoStd => opts.no_std = true,
+            }
+        }
+
+        opts
     }
 }
 
@@ -26,3 +354,37 @@ pub enum Input {
     File(PathBuf),
     Str { name: String, input: String },
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use crate::Args;
+    use crate::stelaro_session::config::build_session_options;
+    use crate::stelaro_session::lint::LintLevel;
+
+    fn lint_opts(argv: &[&str]) -> Vec<(String, LintLevel)> {
+        let matches = Args::command().get_matches_from(argv);
+        let args = Args::from_arg_matches(&matches).unwrap();
+        build_session_options(&args, &matches).lint_opts
+    }
+
+    #[test]
+    fn test_later_lint_flag_wins() {
+        let opts = lint_opts(&["stelaro", "a.stelo", "-D", "x", "-A", "x"]);
+        assert_eq!(
+            opts,
+            [("x".to_string(), LintLevel::Deny), ("x".to_string(), LintLevel::Allow)],
+        );
+
+        let opts = lint_opts(&["stelaro", "a.stelo", "-A", "x", "-W", "y", "-D", "x"]);
+        assert_eq!(
+            opts,
+            [
+                ("x".to_string(), LintLevel::Allow),
+                ("y".to_string(), LintLevel::Warn),
+                ("x".to_string(), LintLevel::Deny),
+            ],
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+
+/// リントの報告レベル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// 報告しない
+    Allow,
+    /// 警告として報告する
+    Warn,
+    /// エラーとして報告し、コンパイルを失敗させる
+    Deny,
+}
+
+impl LintLevel {
+    /// 属性名 (`allow`, `warn`, `deny`) からレベルを得る
+    pub fn from_attr_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        }
+    }
+
+    /// このレベルを指定するコマンドライン引数
+    pub fn as_flag(self) -> &'static str {
+        match self {
+            LintLevel::Allow => "-A",
+            LintLevel::Warn => "-W",
+            LintLevel::Deny => "-D",
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 登録されたリントの定義
+#[derive(Debug)]
+pub struct Lint {
+    /// `NON_SNAKE_CASE` のように、定義時の静的変数名で表されたリント名
+    pub name: &'static str,
+
+    /// 属性やコマンドライン引数で指定されない場合のレベル
+    pub default_level: LintLevel,
+
+    /// リントの短い説明
+    pub desc: &'static str,
+}
+
+impl Lint {
+    /// 属性やコマンドライン引数で使われる小文字のリント名 (e.g. `non_snake_case`)
+    pub fn name_lower(&self) -> String {
+        self.name.to_ascii_lowercase()
+    }
+}
+
+impl PartialEq for Lint {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Lint {}
+
+/// リントを定義し、`&'static Lint` として参照できる静的変数を生成します。
+///
+/// ```ignore
+/// declare_lint! {
+///     pub NON_SNAKE_CASE, Warn, "名前が snake_case でない"
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_lint {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr) => {
+        $(#[$attr])*
+        $vis static $NAME: &$crate::stelaro_session::lint::Lint = &$crate::stelaro_session::lint::Lint {
+            name: stringify!($NAME),
+            default_level: $crate::stelaro_session::lint::LintLevel::$Level,
+            desc: $desc,
+        };
+    };
+}
+
+/// コマンドライン引数や属性で指定されたリント名を正規化する
+/// (e.g. `non-snake-case` -> `non_snake_case`)
+pub fn normalize_lint_name(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// 名前からリントを探す
+pub fn find_lint(name: &str) -> Option<&'static Lint> {
+    let name = normalize_lint_name(name);
+
+    builtin::BUILTIN_LINTS
+        .iter()
+        .copied()
+        .find(|lint| lint.name_lower() == name)
+}
+
+pub mod builtin {
+    use super::Lint;
+
+    declare_lint! {
+        /// 全ての警告に対するリントのグループ。
+        /// `-D warnings` では、警告レベルのリント全てがエラーとして報告される。
+        pub WARNINGS, Warn, "警告として報告される全てのリント"
+    }
+
+    declare_lint! {
+        pub NON_SNAKE_CASE, Warn, "関数、モジュール、変数の名前が snake_case でない"
+    }
+
+    declare_lint! {
+        pub UNUSED_VARIABLES, Warn, "使用されていない変数"
+    }
+
+    declare_lint! {
+        pub UNKNOWN_LINTS, Warn, "存在しないリント名が指定された"
+    }
+
+    declare_lint! {
+        pub ARITHMETIC_OVERFLOW, Deny, "算術演算が常にオーバーフローする"
+    }
+
+    declare_lint! {
+        pub UNCONDITIONAL_PANIC, Deny, "ゼロ除算などで常にパニックする演算"
+    }
+
+    /// 組み込みのリントのレジストリ。
+    /// リントを追加するときには、名前で参照可能にするためにここに追加しなければならない。
+    pub static BUILTIN_LINTS: &[&Lint] = &[
+        WARNINGS,
+        NON_SNAKE_CASE,
+        UNUSED_VARIABLES,
+        UNKNOWN_LINTS,
+        ARITHMETIC_OVERFLOW,
+        UNCONDITIONAL_PANIC,
+    ];
+
+    /// MIR に対して実行されるリント。
+    /// MIR は属性を持たないため、これらのレベルは AST に対するリントの実行時に記録しておく。
+    pub static MIR_LINTS: &[&Lint] = &[
+        ARITHMETIC_OVERFLOW,
+        UNCONDITIONAL_PANIC,
+    ];
+}
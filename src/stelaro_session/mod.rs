@@ -1,4 +1,5 @@
 pub mod config;
+pub mod lint;
 pub mod parse;
 pub mod session;
 
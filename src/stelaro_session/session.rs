@@ -3,10 +3,12 @@ use std::rc::Rc;
 
 use crate::stelaro_common::{SourceMap, source_map::get_source_map};
 use crate::stelaro_diagnostics::{
-    DiagCtxt, DiagCtxtHandle,
+    DiagCtxt, DiagCtxtFlags, DiagCtxtHandle,
     emitter::{AriadneEmitter, DynEmitter},
+    json::JsonEmitter,
 };
-use crate::stelaro_session::config::Options;
+use crate::stelaro_session::config::{ErrorOutputType, Options};
+use crate::stelaro_session::lint::{LintLevel, builtin::WARNINGS, normalize_lint_name};
 
 use super::config::Input;
 use super::parse::ParseSess;
@@ -35,21 +37,50 @@ impl Session {
     pub fn source_map(&self) -> &SourceMap {
         self.psess.source_map()
     }
+
+    /// 整数の算術演算のオーバーフローを実行時に検査するかどうか。
+    /// `-C overflow-checks` の指定が無ければ、リリースビルドでない場合に検査する。
+    pub fn overflow_checks(&self) -> bool {
+        self.opts.cg_opts.overflow_checks.unwrap_or(!self.opts.release)
+    }
+
+    /// インクリメンタルコンパイルのキャッシュを保存するディレクトリ。
+    /// インクリメンタルコンパイルを行わない場合は `None` を返す。
+    pub fn incremental_dir(&self) -> Option<PathBuf> {
+        let cg_opts = &self.opts.cg_opts;
+        if !cg_opts.incremental {
+            return None;
+        }
+
+        cg_opts
+            .incremental_dir
+            .clone()
+            .or_else(|| Some(self.paths.temps_dir.as_ref()?.join("stelaro-incremental")))
+    }
 }
 
 pub fn default_emitter(
     source_map: Rc<SourceMap>,
+    error_format: ErrorOutputType,
 ) -> Box<DynEmitter> {
-    Box::new(AriadneEmitter::new(source_map))
+    match error_format {
+        ErrorOutputType::HumanReadable => Box::new(AriadneEmitter::new(source_map)),
+        ErrorOutputType::Json => Box::new(JsonEmitter::stderr(source_map)),
+    }
 }
 
+/// `emitter` を省略した場合は、`opts.error_format` に従って診断を標準エラー出力に出力する。
 pub fn build_session(
     opts: Options,
     paths: CompilerPaths,
+    emitter: Option<Box<DynEmitter>>,
 ) -> Session {
     let source_map = get_source_map().unwrap();
-    let emitter = default_emitter(Rc::clone(&source_map));
-    let dcx = DiagCtxt::new(emitter);
+    let emitter = emitter
+        .unwrap_or_else(|| default_emitter(Rc::clone(&source_map), opts.error_format));
+    let dcx = DiagCtxt::new(emitter)
+        .with_flags(diag_ctxt_flags(&opts))
+        .with_lang(opts.lang);
 
     let psess = ParseSess::with_dcx(dcx, source_map);
 
@@ -65,3 +96,17 @@ pub fn build_session(
             },
     }
 }
+
+/// `-A warnings`, `-D warnings` の指定を診断の出力方法に反映する
+fn diag_ctxt_flags(opts: &Options) -> DiagCtxtFlags {
+    let warnings_level = opts.lint_opts
+        .iter()
+        .rev()
+        .find(|(name, _)| normalize_lint_name(name) == WARNINGS.name_lower())
+        .map(|&(_, level)| level);
+
+    DiagCtxtFlags {
+        can_emit_warnings: warnings_level != Some(LintLevel::Allow),
+        warnings_as_errors: warnings_level == Some(LintLevel::Deny),
+    }
+}
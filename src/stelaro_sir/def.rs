@@ -1,4 +1,5 @@
 use crate::stelaro_common::{DefId, Symbol};
+use crate::stelaro_diagnostics::Lang;
 use crate::stelaro_sir::{sir::PrimTy, sir_id::SirId};
 
 use super::definitions::DefPathData;
@@ -6,10 +7,12 @@ use super::definitions::DefPathData;
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DefKind {
     Mod, // モジュール
+    TyAlias, // 型エイリアス
     // Struct,
     // Enum,
     // Field,
     Fn, // 関数定義
+    Closure, // クロージャ式
     // Static, // Static item
     // Const,  // Const item
 }
@@ -19,6 +22,8 @@ impl DefKind {
     pub fn descr(self, def_id: DefId) -> &'static str {
         match self {
             DefKind::Fn => "function",
+            DefKind::Closure => "closure",
+            DefKind::TyAlias => "type alias",
             DefKind::Mod if def_id.is_stelo_root() && !def_id.is_local() => "stelo",
             DefKind::Mod => "module",
         }
@@ -27,14 +32,25 @@ impl DefKind {
     pub fn descr_ja(self, def_id: DefId) -> &'static str {
         match self {
             DefKind::Fn => "関数",
+            DefKind::Closure => "クロージャ",
+            DefKind::TyAlias => "型エイリアス",
             DefKind::Mod if def_id.is_stelo_root() && !def_id.is_local() => "ステロ",
             DefKind::Mod => "モジュール",
         }
     }
 
+    /// 診断の言語に応じた説明を得る。
+    pub fn descr_in(self, def_id: DefId, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.descr_ja(def_id),
+            Lang::En => self.descr(def_id),
+        }
+    }
+
     pub fn def_path_data(self, name: Option<Symbol>) -> DefPathData {
         match self {
             DefKind::Mod
+                    | DefKind::TyAlias
                     // | DefKind::Struct
                     // | DefKind::Enum
                     // | DefKind::Variant
@@ -45,6 +61,7 @@ impl DefKind {
                     // | DefKind::Static { .. }
                     // | DefKind::Field
                         => DefPathData::ValueNs(name.unwrap()),
+            DefKind::Closure => DefPathData::Closure,
             // DefKind::Ctor => DefPathData::Ctor,
         }
     }
@@ -89,6 +106,13 @@ impl<Id> Res<Id> {
         }
     }
 
+    pub fn descr_in(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.descr_ja(),
+            Lang::En => self.descr(),
+        }
+    }
+
     pub fn apply_id<R, E>(self, mut map: impl FnMut(Id) -> Result<R, E>) -> Result<Res<R>, E> {
         Ok(match self {
             Res::Def(kind, id) => Res::Def(kind, id),
@@ -119,6 +143,13 @@ impl Namespace {
             Self::ValueNS => "値",
         }
     }
+
+    pub fn descr_in(self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.descr_ja(),
+            Lang::En => self.descr(),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
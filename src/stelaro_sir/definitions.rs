@@ -72,6 +72,16 @@ impl DefPathTable {
         DefPathHash::new(self.stable_stelo_id, hash)
     }
 
+    /// `DefPathHash` に対応する定義の `DefIndex` を返す。
+    /// 他のステロの `DefPathHash` や、このステロに存在しない定義に対しては `None` を返す。
+    #[inline]
+    pub fn def_path_hash_to_index(&self, hash: DefPathHash) -> Option<DefIndex> {
+        if hash.stable_stelo_id() != self.stable_stelo_id {
+            return None;
+        }
+        self.def_path_hash_to_index.get(&hash.local_hash()).copied()
+    }
+
     pub fn enumerated_keys_and_path_hashes(
         &self,
     ) -> impl ExactSizeIterator<Item = (DefIndex, &DefKey, DefPathHash)> {
@@ -157,6 +167,10 @@ pub struct DefPath {
 
 impl DisambiguatedDefPathData {
     pub fn fmt_maybe_verbose(&self, writer: &mut impl Write, verbose: bool) -> fmt::Result {
+        if self.data == DefPathData::Closure {
+            return write!(writer, "{{closure#{}}}", self.disambiguator);
+        }
+
         let name = self.data.get_opt_name().unwrap_or(sym::UNKNOWN);
         if verbose && self.disambiguator != 0 {
             write!(writer, "{}#{}", name, self.disambiguator)
@@ -222,6 +236,8 @@ pub enum DefPathData {
     TypeNs(Option<Symbol>),
     /// 値名前空間に属するもの。
     ValueNs(Symbol),
+    /// クロージャ式。名前をもたないため、曖昧さ回避子で区別される。
+    Closure,
     // アイテムの構成要素:
     // /// ユニット型あるいはタプル様の構造体、またはenumバリアントの暗黙的なコンストラクタ。
     // Ctor,
@@ -247,6 +263,15 @@ impl Definitions {
         self.table.def_path_hash(id.local_def_index)
     }
 
+    /// `DefPathHash` に対応するローカルな定義を返します。
+    /// 前回のコンパイルで保存された `DefPathHash` から、現在のセッションの `LocalDefId` を得るために使います。
+    #[inline]
+    pub fn local_def_path_hash_to_def_id(&self, hash: DefPathHash) -> Option<LocalDefId> {
+        self.table
+            .def_path_hash_to_index(hash)
+            .map(|local_def_index| LocalDefId { local_def_index })
+    }
+
     /// ステロルートから `index` で識別される定義へのパスを返します。
     /// ルートノードはこのパスに含まれません。(i.e. ステロルート自身に対しては空のベクタになります)
     /// 将来的に、インライン化されたアイテムの場合、これは外部ステロにおけるアイテムのパスになります。
@@ -321,7 +346,7 @@ impl DefPathData {
 
             ValueNs(name) => Some(name),
 
-            Self::SteloRoot => None,
+            Self::SteloRoot | Self::Closure => None,
         }
     }
 }
@@ -0,0 +1,271 @@
+//! `--emit=sir` のための、SIR の木構造の出力。
+//!
+//! `--emit=ast` と同じく各行は `行:列-行:列` の位置をもつ。
+//! パスには名前解決の結果を併記する。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::CaptureBy;
+use crate::stelaro_common::{LineTable, Span};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::Res;
+use crate::stelaro_sir::sir::{
+    Block, Expr, ExprKind, FnRetTy, Item, ItemId, ItemKind, LitKind, LoopSource, Mod, Pat,
+    PatKind, Path, Stmt, StmtKind, Ty, TyKind,
+};
+
+
+const INDENT: &str = "  ";
+
+/// SIR を、ルートモジュールから順にノードごとに一行の字下げされた木として出力する。
+pub fn write_sir_tree(tcx: TyCtxt<'_>, lines: &LineTable, w: &mut dyn Write) -> fmt::Result {
+    let root = tcx.sir_root_module();
+    let mut printer = TreePrinter {
+        tcx,
+        lines,
+        w,
+        depth: 0,
+    };
+
+    printer.node("Stelo", root.spans.inner_span, |this| this.print_mod_items(root))
+}
+
+struct TreePrinter<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    lines: &'a LineTable,
+    w: &'a mut dyn Write,
+    depth: usize,
+}
+
+impl<'tcx> TreePrinter<'_, 'tcx> {
+    /// `label` と `span` の行を書き、`children` を一段深く字下げして書く。
+    fn node(
+        &mut self,
+        label: &str,
+        span: Span,
+        children: impl FnOnce(&mut Self) -> fmt::Result,
+    ) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.w.write_str(INDENT)?;
+        }
+        writeln!(self.w, "{label} @ {}", self.lines.span_to_range_string(span))?;
+
+        self.depth += 1;
+        let result = children(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn leaf(&mut self, label: &str, span: Span) -> fmt::Result {
+        self.node(label, span, |_| Ok(()))
+    }
+
+    fn print_mod_items(&mut self, module: &Mod<'tcx>) -> fmt::Result {
+        for &item_id in module.item_ids {
+            self.print_item_id(item_id)?;
+        }
+        Ok(())
+    }
+
+    fn print_item_id(&mut self, item_id: ItemId) -> fmt::Result {
+        let item = self.tcx.sir_item(item_id);
+        self.print_item(item)
+    }
+
+    fn print_item(&mut self, item: &Item<'tcx>) -> fmt::Result {
+        let path = self.tcx.def_path_str(item.owner_id.def_id.to_def_id());
+
+        match item.kind {
+            ItemKind::Fn { sig, body, .. } => self.node(&format!("Fn {path}"), item.span, |this| {
+                let body = this.tcx.sir_body(body);
+                for (param, ty) in body.params.iter().zip(sig.decl.inputs) {
+                    this.node("Param", param.span, |this| {
+                        this.print_pat(param.pat)?;
+                        this.print_ty(ty)
+                    })?;
+                }
+                match sig.decl.output {
+                    FnRetTy::DefaultReturn(span) => this.leaf("Ret ()", span)?,
+                    FnRetTy::Return(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty))?,
+                }
+                this.node("Body", body.value.span, |this| this.print_expr(body.value))
+            }),
+            ItemKind::Mod(_, module) => self.node(&format!("Mod {path}"), item.span, |this| {
+                this.print_mod_items(module)
+            }),
+            ItemKind::TyAlias(_, ty) => {
+                self.node(&format!("TyAlias {path}"), item.span, |this| this.print_ty(ty))
+            }
+        }
+    }
+
+    fn print_block(&mut self, label: &str, block: &Block<'tcx>) -> fmt::Result {
+        self.node(label, block.span, |this| {
+            for stmt in block.stmts {
+                this.print_stmt(stmt)?;
+            }
+            match block.expr {
+                Some(expr) => this.node("Tail", expr.span, |this| this.print_expr(expr)),
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn print_stmt(&mut self, stmt: &Stmt<'tcx>) -> fmt::Result {
+        match stmt.kind {
+            StmtKind::Let(local) => self.node("Let", stmt.span, |this| {
+                this.print_pat(local.pat)?;
+                if let Some(ty) = local.ty {
+                    this.print_ty(ty)?;
+                }
+                match local.init {
+                    Some(init) => this.print_expr(init),
+                    None => Ok(()),
+                }
+            }),
+            StmtKind::Item(item_id) => self.print_item_id(item_id),
+            StmtKind::Expr(expr) => self.node("Expr", stmt.span, |this| this.print_expr(expr)),
+            StmtKind::Semi(expr) => self.node("Semi", stmt.span, |this| this.print_expr(expr)),
+            StmtKind::Break(_, expr) => self.node("Break", stmt.span, |this| match expr {
+                Some(expr) => this.print_expr(expr),
+                None => Ok(()),
+            }),
+            StmtKind::Continue(_) => self.leaf("Continue", stmt.span),
+            StmtKind::Return(expr) => self.node("Return", stmt.span, |this| match expr {
+                Some(expr) => this.print_expr(expr),
+                None => Ok(()),
+            }),
+            StmtKind::Loop(block, source, _) => {
+                let label = match source {
+                    LoopSource::Loop => "Loop",
+                    LoopSource::While => "Loop (while)",
+                };
+                self.node(label, stmt.span, |this| this.print_block("Block", block))
+            }
+        }
+    }
+
+    fn print_expr(&mut self, expr: &Expr<'tcx>) -> fmt::Result {
+        match expr.kind {
+            ExprKind::Call(func, args) => self.node("Call", expr.span, |this| {
+                this.print_expr(func)?;
+                for arg in args {
+                    this.print_expr(arg)?;
+                }
+                Ok(())
+            }),
+            ExprKind::Binary(op, lhs, rhs) => {
+                self.node(&format!("Binary {}", op.node.as_str()), expr.span, |this| {
+                    this.print_expr(lhs)?;
+                    this.print_expr(rhs)
+                })
+            }
+            ExprKind::Unary(op, operand) => {
+                self.node(&format!("Unary {}", op.as_str()), expr.span, |this| {
+                    this.print_expr(operand)
+                })
+            }
+            ExprKind::Lit(lit) => self.leaf(&format!("Lit {}", lit_to_string(&lit.node)), expr.span),
+            ExprKind::If(cond, then, els) => self.node("If", expr.span, |this| {
+                this.print_expr(cond)?;
+                this.node("Then", then.span, |this| this.print_expr(then))?;
+                match els {
+                    Some(els) => this.node("Else", els.span, |this| this.print_expr(els)),
+                    None => Ok(()),
+                }
+            }),
+            ExprKind::Path(path) => {
+                let label = format!("Path {}", self.path_with_res(&path));
+                self.leaf(&label, expr.span)
+            }
+            ExprKind::Block(block) => self.print_block("Block", block),
+            ExprKind::Assign(lhs, rhs, _) => self.node("Assign", expr.span, |this| {
+                this.print_expr(lhs)?;
+                this.print_expr(rhs)
+            }),
+            ExprKind::Closure(closure) => {
+                let path = self.tcx.def_path_str(closure.def_id.to_def_id());
+                let label = match closure.capture_clause {
+                    CaptureBy::Value { .. } => format!("Closure move {path}"),
+                    CaptureBy::Ref => format!("Closure {path}"),
+                };
+                self.node(&label, expr.span, |this| {
+                    let body = this.tcx.sir_body(closure.body);
+                    for (param, ty) in body.params.iter().zip(closure.fn_decl.inputs) {
+                        this.node("Param", param.span, |this| {
+                            this.print_pat(param.pat)?;
+                            this.print_ty(ty)
+                        })?;
+                    }
+                    match closure.fn_decl.output {
+                        FnRetTy::DefaultReturn(span) => this.leaf("Ret _", span)?,
+                        FnRetTy::Return(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty))?,
+                    }
+                    this.node("Body", body.value.span, |this| this.print_expr(body.value))
+                })
+            }
+            ExprKind::Err(_) => self.leaf("Err", expr.span),
+        }
+    }
+
+    fn print_pat(&mut self, pat: &Pat) -> fmt::Result {
+        match pat.kind {
+            PatKind::WildCard => self.leaf("Pat _", pat.span),
+            PatKind::Binding(_, ident) => self.leaf(&format!("Pat {ident}"), pat.span),
+        }
+    }
+
+    fn print_ty(&mut self, ty: &Ty<'tcx>) -> fmt::Result {
+        match ty.kind {
+            TyKind::Path(path) => {
+                let label = format!("Ty {}", self.path_with_res(&path));
+                self.leaf(&label, ty.span)
+            }
+            TyKind::Unit => self.leaf("Ty ()", ty.span),
+            TyKind::Never => self.leaf("Ty !", ty.span),
+            TyKind::Infer => self.leaf("Ty _", ty.span),
+            TyKind::FnPtr(decl) => self.node("Ty fn", ty.span, |this| {
+                for input in decl.inputs {
+                    this.print_ty(input)?;
+                }
+                match decl.output {
+                    FnRetTy::DefaultReturn(span) => this.leaf("Ret ()", span),
+                    FnRetTy::Return(ty) => this.node("Ret", ty.span, |this| this.print_ty(ty)),
+                }
+            }),
+        }
+    }
+
+    /// `a::b => function a::b` のように、パスとその解決結果を表す。
+    fn path_with_res(&self, path: &Path<'tcx>) -> String {
+        let written = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.name.as_str())
+            .collect::<Vec<_>>()
+            .join("::");
+
+        let res = match path.res {
+            Res::Def(kind, def_id) => {
+                format!("{} {}", kind.descr(def_id), self.tcx.def_path_str(def_id))
+            }
+            Res::Local(_) => "local".to_string(),
+            Res::PrimTy(_) => "primitive".to_string(),
+            Res::Err => "{error}".to_string(),
+        };
+
+        format!("{written} => {res}")
+    }
+}
+
+/// リテラルをソースコード上の表記で表す。
+pub(crate) fn lit_to_string(lit: &LitKind) -> String {
+    match lit {
+        LitKind::Str(symbol) => format!("{:?}", symbol.as_str()),
+        LitKind::Char(c) => format!("{c:?}"),
+        LitKind::Int(n) => n.to_string(),
+        LitKind::Float(symbol) => symbol.to_string(),
+        LitKind::Bool(b) => b.to_string(),
+        LitKind::Err(_) => "{error}".to_string(),
+    }
+}
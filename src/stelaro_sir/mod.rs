@@ -1,7 +1,10 @@
 pub mod def;
 pub mod definitions;
+pub mod dump;
+pub mod pretty;
 pub mod sir;
 pub mod sir_id;
+pub mod stable_hash;
 pub mod visit;
 
 pub use def::{DefKind, Namespace, PerNS, Res};
@@ -10,32 +13,25 @@ pub use visit::{Visitor, nested_filter::NestedFilter};
 
 use crate::stelaro_common::LocalDefId;
 use crate::stelaro_context::TyCtxt;
+use crate::stelaro_context::query::Providers;
 use crate::stelaro_sir::{
-    sir::OwnerNodes,
     sir_id::{ItemLocalId, OwnerId, STELO_SIR_ID, SirId},
 };
 
-impl<'tcx> TyCtxt<'tcx> {
-    #[inline]
-    pub fn sir_stelo(self) -> &'tcx sir::Stelo<'tcx> {
-        match *self.sir_stelo.borrow() {
-            Some(stelo) => stelo,
-            None => panic!("bug: TyCtxt sir::Stelo は初期化されていません"),
-        }
-    }
-
-    #[inline]
-    pub fn opt_sir_owner_nodes(self, def_id: LocalDefId) -> Option<&'tcx OwnerNodes<'tcx>> {
-        self.sir_stelo()
+pub fn provide(providers: &mut Providers) {
+    providers.opt_sir_owner_nodes = |tcx, def_id| {
+        tcx.sir_stelo(())
             .owners
             .get(def_id)?
             .as_owner()
             .map(|i| &i.nodes)
-    }
+    };
+}
 
+impl<'tcx> TyCtxt<'tcx> {
     #[inline]
     pub fn local_def_id_to_sir_id(self, def_id: LocalDefId) -> SirId {
-        match self.sir_stelo().owners[def_id] {
+        match self.sir_stelo(()).owners[def_id] {
             sir::MaybeOwner::Owner(_) => SirId::make_owner(def_id),
             sir::MaybeOwner::NonOwner(sir_id) => sir_id,
             sir::MaybeOwner::Phantom => panic!("bug: {:?} に SirId はない", def_id),
@@ -49,7 +45,7 @@ impl<'tcx> TyCtxt<'tcx> {
                 let parent_owner_id = self.local_def_id_to_sir_id(parent_def_id).owner;
                 SirId {
                     owner: parent_owner_id,
-                    local_id: self.sir_stelo().owners[parent_owner_id.def_id]
+                    local_id: self.sir_stelo(()).owners[parent_owner_id.def_id]
                         .unwrap()
                         .parenting
                         .get(&owner_id.def_id)
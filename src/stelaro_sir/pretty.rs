@@ -0,0 +1,308 @@
+//! `--emit=sir-pretty` のための、SIR のソースコードに近い形式での出力。
+//!
+//! 定義に解決されたパスはステロ名から始まる完全なパスで出力し、ローカル変数は
+//! 束縛の `ItemLocalId` を付けて `x#3` の形式で出力する。これにより、名前解決の結果
+//! (どの定義、どの束縛を指すか) を読み取れる。
+//! `while` のような脱糖された構文は脱糖後の形で出力する。
+//! SIR は括弧を保持しないため、入れ子になった二項演算と代入は常に括弧で囲む。
+
+use std::fmt::{self, Write};
+
+use crate::stelaro_ast::ast::CaptureBy;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::Res;
+use crate::stelaro_sir::dump::lit_to_string;
+use crate::stelaro_sir::sir::{
+    Block, Expr, ExprKind, FnRetTy, ItemId, ItemKind, Mod, Pat, PatKind, Path, Stmt, StmtKind,
+    Ty, TyKind,
+};
+
+
+const INDENT: &str = "    ";
+
+/// SIR をソースコードの形式で出力する。
+pub fn write_sir_pretty(tcx: TyCtxt<'_>, w: &mut dyn Write) -> fmt::Result {
+    let mut printer = PrettyPrinter { tcx, w, depth: 0 };
+    printer.print_mod_items(tcx.sir_root_module())
+}
+
+struct PrettyPrinter<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    w: &'a mut dyn Write,
+    depth: usize,
+}
+
+impl<'tcx> PrettyPrinter<'_, 'tcx> {
+    fn indent(&mut self) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.w.write_str(INDENT)?;
+        }
+        Ok(())
+    }
+
+    fn print_mod_items(&mut self, module: &Mod<'tcx>) -> fmt::Result {
+        for (i, &item_id) in module.item_ids.iter().enumerate() {
+            if i != 0 {
+                writeln!(self.w)?;
+            }
+            self.print_item(item_id)?;
+        }
+        Ok(())
+    }
+
+    fn print_item(&mut self, item_id: ItemId) -> fmt::Result {
+        let item = self.tcx.sir_item(item_id);
+        self.indent()?;
+
+        match item.kind {
+            ItemKind::Fn { sig, ident, body } => {
+                let body = self.tcx.sir_body(body);
+
+                write!(self.w, "fn {ident}(")?;
+                for (i, (param, ty)) in body.params.iter().zip(sig.decl.inputs).enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_pat(param.pat)?;
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                write!(self.w, ")")?;
+                if let FnRetTy::Return(ty) = sig.decl.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                write!(self.w, " ")?;
+                self.print_expr(body.value)?;
+                writeln!(self.w)
+            }
+            ItemKind::Mod(ident, module) => {
+                writeln!(self.w, "mod {ident} {{")?;
+                self.depth += 1;
+                self.print_mod_items(module)?;
+                self.depth -= 1;
+                self.indent()?;
+                writeln!(self.w, "}}")
+            }
+            ItemKind::TyAlias(ident, ty) => {
+                write!(self.w, "type {ident} = ")?;
+                self.print_ty(ty)?;
+                writeln!(self.w, ";")
+            }
+        }
+    }
+
+    /// ブロックを `{` から `}` まで出力する。閉じ括弧の後の改行は呼び出し元が書く。
+    fn print_block(&mut self, block: &Block<'tcx>) -> fmt::Result {
+        if block.stmts.is_empty() && block.expr.is_none() {
+            return write!(self.w, "{{}}");
+        }
+
+        writeln!(self.w, "{{")?;
+        self.depth += 1;
+        for stmt in block.stmts {
+            self.print_stmt(stmt)?;
+        }
+        if let Some(expr) = block.expr {
+            self.indent()?;
+            self.print_expr(expr)?;
+            writeln!(self.w)?;
+        }
+        self.depth -= 1;
+        self.indent()?;
+        write!(self.w, "}}")
+    }
+
+    fn print_stmt(&mut self, stmt: &Stmt<'tcx>) -> fmt::Result {
+        if let StmtKind::Item(item_id) = stmt.kind {
+            return self.print_item(item_id);
+        }
+
+        self.indent()?;
+
+        match stmt.kind {
+            StmtKind::Let(local) => {
+                write!(self.w, "let ")?;
+                self.print_pat(local.pat)?;
+                if let Some(ty) = local.ty {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                if let Some(init) = local.init {
+                    write!(self.w, " = ")?;
+                    self.print_expr(init)?;
+                }
+                write!(self.w, ";")?;
+            }
+            StmtKind::Item(_) => unreachable!(),
+            StmtKind::Expr(expr) => self.print_expr(expr)?,
+            StmtKind::Semi(expr) => {
+                self.print_expr(expr)?;
+                write!(self.w, ";")?;
+            }
+            StmtKind::Break(_, expr) => {
+                write!(self.w, "break")?;
+                if let Some(expr) = expr {
+                    write!(self.w, " ")?;
+                    self.print_expr(expr)?;
+                }
+                write!(self.w, ";")?;
+            }
+            StmtKind::Continue(_) => write!(self.w, "continue;")?,
+            StmtKind::Return(expr) => {
+                write!(self.w, "return")?;
+                if let Some(expr) = expr {
+                    write!(self.w, " ")?;
+                    self.print_expr(expr)?;
+                }
+                write!(self.w, ";")?;
+            }
+            StmtKind::Loop(block, _, _) => {
+                write!(self.w, "loop ")?;
+                self.print_block(block)?;
+            }
+        }
+
+        writeln!(self.w)
+    }
+
+    fn print_expr(&mut self, expr: &Expr<'tcx>) -> fmt::Result {
+        match expr.kind {
+            ExprKind::Call(func, args) => {
+                self.print_expr(func)?;
+                write!(self.w, "(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_expr(arg)?;
+                }
+                write!(self.w, ")")
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                self.print_operand(lhs)?;
+                write!(self.w, " {} ", op.node.as_str())?;
+                self.print_operand(rhs)
+            }
+            ExprKind::Unary(op, operand) => {
+                write!(self.w, "{}", op.as_str())?;
+                self.print_operand(operand)
+            }
+            ExprKind::Lit(lit) => write!(self.w, "{}", lit_to_string(&lit.node)),
+            ExprKind::If(cond, then, els) => {
+                write!(self.w, "if ")?;
+                self.print_expr(cond)?;
+                write!(self.w, " ")?;
+                self.print_expr(then)?;
+                if let Some(els) = els {
+                    write!(self.w, " else ")?;
+                    self.print_expr(els)?;
+                }
+                Ok(())
+            }
+            ExprKind::Path(path) => self.print_path(&path),
+            ExprKind::Block(block) => self.print_block(block),
+            ExprKind::Assign(lhs, rhs, _) => {
+                self.print_expr(lhs)?;
+                write!(self.w, " = ")?;
+                self.print_expr(rhs)
+            }
+            ExprKind::Closure(closure) => {
+                let body = self.tcx.sir_body(closure.body);
+
+                if let CaptureBy::Value { .. } = closure.capture_clause {
+                    write!(self.w, "move ")?;
+                }
+                write!(self.w, "|")?;
+                for (i, (param, ty)) in body.params.iter().zip(closure.fn_decl.inputs).enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_pat(param.pat)?;
+                    if !matches!(ty.kind, TyKind::Infer) {
+                        write!(self.w, ": ")?;
+                        self.print_ty(ty)?;
+                    }
+                }
+                write!(self.w, "|")?;
+                if let FnRetTy::Return(ty) = closure.fn_decl.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                write!(self.w, " ")?;
+                self.print_expr(body.value)
+            }
+            ExprKind::Err(_) => write!(self.w, "{{error}}"),
+        }
+    }
+
+    /// 演算子の被演算子を出力する。結合の順序が失われないよう、二項演算と代入は括弧で囲む。
+    fn print_operand(&mut self, expr: &Expr<'tcx>) -> fmt::Result {
+        match expr.kind {
+            ExprKind::Binary(..) | ExprKind::Assign(..) => {
+                write!(self.w, "(")?;
+                self.print_expr(expr)?;
+                write!(self.w, ")")
+            }
+            _ => self.print_expr(expr),
+        }
+    }
+
+    fn print_pat(&mut self, pat: &Pat) -> fmt::Result {
+        match pat.kind {
+            PatKind::WildCard => write!(self.w, "_"),
+            PatKind::Binding(sir_id, ident) => write!(self.w, "{ident}#{}", sir_id.local_id.as_u32()),
+        }
+    }
+
+    fn print_ty(&mut self, ty: &Ty<'tcx>) -> fmt::Result {
+        match ty.kind {
+            TyKind::Path(path) => self.print_path(&path),
+            TyKind::Unit => write!(self.w, "()"),
+            TyKind::Never => write!(self.w, "!"),
+            TyKind::Infer => write!(self.w, "_"),
+            TyKind::FnPtr(decl) => {
+                write!(self.w, "fn(")?;
+                for (i, input) in decl.inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(self.w, ", ")?;
+                    }
+                    self.print_ty(input)?;
+                }
+                write!(self.w, ")")?;
+                if let FnRetTy::Return(ty) = decl.output {
+                    write!(self.w, ": ")?;
+                    self.print_ty(ty)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 定義に解決されたパスはステロ名から始まる完全なパスで、ローカル変数は束縛の ID を付けて、
+    /// それ以外は書かれた通りに出力する。
+    fn print_path(&mut self, path: &Path<'tcx>) -> fmt::Result {
+        match path.res {
+            Res::Def(_, def_id) => {
+                write!(self.w, "{}", self.tcx.stelo_name(def_id.stelo))?;
+                for component in &self.tcx.def_path(def_id).data {
+                    write!(self.w, "::{component}")?;
+                }
+                return Ok(());
+            }
+            Res::Local(sir_id) => {
+                let ident = path.segments.last().unwrap().ident;
+                return write!(self.w, "{ident}#{}", sir_id.local_id.as_u32());
+            }
+            Res::PrimTy(_) | Res::Err => {}
+        }
+
+        for (i, segment) in path.segments.iter().enumerate() {
+            if i != 0 {
+                write!(self.w, "::")?;
+            }
+            write!(self.w, "{}", segment.ident)?;
+        }
+        Ok(())
+    }
+}
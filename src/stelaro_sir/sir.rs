@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt};
 
-use crate::stelaro_ast::ast::{BinOp, UnOp};
+use crate::stelaro_ast::ast::{BinOp, CaptureBy, UnOp};
 use crate::stelaro_common::{Ident, IndexVec, LocalDefId, SortedMap, Span, Spanned, Symbol, sym};
 use crate::stelaro_diagnostics::ErrorEmitted;
 use crate::stelaro_sir::{
@@ -333,9 +333,26 @@ pub enum ExprKind<'sir> {
     /// 代入 (e.g., `a = foo()`)
     Assign(&'sir Expr<'sir>, &'sir Expr<'sir>, Span),
 
+    /// クロージャ (e.g., `move |a, b| a + b + offset`)
+    Closure(&'sir Closure<'sir>),
+
     Err(ErrorEmitted),
 }
 
+/// クロージャ式。
+///
+/// 本体は関数と同様に `Body` として、クロージャを含むオーナーに格納される。
+#[derive(Debug, Clone, Copy)]
+pub struct Closure<'sir> {
+    pub def_id: LocalDefId,
+    pub capture_clause: CaptureBy,
+    /// 引数と戻り値の型。省略された型は `TyKind::Infer` と `FnRetTy::DefaultReturn` になる。
+    pub fn_decl: &'sir FnDecl<'sir>,
+    pub body: BodyId,
+    /// `|...|` と戻り値の型を含む、クロージャのヘッダーのスパン
+    pub fn_decl_span: Span,
+}
+
 // アイテムの本体は、`Stelo` 内の別の
 // ハッシュマップに格納されます。ここでは、後で取得できるように
 // アイテムの sir-id を記録するだけです。
@@ -385,6 +402,9 @@ pub enum ItemKind<'sir> {
 
     /// モジュール
     Mod(Ident, &'sir Mod<'sir>),
+
+    /// 型エイリアス `type Meters = i64;`
+    TyAlias(Ident, &'sir Ty<'sir>),
 }
 
 /// SIR での型を表す。
@@ -403,7 +423,13 @@ pub enum TyKind<'sir> {
     // タプルが実装できた際に、これを削除し空のTupleがUnitを表すように変更する
     Unit,
 
+    /// 決して値をもたない `!` 型
+    Never,
+
     Infer,
+
+    /// `fn(i32): i32` のような関数ポインタ型
+    FnPtr(&'sir FnDecl<'sir>),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -413,6 +439,7 @@ pub enum PrimTy {
     Int(IntTy),
     Uint(UintTy),
     Float(FloatTy),
+    Str,
 }
 
 impl PrimTy {
@@ -420,8 +447,21 @@ impl PrimTy {
         let ty = match name {
             sym::BOOL => PrimTy::Bool,
             sym::CHAR => PrimTy::Char,
+            sym::ISIZE => PrimTy::Int(IntTy::Isize),
+            sym::I8 => PrimTy::Int(IntTy::I8),
+            sym::I16 => PrimTy::Int(IntTy::I16),
             sym::I32 => PrimTy::Int(IntTy::I32),
             sym::I64 => PrimTy::Int(IntTy::I64),
+            sym::I128 => PrimTy::Int(IntTy::I128),
+            sym::USIZE => PrimTy::Uint(UintTy::Usize),
+            sym::U8 => PrimTy::Uint(UintTy::U8),
+            sym::U16 => PrimTy::Uint(UintTy::U16),
+            sym::U32 => PrimTy::Uint(UintTy::U32),
+            sym::U64 => PrimTy::Uint(UintTy::U64),
+            sym::U128 => PrimTy::Uint(UintTy::U128),
+            sym::F32 => PrimTy::Float(FloatTy::F32),
+            sym::F64 => PrimTy::Float(FloatTy::F64),
+            sym::STR => PrimTy::Str,
             _ => return None,
         };
 
@@ -542,6 +582,11 @@ impl<'sir> Node<'sir> {
                 ..
             }) => Some((owner_id.def_id, *body)),
 
+            Node::Expr(Expr {
+                kind: ExprKind::Closure(closure),
+                ..
+            }) => Some((closure.def_id, closure.body)),
+
             _ => None,
         }
     }
@@ -0,0 +1,272 @@
+//! インクリメンタルコンパイルのための、SIR の安定したハッシュ。
+//!
+//! スパンは含めず、定義への参照は `DefId` の代わりに `DefPathHash` としてハッシュする。
+//! オーナー内の `ItemLocalId` は SIR の構造から決まるため、ローカル変数やループへの参照は
+//! `ItemLocalId` としてハッシュすれば、セッションをまたいで安定する。
+//! 型エイリアスへの参照は、エイリアスの定義の変更を検出するため、展開した型もハッシュする。
+
+use std::hash::Hash;
+use std::mem;
+
+use crate::stelaro_ast::ast::CaptureBy;
+use crate::stelaro_common::encoder::Encoder;
+use crate::stelaro_common::stable_hasher::HashStable;
+use crate::stelaro_common::{DefId, DefPathHash, Fingerprint, LocalDefId, StableHasher};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::def::{DefKind, Res};
+use crate::stelaro_sir::sir::{
+    Block, Destination, Expr, ExprKind, FnDecl, FnRetTy, ItemId, ItemKind, LitKind, Node, Pat,
+    PatKind, Path, Stmt, StmtKind, Ty, TyKind,
+};
+use crate::stelaro_sir::sir_id::SirId;
+use crate::stelaro_ty::codec::encode_ty;
+
+
+/// 関数のアイテムのフィンガープリント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnFingerprints {
+    /// シグネチャと本体を含むアイテム全体
+    pub item: Fingerprint,
+
+    /// シグネチャのみ。この関数を呼び出す関数の型検査は、シグネチャにのみ依存する
+    pub signature: Fingerprint,
+
+    /// アイテムから参照している定義。重複を除き、順序は安定している
+    pub deps: Vec<DefPathHash>,
+}
+
+/// 関数のアイテムの SIR から、セッションをまたいで安定したフィンガープリントを計算する。
+/// `def_id` が関数でない場合は `None` を返す。
+pub fn fn_fingerprints(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Option<FnFingerprints> {
+    let Node::Item(item) = tcx.sir_node_by_def_id(def_id) else {
+        return None;
+    };
+    let ItemKind::Fn { sig, ident, body } = item.kind else {
+        return None;
+    };
+    let body = tcx.sir_body(body);
+
+    let mut signature = SirHasher::new(tcx);
+    signature.hash_fn_decl(sig.decl);
+    let (signature, _) = signature.finish();
+
+    let mut hasher = SirHasher::new(tcx);
+    ident.hash_stable(&mut hasher.hasher);
+    hasher.hash_fn_decl(sig.decl);
+    body.params.len().hash_stable(&mut hasher.hasher);
+    for param in body.params {
+        hasher.hash_pat(param.pat);
+    }
+    hasher.hash_expr(body.value);
+
+    let (item, mut deps) = hasher.finish();
+    deps.sort_unstable();
+    deps.dedup();
+
+    Some(FnFingerprints {
+        item,
+        signature,
+        deps,
+    })
+}
+
+struct SirHasher<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    hasher: StableHasher,
+    deps: Vec<DefPathHash>,
+}
+
+impl<'tcx> SirHasher<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        SirHasher {
+            tcx,
+            hasher: StableHasher::new(),
+            deps: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> (Fingerprint, Vec<DefPathHash>) {
+        (self.hasher.finish(), self.deps)
+    }
+
+    fn hash_sir_id(&mut self, sir_id: SirId) {
+        sir_id.local_id.as_u32().hash_stable(&mut self.hasher);
+    }
+
+    fn hash_def_id(&mut self, def_id: DefId) {
+        let def_path_hash = self.tcx.def_path_hash(def_id);
+        def_path_hash.hash_stable(&mut self.hasher);
+        self.deps.push(def_path_hash);
+    }
+
+    fn hash_fn_decl(&mut self, decl: &FnDecl<'tcx>) {
+        decl.inputs.len().hash_stable(&mut self.hasher);
+        for ty in decl.inputs {
+            self.hash_ty(ty);
+        }
+
+        mem::discriminant(&decl.output).hash_stable(&mut self.hasher);
+        if let FnRetTy::Return(ty) = decl.output {
+            self.hash_ty(ty);
+        }
+    }
+
+    fn hash_ty(&mut self, ty: &Ty<'tcx>) {
+        mem::discriminant(&ty.kind).hash_stable(&mut self.hasher);
+        match ty.kind {
+            TyKind::Path(path) => {
+                self.hash_path(&path);
+                if let Res::Def(DefKind::TyAlias, def_id) = path.res {
+                    self.hash_alias_ty(def_id);
+                }
+            }
+            TyKind::FnPtr(decl) => self.hash_fn_decl(decl),
+            TyKind::Unit | TyKind::Never | TyKind::Infer => {}
+        }
+    }
+
+    /// 型エイリアスを展開した型を、メタデータと同じ形式に書き出してハッシュする。
+    fn hash_alias_ty(&mut self, def_id: DefId) {
+        let mut e = Encoder::new();
+        let encoded = encode_ty(self.tcx, &mut e, self.tcx.type_of(def_id));
+        encoded.is_some().hash_stable(&mut self.hasher);
+        e.finish().hash_stable(&mut self.hasher);
+    }
+
+    fn hash_path(&mut self, path: &Path<'tcx>) {
+        path.segments.len().hash_stable(&mut self.hasher);
+        for segment in path.segments {
+            segment.ident.hash_stable(&mut self.hasher);
+        }
+
+        mem::discriminant(&path.res).hash_stable(&mut self.hasher);
+        match path.res {
+            Res::Def(kind, def_id) => {
+                mem::discriminant(&kind).hash_stable(&mut self.hasher);
+                self.hash_def_id(def_id);
+            }
+            Res::Local(sir_id) => self.hash_sir_id(sir_id),
+            Res::PrimTy(prim_ty) => prim_ty.hash(&mut self.hasher),
+            Res::Err => {}
+        }
+    }
+
+    fn hash_pat(&mut self, pat: &Pat) {
+        self.hash_sir_id(pat.sir_id);
+        mem::discriminant(&pat.kind).hash_stable(&mut self.hasher);
+        if let PatKind::Binding(_, ident) = pat.kind {
+            ident.hash_stable(&mut self.hasher);
+        }
+    }
+
+    fn hash_block(&mut self, block: &Block<'tcx>) {
+        block.stmts.len().hash_stable(&mut self.hasher);
+        for stmt in block.stmts {
+            self.hash_stmt(stmt);
+        }
+        self.hash_opt_expr(block.expr);
+    }
+
+    fn hash_stmt(&mut self, stmt: &Stmt<'tcx>) {
+        mem::discriminant(&stmt.kind).hash_stable(&mut self.hasher);
+        match stmt.kind {
+            StmtKind::Let(local) => {
+                self.hash_pat(local.pat);
+                local.ty.is_some().hash_stable(&mut self.hasher);
+                if let Some(ty) = local.ty {
+                    self.hash_ty(ty);
+                }
+                self.hash_opt_expr(local.init);
+            }
+            StmtKind::Item(ItemId { owner_id }) => self.hash_def_id(owner_id.to_def_id()),
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => self.hash_expr(expr),
+            StmtKind::Break(destination, expr) => {
+                self.hash_destination(destination);
+                self.hash_opt_expr(expr);
+            }
+            StmtKind::Continue(destination) => self.hash_destination(destination),
+            StmtKind::Return(expr) => self.hash_opt_expr(expr),
+            StmtKind::Loop(block, source, _) => {
+                self.hash_sir_id(block.sir_id);
+                mem::discriminant(&source).hash_stable(&mut self.hasher);
+                self.hash_block(block);
+            }
+        }
+    }
+
+    fn hash_destination(&mut self, destination: Destination) {
+        mem::discriminant(&destination.target_id).hash_stable(&mut self.hasher);
+        if let Ok(target_id) = destination.target_id {
+            self.hash_sir_id(target_id);
+        }
+    }
+
+    fn hash_opt_expr(&mut self, expr: Option<&Expr<'tcx>>) {
+        expr.is_some().hash_stable(&mut self.hasher);
+        if let Some(expr) = expr {
+            self.hash_expr(expr);
+        }
+    }
+
+    fn hash_expr(&mut self, expr: &Expr<'tcx>) {
+        self.hash_sir_id(expr.sir_id);
+        mem::discriminant(&expr.kind).hash_stable(&mut self.hasher);
+        match expr.kind {
+            ExprKind::Call(func, args) => {
+                self.hash_expr(func);
+                args.len().hash_stable(&mut self.hasher);
+                for arg in args {
+                    self.hash_expr(arg);
+                }
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                op.node.hash_stable(&mut self.hasher);
+                self.hash_expr(lhs);
+                self.hash_expr(rhs);
+            }
+            ExprKind::Unary(op, operand) => {
+                op.hash_stable(&mut self.hasher);
+                self.hash_expr(operand);
+            }
+            ExprKind::Lit(lit) => {
+                mem::discriminant(&lit.node).hash_stable(&mut self.hasher);
+                match lit.node {
+                    LitKind::Str(symbol) | LitKind::Float(symbol) => {
+                        symbol.hash_stable(&mut self.hasher);
+                    }
+                    LitKind::Char(c) => c.hash_stable(&mut self.hasher),
+                    LitKind::Int(n) => n.hash_stable(&mut self.hasher),
+                    LitKind::Bool(b) => b.hash_stable(&mut self.hasher),
+                    LitKind::Err(_) => {}
+                }
+            }
+            ExprKind::If(cond, then, els) => {
+                self.hash_expr(cond);
+                self.hash_expr(then);
+                self.hash_opt_expr(els);
+            }
+            ExprKind::Path(path) => self.hash_path(&path),
+            ExprKind::Block(block) => {
+                self.hash_sir_id(block.sir_id);
+                self.hash_block(block);
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                self.hash_expr(lhs);
+                self.hash_expr(rhs);
+            }
+            // クロージャの定義のパスは、それを含む関数のパスと出現順から決まるため含めない
+            ExprKind::Closure(closure) => {
+                let body = self.tcx.sir_body(closure.body);
+                matches!(closure.capture_clause, CaptureBy::Value { .. })
+                    .hash_stable(&mut self.hasher);
+                self.hash_fn_decl(closure.fn_decl);
+                body.params.len().hash_stable(&mut self.hasher);
+                for param in body.params {
+                    self.hash_pat(param.pat);
+                }
+                self.hash_expr(body.value);
+            }
+            ExprKind::Err(_) => {}
+        }
+    }
+}
@@ -242,6 +242,10 @@ pub fn walk_item<'v, V: Visitor<'v>>(visitor: &mut V, item: &'v Item<'v>) -> V::
             try_visit!(visitor.visit_ident(ident));
             try_visit!(visitor.visit_mod(module, item.span, item.sir_id()))
         }
+        ItemKind::TyAlias(ident, ty) => {
+            try_visit!(visitor.visit_ident(ident));
+            try_visit!(visitor.visit_ty(ty))
+        }
     }
 
     V::Result::output()
@@ -274,8 +278,9 @@ pub fn walk_ty<'v, V: Visitor<'v>>(visitor: &mut V, ty: &'v Ty<'v>) -> V::Result
 
     match *kind {
         TyKind::Path(ref path) => try_visit!(visitor.visit_path(path)),
-        TyKind::Unit => {}
+        TyKind::Unit | TyKind::Never => {}
         TyKind::Infer => try_visit!(visitor.visit_infer(ty.sir_id, ty.span)),
+        TyKind::FnPtr(decl) => try_visit!(visitor.visit_fn_decl(decl)),
     }
 
     V::Result::output()
@@ -441,6 +446,10 @@ pub fn walk_expr<'v, V: Visitor<'v>>(visitor: &mut V, expr: &'v Expr<'v>) -> V::
             try_visit!(visitor.visit_expr(rhs));
             try_visit!(visitor.visit_expr(lhs));
         }
+        ExprKind::Closure(closure) => {
+            try_visit!(visitor.visit_fn_decl(closure.fn_decl));
+            try_visit!(visitor.visit_nested_body(closure.body));
+        }
         ExprKind::Err(_) => {}
     }
     V::Result::output()
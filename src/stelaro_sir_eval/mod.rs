@@ -0,0 +1,439 @@
+//! 型検査済みの SIR を直接評価するインタプリタ。
+//!
+//! 式の木を再帰的にたどって評価する。MIR の構築や最適化を経ないため、
+//! バイトコードの仮想機械 (`stelaro_vm`) の実行結果を確かめる基準として、
+//! またその性能を比較する対象として使う。
+//! 値の表現と演算、標準ステロの関数の実装は `stelaro_vm` のものを共有する。
+//! ゼロ除算とオーバーフローは、MIR の構築で挿入される `Assert` と同じ条件で検査する。
+
+use std::collections::HashMap;
+
+use crate::stelaro_ast::ast::{BinOpKind, UnOp};
+use crate::stelaro_common::{DefIndex, Idx, LocalDefId, ensure_sufficient_stack};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{AssertKind, BinOp, ConstValue};
+use crate::stelaro_sir::sir::{self, ExprKind, ItemKind, LitKind, MaybeOwner, OwnerNode, PatKind, StmtKind};
+use crate::stelaro_sir::sir_id::SirId;
+use crate::stelaro_sir::{DefKind, Res};
+use crate::stelaro_sir_typecheck::result::{CaptureKind, TypeckResults};
+use crate::stelaro_std::intrinsic;
+use crate::stelaro_ty::{Ty, TyKind};
+use crate::stelaro_vm::const_slot;
+use crate::stelaro_vm::intrinsic::{Output, call_intrinsic};
+use crate::stelaro_vm::value::{Slot, Trap, Value, ValueTy, binary_op, unary_op};
+
+
+/// 関数呼び出しの深さの上限。これを超えると `Trap::StackOverflow` となる。
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// 式の評価を中断して、外側へ制御を移す原因。
+enum Unwind {
+    Break(SirId),
+    Continue(SirId),
+    Return(Slot),
+    Trap(Trap),
+}
+
+impl From<Trap> for Unwind {
+    fn from(trap: Trap) -> Self {
+        Unwind::Trap(trap)
+    }
+}
+
+type EvalResult<T = Slot> = Result<T, Unwind>;
+
+/// 評価中の関数のローカル変数。
+struct Frame<'a, 'tcx> {
+    typeck_results: &'a TypeckResults<'tcx>,
+    /// 束縛パターンの `SirId` から、変数の値へのマップ。
+    vars: HashMap<SirId, Slot>,
+    /// 値によってキャプチャされた変数の複製。クロージャと変数の `SirId` の組をキーとする。
+    closure_envs: HashMap<(LocalDefId, SirId), Slot>,
+}
+
+impl<'a, 'tcx> Frame<'a, 'tcx> {
+    fn node_ty(&self, sir_id: SirId) -> Ty<'tcx> {
+        self.typeck_results
+            .node_type(sir_id.local_id)
+            .unwrap_or_else(|| panic!("bug: {sir_id:?} の型が記録されていない"))
+    }
+}
+
+pub struct SirEvaluator<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &'a HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>,
+    /// 関数と、関数ポインタへ型強制されたクロージャの定義から、その本体へのマップ。
+    bodies: HashMap<LocalDefId, &'tcx sir::Body<'tcx>>,
+    /// 現在の関数呼び出しの深さ。
+    depth: usize,
+    /// 標準ステロの関数による出力。
+    output: Output,
+}
+
+impl<'a, 'tcx> SirEvaluator<'a, 'tcx> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        typeck_results: &'a HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>,
+    ) -> Self {
+        let mut bodies = HashMap::new();
+        for (def_id, owner) in tcx.sir_stelo(()).owners.iter_enumerated() {
+            if let MaybeOwner::Owner(info) = owner
+                && let OwnerNode::Item(sir::Item {
+                    kind: ItemKind::Fn { body, .. },
+                    ..
+                }) = info.nodes.node()
+            {
+                bodies.insert(def_id, tcx.sir_body(*body));
+            }
+        }
+        for results in typeck_results.values() {
+            for (id, _) in results.fn_ptr_coercions() {
+                if let Some(TyKind::Closure(def_id, _)) = results.node_type(id).map(|ty| *ty.kind()) {
+                    let closure_id = def_id.expect_local();
+                    bodies.insert(closure_id, tcx.sir_body(tcx.expect_sir_closure(closure_id).body));
+                }
+            }
+        }
+
+        SirEvaluator {
+            tcx,
+            typeck_results,
+            bodies,
+            depth: 0,
+            output: Output::default(),
+        }
+    }
+
+    /// これまでの評価で、プログラムが標準出力と標準エラー出力に書いた内容。
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// ルートにある `main` 関数を評価する。`main` 関数が無い場合は `None` を返す。
+    pub fn run_main(&mut self) -> Option<Result<Value, Trap>> {
+        let main = self.bodies.iter().find_map(|(&def_id, body)| {
+            (self.tcx.def_path_str(def_id.to_def_id()) == "main" && body.params.is_empty())
+                .then_some(def_id)
+        })?;
+
+        Some(self.call(main, &[]))
+    }
+
+    /// 関数を呼び出し、戻り値を返す。
+    pub fn call(&mut self, def_id: LocalDefId, args: &[Slot]) -> Result<Value, Trap> {
+        let output = self.typeck_results[&def_id]
+            .fn_sig
+            .as_ref()
+            .expect("bug: 関数のシグネチャが記録されていない")
+            .output;
+        let result = self.call_fn(def_id, args)?;
+
+        Ok(Value::from_slot(ValueTy::of(output), result))
+    }
+
+    fn call_fn(&mut self, def_id: LocalDefId, args: &[Slot]) -> Result<Slot, Trap> {
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(Trap::StackOverflow);
+        }
+
+        // クロージャの型検査の結果は、それを含む関数のものに含まれる
+        let owner = self.tcx.local_def_id_to_sir_id(def_id).owner.def_id;
+        let body = self.bodies[&def_id];
+        let mut frame = Frame {
+            typeck_results: self.typeck_results[&owner],
+            vars: HashMap::new(),
+            closure_envs: HashMap::new(),
+        };
+        for (param, &arg) in body.params.iter().zip(args) {
+            if let PatKind::Binding(var_id, _) = param.pat.kind {
+                frame.vars.insert(var_id, arg);
+            }
+        }
+
+        self.depth += 1;
+        let result = self.expr(&mut frame, body.value);
+        self.depth -= 1;
+
+        match result {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Trap(trap)) => Err(trap),
+            Err(Unwind::Break(_) | Unwind::Continue(_)) => {
+                unreachable!("bug: ループの外へ `break` または `continue` した")
+            }
+        }
+    }
+
+    fn expr(&mut self, frame: &mut Frame<'a, 'tcx>, expr: &'tcx sir::Expr<'tcx>) -> EvalResult {
+        // 関数ポインタは、関数の `LocalDefId` の番号として表す
+        if frame.typeck_results.fn_ptr_coercion(expr.sir_id.local_id).is_some() {
+            let (TyKind::FnDef(def_id) | TyKind::Closure(def_id, _)) =
+                *frame.node_ty(expr.sir_id).kind()
+            else {
+                panic!("bug: 関数アイテムではない式が関数ポインタへ型強制された: {expr:?}");
+            };
+            let def_id = def_id
+                .as_local()
+                .unwrap_or_else(|| panic!("bug: 外部ステロの関数は評価できない: {def_id:?}"));
+            return Ok(def_id.index() as Slot);
+        }
+
+        ensure_sufficient_stack(|| match expr.kind {
+            ExprKind::Lit(lit) => {
+                let const_ = match lit.node {
+                    LitKind::Str(symbol) => ConstValue::Str(symbol),
+                    LitKind::Char(c) => ConstValue::Char(c),
+                    LitKind::Int(n) => ConstValue::Int(n),
+                    LitKind::Float(symbol) => ConstValue::Float(symbol),
+                    LitKind::Bool(b) => ConstValue::Bool(b),
+                    LitKind::Err(_) => panic!("bug: 不正なリテラルを評価しようとした"),
+                };
+                Ok(const_slot(ValueTy::of(frame.node_ty(expr.sir_id)), const_))
+            }
+            ExprKind::Path(sir::Path {
+                res: Res::Local(var_id),
+                ..
+            }) => Ok(*frame
+                .vars
+                .get(&var_id)
+                .unwrap_or_else(|| panic!("bug: 変数 {var_id:?} が初期化されていない"))),
+            // 関数アイテムは値をもたない
+            ExprKind::Path(sir::Path {
+                res: Res::Def(DefKind::Fn, _),
+                ..
+            }) => Ok(0),
+            ExprKind::Path(path) => panic!("bug: 値として評価できないパス: {path:?}"),
+            // クロージャは値をもたず、値によってキャプチャする変数を複製しておく
+            ExprKind::Closure(closure) => {
+                for capture in frame.typeck_results.closure_captures(closure.def_id) {
+                    if capture.kind == CaptureKind::ByValue {
+                        let value = frame.vars[&capture.var_id];
+                        frame.closure_envs.insert((closure.def_id, capture.var_id), value);
+                    }
+                }
+                Ok(0)
+            }
+            ExprKind::Call(callee, args) if frame.node_ty(callee.sir_id).is_closure() => {
+                self.expr(frame, callee)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.expr(frame, arg))
+                    .collect::<EvalResult<Vec<_>>>()?;
+                let TyKind::Closure(def_id, _) = *frame.node_ty(callee.sir_id).kind() else {
+                    unreachable!()
+                };
+                self.call_closure(frame, def_id.expect_local(), &args)
+            }
+            ExprKind::Call(callee, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.expr(frame, arg))
+                    .collect::<EvalResult<Vec<_>>>()?;
+                let def_id = match *frame.node_ty(callee.sir_id).kind() {
+                    TyKind::FnDef(def_id) => def_id,
+                    TyKind::FnPtr(_) => {
+                        let callee = self.expr(frame, callee)?;
+                        return Ok(self.call_fn(LocalDefId::new(DefIndex::from_usize(callee as usize)), &args)?);
+                    }
+                    _ => panic!("bug: 関数ではない値の呼び出し: {callee:?}"),
+                };
+
+                if let Some(intrinsic) = intrinsic(self.tcx, def_id) {
+                    return Ok(call_intrinsic(intrinsic, args[0], &mut self.output)?);
+                }
+                let Some(def_id) = def_id.as_local() else {
+                    // 外部ステロの関数の本体はメタデータに含まれないため、評価できない。
+                    // このインタプリタは `--extern` を使わないテストとベンチマークからのみ使われる
+                    panic!("bug: 外部ステロの関数は評価できない: {def_id:?}");
+                };
+                Ok(self.call_fn(def_id, &args)?)
+            }
+            ExprKind::Binary(op, lhs, rhs) if op.node.is_lazy() => {
+                let lhs = self.expr(frame, lhs)?;
+                match (op.node, lhs != 0) {
+                    (BinOpKind::And, false) => Ok(0),
+                    (BinOpKind::Or, true) => Ok(1),
+                    _ => self.expr(frame, rhs),
+                }
+            }
+            ExprKind::Binary(op, lhs_expr, rhs) => {
+                let op = BinOp::from_ast(op.node)
+                    .expect("bug: 短絡評価される演算子は別に評価される");
+                let lhs_ty = frame.node_ty(lhs_expr.sir_id);
+                let ty = ValueTy::of(lhs_ty);
+                let lhs = self.expr(frame, lhs_expr)?;
+                let rhs = self.expr(frame, rhs)?;
+                if lhs_ty.is_integral() {
+                    self.check_binary_op(op, ty, lhs, rhs)?;
+                }
+                Ok(binary_op(op, ty, lhs, rhs)?)
+            }
+            ExprKind::Unary(op, operand) => {
+                let operand_ty = frame.node_ty(operand.sir_id);
+                let ty = ValueTy::of(operand_ty);
+                let value = self.expr(frame, operand)?;
+                let result = unary_op(op, ty, value);
+                // 2 の補数で符号を反転しても変わらない 0 以外の値は、最小値のみ。
+                // 負のリテラル `-2147483648` は MIR と同じく一つの定数として扱い、検査しない
+                if op == UnOp::Neg
+                    && !matches!(operand.kind, ExprKind::Lit(_))
+                    && operand_ty.is_integral()
+                    && self.tcx.sess.overflow_checks()
+                    && result == value
+                    && value != 0
+                {
+                    return Err(Trap::Panic(AssertKind::OverflowNeg).into());
+                }
+                Ok(result)
+            }
+            ExprKind::If(cond, then, else_opt) => {
+                if self.expr(frame, cond)? != 0 {
+                    self.expr(frame, then)
+                } else if let Some(else_expr) = else_opt {
+                    self.expr(frame, else_expr)
+                } else {
+                    Ok(0)
+                }
+            }
+            ExprKind::Block(block) => self.block(frame, block),
+            ExprKind::Assign(lhs, rhs, _) => {
+                let ExprKind::Path(sir::Path {
+                    res: Res::Local(var_id),
+                    ..
+                }) = lhs.kind
+                else {
+                    panic!("bug: 代入の左辺がローカル変数ではない: {lhs:?}");
+                };
+
+                let value = self.expr(frame, rhs)?;
+                frame.vars.insert(var_id, value);
+                Ok(0)
+            }
+            ExprKind::Err(_) => panic!("bug: 型検査に失敗した本体を評価しようとした"),
+        })
+    }
+
+    /// クロージャを、呼び出した関数のフレームで評価する。
+    ///
+    /// 参照によってキャプチャされた変数はフレームの変数をそのまま読み書きし、
+    /// 値によってキャプチャされた変数は、評価の間だけその複製に置き換える。
+    fn call_closure(
+        &mut self,
+        frame: &mut Frame<'a, 'tcx>,
+        closure_id: LocalDefId,
+        args: &[Slot],
+    ) -> EvalResult {
+        let body = self.tcx.sir_body(self.tcx.expect_sir_closure(closure_id).body);
+        for (param, &arg) in body.params.iter().zip(args) {
+            if let PatKind::Binding(var_id, _) = param.pat.kind {
+                frame.vars.insert(var_id, arg);
+            }
+        }
+
+        let by_value = frame
+            .typeck_results
+            .closure_captures(closure_id)
+            .iter()
+            .filter(|capture| capture.kind == CaptureKind::ByValue)
+            .map(|capture| capture.var_id)
+            .collect::<Vec<_>>();
+        let mut shadowed = Vec::new();
+        for &var_id in &by_value {
+            let env = frame.closure_envs[&(closure_id, var_id)];
+            shadowed.push(frame.vars.insert(var_id, env));
+        }
+
+        let result = self.expr(frame, body.value);
+
+        for (var_id, outer) in by_value.into_iter().zip(shadowed) {
+            let env = match outer {
+                Some(outer) => frame.vars.insert(var_id, outer),
+                None => frame.vars.remove(&var_id),
+            };
+            if let Some(env) = env {
+                frame.closure_envs.insert((closure_id, var_id), env);
+            }
+        }
+
+        match result {
+            Err(Unwind::Return(value)) => Ok(value),
+            result => result,
+        }
+    }
+
+    /// 整数の二項演算のゼロ除算とオーバーフローを検査する。
+    fn check_binary_op(&self, op: BinOp, ty: ValueTy, lhs: Slot, rhs: Slot) -> Result<(), Trap> {
+        match op {
+            BinOp::Div if rhs == 0 => return Err(Trap::Panic(AssertKind::DivisionByZero)),
+            BinOp::Rem if rhs == 0 => return Err(Trap::Panic(AssertKind::RemainderByZero)),
+            BinOp::Div | BinOp::Rem => {}
+            BinOp::Add | BinOp::Sub | BinOp::Mul if self.tcx.sess.overflow_checks() => {}
+            _ => return Ok(()),
+        }
+
+        let check = op.overflow_check().unwrap();
+        if binary_op(check, ty, lhs, rhs)? != 0 {
+            return Err(Trap::Panic(AssertKind::Overflow(op)));
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, frame: &mut Frame<'a, 'tcx>, block: &'tcx sir::Block<'tcx>) -> EvalResult {
+        for stmt in block.stmts {
+            self.stmt(frame, stmt)?;
+        }
+
+        match block.expr {
+            Some(tail) => self.expr(frame, tail),
+            None => Ok(0),
+        }
+    }
+
+    fn stmt(&mut self, frame: &mut Frame<'a, 'tcx>, stmt: &'tcx sir::Stmt<'tcx>) -> EvalResult<()> {
+        match stmt.kind {
+            StmtKind::Let(local) => {
+                if let Some(init) = local.init {
+                    let value = self.expr(frame, init)?;
+                    if let PatKind::Binding(var_id, _) = local.pat.kind {
+                        frame.vars.insert(var_id, value);
+                    }
+                }
+            }
+            StmtKind::Item(_) => {}
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => {
+                self.expr(frame, expr)?;
+            }
+            StmtKind::Break(destination, value) => {
+                if let Some(value) = value {
+                    self.expr(frame, value)?;
+                }
+                let loop_id = destination
+                    .target_id
+                    .expect("bug: ループの外の `break` を評価しようとした");
+                return Err(Unwind::Break(loop_id));
+            }
+            StmtKind::Continue(destination) => {
+                let loop_id = destination
+                    .target_id
+                    .expect("bug: ループの外の `continue` を評価しようとした");
+                return Err(Unwind::Continue(loop_id));
+            }
+            StmtKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.expr(frame, value)?,
+                    None => 0,
+                };
+                return Err(Unwind::Return(value));
+            }
+            StmtKind::Loop(body, _, _) => loop {
+                match self.block(frame, body) {
+                    Ok(_) => {}
+                    Err(Unwind::Break(loop_id)) if loop_id == stmt.sir_id => break,
+                    Err(Unwind::Continue(loop_id)) if loop_id == stmt.sir_id => {}
+                    Err(unwind) => return Err(unwind),
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,723 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Applicability, Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsTypeck;
+
+impl<'dcx> DiagsTypeck {
+    /// 期待した型と、見つかった値の型が一致しない。
+    ///
+    /// `found_alias` は、見つかった値の型が書かれた型エイリアスの名前。展開した型と並べて示す。
+    pub fn mismatched_types(
+        dcx: DiagCtxtHandle<'dcx>,
+        expected: &str,
+        found: &str,
+        found_alias: Option<&str>,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::MismatchedTypes.into());
+        diag.set_message(dcx.translate(messages::MISMATCHED_TYPES, &[]));
+        let label = match found_alias {
+            Some(alias) => dcx.translate(
+                messages::MISMATCHED_TYPES_FOUND_ALIAS_LABEL,
+                &[("expected", &expected), ("alias", &alias), ("found", &found)],
+            ),
+            None => dcx.translate(
+                messages::MISMATCHED_TYPES_LABEL,
+                &[("expected", &expected), ("found", &found)],
+            ),
+        };
+        diag.set_label(span, label);
+
+        diag
+    }
+
+    /// 関数本体の末尾の式の型が、戻り値の型と一致しない。
+    /// `ret_span` は戻り値の型の位置で、省略されている場合は `None`。
+    pub fn mismatched_return_type(
+        dcx: DiagCtxtHandle<'dcx>,
+        expected: &str,
+        found: &str,
+        found_alias: Option<&str>,
+        span: Span,
+        ret_span: Option<Span>,
+    ) -> Diag<'dcx> {
+        let mut diag = Self::mismatched_types(dcx, expected, found, found_alias, span);
+        if let Some(ret_span) = ret_span {
+            diag.set_label(
+                ret_span,
+                dcx.translate(messages::RETURN_TYPE_LABEL, &[("expected", &expected)]),
+            );
+        }
+
+        diag
+    }
+
+    /// 関数本体が末尾の式をもたず、戻り値の型の代わりに `()` を返す。
+    ///
+    /// `implicit_span` は `()` が返される本体の終端、`semi_span` は削除すると
+    /// 最後の文の値が返されるようになるセミコロンの位置。
+    pub fn missing_return_value(
+        dcx: DiagCtxtHandle<'dcx>,
+        expected: &str,
+        ret_span: Span,
+        implicit_span: Span,
+        semi_span: Option<Span>,
+    ) -> Diag<'dcx> {
+        let mut diag = Self::mismatched_types(dcx, expected, "()", None, ret_span);
+        diag.set_label(implicit_span, dcx.translate(messages::IMPLICIT_UNIT_RETURN_LABEL, &[]));
+        if let Some(semi_span) = semi_span {
+            diag.set_suggestion(
+                semi_span,
+                dcx.translate(messages::REMOVE_SEMICOLON_SUGGESTION, &[]),
+                String::new(),
+                Applicability::MachineApplicable,
+            );
+        }
+
+        diag
+    }
+
+    pub fn type_annotations_needed(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::TypeAnnotationsNeeded.into());
+        diag.set_message(dcx.translate(messages::TYPE_ANNOTATIONS_NEEDED, &[]));
+        diag.set_label(span, dcx.translate(messages::TYPE_ANNOTATIONS_NEEDED_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::TYPE_ANNOTATIONS_NEEDED_HELP, &[]));
+
+        diag
+    }
+
+    pub fn not_a_function(dcx: DiagCtxtHandle<'dcx>, ty: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::NotAFunction.into());
+        diag.set_message(dcx.translate(messages::NOT_A_FUNCTION, &[]));
+        diag.set_label(span, dcx.translate(messages::NOT_A_FUNCTION_LABEL, &[("ty", &ty)]));
+
+        diag
+    }
+
+    pub fn argument_count_mismatch(
+        dcx: DiagCtxtHandle<'dcx>,
+        expected: usize,
+        found: usize,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::ArgumentCountMismatch.into());
+        diag.set_message(dcx.translate(messages::ARGUMENT_COUNT_MISMATCH, &[]));
+        diag.set_label(
+            span,
+            dcx.translate(
+                messages::ARGUMENT_COUNT_MISMATCH_LABEL,
+                &[("expected", &expected), ("found", &found)],
+            ),
+        );
+
+        diag
+    }
+
+    pub fn binary_op_invalid_operand(
+        dcx: DiagCtxtHandle<'dcx>,
+        op: &str,
+        ty: &str,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::BinaryOpInvalidOperand.into());
+        diag.set_message(
+            dcx.translate(messages::BINARY_OP_INVALID_OPERAND, &[("op", &op), ("ty", &ty)]),
+        );
+        diag.set_label(
+            span,
+            dcx.translate(messages::BINARY_OP_INVALID_OPERAND_LABEL, &[("op", &op), ("ty", &ty)]),
+        );
+
+        diag
+    }
+
+    pub fn unary_op_invalid_operand(
+        dcx: DiagCtxtHandle<'dcx>,
+        op: &str,
+        ty: &str,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::UnaryOpInvalidOperand.into());
+        diag.set_message(
+            dcx.translate(messages::UNARY_OP_INVALID_OPERAND, &[("op", &op), ("ty", &ty)]),
+        );
+        diag.set_label(
+            span,
+            dcx.translate(messages::UNARY_OP_INVALID_OPERAND_LABEL, &[("op", &op), ("ty", &ty)]),
+        );
+
+        diag
+    }
+
+    /// `kind` は `break` または `continue`
+    pub fn outside_of_loop(dcx: DiagCtxtHandle<'dcx>, kind: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::OutsideOfLoop.into());
+        diag.set_message(dcx.translate(messages::OUTSIDE_OF_LOOP, &[("kind", &kind)]));
+        diag.set_label(span, dcx.translate(messages::OUTSIDE_OF_LOOP_LABEL, &[("kind", &kind)]));
+
+        diag
+    }
+
+    pub fn expected_type_found_module(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: &str,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::ExpectedTypeFoundModule.into());
+        diag.set_message(
+            dcx.translate(messages::EXPECTED_TYPE_FOUND_MODULE, &[("name", &name)]),
+        );
+        diag.set_label(span, dcx.translate(messages::EXPECTED_TYPE_FOUND_MODULE_LABEL, &[]));
+
+        diag
+    }
+
+    pub fn invalid_assignment_target(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::InvalidAssignmentTarget.into());
+        diag.set_message(dcx.translate(messages::INVALID_ASSIGNMENT_TARGET, &[]));
+        diag.set_label(span, dcx.translate(messages::INVALID_ASSIGNMENT_TARGET_LABEL, &[]));
+
+        diag
+    }
+
+    pub fn placeholder_in_signature(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::PlaceholderInSignature.into());
+        diag.set_message(dcx.translate(messages::PLACEHOLDER_IN_SIGNATURE, &[]));
+        diag.set_label(span, dcx.translate(messages::PLACEHOLDER_IN_SIGNATURE_LABEL, &[]));
+
+        diag
+    }
+
+    pub fn placeholder_in_ty_alias(dcx: DiagCtxtHandle<'dcx>, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::PlaceholderInSignature.into());
+        diag.set_message(dcx.translate(messages::PLACEHOLDER_IN_TY_ALIAS, &[]));
+        diag.set_label(span, dcx.translate(messages::PLACEHOLDER_IN_SIGNATURE_LABEL, &[]));
+
+        diag
+    }
+
+    pub fn intrinsic_as_fn_ptr(dcx: DiagCtxtHandle<'dcx>, name: &str, span: Span) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::IntrinsicAsFnPtr.into());
+        diag.set_message(dcx.translate(messages::INTRINSIC_AS_FN_PTR, &[("name", &name)]));
+        diag.set_label(span, dcx.translate(messages::INTRINSIC_AS_FN_PTR_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::INTRINSIC_AS_FN_PTR_HELP, &[]));
+
+        diag
+    }
+
+    /// 変数をキャプチャするクロージャが関数ポインタに型強制された。
+    /// `var_span` はキャプチャされた変数の宣言の位置。
+    pub fn capturing_closure_as_fn_ptr(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: &str,
+        span: Span,
+        var_span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::CapturingClosureAsFnPtr.into());
+        diag.set_message(dcx.translate(messages::CAPTURING_CLOSURE_AS_FN_PTR, &[]));
+        diag.set_label(span, dcx.translate(messages::CAPTURING_CLOSURE_AS_FN_PTR_LABEL, &[]));
+        diag.set_label(
+            var_span,
+            dcx.translate(messages::CAPTURED_VARIABLE_LABEL, &[("name", &name)]),
+        );
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    MismatchedTypes = 400,
+    TypeAnnotationsNeeded = 401,
+    NotAFunction = 402,
+    ArgumentCountMismatch = 403,
+    BinaryOpInvalidOperand = 404,
+    UnaryOpInvalidOperand = 405,
+    OutsideOfLoop = 406,
+    ExpectedTypeFoundModule = 407,
+    InvalidAssignmentTarget = 408,
+    PlaceholderInSignature = 409,
+    IntrinsicAsFnPtr = 410,
+    CapturingClosureAsFnPtr = 411,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::stelaro_common::{Span, create_default_session_globals_then};
+    use crate::stelaro_common::source_map::SourceMap;
+    use crate::stelaro_diagnostics::emitter::{CollectingEmitter, Emitter, SilentEmitter};
+    use crate::stelaro_diagnostics::{DiagCtxt, DiagInner};
+    use crate::stelaro_interface::passes;
+    use crate::stelaro_session::session::CompilerPaths;
+    use crate::stelaro_session::{Input, Options, ParseSess, Session};
+    use crate::stelaro_sir_typecheck::{check_stelo, diagnostics::ErrorCode};
+
+    fn create_test_session(src: &str, emitter: Box<dyn Emitter>) -> Session {
+        let source_map = Rc::new(SourceMap::new());
+        let dcx = DiagCtxt::new(emitter);
+
+        Session {
+            psess: ParseSess::with_dcx(dcx, source_map),
+            opts: Options::default(),
+            paths: CompilerPaths {
+                input: Input::Str {
+                    name: "typeck_test".to_string(),
+                    input: src.to_string(),
+                },
+                output_dir: None,
+                output_file: None,
+                temps_dir: None,
+            },
+        }
+    }
+
+    fn get_sess_after_typeck(src: &str) -> Session {
+        typeck_with_emitter(src, Box::new(SilentEmitter::new()))
+    }
+
+    /// 型検査で報告された診断を、報告された順に返す
+    fn get_diags_after_typeck(src: &str) -> Vec<DiagInner> {
+        let emitter = CollectingEmitter::new();
+        typeck_with_emitter(src, Box::new(emitter.clone()));
+        emitter.take_diagnostics()
+    }
+
+    fn typeck_with_emitter(src: &str, emitter: Box<dyn Emitter>) -> Session {
+        create_default_session_globals_then(|| {
+            let sess = create_test_session(src, emitter);
+            let stelo = passes::parse(&sess);
+
+            passes::create_and_enter_global_ctxt(&sess, |tcx| {
+                passes::lower_to_sir(tcx, stelo);
+                check_stelo(tcx);
+            });
+
+            sess
+        })
+    }
+
+    #[test]
+    fn test_well_typed() {
+        let sess = get_sess_after_typeck(
+            r#"
+    fn main(x: i64) {
+        let y = 1 + x;
+        let z = if y > 2 and true { y } else { -y };
+        let w;
+        w = z * 2;
+        loop {
+            if w == 0 {
+                break;
+            }
+            w = w - 1;
+        }
+        let c: char = 'c';
+    }
+"#.trim(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_mismatched_types() {
+        let sess = get_sess_after_typeck("fn main() { let x: i64 = 1; let y: i32 = x; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+    }
+
+    #[test]
+    fn test_mismatched_types_in_if_branches() {
+        let sess = get_sess_after_typeck("fn main() { let x = if true { 1 } else { 'a' }; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+    }
+
+    #[test]
+    fn test_returning_values() {
+        let sess = get_sess_after_typeck(
+            "fn a(): i64 { 1 } fn b(c: bool): i64 { if c { return 1; } return 2; } fn d(): i64 { loop {} }",
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_missing_return_value() {
+        let sess = get_sess_after_typeck("fn a(): i64 {}");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+        assert!(sess.dcx().take_machine_applicable_suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_missing_return_value_with_trailing_semicolon() {
+        let sess = get_sess_after_typeck("fn a(x: i64): i64 { x + 1; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+
+        let suggestions = sess.dcx().take_machine_applicable_suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].span, (25..26).into());
+        assert!(suggestions[0].replacement.is_empty());
+    }
+
+    #[test]
+    fn test_missing_return_value_with_trailing_semicolon_after_literal() {
+        let sess = get_sess_after_typeck("fn a(): i64 { 1; }");
+
+        let suggestions = sess.dcx().take_machine_applicable_suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].span, (15..16).into());
+        assert!(suggestions[0].replacement.is_empty());
+
+        // 負の値をもち得ない型には、負のリテラルを返せない
+        let sess = get_sess_after_typeck("fn a(): u64 { -1; }");
+        assert!(sess.dcx().take_machine_applicable_suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_mismatched_return_type() {
+        let sess = get_sess_after_typeck("fn a(): i64 { true }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+    }
+
+    #[test]
+    fn test_type_annotations_needed() {
+        let sess = get_sess_after_typeck("fn main() { let x; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::TypeAnnotationsNeeded.into())
+        );
+    }
+
+    #[test]
+    fn test_not_a_function() {
+        let sess = get_sess_after_typeck("fn main() { let x = 1; x(); }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::NotAFunction.into())
+        );
+    }
+
+    #[test]
+    fn test_argument_count_mismatch() {
+        let sess = get_sess_after_typeck("fn f(a: i32) {} fn main() { f(1, 2); }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::ArgumentCountMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_binary_op_invalid_operand() {
+        let sess = get_sess_after_typeck("fn main() { let x = 'a' + 'b'; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::BinaryOpInvalidOperand.into())
+        );
+    }
+
+    /// 文字列は連結と等価性の比較のみをサポートする
+    #[test]
+    fn test_str_binary_ops() {
+        let sess = get_sess_after_typeck(
+            r#"fn main(s: str): bool { let t = s + "!"; t == s and t != "" }"#,
+        );
+        assert!(sess.dcx().has_errors().is_none());
+
+        for op in ["<", "-"] {
+            let sess = get_sess_after_typeck(&format!(r#"fn main(s: str) {{ let t = s {op} "a"; }}"#));
+            assert!(
+                sess.dcx()
+                    .has_err_code(ErrorCode::BinaryOpInvalidOperand.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_unary_op_invalid_operand() {
+        let sess = get_sess_after_typeck("fn main() { let x = -true; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::UnaryOpInvalidOperand.into())
+        );
+    }
+
+    #[test]
+    fn test_outside_of_loop() {
+        let sess = get_sess_after_typeck("fn main() { continue; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::OutsideOfLoop.into())
+        );
+    }
+
+    #[test]
+    fn test_expected_type_found_module() {
+        let sess = get_sess_after_typeck("mod m {} fn main() { let x: m = 1; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::ExpectedTypeFoundModule.into())
+        );
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let sess = get_sess_after_typeck("fn main() { 1 = 2; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::InvalidAssignmentTarget.into())
+        );
+    }
+
+    #[test]
+    fn test_placeholder_in_signature() {
+        let sess = get_sess_after_typeck("fn f(x: _) {}");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::PlaceholderInSignature.into())
+        );
+    }
+
+    #[test]
+    fn test_fn_ptr_coercion() {
+        let sess = get_sess_after_typeck(
+            r#"
+    fn double(x: i32): i32 { x * 2 }
+    fn inc(x: i32): i32 { x + 1 }
+    fn apply(f: fn(i32): i32, x: i32): i32 { f(x) }
+    fn main(): i32 {
+        let f: fn(i32): i32 = double;
+        f = inc;
+        let g: fn(i32): i32 = if true { double } else { inc };
+        apply(f, 1) + apply(inc, 2) + g(3)
+    }
+"#.trim(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_fn_ptr_signature_mismatch() {
+        let sess = get_sess_after_typeck(
+            "fn f(x: i64): i64 { x } fn main() { let g: fn(i32): i32 = f; }",
+        );
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::MismatchedTypes.into())
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_as_fn_ptr() {
+        let sess = get_sess_after_typeck("fn main() { let f: fn(i32) = print_i32; }");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::IntrinsicAsFnPtr.into())
+        );
+    }
+
+    #[test]
+    fn test_closure() {
+        let sess = get_sess_after_typeck(
+            r#"
+    fn apply(f: fn(i32): i32, x: i32): i32 { f(x) }
+    fn main(): i32 {
+        let offset = 1;
+        let add = |x: i32| x + offset;
+        let get = move || offset;
+        apply(|x| x * 2, add(get()))
+    }
+"#.trim(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_capturing_closure_as_fn_ptr() {
+        let sess = get_sess_after_typeck(
+            r#"
+    fn apply(f: fn(i32): i32, x: i32): i32 { f(x) }
+    fn main() { let offset = 1; apply(|x| x + offset, 1); }
+"#.trim(),
+        );
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::CapturingClosureAsFnPtr.into())
+        );
+    }
+
+    #[test]
+    fn test_ty_alias() {
+        let sess = get_sess_after_typeck(
+            r#"
+    type Meters = i64;
+    type Scale = fn(Meters): Meters;
+    mod units {
+        type Meters = i32;
+        fn half(m: Meters): Meters { m / 2 }
+    }
+    fn double(m: Meters): Meters { m * 2 }
+    fn main(): i32 {
+        let f: Scale = double;
+        let d: Meters = f(21);
+        units::half(84)
+    }
+"#.trim(),
+        );
+
+        assert!(sess.dcx().has_errors().is_none());
+    }
+
+    #[test]
+    fn test_ty_alias_name_in_mismatch() {
+        let diags = get_diags_after_typeck(
+            r#"
+    type Meters = i64;
+    fn walk(m: Meters): Meters { return 'a'; }
+    fn main() { let d: Meters = true; walk(false); let n: i64 = d; }
+"#.trim(),
+        );
+
+        let labels = diags
+            .iter()
+            .filter(|diag| diag.code == Some(ErrorCode::MismatchedTypes.into()))
+            .map(|diag| diag.label[0].1.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "`Meters` を期待していましたが、`char` が見つかりました",
+                "`Meters` を期待していましたが、`bool` が見つかりました",
+                "`Meters` を期待していましたが、`bool` が見つかりました",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ty_alias_name_of_found_type() {
+        let diags = get_diags_after_typeck(
+            r#"
+    type Meters = i64;
+    fn walk(m: Meters): Meters { m }
+    fn ok(): bool { walk(1) }
+    fn main() { let d: Meters = 1; let n: i32 = d; let c = |m: Meters| { let b: bool = m; }; }
+"#.trim(),
+        );
+
+        let labels = diags
+            .iter()
+            .filter(|diag| diag.code == Some(ErrorCode::MismatchedTypes.into()))
+            .map(|diag| diag.label[0].1.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "`bool` を期待していましたが、`Meters` (`i64`) が見つかりました",
+                "`i32` を期待していましたが、`Meters` (`i64`) が見つかりました",
+                "`bool` を期待していましたが、`Meters` (`i64`) が見つかりました",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ty_alias_name_in_assignment_and_binary_mismatch() {
+        let diags = get_diags_after_typeck(
+            r#"
+    type Meters = i64;
+    fn main() { let m: Meters = 1; m = false; let x = m + true; let c = 'a'; let y = c * m; }
+"#.trim(),
+        );
+
+        let labels = diags
+            .iter()
+            .filter(|diag| diag.code == Some(ErrorCode::MismatchedTypes.into()))
+            .map(|diag| diag.label[0].1.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "`Meters` を期待していましたが、`bool` が見つかりました",
+                "`Meters` を期待していましたが、`bool` が見つかりました",
+                "`char` を期待していましたが、`Meters` (`i64`) が見つかりました",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cyclic_ty_alias() {
+        let diags = get_diags_after_typeck(
+            "type A = fn(B); type B = A; fn f(_x: A) {} fn main() {}",
+        );
+
+        assert_eq!(diags.len(), 1, "{diags:?}");
+        assert_eq!(diags[0].code, Some(901));
+        assert_eq!(diags[0].msg, ["型エイリアス `A` が自身を参照しています"]);
+        assert_eq!(diags[0].label[0].0, Span::from(0..15));
+        assert_eq!(diags[0].help, ["循環: `A` → `B` → `A`"]);
+    }
+
+    #[test]
+    fn test_placeholder_in_ty_alias() {
+        let sess = get_sess_after_typeck("type T = _; fn main() {}");
+
+        assert!(
+            sess.dcx()
+                .has_err_code(ErrorCode::PlaceholderInSignature.into())
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! `--emit=types` のための、型検査の結果の出力。
+//!
+//! 関数ごとにシグネチャを書き、続けて型が記録された式とパターンを
+//! ソースコード上の位置の順に `位置 種類 ソースコード: 型` の形式で一行ずつ書く。
+//! 型エイリアスの名前で書かれた型は、展開した型ではなくその名前で示す。
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use crate::stelaro_common::{DefId, LineTable, LocalDefId, Span};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::sir::{MaybeOwner, Node};
+use crate::stelaro_sir::sir_id::OwnerId;
+use crate::stelaro_sir_typecheck::result::TypeckResults;
+use crate::stelaro_sir_typecheck::ty_lowering;
+use crate::stelaro_ty::Ty;
+
+
+const INDENT: &str = "    ";
+
+/// 一行に収めるため、これより長いソースコードは省略する。
+const MAX_SNIPPET_CHARS: usize = 40;
+
+/// すべての関数の型検査の結果を、定義の順に出力する。
+pub fn write_types<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &HashMap<LocalDefId, &'tcx TypeckResults<'tcx>>,
+    lines: &LineTable,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    let owners = tcx
+        .sir_stelo(())
+        .owners
+        .iter_enumerated()
+        .filter(|(_, owner)| matches!(owner, MaybeOwner::Owner(_)))
+        .filter_map(|(def_id, _)| typeck_results.get(&def_id));
+
+    for (i, results) in owners.enumerate() {
+        if i != 0 {
+            writeln!(w)?;
+        }
+        write_owner_types(tcx, results, lines, w)?;
+    }
+
+    Ok(())
+}
+
+fn write_owner_types<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    results: &TypeckResults<'tcx>,
+    lines: &LineTable,
+    w: &mut dyn Write,
+) -> fmt::Result {
+    let def_id = results.owner_id.to_def_id();
+    write!(w, "fn {}(", tcx.def_path_str(def_id))?;
+    if let Some(sig) = &results.fn_sig {
+        let input_aliases = ty_lowering::fn_input_ty_aliases(tcx, def_id);
+        let inputs = sig
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, &ty)| ty_string(tcx, ty, input_aliases.get(i).copied().flatten()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let output_alias = ty_lowering::fn_output_ty_alias(tcx, def_id);
+        write!(w, "{inputs}): {}", ty_string(tcx, sig.output, output_alias))?;
+    } else {
+        write!(w, ")")?;
+    }
+    writeln!(w)?;
+
+    let nodes = &tcx.sir_owner_nodes(OwnerId { def_id: results.owner_id }).nodes;
+    let mut entries = nodes
+        .iter_enumerated()
+        .filter_map(|(local_id, node)| {
+            let ty = results.node_type(local_id)?;
+            let (kind, span) = match node.node {
+                Node::Expr(expr) => ("expr", expr.span),
+                Node::Pat(pat) => ("pat", pat.span),
+                _ => return None,
+            };
+            Some((span, kind, ty_string(tcx, ty, results.ty_alias(local_id))))
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|&(span, kind, _)| (span.start, std::cmp::Reverse(span.end), kind));
+
+    for (span, kind, ty) in entries {
+        writeln!(
+            w,
+            "{INDENT}{:<12} {kind:<5} {}: {}",
+            lines.span_to_range_string(span),
+            snippet(lines, span),
+            ty,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 型を文字列に変換する。型エイリアスの名前で書かれた型は、その名前で示す。
+fn ty_string<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>, alias: Option<DefId>) -> String {
+    match alias {
+        Some(alias) => tcx.def_path_str(alias),
+        None => tcx.ty_string(ty),
+    }
+}
+
+/// `span` のソースコードを、空白を詰めて一行にしたものを `` ` `` で囲んで返す。
+fn snippet(lines: &LineTable, span: Span) -> String {
+    let text = lines.snippet(span).split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.chars().count() > MAX_SNIPPET_CHARS {
+        let truncated = text.chars().take(MAX_SNIPPET_CHARS).collect::<String>();
+        format!("`{truncated}...`")
+    } else {
+        format!("`{text}`")
+    }
+}
@@ -45,6 +45,7 @@ impl<'a, 'tcx> Expectation<'tcx> {
     /// `only_has_type` に似ていますが、強い制約が存在しない場合、新しい型変数を生成して返します。
     /// これは「期待される型があればそれを使い、なければこれから推論する」という型推論の
     /// 基本的なパターンです。
+    #[allow(dead_code)]
     pub(super) fn coercion_target_type(self, fcx: &FnCtxt<'a, 'tcx>, span: Span) -> Ty<'tcx> {
         self.only_has_type(fcx).unwrap_or_else(|| fcx.next_ty_var(span))
     }
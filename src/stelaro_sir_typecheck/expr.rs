@@ -0,0 +1,509 @@
+use crate::stelaro_ast::ast::{BinOp, BinOpKind, UnOp};
+use crate::stelaro_common::{DefId, ensure_sufficient_stack};
+use crate::stelaro_sir::{
+    DefKind, Res,
+    sir::{self, ExprKind, FnRetTy, LitKind},
+};
+use crate::stelaro_sir_typecheck::{
+    Diverges, FnCtxt,
+    diagnostics::DiagsTypeck,
+    expectation::Expectation::{self, *},
+    ty_lowering,
+};
+use crate::stelaro_std::intrinsic;
+use crate::stelaro_ty::{FnSig, FnSigTys, Ty, TyKind, visit::TypeVisitableExt};
+
+impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
+    pub(crate) fn check_expr(&self, expr: &'tcx sir::Expr<'tcx>) -> Ty<'tcx> {
+        self.check_expr_with_expectation(expr, NoExpectation)
+    }
+
+    /// 式を型検査し、その型が `expected` と一致することを要求する。
+    pub(crate) fn check_expr_has_type(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        expected: Ty<'tcx>,
+    ) -> Ty<'tcx> {
+        self.check_expr_has_type_with_alias(expr, expected, None)
+    }
+
+    /// `check_expr_has_type` と同じだが、`expected` が型エイリアス `alias` の名前で
+    /// 書かれていれば、型の不一致をその名前で報告する。
+    pub(crate) fn check_expr_has_type_with_alias(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        expected: Ty<'tcx>,
+        alias: Option<DefId>,
+    ) -> Ty<'tcx> {
+        let ty = self.check_expr_with_expectation(expr, ExpectHasType(expected));
+        self.demand_eqtype_with_alias(expr.span, expected, ty, alias, self.expr_ty_alias(expr));
+        ty
+    }
+
+    /// 式を型検査し、その型を記録する。
+    ///
+    /// `expected` はあくまでヒントであり、式の型が `expected` と一致することは保証しない。
+    pub(crate) fn check_expr_with_expectation(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let ty = ensure_sufficient_stack(|| self.check_expr_kind(expr, expected));
+        self.record_type(expr.sir_id, ty);
+        if let Some(alias) = self.expr_ty_alias(expr) {
+            self.tccx.results_for(self.owner_id).record_ty_alias(expr.sir_id.local_id, alias);
+        }
+        self.coerce_fn_item(expr, ty, expected)
+    }
+
+    /// 関数ポインタ型が期待される位置に現れた関数アイテムやクロージャを、関数ポインタに型強制する。
+    ///
+    /// シグネチャが一致する場合は型強制を記録して関数ポインタ型を返し、
+    /// それ以外の場合は `ty` をそのまま返す。
+    fn coerce_fn_item(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        ty: Ty<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        let Some(expected) = expected.only_has_type(self) else {
+            return ty;
+        };
+        if !expected.is_fn_ptr() {
+            return ty;
+        }
+        let def_id = match *self.resolve_vars_if_possible(ty).kind() {
+            TyKind::FnDef(def_id) => def_id,
+            TyKind::Closure(def_id, sig_tys) => {
+                return self.coerce_closure(expr, ty, def_id, sig_tys, expected);
+            }
+            _ => return ty,
+        };
+
+        let fn_ptr = tcx.mk_fn_ptr(&tcx.fn_sig(def_id));
+        if fn_ptr != expected {
+            return ty;
+        }
+
+        // 組み込み関数は本体をもたず、各バックエンドが呼び出しの位置で直接実装する
+        if intrinsic(tcx, def_id).is_some() {
+            let guar = DiagsTypeck::intrinsic_as_fn_ptr(
+                self.dcx(),
+                &tcx.def_path_str(def_id),
+                expr.span,
+            )
+            .emit();
+            return self.error_ty(guar);
+        }
+
+        self.tccx
+            .results_for(self.owner_id)
+            .record_fn_ptr_coercion(expr.sir_id.local_id, fn_ptr);
+        fn_ptr
+    }
+
+    /// 変数をキャプチャしないクロージャを、関数ポインタに型強制する。
+    ///
+    /// クロージャのシグネチャは推論変数を含みうるため、期待される関数ポインタ型と統一する。
+    fn coerce_closure(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        ty: Ty<'tcx>,
+        def_id: DefId,
+        sig_tys: FnSigTys<'tcx>,
+        expected: Ty<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        let fn_ptr = tcx.mk_fn_ptr(&sig_tys.to_fn_sig());
+        if self.tccx.infcx.eq(expected, fn_ptr).is_err() {
+            return ty;
+        }
+
+        let first_capture = self
+            .tccx
+            .results_for(self.owner_id)
+            .closure_captures(def_id.expect_local())
+            .first()
+            .copied();
+
+        if let Some(capture) = first_capture {
+            let sir::Node::Pat(sir::Pat {
+                kind: sir::PatKind::Binding(_, ident),
+                span: var_span,
+                ..
+            }) = tcx.sir_node(capture.var_id)
+            else {
+                panic!("bug: キャプチャされた変数が束縛ではない: {:?}", capture.var_id);
+            };
+            let guar = DiagsTypeck::capturing_closure_as_fn_ptr(
+                self.dcx(),
+                ident.name.as_str(),
+                expr.span,
+                *var_span,
+            )
+            .emit();
+            return self.error_ty(guar);
+        }
+
+        self.tccx
+            .results_for(self.owner_id)
+            .record_fn_ptr_coercion(expr.sir_id.local_id, expected);
+        expected
+    }
+
+    fn check_expr_kind(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        match expr.kind {
+            ExprKind::Lit(lit) => self.check_lit(lit, expected),
+            ExprKind::Path(ref path) => self.check_expr_path(path),
+            ExprKind::Binary(op, lhs, rhs) => self.check_binary(op, lhs, rhs, expected),
+            ExprKind::Unary(op, operand) => self.check_unary(op, operand, expr, expected),
+            ExprKind::Call(callee, args) => self.check_call(expr, callee, args),
+            ExprKind::If(cond, then, else_opt) => self.check_if(cond, then, else_opt, expected),
+            ExprKind::Block(block) => self.check_block_with_expected(block, expected),
+            ExprKind::Closure(closure) => self.check_expr_closure(closure, expected),
+            ExprKind::Assign(lhs, rhs, _) => {
+                let is_place = matches!(
+                    lhs.kind,
+                    ExprKind::Path(sir::Path {
+                        res: Res::Local(_),
+                        ..
+                    })
+                );
+
+                if is_place {
+                    let lhs_ty = self.check_expr(lhs);
+                    self.check_expr_has_type_with_alias(rhs, lhs_ty, self.expr_ty_alias(lhs));
+                } else {
+                    let guar = DiagsTypeck::invalid_assignment_target(self.dcx(), lhs.span).emit();
+                    self.error_ty(guar);
+                    self.check_expr(lhs);
+                    self.check_expr(rhs);
+                }
+
+                tcx.types.unit
+            }
+            ExprKind::Err(guar) => self.error_ty(guar),
+        }
+    }
+
+    fn check_lit(&self, lit: &sir::Lit, expected: Expectation<'tcx>) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        match lit.node {
+            LitKind::Str(_) => tcx.types.str_,
+            LitKind::Char(_) => tcx.types.char,
+            LitKind::Bool(_) => tcx.types.bool,
+            // 整数リテラルは期待される整数型を持つ。期待される型が無ければ `i32` とする
+            LitKind::Int(_) => match expected.to_option(self) {
+                Some(ty) if ty.is_integral() => ty,
+                _ => tcx.types.i32,
+            },
+            // 浮動小数点数リテラルは期待される浮動小数点数型を持つ。期待される型が無ければ `f64` とする
+            LitKind::Float(_) => match expected.to_option(self) {
+                Some(ty) if ty.is_floating_point() => ty,
+                _ => tcx.types.f64,
+            },
+            LitKind::Err(guar) => self.error_ty(guar),
+        }
+    }
+
+    fn check_expr_path(&self, path: &sir::Path<'tcx>) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        match path.res {
+            Res::Local(sir_id) => self.node_ty(sir_id),
+            Res::Def(DefKind::Fn, def_id) => tcx.mk_ty(TyKind::FnDef(def_id)),
+            Res::Err => {
+                let guar = self
+                    .dcx()
+                    .has_errors()
+                    .expect("bug: 名前解決のエラーが報告されていない");
+                self.error_ty(guar)
+            }
+            // モジュールや型が値として使われた場合は、名前解決で報告される
+            Res::Def(DefKind::Mod | DefKind::TyAlias, _) | Res::PrimTy(_) => {
+                panic!("bug: 値の名前空間で型に解決されたパス: {:?}", path.res)
+            }
+            Res::Def(DefKind::Closure, _) => {
+                panic!("bug: 名前をもたないクロージャに解決されたパス: {:?}", path.res)
+            }
+        }
+    }
+
+    /// クロージャ式を型検査する。
+    ///
+    /// 型注釈の無い引数と戻り値の型は、期待される関数ポインタ型があればそこから、
+    /// 無ければ新しい型変数として推論する。本体は、戻り値の型を `return` の対象とする
+    /// 新しい `FnCtxt` で検査する。
+    fn check_expr_closure(
+        &self,
+        closure: &'tcx sir::Closure<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+        let decl = closure.fn_decl;
+
+        let expected_sig = match expected.only_has_type(self).map(|ty| *ty.kind()) {
+            Some(TyKind::FnPtr(sig_tys)) if sig_tys.inputs().len() == decl.inputs.len() => {
+                Some(sig_tys)
+            }
+            _ => None,
+        };
+
+        let inputs = decl
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                ty_lowering::lower_ty(tcx, ty)
+                    .or_else(|| expected_sig.map(|sig| sig.inputs()[i]))
+                    .unwrap_or_else(|| self.next_ty_var(ty.span))
+            })
+            .collect::<Vec<_>>();
+        let output = match decl.output {
+            FnRetTy::Return(ty) => ty_lowering::lower_ty(tcx, ty),
+            // 関数と異なり、戻り値の型が省略された場合は推論する
+            FnRetTy::DefaultReturn(_) => expected_sig.map(|sig| sig.output()),
+        }
+        .unwrap_or_else(|| self.next_ty_var(decl.output.span()));
+
+        let body = tcx.sir_body(closure.body);
+        let fcx = FnCtxt::new(self.tccx, self.owner_id, output, &decl.output);
+
+        for ((param, &ty), decl_ty) in body.params.iter().zip(&inputs).zip(decl.inputs) {
+            fcx.record_type(param.pat.sir_id, ty);
+            fcx.record_local_alias(param.pat, decl_ty);
+        }
+
+        let body_ty = fcx.check_expr_with_expectation(body.value, ExpectHasType(output));
+        fcx.check_return_of_body(body, &decl.output, body_ty);
+
+        self.analyze_closure(closure);
+
+        tcx.mk_closure(closure.def_id.to_def_id(), &FnSig { inputs, output })
+    }
+
+    fn check_binary(
+        &self,
+        op: BinOp,
+        lhs: &'tcx sir::Expr<'tcx>,
+        rhs: &'tcx sir::Expr<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        if op.node.is_lazy() {
+            self.check_expr_has_type(lhs, tcx.types.bool);
+            self.check_expr_has_type(rhs, tcx.types.bool);
+            return tcx.types.bool;
+        }
+
+        // 算術演算の結果の型は被演算子の型と等しいため、期待される型を被演算子に伝える
+        let operand_expectation = if op.node.is_comparison() {
+            NoExpectation
+        } else {
+            expected
+        };
+
+        // `1 + x` のように、リテラルの型を他方の被演算子から決められるようにする
+        let is_lit = |e: &sir::Expr<'_>| matches!(e.kind, ExprKind::Lit(_));
+        let (first, second) = if is_lit(lhs) && !is_lit(rhs) {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+
+        let first_ty = self.check_expr_with_expectation(first, operand_expectation);
+        let second_ty = self.check_expr_with_expectation(second, ExpectHasType(first_ty));
+        self.demand_eqtype_with_alias(
+            second.span,
+            first_ty,
+            second_ty,
+            self.expr_ty_alias(first),
+            self.expr_ty_alias(second),
+        );
+
+        let operand_ty = self.resolve_vars_if_possible(if first_ty.is_never() {
+            second_ty
+        } else {
+            first_ty
+        });
+
+        // 文字列は連結と等価性の比較のみをサポートする
+        let is_valid = if operand_ty.is_str() {
+            matches!(op.node, BinOpKind::Add | BinOpKind::Eq | BinOpKind::Ne)
+        } else if op.node.is_comparison() {
+            operand_ty.is_scalar()
+        } else {
+            operand_ty.is_numeric()
+        };
+
+        if operand_ty.references_error() {
+            return operand_ty;
+        }
+
+        if !is_valid && !operand_ty.is_never() {
+            let guar = DiagsTypeck::binary_op_invalid_operand(
+                self.dcx(),
+                op.node.as_str(),
+                &tcx.ty_string(operand_ty),
+                op.span,
+            )
+            .emit();
+            return self.error_ty(guar);
+        }
+
+        if op.node.is_comparison() {
+            tcx.types.bool
+        } else {
+            operand_ty
+        }
+    }
+
+    fn check_unary(
+        &self,
+        op: UnOp,
+        operand: &'tcx sir::Expr<'tcx>,
+        expr: &'tcx sir::Expr<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let ty = self.check_expr_with_expectation(operand, expected);
+        let ty = self.resolve_vars_if_possible(ty);
+
+        if ty.references_error() || ty.is_never() {
+            return ty;
+        }
+
+        let is_valid = match op {
+            UnOp::Not => ty.is_bool() || ty.is_integral(),
+            UnOp::Neg => ty.is_signed(),
+        };
+
+        if !is_valid {
+            let guar = DiagsTypeck::unary_op_invalid_operand(
+                self.dcx(),
+                op.as_str(),
+                &self.tcx().ty_string(ty),
+                expr.span,
+            )
+            .emit();
+            return self.error_ty(guar);
+        }
+
+        ty
+    }
+
+    fn check_call(
+        &self,
+        expr: &'tcx sir::Expr<'tcx>,
+        callee: &'tcx sir::Expr<'tcx>,
+        args: &'tcx [sir::Expr<'tcx>],
+    ) -> Ty<'tcx> {
+        let callee_ty = self.check_expr(callee);
+        let callee_ty = self.resolve_vars_if_possible(callee_ty);
+
+        let sig = match *callee_ty.kind() {
+            TyKind::FnDef(def_id) => self.tcx().fn_sig(def_id),
+            TyKind::FnPtr(sig_tys) | TyKind::Closure(_, sig_tys) => sig_tys.to_fn_sig(),
+            _ => {
+                for arg in args {
+                    self.check_expr(arg);
+                }
+
+                if callee_ty.references_error() {
+                    return callee_ty;
+                }
+
+                let guar = DiagsTypeck::not_a_function(
+                    self.dcx(),
+                    &self.tcx().ty_string(callee_ty),
+                    callee.span,
+                )
+                .emit();
+                return self.error_ty(guar);
+            }
+        };
+
+        if sig.inputs.len() != args.len() {
+            DiagsTypeck::argument_count_mismatch(
+                self.dcx(),
+                sig.inputs.len(),
+                args.len(),
+                expr.span,
+            )
+            .emit();
+            self.record_error();
+
+            for arg in args {
+                self.check_expr(arg);
+            }
+        } else {
+            let aliases = match *callee_ty.kind() {
+                TyKind::FnDef(def_id) => ty_lowering::fn_input_ty_aliases(self.tcx(), def_id),
+                _ => Vec::new(),
+            };
+            for (i, (arg, &input)) in args.iter().zip(&sig.inputs).enumerate() {
+                let alias = aliases.get(i).copied().flatten();
+                self.check_expr_has_type_with_alias(arg, input, alias);
+            }
+        }
+
+        // `!` を返す関数の呼び出しの後には到達しない
+        if sig.output.is_never() {
+            self.diverges.set(Diverges::Always);
+        }
+
+        sig.output
+    }
+
+    fn check_if(
+        &self,
+        cond: &'tcx sir::Expr<'tcx>,
+        then: &'tcx sir::Expr<'tcx>,
+        else_opt: Option<&'tcx sir::Expr<'tcx>>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let tcx = self.tcx();
+
+        self.check_expr_has_type(cond, tcx.types.bool);
+        let cond_diverges = self.diverges.replace(Diverges::Maybe);
+
+        let expected = expected.adjust_for_branches(self);
+        let then_ty = self.check_expr_with_expectation(then, expected);
+        let then_diverges = self.diverges.replace(Diverges::Maybe);
+
+        let Some(else_expr) = else_opt else {
+            // `else` 節が無い場合、`then` 節は `()` でなければならない
+            self.demand_eqtype(then.span, tcx.types.unit, then_ty);
+            self.diverges.set(cond_diverges);
+            return tcx.types.unit;
+        };
+
+        let else_expectation = if then_ty.is_never() {
+            expected
+        } else {
+            ExpectHasType(then_ty)
+        };
+        let else_ty = self.check_expr_with_expectation(else_expr, else_expectation);
+        let else_diverges = self.diverges.get();
+
+        // 両方の節が発散する場合にのみ、`if` 式全体が発散する
+        self.diverges.set(cond_diverges.max(then_diverges.min(else_diverges)));
+
+        if then_ty.is_never() {
+            else_ty
+        } else {
+            self.demand_eqtype(else_expr.span, then_ty, else_ty);
+            then_ty
+        }
+    }
+}
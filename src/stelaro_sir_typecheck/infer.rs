@@ -161,6 +161,7 @@ impl<'tcx> UnifyValue for TypeVariableValue<'tcx> {
 }
 
 impl<'a, 'tcx> TypeVariableTable<'a, 'tcx> {
+    #[allow(dead_code)]
     pub fn new(storage: &'a mut TypeVariableStorage<'tcx>) -> Self {
         Self { storage }
     }
@@ -216,6 +217,7 @@ impl<'a, 'tcx> TypeVariableTable<'a, 'tcx> {
     }
 
     /// これまでに作成された型変数の総数を返します。
+    #[allow(dead_code)]
     pub(crate) fn num_vars(&self) -> usize {
         self.storage.values.len()
     }
@@ -239,6 +241,7 @@ impl<'a, 'tcx> TypeVariableTable<'a, 'tcx> {
 
     /// まだ解決されていない（Unknown状態の）すべての型変数のリストを返します。
     /// 型チェックの最後に呼び出し、もしリストが空でなければ型エラーを報告するために使います。
+    #[allow(dead_code)]
     pub(crate) fn unresolved_variables(&mut self) -> Vec<TyVid> {
         (0..self.num_vars())
             .filter_map(|i| {
@@ -252,7 +255,76 @@ impl<'a, 'tcx> TypeVariableTable<'a, 'tcx> {
     }
 }
 
+/// 2つの型を統一できなかったことを表す。
+#[derive(Copy, Clone, Debug)]
+pub struct TypeError<'tcx> {
+    pub expected: Ty<'tcx>,
+    pub found: Ty<'tcx>,
+}
+
 impl<'tcx> InferCtxt<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> InferCtxt<'tcx> {
+        InferCtxt {
+            tcx,
+            inner: RefCell::new(InferCtxtInner::new()),
+            tainted_by_errors: Cell::new(false),
+        }
+    }
+
+    pub fn set_tainted_by_errors(&self) {
+        self.tainted_by_errors.set(true);
+    }
+
+    pub fn tainted_by_errors(&self) -> bool {
+        self.tainted_by_errors.get()
+    }
+
+    /// 新しい型変数を生成する。
+    pub fn next_ty_var(&self, origin: TypeVariableOrigin) -> Ty<'tcx> {
+        let vid = self.inner.borrow_mut().type_variables().new_var(origin);
+        self.tcx.mk_ty(TyKind::Infer(InferTy::TyVar(vid)))
+    }
+
+    /// 型変数が生成されたときの発生源を返す。
+    pub fn var_origin(&self, vid: TyVid) -> TypeVariableOrigin {
+        *self.inner.borrow_mut().type_variables().var_origin(vid)
+    }
+
+    /// 型変数の同値クラスを代表する変数を返す。
+    pub fn root_var(&self, vid: TyVid) -> TyVid {
+        self.inner.borrow_mut().type_variables().root_var(vid)
+    }
+
+    /// `expected` と `found` が等しくなるように、型変数を束縛する。
+    ///
+    /// 型変数が含まれない場合は、単に2つの型が等しいかどうかを確かめる。
+    pub fn eq(&self, expected: Ty<'tcx>, found: Ty<'tcx>) -> Result<(), TypeError<'tcx>> {
+        let a = self.shallow_resolve(expected);
+        let b = self.shallow_resolve(found);
+
+        let mut inner = self.inner.borrow_mut();
+        let mut table = inner.type_variables();
+
+        match (*a.kind(), *b.kind()) {
+            (TyKind::Infer(InferTy::TyVar(a_vid)), TyKind::Infer(InferTy::TyVar(b_vid))) => {
+                if table.root_var(a_vid) != table.root_var(b_vid) {
+                    table.equate(a_vid, b_vid);
+                }
+                Ok(())
+            }
+            (TyKind::Infer(InferTy::TyVar(vid)), _) => {
+                table.instantiate(vid, b);
+                Ok(())
+            }
+            (_, TyKind::Infer(InferTy::TyVar(vid))) => {
+                table.instantiate(vid, a);
+                Ok(())
+            }
+            _ if a == b => Ok(()),
+            _ => Err(TypeError { expected: a, found: b }),
+        }
+    }
+
     pub fn shallow_resolve(&self, ty: Ty<'tcx>) -> Ty<'tcx> {
         if let TyKind::Infer(InferTy::TyVar(v)) = *ty.kind() {
             // ここは完全に自明ではなく、もし `ty` が型変数であっても、
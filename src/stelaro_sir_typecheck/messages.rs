@@ -0,0 +1,147 @@
+//! 型検査で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    MISMATCHED_TYPES {
+        ja: "型が一致しません",
+        en: "mismatched types",
+    }
+    MISMATCHED_TYPES_LABEL {
+        ja: "`{expected}` を期待していましたが、`{found}` が見つかりました",
+        en: "expected `{expected}`, found `{found}`",
+    }
+    MISMATCHED_TYPES_FOUND_ALIAS_LABEL {
+        ja: "`{expected}` を期待していましたが、`{alias}` (`{found}`) が見つかりました",
+        en: "expected `{expected}`, found `{alias}` (`{found}`)",
+    }
+
+    RETURN_TYPE_LABEL {
+        ja: "戻り値の型により `{expected}` を期待しています",
+        en: "expected `{expected}` because of return type",
+    }
+    IMPLICIT_UNIT_RETURN_LABEL {
+        ja: "本体に末尾の式も `return` も無いため、暗黙的に `()` を返します",
+        en: "implicitly returns `()` as its body has no tail or `return` expression",
+    }
+    REMOVE_SEMICOLON_SUGGESTION {
+        ja: "この値を返すには、このセミコロンを削除してください",
+        en: "remove this semicolon to return this value",
+    }
+
+    TYPE_ANNOTATIONS_NEEDED {
+        ja: "型注釈が必要です",
+        en: "type annotations needed",
+    }
+    TYPE_ANNOTATIONS_NEEDED_LABEL {
+        ja: "この変数の型を推論できませんでした",
+        en: "cannot infer the type of this variable",
+    }
+    TYPE_ANNOTATIONS_NEEDED_HELP {
+        ja: "`let x: i32` のように型注釈を追加してください",
+        en: "consider giving it an explicit type, such as `let x: i32`",
+    }
+
+    NOT_A_FUNCTION {
+        ja: "関数ではない値の呼び出し",
+        en: "call to a non-function value",
+    }
+    NOT_A_FUNCTION_LABEL {
+        ja: "`{ty}` 型の値を呼び出すことはできません",
+        en: "a value of type `{ty}` cannot be called",
+    }
+
+    ARGUMENT_COUNT_MISMATCH {
+        ja: "引数の数が一致しません",
+        en: "this function takes a different number of arguments",
+    }
+    ARGUMENT_COUNT_MISMATCH_LABEL {
+        ja: "{expected} 個の引数を期待していましたが、{found} 個の引数が渡されました",
+        en: "expected {expected} argument(s), found {found}",
+    }
+
+    BINARY_OP_INVALID_OPERAND {
+        ja: "`{ty}` に二項演算子 `{op}` を適用することはできません",
+        en: "cannot apply binary operator `{op}` to type `{ty}`",
+    }
+    BINARY_OP_INVALID_OPERAND_LABEL {
+        ja: "`{ty}` 型の値に `{op}` を使用することはできません",
+        en: "`{op}` cannot be used on values of type `{ty}`",
+    }
+
+    UNARY_OP_INVALID_OPERAND {
+        ja: "`{ty}` に単項演算子 `{op}` を適用することはできません",
+        en: "cannot apply unary operator `{op}` to type `{ty}`",
+    }
+    UNARY_OP_INVALID_OPERAND_LABEL {
+        ja: "`{ty}` 型の値に `{op}` を使用することはできません",
+        en: "`{op}` cannot be used on values of type `{ty}`",
+    }
+
+    /// `kind` は `break` または `continue`
+    OUTSIDE_OF_LOOP {
+        ja: "ループの外側にある `{kind}`",
+        en: "`{kind}` outside of a loop",
+    }
+    OUTSIDE_OF_LOOP_LABEL {
+        ja: "`{kind}` はループの内側でのみ使用できます",
+        en: "cannot `{kind}` outside of a loop",
+    }
+
+    EXPECTED_TYPE_FOUND_MODULE {
+        ja: "型を期待していましたが、モジュール `{name}` が見つかりました",
+        en: "expected type, found module `{name}`",
+    }
+    EXPECTED_TYPE_FOUND_MODULE_LABEL {
+        ja: "これは型ではありません",
+        en: "not a type",
+    }
+
+    INVALID_ASSIGNMENT_TARGET {
+        ja: "無効な代入先",
+        en: "invalid left-hand side of assignment",
+    }
+    INVALID_ASSIGNMENT_TARGET_LABEL {
+        ja: "この式に代入することはできません",
+        en: "cannot assign to this expression",
+    }
+
+    PLACEHOLDER_IN_SIGNATURE {
+        ja: "関数のシグネチャに型のプレースホルダ `_` を使用することはできません",
+        en: "the placeholder `_` is not allowed within types on function signatures",
+    }
+    PLACEHOLDER_IN_SIGNATURE_LABEL {
+        ja: "ここに型を明示してください",
+        en: "write an explicit type here",
+    }
+    PLACEHOLDER_IN_TY_ALIAS {
+        ja: "型エイリアスに型のプレースホルダ `_` を使用することはできません",
+        en: "the placeholder `_` is not allowed within types on type aliases",
+    }
+
+    INTRINSIC_AS_FN_PTR {
+        ja: "組み込み関数 `{name}` を関数ポインタに変換することはできません",
+        en: "cannot coerce the intrinsic function `{name}` to a function pointer",
+    }
+    INTRINSIC_AS_FN_PTR_LABEL {
+        ja: "標準ステロの関数は直接呼び出す必要があります",
+        en: "functions of the standard stelo must be called directly",
+    }
+    INTRINSIC_AS_FN_PTR_HELP {
+        ja: "呼び出しを包む関数を定義し、その関数を渡してください",
+        en: "define a function that wraps the call and pass that function instead",
+    }
+    CAPTURING_CLOSURE_AS_FN_PTR {
+        ja: "変数をキャプチャするクロージャを関数ポインタに変換することはできません",
+        en: "cannot coerce a closure that captures variables to a function pointer",
+    }
+    CAPTURING_CLOSURE_AS_FN_PTR_LABEL {
+        ja: "関数ポインタが期待されています",
+        en: "expected a function pointer",
+    }
+    CAPTURED_VARIABLE_LABEL {
+        ja: "クロージャは `{name}` をキャプチャしています",
+        en: "the closure captures `{name}`",
+    }
+}
@@ -1,28 +1,116 @@
+mod diagnostics;
+pub mod dump;
 mod expectation;
 mod expr;
 mod infer;
+pub mod messages;
 mod resolve;
 pub mod result;
+mod stmt;
+mod ty_lowering;
+mod upvar;
+mod writeback;
 
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 
-use crate::stelaro_common::{LocalDefId, Span};
+use crate::stelaro_common::{DefId, LocalDefId, Span};
 use crate::stelaro_context::TyCtxt;
+use crate::stelaro_context::query::Providers;
 use crate::stelaro_diagnostics::DiagCtxtHandle;
+use crate::stelaro_sir::Res;
+use crate::stelaro_sir::sir::{self, ItemKind, MaybeOwner, OwnerNode};
 use crate::stelaro_sir::sir_id::SirId;
-use crate::stelaro_sir_typecheck::{infer::InferCtxt, result::TypeckResults};
-use crate::stelaro_ty::Ty;
+use crate::stelaro_sir_typecheck::{
+    expectation::Expectation,
+    infer::{InferCtxt, TypeVariableOrigin},
+    result::TypeckResults,
+};
+use crate::stelaro_diagnostics::ErrorEmitted;
+use crate::stelaro_sir_typecheck::diagnostics::DiagsTypeck;
+use crate::stelaro_ty::fold::TypeFoldable;
+use crate::stelaro_ty::visit::TypeVisitableExt;
+use crate::stelaro_ty::{Ty, TyKind};
+
+pub fn provide(providers: &mut Providers) {
+    providers.fn_sig = ty_lowering::fn_sig;
+    providers.type_of = ty_lowering::type_of;
+    providers.typeck = typeck;
+}
+
+/// ステロ内のすべての関数本体を型検査し、オーナーごとの結果を返す。
+///
+/// 使われていない型エイリアスの循環も報告するため、すべての型エイリアスを展開する。
+pub fn check_stelo<'tcx>(tcx: TyCtxt<'tcx>) -> HashMap<LocalDefId, &'tcx TypeckResults<'tcx>> {
+    let mut results = HashMap::new();
+
+    for (def_id, owner) in tcx.sir_stelo(()).owners.iter_enumerated() {
+        let MaybeOwner::Owner(info) = owner else {
+            continue;
+        };
+
+        match info.nodes.node() {
+            OwnerNode::Item(sir::Item {
+                kind: ItemKind::Fn { .. },
+                ..
+            }) => {
+                results.insert(def_id, tcx.typeck(def_id));
+            }
+            OwnerNode::Item(sir::Item {
+                kind: ItemKind::TyAlias(..),
+                ..
+            }) => {
+                tcx.type_of(def_id.to_def_id());
+            }
+            _ => {}
+        }
+    }
+
+    results
+}
+
+/// 関数本体を型検査する。`typeck` クエリの実装。
+///
+/// インクリメンタルコンパイルでは、前回のコンパイルから変更されていない関数の結果を再利用する。
+fn typeck(tcx: TyCtxt<'_>, def_id: LocalDefId) -> &TypeckResults<'_> {
+    let reused = tcx
+        .incremental
+        .borrow_mut()
+        .as_mut()
+        .and_then(|cache| cache.reuse_typeck(tcx, def_id));
+
+    let results = reused.unwrap_or_else(|| {
+        let OwnerNode::Item(sir::Item {
+            kind: ItemKind::Fn { sig, body, .. },
+            ..
+        }) = tcx.expect_sir_owner_node(def_id)
+        else {
+            panic!("bug: {def_id:?} は関数ではない");
+        };
+
+        let tccx = TypeCheckCtxt::new(tcx);
+        tccx.check_fn(def_id, sig, tcx.sir_body(*body));
+        tccx.take_results().remove(&def_id).unwrap()
+    });
+
+    tcx.arena.alloc(results)
+}
 
 pub struct TypeCheckCtxt<'tcx> {
     pub infcx: InferCtxt<'tcx>,
 
     /// 各オーナーの型チェック結果を格納するマップ。
     results_map: RefCell<HashMap<LocalDefId, TypeckResults<'tcx>>>,
+
+    /// 型が型エイリアスの名前で書かれたローカル変数と、その型エイリアス。
+    /// クロージャの本体からも参照できるよう、オーナーごとではなくここで保持する。
+    local_aliases: RefCell<HashMap<SirId, DefId>>,
 }
 
 /// 型チェック中にコードの発散（divergence）状態を追跡します。
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// 発散の度合いの順に並んでいるため、`max` や `min` で合成できます。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Diverges {
     /// 発散しない
     Maybe,
@@ -40,6 +128,35 @@ pub struct BreakableScope<'tcx> {
 }
 
 impl<'tcx> TypeCheckCtxt<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            infcx: InferCtxt::new(tcx),
+            results_map: RefCell::new(HashMap::new()),
+            local_aliases: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// 関数本体を型検査し、結果を `results_map` に記録する。
+    fn check_fn(&self, def_id: LocalDefId, sig: &sir::FnSig<'tcx>, body: &'tcx sir::Body<'tcx>) {
+        let fn_sig = self.infcx.tcx.fn_sig(def_id.to_def_id());
+
+        // 本体が空であっても、すべての関数が結果を持つようにする
+        self.results_for(def_id).fn_sig = Some(fn_sig.clone());
+
+        let fcx = FnCtxt::new(self, def_id, fn_sig.output, &sig.decl.output);
+
+        for ((param, &ty), decl_ty) in body.params.iter().zip(&fn_sig.inputs).zip(sig.decl.inputs) {
+            fcx.record_type(param.pat.sir_id, ty);
+            fcx.record_local_alias(param.pat, decl_ty);
+        }
+
+        let body_ty =
+            fcx.check_expr_with_expectation(body.value, Expectation::ExpectHasType(fn_sig.output));
+        fcx.check_return_of_body(body, &sig.decl.output, body_ty);
+
+        fcx.resolve_type_vars_in_body();
+    }
+
     /// 指定されたオーナーの`TypeckResults`への可変参照を取得し、存在しない場合は新しく作成する。
     pub(crate) fn results_for(&self, owner_id: LocalDefId) -> std::cell::RefMut<'_, TypeckResults<'tcx>> {
         let mut map = self.results_map.borrow_mut();
@@ -66,6 +183,9 @@ pub struct FnCtxt<'a, 'tcx> {
     /// `return` が最初に現れる場所。
     pub return_span: Span,
 
+    /// 戻り値の型が型エイリアスの名前で書かれていれば、その型エイリアス。
+    pub return_alias: Option<DefId>,
+
     /// 現在のコードパスが発散しているかどうか。
     pub diverges: Cell<Diverges>,
 
@@ -81,13 +201,19 @@ impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
         tccx: &'a TypeCheckCtxt<'tcx>,
         owner_id: LocalDefId,
         return_ty: Ty<'tcx>,
-        return_span: Span,
+        output: &sir::FnRetTy<'tcx>,
     ) -> Self {
+        let return_alias = match output {
+            sir::FnRetTy::Return(ty) => ty_lowering::ty_alias_of(ty),
+            sir::FnRetTy::DefaultReturn(_) => None,
+        };
+
         Self {
             tccx,
             owner_id,
             return_ty,
-            return_span,
+            return_span: output.span(),
+            return_alias,
             diverges: Cell::new(Diverges::Maybe),
             breakable_scopes: RefCell::new(Vec::new()),
             loop_break_types: RefCell::new(HashMap::new()),
@@ -118,6 +244,7 @@ impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
     pub fn record_error(&self) {
         let mut results = self.tccx.results_for(self.owner_id);
         results.tainted_by_errors = true;
+        self.tccx.infcx.set_tainted_by_errors();
     }
 
     /// エラーを報告し、`tainted_by_errors`フラグを立てます。
@@ -128,19 +255,117 @@ impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
         self.record_error();
     }
 
-    // /// 可能な場合、`value`内の型変数を最終的な値で置換します。
-    // /// また、型変数が未統合の場合はそのまま保持されます。
-    // /// この操作は冪等性を有しており、推論状態に一切の
-    // /// 影響を与えないため、任意のタイミングで実行可能です。
-    // pub fn resolve_vars_if_possible<T>(&self, value: T) -> T
-    // where
-    //     T: TypeFoldable<'tcx>,
-    // {
-    //     if let Err(guar) = value.error_reported() {
-    //         self.set_tainted_by_errors(guar);
-    //     }
-    //     let mut r = resolve::OpportunisticVarResolver::new(self);
-    //     value.fold_with(&mut r)
-    // }
+    /// 報告済みのエラーを表す型を返し、`tainted_by_errors`フラグを立てます。
+    pub fn error_ty(&self, guar: ErrorEmitted) -> Ty<'tcx> {
+        self.record_error();
+        self.tcx().mk_ty(TyKind::Error(guar))
+    }
+
+    /// 同じオーナー内のノードに記録された型を返す。
+    pub fn node_ty(&self, sir_id: SirId) -> Ty<'tcx> {
+        self.tccx
+            .results_for(self.owner_id)
+            .node_type(sir_id.local_id)
+            .unwrap_or_else(|| panic!("bug: {sir_id:?} の型が記録されていない"))
+    }
+
+    /// `expected` と `found` を統一し、統一できない場合は型の不一致を報告します。
+    ///
+    /// `found` が `!` の場合は、任意の型に型強制できるものとして扱います。
+    pub fn demand_eqtype(&self, span: Span, expected: Ty<'tcx>, found: Ty<'tcx>) {
+        self.demand_eqtype_with_alias(span, expected, found, None, None);
+    }
+
+    /// `demand_eqtype` と同じですが、`expected` が型エイリアス `alias` の名前で
+    /// 書かれた型である場合に、型の不一致をその名前で報告します。
+    /// `found` が型エイリアス `found_alias` の名前で書かれた型であれば、その名前も示します。
+    pub fn demand_eqtype_with_alias(
+        &self,
+        span: Span,
+        expected: Ty<'tcx>,
+        found: Ty<'tcx>,
+        alias: Option<DefId>,
+        found_alias: Option<DefId>,
+    ) {
+        let expected = self.resolve_vars_if_possible(expected);
+        let found = self.resolve_vars_if_possible(found);
+
+        if expected.references_error() || found.references_error() || found.is_never() {
+            return;
+        }
+
+        if let Err(err) = self.tccx.infcx.eq(expected, found) {
+            // 型の一部が一致しない場合は、その部分を展開した型で示す
+            let alias = alias.filter(|_| err.expected == expected);
+            let found_alias = found_alias
+                .filter(|_| err.found == found)
+                .map(|alias| self.tcx().def_path_str(alias));
+            DiagsTypeck::mismatched_types(
+                self.dcx(),
+                &self.ty_string_with_alias(err.expected, alias),
+                &self.tcx().ty_string(err.found),
+                found_alias.as_deref(),
+                span,
+            )
+            .emit();
+            self.record_error();
+        }
+    }
 
+    /// 変数の宣言の型が型エイリアスの名前で書かれていれば、それを記録します。
+    pub fn record_local_alias(&self, pat: &sir::Pat, ty: &sir::Ty<'tcx>) {
+        if let sir::PatKind::Binding(var_id, _) = pat.kind
+            && let Some(alias) = ty_lowering::ty_alias_of(ty)
+        {
+            self.tccx.local_aliases.borrow_mut().insert(var_id, alias);
+            self.tccx.results_for(self.owner_id).record_ty_alias(pat.sir_id.local_id, alias);
+        }
+    }
+
+    /// 式の値の型が型エイリアスの名前で書かれていれば、その型エイリアスを返します。
+    ///
+    /// 型エイリアスで宣言された変数と、戻り値の型が型エイリアスで書かれた関数の呼び出しが対象です。
+    pub fn expr_ty_alias(&self, expr: &sir::Expr<'tcx>) -> Option<DefId> {
+        match expr.kind {
+            sir::ExprKind::Path(sir::Path {
+                res: Res::Local(var_id),
+                ..
+            }) => self.tccx.local_aliases.borrow().get(&var_id).copied(),
+            sir::ExprKind::Call(callee, _) => {
+                match *self.resolve_vars_if_possible(self.node_ty(callee.sir_id)).kind() {
+                    TyKind::FnDef(def_id) => ty_lowering::fn_output_ty_alias(self.tcx(), def_id),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// 型を文字列に変換します。型エイリアス `alias` の名前で書かれた型であれば、その名前を用います。
+    pub fn ty_string_with_alias(&self, ty: Ty<'tcx>, alias: Option<DefId>) -> String {
+        match alias {
+            Some(alias) => self.tcx().def_path_str(alias),
+            None => self.tcx().ty_string(ty),
+        }
+    }
+
+    /// 可能な場合、`value`内の型変数を最終的な値で置換します。
+    /// また、型変数が未統合の場合はそのまま保持されます。
+    /// この操作は冪等性を有しており、推論状態に一切の
+    /// 影響を与えないため、任意のタイミングで実行可能です。
+    pub fn resolve_vars_if_possible<T>(&self, value: T) -> T
+    where
+        T: TypeFoldable<'tcx>,
+    {
+        if !value.has_infer_types() {
+            return value;
+        }
+        let mut r = resolve::OpportunisticVarResolver::new(&self.tccx.infcx);
+        value.fold_with(&mut r)
+    }
+
+    /// `span` の位置で、新しい型変数を生成します。
+    pub fn next_ty_var(&self, span: Span) -> Ty<'tcx> {
+        self.tccx.infcx.next_ty_var(TypeVariableOrigin { span, param_def_id: None })
+    }
 }
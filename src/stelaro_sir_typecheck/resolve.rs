@@ -3,9 +3,9 @@ use std::fmt;
 use crate::stelaro_common::DelayedMap;
 use crate::stelaro_context::TyCtxt;
 use crate::stelaro_sir_typecheck::infer::InferCtxt;
-use crate::stelaro_ty::Ty;
+use crate::stelaro_ty::{Ty, TyKind};
 use crate::stelaro_ty::fold::{FallibleTypeFolder, TypeFoldable, TypeFolder, TypeSuperFoldable};
-use crate::stelaro_ty::ty::TyVid;
+use crate::stelaro_ty::ty::{InferTy, TyVid};
 use crate::stelaro_ty::visit::{Flags, TypeFlags};
 
 /// `fully_resolve` が失敗したときに返されるエラー。
@@ -13,7 +13,7 @@ use crate::stelaro_ty::visit::{Flags, TypeFlags};
 #[derive(Copy, Clone, Debug)]
 pub struct UnresolvedInferVar {
     /// 解決できなかった型変数のID。
-    vid: TyVid,
+    pub vid: TyVid,
 }
 
 impl UnresolvedInferVar {
@@ -99,6 +99,10 @@ impl<'a, 'tcx> FallibleTypeFolder<'tcx> for FullTypeResolver<'a, 'tcx> {
             return Ok(ty);
         }
 
-        todo!()
+        let ty = self.infcx.shallow_resolve(ty);
+        match *ty.kind() {
+            TyKind::Infer(InferTy::TyVar(vid)) => Err(UnresolvedInferVar::new(vid)),
+            _ => ty.try_super_fold_with(self),
+        }
     }
 }
@@ -1,6 +1,6 @@
-use crate::stelaro_common::LocalDefId;
-use crate::stelaro_sir::sir_id::ItemLocalId;
-use crate::stelaro_ty::ty::Ty;
+use crate::stelaro_common::{DefId, LocalDefId};
+use crate::stelaro_sir::sir_id::{ItemLocalId, SirId};
+use crate::stelaro_ty::{FnSig, ty::Ty};
 use std::collections::HashMap;
 
 /// 型チェックの成果物を集約する構造体。
@@ -13,6 +13,20 @@ pub struct TypeckResults<'tcx> {
     /// 各SIRノードの型を格納するマップ
     node_types: HashMap<ItemLocalId, Ty<'tcx>>,
 
+    /// 関数アイテムから関数ポインタへ型強制された式と、強制後の型。
+    /// 式そのものの型は、強制前の型として `node_types` に記録される。
+    fn_ptr_coercions: HashMap<ItemLocalId, Ty<'tcx>>,
+
+    /// 型が型エイリアスの名前で書かれたノードと、その型エイリアス。
+    /// 型エイリアスで宣言された変数の束縛とその使用、戻り値の型が型エイリアスで書かれた関数の呼び出しが対象。
+    ty_aliases: HashMap<ItemLocalId, DefId>,
+
+    /// オーナー内の各クロージャがキャプチャした変数。変数が最初に使われた順に並ぶ。
+    closure_captures: HashMap<LocalDefId, Vec<CapturedVar>>,
+
+    /// オーナーが関数の場合、そのシグネチャ。
+    pub fn_sig: Option<FnSig<'tcx>>,
+
     /// 型チェック中にエラーが発生したかどうか。
     /// エラーがあった場合、後続のフェーズをスキップできる。
     pub tainted_by_errors: bool,
@@ -23,6 +37,10 @@ impl<'tcx> TypeckResults<'tcx> {
         Self {
             owner_id,
             node_types: HashMap::new(),
+            fn_ptr_coercions: HashMap::new(),
+            ty_aliases: HashMap::new(),
+            closure_captures: HashMap::new(),
+            fn_sig: None,
             tainted_by_errors: false,
         }
     }
@@ -36,4 +54,82 @@ impl<'tcx> TypeckResults<'tcx> {
     pub fn node_type(&self, id: ItemLocalId) -> Option<Ty<'tcx>> {
         self.node_types.get(&id).copied()
     }
+
+    /// 記録されたすべてのノードの型を返す。順序は定まらない。
+    pub fn node_types(&self) -> impl Iterator<Item = (ItemLocalId, Ty<'tcx>)> {
+        self.node_types.iter().map(|(&id, &ty)| (id, ty))
+    }
+
+    /// 式が関数ポインタへ型強制されたことを記録する。
+    pub fn record_fn_ptr_coercion(&mut self, id: ItemLocalId, ty: Ty<'tcx>) {
+        self.fn_ptr_coercions.insert(id, ty);
+    }
+
+    /// 式が関数ポインタへ型強制されている場合、強制後の型を返す。
+    pub fn fn_ptr_coercion(&self, id: ItemLocalId) -> Option<Ty<'tcx>> {
+        self.fn_ptr_coercions.get(&id).copied()
+    }
+
+    /// 記録されたすべての関数ポインタへの型強制を返す。順序は定まらない。
+    pub fn fn_ptr_coercions(&self) -> impl Iterator<Item = (ItemLocalId, Ty<'tcx>)> {
+        self.fn_ptr_coercions.iter().map(|(&id, &ty)| (id, ty))
+    }
+
+    /// ノードの型が型エイリアスの名前で書かれていることを記録する。
+    pub fn record_ty_alias(&mut self, id: ItemLocalId, alias: DefId) {
+        self.ty_aliases.insert(id, alias);
+    }
+
+    /// ノードの型が型エイリアスの名前で書かれている場合、その型エイリアスを返す。
+    pub fn ty_alias(&self, id: ItemLocalId) -> Option<DefId> {
+        self.ty_aliases.get(&id).copied()
+    }
+
+    /// 記録されたすべての型エイリアスの名前で書かれたノードを返す。順序は定まらない。
+    pub fn ty_aliases(&self) -> impl Iterator<Item = (ItemLocalId, DefId)> {
+        self.ty_aliases.iter().map(|(&id, &alias)| (id, alias))
+    }
+
+    /// クロージャがキャプチャした変数を記録する。
+    pub fn record_closure_captures(&mut self, closure: LocalDefId, captures: Vec<CapturedVar>) {
+        self.closure_captures.insert(closure, captures);
+    }
+
+    /// クロージャがキャプチャした変数を返す。
+    pub fn closure_captures(&self, closure: LocalDefId) -> &[CapturedVar] {
+        self.closure_captures
+            .get(&closure)
+            .map_or(&[], |captures| captures.as_slice())
+    }
+
+    /// 記録されたすべてのクロージャのキャプチャを返す。順序は定まらない。
+    pub fn all_closure_captures(&self) -> impl Iterator<Item = (LocalDefId, &[CapturedVar])> {
+        self.closure_captures
+            .iter()
+            .map(|(&closure, captures)| (closure, captures.as_slice()))
+    }
+
+    /// 記録されたすべてのノードの型への可変参照を返す。
+    pub(crate) fn node_types_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&ItemLocalId, &mut Ty<'tcx>)> {
+        self.node_types.iter_mut()
+    }
+}
+
+/// クロージャがキャプチャした変数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapturedVar {
+    /// キャプチャされた変数の束縛。クロージャを含むオーナーの中にある。
+    pub var_id: SirId,
+    pub kind: CaptureKind,
+}
+
+/// 変数をどのようにキャプチャするか。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureKind {
+    /// 外側の変数そのものを読み書きする。
+    ByRef,
+    /// クロージャの生成時に値をコピーし、以降はそのコピーを読み書きする。
+    ByValue,
 }
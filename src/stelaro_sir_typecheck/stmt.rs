@@ -0,0 +1,245 @@
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::Span;
+use crate::stelaro_sir::sir::{self, ExprKind, LitKind, LoopSource, StmtKind};
+use crate::stelaro_sir_typecheck::{
+    BreakableScope, Diverges, FnCtxt,
+    diagnostics::DiagsTypeck,
+    expectation::Expectation::{self, *},
+    ty_lowering,
+};
+use crate::stelaro_ty::Ty;
+use crate::stelaro_ty::visit::TypeVisitableExt;
+
+/// 期待される型が無かったために既定の型 (`i32`, `f64`) をもつリテラルが、
+/// 戻り値の型として期待されていれば `ty` をもち得るかどうか。
+fn lit_can_have_type(expr: &sir::Expr<'_>, ty: Ty<'_>) -> bool {
+    match expr.kind {
+        ExprKind::Lit(lit) => match lit.node {
+            LitKind::Int(_) => ty.is_integral(),
+            LitKind::Float(_) => ty.is_floating_point(),
+            _ => false,
+        },
+        ExprKind::Unary(UnOp::Neg, operand) => ty.is_signed() && lit_can_have_type(operand, ty),
+        _ => false,
+    }
+}
+
+impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
+    pub(crate) fn check_block_with_expected(
+        &self,
+        block: &'tcx sir::Block<'tcx>,
+        expected: Expectation<'tcx>,
+    ) -> Ty<'tcx> {
+        let prev_diverges = self.diverges.replace(Diverges::Maybe);
+
+        for stmt in block.stmts {
+            self.check_stmt(stmt);
+        }
+
+        let ty = match block.expr {
+            Some(tail) => self.check_expr_with_expectation(tail, expected),
+            // 末尾の式が無いブロックは、発散しない限り `()` を持つ
+            None if self.diverges.get() >= Diverges::Always => self.tcx().types.never,
+            None => self.tcx().types.unit,
+        };
+
+        self.record_type(block.sir_id, ty);
+        self.diverges.set(prev_diverges.max(self.diverges.get()));
+
+        ty
+    }
+
+    /// 関数本体の型が戻り値の型と一致することを確かめる。
+    ///
+    /// 本体の末尾の式には戻り値の型が期待として伝えられるだけなので、ここで一致を要求する。
+    pub(crate) fn check_return_of_body(
+        &self,
+        body: &'tcx sir::Body<'tcx>,
+        output: &sir::FnRetTy<'tcx>,
+        body_ty: Ty<'tcx>,
+    ) {
+        let return_ty = self.resolve_vars_if_possible(self.return_ty);
+        let body_ty = self.resolve_vars_if_possible(body_ty);
+
+        if return_ty.references_error() || body_ty.references_error() || body_ty.is_never() {
+            return;
+        }
+
+        if self.tccx.infcx.eq(return_ty, body_ty).is_ok() {
+            return;
+        }
+
+        let tcx = self.tcx();
+        let expected = self.ty_string_with_alias(return_ty, self.return_alias);
+        let ret_span = match output {
+            sir::FnRetTy::Return(ty) => Some(ty.span),
+            sir::FnRetTy::DefaultReturn(_) => None,
+        };
+
+        let diag = match body.value.kind {
+            ExprKind::Block(sir::Block {
+                stmts,
+                expr: None,
+                span,
+                ..
+            }) => {
+                // 最後の文の値が戻り値の型をもつなら、セミコロンを削除すればよい
+                let semi_span = stmts.last().and_then(|stmt| match stmt.kind {
+                    StmtKind::Semi(expr)
+                        if self.resolve_vars_if_possible(self.node_ty(expr.sir_id)) == return_ty
+                            || lit_can_have_type(expr, return_ty) =>
+                    {
+                        Some(Span::from(expr.span.end..stmt.span.end))
+                    }
+                    _ => None,
+                });
+                // 本体の閉じ括弧で `()` が返される
+                let implicit_span = Span::from(span.end - 1..span.end);
+
+                DiagsTypeck::missing_return_value(
+                    self.dcx(),
+                    &expected,
+                    output.span(),
+                    implicit_span,
+                    semi_span,
+                )
+            }
+            ExprKind::Block(sir::Block {
+                expr: Some(tail), ..
+            }) => DiagsTypeck::mismatched_return_type(
+                self.dcx(),
+                &expected,
+                &tcx.ty_string(body_ty),
+                self.expr_ty_alias(tail).map(|alias| tcx.def_path_str(alias)).as_deref(),
+                tail.span,
+                ret_span,
+            ),
+            _ => DiagsTypeck::mismatched_return_type(
+                self.dcx(),
+                &expected,
+                &tcx.ty_string(body_ty),
+                self.expr_ty_alias(body.value).map(|alias| tcx.def_path_str(alias)).as_deref(),
+                body.value.span,
+                ret_span,
+            ),
+        };
+
+        diag.emit();
+        self.record_error();
+    }
+
+    fn check_stmt(&self, stmt: &'tcx sir::Stmt<'tcx>) {
+        let tcx = self.tcx();
+
+        match stmt.kind {
+            StmtKind::Let(local) => self.check_let(local),
+            // アイテムは自身のオーナーで検査される
+            StmtKind::Item(_) => {}
+            StmtKind::Expr(expr) => {
+                self.check_expr_has_type(expr, tcx.types.unit);
+            }
+            StmtKind::Semi(expr) => {
+                self.check_expr(expr);
+            }
+            StmtKind::Break(destination, value) => {
+                let value_ty = match value {
+                    Some(value) => self.check_expr(value),
+                    None => tcx.types.unit,
+                };
+
+                match destination.target_id {
+                    Ok(loop_id) => {
+                        let span = value.map_or(stmt.span, |value| value.span);
+                        let break_ty = self.loop_break_types.borrow().get(&loop_id).copied();
+                        match break_ty {
+                            Some(break_ty) => self.demand_eqtype(span, break_ty, value_ty),
+                            None => {
+                                self.loop_break_types.borrow_mut().insert(loop_id, value_ty);
+                            }
+                        }
+                    }
+                    Err(_) => {
+                        DiagsTypeck::outside_of_loop(self.dcx(), "break", stmt.span).emit();
+                        self.record_error();
+                    }
+                }
+
+                self.diverges.set(Diverges::Always);
+            }
+            StmtKind::Continue(destination) => {
+                if destination.target_id.is_err() {
+                    DiagsTypeck::outside_of_loop(self.dcx(), "continue", stmt.span).emit();
+                    self.record_error();
+                }
+
+                self.diverges.set(Diverges::Always);
+            }
+            StmtKind::Return(value) => {
+                match value {
+                    Some(value) => {
+                        self.check_expr_has_type_with_alias(value, self.return_ty, self.return_alias);
+                    }
+                    None => self.demand_eqtype_with_alias(
+                        stmt.span,
+                        self.return_ty,
+                        tcx.types.unit,
+                        self.return_alias,
+                        None,
+                    ),
+                }
+
+                self.diverges.set(Diverges::Always);
+            }
+            StmtKind::Loop(block, source, _) => {
+                let prev_diverges = self.diverges.get();
+
+                self.breakable_scopes.borrow_mut().push(BreakableScope {
+                    loop_id: stmt.sir_id,
+                    break_ty: None,
+                });
+                let body_ty = self.check_block_with_expected(block, ExpectHasType(tcx.types.unit));
+                self.demand_eqtype(block.span, tcx.types.unit, body_ty);
+                self.breakable_scopes.borrow_mut().pop();
+
+                // `break` を含まない `loop` は決して終了しない
+                let has_break = self.loop_break_types.borrow().contains_key(&stmt.sir_id);
+                self.diverges.set(if source == LoopSource::Loop && !has_break {
+                    Diverges::Always
+                } else {
+                    prev_diverges
+                });
+            }
+        }
+    }
+
+    fn check_let(&self, local: &'tcx sir::LetStmt<'tcx>) {
+        // `let x: _ = ...` のような型のプレースホルダは推論する
+        let annotation = local.ty.map(|ty| {
+            ty_lowering::lower_ty(self.tcx(), ty)
+                .unwrap_or_else(|| self.next_ty_var(ty.span))
+        });
+
+        let ty = match (annotation, local.init) {
+            (Some(ty), Some(init)) => {
+                let alias = local.ty.and_then(ty_lowering::ty_alias_of);
+                self.check_expr_has_type_with_alias(init, ty, alias);
+                ty
+            }
+            (Some(ty), None) => ty,
+            (None, Some(init)) => {
+                let init_ty = self.check_expr(init);
+                if init_ty.is_never() {
+                    self.next_ty_var(local.pat.span)
+                } else {
+                    init_ty
+                }
+            }
+            (None, None) => self.next_ty_var(local.pat.span),
+        };
+
+        self.record_type(local.pat.sir_id, ty);
+        if let Some(decl_ty) = local.ty {
+            self.record_local_alias(local.pat, decl_ty);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! SIR の型 (`sir::Ty`) を、型検査で用いる `Ty` に変換する。
+
+use crate::stelaro_common::DefId;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_metadata;
+use crate::stelaro_sir::{
+    DefKind, Res,
+    sir::{self, FnRetTy, ItemKind, Node, PrimTy},
+};
+use crate::stelaro_sir_typecheck::diagnostics::DiagsTypeck;
+use crate::stelaro_ty::{FnSig, Ty, TyKind};
+
+
+/// 関数のシグネチャを計算する。`fn_sig` クエリの実装。
+///
+/// 結果はクエリに記録されるため、シグネチャに関するエラーは一度だけ報告される。
+/// 外部ステロの関数のシグネチャは、そのステロのメタデータから読む。
+pub(crate) fn fn_sig<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> FnSig<'tcx> {
+    if !def_id.is_local() {
+        return stelaro_metadata::foreign_fn_sig(tcx, def_id);
+    }
+
+    let Some(Node::Item(sir::Item {
+        kind: ItemKind::Fn { sig, .. },
+        ..
+    })) = tcx.sir_get_if_local(def_id)
+    else {
+        panic!("bug: 関数ではない定義のシグネチャが要求された: {def_id:?}");
+    };
+
+    lower_fn_decl(tcx, sig.decl)
+}
+
+/// 型エイリアスを展開した型を計算する。`type_of` クエリの実装。
+///
+/// 別の型エイリアスを参照している場合は、このクエリを要求して展開する。
+/// そのため、`type A = B; type B = A;` のような循環はクエリの循環として検出され、
+/// 型エイリアスの名前を並べた診断 (`DiagsQuery::ty_alias_cycle`) で報告される。
+/// 外部ステロの型エイリアスの型は、そのステロのメタデータから読む。
+pub(crate) fn type_of<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> Ty<'tcx> {
+    if !def_id.is_local() {
+        return stelaro_metadata::foreign_type_of(tcx, def_id);
+    }
+
+    let Some(Node::Item(sir::Item {
+        kind: ItemKind::TyAlias(_, ty),
+        ..
+    })) = tcx.sir_get_if_local(def_id)
+    else {
+        panic!("bug: 型エイリアスではない定義の型が要求された: {def_id:?}");
+    };
+
+    lower_ty(tcx, ty).unwrap_or_else(|| {
+        let guar = DiagsTypeck::placeholder_in_ty_alias(tcx.dcx(), ty.span).emit();
+        tcx.mk_ty(TyKind::Error(guar))
+    })
+}
+
+/// 関数の宣言、または関数ポインタ型の引数と戻り値の型を変換する。
+///
+/// これらの型は推論しないため、型のプレースホルダ `_` はエラーとして報告する。
+fn lower_fn_decl<'tcx>(tcx: TyCtxt<'tcx>, decl: &sir::FnDecl<'tcx>) -> FnSig<'tcx> {
+    let mut lower = |ty: &sir::Ty<'tcx>| {
+        lower_ty(tcx, ty).unwrap_or_else(|| {
+            let guar = DiagsTypeck::placeholder_in_signature(tcx.dcx(), ty.span).emit();
+            tcx.mk_ty(TyKind::Error(guar))
+        })
+    };
+
+    let inputs = decl.inputs.iter().map(&mut lower).collect();
+    let output = match decl.output {
+        FnRetTy::DefaultReturn(_) => tcx.types.unit,
+        FnRetTy::Return(ty) => lower(ty),
+    };
+
+    FnSig { inputs, output }
+}
+
+/// 型が型エイリアスの名前で書かれていれば、その型エイリアスを返す。
+///
+/// `Ty` は展開した型をもつため、診断で型エイリアスの名前を示すために使う。
+pub(crate) fn ty_alias_of(ty: &sir::Ty<'_>) -> Option<DefId> {
+    match ty.kind {
+        sir::TyKind::Path(sir::Path {
+            res: Res::Def(DefKind::TyAlias, def_id),
+            ..
+        }) => Some(def_id),
+        _ => None,
+    }
+}
+
+/// 関数の各引数の型が型エイリアスの名前で書かれていれば、その型エイリアスを返す。
+/// 外部ステロの関数の場合は、型がどのように書かれたかは分からないため空を返す。
+pub(crate) fn fn_input_ty_aliases(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<Option<DefId>> {
+    match tcx.sir_get_if_local(def_id) {
+        Some(Node::Item(sir::Item {
+            kind: ItemKind::Fn { sig, .. },
+            ..
+        })) => sig.decl.inputs.iter().map(ty_alias_of).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 関数の戻り値の型が型エイリアスの名前で書かれていれば、その型エイリアスを返す。
+/// 外部ステロの関数の場合は、`fn_input_ty_aliases` と同様に `None` を返す。
+pub(crate) fn fn_output_ty_alias(tcx: TyCtxt<'_>, def_id: DefId) -> Option<DefId> {
+    match tcx.sir_get_if_local(def_id) {
+        Some(Node::Item(sir::Item {
+            kind: ItemKind::Fn { sig, .. },
+            ..
+        })) => match sig.decl.output {
+            sir::FnRetTy::Return(ty) => ty_alias_of(ty),
+            sir::FnRetTy::DefaultReturn(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// `sir::Ty` を `Ty` に変換する。
+///
+/// 型のプレースホルダ `_` の場合は `None` を返す。
+/// それをどのように扱うかは呼び出し元が決める。
+pub(crate) fn lower_ty<'tcx>(tcx: TyCtxt<'tcx>, ty: &sir::Ty<'tcx>) -> Option<Ty<'tcx>> {
+    let ty = match ty.kind {
+        sir::TyKind::Path(path) => match path.res {
+            Res::PrimTy(prim_ty) => lower_prim_ty(tcx, prim_ty),
+            Res::Def(DefKind::TyAlias, def_id) => tcx.type_of(def_id),
+            Res::Def(DefKind::Mod, _) => {
+                let name = path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                let guar =
+                    DiagsTypeck::expected_type_found_module(tcx.dcx(), &name, path.span).emit();
+                tcx.mk_ty(TyKind::Error(guar))
+            }
+            Res::Err => {
+                let guar = tcx
+                    .dcx()
+                    .has_errors()
+                    .expect("bug: 名前解決のエラーが報告されていない");
+                tcx.mk_ty(TyKind::Error(guar))
+            }
+            Res::Def(DefKind::Fn | DefKind::Closure, _) | Res::Local(_) => {
+                panic!("bug: 型の名前空間で値に解決されたパス: {:?}", path.res)
+            }
+        },
+        sir::TyKind::Unit => tcx.types.unit,
+        sir::TyKind::Never => tcx.types.never,
+        sir::TyKind::Infer => return None,
+        sir::TyKind::FnPtr(decl) => tcx.mk_fn_ptr(&lower_fn_decl(tcx, decl)),
+    };
+
+    Some(ty)
+}
+
+fn lower_prim_ty(tcx: TyCtxt<'_>, prim_ty: PrimTy) -> Ty<'_> {
+    match prim_ty {
+        PrimTy::Bool => tcx.types.bool,
+        PrimTy::Char => tcx.types.char,
+        PrimTy::Int(int_ty) => tcx.mk_ty(TyKind::Int(int_ty)),
+        PrimTy::Uint(uint_ty) => tcx.mk_ty(TyKind::Uint(uint_ty)),
+        PrimTy::Float(float_ty) => tcx.mk_ty(TyKind::Float(float_ty)),
+        PrimTy::Str => tcx.types.str_,
+    }
+}
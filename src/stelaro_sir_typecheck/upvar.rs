@@ -0,0 +1,86 @@
+//! クロージャがキャプチャする変数の解析。
+//!
+//! クロージャの本体 (`sir::Body`) を走査し、`Res::Local` に解決されたパスのうち、
+//! 束縛がクロージャの外側にあるものをキャプチャとして集める。
+//! ネストしたクロージャの本体も走査するため、内側のクロージャがキャプチャする変数は
+//! 外側のクロージャにもキャプチャされる。
+
+use std::collections::HashSet;
+
+use crate::stelaro_ast::ast::CaptureBy;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_sir::{
+    Res,
+    sir::{self, ExprKind, PatKind},
+    sir_id::SirId,
+    visit::{Visitor, nested_filter, walk_expr, walk_pat},
+};
+use crate::stelaro_sir_typecheck::{
+    FnCtxt,
+    result::{CaptureKind, CapturedVar},
+};
+
+impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
+    /// クロージャがキャプチャする変数を求め、型検査の結果に記録する。
+    pub(crate) fn analyze_closure(&self, closure: &'tcx sir::Closure<'tcx>) {
+        let tcx = self.tcx();
+
+        let mut collector = CaptureCollector {
+            tcx,
+            bindings: HashSet::new(),
+            uses: Vec::new(),
+        };
+        collector.visit_body(tcx.sir_body(closure.body));
+
+        let kind = match closure.capture_clause {
+            CaptureBy::Value { .. } => CaptureKind::ByValue,
+            CaptureBy::Ref => CaptureKind::ByRef,
+        };
+
+        let mut seen = HashSet::new();
+        let captures = collector
+            .uses
+            .into_iter()
+            .filter(|&var_id| !collector.bindings.contains(&var_id) && seen.insert(var_id))
+            .map(|var_id| CapturedVar { var_id, kind })
+            .collect();
+
+        self.tccx
+            .results_for(self.owner_id)
+            .record_closure_captures(closure.def_id, captures);
+    }
+}
+
+struct CaptureCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// クロージャの内側で束縛された変数。
+    bindings: HashSet<SirId>,
+    /// パスが参照するローカル変数。現れた順に並ぶ。
+    uses: Vec<SirId>,
+}
+
+impl<'tcx> Visitor<'tcx> for CaptureCollector<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn maybe_tcx(&mut self) -> Self::MaybeTyCtxt {
+        self.tcx
+    }
+
+    fn visit_pat(&mut self, p: &'tcx sir::Pat) {
+        if let PatKind::Binding(binding_id, _) = p.kind {
+            self.bindings.insert(binding_id);
+        }
+        walk_pat(self, p)
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx sir::Expr<'tcx>) {
+        if let ExprKind::Path(sir::Path {
+            res: Res::Local(var_id),
+            ..
+        }) = expr.kind
+        {
+            self.uses.push(var_id);
+        }
+        walk_expr(self, expr)
+    }
+}
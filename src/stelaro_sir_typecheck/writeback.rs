@@ -0,0 +1,59 @@
+//! 型検査の最後に、記録された型から型変数を取り除く。
+
+use std::collections::HashMap;
+
+use crate::stelaro_sir_typecheck::{
+    FnCtxt, diagnostics::DiagsTypeck, infer::TypeVariableOrigin, resolve::fully_resolve,
+};
+use crate::stelaro_ty::{TyKind, ty::TyVid};
+
+impl<'a, 'tcx> FnCtxt<'a, 'tcx> {
+    /// 関数本体で記録されたすべての型を完全に解決する。
+    ///
+    /// 解決できなかった型変数は型注釈が必要であるとして報告され、エラー型に置き換えられる。
+    /// 既にエラーが報告されている場合、それに起因する可能性が高いため報告しない。
+    pub(crate) fn resolve_type_vars_in_body(&self) {
+        let infcx = &self.tccx.infcx;
+        let mut results = self.tccx.results_for(self.owner_id);
+        let report = !results.tainted_by_errors;
+
+        let mut unresolved: HashMap<TyVid, TypeVariableOrigin> = HashMap::new();
+        let mut error_tys = Vec::new();
+
+        for (_, ty) in results.node_types_mut() {
+            match fully_resolve(infcx, *ty) {
+                Ok(resolved) => *ty = resolved,
+                Err(err) => {
+                    let root = infcx.root_var(err.vid);
+                    unresolved.entry(root).or_insert_with(|| infcx.var_origin(root));
+                    error_tys.push(ty);
+                }
+            }
+        }
+
+        if error_tys.is_empty() {
+            return;
+        }
+
+        let guar = if report {
+            let mut origins = unresolved.into_values().collect::<Vec<_>>();
+            origins.sort_by_key(|origin| (origin.span.start, origin.span.end));
+
+            let mut guar = None;
+            for origin in origins {
+                guar = Some(DiagsTypeck::type_annotations_needed(self.dcx(), origin.span).emit());
+            }
+            guar.unwrap()
+        } else {
+            self.dcx()
+                .has_errors()
+                .expect("bug: 型検査のエラーが報告されていない")
+        };
+
+        let error_ty = self.tcx().mk_ty(TyKind::Error(guar));
+        for ty in error_tys {
+            *ty = error_ty;
+        }
+        results.tainted_by_errors = true;
+    }
+}
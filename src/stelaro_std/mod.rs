@@ -0,0 +1,212 @@
+//! 標準ステロ `std`。
+//!
+//! `std` はコンパイラに埋め込まれた `lib.stelo` を、コンパイラ自身がライブラリとして
+//! コンパイルしたものである。`-Z no-std` が指定されない限り、`--extern std=...` と同様に
+//! `CStore` に読み込まれ、その関数は名前解決のプレリュードから直接参照できる。
+//!
+//! `std` の関数はすべて組み込み関数 (`Intrinsic`) であり、本体は実行されない。
+//! 呼び出しは各バックエンドとインタプリタがそれぞれ実装する。
+
+use std::sync::OnceLock;
+use std::thread;
+
+use crate::stelaro_common::{DefId, Symbol};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_interface::{self, Config};
+use crate::stelaro_session::config::{EmitKind, SteloType};
+use crate::stelaro_session::{Input, Options};
+use crate::stelaro_sir::definitions::DefPathData;
+use crate::stelaro_ty::ty::{FloatTy, IntTy, UintTy};
+
+
+/// 標準ステロの名前
+pub const STD_STELO_NAME: &str = "std";
+
+/// 標準ステロのソースコード
+pub const STD_SOURCE: &str = include_str!("lib.stelo");
+
+/// 標準ステロの関数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Intrinsic {
+    /// 文字列の UTF-8 でのバイト数を返す。
+    Len,
+    /// 文字列を標準出力に出力する。
+    Print,
+    /// 文字列と改行を標準出力に出力する。
+    Println,
+    /// 文字列を標準エラー出力に出力する。
+    Eprint,
+    /// 文字列と改行を標準エラー出力に出力する。
+    Eprintln,
+    /// 整数を 10 進数で標準出力に出力する。`i128` と `u128` は C と wasm で扱えないため含まない。
+    PrintInt(IntTy),
+    PrintUint(UintTy),
+    /// 浮動小数点数を小数点以下 6 桁で標準出力に出力する。
+    PrintFloat(FloatTy),
+    /// `true` または `false` を標準出力に出力する。
+    PrintBool,
+    /// 文字を UTF-8 で標準出力に出力する。
+    PrintChar,
+    /// 整数を 10 進数の文字列に書式化する。書式は `PrintInt` と同じ。
+    FormatInt(IntTy),
+    FormatUint(UintTy),
+    /// 浮動小数点数を小数点以下 6 桁の文字列に書式化する。
+    FormatFloat(FloatTy),
+    /// `true` または `false` の文字列を返す。
+    FormatBool,
+    /// 標準出力を書き出し、引数を終了コードとしてプロセスを終了する。
+    Exit,
+    /// 引数が偽であればパニックする。MIR の構築の際に `Assert` に置き換えられる。
+    Assert,
+}
+
+impl Intrinsic {
+    pub const ALL: [Intrinsic; 34] = [
+        Intrinsic::Len,
+        Intrinsic::Print,
+        Intrinsic::Println,
+        Intrinsic::Eprint,
+        Intrinsic::Eprintln,
+        Intrinsic::PrintInt(IntTy::I8),
+        Intrinsic::PrintInt(IntTy::I16),
+        Intrinsic::PrintInt(IntTy::I32),
+        Intrinsic::PrintInt(IntTy::I64),
+        Intrinsic::PrintInt(IntTy::Isize),
+        Intrinsic::PrintUint(UintTy::U8),
+        Intrinsic::PrintUint(UintTy::U16),
+        Intrinsic::PrintUint(UintTy::U32),
+        Intrinsic::PrintUint(UintTy::U64),
+        Intrinsic::PrintUint(UintTy::Usize),
+        Intrinsic::PrintFloat(FloatTy::F32),
+        Intrinsic::PrintFloat(FloatTy::F64),
+        Intrinsic::PrintBool,
+        Intrinsic::PrintChar,
+        Intrinsic::FormatInt(IntTy::I8),
+        Intrinsic::FormatInt(IntTy::I16),
+        Intrinsic::FormatInt(IntTy::I32),
+        Intrinsic::FormatInt(IntTy::I64),
+        Intrinsic::FormatInt(IntTy::Isize),
+        Intrinsic::FormatUint(UintTy::U8),
+        Intrinsic::FormatUint(UintTy::U16),
+        Intrinsic::FormatUint(UintTy::U32),
+        Intrinsic::FormatUint(UintTy::U64),
+        Intrinsic::FormatUint(UintTy::Usize),
+        Intrinsic::FormatFloat(FloatTy::F32),
+        Intrinsic::FormatFloat(FloatTy::F64),
+        Intrinsic::FormatBool,
+        Intrinsic::Exit,
+        Intrinsic::Assert,
+    ];
+
+    /// `std` での関数の名前。wasm でホストから読み込む関数の名前にも使う。
+    pub fn name(self) -> String {
+        match self {
+            Intrinsic::Len => "len".to_string(),
+            Intrinsic::Print => "print".to_string(),
+            Intrinsic::Println => "println".to_string(),
+            Intrinsic::Eprint => "eprint".to_string(),
+            Intrinsic::Eprintln => "eprintln".to_string(),
+            Intrinsic::PrintInt(int_ty) => format!("print_{}", int_ty.name_str()),
+            Intrinsic::PrintUint(uint_ty) => format!("print_{}", uint_ty.name_str()),
+            Intrinsic::PrintFloat(float_ty) => format!("print_{}", float_ty.name_str()),
+            Intrinsic::PrintBool => "print_bool".to_string(),
+            Intrinsic::PrintChar => "print_char".to_string(),
+            Intrinsic::FormatInt(int_ty) => format!("format_{}", int_ty.name_str()),
+            Intrinsic::FormatUint(uint_ty) => format!("format_{}", uint_ty.name_str()),
+            Intrinsic::FormatFloat(float_ty) => format!("format_{}", float_ty.name_str()),
+            Intrinsic::FormatBool => "format_bool".to_string(),
+            Intrinsic::Exit => "exit".to_string(),
+            Intrinsic::Assert => "assert".to_string(),
+        }
+    }
+
+    pub fn from_name(name: Symbol) -> Option<Intrinsic> {
+        Intrinsic::ALL.into_iter().find(|intrinsic| intrinsic.name() == name.as_str())
+    }
+}
+
+/// `def_id` が標準ステロの関数であれば、対応する組み込み関数を返す。
+pub fn intrinsic(tcx: TyCtxt<'_>, def_id: DefId) -> Option<Intrinsic> {
+    if tcx.cstore.std_stelo() != Some(def_id.stelo) {
+        return None;
+    }
+
+    match tcx.def_key(def_id).disambiguated_data.data {
+        DefPathData::ValueNs(name) => Intrinsic::from_name(name),
+        _ => None,
+    }
+}
+
+/// 標準ステロのメタデータ。最初に要求されたときにコンパイルし、プロセス内で共有する。
+pub fn std_metadata() -> &'static [u8] {
+    static METADATA: OnceLock<Vec<u8>> = OnceLock::new();
+
+    METADATA.get_or_init(|| {
+        // セッションは同じスレッドで入れ子にできないため、別のスレッドでコンパイルする
+        thread::spawn(compile_std)
+            .join()
+            .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+    })
+}
+
+fn compile_std() -> Vec<u8> {
+    let mut opts = Options {
+        stelo_name: Some(STD_STELO_NAME.to_string()),
+        stelo_type: SteloType::Lib,
+        emit: vec![EmitKind::Metadata],
+        ..Options::default()
+    };
+    opts.unstable_opts.no_std = true;
+
+    let outcome = stelaro_interface::compile(Config {
+        opts,
+        input: Input::Str {
+            name: "std/lib.stelo".to_string(),
+            input: STD_SOURCE.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    });
+
+    match outcome.metadata {
+        Some(metadata) if outcome.succeeded => metadata,
+        _ => {
+            let messages = outcome
+                .diagnostics
+                .iter()
+                .flat_map(|diag| diag.msg.clone())
+                .collect::<Vec<_>>();
+            panic!("bug: 標準ステロをコンパイルできない: {messages:?}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stelaro_common::create_default_session_globals_then;
+
+    /// `lib.stelo` の関数と `Intrinsic` が一対一に対応する
+    #[test]
+    fn test_std_fns_are_intrinsics() {
+        let source = STD_SOURCE
+            .lines()
+            .filter_map(|line| line.strip_prefix("fn "))
+            .map(|line| line.split('(').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        create_default_session_globals_then(|| {
+            for name in &source {
+                assert!(Intrinsic::from_name(Symbol::intern(name)).is_some(), "{name}");
+            }
+        });
+        assert_eq!(source.len(), Intrinsic::ALL.len());
+    }
+
+    #[test]
+    fn test_std_compiles() {
+        assert!(!std_metadata().is_empty());
+    }
+}
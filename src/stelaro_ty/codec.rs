@@ -0,0 +1,119 @@
+//! 型のバイナリ形式での保存と復元。
+//!
+//! 型は `TyCtxt` にインターンされたセッション固有の値であるため、構造をたどって書き出し、
+//! 関数の定義を指す型は `DefPathHash` で表す。インクリメンタルコンパイルのキャッシュと、
+//! ステロのメタデータの両方で使われる。
+
+use crate::stelaro_common::DefPathHash;
+use crate::stelaro_common::encoder::{Decoder, Encoder};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_ty::ty::{FloatTy, IntTy, UintTy};
+use crate::stelaro_ty::{FnSig, Ty, TyKind};
+
+
+const INT_TYS: [IntTy; 6] = [IntTy::Isize, IntTy::I8, IntTy::I16, IntTy::I32, IntTy::I64, IntTy::I128];
+const UINT_TYS: [UintTy; 6] =
+    [UintTy::Usize, UintTy::U8, UintTy::U16, UintTy::U32, UintTy::U64, UintTy::U128];
+const FLOAT_TYS: [FloatTy; 2] = [FloatTy::F32, FloatTy::F64];
+
+const TAG_BOOL: u8 = 0;
+const TAG_CHAR: u8 = 1;
+const TAG_STR: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_UINT: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_FN_DEF: u8 = 6;
+const TAG_UNIT: u8 = 7;
+const TAG_NEVER: u8 = 8;
+const TAG_FN_PTR: u8 = 9;
+const TAG_CLOSURE: u8 = 10;
+
+/// 型を書き出す。推論変数やエラーなど、保存できない型を含む場合は `None` を返す。
+pub fn encode_ty(tcx: TyCtxt<'_>, e: &mut Encoder, ty: Ty<'_>) -> Option<()> {
+    match *ty.kind() {
+        TyKind::Bool => e.emit_u8(TAG_BOOL),
+        TyKind::Char => e.emit_u8(TAG_CHAR),
+        TyKind::Str => e.emit_u8(TAG_STR),
+        TyKind::Int(int_ty) => {
+            e.emit_u8(TAG_INT);
+            e.emit_u8(index_of(&INT_TYS, int_ty)?);
+        }
+        TyKind::Uint(uint_ty) => {
+            e.emit_u8(TAG_UINT);
+            e.emit_u8(index_of(&UINT_TYS, uint_ty)?);
+        }
+        TyKind::Float(float_ty) => {
+            e.emit_u8(TAG_FLOAT);
+            e.emit_u8(index_of(&FLOAT_TYS, float_ty)?);
+        }
+        TyKind::FnDef(def_id) => {
+            e.emit_u8(TAG_FN_DEF);
+            e.emit_fingerprint(tcx.def_path_hash(def_id).0);
+        }
+        TyKind::FnPtr(sig_tys) => {
+            e.emit_u8(TAG_FN_PTR);
+            encode_fn_sig(tcx, e, &sig_tys.to_fn_sig())?;
+        }
+        TyKind::Closure(def_id, sig_tys) => {
+            e.emit_u8(TAG_CLOSURE);
+            e.emit_fingerprint(tcx.def_path_hash(def_id).0);
+            encode_fn_sig(tcx, e, &sig_tys.to_fn_sig())?;
+        }
+        TyKind::Unit => e.emit_u8(TAG_UNIT),
+        TyKind::Never => e.emit_u8(TAG_NEVER),
+        TyKind::Tuple(_) | TyKind::Infer(_) | TyKind::Error(_) => return None,
+    }
+
+    Some(())
+}
+
+fn index_of<T: PartialEq>(tys: &[T], ty: T) -> Option<u8> {
+    tys.iter().position(|t| *t == ty).map(|i| i as u8)
+}
+
+/// `encode_ty` で書き出した型を読む。
+/// 参照している関数が現在のステロにも読み込んだステロにも存在しない場合などは `None` を返す。
+pub fn decode_ty<'tcx>(tcx: TyCtxt<'tcx>, d: &mut Decoder<'_>) -> Option<Ty<'tcx>> {
+    let kind = match d.read_u8()? {
+        TAG_BOOL => TyKind::Bool,
+        TAG_CHAR => TyKind::Char,
+        TAG_STR => TyKind::Str,
+        TAG_INT => TyKind::Int(*INT_TYS.get(d.read_u8()? as usize)?),
+        TAG_UINT => TyKind::Uint(*UINT_TYS.get(d.read_u8()? as usize)?),
+        TAG_FLOAT => TyKind::Float(*FLOAT_TYS.get(d.read_u8()? as usize)?),
+        TAG_FN_DEF => {
+            let hash = DefPathHash(d.read_fingerprint()?);
+            TyKind::FnDef(tcx.def_path_hash_to_def_id(hash)?)
+        }
+        TAG_FN_PTR => return Some(tcx.mk_fn_ptr(&decode_fn_sig(tcx, d)?)),
+        TAG_CLOSURE => {
+            let hash = DefPathHash(d.read_fingerprint()?);
+            let def_id = tcx.def_path_hash_to_def_id(hash)?;
+            return Some(tcx.mk_closure(def_id, &decode_fn_sig(tcx, d)?));
+        }
+        TAG_UNIT => TyKind::Unit,
+        TAG_NEVER => TyKind::Never,
+        _ => return None,
+    };
+
+    Some(tcx.mk_ty(kind))
+}
+
+/// 関数のシグネチャを書き出す。
+pub fn encode_fn_sig(tcx: TyCtxt<'_>, e: &mut Encoder, fn_sig: &FnSig<'_>) -> Option<()> {
+    e.emit_usize(fn_sig.inputs.len());
+    for &ty in &fn_sig.inputs {
+        encode_ty(tcx, e, ty)?;
+    }
+    encode_ty(tcx, e, fn_sig.output)
+}
+
+/// `encode_fn_sig` で書き出したシグネチャを読む。
+pub fn decode_fn_sig<'tcx>(tcx: TyCtxt<'tcx>, d: &mut Decoder<'_>) -> Option<FnSig<'tcx>> {
+    let len = d.read_usize()?;
+    let inputs = (0..len)
+        .map(|_| decode_ty(tcx, d))
+        .collect::<Option<Vec<_>>>()?;
+    let output = decode_ty(tcx, d)?;
+    Some(FnSig { inputs, output })
+}
@@ -2,7 +2,7 @@ use crate::{
     stelaro_context::TyCtxt,
     stelaro_diagnostics::ErrorEmitted,
     stelaro_ty::{
-        Ty, TyKind,
+        FnSig, Ty, TyKind,
         visit::TypeVisitable,
     },
 };
@@ -83,12 +83,24 @@ impl<'tcx> TypeFoldable<'tcx> for Ty<'tcx> {
 }
 
 impl<'tcx> TypeSuperFoldable<'tcx> for Ty<'tcx> {
-    fn try_super_fold_with<F: FallibleTypeFolder<'tcx>>(self, _folder: &mut F) -> Result<Self, F::Error> {
+    fn try_super_fold_with<F: FallibleTypeFolder<'tcx>>(self, folder: &mut F) -> Result<Self, F::Error> {
         let _ = match *self.kind() {
             // 再帰的にフォールドが必要なバリアント
             TyKind::Tuple(_) => {
                 unimplemented!("Folding for Tuple is not yet implemented");
             }
+            TyKind::FnPtr(sig_tys) => {
+                let sig = sig_tys.to_fn_sig();
+                let inputs = sig.inputs.try_fold_with(folder)?;
+                let output = sig.output.try_fold_with(folder)?;
+                return Ok(folder.tcx().mk_fn_ptr(&FnSig { inputs, output }));
+            }
+            TyKind::Closure(def_id, sig_tys) => {
+                let sig = sig_tys.to_fn_sig();
+                let inputs = sig.inputs.try_fold_with(folder)?;
+                let output = sig.output.try_fold_with(folder)?;
+                return Ok(folder.tcx().mk_closure(def_id, &FnSig { inputs, output }));
+            }
 
             // 末端の型、あるいは内部にフォールドすべき `Ty` を持たない型。
             TyKind::Bool
@@ -110,12 +122,23 @@ impl<'tcx> TypeSuperFoldable<'tcx> for Ty<'tcx> {
         // Ok(tcx.mk_ty(kind))
     }
 
-    fn super_fold_with<F: TypeFolder<'tcx>>(self, _folder: &mut F) -> Self {
-        if let TyKind::Tuple(_) = self.kind() {
-            unimplemented!()
+    fn super_fold_with<F: TypeFolder<'tcx>>(self, folder: &mut F) -> Self {
+        match *self.kind() {
+            TyKind::Tuple(_) => unimplemented!(),
+            TyKind::FnPtr(sig_tys) => {
+                let sig = sig_tys.to_fn_sig();
+                let inputs = sig.inputs.fold_with(folder);
+                let output = sig.output.fold_with(folder);
+                folder.tcx().mk_fn_ptr(&FnSig { inputs, output })
+            }
+            TyKind::Closure(def_id, sig_tys) => {
+                let sig = sig_tys.to_fn_sig();
+                let inputs = sig.inputs.fold_with(folder);
+                let output = sig.output.fold_with(folder);
+                folder.tcx().mk_closure(def_id, &FnSig { inputs, output })
+            }
+            _ => self,
         }
-
-        self
     }
 }
 
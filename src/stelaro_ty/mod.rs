@@ -1,10 +1,12 @@
+pub mod codec;
 pub mod ty;
 pub mod fold;
 pub mod visit;
+pub mod print;
 
 use std::collections::HashMap;
 
-pub use ty::{Ty, TyKind};
+pub use ty::{FnSig, FnSigTys, Ty, TyKind};
 
 use crate::stelaro_ast::NodeId;
 use crate::stelaro_common::{LocalDefId, Span};
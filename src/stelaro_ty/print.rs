@@ -0,0 +1,71 @@
+use std::fmt::Write;
+
+use crate::stelaro_common::DefId;
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_ty::{Ty, TyKind, ty::InferTy};
+
+
+impl<'tcx> TyCtxt<'tcx> {
+    /// 診断や中間表現の出力のために、型を文字列に変換する。
+    pub fn ty_string(self, ty: Ty<'tcx>) -> String {
+        let mut s = String::new();
+        self.write_ty(&mut s, ty);
+        s
+    }
+
+    fn write_ty(self, s: &mut String, ty: Ty<'tcx>) {
+        match *ty.kind() {
+            TyKind::Bool => s.push_str("bool"),
+            TyKind::Char => s.push_str("char"),
+            TyKind::Str => s.push_str("str"),
+            TyKind::Int(int_ty) => s.push_str(int_ty.name_str()),
+            TyKind::Uint(uint_ty) => s.push_str(uint_ty.name_str()),
+            TyKind::Float(float_ty) => s.push_str(float_ty.name_str()),
+            TyKind::FnDef(def_id) => write!(s, "fn {{{}}}", self.def_path_str(def_id)).unwrap(),
+            TyKind::Closure(def_id, _) => write!(s, "closure {{{}}}", self.def_path_str(def_id)).unwrap(),
+            TyKind::FnPtr(sig_tys) => {
+                s.push_str("fn(");
+                for (i, ty) in sig_tys.inputs().iter().enumerate() {
+                    if i != 0 {
+                        s.push_str(", ");
+                    }
+                    self.write_ty(s, *ty);
+                }
+                s.push(')');
+                // 戻り値の型が `()` の場合は、ソースコードと同じく省略する
+                if !sig_tys.output().is_unit() {
+                    s.push_str(": ");
+                    self.write_ty(s, sig_tys.output());
+                }
+            }
+            TyKind::Tuple(tys) => {
+                s.push('(');
+                for (i, ty) in tys.iter().enumerate() {
+                    if i != 0 {
+                        s.push_str(", ");
+                    }
+                    self.write_ty(s, *ty);
+                }
+                s.push(')');
+            }
+            TyKind::Infer(InferTy::TyVar(_)) => s.push('_'),
+            TyKind::Infer(InferTy::IntVar(_)) => s.push_str("{integer}"),
+            TyKind::Infer(InferTy::FloatVar(_)) => s.push_str("{float}"),
+            TyKind::Unit => s.push_str("()"),
+            TyKind::Never => s.push('!'),
+            TyKind::Error(_) => s.push_str("{error}"),
+        }
+    }
+
+    /// `my_mod::f` のような、ステロのルートからの定義のパスを返す。
+    /// 外部ステロの定義には、`other_lib::f` のようにステロの名前を前に付ける。
+    pub fn def_path_str(self, def_id: DefId) -> String {
+        let stelo = (!def_id.is_local()).then(|| self.stelo_name(def_id.stelo).to_string());
+
+        stelo
+            .into_iter()
+            .chain(self.def_path(def_id).data.iter().map(|component| component.to_string()))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
@@ -34,7 +34,11 @@ pub enum TyKind<'tcx> {
     FnDef(DefId),
 
     // 関数ポインタ
-    // FnPtr(...),
+    FnPtr(FnSigTys<'tcx>),
+
+    // クロージャ。各クロージャ式はそれぞれ固有の型をもつ
+    // シグネチャは型推論の途中では推論変数を含みうる
+    Closure(DefId, FnSigTys<'tcx>),
 
     // 型パラメータ
     // Param(ParamTy),
@@ -71,6 +75,51 @@ impl<'tcx> Ty<'tcx> {
     pub fn is_ty_var(&self) -> bool {
         matches!(self.kind(), TyKind::Infer(InferTy::TyVar(_)))
     }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(self.kind(), TyKind::Unit)
+    }
+
+    pub fn is_never(&self) -> bool {
+        matches!(self.kind(), TyKind::Never)
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self.kind(), TyKind::Bool)
+    }
+
+    pub fn is_integral(&self) -> bool {
+        matches!(self.kind(), TyKind::Int(_) | TyKind::Uint(_))
+    }
+
+    pub fn is_floating_point(&self) -> bool {
+        matches!(self.kind(), TyKind::Float(_))
+    }
+
+    pub fn is_str(&self) -> bool {
+        matches!(self.kind(), TyKind::Str)
+    }
+
+    pub fn is_fn_ptr(&self) -> bool {
+        matches!(self.kind(), TyKind::FnPtr(_))
+    }
+
+    pub fn is_closure(&self) -> bool {
+        matches!(self.kind(), TyKind::Closure(..))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integral() || self.is_floating_point()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self.kind(), TyKind::Int(_) | TyKind::Float(_))
+    }
+
+    /// 比較演算子 (`==`, `<` など) を適用できるスカラー型であるかどうか
+    pub fn is_scalar(&self) -> bool {
+        self.is_numeric() || matches!(self.kind(), TyKind::Bool | TyKind::Char)
+    }
 }
 
 impl fmt::Debug for Ty<'_> {
@@ -281,7 +330,7 @@ impl<T: Eq> Eq for WithCachedTypeInfo<T> {}
 
 impl<T: Ord> PartialOrd for WithCachedTypeInfo<T> {
     fn partial_cmp(&self, other: &WithCachedTypeInfo<T>) -> Option<Ordering> {
-        Some(self.internee.cmp(&other.internee))
+        Some(self.cmp(other))
     }
 }
 
@@ -307,3 +356,93 @@ impl<T: std::hash::Hash> std::hash::Hash for WithCachedTypeInfo<T> {
     }
 }
 
+
+impl IntTy {
+    pub fn name_str(&self) -> &'static str {
+        match *self {
+            IntTy::Isize => "isize",
+            IntTy::I8 => "i8",
+            IntTy::I16 => "i16",
+            IntTy::I32 => "i32",
+            IntTy::I64 => "i64",
+            IntTy::I128 => "i128",
+        }
+    }
+
+    /// この型のビット幅 (`isize` は 64 ビットとして扱う)
+    pub fn bit_width(&self) -> u32 {
+        match *self {
+            IntTy::I8 => 8,
+            IntTy::I16 => 16,
+            IntTy::I32 => 32,
+            IntTy::Isize | IntTy::I64 => 64,
+            IntTy::I128 => 128,
+        }
+    }
+}
+
+impl UintTy {
+    pub fn name_str(&self) -> &'static str {
+        match *self {
+            UintTy::Usize => "usize",
+            UintTy::U8 => "u8",
+            UintTy::U16 => "u16",
+            UintTy::U32 => "u32",
+            UintTy::U64 => "u64",
+            UintTy::U128 => "u128",
+        }
+    }
+
+    /// この型のビット幅 (`usize` は 64 ビットとして扱う)
+    pub fn bit_width(&self) -> u32 {
+        match *self {
+            UintTy::U8 => 8,
+            UintTy::U16 => 16,
+            UintTy::U32 => 32,
+            UintTy::Usize | UintTy::U64 => 64,
+            UintTy::U128 => 128,
+        }
+    }
+}
+
+impl FloatTy {
+    pub fn name_str(&self) -> &'static str {
+        match *self {
+            FloatTy::F32 => "f32",
+            FloatTy::F64 => "f64",
+        }
+    }
+}
+
+/// 関数のシグネチャ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnSig<'tcx> {
+    pub inputs: Vec<Ty<'tcx>>,
+    pub output: Ty<'tcx>,
+}
+
+/// 関数ポインタ型の引数と戻り値の型。
+///
+/// 型はインターンされるため、`FnSig` と異なりアリーナに確保したスライスで保持する。
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct FnSigTys<'tcx> {
+    /// 引数の型の後に、戻り値の型を最後の要素として並べたもの。
+    pub inputs_and_output: &'tcx [Ty<'tcx>],
+}
+
+impl<'tcx> FnSigTys<'tcx> {
+    pub fn inputs(&self) -> &'tcx [Ty<'tcx>] {
+        &self.inputs_and_output[..self.inputs_and_output.len() - 1]
+    }
+
+    pub fn output(&self) -> Ty<'tcx> {
+        self.inputs_and_output[self.inputs_and_output.len() - 1]
+    }
+
+    pub fn to_fn_sig(&self) -> FnSig<'tcx> {
+        FnSig {
+            inputs: self.inputs().to_vec(),
+            output: self.output(),
+        }
+    }
+}
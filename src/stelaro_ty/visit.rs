@@ -57,6 +57,10 @@ impl<'tcx> TypeSuperVisitable<'tcx> for Ty<'tcx> {
 
         match self.kind() {
             TyKind::Tuple(_) => unimplemented!(),
+            TyKind::FnPtr(sig_tys) | TyKind::Closure(_, sig_tys) => {
+                walk_visitable_list!(visitor, sig_tys.inputs_and_output.iter());
+                V::Result::output()
+            }
             TyKind::Error(error_emitted) => error_emitted.visit_with(visitor),
 
             TyKind::Bool |
@@ -218,6 +222,12 @@ impl<'tcx> FlagComputation {
             TyKind::Infer(_) => self.add_flags(TypeFlags::HAS_TY_INFER),
             TyKind::Error(_) => self.add_flags(TypeFlags::HAS_ERROR),
 
+            TyKind::FnPtr(sig_tys) | TyKind::Closure(_, sig_tys) => {
+                for ty in sig_tys.inputs_and_output {
+                    self.add_flags(ty.flags());
+                }
+            }
+
             TyKind::Tuple(_) => unreachable!(),
         }
     }
@@ -243,9 +253,7 @@ pub trait TypeVisitableExt<'tcx>: TypeVisitable<'tcx> {
 
 impl<'tcx, T: TypeVisitable<'tcx>> TypeVisitableExt<'tcx> for T {
     fn has_type_flags(&self, flags: TypeFlags) -> bool {
-        let res =
-            self.visit_with(&mut HasTypeFlagsVisitor { flags }) == ControlFlow::Break(FoundFlags);
-        res
+        self.visit_with(&mut HasTypeFlagsVisitor { flags }) == ControlFlow::Break(FoundFlags)
     }
 
     fn error_reported(&self) -> Result<(), ErrorEmitted> {
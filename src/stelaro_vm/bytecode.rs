@@ -0,0 +1,170 @@
+//! スタック型のバイトコードの形式と、その逆アセンブラ (`--emit=bytecode`)。
+
+use std::fmt;
+
+use crate::stelaro_common::IndexVec;
+use crate::stelaro_diagnostics::Lang;
+use crate::stelaro_mir::AssertKind;
+use crate::stelaro_std::Intrinsic;
+
+use super::value::{Slot, Value, ValueTy};
+
+
+stelaro_macros::newtype_index! {
+    /// プログラムの中の関数。
+    #[orderable]
+    #[debug_format = "fn{}"]
+    pub struct FuncId {}
+}
+
+/// 一つの命令。
+///
+/// 命令は被演算子をスタックから取り出し、結果をスタックに積む。
+/// 演算の命令は、被演算子の型をもつ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instr {
+    /// 関数の定数表の値を積む。
+    Const(u32),
+    /// ローカル変数の値を積む。
+    Load(u32),
+    /// 値を取り出し、ローカル変数に格納する。
+    Store(u32),
+
+    Add(ValueTy),
+    Sub(ValueTy),
+    Mul(ValueTy),
+    Div(ValueTy),
+    Rem(ValueTy),
+    Eq(ValueTy),
+    Ne(ValueTy),
+    Lt(ValueTy),
+    Le(ValueTy),
+    Gt(ValueTy),
+    Ge(ValueTy),
+    Neg(ValueTy),
+    Not(ValueTy),
+    /// 加算がオーバーフローするかどうかを `bool` として積む。以下も同様。
+    AddOverflows(ValueTy),
+    SubOverflows(ValueTy),
+    MulOverflows(ValueTy),
+    DivOverflows(ValueTy),
+
+    /// 指定した位置の命令へ移る。
+    Jump(u32),
+    /// `bool` の値を取り出し、真であれば指定した位置へ移る。
+    JumpIfTrue(u32),
+    /// `bool` の値を取り出し、偽であれば指定した位置へ移る。
+    JumpIfFalse(u32),
+
+    /// 呼び出す関数の引数の数だけ値を取り出して関数を呼び出し、戻り値を積む。
+    Call(FuncId),
+    /// 関数ポインタを取り出し、`Call` と同様にその関数を呼び出す。
+    CallIndirect,
+    /// 引数を一つ取り出して標準ステロの関数を呼び出し、戻り値を積む。
+    Intrinsic(Intrinsic),
+    /// 戻り値 (ローカル変数 `0`) を返して関数から戻る。
+    Return,
+    /// `bool` の値を取り出し、期待した値と異なればパニックする。
+    Assert(bool, AssertKind),
+    /// 到達しないはずのコードに到達したことを表す。
+    Unreachable,
+}
+
+/// 一つの関数のバイトコード。
+#[derive(Debug, Clone)]
+pub struct Function {
+    /// 逆アセンブル結果や実行時のエラーのための、関数の定義のパス。
+    pub name: String,
+    /// 引数の数。引数はローカル変数 `1` から順に格納される。
+    pub arg_count: u32,
+    /// 戻り値と引数を含む、ローカル変数の数。
+    pub local_count: u32,
+    pub return_ty: ValueTy,
+    /// `Const` 命令が参照する定数。
+    pub consts: Vec<Slot>,
+    /// 定数の型。逆アセンブル結果にのみ使う。
+    pub const_tys: Vec<ValueTy>,
+    pub code: Vec<Instr>,
+}
+
+/// プログラム全体のバイトコード。
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: IndexVec<FuncId, Function>,
+    /// ルートにある、引数をとらない `main` 関数。
+    pub entry: Option<FuncId>,
+    /// 逆アセンブル結果に含める、パニックの原因の説明の言語。
+    pub lang: Lang,
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (id, function)) in self.functions.iter_enumerated().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write_function(self, id, function, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_function(
+    program: &Program,
+    id: FuncId,
+    function: &Function,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{id:?} {}: args={} locals={} -> {}",
+        function.name, function.arg_count, function.local_count, function.return_ty
+    )?;
+
+    // 命令の位置の桁数をそろえる
+    let width = function.code.len().saturating_sub(1).to_string().len();
+    for (pc, instr) in function.code.iter().enumerate() {
+        write!(f, "    {pc:>width$}: ")?;
+        match *instr {
+            Instr::Const(index) => {
+                let value = function.consts[index as usize];
+                let ty = function.const_tys[index as usize];
+                write!(f, "const.{ty} {}", Value::from_slot(ty, value))?;
+            }
+            Instr::Load(local) => write!(f, "load _{local}")?,
+            Instr::Store(local) => write!(f, "store _{local}")?,
+            Instr::Add(ty) => write!(f, "add.{ty}")?,
+            Instr::Sub(ty) => write!(f, "sub.{ty}")?,
+            Instr::Mul(ty) => write!(f, "mul.{ty}")?,
+            Instr::Div(ty) => write!(f, "div.{ty}")?,
+            Instr::Rem(ty) => write!(f, "rem.{ty}")?,
+            Instr::Eq(ty) => write!(f, "eq.{ty}")?,
+            Instr::Ne(ty) => write!(f, "ne.{ty}")?,
+            Instr::Lt(ty) => write!(f, "lt.{ty}")?,
+            Instr::Le(ty) => write!(f, "le.{ty}")?,
+            Instr::Gt(ty) => write!(f, "gt.{ty}")?,
+            Instr::Ge(ty) => write!(f, "ge.{ty}")?,
+            Instr::Neg(ty) => write!(f, "neg.{ty}")?,
+            Instr::Not(ty) => write!(f, "not.{ty}")?,
+            Instr::AddOverflows(ty) => write!(f, "add_overflows.{ty}")?,
+            Instr::SubOverflows(ty) => write!(f, "sub_overflows.{ty}")?,
+            Instr::MulOverflows(ty) => write!(f, "mul_overflows.{ty}")?,
+            Instr::DivOverflows(ty) => write!(f, "div_overflows.{ty}")?,
+            Instr::Jump(target) => write!(f, "jump {target}")?,
+            Instr::JumpIfTrue(target) => write!(f, "jump_if_true {target}")?,
+            Instr::JumpIfFalse(target) => write!(f, "jump_if_false {target}")?,
+            Instr::Call(callee) => write!(f, "call {callee:?} <{}>", program.functions[callee].name)?,
+            Instr::CallIndirect => write!(f, "call_indirect")?,
+            Instr::Intrinsic(intrinsic) => write!(f, "intrinsic {}", intrinsic.name())?,
+            Instr::Return => write!(f, "return")?,
+            Instr::Assert(expected, kind) => {
+                write!(f, "assert {expected} {:?}", kind.description(program.lang))?;
+            }
+            Instr::Unreachable => write!(f, "unreachable")?,
+        }
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
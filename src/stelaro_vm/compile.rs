@@ -0,0 +1,325 @@
+//! 最適化済みの MIR からバイトコードへの変換。
+
+use std::collections::HashMap;
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::{IndexVec, LocalDefId};
+use crate::stelaro_context::TyCtxt;
+use crate::stelaro_mir::{
+    BasicBlock, BinOp, Body, ConstOperand, ConstValue, Local, Operand, Rvalue, StatementKind,
+    TerminatorKind,
+};
+use crate::stelaro_session::config::SteloType;
+use crate::stelaro_std::intrinsic;
+use crate::stelaro_ty::{Ty, TyKind};
+
+use super::diagnostics::DiagsVm;
+use super::bytecode::{FuncId, Function, Instr, Program};
+use super::value::{Slot, ValueTy};
+
+
+/// すべての関数本体をバイトコードに変換する。関数の番号は `bodies` の順に割り当てる。
+pub fn compile_program<'tcx>(tcx: TyCtxt<'tcx>, bodies: &[Body<'tcx>]) -> Program {
+    let func_ids = bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| (body.def_id, FuncId::from_usize(i)))
+        .collect::<HashMap<_, _>>();
+
+    let functions = bodies
+        .iter()
+        .map(|body| FnCompiler::new(tcx, body, &func_ids).compile())
+        .collect::<IndexVec<FuncId, _>>();
+
+    let entry = bodies
+        .iter()
+        .find(|body| {
+            tcx.sess.opts.stelo_type == SteloType::Bin
+                && tcx.def_path_str(body.def_id.to_def_id()) == "main"
+                && body.arg_count == 0
+        })
+        .map(|body| func_ids[&body.def_id]);
+
+    Program { functions, entry, lang: tcx.sess.opts.lang }
+}
+
+/// 移動先の基本ブロックが決まっていない分岐命令。
+enum Jump {
+    Always(BasicBlock),
+    IfTrue(BasicBlock),
+    IfFalse(BasicBlock),
+}
+
+struct FnCompiler<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    func_ids: &'a HashMap<LocalDefId, FuncId>,
+
+    consts: Vec<Slot>,
+    const_tys: Vec<ValueTy>,
+    code: Vec<Instr>,
+    /// 各基本ブロックの先頭の命令の位置。
+    block_starts: IndexVec<BasicBlock, u32>,
+    /// 基本ブロックの位置が決まった後に書き換える分岐命令。
+    jumps: Vec<(usize, Jump)>,
+}
+
+impl<'a, 'tcx> FnCompiler<'a, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        body: &'a Body<'tcx>,
+        func_ids: &'a HashMap<LocalDefId, FuncId>,
+    ) -> Self {
+        FnCompiler {
+            tcx,
+            body,
+            func_ids,
+            consts: Vec::new(),
+            const_tys: Vec::new(),
+            code: Vec::new(),
+            block_starts: IndexVec::from_elem_n(0, body.basic_blocks.len()),
+            jumps: Vec::new(),
+        }
+    }
+
+    fn compile(mut self) -> Function {
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            self.block_starts[bb] = self.code.len() as u32;
+
+            for statement in &data.statements {
+                match &statement.kind {
+                    StatementKind::Assign(box (place, rvalue)) => {
+                        self.rvalue(rvalue);
+                        self.code.push(Instr::Store(place.local.as_u32()));
+                    }
+                    StatementKind::StorageLive(_) => {}
+                }
+            }
+
+            self.terminator(bb, &data.terminator().kind);
+        }
+
+        for (pc, jump) in std::mem::take(&mut self.jumps) {
+            self.code[pc] = match jump {
+                Jump::Always(bb) => Instr::Jump(self.block_starts[bb]),
+                Jump::IfTrue(bb) => Instr::JumpIfTrue(self.block_starts[bb]),
+                Jump::IfFalse(bb) => Instr::JumpIfFalse(self.block_starts[bb]),
+            };
+        }
+
+        Function {
+            name: self.tcx.def_path_str(self.body.def_id.to_def_id()),
+            arg_count: self.body.arg_count as u32,
+            local_count: self.body.local_decls.len() as u32,
+            return_ty: ValueTy::of(self.body.return_ty()),
+            consts: self.consts,
+            const_tys: self.const_tys,
+            code: self.code,
+        }
+    }
+
+    fn terminator(&mut self, bb: BasicBlock, kind: &TerminatorKind<'tcx>) {
+        match kind {
+            TerminatorKind::Goto { target } => self.goto(bb, *target),
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let ty = ValueTy::of(self.operand_ty(discr));
+
+                if ty == ValueTy::Bool && let [(0, else_)] = targets.values[..] {
+                    self.operand(discr);
+                    self.jump(Jump::IfFalse(else_));
+                } else {
+                    for &(value, target) in &targets.values {
+                        self.operand(discr);
+                        self.push_const(ty, ty.normalize(value));
+                        self.code.push(Instr::Eq(ty));
+                        self.jump(Jump::IfTrue(target));
+                    }
+                }
+                self.goto(bb, targets.otherwise);
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target,
+            } => {
+                let def_id = match *self.operand_ty(func).kind() {
+                    TyKind::FnDef(def_id) => def_id,
+                    TyKind::FnPtr(_) => {
+                        // 関数ポインタを引数の後に積み、呼び出し時に取り出す
+                        for arg in args {
+                            self.operand(arg);
+                        }
+                        self.operand(func);
+                        self.code.push(Instr::CallIndirect);
+                        self.code.push(Instr::Store(destination.local.as_u32()));
+                        self.goto(bb, *target);
+                        return;
+                    }
+                    _ => unreachable!("bug: 関数ではない値の呼び出し: {func:?}"),
+                };
+                if let Some(intrinsic) = intrinsic(self.tcx, def_id) {
+                    for arg in args {
+                        self.operand(arg);
+                    }
+                    self.code.push(Instr::Intrinsic(intrinsic));
+                    self.code.push(Instr::Store(destination.local.as_u32()));
+                    self.goto(bb, *target);
+                    return;
+                }
+                let Some(def_id) = def_id.as_local() else {
+                    // 標準ステロ以外の、本体をもたない外部ステロの関数は実行できない。
+                    // エラーを報告したバイトコードは実行されないため、呼び出しの代わりに何も出力しない
+                    let span = self.body.basic_blocks[bb].terminator().source_info.span;
+                    let name = self.tcx.def_path_str(def_id);
+                    DiagsVm::foreign_call_in_bytecode(self.tcx.dcx(), &name, span).emit();
+                    self.goto(bb, *target);
+                    return;
+                };
+
+                for arg in args {
+                    self.operand(arg);
+                }
+                self.code.push(Instr::Call(self.func_ids[&def_id]));
+                self.code.push(Instr::Store(destination.local.as_u32()));
+                self.goto(bb, *target);
+            }
+            TerminatorKind::Assert {
+                cond,
+                expected,
+                msg,
+                target,
+            } => {
+                self.operand(cond);
+                self.code.push(Instr::Assert(*expected, *msg));
+                self.goto(bb, *target);
+            }
+            TerminatorKind::Return => self.code.push(Instr::Return),
+            TerminatorKind::Unreachable => self.code.push(Instr::Unreachable),
+        }
+    }
+
+    /// `from` の末尾から `target` へ移る。直後のブロックへ移る場合は命令を出力しない。
+    fn goto(&mut self, from: BasicBlock, target: BasicBlock) {
+        if target != from + 1 {
+            self.jump(Jump::Always(target));
+        }
+    }
+
+    fn jump(&mut self, jump: Jump) {
+        self.jumps.push((self.code.len(), jump));
+        // 移動先が決まった後に書き換える
+        self.code.push(Instr::Unreachable);
+    }
+
+    fn rvalue(&mut self, rvalue: &Rvalue<'tcx>) {
+        match rvalue {
+            Rvalue::Use(operand) => self.operand(operand),
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+                let ty = ValueTy::of(self.operand_ty(lhs));
+                self.operand(lhs);
+                self.operand(rhs);
+                self.code.push(match op {
+                    BinOp::Add => Instr::Add(ty),
+                    BinOp::Sub => Instr::Sub(ty),
+                    BinOp::Mul => Instr::Mul(ty),
+                    BinOp::Div => Instr::Div(ty),
+                    BinOp::Rem => Instr::Rem(ty),
+                    BinOp::Eq => Instr::Eq(ty),
+                    BinOp::Ne => Instr::Ne(ty),
+                    BinOp::Lt => Instr::Lt(ty),
+                    BinOp::Le => Instr::Le(ty),
+                    BinOp::Gt => Instr::Gt(ty),
+                    BinOp::Ge => Instr::Ge(ty),
+                    BinOp::AddOverflows => Instr::AddOverflows(ty),
+                    BinOp::SubOverflows => Instr::SubOverflows(ty),
+                    BinOp::MulOverflows => Instr::MulOverflows(ty),
+                    BinOp::DivOverflows => Instr::DivOverflows(ty),
+                });
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = ValueTy::of(self.operand_ty(operand));
+                self.operand(operand);
+                self.code.push(match op {
+                    UnOp::Neg => Instr::Neg(ty),
+                    UnOp::Not => Instr::Not(ty),
+                });
+            }
+        }
+    }
+
+    fn operand(&mut self, operand: &Operand<'tcx>) {
+        match operand {
+            Operand::Copy(place) => self.code.push(Instr::Load(place.local.as_u32())),
+            Operand::Constant(box ConstOperand {
+                span,
+                const_: ConstValue::FnPtr(def_id),
+                ..
+            }) => {
+                let func_id = match def_id.as_local() {
+                    Some(def_id) => self.func_ids[&def_id],
+                    None => {
+                        // 外部ステロの関数は本体をもたないため、番号を割り当てられない。
+                        // エラーを報告したバイトコードは実行されないため、値は何でもよい
+                        let name = self.tcx.def_path_str(*def_id);
+                        DiagsVm::foreign_call_in_bytecode(self.tcx.dcx(), &name, *span).emit();
+                        FuncId::ZERO
+                    }
+                };
+                self.push_const(ValueTy::Fn, func_id.as_usize() as Slot);
+            }
+            Operand::Constant(box ConstOperand { ty, const_, .. }) => {
+                let ty = ValueTy::of(*ty);
+                self.push_const(ty, const_slot(ty, *const_));
+            }
+        }
+    }
+
+    /// 定数を積む命令を出力する。同じ定数は定数表の同じ位置を使う。
+    fn push_const(&mut self, ty: ValueTy, value: Slot) {
+        let index = self
+            .consts
+            .iter()
+            .zip(&self.const_tys)
+            .position(|(&v, &t)| v == value && t == ty)
+            .unwrap_or_else(|| {
+                self.consts.push(value);
+                self.const_tys.push(ty);
+                self.consts.len() - 1
+            });
+        self.code.push(Instr::Const(index as u32));
+    }
+
+    fn local_ty(&self, local: Local) -> Ty<'tcx> {
+        self.body.local_decls[local].ty
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        match operand {
+            Operand::Copy(place) => self.local_ty(place.local),
+            Operand::Constant(constant) => constant.ty,
+        }
+    }
+}
+
+/// 定数を `ty` 型の値としてスロットに格納する。
+pub fn const_slot(ty: ValueTy, const_: ConstValue) -> Slot {
+    match const_ {
+        ConstValue::Int(bits) => ty.normalize(bits),
+        ConstValue::Float(symbol) => match ty {
+            ValueTy::F32 => parse_float::<f32>(symbol.as_str()).to_bits() as Slot,
+            _ => parse_float::<f64>(symbol.as_str()).to_bits() as Slot,
+        },
+        ConstValue::Bool(b) => b as Slot,
+        ConstValue::Char(c) => c as Slot,
+        ConstValue::Str(symbol) => symbol.as_usize() as Slot,
+        ConstValue::ZeroSized => 0,
+        ConstValue::FnPtr(_) => unreachable!("bug: 関数ポインタの値は関数の番号から求める"),
+    }
+}
+
+fn parse_float<T: std::str::FromStr>(text: &str) -> T {
+    text.replace('_', "")
+        .parse()
+        .unwrap_or_else(|_| panic!("bug: 浮動小数点数として解釈できないリテラル: {text}"))
+}
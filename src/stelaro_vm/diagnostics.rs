@@ -0,0 +1,33 @@
+use crate::stelaro_common::Span;
+use crate::stelaro_diagnostics::{Diag, DiagCtxtHandle};
+
+use super::messages;
+
+pub struct DiagsVm;
+
+impl<'dcx> DiagsVm {
+    pub fn foreign_call_in_bytecode(
+        dcx: DiagCtxtHandle<'dcx>,
+        name: &str,
+        span: Span,
+    ) -> Diag<'dcx> {
+        let mut diag = dcx.struct_err(span);
+        diag.set_code(ErrorCode::ForeignCallInBytecode.into());
+        diag.set_message(dcx.translate(messages::FOREIGN_CALL_IN_BYTECODE, &[("name", &name)]));
+        diag.set_label(span, dcx.translate(messages::FOREIGN_CALL_IN_BYTECODE_LABEL, &[]));
+        diag.set_help(dcx.translate(messages::FOREIGN_CALL_IN_BYTECODE_HELP, &[]));
+
+        diag
+    }
+}
+
+#[repr(i32)]
+enum ErrorCode {
+    ForeignCallInBytecode = 800,
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(value: ErrorCode) -> Self {
+        value as i32
+    }
+}
@@ -0,0 +1,281 @@
+//! バイトコードを実行する仮想機械。
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_mir::BinOp;
+
+use super::bytecode::{FuncId, Instr, Program};
+use super::intrinsic::{Output, call_intrinsic};
+use super::value::{Slot, Trap, Value, binary_op, unary_op};
+
+
+/// 関数呼び出しの深さの上限。これを超えると `Trap::StackOverflow` となる。
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// 呼び出し中の関数。
+struct Frame {
+    func: FuncId,
+    /// 次に実行する命令の位置。
+    pc: usize,
+    /// `Vm::locals` の中で、この関数のローカル変数が始まる位置。
+    base: usize,
+}
+
+/// バイトコードの仮想機械。
+pub struct Vm<'a> {
+    program: &'a Program,
+    /// 演算のためのスタック。
+    stack: Vec<Slot>,
+    /// 呼び出し中のすべての関数のローカル変数。
+    locals: Vec<Slot>,
+    frames: Vec<Frame>,
+    /// 標準ステロの関数による出力。
+    output: Output,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Vm {
+            program,
+            stack: Vec::new(),
+            locals: Vec::new(),
+            frames: Vec::new(),
+            output: Output::default(),
+        }
+    }
+
+    /// これまでの実行で、プログラムが標準出力と標準エラー出力に書いた内容。
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// プログラムの `main` 関数を実行する。`main` 関数が無い場合は `None` を返す。
+    pub fn run_main(&mut self) -> Option<Result<Value, Trap>> {
+        let entry = self.program.entry?;
+        Some(self.call(entry, &[]))
+    }
+
+    /// 関数を呼び出し、戻り値を返す。
+    pub fn call(&mut self, func: FuncId, args: &[Slot]) -> Result<Value, Trap> {
+        let function = &self.program.functions[func];
+        assert_eq!(
+            args.len(),
+            function.arg_count as usize,
+            "bug: {} の引数の数が一致しない",
+            function.name
+        );
+
+        self.stack.clear();
+        self.locals.clear();
+        self.frames.clear();
+
+        self.stack.extend_from_slice(args);
+        self.push_frame(func)?;
+        let result = self.execute()?;
+
+        Ok(Value::from_slot(function.return_ty, result))
+    }
+
+    /// 引数をスタックから取り出し、新しいフレームのローカル変数に移す。
+    fn push_frame(&mut self, func: FuncId) -> Result<(), Trap> {
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(Trap::StackOverflow);
+        }
+
+        let function = &self.program.functions[func];
+        let base = self.locals.len();
+        self.locals.resize(base + function.local_count as usize, 0);
+
+        let arg_count = function.arg_count as usize;
+        let args_start = self.stack.len() - arg_count;
+        self.locals[base + 1..base + 1 + arg_count].copy_from_slice(&self.stack[args_start..]);
+        self.stack.truncate(args_start);
+
+        self.frames.push(Frame { func, pc: 0, base });
+        Ok(())
+    }
+
+    /// 最初のフレームが戻るまで命令を実行し、その戻り値を返す。
+    fn execute(&mut self) -> Result<Slot, Trap> {
+        let program = self.program;
+        let mut frame = self.frames.pop().unwrap();
+        let mut code = &program.functions[frame.func].code[..];
+        let mut consts = &program.functions[frame.func].consts[..];
+
+        macro_rules! binary {
+            ($op:expr, $ty:expr) => {{
+                let b = self.stack.pop().unwrap();
+                let a = self.stack.pop().unwrap();
+                self.stack.push(binary_op($op, $ty, a, b)?);
+            }};
+        }
+
+        macro_rules! unary {
+            ($op:expr, $ty:expr) => {{
+                let a = self.stack.pop().unwrap();
+                self.stack.push(unary_op($op, $ty, a));
+            }};
+        }
+
+        loop {
+            let instr = code[frame.pc];
+            frame.pc += 1;
+
+            match instr {
+                Instr::Const(index) => self.stack.push(consts[index as usize]),
+                Instr::Load(local) => self.stack.push(self.locals[frame.base + local as usize]),
+                Instr::Store(local) => {
+                    self.locals[frame.base + local as usize] = self.stack.pop().unwrap();
+                }
+                Instr::Add(ty) => binary!(BinOp::Add, ty),
+                Instr::Sub(ty) => binary!(BinOp::Sub, ty),
+                Instr::Mul(ty) => binary!(BinOp::Mul, ty),
+                Instr::Div(ty) => binary!(BinOp::Div, ty),
+                Instr::Rem(ty) => binary!(BinOp::Rem, ty),
+                Instr::Eq(ty) => binary!(BinOp::Eq, ty),
+                Instr::Ne(ty) => binary!(BinOp::Ne, ty),
+                Instr::Lt(ty) => binary!(BinOp::Lt, ty),
+                Instr::Le(ty) => binary!(BinOp::Le, ty),
+                Instr::Gt(ty) => binary!(BinOp::Gt, ty),
+                Instr::Ge(ty) => binary!(BinOp::Ge, ty),
+                Instr::Neg(ty) => unary!(UnOp::Neg, ty),
+                Instr::Not(ty) => unary!(UnOp::Not, ty),
+                Instr::AddOverflows(ty) => binary!(BinOp::AddOverflows, ty),
+                Instr::SubOverflows(ty) => binary!(BinOp::SubOverflows, ty),
+                Instr::MulOverflows(ty) => binary!(BinOp::MulOverflows, ty),
+                Instr::DivOverflows(ty) => binary!(BinOp::DivOverflows, ty),
+                Instr::Jump(target) => frame.pc = target as usize,
+                Instr::JumpIfTrue(target) => {
+                    if self.stack.pop().unwrap() != 0 {
+                        frame.pc = target as usize;
+                    }
+                }
+                Instr::JumpIfFalse(target) => {
+                    if self.stack.pop().unwrap() == 0 {
+                        frame.pc = target as usize;
+                    }
+                }
+                Instr::Call(callee) => {
+                    self.frames.push(frame);
+                    self.push_frame(callee)?;
+                    frame = self.frames.pop().unwrap();
+                    code = &program.functions[frame.func].code[..];
+                    consts = &program.functions[frame.func].consts[..];
+                }
+                Instr::CallIndirect => {
+                    let callee = FuncId::from_usize(self.stack.pop().unwrap() as usize);
+                    self.frames.push(frame);
+                    self.push_frame(callee)?;
+                    frame = self.frames.pop().unwrap();
+                    code = &program.functions[frame.func].code[..];
+                    consts = &program.functions[frame.func].consts[..];
+                }
+                Instr::Intrinsic(intrinsic) => {
+                    let arg = self.stack.pop().unwrap();
+                    let result = call_intrinsic(intrinsic, arg, &mut self.output)?;
+                    self.stack.push(result);
+                }
+                Instr::Return => {
+                    let result = self.locals[frame.base];
+                    self.locals.truncate(frame.base);
+
+                    let Some(caller) = self.frames.pop() else {
+                        return Ok(result);
+                    };
+                    frame = caller;
+                    code = &program.functions[frame.func].code[..];
+                    consts = &program.functions[frame.func].consts[..];
+                    self.stack.push(result);
+                }
+                Instr::Assert(expected, kind) => {
+                    if (self.stack.pop().unwrap() != 0) != expected {
+                        return Err(Trap::Panic(kind));
+                    }
+                }
+                Instr::Unreachable => return Err(Trap::Unreachable),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stelaro_common::IndexVec;
+    use crate::stelaro_diagnostics::Lang;
+    use crate::stelaro_vm::bytecode::Function;
+    use crate::stelaro_vm::value::ValueTy;
+
+    fn function(arg_count: u32, return_ty: ValueTy, consts: Vec<Slot>, code: Vec<Instr>) -> Function {
+        Function {
+            name: "f".to_string(),
+            arg_count,
+            local_count: arg_count + 1,
+            return_ty,
+            const_tys: vec![return_ty; consts.len()],
+            consts,
+            code,
+        }
+    }
+
+    fn program(functions: Vec<Function>) -> Program {
+        Program {
+            functions: IndexVec::from_raw(functions),
+            entry: None,
+            lang: Lang::default(),
+        }
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic() {
+        // i8: 引数 * 2
+        let program = program(vec![function(
+            1,
+            ValueTy::I8,
+            vec![2],
+            vec![
+                Instr::Load(1),
+                Instr::Const(0),
+                Instr::Mul(ValueTy::I8),
+                Instr::Store(0),
+                Instr::Return,
+            ],
+        )]);
+
+        let arg = ValueTy::I8.normalize(100);
+        let result = Vm::new(&program).call(FuncId::ZERO, &[arg]);
+        assert_eq!(result, Ok(Value::Int(-56)));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let program = program(vec![function(
+            1,
+            ValueTy::U32,
+            vec![7],
+            vec![
+                Instr::Const(0),
+                Instr::Load(1),
+                Instr::Div(ValueTy::U32),
+                Instr::Store(0),
+                Instr::Return,
+            ],
+        )]);
+
+        let result = Vm::new(&program).call(FuncId::ZERO, &[0]);
+        assert_eq!(result, Err(Trap::DivisionByZero));
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        // 自身を呼び出し続ける
+        let program = program(vec![function(
+            0,
+            ValueTy::Unit,
+            vec![],
+            vec![Instr::Call(FuncId::ZERO), Instr::Store(0), Instr::Return],
+        )]);
+
+        let result = Vm::new(&program).call(FuncId::ZERO, &[]);
+        assert_eq!(result, Err(Trap::StackOverflow));
+    }
+}
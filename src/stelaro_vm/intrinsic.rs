@@ -0,0 +1,143 @@
+//! 標準ステロの関数 (`stelaro_std::Intrinsic`) の実装。
+//!
+//! バイトコードの仮想機械と SIR のインタプリタで共有する。
+//! 出力は `Output` に集め、実行結果とともにテストで比べられるようにする。
+//! 書式は C のバックエンドの `printf` と一致させる。
+
+use crate::stelaro_common::Symbol;
+use crate::stelaro_mir::AssertKind;
+use crate::stelaro_std::Intrinsic;
+use crate::stelaro_ty::ty::FloatTy;
+
+use super::value::{Slot, Trap};
+
+
+/// プログラムが標準出力と標準エラー出力に書いた内容。
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 組み込み関数を呼び出し、戻り値を返す。`arg` は関数のシグネチャの型の値。
+pub fn call_intrinsic(intrinsic: Intrinsic, arg: Slot, output: &mut Output) -> Result<Slot, Trap> {
+    match intrinsic {
+        Intrinsic::Len => return Ok(Symbol::new(arg as u32).as_str().len() as Slot),
+        Intrinsic::Print => output.stdout.push_str(Symbol::new(arg as u32).as_str()),
+        Intrinsic::Println => {
+            output.stdout.push_str(Symbol::new(arg as u32).as_str());
+            output.stdout.push('\n');
+        }
+        Intrinsic::Eprint => output.stderr.push_str(Symbol::new(arg as u32).as_str()),
+        Intrinsic::Eprintln => {
+            output.stderr.push_str(Symbol::new(arg as u32).as_str());
+            output.stderr.push('\n');
+        }
+        Intrinsic::PrintInt(_)
+        | Intrinsic::PrintUint(_)
+        | Intrinsic::PrintFloat(_)
+        | Intrinsic::PrintBool => output.stdout.push_str(&format_value(intrinsic, arg)),
+        Intrinsic::PrintChar => {
+            output.stdout.push(char::from_u32(arg as u32).expect("bug: 不正な文字"));
+        }
+        // 文字列はインターンしたシンボルとして表す
+        Intrinsic::FormatInt(_)
+        | Intrinsic::FormatUint(_)
+        | Intrinsic::FormatFloat(_)
+        | Intrinsic::FormatBool => {
+            return Ok(Symbol::intern(&format_value(intrinsic, arg)).as_usize() as Slot);
+        }
+        Intrinsic::Exit => return Err(Trap::Exit(arg as i32)),
+        Intrinsic::Assert => {
+            if arg == 0 {
+                return Err(Trap::Panic(AssertKind::Assertion));
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+/// `print_*` と `format_*` が書式化する値の文字列。出力と文字列の書式は一致させる。
+fn format_value(intrinsic: Intrinsic, arg: Slot) -> String {
+    match intrinsic {
+        // 符号付き整数のスロットは符号拡張されている
+        Intrinsic::PrintInt(_) | Intrinsic::FormatInt(_) => (arg as i128).to_string(),
+        Intrinsic::PrintUint(_) | Intrinsic::FormatUint(_) => arg.to_string(),
+        Intrinsic::PrintFloat(float_ty) | Intrinsic::FormatFloat(float_ty) => {
+            let x = match float_ty {
+                FloatTy::F32 => f32::from_bits(arg as u32) as f64,
+                FloatTy::F64 => f64::from_bits(arg as u64),
+            };
+            format_float(x)
+        }
+        Intrinsic::PrintBool | Intrinsic::FormatBool => {
+            if arg != 0 { "true" } else { "false" }.to_string()
+        }
+        _ => unreachable!("bug: 値を書式化しない組み込み関数: {intrinsic:?}"),
+    }
+}
+
+/// 浮動小数点数を、C の `printf("%.6f", x)` と同じ形式で書式化する。
+fn format_float(x: f64) -> String {
+    if x.is_nan() {
+        "nan".to_string()
+    } else if x.is_infinite() {
+        if x > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        format!("{x:.6}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stelaro_common::create_default_session_globals_then;
+    use crate::stelaro_ty::ty::{IntTy, UintTy};
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(3.25), "3.250000");
+        assert_eq!(format_float(-0.0), "-0.000000");
+        assert_eq!(format_float(1e20), "100000000000000000000.000000");
+        assert_eq!(format_float(f64::NAN), "nan");
+        assert_eq!(format_float(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn test_print_int() {
+        let mut output = Output::default();
+        call_intrinsic(Intrinsic::PrintInt(IntTy::I8), (-5i128) as u128, &mut output).unwrap();
+        call_intrinsic(Intrinsic::PrintChar, 'あ' as u128, &mut output).unwrap();
+        call_intrinsic(Intrinsic::PrintBool, 1, &mut output).unwrap();
+        assert_eq!(output.stdout, "-5あtrue");
+        assert_eq!(call_intrinsic(Intrinsic::Exit, 3, &mut output), Err(Trap::Exit(3)));
+    }
+
+    #[test]
+    fn test_format() {
+        create_default_session_globals_then(|| {
+            let mut output = Output::default();
+            let format = |intrinsic, arg, output: &mut Output| {
+                let symbol = call_intrinsic(intrinsic, arg, output).unwrap();
+                Symbol::new(symbol as u32).as_str().to_string()
+            };
+            let int = format(Intrinsic::FormatInt(IntTy::I32), (-7i128) as u128, &mut output);
+            assert_eq!(int, "-7");
+            let uint = format(Intrinsic::FormatUint(UintTy::U64), u64::MAX as u128, &mut output);
+            assert_eq!(uint, "18446744073709551615");
+            let float = 2.5f64.to_bits() as u128;
+            assert_eq!(format(Intrinsic::FormatFloat(FloatTy::F64), float, &mut output), "2.500000");
+            assert_eq!(format(Intrinsic::FormatBool, 0, &mut output), "false");
+            assert!(output.stdout.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_len() {
+        create_default_session_globals_then(|| {
+            let s = Symbol::intern("あa").as_usize() as Slot;
+            assert_eq!(call_intrinsic(Intrinsic::Len, s, &mut Output::default()), Ok(4));
+        });
+    }
+}
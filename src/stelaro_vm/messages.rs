@@ -0,0 +1,19 @@
+//! バイトコードへの変換で報告される診断のメッセージカタログ
+
+use crate::declare_messages;
+
+
+declare_messages! {
+    FOREIGN_CALL_IN_BYTECODE {
+        ja: "外部ステロの関数 `{name}` はバイトコードから呼び出せません",
+        en: "the function `{name}` from an external stelo cannot be called from bytecode",
+    }
+    FOREIGN_CALL_IN_BYTECODE_LABEL {
+        ja: "ここで呼び出されています",
+        en: "called here",
+    }
+    FOREIGN_CALL_IN_BYTECODE_HELP {
+        ja: "メタデータには関数の本体が含まれないため、仮想機械では実行できません。`--emit=c` や `--emit=asm` を使用してください",
+        en: "metadata does not contain function bodies, so the virtual machine cannot run them; consider using `--emit=c` or `--emit=asm`",
+    }
+}
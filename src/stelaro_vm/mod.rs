@@ -0,0 +1,15 @@
+//! スタック型のバイトコードと、それを実行する仮想機械。
+//!
+//! 最適化済みの MIR を関数ごとのバイトコードに変換し、`Vm` で実行する。
+//! SIR を直接評価する `stelaro_sir_eval` よりも高速に、テストのプログラムを実行できる。
+
+pub mod bytecode;
+mod compile;
+mod diagnostics;
+mod exec;
+pub mod intrinsic;
+pub mod messages;
+pub mod value;
+
+pub use compile::{compile_program, const_slot};
+pub use exec::Vm;
@@ -0,0 +1,293 @@
+//! バイトコードが扱う値と、型ごとの演算。
+//!
+//! 値はすべて 128 ビットのスロットに格納する。符号付き整数は符号拡張し、
+//! 符号なし整数はゼロ拡張した形で保持するため、比較は型の幅に関係なく行える。
+//! 浮動小数点数はビット列として、文字列はシンボルの番号として、
+//! 関数ポインタは関数の番号として格納する。
+
+use std::fmt;
+
+use crate::stelaro_ast::ast::UnOp;
+use crate::stelaro_common::Symbol;
+use crate::stelaro_diagnostics::Lang;
+use crate::stelaro_mir::{AssertKind, BinOp};
+use crate::stelaro_ty::{
+    Ty, TyKind,
+    ty::{FloatTy, IntTy, UintTy},
+};
+
+
+/// 一つの値を格納するスロット。
+pub type Slot = u128;
+
+/// スロットに格納された値の型。演算の命令は、この型によって値を解釈する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueTy {
+    /// `()` や関数アイテムのように、値をもたない型。
+    Unit,
+    Bool,
+    Char,
+    Str,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    /// 関数ポインタ。
+    Fn,
+}
+
+impl ValueTy {
+    /// 型に対応する値の型。`isize` と `usize` は 64 ビットとして扱う。
+    pub fn of(ty: Ty<'_>) -> ValueTy {
+        match *ty.kind() {
+            TyKind::Bool => ValueTy::Bool,
+            TyKind::Char => ValueTy::Char,
+            TyKind::Str => ValueTy::Str,
+            TyKind::Int(int_ty) => match int_ty {
+                IntTy::I8 => ValueTy::I8,
+                IntTy::I16 => ValueTy::I16,
+                IntTy::I32 => ValueTy::I32,
+                IntTy::Isize | IntTy::I64 => ValueTy::I64,
+                IntTy::I128 => ValueTy::I128,
+            },
+            TyKind::Uint(uint_ty) => match uint_ty {
+                UintTy::U8 => ValueTy::U8,
+                UintTy::U16 => ValueTy::U16,
+                UintTy::U32 => ValueTy::U32,
+                UintTy::Usize | UintTy::U64 => ValueTy::U64,
+                UintTy::U128 => ValueTy::U128,
+            },
+            TyKind::Float(FloatTy::F32) => ValueTy::F32,
+            TyKind::Float(FloatTy::F64) => ValueTy::F64,
+            TyKind::FnPtr(_) => ValueTy::Fn,
+            TyKind::FnDef(_) | TyKind::Closure(..) | TyKind::Unit | TyKind::Never | TyKind::Tuple([]) => {
+                ValueTy::Unit
+            }
+            TyKind::Tuple(_) | TyKind::Infer(_) | TyKind::Error(_) => {
+                unreachable!("bug: バイトコードで表せない型: {ty:?}")
+            }
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueTy::Unit => "unit",
+            ValueTy::Bool => "bool",
+            ValueTy::Char => "char",
+            ValueTy::Str => "str",
+            ValueTy::I8 => "i8",
+            ValueTy::I16 => "i16",
+            ValueTy::I32 => "i32",
+            ValueTy::I64 => "i64",
+            ValueTy::I128 => "i128",
+            ValueTy::U8 => "u8",
+            ValueTy::U16 => "u16",
+            ValueTy::U32 => "u32",
+            ValueTy::U64 => "u64",
+            ValueTy::U128 => "u128",
+            ValueTy::F32 => "f32",
+            ValueTy::F64 => "f64",
+            ValueTy::Fn => "fn",
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            ValueTy::I8 | ValueTy::I16 | ValueTy::I32 | ValueTy::I64 | ValueTy::I128
+        )
+    }
+
+    /// 演算の結果を、型の幅に切り詰めて符号拡張またはゼロ拡張する。
+    pub fn normalize(self, bits: Slot) -> Slot {
+        match self {
+            ValueTy::I8 => bits as i8 as i128 as u128,
+            ValueTy::I16 => bits as i16 as i128 as u128,
+            ValueTy::I32 => bits as i32 as i128 as u128,
+            ValueTy::I64 => bits as i64 as i128 as u128,
+            ValueTy::U8 => bits as u8 as u128,
+            ValueTy::U16 => bits as u16 as u128,
+            ValueTy::U32 => bits as u32 as u128,
+            ValueTy::U64 => bits as u64 as u128,
+            _ => bits,
+        }
+    }
+}
+
+impl fmt::Display for ValueTy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 実行を続けられなくなった原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    /// 整数のゼロによる除算、または剰余。
+    DivisionByZero,
+    /// 到達しないはずのコードに到達した。
+    Unreachable,
+    /// 関数呼び出しが深くなりすぎた。
+    StackOverflow,
+    /// 実行時の検査に失敗した。
+    Panic(AssertKind),
+    /// `std::exit` によって、指定した終了コードで終了した。
+    Exit(i32),
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Trap::DivisionByZero => "ゼロによる除算",
+            Trap::Unreachable => "到達しないはずのコードに到達した",
+            Trap::StackOverflow => "関数呼び出しが深くなりすぎた",
+            // 他の原因と同じく、日本語で表示する
+            Trap::Panic(kind) => kind.description(Lang::Ja),
+            Trap::Exit(code) => return write!(f, "終了コード {code} で終了した"),
+        })
+    }
+}
+
+/// 型の情報をもつ値。プログラムの実行結果として使う。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Char(char),
+    Str(Symbol),
+    Int(i128),
+    Uint(u128),
+    Float(f64),
+    /// 関数の番号。
+    Fn(u32),
+}
+
+impl Value {
+    /// `ty` 型の値が格納されたスロットから、値を取り出す。
+    pub fn from_slot(ty: ValueTy, slot: Slot) -> Value {
+        match ty {
+            ValueTy::Unit => Value::Unit,
+            ValueTy::Bool => Value::Bool(slot != 0),
+            ValueTy::Char => Value::Char(char::from_u32(slot as u32).expect("bug: 不正な文字")),
+            ValueTy::Str => Value::Str(Symbol::new(slot as u32)),
+            ValueTy::F32 => Value::Float(f32::from_bits(slot as u32) as f64),
+            ValueTy::F64 => Value::Float(f64::from_bits(slot as u64)),
+            ValueTy::Fn => Value::Fn(slot as u32),
+            _ if ty.is_signed() => Value::Int(slot as i128),
+            _ => Value::Uint(slot),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Char(c) => write!(f, "{c:?}"),
+            Value::Str(symbol) => write!(f, "{:?}", symbol.as_str()),
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Uint(n) => write!(f, "{n}"),
+            Value::Float(x) => write!(f, "{x:?}"),
+            Value::Fn(index) => write!(f, "fn{index}"),
+        }
+    }
+}
+
+/// 二項演算を行う。比較の結果は `bool` の値 (0 または 1) になる。
+///
+/// 整数の四則演算は、型の幅で 2 の補数として折り返す。
+/// `AddOverflows` などのオーバーフローの検査は、折り返す前の結果が型の範囲に収まらないかを求める。
+/// 文字列の `Add` は連結した文字列をインターンする。
+pub fn binary_op(op: BinOp, ty: ValueTy, a: Slot, b: Slot) -> Result<Slot, Trap> {
+    macro_rules! float_op {
+        ($float:ty, $bits:ty) => {{
+            let a = <$float>::from_bits(a as $bits);
+            let b = <$float>::from_bits(b as $bits);
+            let result = match op {
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                BinOp::Div => a / b,
+                BinOp::Rem => a % b,
+                _ => return Ok(compare(op, a, b) as Slot),
+            };
+            result.to_bits() as Slot
+        }};
+    }
+
+    macro_rules! int_op {
+        ($int:ty) => {{
+            let a = a as $int;
+            let b = b as $int;
+            // 128 ビットで計算できないか、型の範囲に収まらなければオーバーフローする
+            let overflows = |result: Option<$int>| {
+                result.is_none_or(|result| ty.normalize(result as Slot) != result as Slot)
+            };
+            let result = match op {
+                BinOp::AddOverflows => return Ok(overflows(a.checked_add(b)) as Slot),
+                BinOp::SubOverflows => return Ok(overflows(a.checked_sub(b)) as Slot),
+                BinOp::MulOverflows => return Ok(overflows(a.checked_mul(b)) as Slot),
+                BinOp::DivOverflows => {
+                    let minus_one = (0 as $int).wrapping_sub(1);
+                    let overflows = ty.is_signed() && b == minus_one && overflows(a.checked_neg());
+                    return Ok(overflows as Slot);
+                }
+                BinOp::Add => a.wrapping_add(b),
+                BinOp::Sub => a.wrapping_sub(b),
+                BinOp::Mul => a.wrapping_mul(b),
+                BinOp::Div | BinOp::Rem if b == 0 => return Err(Trap::DivisionByZero),
+                BinOp::Div => a.wrapping_div(b),
+                BinOp::Rem => a.wrapping_rem(b),
+                _ => return Ok(compare(op, a, b) as Slot),
+            };
+            ty.normalize(result as Slot)
+        }};
+    }
+
+    let result = match ty {
+        // 等しい文字列は同じシンボルにインターンされるため、スロットをそのまま比較できる
+        ValueTy::Str if op == BinOp::Add => {
+            let concat = format!("{}{}", Symbol::new(a as u32), Symbol::new(b as u32));
+            Symbol::intern(&concat).as_usize() as Slot
+        }
+        ValueTy::Str => compare(op, a, b) as Slot,
+        ValueTy::F32 => float_op!(f32, u32),
+        ValueTy::F64 => float_op!(f64, u64),
+        _ if ty.is_signed() => int_op!(i128),
+        _ => int_op!(u128),
+    };
+
+    Ok(result)
+}
+
+fn compare<T: PartialOrd>(op: BinOp, a: T, b: T) -> bool {
+    match op {
+        BinOp::Eq => a == b,
+        BinOp::Ne => a != b,
+        BinOp::Lt => a < b,
+        BinOp::Le => a <= b,
+        BinOp::Gt => a > b,
+        BinOp::Ge => a >= b,
+        _ => unreachable!("bug: 比較ではない演算子: {op:?}"),
+    }
+}
+
+/// 単項演算を行う。`!` は `bool` では論理否定、整数ではビット反転となる。
+pub fn unary_op(op: UnOp, ty: ValueTy, a: Slot) -> Slot {
+    match (op, ty) {
+        (UnOp::Not, ValueTy::Bool) => a ^ 1,
+        (UnOp::Not, _) => ty.normalize(!a),
+        (UnOp::Neg, ValueTy::F32) => (-f32::from_bits(a as u32)).to_bits() as Slot,
+        (UnOp::Neg, ValueTy::F64) => (-f64::from_bits(a as u64)).to_bits() as Slot,
+        (UnOp::Neg, _) => ty.normalize(a.wrapping_neg()),
+    }
+}
@@ -0,0 +1,639 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use insta::assert_snapshot;
+use stelaro::stelaro_codegen::c::codegen_c;
+use stelaro::stelaro_codegen::wasm::codegen_wat;
+use stelaro::stelaro_codegen::x86_64::{codegen_asm, link_executable};
+use stelaro::stelaro_context::TyCtxt;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_mir::{Body, PANIC_EXIT_CODE};
+use stelaro::stelaro_session::Input;
+
+mod common;
+
+use common::{expected_exit_code, expected_stdout, options, read_source, snapshot_name};
+
+fn generate_c(path: &Path) -> String {
+    generate(path, codegen_c)
+}
+
+fn generate_wat(path: &Path) -> String {
+    generate(path, codegen_wat)
+}
+
+fn generate_asm(path: &Path) -> String {
+    generate(path, codegen_asm)
+}
+
+fn generate(path: &Path, codegen: impl for<'tcx> FnOnce(TyCtxt<'tcx>, &[Body<'tcx>]) -> String) -> String {
+    let source_code = read_source(path);
+
+    let config = Config {
+        opts: options(&source_code),
+        input: Input::Str {
+            name: "codegen_tests".to_string(),
+            input: source_code,
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    };
+
+    stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let mut bodies = passes::analysis(tcx, &typeck_results);
+            passes::optimize(tcx, &mut bodies);
+            codegen(tcx, &bodies)
+        })
+    })
+}
+
+/// 実行ファイルを実行し、終了コードと標準出力を確かめる。パニックした場合は、そのメッセージも確かめる。
+fn check_run(exe_file: &Path, expected: i32, path: &Path) {
+    let output = Command::new(exe_file).output().unwrap();
+    assert_eq!(output.status.code(), Some(expected), "終了コードが異なります: {path:?}");
+
+    if let Some(stdout) = expected_stdout(path) {
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), stdout, "標準出力が異なります: {path:?}");
+    }
+
+    if expected == PANIC_EXIT_CODE {
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("でパニックしました"), "パニックのメッセージがありません: {stderr:?}");
+    }
+}
+
+#[test]
+fn test_c_inputs() {
+    insta::glob!("codegen_inputs/*.stelo", |path| {
+        let output = generate_c(path);
+        assert_snapshot!(format!("c_{}", snapshot_name(path)), output);
+    });
+}
+
+/// 生成した C のソースコードをシステムの `cc` でビルドし、実行結果を確かめる。
+/// `cc` が無い環境では何もしない。
+#[test]
+fn test_c_executables() {
+    if Command::new("cc").arg("--version").output().is_err() {
+        eprintln!("`cc` が見つからないため、C のビルドを省略します");
+        return;
+    }
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("codegen_tests");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    insta::glob!("codegen_inputs/*.stelo", |path| {
+        let exe_file = build_c_executable(path, &out_dir);
+
+        if let Some(expected) = expected_exit_code(path) {
+            check_run(&exe_file, expected, path);
+        }
+    });
+}
+
+/// 生成した C のソースコードを `out_dir` でビルドし、実行ファイルのパスを返す。
+fn build_c_executable(path: &Path, out_dir: &Path) -> PathBuf {
+    let name = snapshot_name(path);
+    let c_file = out_dir.join(format!("{name}.c"));
+    let exe_file = out_dir.join(name);
+    fs::write(&c_file, generate_c(path)).unwrap();
+
+    let status = Command::new("cc")
+        .args(["-std=c99", "-pedantic", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&exe_file)
+        .arg(&c_file)
+        .arg("-lm")
+        .status()
+        .unwrap();
+    assert!(status.success(), "C のビルドに失敗しました: {c_file:?}");
+
+    exe_file
+}
+
+#[test]
+fn test_wat_inputs() {
+    insta::glob!("codegen_inputs/*.stelo", |path| {
+        let output = generate_wat(path);
+        validate_wat(&output).unwrap_or_else(|e| panic!("不正な WAT が生成されました {path:?}: {e}"));
+        assert_snapshot!(format!("wat_{}", snapshot_name(path)), output);
+    });
+}
+
+#[test]
+fn test_asm_inputs() {
+    insta::glob!("asm_inputs/*.stelo", |path| {
+        let output = generate_asm(path);
+        assert_snapshot!(format!("asm_{}", snapshot_name(path)), output);
+    });
+}
+
+/// 生成したアセンブリをシステムの `as` と `ld` でリンクし、実行結果を確かめる。
+/// x86-64 の Linux 以外や、`as` と `ld` が無い環境では何もしない。
+#[test]
+fn test_asm_executables() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux"))
+        || Command::new("as").arg("--version").output().is_err()
+        || Command::new("ld").arg("--version").output().is_err()
+    {
+        eprintln!("x86-64 の `as` と `ld` が使えないため、アセンブリのリンクを省略します");
+        return;
+    }
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("asm_tests");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    insta::glob!("asm_inputs/*.stelo", |path| {
+        let exe_file = out_dir.join(snapshot_name(path));
+        link_executable(&generate_asm(path), &[], &exe_file)
+            .unwrap_or_else(|e| panic!("リンクに失敗しました {path:?}: {e:?}"));
+
+        if let Some(expected) = expected_exit_code(path) {
+            check_run(&exe_file, expected, path);
+        }
+    });
+}
+
+/// 標準出力と標準エラー出力を一つのパイプにまとめて実行したときの出力を返す。
+fn run_with_merged_output(exe_file: &Path) -> String {
+    let output = Command::new("sh").arg("-c").arg("\"$0\" 2>&1").arg(exe_file).output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// パニックする前に出力した内容は、C と x86-64 のどちらでもパニックのメッセージより先に書かれる
+#[test]
+fn test_output_order_on_panic_matches_between_c_and_asm() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux"))
+        || Command::new("cc").arg("--version").output().is_err()
+        || Command::new("as").arg("--version").output().is_err()
+        || Command::new("ld").arg("--version").output().is_err()
+    {
+        eprintln!("`cc`、`as`、`ld` が使えないため、C と x86-64 の出力の比較を省略します");
+        return;
+    }
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("differential_tests");
+    fs::create_dir_all(&out_dir).unwrap();
+
+    let c_exe = build_c_executable(Path::new("tests/codegen_inputs/print_panic.stelo"), &out_dir);
+
+    let asm_path = Path::new("tests/asm_inputs/print_panic.stelo");
+    let asm_exe = out_dir.join("print_panic_asm");
+    link_executable(&generate_asm(asm_path), &[], &asm_exe)
+        .unwrap_or_else(|e| panic!("リンクに失敗しました {asm_path:?}: {e:?}"));
+
+    let c_output = run_with_merged_output(&c_exe);
+    let asm_output = run_with_merged_output(&asm_exe);
+    assert!(c_output.starts_with("before panic\n"), "{c_output:?}");
+    assert_eq!(c_output, asm_output);
+}
+
+#[test]
+fn test_validate_wat_rejects_malformed_modules() {
+    let cases = [
+        "(module (func $f (result i32) i32.const 1)",
+        "(module (func $f block $a br $b end))",
+        "(module (func $f loop $a end end))",
+        "(module (func $f (result i32) local.get $x))",
+        "(module (func $f call $g))",
+        "(module (func $f i32.add))",
+        "(module (func $f (result i32) i32.const 1 i32.const 2))",
+        "(module (func $f else))",
+        "(module (export \"main\" (func $main)))",
+        "(module (export \"stelo_heap\" (global $stelo_heap)))",
+        "(module (func $f (param $x i32)) (import \"std\" \"f\" (func $g)))",
+        "(module (func $f (result i32) global.get $g))",
+        "(module (global $g i32 (i32.const 0)) (func $f i32.const 1 global.set $g))",
+        "(module (data (i32.const 0) \"a\"))",
+        "(module (func $f i32.const 0 call_indirect (type $t)))",
+        "(module (type $t (func)) (func $f i32.const 0 call_indirect (type $t)))",
+        "(module (type $t (func (param i32))) (table 1 funcref) (func $f i32.const 0 call_indirect (type $t)))",
+        "(module (table 1 funcref) (elem (i32.const 0) func $g))",
+        "(module (table 1 funcref) (elem (i32.const 0) func $f $f) (func $f))",
+    ];
+
+    for case in cases {
+        assert!(validate_wat(case).is_err(), "{case}");
+    }
+}
+
+/// S 式。
+enum Sexp<'a> {
+    Atom(&'a str),
+    List(Vec<Sexp<'a>>),
+}
+
+fn parse_sexp<'a>(tokens: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>) -> Result<Sexp<'a>, String> {
+    match tokens.next() {
+        Some("(") => {
+            let mut items = Vec::new();
+            loop {
+                match tokens.peek() {
+                    Some(&")") => {
+                        tokens.next();
+                        return Ok(Sexp::List(items));
+                    }
+                    Some(_) => items.push(parse_sexp(tokens)?),
+                    None => return Err("`)` が足りません".to_string()),
+                }
+            }
+        }
+        Some(")") => Err("対応する `(` が無い `)` があります".to_string()),
+        Some(atom) => Ok(Sexp::Atom(atom)),
+        None => Err("入力が空です".to_string()),
+    }
+}
+
+fn tokenize_wat(src: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in src.char_indices() {
+        if c == '(' || c == ')' || c.is_whitespace() {
+            if let Some(s) = start.take() {
+                tokens.push(&src[s..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&src[i..i + 1]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&src[s..]);
+    }
+    tokens
+}
+
+/// 生成された WAT の構造を検証する。
+///
+/// 括弧の対応、`block`, `loop`, `if` と `end` の対応、`br` のラベル、
+/// ローカル変数、グローバル変数と関数の名前の解決、および各命令の被演算子スタックの深さを調べる。
+fn validate_wat(src: &str) -> Result<(), String> {
+    let mut tokens = tokenize_wat(src).into_iter().peekable();
+    let Sexp::List(module) = parse_sexp(&mut tokens)? else {
+        return Err("モジュールが S 式ではありません".to_string());
+    };
+    if tokens.next().is_some() {
+        return Err("モジュールの後に余分な入力があります".to_string());
+    }
+
+    let mut items = module.iter();
+    if !matches!(items.next(), Some(Sexp::Atom("module"))) {
+        return Err("`module` で始まっていません".to_string());
+    }
+
+    // 関数の名前と、引数の数と戻り値の数
+    let mut funcs = std::collections::HashMap::new();
+    // グローバル変数の名前と、変更できるかどうか
+    let mut globals = std::collections::HashMap::new();
+    // 型の名前と、引数の数と戻り値の数
+    let mut types = std::collections::HashMap::new();
+    // 関数テーブルの大きさと、関数テーブルに置く関数
+    let mut table_size = None;
+    let mut elems = Vec::new();
+    let mut has_memory = false;
+    let mut bodies = Vec::new();
+    for item in items {
+        let Sexp::List(item) = item else {
+            return Err("モジュールの要素が S 式ではありません".to_string());
+        };
+        match item.as_slice() {
+            [Sexp::Atom("import"), Sexp::Atom(_), Sexp::Atom(_), Sexp::List(func)] => {
+                // インポートは関数の定義よりも前に置く必要がある
+                if !bodies.is_empty() {
+                    return Err("関数の定義の後にインポートがあります".to_string());
+                }
+                let [Sexp::Atom("func"), Sexp::Atom(name), decls @ ..] = func.as_slice() else {
+                    return Err("関数ではないものをインポートしています".to_string());
+                };
+                let mut params = 0;
+                let mut results = 0;
+                for decl in decls {
+                    match decl {
+                        Sexp::List(decl) => match decl.as_slice() {
+                            [Sexp::Atom("param"), Sexp::Atom(_)] => params += 1,
+                            [Sexp::Atom("result"), Sexp::Atom(_)] => results += 1,
+                            _ => return Err(format!("{name}: 不正な宣言があります")),
+                        },
+                        Sexp::Atom(_) => return Err(format!("{name}: インポートに本体があります")),
+                    }
+                }
+                if funcs.insert(*name, (params, results)).is_some() {
+                    return Err(format!("関数 {name} が重複しています"));
+                }
+            }
+            [Sexp::Atom("func"), Sexp::Atom(name), rest @ ..] => {
+                let mut params = 0;
+                let mut results = 0;
+                let mut locals = std::collections::HashSet::new();
+                let mut instrs = Vec::new();
+                for part in rest {
+                    match part {
+                        Sexp::List(decl) => match decl.as_slice() {
+                            [Sexp::Atom("param"), Sexp::Atom(local), Sexp::Atom(_)] => {
+                                params += 1;
+                                locals.insert(*local);
+                            }
+                            [Sexp::Atom("local"), Sexp::Atom(local), Sexp::Atom(_)] => {
+                                locals.insert(*local);
+                            }
+                            [Sexp::Atom("result"), Sexp::Atom(_)] => results += 1,
+                            // `call_indirect` の型は、命令の引数として扱う
+                            [Sexp::Atom("type"), Sexp::Atom(ty)] if !instrs.is_empty() => {
+                                instrs.push(*ty);
+                            }
+                            _ => return Err(format!("{name}: 不正な宣言があります")),
+                        },
+                        Sexp::Atom(instr) => instrs.push(*instr),
+                    }
+                }
+                if funcs.insert(*name, (params, results)).is_some() {
+                    return Err(format!("関数 {name} が重複しています"));
+                }
+                bodies.push((*name, results, locals, instrs));
+            }
+            [Sexp::Atom("memory"), Sexp::List(export), Sexp::Atom(_)]
+                if matches!(export.as_slice(), [Sexp::Atom("export"), Sexp::Atom(_)]) =>
+            {
+                has_memory = true;
+            }
+            [Sexp::Atom("data"), Sexp::List(offset), Sexp::Atom(_)] => {
+                if !has_memory {
+                    return Err("メモリの宣言の前にデータセグメントがあります".to_string());
+                }
+                if !matches!(offset.as_slice(), [Sexp::Atom("i32.const"), Sexp::Atom(_)]) {
+                    return Err("データセグメントの位置が定数ではありません".to_string());
+                }
+            }
+            [Sexp::Atom("global"), Sexp::Atom(name), ty, Sexp::List(init)] => {
+                let mutable = match ty {
+                    Sexp::List(ty) => matches!(ty.as_slice(), [Sexp::Atom("mut"), Sexp::Atom(_)]),
+                    Sexp::Atom(_) => false,
+                };
+                if !matches!(init.as_slice(), [Sexp::Atom(_), Sexp::Atom(_)]) {
+                    return Err(format!("グローバル変数 {name} の初期値が定数ではありません"));
+                }
+                if globals.insert(*name, mutable).is_some() {
+                    return Err(format!("グローバル変数 {name} が重複しています"));
+                }
+            }
+            [Sexp::Atom("type"), Sexp::Atom(name), Sexp::List(func)] => {
+                let [Sexp::Atom("func"), decls @ ..] = func.as_slice() else {
+                    return Err(format!("型 {name} が関数型ではありません"));
+                };
+                let mut params = 0;
+                let mut results = 0;
+                for decl in decls {
+                    match decl {
+                        Sexp::List(decl) if matches!(decl.as_slice(), [Sexp::Atom("param"), Sexp::Atom(_)]) => params += 1,
+                        Sexp::List(decl) if matches!(decl.as_slice(), [Sexp::Atom("result"), Sexp::Atom(_)]) => results += 1,
+                        _ => return Err(format!("型 {name} に不正な宣言があります")),
+                    }
+                }
+                if types.insert(*name, (params, results)).is_some() {
+                    return Err(format!("型 {name} が重複しています"));
+                }
+            }
+            [Sexp::Atom("table"), Sexp::Atom(size), Sexp::Atom("funcref")] => {
+                let size = size.parse::<usize>().map_err(|_| "関数テーブルの大きさが不正です".to_string())?;
+                if table_size.replace(size).is_some() {
+                    return Err("関数テーブルが重複しています".to_string());
+                }
+            }
+            [Sexp::Atom("elem"), Sexp::List(offset), Sexp::Atom("func"), names @ ..] => {
+                if !matches!(offset.as_slice(), [Sexp::Atom("i32.const"), Sexp::Atom("0")]) {
+                    return Err("要素セグメントの位置が 0 ではありません".to_string());
+                }
+                for name in names {
+                    let Sexp::Atom(name) = name else {
+                        return Err("要素セグメントに関数ではないものがあります".to_string());
+                    };
+                    elems.push(*name);
+                }
+            }
+            [Sexp::Atom("export"), Sexp::Atom(_), Sexp::List(func)] => match func.as_slice() {
+                [Sexp::Atom("func"), Sexp::Atom(name)] if funcs.contains_key(name) => {}
+                [Sexp::Atom("global"), Sexp::Atom(name)] if globals.contains_key(name) => {}
+                _ => return Err("定義されていない関数かグローバル変数をエクスポートしています".to_string()),
+            },
+            _ => return Err("不正なモジュールの要素があります".to_string()),
+        }
+    }
+
+    // 要素セグメントは、後に定義される関数も参照できる
+    if !elems.is_empty() && table_size.is_none_or(|size| size < elems.len()) {
+        return Err("要素セグメントが関数テーブルに収まりません".to_string());
+    }
+    if let Some(name) = elems.iter().find(|name| !funcs.contains_key(*name)) {
+        return Err(format!("要素セグメントの関数 {name} が定義されていません"));
+    }
+
+    let module = Module {
+        funcs: &funcs,
+        globals: &globals,
+        types: &types,
+        has_table: table_size.is_some(),
+        has_memory,
+    };
+    for (name, results, locals, instrs) in bodies {
+        validate_func_body(name, results, &locals, &instrs, &module)?;
+    }
+
+    Ok(())
+}
+
+/// 関数の本体の検査に使う、モジュールの宣言。
+struct Module<'a, 'b> {
+    funcs: &'b std::collections::HashMap<&'a str, (usize, usize)>,
+    globals: &'b std::collections::HashMap<&'a str, bool>,
+    types: &'b std::collections::HashMap<&'a str, (usize, usize)>,
+    has_table: bool,
+    has_memory: bool,
+}
+
+fn validate_func_body(
+    name: &str,
+    results: usize,
+    locals: &std::collections::HashSet<&str>,
+    instrs: &[&str],
+    module: &Module<'_, '_>,
+) -> Result<(), String> {
+    let Module { funcs, globals, types, has_table, has_memory } = *module;
+    struct Frame<'a> {
+        kind: &'a str,
+        label: Option<&'a str>,
+        /// 構造に入った時点のスタックの深さ
+        height: usize,
+        seen_else: bool,
+    }
+
+    let err = |msg: String| Err(format!("{name}: {msg}"));
+    let mut frames: Vec<Frame<'_>> = Vec::new();
+    let mut height = 0;
+    // `br` や `return` の後は、スタックの深さを問わない
+    let mut unreachable = false;
+    let mut iter = instrs.iter().copied().peekable();
+
+    let pop = |height: &mut usize, unreachable: bool, n: usize, instr: &str| {
+        if *height < n {
+            if unreachable {
+                *height = 0;
+                return Ok(());
+            }
+            return Err(format!("{name}: `{instr}` の被演算子が足りません"));
+        }
+        *height -= n;
+        Ok(())
+    };
+
+    while let Some(instr) = iter.next() {
+        let mut operand = || iter.next().ok_or_else(|| format!("{name}: `{instr}` の引数がありません"));
+        match instr {
+            "block" | "loop" | "if" => {
+                if instr == "if" {
+                    pop(&mut height, unreachable, 1, instr)?;
+                }
+                let label = iter.next_if(|next| next.starts_with('$'));
+                frames.push(Frame { kind: instr, label, height, seen_else: false });
+            }
+            "else" => {
+                let Some(frame) = frames.last_mut().filter(|frame| frame.kind == "if" && !frame.seen_else) else {
+                    return err("対応する `if` が無い `else` があります".to_string());
+                };
+                if height != frame.height && !unreachable {
+                    return err("`if` の分岐の終わりにスタックに値が残っています".to_string());
+                }
+                frame.seen_else = true;
+                height = frame.height;
+                unreachable = false;
+            }
+            "end" => {
+                let Some(frame) = frames.pop() else {
+                    return err("対応する構造が無い `end` があります".to_string());
+                };
+                if height != frame.height && !unreachable {
+                    return err(format!("`{}` の終わりにスタックに値が残っています", frame.kind));
+                }
+                height = frame.height;
+                unreachable = false;
+            }
+            "br" => {
+                let label = operand()?;
+                if !frames.iter().any(|frame| frame.label == Some(label)) {
+                    return err(format!("ラベル {label} が見つかりません"));
+                }
+                unreachable = true;
+            }
+            "return" => {
+                pop(&mut height, unreachable, results, instr)?;
+                unreachable = true;
+            }
+            "unreachable" => unreachable = true,
+            "drop" => pop(&mut height, unreachable, 1, instr)?,
+            "select" => {
+                pop(&mut height, unreachable, 3, instr)?;
+                height += 1;
+            }
+            "local.get" | "local.set" => {
+                let local = operand()?;
+                if !locals.contains(local) {
+                    return err(format!("ローカル変数 {local} が宣言されていません"));
+                }
+                if instr == "local.get" {
+                    height += 1;
+                } else {
+                    pop(&mut height, unreachable, 1, instr)?;
+                }
+            }
+            "global.get" | "global.set" => {
+                let global = operand()?;
+                let Some(&mutable) = globals.get(global) else {
+                    return err(format!("グローバル変数 {global} が宣言されていません"));
+                };
+                if instr == "global.get" {
+                    height += 1;
+                } else if mutable {
+                    pop(&mut height, unreachable, 1, instr)?;
+                } else {
+                    return err(format!("変更できないグローバル変数 {global} に代入しています"));
+                }
+            }
+            "memory.size" | "memory.grow" | "memory.copy" => {
+                if !has_memory {
+                    return err(format!("メモリが無いモジュールで `{instr}` を使っています"));
+                }
+                match instr {
+                    "memory.size" => height += 1,
+                    "memory.grow" => {
+                        pop(&mut height, unreachable, 1, instr)?;
+                        height += 1;
+                    }
+                    _ => pop(&mut height, unreachable, 3, instr)?,
+                }
+            }
+            "call" => {
+                let callee = operand()?;
+                let Some(&(params, results)) = funcs.get(callee) else {
+                    return err(format!("関数 {callee} が定義されていません"));
+                };
+                pop(&mut height, unreachable, params, instr)?;
+                height += results;
+            }
+            "call_indirect" => {
+                if !has_table {
+                    return err("関数テーブルが無いモジュールで `call_indirect` を使っています".to_string());
+                }
+                let ty = operand()?;
+                let Some(&(params, results)) = types.get(ty) else {
+                    return err(format!("型 {ty} が宣言されていません"));
+                };
+                // 関数テーブルの位置は引数の後に積まれる
+                pop(&mut height, unreachable, params + 1, instr)?;
+                height += results;
+            }
+            _ => {
+                let Some((ty, op)) = instr.split_once('.') else {
+                    return err(format!("不明な命令 `{instr}` があります"));
+                };
+                if !matches!(ty, "i32" | "i64" | "f32" | "f64") {
+                    return err(format!("不明な値型の命令 `{instr}` があります"));
+                }
+                let arity = match op {
+                    "const" => {
+                        operand()?;
+                        0
+                    }
+                    "eqz" | "neg" | "trunc" | "extend8_s" | "extend16_s" | "extend_i32_s"
+                    | "extend_i32_u" | "wrap_i64" => 1,
+                    "load8_u" if !has_memory => {
+                        return err(format!("メモリが無いモジュールで `{instr}` を使っています"));
+                    }
+                    "load8_u" => 1,
+                    "add" | "sub" | "mul" | "div" | "div_s" | "div_u" | "rem_s" | "rem_u"
+                    | "and" | "or" | "xor" | "shl" | "shr_u" | "eq" | "ne" | "lt" | "lt_s" | "lt_u" | "le" | "le_s"
+                    | "le_u" | "gt" | "gt_s" | "gt_u" | "ge" | "ge_s" | "ge_u" => 2,
+                    _ => return err(format!("不明な命令 `{instr}` があります")),
+                };
+                pop(&mut height, unreachable, arity, instr)?;
+                height += 1;
+            }
+        }
+    }
+
+    if !frames.is_empty() {
+        return err("`end` が足りません".to_string());
+    }
+    if !unreachable && height != results {
+        return err(format!("関数の終わりのスタックの深さが {height} です ({results} を期待)"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! `codegen_inputs` などの入力ファイルを用いる統合テストで共有する関数。
+
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use stelaro::stelaro_session::Options;
+
+pub fn read_source(path: &Path) -> String {
+    fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("テストファイルを読み込むことができませんでした {path:?}: {e}"))
+}
+
+pub fn snapshot_name(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_else(|| {
+            panic!("ファイル名からスナップショット名を生成できませんでした: {path:?}")
+        })
+}
+
+/// 入力ファイルの先頭のコメント `// key: value` から、`key` の値を読む。
+fn header<'a>(source_code: &'a str, key: &str) -> Option<&'a str> {
+    source_code
+        .lines()
+        .map_while(|line| line.strip_prefix("// "))
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(": "))
+        .map(str::trim)
+}
+
+/// 入力ファイルをコンパイルするオプション。
+/// `// overflow-checks: no` があれば、オーバーフローを検査せずにコンパイルする。
+pub fn options(source_code: &str) -> Options {
+    let mut opts = Options::default();
+    if header(source_code, "overflow-checks") == Some("no") {
+        opts.cg_opts.overflow_checks = Some(false);
+    }
+    opts
+}
+
+/// 入力ファイルの先頭の `// exit code: N` から、終了コード (`main` の戻り値) の期待値を読む。
+pub fn expected_exit_code<T: FromStr<Err: Debug>>(path: &Path) -> Option<T> {
+    header(&read_source(path), "exit code").map(|code| code.parse().unwrap())
+}
+
+/// 入力ファイルの先頭の `// stdout: ...` から、標準出力の期待値を読む。`\n` は改行を表す。
+pub fn expected_stdout(path: &Path) -> Option<String> {
+    header(&read_source(path), "stdout").map(|stdout| stdout.replace("\\n", "\n"))
+}
@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::Path;
+
+use insta::assert_snapshot;
+use stelaro::stelaro_ast::dump::{write_ast_tree, write_tokens};
+use stelaro::stelaro_ast::pretty::write_ast_pretty;
+use stelaro::stelaro_common::LineTable;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_lexer::Lexer;
+use stelaro::stelaro_sir::dump::write_sir_tree;
+use stelaro::stelaro_sir::pretty::write_sir_pretty;
+use stelaro::stelaro_sir_typecheck::dump::write_types;
+use stelaro::stelaro_session::{Input, Options};
+
+/// `--emit` の各段階のテキスト出力
+struct Stages {
+    tokens: String,
+    ast: String,
+    ast_pretty: String,
+    sir: String,
+    sir_pretty: String,
+    types: String,
+}
+
+fn config(name: &str, source_code: String) -> Config {
+    Config {
+        opts: Options::default(),
+        input: Input::Str {
+            name: name.to_string(),
+            input: source_code,
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    }
+}
+
+fn emit_stages(source_code: String) -> Stages {
+    stelaro_interface::run_compiler(config("emit_tests", source_code), |sess| {
+        let stelo = passes::parse(sess);
+        let lines = LineTable::new(&sess.source_map().file.borrow());
+
+        let src = sess.source_map().file.borrow().src.clone();
+        let tokens = Lexer::new(&sess.psess, &src).lex().unwrap();
+
+        let mut stages = Stages {
+            tokens: String::new(),
+            ast: String::new(),
+            ast_pretty: String::new(),
+            sir: String::new(),
+            sir_pretty: String::new(),
+            types: String::new(),
+        };
+        write_tokens(&tokens, &lines, &mut stages.tokens).unwrap();
+        write_ast_tree(&stelo, &lines, &mut stages.ast).unwrap();
+        write_ast_pretty(&stelo, &mut stages.ast_pretty).unwrap();
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            write_sir_tree(tcx, &lines, &mut stages.sir).unwrap();
+            write_sir_pretty(tcx, &mut stages.sir_pretty).unwrap();
+
+            let typeck_results = passes::typeck(tcx);
+            write_types(tcx, &typeck_results, &lines, &mut stages.types).unwrap();
+        });
+
+        stages
+    })
+}
+
+fn run_emit_test(path: &Path) {
+    let source_code = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("テストファイルを読み込むことができませんでした {path:?}: {e}"));
+
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_else(|| {
+            panic!("ファイル名からスナップショット名を生成できませんでした: {path:?}")
+        });
+
+    let stages = emit_stages(source_code);
+    assert_snapshot!(format!("tokens_{name}"), stages.tokens);
+    assert_snapshot!(format!("ast_{name}"), stages.ast);
+    assert_snapshot!(format!("ast_pretty_{name}"), stages.ast_pretty);
+    assert_snapshot!(format!("sir_{name}"), stages.sir);
+    assert_snapshot!(format!("sir_pretty_{name}"), stages.sir_pretty);
+    assert_snapshot!(format!("types_{name}"), stages.types);
+
+    // ソースコードの形式の出力は、再びコンパイルしても同じ出力になる。
+    // SIR の出力は名前解決の結果を含むソースコードではない形式のため、AST の出力から比較する
+    let reemitted = emit_stages(stages.ast_pretty.clone());
+    assert_eq!(reemitted.ast_pretty, stages.ast_pretty, "{path:?}");
+    assert_eq!(reemitted.sir_pretty, stages.sir_pretty, "{path:?}");
+}
+
+#[test]
+fn test_emit_inputs() {
+    insta::glob!("mir_inputs/*.stelo", |path| {
+        run_emit_test(path);
+    });
+}
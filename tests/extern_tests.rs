@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use stelaro::stelaro_interface::{self, CompileOutcome, Config};
+use stelaro::stelaro_session::config::{EmitKind, ExternEntry, SteloType};
+use stelaro::stelaro_session::{Input, Options};
+
+const MATHLIB: &str = "\
+type Meters = i64;
+
+fn square(x: i64): i64 {
+    x * x
+}
+
+mod ops {
+    fn add(a: i64, b: i64): i64 {
+        a + b
+    }
+
+    fn is_even(x: i64): bool {
+        x % 2 == 0
+    }
+}
+";
+
+/// テストごとに空のディレクトリを用意する
+fn test_dir(test_name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("extern_tests").join(test_name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config(stelo_name: &str, source_code: &str, opts: Options) -> Config {
+    Config {
+        opts: Options {
+            stelo_name: Some(stelo_name.to_string()),
+            ..opts
+        },
+        input: Input::Str {
+            name: stelo_name.to_string(),
+            input: source_code.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    }
+}
+
+/// ライブラリのステロをコンパイルし、メタデータを `dir` に書き出す
+fn compile_lib(dir: &Path, stelo_name: &str, source_code: &str) -> PathBuf {
+    let opts = Options {
+        stelo_type: SteloType::Lib,
+        emit: vec![EmitKind::Metadata],
+        ..Options::default()
+    };
+    let outcome = stelaro_interface::compile(config(stelo_name, source_code, opts));
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let path = dir.join(format!("{stelo_name}.stmeta"));
+    fs::write(&path, outcome.metadata.unwrap()).unwrap();
+    path
+}
+
+fn compile_with_externs(
+    source_code: &str,
+    externs: &[(&str, &Path)],
+    emit: Vec<EmitKind>,
+) -> CompileOutcome {
+    let externs = externs
+        .iter()
+        .map(|(name, path)| ExternEntry {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        })
+        .collect();
+    let opts = Options {
+        externs,
+        emit,
+        ..Options::default()
+    };
+    stelaro_interface::compile(config("app", source_code, opts))
+}
+
+fn messages(outcome: &CompileOutcome) -> Vec<String> {
+    outcome.diagnostics.iter().flat_map(|diag| diag.msg.clone()).collect()
+}
+
+/// 外部ステロの関数を呼び出すと、その関数のシンボル名で宣言され、呼び出される
+#[test]
+fn test_call_foreign_fn() {
+    let dir = test_dir("call_foreign_fn");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): i64 {\n    mathlib::ops::add(mathlib::square(3), 1)\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::C, EmitKind::Wat],
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let c = &outcome.artifacts[&EmitKind::C];
+    assert!(c.contains("int64_t stelo_7mathlib6square(int64_t);"), "{c}");
+    assert!(c.contains("int64_t stelo_7mathlib3ops3add(int64_t, int64_t);"), "{c}");
+    assert!(c.contains("_1 = stelo_7mathlib6square("), "{c}");
+
+    let wat = &outcome.artifacts[&EmitKind::Wat];
+    assert!(wat.contains("(import \"mathlib\" \"stelo_7mathlib6square\""), "{wat}");
+}
+
+/// ライブラリのステロのシンボル名はステロの名前を含み、エントリポイントをもたない
+#[test]
+fn test_lib_symbols() {
+    let opts = Options {
+        stelo_type: SteloType::Lib,
+        emit: vec![EmitKind::C, EmitKind::Asm],
+        ..Options::default()
+    };
+    let outcome = stelaro_interface::compile(config(
+        "mathlib",
+        "fn main(): i64 {\n    0\n}\n",
+        opts,
+    ));
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let c = &outcome.artifacts[&EmitKind::C];
+    assert!(c.contains("int64_t stelo_7mathlib4main(void) {"), "{c}");
+    assert!(!c.contains("int main(void)"), "{c}");
+
+    let asm = &outcome.artifacts[&EmitKind::Asm];
+    assert!(asm.contains(".globl stelo_7mathlib4main"), "{asm}");
+    assert!(!asm.contains("_start"), "{asm}");
+}
+
+/// 外部ステロの関数の呼び出しも、メタデータのシグネチャで型検査される
+#[test]
+fn test_foreign_fn_sig_is_checked() {
+    let dir = test_dir("foreign_fn_sig_is_checked");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): i64 {\n    mathlib::square(mathlib::ops::is_even(2))\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::Types],
+    );
+    assert!(!outcome.succeeded);
+    assert!(outcome.diagnostics.iter().any(|diag| diag.code == Some(400)), "{:?}", messages(&outcome));
+}
+
+/// 外部ステロの型エイリアスはメタデータに記録した型に展開され、型の出力と型の不一致ではその名前で示される
+#[test]
+fn test_foreign_ty_alias() {
+    let dir = test_dir("foreign_ty_alias");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): mathlib::Meters {\n    mathlib::square(3)\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::Types],
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+    assert!(outcome.artifacts[&EmitKind::Types].starts_with("fn main(): mathlib::Meters"));
+
+    let outcome = compile_with_externs(
+        "fn main() {\n    let m: mathlib::Meters = true;\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::Types],
+    );
+    assert!(!outcome.succeeded);
+    let mismatch = outcome.diagnostics.iter().find(|diag| diag.code == Some(400));
+    assert!(
+        mismatch.is_some_and(|diag| diag.label[0].1.contains("`mathlib::Meters`")),
+        "{:?}",
+        messages(&outcome)
+    );
+}
+
+/// 外部ステロに存在しない名前は解決できない
+#[test]
+fn test_unresolved_foreign_path() {
+    let dir = test_dir("unresolved_foreign_path");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): i64 {\n    mathlib::cube(2)\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::Sir],
+    );
+    assert!(!outcome.succeeded);
+    assert!(outcome.diagnostics.iter().any(|diag| diag.code == Some(302)), "{:?}", messages(&outcome));
+}
+
+/// 読み込めないメタデータは、コンパイルを始める前にエラーとして報告される
+#[test]
+fn test_invalid_metadata() {
+    let dir = test_dir("invalid_metadata");
+    let missing = dir.join("missing.stmeta");
+    let invalid = dir.join("invalid.stmeta");
+    fs::write(&invalid, b"not metadata").unwrap();
+
+    for (path, code) in [(&missing, 1000), (&invalid, 1001)] {
+        let outcome = compile_with_externs(
+            "fn main(): i64 {\n    0\n}\n",
+            &[("mathlib", path)],
+            vec![EmitKind::Sir],
+        );
+        assert!(!outcome.succeeded);
+        assert!(!outcome.artifacts.contains_key(&EmitKind::Sir));
+        assert_eq!(outcome.diagnostics.len(), 1, "{:?}", messages(&outcome));
+        assert_eq!(outcome.diagnostics[0].code, Some(code));
+        // 診断の位置がないため、どの引数によるものかをメッセージに含める
+        let flag = format!("--extern mathlib={}", path.display());
+        assert!(outcome.diagnostics[0].msg.concat().contains(&flag), "{:?}", messages(&outcome));
+    }
+}
+
+/// 同じステロを異なる名前で読み込むと、`DefPathHash` が衝突するためエラーとなる
+#[test]
+fn test_duplicate_stelo() {
+    let dir = test_dir("duplicate_stelo");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): i64 {\n    0\n}\n",
+        &[("m", &mathlib), ("k", &mathlib)],
+        vec![EmitKind::Sir],
+    );
+    assert!(!outcome.succeeded);
+    assert_eq!(outcome.diagnostics.len(), 1, "{:?}", messages(&outcome));
+    assert_eq!(outcome.diagnostics[0].code, Some(1003));
+}
+
+/// 外部ステロの関数の本体はバイトコードに変換できない
+#[test]
+fn test_foreign_call_in_bytecode() {
+    let dir = test_dir("foreign_call_in_bytecode");
+    let mathlib = compile_lib(&dir, "mathlib", MATHLIB);
+
+    let outcome = compile_with_externs(
+        "fn main(): i64 {\n    mathlib::square(3)\n}\n",
+        &[("mathlib", &mathlib)],
+        vec![EmitKind::Bytecode],
+    );
+    assert!(!outcome.succeeded);
+    assert!(outcome.diagnostics.iter().any(|diag| diag.code == Some(800)), "{:?}", messages(&outcome));
+}
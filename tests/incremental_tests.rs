@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use stelaro::stelaro_codegen::c::codegen_c;
+use stelaro::stelaro_incremental::IncrementalStats;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_session::config::EmitKind;
+use stelaro::stelaro_session::{Input, Options};
+
+const SOURCE: &str = "\
+fn square(x: i32): i32 {
+    x * x
+}
+
+fn sum_of_squares(a: i32, b: i32): i32 {
+    square(a) + square(b)
+}
+
+fn negate(b: bool): bool {
+    !b
+}
+
+fn main(): i32 {
+    sum_of_squares(3, 4)
+}
+";
+
+/// テストごとに空のキャッシュのディレクトリを用意する
+fn cache_dir(test_name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("incremental_tests").join(test_name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn config(source_code: &str, incremental_dir: Option<&Path>) -> Config {
+    let mut opts = Options::default();
+    if let Some(dir) = incremental_dir {
+        opts.cg_opts.incremental = true;
+        opts.cg_opts.incremental_dir = Some(dir.to_path_buf());
+    }
+
+    Config {
+        opts,
+        input: Input::Str {
+            name: "incremental_tests".to_string(),
+            input: source_code.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    }
+}
+
+/// C のコードを生成し、インクリメンタルコンパイルの統計とともに返す
+fn compile(config: Config) -> (String, Option<IncrementalStats>) {
+    stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let mut bodies = passes::analysis(tcx, &typeck_results);
+            passes::optimize(tcx, &mut bodies);
+            let c = codegen_c(tcx, &bodies);
+
+            (c, tcx.incremental.borrow().as_ref().map(|cache| cache.stats()))
+        })
+    })
+}
+
+fn compile_incrementally(source_code: &str, dir: &Path) -> (String, IncrementalStats) {
+    let (c, stats) = compile(config(source_code, Some(dir)));
+    (c, stats.unwrap())
+}
+
+/// インクリメンタルでないコンパイルと同じ出力になることを確かめる
+fn assert_same_as_clean_build(source_code: &str, c: &str) {
+    let (clean, stats) = compile(config(source_code, None));
+    assert!(stats.is_none());
+    assert_eq!(c, clean);
+}
+
+#[test]
+fn test_unchanged_source_reuses_everything() {
+    let dir = cache_dir("unchanged");
+
+    let (first, stats) = compile_incrementally(SOURCE, &dir);
+    assert_eq!(stats, IncrementalStats {
+        items: 4,
+        typeck_checked: 4,
+        codegen_generated: 4,
+        ..IncrementalStats::default()
+    });
+
+    let (second, stats) = compile_incrementally(SOURCE, &dir);
+    assert_eq!(stats, IncrementalStats {
+        items: 4,
+        ast_unchanged: 4,
+        sir_unchanged: 4,
+        typeck_reused: 4,
+        codegen_reused: 4,
+        ..IncrementalStats::default()
+    });
+    assert_eq!(first, second);
+    assert_same_as_clean_build(SOURCE, &second);
+}
+
+/// 本体だけを変更した場合は、その関数だけを検査し直す
+#[test]
+fn test_body_change_rechecks_only_that_function() {
+    let dir = cache_dir("body_change");
+    compile_incrementally(SOURCE, &dir);
+
+    // 行数と列を変えないよう、同じ長さの式に置き換える
+    let edited = SOURCE.replace("    x * x\n", "    x + x\n");
+    let (c, stats) = compile_incrementally(&edited, &dir);
+
+    assert_eq!(stats.sir_unchanged, 3);
+    assert_eq!(stats.typeck_checked, 1);
+    assert_eq!(stats.typeck_reused, 3);
+    assert_eq!(stats.codegen_generated, 1);
+    assert_eq!(stats.codegen_reused, 3);
+    assert_same_as_clean_build(&edited, &c);
+}
+
+/// シグネチャを変更した場合は、その関数を呼び出している関数も検査し直す
+#[test]
+fn test_signature_change_rechecks_callers() {
+    let dir = cache_dir("signature_change");
+    compile_incrementally(SOURCE, &dir);
+
+    let edited = SOURCE
+        .replace("fn square(x: i32): i32", "fn square(x: i64): i64")
+        .replace("fn sum_of_squares(a: i32, b: i32): i32", "fn sum_of_squares(a: i64, b: i64): i64")
+        .replace("fn main(): i32", "fn main(): i64");
+    let (c, stats) = compile_incrementally(&edited, &dir);
+
+    // `main` の本体は変わっていないが、呼び出している関数のシグネチャが変わった
+    assert_eq!(stats.sir_unchanged, 1);
+    assert_eq!(stats.typeck_checked, 4 - 1);
+    assert_eq!(stats.typeck_reused, 1);
+    assert_same_as_clean_build(&edited, &c);
+}
+
+/// 関数の位置だけが変わった場合は、型検査の結果は再利用するが、
+/// パニックのメッセージに位置が含まれるため、コードは生成し直す
+#[test]
+fn test_moved_functions_regenerate_code() {
+    let dir = cache_dir("moved");
+    compile_incrementally(SOURCE, &dir);
+
+    let edited = format!("\n{SOURCE}");
+    let (c, stats) = compile_incrementally(&edited, &dir);
+
+    assert_eq!(stats.ast_unchanged, 4);
+    assert_eq!(stats.sir_unchanged, 4);
+    assert_eq!(stats.typeck_reused, 4);
+    assert_eq!(stats.codegen_generated, 4);
+    assert_same_as_clean_build(&edited, &c);
+}
+
+/// 異なるオプションで保存されたキャッシュは使わない
+#[test]
+fn test_different_options_invalidate_cache() {
+    let dir = cache_dir("options");
+    compile_incrementally(SOURCE, &dir);
+
+    let mut config = config(SOURCE, Some(&dir));
+    config.opts.cg_opts.overflow_checks = Some(false);
+    let (_, stats) = compile(config);
+
+    assert_eq!(stats.unwrap().typeck_reused, 0);
+}
+
+/// エラーのあるコンパイルはキャッシュを上書きしない
+#[test]
+fn test_failed_compilation_keeps_cache() {
+    let dir = cache_dir("failed");
+    compile_incrementally(SOURCE, &dir);
+
+    let broken = SOURCE.replace("!b", "b + 1");
+    let mut config = config(&broken, Some(&dir));
+    config.opts.emit = vec![EmitKind::Types];
+    let outcome = stelaro_interface::compile(config);
+    assert!(!outcome.succeeded);
+
+    let (_, stats) = compile_incrementally(SOURCE, &dir);
+    assert_eq!(stats.typeck_reused, 4);
+}
+
+/// 型エイリアスの展開先を変更した場合は、それを使っている関数も検査し直す
+#[test]
+fn test_ty_alias_change_rechecks_users() {
+    let dir = cache_dir("ty_alias");
+    let source = SOURCE.replace("fn square(x: i32): i32", "fn square(x: Num): Num");
+    let source = format!("type Num = i32;\n{source}");
+    compile_incrementally(&source, &dir);
+
+    let edited = source
+        .replace("type Num = i32;", "type Num = i64;")
+        .replace("fn sum_of_squares(a: i32, b: i32): i32", "fn sum_of_squares(a: i64, b: i64): i64")
+        .replace("fn main(): i32", "fn main(): i64");
+    let (c, stats) = compile_incrementally(&edited, &dir);
+
+    // `square` の SIR は変わっていないが、エイリアスの展開先が変わった
+    assert_eq!(stats.typeck_reused, 1);
+    assert_same_as_clean_build(&edited, &c);
+}
@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+
+use stelaro::stelaro_common::InMemoryFileLoader;
+use stelaro::stelaro_diagnostics::{Lang, Level};
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_mir::pretty::write_mir_pretty;
+use stelaro::stelaro_session::config::EmitKind;
+use stelaro::stelaro_session::{Input, Options};
+
+/// ディスク上に存在しないパスを入力として、`InMemoryFileLoader` からコンパイルできる
+#[test]
+fn test_compile_with_in_memory_file_loader() {
+    let path = PathBuf::from("virtual/does_not_exist/main.stelo");
+    assert!(!path.exists());
+
+    let loader = InMemoryFileLoader::from_iter([(
+        path.clone(),
+        "fn double(x: i32): i32 {\n    x * 2\n}\n",
+    )]);
+
+    let config = Config {
+        opts: Options::default(),
+        input: Input::File(path.clone()),
+        output_dir: None,
+        output_file: None,
+        file_loader: Some(Box::new(loader)),
+        emitter: None,
+    };
+
+    let (file_name, mir) = stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+        let file_name = sess.source_map().file.borrow().name.clone();
+
+        let mir = passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let bodies = passes::analysis(tcx, &typeck_results);
+
+            let mut output = String::new();
+            write_mir_pretty(tcx, &bodies, &mut output).unwrap();
+            output
+        });
+
+        (file_name, mir)
+    });
+
+    assert_eq!(file_name, Path::new("virtual/does_not_exist/main.stelo"));
+    assert!(mir.starts_with("fn double(_1: i32) -> i32 {"), "{mir}");
+}
+
+fn str_config(source_code: &str, emit: Vec<EmitKind>) -> Config {
+    Config {
+        opts: Options {
+            emit,
+            ..Options::default()
+        },
+        input: Input::Str {
+            name: "interface_tests".to_string(),
+            input: source_code.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    }
+}
+
+/// 要求されたすべての段階の出力が返される
+#[test]
+fn test_compile_returns_artifacts() {
+    let outcome = stelaro_interface::compile(str_config(
+        "fn double(x: i32): i32 {\n    x * 2\n}\n",
+        vec![EmitKind::Ast, EmitKind::Types, EmitKind::Mir],
+    ));
+
+    assert!(outcome.succeeded);
+    assert!(outcome.diagnostics.is_empty());
+    assert_eq!(
+        outcome.artifacts.keys().copied().collect::<Vec<_>>(),
+        [EmitKind::Ast, EmitKind::Types, EmitKind::Mir]
+    );
+    assert!(outcome.artifacts[&EmitKind::Types].starts_with("fn double(i32): i32"));
+    assert!(outcome.artifacts[&EmitKind::Mir].starts_with("fn double(_1: i32) -> i32 {"));
+    assert_eq!(outcome.source.unwrap().name, Path::new("interface_tests"));
+}
+
+/// `--emit=types` では、型エイリアスの名前で書かれた型はその名前で出力される
+#[test]
+fn test_types_artifact_shows_ty_alias_names() {
+    let outcome = stelaro_interface::compile(str_config(
+        "type Meters = i64;\nfn walk(m: Meters): Meters {\n    let d: Meters = m;\n    d + 1\n}\n",
+        vec![EmitKind::Types],
+    ));
+
+    assert!(outcome.succeeded);
+    let types = &outcome.artifacts[&EmitKind::Types];
+    assert!(types.starts_with("fn walk(Meters): Meters\n"), "{types}");
+    assert!(types.contains("pat   `d`: Meters\n"), "{types}");
+    assert!(types.contains("expr  `d`: Meters\n"), "{types}");
+    assert!(types.contains("expr  `d + 1`: i64\n"), "{types}");
+}
+
+/// 型エラーは診断として返され、プロセスは終了しない
+#[test]
+fn test_compile_collects_errors() {
+    let outcome = stelaro_interface::compile(str_config(
+        "fn main(): i32 {\n    let x: bool = 1;\n    0\n}\n",
+        vec![EmitKind::Sir, EmitKind::Mir],
+    ));
+
+    assert!(!outcome.succeeded);
+    assert!(!outcome.diagnostics.is_empty());
+    assert!(outcome.diagnostics.iter().any(|diag| diag.level == Level::Error));
+    // 型検査の前の段階の出力は得られる
+    assert!(outcome.artifacts.contains_key(&EmitKind::Sir));
+    assert!(!outcome.artifacts.contains_key(&EmitKind::Mir));
+}
+
+/// 入力ファイルが存在しない場合は致命的なエラーとして返される
+#[test]
+fn test_compile_missing_file() {
+    let config = Config {
+        input: Input::File(PathBuf::from("virtual/missing.stelo")),
+        file_loader: Some(Box::new(InMemoryFileLoader::new())),
+        ..str_config("", vec![EmitKind::Mir])
+    };
+    let outcome = stelaro_interface::compile(config);
+
+    assert!(!outcome.succeeded);
+    assert!(outcome.artifacts.is_empty());
+    assert!(outcome.source.is_none());
+    assert!(
+        outcome.diagnostics.iter().any(|diag| diag.level == Level::FatalError),
+        "{:?}",
+        outcome.diagnostics.iter().map(|diag| &diag.msg).collect::<Vec<_>>()
+    );
+}
+
+/// 実行ファイルは生成できないため、エラーとして報告される
+#[test]
+fn test_compile_rejects_exe() {
+    let outcome = stelaro_interface::compile(str_config("fn main(): i32 {\n    0\n}\n", vec![EmitKind::Exe]));
+
+    assert!(!outcome.succeeded);
+    assert!(outcome.artifacts.is_empty());
+    assert_eq!(outcome.diagnostics.len(), 1);
+    assert_eq!(outcome.diagnostics[0].level, Level::FatalError);
+}
+
+/// 実行時のパニックのメッセージは、セッションの言語で生成される
+#[test]
+fn test_panic_messages_follow_lang() {
+    let mut config = str_config(
+        "fn add(a: i32, b: i32): i32 {\n    a + b\n}\n",
+        vec![EmitKind::Mir, EmitKind::C, EmitKind::Bytecode, EmitKind::Asm],
+    );
+    config.opts.lang = Lang::En;
+    let outcome = stelaro_interface::compile(config);
+
+    assert!(outcome.succeeded);
+    for kind in [EmitKind::Mir, EmitKind::Bytecode] {
+        assert!(outcome.artifacts[&kind].contains("\"attempt to add with overflow\""), "{kind:?}");
+    }
+    for kind in [EmitKind::C, EmitKind::Asm] {
+        let output = &outcome.artifacts[&kind];
+        assert!(output.contains("panicked at interface_tests:2:5: attempt to add with overflow"), "{output}");
+    }
+}
+
+/// 定数の伝播によって常に失敗することが分かる演算は、デフォルトでエラーとして報告される
+#[test]
+fn test_known_panics_are_denied_by_default() {
+    let mut config = str_config(
+        "fn overflow(): i32 {\n    let x = 2147483647;\n    x + 1\n}\n\n\
+         fn div_by_zero(n: i32): i32 {\n    let zero = 0;\n    n / zero\n}\n",
+        vec![EmitKind::Mir],
+    );
+    config.opts.lang = Lang::En;
+    let outcome = stelaro_interface::compile(config);
+
+    assert!(!outcome.succeeded);
+    let messages = outcome
+        .diagnostics
+        .iter()
+        .map(|diag| (diag.level, diag.msg.concat(), diag.label[0].1.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            (
+                Level::Error,
+                "this arithmetic operation will overflow".to_string(),
+                "attempt to add with overflow".to_string(),
+            ),
+            (
+                Level::Error,
+                "this operation will panic at runtime".to_string(),
+                "attempt to divide by zero".to_string(),
+            ),
+        ]
+    );
+}
+
+/// `#[allow(...)]` を付けた関数の中では報告されず、実行時にパニックする
+#[test]
+fn test_known_panics_can_be_allowed() {
+    let outcome = stelaro_interface::compile(str_config(
+        "#[allow(arithmetic_overflow)]\nfn overflow(): i32 {\n    let f = || { let x = 2147483647; x + 1 };\n    f()\n}\n",
+        vec![EmitKind::Mir],
+    ));
+
+    assert!(outcome.succeeded, "{:?}", outcome.diagnostics.iter().map(|diag| &diag.msg).collect::<Vec<_>>());
+    assert!(outcome.diagnostics.is_empty());
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+
+use insta::assert_snapshot;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_mir::pretty::write_mir_pretty;
+use stelaro::stelaro_mir_build::build_mir;
+use stelaro::stelaro_mir_transform::run_optimization_passes;
+use stelaro::stelaro_session::{Input, Options};
+
+fn run_mir_test(path: &Path, optimize: bool) {
+    let source_code = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("テストファイルを読み込むことができませんでした {path:?}: {e}"));
+
+    let config = Config {
+        opts: Options::default(),
+        input: Input::Str {
+            name: "mir_tests".to_string(),
+            input: source_code,
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    };
+
+    let output = stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let mut bodies = build_mir(tcx, &typeck_results);
+            if optimize {
+                for body in &mut bodies {
+                    run_optimization_passes(body);
+                }
+            }
+
+            let mut output = String::new();
+            write_mir_pretty(tcx, &bodies, &mut output).unwrap();
+            output
+        })
+    });
+
+    let snapshot_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_else(|| {
+            panic!("ファイル名からスナップショット名を生成できませんでした: {path:?}")
+        });
+
+    assert_snapshot!(snapshot_name, output);
+}
+
+#[test]
+fn test_mir_inputs() {
+    insta::glob!("mir_inputs/*.stelo", |path| {
+        run_mir_test(path, false);
+    });
+}
+
+#[test]
+fn test_mir_opt_inputs() {
+    insta::glob!("mir_opt_inputs/*.stelo", |path| {
+        run_mir_test(path, true);
+    });
+}
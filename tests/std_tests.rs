@@ -0,0 +1,117 @@
+use stelaro::stelaro_interface::{self, CompileOutcome, Config};
+use stelaro::stelaro_session::config::EmitKind;
+use stelaro::stelaro_session::{Input, Options};
+
+fn compile(source_code: &str, emit: Vec<EmitKind>, no_std: bool) -> CompileOutcome {
+    let mut opts = Options {
+        stelo_name: Some("app".to_string()),
+        emit,
+        ..Options::default()
+    };
+    opts.unstable_opts.no_std = no_std;
+
+    stelaro_interface::compile(Config {
+        opts,
+        input: Input::Str {
+            name: "app".to_string(),
+            input: source_code.to_string(),
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    })
+}
+
+fn messages(outcome: &CompileOutcome) -> Vec<String> {
+    outcome.diagnostics.iter().flat_map(|diag| diag.msg.clone()).collect()
+}
+
+/// `std` の関数はプレリュードから参照でき、C では組み込み関数として定義される
+#[test]
+fn test_prelude_fn_is_intrinsic() {
+    let outcome = compile(
+        "fn main(): i32 {\n    println(\"hello\");\n    print_i32(42);\n    0\n}\n",
+        vec![EmitKind::C],
+        false,
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let c = &outcome.artifacts[&EmitKind::C];
+    assert!(c.contains("static void stelo_3std7println("), "{c}");
+    assert!(c.contains("static void stelo_3std10print__i32("), "{c}");
+    assert!(c.contains("fputc('\\n', stdout);"), "{c}");
+}
+
+/// 局所的な定義はプレリュードの名前を隠す
+#[test]
+fn test_local_fn_shadows_prelude() {
+    let outcome = compile(
+        "fn print_i32(x: i32): i32 {\n    x\n}\n\nfn main(): i32 {\n    print_i32(7)\n}\n",
+        vec![EmitKind::C],
+        false,
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let c = &outcome.artifacts[&EmitKind::C];
+    assert!(!c.contains("stelo_3std"), "{c}");
+}
+
+/// `-Z no-std` では `std` が読み込まれず、プレリュードの名前は解決できない
+#[test]
+fn test_no_std() {
+    let outcome = compile(
+        "fn main(): i32 {\n    println(\"hello\");\n    0\n}\n",
+        vec![EmitKind::Sir],
+        true,
+    );
+    assert!(!outcome.succeeded);
+    assert!(outcome.diagnostics.iter().any(|diag| diag.code == Some(302)), "{:?}", messages(&outcome));
+}
+
+/// wasm では `println` をホストからインポートし、文字列を読めるようにメモリをエクスポートする
+#[test]
+fn test_println_in_wasm() {
+    let outcome = compile(
+        "fn main(): i32 {\n    println(\"hello\");\n    0\n}\n",
+        vec![EmitKind::Wat],
+        false,
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let wat = &outcome.artifacts[&EmitKind::Wat];
+    assert!(wat.contains("(import \"std\" \"println\" (func $stelo_3std7println (param i64)))"), "{wat}");
+    assert!(wat.contains("(memory (export \"memory\") 1)"), "{wat}");
+    assert!(wat.contains("(data (i32.const 0) \"hello\")"), "{wat}");
+}
+
+/// 失敗したアサーションはパニックとして扱われる
+#[test]
+fn test_assert_is_lowered() {
+    let outcome = compile(
+        "fn main(): i32 {\n    assert(1 == 2);\n    0\n}\n",
+        vec![EmitKind::Mir],
+        false,
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let mir = &outcome.artifacts[&EmitKind::Mir];
+    assert!(mir.contains("assert("), "{mir}");
+    assert!(!mir.contains("std::assert"), "{mir}");
+}
+
+/// `exit` は `!` を返すため、任意の型の値の代わりに書ける
+#[test]
+fn test_exit_diverges() {
+    let outcome = compile(
+        "fn parse(ok: bool): i32 {\n    if ok { 1 } else { exit(2) }\n}\n\nfn main(): i32 {\n    let x: i32 = exit(parse(true));\n    x\n}\n",
+        vec![EmitKind::Mir, EmitKind::C],
+        false,
+    );
+    assert!(outcome.succeeded, "{:?}", messages(&outcome));
+
+    let mir = &outcome.artifacts[&EmitKind::Mir];
+    assert!(mir.contains("unreachable"), "{mir}");
+    let c = &outcome.artifacts[&EmitKind::C];
+    assert!(c.contains("static void stelo_3std4exit("), "{c}");
+}
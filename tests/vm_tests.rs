@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use insta::assert_snapshot;
+use stelaro::stelaro_interface::{self, Config, passes};
+use stelaro::stelaro_mir::{AssertKind, BinOp, PANIC_EXIT_CODE};
+use stelaro::stelaro_session::Input;
+use stelaro::stelaro_sir_eval::SirEvaluator;
+use stelaro::stelaro_vm::value::{Trap, Value};
+use stelaro::stelaro_vm::{Vm, compile_program, intrinsic};
+
+mod common;
+
+use common::{expected_exit_code, expected_stdout, options, read_source, snapshot_name};
+
+/// バイトコードの逆アセンブル結果と、VM と SIR の評価による `main` の実行結果と出力。
+struct Output {
+    disassembly: String,
+    vm_result: Option<Result<Value, Trap>>,
+    sir_result: Option<Result<Value, Trap>>,
+    vm_output: intrinsic::Output,
+    sir_output: intrinsic::Output,
+}
+
+fn run(source_code: String) -> Output {
+    let config = Config {
+        opts: options(&source_code),
+        input: Input::Str {
+            name: "vm_tests".to_string(),
+            input: source_code,
+        },
+        output_dir: None,
+        output_file: None,
+        file_loader: None,
+        emitter: None,
+    };
+
+    stelaro_interface::run_compiler(config, |sess| {
+        let stelo = passes::parse(sess);
+
+        passes::create_and_enter_global_ctxt(sess, |tcx| {
+            passes::lower_to_sir(tcx, stelo);
+            let typeck_results = passes::typeck(tcx);
+            let mut bodies = passes::analysis(tcx, &typeck_results);
+            passes::optimize(tcx, &mut bodies);
+
+            let program = compile_program(tcx, &bodies);
+            let mut vm = Vm::new(&program);
+            let mut sir_evaluator = SirEvaluator::new(tcx, &typeck_results);
+            Output {
+                disassembly: program.to_string(),
+                vm_result: vm.run_main(),
+                sir_result: sir_evaluator.run_main(),
+                vm_output: vm.output().clone(),
+                sir_output: sir_evaluator.output().clone(),
+            }
+        })
+    })
+}
+
+fn run_file(path: &Path) -> Output {
+    run(read_source(path))
+}
+
+#[test]
+fn test_bytecode_inputs() {
+    insta::glob!("codegen_inputs/*.stelo", |path| {
+        let output = run_file(path);
+        assert_snapshot!(format!("bytecode_{}", snapshot_name(path)), output.disassembly);
+    });
+}
+
+/// VM と SIR の評価が同じ結果と出力になり、それらが期待される終了コードと標準出力に一致することを確かめる。
+#[test]
+fn test_vm_matches_sir_eval() {
+    insta::glob!("codegen_inputs/*.stelo", |path| {
+        let output = run_file(path);
+        assert_eq!(output.vm_result, output.sir_result, "{path:?}");
+        assert_eq!(output.vm_output, output.sir_output, "{path:?}");
+
+        if let Some(stdout) = expected_stdout(path) {
+            assert_eq!(output.vm_output.stdout, stdout, "{path:?}");
+        }
+
+        if let Some(expected) = expected_exit_code::<i128>(path) {
+            let exit_code = match output.vm_result {
+                Some(Ok(Value::Int(n))) => n,
+                Some(Ok(Value::Unit)) => 0,
+                Some(Err(Trap::Panic(_))) => PANIC_EXIT_CODE as i128,
+                Some(Err(Trap::Exit(code))) => code as i128,
+                result => panic!("`main` の実行結果が終了コードではありません {path:?}: {result:?}"),
+            };
+            assert_eq!(exit_code, expected, "{path:?}");
+        }
+    });
+}
+
+#[test]
+fn test_division_by_zero_traps() {
+    let output = run(r#"
+fn div(a: u32, b: u32): u32 {
+    a / b
+}
+
+fn main(): u32 {
+    div(7, 0)
+}
+"#
+    .to_string());
+
+    let expected = Some(Err(Trap::Panic(AssertKind::DivisionByZero)));
+    assert_eq!(output.vm_result, expected);
+    assert_eq!(output.sir_result, expected);
+}
+
+#[test]
+fn test_overflow_panics() {
+    let output = run(r#"
+fn neg(a: i8): i8 {
+    -a
+}
+
+fn rem(a: i64, b: i64): i64 {
+    a % b
+}
+
+fn mul(a: u16, b: u16): u16 {
+    a * b
+}
+
+fn main(): u16 {
+    if neg(-127) == 127 and rem(-9223372036854775807, -1) == 0 {
+        mul(256, 256)
+    } else {
+        0
+    }
+}
+"#
+    .to_string());
+
+    let expected = Some(Err(Trap::Panic(AssertKind::Overflow(BinOp::Mul))));
+    assert_eq!(output.vm_result, expected);
+    assert_eq!(output.sir_result, expected);
+}
+
+#[test]
+fn test_overflow_wraps_without_checks() {
+    let output = run(r#"// overflow-checks: no
+fn add(a: i32, b: i32): i32 {
+    a + b
+}
+
+fn main(): i32 {
+    add(2147483647, 1)
+}
+"#
+    .to_string());
+
+    let expected = Some(Ok(Value::Int(i32::MIN as i128)));
+    assert_eq!(output.vm_result, expected);
+    assert_eq!(output.sir_result, expected);
+}
+
+#[test]
+fn test_unbounded_recursion_traps() {
+    let output = run(r#"
+fn forever(n: i64): i64 {
+    forever(n + 1)
+}
+
+fn main(): i64 {
+    forever(0)
+}
+"#
+    .to_string());
+
+    assert_eq!(output.vm_result, Some(Err(Trap::StackOverflow)));
+    assert_eq!(output.sir_result, Some(Err(Trap::StackOverflow)));
+}